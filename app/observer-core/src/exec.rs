@@ -0,0 +1,121 @@
+// In observer-core/src/exec.rs
+//
+// The ollama commands behind `/exec`: which ones may run, and running them
+// with their output as a stream of events. Only `ollama` itself with a known
+// subcommand is allowed, and nothing that a shell would interpret.
+
+use futures::stream::Stream;
+use std::fmt;
+use std::process::Stdio;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+};
+
+// Ollama subcommands `/exec` runs; callers can narrow this further.
+pub const SUBCOMMANDS: &[&str] = &[
+    "serve", "create", "show", "run", "stop", "pull", "push", "list", "ps", "cp", "rm", "help", "--version",
+    "--help",
+];
+const FORBIDDEN_CHARS: &str = "&;|<>()`$";
+
+#[cfg(target_os = "windows")]
+pub const PROGRAM: &str = "ollama";
+
+#[cfg(not(target_os = "windows"))]
+pub const PROGRAM: &str = "/usr/local/bin/ollama";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecRejection {
+    Empty,
+    ForbiddenCharacters,
+    NotOllama,
+    Subcommand(String),
+}
+
+impl fmt::Display for ExecRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecRejection::Empty => write!(f, "empty command"),
+            ExecRejection::ForbiddenCharacters => write!(f, "contains forbidden characters"),
+            ExecRejection::NotOllama => write!(f, "only 'ollama' is permitted"),
+            ExecRejection::Subcommand(subcommand) => write!(f, "subcommand '{}' is not permitted", subcommand),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecEvent {
+    // One line of stdout or stderr.
+    Output(String),
+    Finished(Option<i32>),
+    Failed(String),
+}
+
+// The arguments to pass to ollama, or why `cmd` may not run. `allows` gets
+// the final say on subcommands that are otherwise permitted.
+pub fn parse_command(cmd: &str, allows: impl Fn(&str) -> bool) -> Result<Vec<String>, ExecRejection> {
+    if cmd.chars().any(|c| FORBIDDEN_CHARS.contains(c)) {
+        return Err(ExecRejection::ForbiddenCharacters);
+    }
+    let parts: Vec<&str> = cmd.split_whitespace().collect();
+    match parts.split_first() {
+        None => Err(ExecRejection::Empty),
+        Some((program, _)) if *program != "ollama" => Err(ExecRejection::NotOllama),
+        Some((_, args)) => match args.first() {
+            Some(subcommand) if !SUBCOMMANDS.contains(subcommand) || !allows(subcommand) => {
+                Err(ExecRejection::Subcommand(subcommand.to_string()))
+            }
+            _ => Ok(args.iter().map(|arg| arg.to_string()).collect()),
+        },
+    }
+}
+
+// Runs `program` with `args` and `envs` added to its environment, yielding
+// its output line by line as it comes and then how it exited.
+pub fn run(program: &str, args: Vec<String>, envs: Vec<(String, String)>) -> impl Stream<Item = ExecEvent> {
+    let program = program.to_string();
+    async_stream::stream! {
+        log::info!("Executing validated command: {} with args {:?}", program, args);
+
+        let mut command = Command::new(&program);
+        command.args(&args);
+        command.envs(envs);
+        // Dropping the stream (a closed or cancelled request) ends the process.
+        command.kill_on_drop(true);
+        command.stdout(Stdio::piped());
+        command.stderr(Stdio::piped());
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                yield ExecEvent::Failed(format!("[ERROR: Failed to spawn command '{}'. Error: {}]", program, e));
+                return;
+            }
+        };
+        let stdout = child.stdout.take().expect("Failed to capture stdout");
+        let stderr = child.stderr.take().expect("Failed to capture stderr");
+
+        let mut stdout_reader = BufReader::new(stdout).lines();
+        let mut stderr_reader = BufReader::new(stderr).lines();
+
+        loop {
+            tokio::select! {
+                Ok(Some(line)) = stdout_reader.next_line() => {
+                    log::info!("RAW STDOUT BYTES AS STRING: {:?}", line);
+                    yield ExecEvent::Output(line);
+                },
+                Ok(Some(line)) = stderr_reader.next_line() => {
+                    log::info!("RAW STDERR BYTES AS STRING: {:?}", line);
+                    yield ExecEvent::Output(line);
+                },
+                else => break,
+            }
+        }
+
+        match child.wait().await {
+            Ok(status) => yield ExecEvent::Finished(status.code()),
+            Err(e) => yield ExecEvent::Failed(format!("[ERROR: Failed to wait for command. Error: {}]", e)),
+        }
+    }
+}
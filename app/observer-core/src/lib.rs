@@ -0,0 +1,14 @@
+// In observer-core/src/lib.rs
+//
+// The parts of Observer that don't need a webview: `/exec` validation and
+// the ollama process runner, the proxy's forwarding core, the mock Ollama
+// backend, the server's core routes over a `Host` and the JSON file helpers.
+// The Tauri app in src-tauri wires these into its router and commands;
+// anything else (a headless server, a CLI, the tests in tests/) can use them
+// directly.
+
+pub mod exec;
+pub mod mock;
+pub mod proxy;
+pub mod server;
+pub mod storage;
@@ -0,0 +1,271 @@
+// In observer-core/src/mock.rs
+//
+// A built-in mock Ollama for offline development and demos: canned model
+// lists for `/v1/models` and `/api/tags`, and synthetic replies streamed
+// token by token with a realistic time-to-first-token and per-token pace,
+// in the Ollama or OpenAI format the request asked for. Nothing leaves the
+// machine, so no GPU or network is needed.
+
+use axum::{
+    body::Body,
+    http::{header, Method, StatusCode},
+    response::Response,
+};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const MODELS: &[(&str, bool)] = &[("mock-vision:latest", true), ("mock-text:latest", false)];
+const FIRST_TOKEN_DELAY: Duration = Duration::from_millis(350);
+const CONTEXT_LENGTH: u64 = 8192;
+
+// Varies between 20 and 75 ms like a mid-sized model on a laptop GPU, but
+// deterministically, so demos look the same every time.
+fn token_delay(index: usize) -> Duration {
+    Duration::from_millis(20 + (index as u64 * 37) % 56)
+}
+
+fn has_images(value: &Value) -> bool {
+    value["images"].as_array().is_some_and(|images| !images.is_empty())
+}
+
+// Length of the user's last message and whether it carried an image.
+fn last_user_text(request: &Value) -> (usize, bool) {
+    if let Some(prompt) = request.get("prompt").and_then(Value::as_str) {
+        return (prompt.len(), has_images(request));
+    }
+    let Some(message) = request
+        .get("messages")
+        .and_then(Value::as_array)
+        .and_then(|messages| messages.iter().rev().find(|m| m["role"] == "user"))
+    else {
+        return (0, false);
+    };
+    match &message["content"] {
+        Value::String(text) => (text.len(), has_images(message)),
+        Value::Array(parts) => {
+            let text: usize = parts.iter().filter_map(|p| p["text"].as_str()).map(str::len).sum();
+            (text, parts.iter().any(|p| p["type"] == "image_url"))
+        }
+        _ => (0, false),
+    }
+}
+
+fn reply_tokens(model: &str, request: &Value) -> Vec<String> {
+    let (chars, image) = last_user_text(request);
+    let reply = format!(
+        "This is a synthetic reply from Observer's mock backend ({}). Your message had {} characters{}. \
+         Nothing was sent to a real model, so use this to try out agents, tools and the interface.",
+        model,
+        chars,
+        if image { " and an image" } else { "" }
+    );
+    reply.split_inclusive(' ').map(str::to_string).collect()
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn models_response(openai: bool) -> Response {
+    let created = chrono::Utc::now();
+    let body = if openai {
+        let data: Vec<Value> = MODELS
+            .iter()
+            .map(|(name, _)| {
+                json!({ "id": name, "object": "model", "created": created.timestamp(), "owned_by": "observer-mock" })
+            })
+            .collect();
+        json!({ "object": "list", "data": data })
+    } else {
+        let models: Vec<Value> = MODELS
+            .iter()
+            .map(|(name, _)| {
+                json!({
+                    "name": name,
+                    "model": name,
+                    "modified_at": created.to_rfc3339(),
+                    "size": 0,
+                    "digest": "mock",
+                    "details": { "family": "mock", "parameter_size": "0B", "quantization_level": "none" }
+                })
+            })
+            .collect();
+        json!({ "models": models })
+    };
+    json_response(StatusCode::OK, body)
+}
+
+fn show_response(request: &Value) -> Response {
+    let name = request["model"].as_str().or(request["name"].as_str()).unwrap_or("");
+    let found = MODELS.iter().find(|(model, _)| *model == name || model.trim_end_matches(":latest") == name);
+    let Some((_, vision)) = found else {
+        return json_response(StatusCode::NOT_FOUND, json!({ "error": format!("model '{}' not found", name) }));
+    };
+    let mut capabilities = vec!["completion"];
+    if *vision {
+        capabilities.push("vision");
+    }
+    json_response(
+        StatusCode::OK,
+        json!({
+            "modelfile": "",
+            "details": { "family": "mock", "parameter_size": "0B", "quantization_level": "none" },
+            "model_info": { "general.architecture": "mock", "mock.context_length": CONTEXT_LENGTH },
+            "capabilities": capabilities,
+        }),
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Flavor {
+    OllamaChat,
+    OllamaGenerate,
+    OpenAi,
+}
+
+fn chunk(flavor: Flavor, model: &str, token: &str) -> String {
+    let now = chrono::Utc::now();
+    match flavor {
+        Flavor::OllamaChat => format!(
+            "{}\n",
+            json!({
+                "model": model,
+                "created_at": now.to_rfc3339(),
+                "message": { "role": "assistant", "content": token },
+                "done": false
+            })
+        ),
+        Flavor::OllamaGenerate => format!(
+            "{}\n",
+            json!({ "model": model, "created_at": now.to_rfc3339(), "response": token, "done": false })
+        ),
+        Flavor::OpenAi => format!(
+            "data: {}\n\n",
+            json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion.chunk",
+                "created": now.timestamp(),
+                "model": model,
+                "choices": [{ "index": 0, "delta": { "role": "assistant", "content": token }, "finish_reason": null }]
+            })
+        ),
+    }
+}
+
+fn final_chunk(flavor: Flavor, model: &str, tokens: usize, elapsed: Duration) -> String {
+    let now = chrono::Utc::now();
+    let mut stats = json!({
+        "model": model,
+        "created_at": now.to_rfc3339(),
+        "done": true,
+        "done_reason": "stop",
+        "total_duration": elapsed.as_nanos() as u64,
+        "eval_count": tokens,
+    });
+    match flavor {
+        Flavor::OllamaChat => {
+            stats["message"] = json!({ "role": "assistant", "content": "" });
+            format!("{}\n", stats)
+        }
+        Flavor::OllamaGenerate => {
+            stats["response"] = json!("");
+            format!("{}\n", stats)
+        }
+        Flavor::OpenAi => format!(
+            "data: {}\n\ndata: [DONE]\n\n",
+            json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion.chunk",
+                "created": now.timestamp(),
+                "model": model,
+                "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }]
+            })
+        ),
+    }
+}
+
+fn complete_body(flavor: Flavor, model: &str, text: &str, tokens: usize, elapsed: Duration) -> Value {
+    let now = chrono::Utc::now();
+    match flavor {
+        Flavor::OllamaChat => json!({
+            "model": model,
+            "created_at": now.to_rfc3339(),
+            "message": { "role": "assistant", "content": text },
+            "done": true,
+            "done_reason": "stop",
+            "total_duration": elapsed.as_nanos() as u64,
+            "eval_count": tokens,
+        }),
+        Flavor::OllamaGenerate => json!({
+            "model": model,
+            "created_at": now.to_rfc3339(),
+            "response": text,
+            "done": true,
+            "done_reason": "stop",
+            "total_duration": elapsed.as_nanos() as u64,
+            "eval_count": tokens,
+        }),
+        Flavor::OpenAi => json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion",
+            "created": now.timestamp(),
+            "model": model,
+            "choices": [{ "index": 0, "message": { "role": "assistant", "content": text }, "finish_reason": "stop" }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": tokens, "total_tokens": tokens },
+        }),
+    }
+}
+
+async fn generation_response(flavor: Flavor, request: Value) -> Response {
+    let model = request["model"].as_str().unwrap_or(MODELS[0].0).to_string();
+    let tokens = reply_tokens(&model, &request);
+    // Ollama streams unless told otherwise; OpenAI doesn't unless asked.
+    let stream = request["stream"].as_bool().unwrap_or(flavor != Flavor::OpenAi);
+
+    if !stream {
+        let total = FIRST_TOKEN_DELAY + (0..tokens.len()).map(token_delay).sum::<Duration>();
+        tokio::time::sleep(total).await;
+        let body = complete_body(flavor, &model, &tokens.concat(), tokens.len(), total);
+        return json_response(StatusCode::OK, body);
+    }
+
+    let content_type = if flavor == Flavor::OpenAi { "text/event-stream" } else { "application/x-ndjson" };
+    let body = async_stream::stream! {
+        let started = std::time::Instant::now();
+        tokio::time::sleep(FIRST_TOKEN_DELAY).await;
+        for (index, token) in tokens.iter().enumerate() {
+            yield Ok::<_, std::convert::Infallible>(chunk(flavor, &model, token));
+            tokio::time::sleep(token_delay(index)).await;
+        }
+        yield Ok(final_chunk(flavor, &model, tokens.len(), started.elapsed()));
+    };
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from_stream(body))
+        .unwrap()
+}
+
+// Answers a proxied request the way Ollama would.
+pub async fn respond(method: &Method, path: &str, body: &[u8]) -> Response {
+    let request: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+    log::info!("Mock backend answering {} {}", method, path);
+    match (method.as_str(), path) {
+        ("GET", "/v1/models") => models_response(true),
+        ("GET", "/api/tags") => models_response(false),
+        ("GET", "/api/version") => json_response(StatusCode::OK, json!({ "version": "0.0.0-mock" })),
+        ("GET", "/api/ps") => json_response(StatusCode::OK, json!({ "models": [] })),
+        ("POST", "/api/show") => show_response(&request),
+        ("POST", "/api/chat") => generation_response(Flavor::OllamaChat, request).await,
+        ("POST", "/api/generate") => generation_response(Flavor::OllamaGenerate, request).await,
+        ("POST", "/v1/chat/completions") => generation_response(Flavor::OpenAi, request).await,
+        _ => json_response(
+            StatusCode::NOT_FOUND,
+            json!({ "error": format!("The mock backend doesn't implement {} {}", method, path) }),
+        ),
+    }
+}
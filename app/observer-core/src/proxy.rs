@@ -0,0 +1,80 @@
+// In observer-core/src/proxy.rs
+//
+// The forwarding core of the `/api` and `/v1` proxy. The Tauri app layers
+// its request processing (privacy, locality, memory, compaction...) on top
+// of `forward`; `router` serves it bare against any Ollama-compatible URL.
+//
+// Bodies are streamed both ways rather than buffered: the request body is
+// read from the client only as fast as the upstream takes it, and the answer
+// only as fast as the client reads it, so a large upload or a slow reader
+// holds a few chunks in memory instead of the whole body.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::Response,
+    routing::any,
+    Router,
+};
+use reqwest::Client;
+
+// A response carrying `upstream`'s status, version and headers, ready for a body.
+pub fn response_builder(upstream: &reqwest::Response) -> axum::http::response::Builder {
+    let mut builder = Response::builder().status(upstream.status()).version(upstream.version());
+    if let Some(headers) = builder.headers_mut() {
+        headers.extend(upstream.headers().clone());
+    }
+    builder
+}
+
+// An incoming body as an upstream one, passed on chunk by chunk.
+pub fn request_body(body: Body) -> reqwest::Body {
+    reqwest::Body::wrap_stream(body.into_data_stream())
+}
+
+// Sends the request to `url` and streams the answer back unchanged.
+pub async fn forward(
+    client: &Client,
+    method: Method,
+    url: &str,
+    headers: HeaderMap,
+    body: impl Into<reqwest::Body>,
+) -> Result<Response, StatusCode> {
+    let upstream = client.request(method, url).headers(headers).body(body).send().await.map_err(|e| {
+        log::error!("Proxy request to {} failed: {}", url, e);
+        StatusCode::BAD_GATEWAY
+    })?;
+    let builder = response_builder(&upstream);
+    Ok(builder.body(Body::from_stream(upstream.bytes_stream())).unwrap())
+}
+
+#[derive(Clone)]
+struct Target {
+    base_url: String,
+    client: Client,
+}
+
+async fn forward_handler(
+    State(target): State<Target>,
+    method: Method,
+    headers: HeaderMap,
+    uri: Uri,
+    body: Body,
+) -> Result<Response, StatusCode> {
+    let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or(uri.path());
+    let url = format!("{}{}", target.base_url, path);
+    forward(&target.client, method, &url, headers, request_body(body)).await
+}
+
+// `/api` and `/v1` passed straight through to `base_url`.
+pub fn router(base_url: impl Into<String>) -> Router {
+    let target = Target {
+        base_url: base_url.into().trim_end_matches('/').to_string(),
+        client: Client::new(),
+    };
+    Router::new()
+        .route("/v1/*path", any(forward_handler))
+        .route("/api/*path", any(forward_handler))
+        .with_state(target)
+}
@@ -0,0 +1,317 @@
+// In observer-core/src/server.rs
+//
+// The embedded server's own routes, without Tauri: `/healthz`, `/version`,
+// `/exec`, `/upload`, `/attachments/:hash` and the `/api` and `/v1` proxy.
+// Whatever needs the app (its health checks, the attachment store, the
+// proxy's request processing) comes from a `Host`. The Tauri app mounts
+// `routes()` with its own state as the host (see src-tauri/src/server.rs),
+// and the tests in tests/ with a stub one.
+//
+// A proxied request is first `admit`ted, before anything is read, and goes
+// to the mock backend if that's on. A body nothing reads is then streamed
+// upstream as it arrives (`pass_through`); otherwise it's read whole and
+// `prepare` may rewrite it or answer in the backend's place. The request is
+// sent unless `abandoned` resolves first, and `respond` turns the upstream
+// answer into the client's.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    response::{
+        sse::{Event, Sse},
+        IntoResponse, Response,
+    },
+    routing::{any, get, post},
+    Json, Router,
+};
+use futures::stream::{BoxStream, Stream, StreamExt};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::future::Future;
+
+use crate::exec::ExecEvent;
+use crate::{mock, proxy};
+
+// Prompts are read whole, since what the host does with them depends on
+// what's in them, and that's only known once the body has been parsed. The
+// price is that such a request is held in memory until it's complete; this
+// bounds that, leaving room for a prompt with several inlined images, and
+// anything bigger is refused with 413.
+pub const MAX_INSPECTED_BODY_BYTES: usize = 128 * 1024 * 1024;
+
+// A request on its way through the proxy.
+#[derive(Debug, Clone)]
+pub struct ProxyRequest {
+    pub method: Method,
+    pub path: String,
+    pub query: String,
+    pub headers: HeaderMap,
+    // The backend it's sent to.
+    pub base_url: String,
+}
+
+impl ProxyRequest {
+    pub fn target_url(&self) -> String {
+        format!("{}{}?{}", self.base_url, self.path, self.query)
+    }
+
+    // Model blob uploads and anything that isn't a POST carry no prompt, so
+    // they're only passed on.
+    pub fn inspects_body(&self) -> bool {
+        self.method == Method::POST && !self.path.starts_with("/api/blobs/")
+    }
+}
+
+pub trait Host: Clone + Send + Sync + 'static {
+    // What a proxied request carries from `prepare` to `respond`.
+    type Flight: Send + 'static;
+    type Attachment: Serialize + Send + 'static;
+
+    fn http_client(&self) -> &reqwest::Client;
+
+    // Whether everything is up, and the report; `verbose` asks for the full one.
+    fn health(&self, verbose: bool) -> impl Future<Output = (bool, Value)> + Send;
+
+    fn version(&self) -> Value;
+
+    // What running `cmd` says, as SSE event names and data (see `exec_message`).
+    fn exec(&self, cmd: String) -> BoxStream<'static, (Option<&'static str>, String)>;
+
+    fn max_attachment_bytes(&self) -> u64;
+
+    // Called off the async runtime, since it writes the file.
+    fn store_attachment(
+        &self,
+        bytes: Vec<u8>,
+        mime: String,
+        conversation_id: Option<i64>,
+    ) -> Result<Self::Attachment, String>;
+
+    // A stored file and its type.
+    fn attachment(&self, hash: String) -> impl Future<Output = Result<(Vec<u8>, String), StatusCode>> + Send;
+
+    // The backend a proxied request goes to.
+    fn backend(&self, headers: &HeaderMap) -> String;
+
+    fn mock_enabled(&self) -> bool;
+
+    fn max_inspected_body_bytes(&self) -> usize {
+        MAX_INSPECTED_BODY_BYTES
+    }
+
+    // Refusals that don't need the body.
+    fn admit(&self, request: &ProxyRequest) -> Result<(), Response>;
+
+    // A request whose body is streamed.
+    fn pass_through(&self, request: &ProxyRequest) -> Self::Flight;
+
+    // A request whose body was read: the body to send, or the answer.
+    fn prepare(
+        &self,
+        request: &mut ProxyRequest,
+        body: Bytes,
+    ) -> impl Future<Output = Result<(Bytes, Self::Flight), Response>> + Send;
+
+    // Resolves, with the client's answer, when a request should be given up
+    // before the backend answers.
+    fn abandoned(&self, request: &ProxyRequest, flight: &Self::Flight) -> impl Future<Output = Response> + Send;
+
+    fn respond(
+        &self,
+        request: ProxyRequest,
+        flight: Self::Flight,
+        upstream: reqwest::Response,
+    ) -> impl Future<Output = Response> + Send;
+}
+
+// An `/exec` event as SSE: output lines are unnamed, the end is "done" and
+// failures are "error".
+pub fn exec_message(event: ExecEvent) -> (Option<&'static str>, String) {
+    match event {
+        ExecEvent::Output(line) => (None, line),
+        ExecEvent::Finished(code) => (Some("done"), format!("[COMMAND_FINISHED code={:?}]", code)),
+        ExecEvent::Failed(message) => (Some("error"), message),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct HealthParams {
+    verbose: Option<String>,
+}
+
+async fn healthz_handler<H: Host>(State(host): State<H>, Query(params): Query<HealthParams>) -> Response {
+    let verbose = matches!(params.verbose.as_deref(), Some("1" | "true"));
+    let (healthy, report) = host.health(verbose).await;
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report)).into_response()
+}
+
+async fn version_handler<H: Host>(State(host): State<H>) -> Json<Value> {
+    Json(host.version())
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecParams {
+    cmd: String,
+}
+
+async fn exec_handler<H: Host>(
+    State(host): State<H>,
+    Query(params): Query<ExecParams>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    log::info!("Received command to execute: '{}'", params.cmd);
+    let events = host.exec(params.cmd).map(|(name, data)| {
+        let event = Event::default().data(data);
+        Ok(match name {
+            Some(name) => event.event(name),
+            None => event,
+        })
+    });
+    Sse::new(events)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct UploadParams {
+    conversation_id: Option<i64>,
+}
+
+// Each file part of the form is stored; the rest is ignored.
+async fn upload_handler<H: Host>(
+    State(host): State<H>,
+    Query(params): Query<UploadParams>,
+    mut multipart: Multipart,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let max_file_bytes = host.max_attachment_bytes();
+    let mut attachments = Vec::new();
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))? {
+        if field.file_name().is_none() {
+            continue;
+        }
+        let mime = field.content_type().unwrap_or("application/octet-stream").to_string();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))? {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() as u64 > max_file_bytes {
+                let message = format!("Attachment is over the {} byte limit", max_file_bytes);
+                return Err((StatusCode::PAYLOAD_TOO_LARGE, message));
+            }
+        }
+        let (host, conversation_id) = (host.clone(), params.conversation_id);
+        let attachment = tokio::task::spawn_blocking(move || host.store_attachment(bytes, mime, conversation_id))
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .map_err(|e| (StatusCode::INSUFFICIENT_STORAGE, e))?;
+        attachments.push(attachment);
+    }
+    if attachments.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "No files in the upload".to_string()));
+    }
+    Ok(Json(json!({ "attachments": attachments })))
+}
+
+async fn attachment_handler<H: Host>(State(host): State<H>, Path(hash): Path<String>) -> Result<Response, StatusCode> {
+    let (bytes, mime) = host.attachment(hash).await?;
+
+    // The type is whatever the uploader claimed, and this is the app's own
+    // origin: only media is shown inline, anything else is a download.
+    let media = ["image/", "audio/", "video/"].iter().any(|prefix| mime.starts_with(prefix));
+    // SVG is an image that can carry script.
+    let inline = media && !mime.starts_with("image/svg");
+    let disposition = if inline { "inline" } else { "attachment" };
+    Response::builder()
+        .header(header::CONTENT_TYPE, mime)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CONTENT_DISPOSITION, disposition)
+        // Content-addressed, so it never changes.
+        .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
+        .body(Body::from(bytes))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn proxy_handler<H: Host>(
+    State(host): State<H>,
+    method: Method,
+    headers: HeaderMap,
+    uri: Uri,
+    body: Body,
+) -> Response {
+    proxy(&host, method, headers, uri, body).await
+}
+
+// Runs a request through the proxy as `proxy_handler` does, for hosts that
+// send requests of their own the way a client's would go.
+pub async fn proxy<H: Host>(host: &H, method: Method, headers: HeaderMap, uri: Uri, body: Body) -> Response {
+    let mut request = ProxyRequest {
+        base_url: host.backend(&headers),
+        method,
+        path: uri.path().to_string(),
+        query: uri.query().unwrap_or("").to_string(),
+        headers,
+    };
+    log::info!("Proxying {} request to: {}", request.method, request.target_url());
+
+    if let Err(response) = host.admit(&request) {
+        return response;
+    }
+    // The mock backend never leaves the machine, so nothing below applies.
+    if host.mock_enabled() {
+        let body = body.collect().await.map(|c| c.to_bytes()).unwrap_or_default();
+        return mock::respond(&request.method, &request.path, &body).await;
+    }
+
+    let (body, flight) = if request.inspects_body() {
+        let limit = host.max_inspected_body_bytes();
+        let body = match http_body_util::Limited::new(body, limit).collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(e) if e.is::<http_body_util::LengthLimitError>() => {
+                log::warn!("Refusing to proxy {}: the body is over {} bytes", request.path, limit);
+                return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+            }
+            Err(e) => {
+                log::error!("Failed to collect request body: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        match host.prepare(&mut request, body).await {
+            Ok((body, flight)) => (reqwest::Body::from(body), flight),
+            Err(response) => return response,
+        }
+    } else {
+        let flight = host.pass_through(&request);
+        (proxy::request_body(body), flight)
+    };
+
+    let send = host
+        .http_client()
+        .request(request.method.clone(), request.target_url())
+        .headers(request.headers.clone())
+        .body(body)
+        .send();
+    let sent = tokio::select! {
+        result = send => result,
+        response = host.abandoned(&request, &flight) => return response,
+    };
+    match sent {
+        Ok(upstream) => host.respond(request, flight, upstream).await,
+        Err(e) => {
+            log::error!("Proxy request to {} failed: {}", request.base_url, e);
+            StatusCode::BAD_GATEWAY.into_response()
+        }
+    }
+}
+
+// The routes, for the host to give its state with `Router::with_state`.
+pub fn routes<H: Host>() -> Router<H> {
+    Router::new()
+        .route("/exec", get(exec_handler::<H>))
+        .route("/healthz", get(healthz_handler::<H>))
+        .route("/version", get(version_handler::<H>))
+        .route("/v1/*path", any(proxy_handler::<H>))
+        .route("/api/*path", any(proxy_handler::<H>))
+        .route("/attachments/:hash", get(attachment_handler::<H>))
+        // Uploads are capped per file by the host instead.
+        .route("/upload", post(upload_handler::<H>).layer(DefaultBodyLimit::disable()))
+}
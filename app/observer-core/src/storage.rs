@@ -0,0 +1,34 @@
+// In observer-core/src/storage.rs
+//
+// Reading and writing the JSON files Observer keeps its settings in. Where
+// the files live is up to the caller (the Tauri app resolves profiles and
+// portable mode before calling in).
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+// Missing or unreadable files fall back to the default so a bad file never
+// keeps the app from starting.
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    match std::fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            log::error!("Failed to parse {:?}, using defaults: {}", path, e);
+            T::default()
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+        Err(e) => {
+            log::error!("Failed to read {:?}, using defaults: {}", path, e);
+            T::default()
+        }
+    }
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+
+    // Write to a temp file first so a crash mid-write can't leave half a file behind.
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, bytes).map_err(|e| format!("Failed to write {:?}: {}", tmp_path, e))?;
+    std::fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace {:?}: {}", path, e))?;
+    Ok(())
+}
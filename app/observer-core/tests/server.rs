@@ -0,0 +1,550 @@
+// In observer-core/tests/server.rs
+//
+// In-process tests for the server logic: `/exec` validation, the proxy's
+// forwarding core against a stub Ollama on a random port, the mock backend,
+// and the core routes the app serves (server.rs) over a stub `Host`.
+// Nothing here needs a webview or a real model.
+
+use observer_core::exec::{self, ExecEvent, ExecRejection};
+use observer_core::server::{self, Host, ProxyRequest};
+use observer_core::{mock, proxy};
+use axum::{
+    body::{Body, Bytes},
+    extract::Query,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+// Serves `router` on 127.0.0.1 and returns its base URL.
+async fn spawn(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+// Just enough of Ollama's API for the proxy to talk to.
+fn stub_ollama() -> Router {
+    Router::new()
+        .route(
+            "/api/tags",
+            get(|| async { Json(json!({ "models": [{ "name": "stub:latest" }] })) }),
+        )
+        .route(
+            "/api/echo",
+            get(|Query(query): Query<HashMap<String, String>>| async move { Json(json!(query)) }),
+        )
+        .route("/api/generate", post(|Json(request): Json<Value>| async move { Json(request) }))
+        .route(
+            "/api/chat",
+            post(|Json(request): Json<Value>| async move {
+                let model = request["model"].as_str().unwrap_or_default().to_string();
+                let chunk = |token: &str| json!({ "model": model, "message": { "content": token }, "done": false });
+                let lines: String = [chunk("Hel"), chunk("lo"), json!({ "model": model, "done": true })]
+                    .iter()
+                    .map(|line| format!("{}\n", line))
+                    .collect();
+                Response::builder()
+                    .header(header::CONTENT_TYPE, "application/x-ndjson")
+                    .body(Body::from(lines))
+                    .unwrap()
+            }),
+        )
+}
+
+async fn proxy() -> String {
+    let ollama = spawn(stub_ollama()).await;
+    spawn(proxy::router(ollama)).await
+}
+
+async fn body_json(response: Response) -> Value {
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+fn parse(cmd: &str) -> Result<Vec<String>, ExecRejection> {
+    exec::parse_command(cmd, |_| true)
+}
+
+#[test]
+fn exec_accepts_allowed_ollama_subcommands() {
+    assert_eq!(parse("ollama list"), Ok(vec!["list".to_string()]));
+    assert_eq!(
+        parse("ollama  pull   gemma3:4b"),
+        Ok(vec!["pull".to_string(), "gemma3:4b".to_string()])
+    );
+    assert_eq!(parse("ollama"), Ok(vec![]));
+}
+
+#[test]
+fn exec_rejects_everything_else() {
+    assert_eq!(parse("   "), Err(ExecRejection::Empty));
+    assert_eq!(parse("ls -la"), Err(ExecRejection::NotOllama));
+    assert_eq!(
+        parse("ollama list; rm -rf ~"),
+        Err(ExecRejection::ForbiddenCharacters)
+    );
+    assert_eq!(
+        parse("ollama $(whoami)"),
+        Err(ExecRejection::ForbiddenCharacters)
+    );
+    assert_eq!(
+        parse("ollama launch"),
+        Err(ExecRejection::Subcommand("launch".to_string()))
+    );
+}
+
+#[test]
+fn exec_defers_to_the_caller_on_subcommands() {
+    assert_eq!(
+        exec::parse_command("ollama rm gemma3:4b", |subcommand| subcommand != "rm"),
+        Err(ExecRejection::Subcommand("rm".to_string()))
+    );
+    assert!(exec::parse_command("ollama list", |subcommand| subcommand != "rm").is_ok());
+}
+
+#[tokio::test]
+async fn exec_reports_programs_that_fail_to_start() {
+    let events: Vec<ExecEvent> = exec::run("/nonexistent/ollama", vec!["list".to_string()], Vec::new()).collect().await;
+    assert!(matches!(events.as_slice(), [ExecEvent::Failed(_)]));
+}
+
+#[tokio::test]
+async fn proxy_forwards_json() {
+    let proxy = proxy().await;
+    let response = reqwest::get(format!("{}/api/tags", proxy)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["models"][0]["name"], "stub:latest");
+}
+
+#[tokio::test]
+async fn proxy_keeps_the_query_string() {
+    let proxy = proxy().await;
+    let body: Value = reqwest::get(format!("{}/api/echo?model=a&verbose=true", proxy))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body, json!({ "model": "a", "verbose": "true" }));
+}
+
+#[tokio::test]
+async fn proxy_streams_chat_responses() {
+    let proxy = proxy().await;
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/chat", proxy))
+        .json(&json!({ "model": "stub", "messages": [{ "role": "user", "content": "hi" }] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+
+    let text = response.text().await.unwrap();
+    let lines: Vec<Value> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let content: String = lines.iter().filter_map(|l| l["message"]["content"].as_str()).collect();
+    assert_eq!(content, "Hello");
+    assert_eq!(lines.last().unwrap()["done"], true);
+    assert!(lines.iter().all(|l| l["model"] == "stub"));
+}
+
+#[tokio::test]
+async fn proxy_streams_request_bodies() {
+    assert_streams_uploads(proxy::router).await;
+}
+
+// Uploads a blob through the proxy `proxy_for` builds for an upstream URL.
+async fn assert_streams_uploads(proxy_for: impl FnOnce(String) -> Router) {
+    // An upstream that says when the first chunk of an upload reaches it.
+    let first_chunk = Arc::new(Notify::new());
+    let seen = first_chunk.clone();
+    let upstream = Router::new().route(
+        "/api/blobs/:digest",
+        post(|body: Body| async move {
+            let mut chunks = body.into_data_stream();
+            let mut received = 0;
+            while let Some(chunk) = chunks.next().await {
+                received += chunk.unwrap().len();
+                seen.notify_one();
+            }
+            Json(json!({ "received": received }))
+        }),
+    );
+    let proxy = spawn(proxy_for(spawn(upstream).await)).await;
+
+    // The rest of the body is only sent once the upstream has the first
+    // chunk, which a buffering proxy would wait for forever.
+    let chunks = async_stream::stream! {
+        yield Ok::<_, std::io::Error>(vec![7u8; 256 * 1024]);
+        first_chunk.notified().await;
+        for _ in 1..16 {
+            yield Ok(vec![7u8; 256 * 1024]);
+        }
+    };
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/blobs/sha256:stub", proxy))
+        .body(reqwest::Body::wrap_stream(chunks))
+        .send();
+    let response = tokio::time::timeout(Duration::from_secs(10), request)
+        .await
+        .expect("the proxy buffered the body instead of streaming it")
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["received"], 16 * 256 * 1024);
+}
+
+#[tokio::test]
+async fn proxy_passes_upstream_errors_through() {
+    let proxy = proxy().await;
+    let response = reqwest::get(format!("{}/api/missing", proxy)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn proxy_reports_unreachable_backends() {
+    // Nothing listens on the port a dropped listener had.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let proxy = spawn(proxy::router(dead)).await;
+    let response = reqwest::get(format!("{}/api/tags", proxy)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+}
+
+#[tokio::test]
+async fn mock_lists_models_in_both_formats() {
+    let openai = body_json(mock::respond(&Method::GET, "/v1/models", b"").await).await;
+    assert_eq!(openai["object"], "list");
+    assert!(!openai["data"].as_array().unwrap().is_empty());
+
+    let ollama = body_json(mock::respond(&Method::GET, "/api/tags", b"").await).await;
+    assert!(!ollama["models"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn mock_streams_ollama_chat() {
+    let request = json!({ "model": "mock-text:latest", "messages": [{ "role": "user", "content": "hello" }] });
+    let response = mock::respond(&Method::POST, "/api/chat", request.to_string().as_bytes()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let lines: Vec<Value> = String::from_utf8_lossy(&bytes)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(lines.len() > 2);
+    assert_eq!(lines.last().unwrap()["done"], true);
+    assert!(lines[..lines.len() - 1].iter().all(|l| l["done"] == false));
+}
+
+#[tokio::test]
+async fn mock_answers_openai_without_streaming() {
+    let request = json!({ "model": "mock-text:latest", "messages": [{ "role": "user", "content": "hello" }] });
+    let response = mock::respond(&Method::POST, "/v1/chat/completions", request.to_string().as_bytes()).await;
+    let body = body_json(response).await;
+    assert_eq!(body["object"], "chat.completion");
+    assert!(body["choices"][0]["message"]["content"].as_str().unwrap().contains("mock"));
+}
+
+#[tokio::test]
+async fn mock_rejects_unknown_endpoints() {
+    let response = mock::respond(&Method::POST, "/api/embed", b"{}").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// A host for the core routes: attachments are kept in memory, `x-refuse`
+// requests aren't admitted, and prompts are marked `"prepared": true`.
+#[derive(Clone)]
+struct TestHost {
+    backend: String,
+    client: reqwest::Client,
+    healthy: bool,
+    mock: bool,
+    attachments: Arc<Mutex<HashMap<String, (Vec<u8>, String)>>>,
+}
+
+impl TestHost {
+    fn new(backend: String) -> Self {
+        TestHost {
+            backend,
+            client: reqwest::Client::new(),
+            healthy: true,
+            mock: false,
+            attachments: Arc::default(),
+        }
+    }
+}
+
+impl Host for TestHost {
+    // Whether the request went through `prepare`.
+    type Flight = bool;
+    type Attachment = Value;
+
+    fn http_client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    async fn health(&self, verbose: bool) -> (bool, Value) {
+        (self.healthy, json!({ "verbose": verbose }))
+    }
+
+    fn version(&self) -> Value {
+        json!({ "app_version": "test" })
+    }
+
+    fn exec(&self, cmd: String) -> BoxStream<'static, (Option<&'static str>, String)> {
+        match exec::parse_command(&cmd, |_| true) {
+            Ok(args) => exec::run("/nonexistent/ollama", args, Vec::new()).map(server::exec_message).boxed(),
+            Err(rejection) => futures::stream::iter([(Some("error"), rejection.to_string())]).boxed(),
+        }
+    }
+
+    fn max_attachment_bytes(&self) -> u64 {
+        1024
+    }
+
+    fn store_attachment(&self, bytes: Vec<u8>, mime: String, _: Option<i64>) -> Result<Value, String> {
+        let mut attachments = self.attachments.lock().unwrap();
+        let hash = format!("file{}", attachments.len());
+        let url = format!("/attachments/{}", hash);
+        let attachment = json!({ "hash": hash, "mime": mime, "size": bytes.len(), "url": url });
+        attachments.insert(hash, (bytes, mime));
+        Ok(attachment)
+    }
+
+    async fn attachment(&self, hash: String) -> Result<(Vec<u8>, String), StatusCode> {
+        self.attachments.lock().unwrap().get(&hash).cloned().ok_or(StatusCode::NOT_FOUND)
+    }
+
+    fn backend(&self, _: &HeaderMap) -> String {
+        self.backend.clone()
+    }
+
+    fn mock_enabled(&self) -> bool {
+        self.mock
+    }
+
+    fn max_inspected_body_bytes(&self) -> usize {
+        64 * 1024
+    }
+
+    fn admit(&self, request: &ProxyRequest) -> Result<(), Response> {
+        match request.headers.contains_key("x-refuse") {
+            true => Err(StatusCode::FORBIDDEN.into_response()),
+            false => Ok(()),
+        }
+    }
+
+    fn pass_through(&self, _: &ProxyRequest) -> bool {
+        false
+    }
+
+    async fn prepare(&self, request: &mut ProxyRequest, body: Bytes) -> Result<(Bytes, bool), Response> {
+        let mut json: Value = serde_json::from_slice(&body).map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+        json["prepared"] = json!(true);
+        request.headers.remove(header::CONTENT_LENGTH);
+        Ok((Bytes::from(json.to_string()), true))
+    }
+
+    async fn abandoned(&self, _: &ProxyRequest, _: &bool) -> Response {
+        std::future::pending().await
+    }
+
+    async fn respond(&self, _: ProxyRequest, prepared: bool, upstream: reqwest::Response) -> Response {
+        proxy::response_builder(&upstream)
+            .header("x-prepared", HeaderValue::from_static(if prepared { "true" } else { "false" }))
+            .body(Body::from_stream(upstream.bytes_stream()))
+            .unwrap()
+    }
+}
+
+async fn core_server(host: TestHost) -> String {
+    spawn(server::routes().with_state(host)).await
+}
+
+// The core routes in front of a stub Ollama.
+async fn core_proxy() -> String {
+    core_server(TestHost::new(spawn(stub_ollama()).await)).await
+}
+
+// A multipart form of (file name, type, contents) files and one text field.
+fn multipart(files: &[(&str, &str, &[u8])]) -> (String, Vec<u8>) {
+    let boundary = "observer-test-boundary";
+    let field = "Content-Disposition: form-data; name=\"note\"\r\n\r\nhi";
+    let mut body = format!("--{}\r\n{}\r\n", boundary, field).into_bytes();
+    for (name, mime, contents) in files {
+        body.extend(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                boundary, name, mime
+            )
+            .into_bytes(),
+        );
+        body.extend_from_slice(contents);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend(format!("--{}--\r\n", boundary).into_bytes());
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+async fn upload(server: &str, files: &[(&str, &str, &[u8])]) -> reqwest::Response {
+    let (content_type, body) = multipart(files);
+    reqwest::Client::new()
+        .post(format!("{}/upload?conversation_id=1", server))
+        .header(header::CONTENT_TYPE, content_type)
+        .body(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn routes_report_health() {
+    let server = core_proxy().await;
+    let response = reqwest::get(format!("{}/healthz?verbose=1", server)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json::<Value>().await.unwrap()["verbose"], true);
+
+    let unhealthy = TestHost { healthy: false, ..TestHost::new(String::new()) };
+    let response = reqwest::get(format!("{}/healthz", core_server(unhealthy).await)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.json::<Value>().await.unwrap()["verbose"], false);
+}
+
+#[tokio::test]
+async fn routes_report_the_version() {
+    let body: Value = reqwest::get(format!("{}/version", core_proxy().await)).await.unwrap().json().await.unwrap();
+    assert_eq!(body["app_version"], "test");
+}
+
+#[tokio::test]
+async fn routes_stream_exec_failures_as_error_events() {
+    let server = core_proxy().await;
+    for cmd in ["ls", "ollama%20list"] {
+        let response = reqwest::get(format!("{}/exec?cmd={}", server, cmd)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+        let text = response.text().await.unwrap();
+        assert!(text.contains("event: error\n"), "{}", text);
+    }
+}
+
+#[tokio::test]
+async fn routes_store_and_serve_attachments() {
+    let server = core_proxy().await;
+    let files = [("a.png", "image/png", &b"png"[..]), ("a.html", "text/html", &b"<script>"[..])];
+    let response = upload(&server, &files).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json().await.unwrap();
+    let attachments = body["attachments"].as_array().unwrap();
+    assert_eq!(attachments.len(), 2);
+
+    // Media is shown inline, anything else is a download, and neither is sniffed.
+    for (attachment, disposition) in attachments.iter().zip(["inline", "attachment"]) {
+        let url = format!("{}{}", server, attachment["url"].as_str().unwrap());
+        let response = reqwest::get(url).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], attachment["mime"].as_str().unwrap());
+        assert_eq!(response.headers()[header::CONTENT_DISPOSITION], disposition);
+        assert_eq!(response.headers()[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    }
+
+    let response = reqwest::get(format!("{}/attachments/missing", server)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn routes_refuse_bad_uploads() {
+    let server = core_proxy().await;
+    let response = upload(&server, &[("big.bin", "application/octet-stream", &[0u8; 2048][..])]).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let response = upload(&server, &[]).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn routes_send_prepared_prompts() {
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/generate", core_proxy().await))
+        .json(&json!({ "model": "stub", "prompt": "hi" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-prepared"], "true");
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body, json!({ "model": "stub", "prompt": "hi", "prepared": true }));
+}
+
+#[tokio::test]
+async fn routes_refuse_prompts_over_the_limit() {
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/generate", core_proxy().await))
+        .json(&json!({ "model": "stub", "prompt": "x".repeat(64 * 1024) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn routes_refuse_what_the_host_does_not_admit() {
+    let response = reqwest::Client::new()
+        .get(format!("{}/api/tags", core_proxy().await))
+        .header("x-refuse", "1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn routes_pass_other_requests_through() {
+    let response = reqwest::get(format!("{}/api/tags", core_proxy().await)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-prepared"], "false");
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["models"][0]["name"], "stub:latest");
+}
+
+#[tokio::test]
+async fn routes_stream_request_bodies() {
+    assert_streams_uploads(|upstream| server::routes().with_state(TestHost::new(upstream))).await;
+}
+
+#[tokio::test]
+async fn routes_answer_from_the_mock_backend() {
+    // The backend isn't reachable; the mock never asks it.
+    let host = TestHost { mock: true, ..TestHost::new("http://127.0.0.1:9".to_string()) };
+    let response = reqwest::get(format!("{}/api/tags", core_server(host).await)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json().await.unwrap();
+    assert!(!body["models"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn routes_report_unreachable_backends() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let server = core_server(TestHost::new(dead)).await;
+    let response = reqwest::get(format!("{}/api/tags", server)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+}
@@ -0,0 +1,196 @@
+// In observerctl/src/main.rs
+//
+// Command-line client for the running Observer app, over its HTTP server
+// (see src-tauri/src/control.rs). Handy for scripts and for anyone who'd
+// rather stay in the terminal.
+//
+//   observerctl agents list
+//   observerctl run-agent <id>
+//   observerctl stop-agent <id>
+//   observerctl models list
+//   observerctl models pull <model>
+//   observerctl logs [-f] [-n <lines>]
+//
+// The server is http://127.0.0.1:3838 unless `--url` or OBSERVER_URL says
+// otherwise.
+
+use futures::StreamExt;
+use reqwest::{Client, Response};
+use serde_json::Value;
+use std::process::ExitCode;
+use tokio::io::AsyncWriteExt;
+
+const DEFAULT_URL: &str = "http://127.0.0.1:3838";
+const USAGE: &str = "Usage: observerctl [--url <url>] <command>
+
+Commands:
+  agents list              List the app's agents
+  run-agent <id>           Start an agent
+  stop-agent <id>          Stop an agent
+  models list              List the models the backend has
+  models pull <model>      Download a model, showing progress
+  logs [-f] [-n <lines>]   Print the app log; -f keeps following it";
+
+enum Command {
+    ListAgents,
+    RunAgent(String),
+    StopAgent(String),
+    ListModels,
+    PullModel(String),
+    Logs { follow: bool, lines: usize },
+}
+
+fn parse(mut args: Vec<String>) -> Result<(String, Command), String> {
+    let mut url = std::env::var("OBSERVER_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    if let Some(i) = args.iter().position(|arg| arg == "--url") {
+        if i + 1 >= args.len() {
+            return Err("--url needs a value".to_string());
+        }
+        url = args.remove(i + 1);
+        args.remove(i);
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let command = match args.as_slice() {
+        ["agents", "list"] => Command::ListAgents,
+        ["run-agent", id] => Command::RunAgent(id.to_string()),
+        ["stop-agent", id] => Command::StopAgent(id.to_string()),
+        ["models", "list"] => Command::ListModels,
+        ["models", "pull", model] => Command::PullModel(model.to_string()),
+        ["logs", options @ ..] => {
+            let mut follow = false;
+            let mut lines = 100;
+            let mut options = options.iter();
+            while let Some(option) = options.next() {
+                match *option {
+                    "-f" | "--follow" => follow = true,
+                    "-n" | "--lines" => {
+                        lines = options
+                            .next()
+                            .and_then(|n| n.parse().ok())
+                            .ok_or_else(|| format!("{} needs a number", option))?;
+                    }
+                    other => return Err(format!("Unknown option '{}'", other)),
+                }
+            }
+            Command::Logs { follow, lines }
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+    Ok((url.trim_end_matches('/').to_string(), command))
+}
+
+// The response if it succeeded, otherwise the server's error message.
+async fn checked(response: Result<Response, reqwest::Error>, url: &str) -> Result<Response, String> {
+    let response = response.map_err(|e| format!("Couldn't reach Observer at {}: {}", url, e))?;
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    let message = body["error"].as_str().or(body["error"]["message"].as_str()).unwrap_or("");
+    Err(format!("Observer answered {}: {}", status, message))
+}
+
+async fn list_agents(client: &Client, url: &str) -> Result<(), String> {
+    let response = checked(client.get(format!("{}/observer/agents", url)).send().await, url).await?;
+    let agents: Vec<Value> = response.json().await.map_err(|e| e.to_string())?;
+    if agents.is_empty() {
+        println!("No agents");
+    }
+    for agent in agents {
+        println!(
+            "{:<24} {:<28} {}",
+            agent["id"].as_str().unwrap_or(""),
+            agent["name"].as_str().unwrap_or(""),
+            agent["model_name"].as_str().unwrap_or("")
+        );
+    }
+    Ok(())
+}
+
+async fn agent_action(client: &Client, url: &str, id: &str, action: &str) -> Result<(), String> {
+    let endpoint = format!("{}/observer/agents/{}/{}", url, id, action);
+    checked(client.post(endpoint).send().await, url).await?;
+    println!("Asked Observer to {} '{}'", action, id);
+    Ok(())
+}
+
+async fn list_models(client: &Client, url: &str) -> Result<(), String> {
+    let response = checked(client.get(format!("{}/api/tags", url)).send().await, url).await?;
+    let body: Value = response.json().await.map_err(|e| e.to_string())?;
+    for model in body["models"].as_array().into_iter().flatten() {
+        let size_gb = model["size"].as_u64().unwrap_or(0) as f64 / 1e9;
+        println!("{:<40} {:>6.1} GB", model["name"].as_str().unwrap_or(""), size_gb);
+    }
+    Ok(())
+}
+
+// Ollama streams one JSON status per line; show the latest on one line.
+async fn pull_model(client: &Client, url: &str, model: &str) -> Result<(), String> {
+    let request = client.post(format!("{}/api/pull", url)).json(&serde_json::json!({ "model": model }));
+    let response = checked(request.send().await, url).await?;
+    let mut stream = response.bytes_stream();
+    let mut buffer = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+        while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let Ok(status) = serde_json::from_slice::<Value>(&line) else {
+                continue;
+            };
+            if let Some(error) = status["error"].as_str() {
+                eprintln!();
+                return Err(error.to_string());
+            }
+            let text = status["status"].as_str().unwrap_or("");
+            match (status["completed"].as_u64(), status["total"].as_u64()) {
+                (Some(completed), Some(total)) if total > 0 => {
+                    eprint!("\r\x1b[K{} {:.0}%", text, completed as f64 * 100.0 / total as f64)
+                }
+                _ => eprint!("\r\x1b[K{}", text),
+            }
+        }
+    }
+    eprintln!();
+    Ok(())
+}
+
+async fn logs(client: &Client, url: &str, follow: bool, lines: usize) -> Result<(), String> {
+    let endpoint = format!("{}/observer/logs?lines={}&follow={}", url, lines, follow);
+    let response = checked(client.get(endpoint).send().await, url).await?;
+    let mut stream = response.bytes_stream();
+    let mut stdout = tokio::io::stdout();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| e.to_string())?;
+        stdout.write_all(&chunk).await.map_err(|e| e.to_string())?;
+        stdout.flush().await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let (url, command) = match parse(std::env::args().skip(1).collect()) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+    let client = Client::new();
+    let result = match command {
+        Command::ListAgents => list_agents(&client, &url).await,
+        Command::RunAgent(id) => agent_action(&client, &url, &id, "run").await,
+        Command::StopAgent(id) => agent_action(&client, &url, &id, "stop").await,
+        Command::ListModels => list_models(&client, &url).await,
+        Command::PullModel(model) => pull_model(&client, &url, &model).await,
+        Command::Logs { follow, lines } => logs(&client, &url, follow, lines).await,
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("observerctl: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
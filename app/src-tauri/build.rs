@@ -1,3 +1,24 @@
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() {
+  // Reported by /version. Release tarballs have no .git, hence "unknown".
+  let commit = Command::new("git")
+    .args(["rev-parse", "--short", "HEAD"])
+    .output()
+    .ok()
+    .filter(|output| output.status.success())
+    .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    .unwrap_or_else(|| "unknown".to_string());
+  // SOURCE_DATE_EPOCH keeps reproducible builds reproducible.
+  let built_at = std::env::var("SOURCE_DATE_EPOCH")
+    .ok()
+    .and_then(|epoch| epoch.parse::<u64>().ok())
+    .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+  println!("cargo:rustc-env=OBSERVER_GIT_COMMIT={}", commit);
+  println!("cargo:rustc-env=OBSERVER_BUILD_TIMESTAMP={}", built_at);
+  println!("cargo:rerun-if-changed=../../.git/HEAD");
+  println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
   tauri_build::build()
 }
@@ -0,0 +1,235 @@
+// In src-tauri/src/access_log.rs
+//
+// Optional access log for the embedded server: one line per request with
+// method, path, status, duration, response size, client address and request
+// ID (see request_id.rs), so
+// users can audit what talked to their local server. Off by default and
+// switchable at runtime.
+//
+// Lines are written in Common Log Format with the duration in milliseconds
+// and the request ID appended, or as JSON objects, to `access_logs/access.log` in the data
+// directory. The file rotates at `max_file_mb`, keeping `max_files` old
+// files (access.log.1 is the newest).
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State as AxumState},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{request_id, storage, AppState};
+
+const SETTINGS_FILE: &str = "access_log.json";
+const LOG_DIR: &str = "access_logs";
+const LOG_FILE: &str = "access.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    Common,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessLogSettings {
+    pub enabled: bool,
+    pub format: LogFormat,
+    pub max_file_mb: u64,
+    pub max_files: usize,
+}
+
+impl Default for AccessLogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: LogFormat::Common,
+            max_file_mb: 10,
+            max_files: 5,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AccessLogState {
+    settings: Mutex<AccessLogSettings>,
+    // The open log file and its size; None until the first write.
+    file: Mutex<Option<(File, u64)>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    time: DateTime<Local>,
+    client: String,
+    method: &'a str,
+    path: &'a str,
+    version: String,
+    status: u16,
+    bytes: Option<u64>,
+    duration_ms: u128,
+    request_id: Option<String>,
+}
+
+impl Entry<'_> {
+    fn line(&self, format: LogFormat) -> String {
+        match format {
+            LogFormat::Json => serde_json::to_string(self).unwrap_or_default(),
+            LogFormat::Common => format!(
+                "{} - - [{}] \"{} {} {}\" {} {} {}ms {}",
+                self.client,
+                self.time.format("%d/%b/%Y:%H:%M:%S %z"),
+                self.method,
+                self.path,
+                self.version,
+                self.status,
+                self.bytes.map_or_else(|| "-".to_string(), |b| b.to_string()),
+                self.duration_ms,
+                self.request_id.as_deref().unwrap_or("-")
+            ),
+        }
+    }
+}
+
+fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = storage::data_dir(app)?.join(LOG_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir)
+}
+
+// access.log -> access.log.1 -> ... -> access.log.<max_files>, dropping the oldest.
+fn rotate(dir: &Path, max_files: usize) {
+    let numbered = |n: usize| dir.join(format!("{}.{}", LOG_FILE, n));
+    let _ = std::fs::remove_file(numbered(max_files));
+    for n in (1..max_files).rev() {
+        let _ = std::fs::rename(numbered(n), numbered(n + 1));
+    }
+    if max_files == 0 {
+        let _ = std::fs::remove_file(dir.join(LOG_FILE));
+    } else {
+        let _ = std::fs::rename(dir.join(LOG_FILE), numbered(1));
+    }
+}
+
+fn open(dir: &Path) -> Result<(File, u64), String> {
+    let path = dir.join(LOG_FILE);
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    Ok((file, size))
+}
+
+fn write(app: &AppHandle, settings: &AccessLogSettings, line: &str) -> Result<(), String> {
+    let dir = log_dir(app)?;
+    let state = app.state::<AccessLogState>();
+    let mut file = state.file.lock().unwrap();
+    if file.as_ref().is_some_and(|(_, size)| *size >= settings.max_file_mb * 1024 * 1024) {
+        *file = None;
+        rotate(&dir, settings.max_files);
+    }
+    if file.is_none() {
+        *file = Some(open(&dir)?);
+    }
+    let (handle, size) = file.as_mut().unwrap();
+    writeln!(handle, "{}", line).map_err(|e| format!("Failed to write the access log: {}", e))?;
+    *size += line.len() as u64 + 1;
+    Ok(())
+}
+
+// The query with any access token (see tunnel.rs) blanked out.
+fn redact_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| if pair.starts_with("token=") { "token=REDACTED" } else { pair })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+pub async fn middleware(
+    AxumState(state): AxumState<AppState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let settings = state.app_handle.state::<AccessLogState>().settings.lock().unwrap().clone();
+    if !settings.enabled {
+        return next.run(request).await;
+    }
+
+    let method = request.method().to_string();
+    let path = match request.uri().query() {
+        Some(query) => format!("{}?{}", request.uri().path(), redact_query(query)),
+        None => request.uri().path().to_string(),
+    };
+    let version = format!("{:?}", request.version());
+    let time = Local::now();
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    // Streamed responses have no length up front.
+    let bytes = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let entry = Entry {
+        time,
+        client: client.ip().to_string(),
+        method: &method,
+        path: &path,
+        version,
+        status: response.status().as_u16(),
+        bytes,
+        duration_ms: started.elapsed().as_millis(),
+        request_id: response
+            .headers()
+            .get(request_id::HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+    };
+    if let Err(e) = write(&state.app_handle, &settings, &entry.line(settings.format)) {
+        log::error!("{}", e);
+    }
+    response
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<AccessLogState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_access_log_settings(state: State<'_, AccessLogState>) -> AccessLogSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_access_log_settings(
+    app: AppHandle,
+    settings: AccessLogSettings,
+    state: State<'_, AccessLogState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    log::info!("Access log {}", if settings.enabled { "enabled" } else { "disabled" });
+    *state.settings.lock().unwrap() = settings;
+    // Reopen on the next write, in case the limits changed.
+    *state.file.lock().unwrap() = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_access_log_path(app: AppHandle) -> Result<String, String> {
+    Ok(log_dir(&app)?.join(LOG_FILE).to_string_lossy().to_string())
+}
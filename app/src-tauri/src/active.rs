@@ -0,0 +1,267 @@
+// In src-tauri/src/active.rs
+//
+// Everything in flight right now: proxied generations and `/exec` jobs,
+// each with what it is, when it started and how much has come back so far.
+// The dashboard reads them from `GET /active` or `list_active_requests` and
+// cancels with `DELETE /active/:id` or `cancel_active_request`, which drops
+// the upstream request (Ollama stops generating when the connection goes)
+// or kills the child process.
+//
+// Handlers `register` a request and keep the returned guard for as long as
+// it runs; dropping the guard removes the entry. Entries are keyed by the
+// request ID (see request_id.rs).
+//
+// Output is also broadcast, so more than one view can follow a long job (the
+// chat and a log console, say): `GET /active/:id/stream` is an SSE stream of
+// what the request has produced so far, from the last `REPLAY_LIMIT` bytes,
+// then everything after it until the request ends. Exec jobs send the same
+// events as `/exec`; generations send Ollama's response chunks as data.
+
+use axum::{
+    extract::{Path as AxumPath, State as AxumState},
+    http::StatusCode,
+    response::{
+        sse::{Event, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::{broadcast, watch};
+
+use crate::{request_id, AppState};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActiveKind {
+    Generation,
+    Exec,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveRequest {
+    pub id: String,
+    pub kind: ActiveKind,
+    // The API path, or the command line.
+    pub description: String,
+    pub model: Option<String>,
+    pub agent_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    // Response bytes, or output lines for exec jobs.
+    pub received: u64,
+}
+
+// Output kept for subscribers that join late.
+const REPLAY_LIMIT: usize = 256 * 1024;
+// Chunks a slow subscriber may fall behind by before it misses some.
+const CHANNEL_CAPACITY: usize = 256;
+
+// One SSE event; `event` is None for plain data.
+#[derive(Debug, Clone)]
+struct Published {
+    event: Option<&'static str>,
+    data: String,
+}
+
+impl Published {
+    fn to_event(&self) -> Event {
+        // SSE can't carry carriage returns, and chunks end in their own newline.
+        let event = Event::default().data(self.data.trim_end_matches('\n').replace('\r', ""));
+        match self.event {
+            Some(name) => event.event(name),
+            None => event,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Replay {
+    chunks: VecDeque<Published>,
+    bytes: usize,
+}
+
+struct Entry {
+    request: ActiveRequest,
+    received: AtomicU64,
+    cancel: watch::Sender<bool>,
+    // Held while publishing, so a new subscriber sees each chunk exactly once.
+    replay: Mutex<Replay>,
+    output: broadcast::Sender<Published>,
+}
+
+#[derive(Default)]
+pub struct ActiveRequests(Mutex<BTreeMap<String, Arc<Entry>>>);
+
+impl ActiveRequests {
+    pub fn list(&self) -> Vec<ActiveRequest> {
+        let mut requests: Vec<ActiveRequest> = self
+            .0
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| ActiveRequest { received: entry.received.load(Ordering::Relaxed), ..entry.request.clone() })
+            .collect();
+        requests.sort_by_key(|request| request.started_at);
+        requests
+    }
+
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.0.lock().unwrap().get(id) {
+            Some(entry) => {
+                log::info!("Cancelling {:?} {} ({})", entry.request.kind, id, entry.request.description);
+                entry.cancel.send_replace(true);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// Keeps a request listed while it's alive.
+pub struct ActiveGuard {
+    app: AppHandle,
+    entry: Arc<Entry>,
+    cancelled: watch::Receiver<bool>,
+}
+
+impl ActiveGuard {
+    pub fn add_received(&self, amount: u64) {
+        self.entry.received.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    // Sends output to subscribers; `event` names the SSE event, if any.
+    pub fn publish(&self, event: Option<&'static str>, data: &str) {
+        let chunk = Published { event, data: data.to_string() };
+        let mut replay = self.entry.replay.lock().unwrap();
+        replay.bytes += chunk.data.len();
+        replay.chunks.push_back(chunk.clone());
+        while replay.bytes > REPLAY_LIMIT {
+            let Some(oldest) = replay.chunks.pop_front() else {
+                break;
+            };
+            replay.bytes -= oldest.data.len();
+        }
+        // Err only means nobody is subscribed.
+        let _ = self.entry.output.send(chunk);
+    }
+
+    // Resolves once someone cancels the request.
+    pub fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut cancelled = self.cancelled.clone();
+        async move {
+            if cancelled.wait_for(|cancelled| *cancelled).await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+// `ActiveGuard::cancelled` for a request that may not be tracked.
+pub async fn cancelled(guard: Option<&ActiveGuard>) {
+    match guard {
+        Some(guard) => guard.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        self.app.state::<ActiveRequests>().0.lock().unwrap().remove(&self.entry.request.id);
+    }
+}
+
+pub fn register(
+    app: &AppHandle,
+    kind: ActiveKind,
+    description: String,
+    model: Option<String>,
+    agent_id: Option<String>,
+) -> ActiveGuard {
+    let (cancel, cancelled) = watch::channel(false);
+    let requests = app.state::<ActiveRequests>();
+    let mut requests = requests.0.lock().unwrap();
+    // Clients may reuse their own IDs for concurrent calls; those get a suffix.
+    let mut id = request_id::current().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    if requests.contains_key(&id) {
+        id = format!("{}-{}", id, &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    }
+    let request = ActiveRequest {
+        id,
+        kind,
+        description,
+        model,
+        agent_id,
+        started_at: Utc::now(),
+        received: 0,
+    };
+    let entry = Arc::new(Entry {
+        request,
+        received: AtomicU64::new(0),
+        cancel,
+        replay: Mutex::new(Replay::default()),
+        output: broadcast::channel(CHANNEL_CAPACITY).0,
+    });
+    requests.insert(entry.request.id.clone(), entry.clone());
+    ActiveGuard { app: app.clone(), entry, cancelled }
+}
+
+pub async fn list_handler(AxumState(state): AxumState<AppState>) -> Json<Vec<ActiveRequest>> {
+    Json(state.app_handle.state::<ActiveRequests>().list())
+}
+
+pub async fn cancel_handler(AxumState(state): AxumState<AppState>, AxumPath(id): AxumPath<String>) -> Response {
+    if state.app_handle.state::<ActiveRequests>().cancel(&id) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        (StatusCode::NOT_FOUND, format!("No active request '{}'", id)).into_response()
+    }
+}
+
+pub async fn stream_handler(AxumState(state): AxumState<AppState>, AxumPath(id): AxumPath<String>) -> Response {
+    let Some(entry) = state.app_handle.state::<ActiveRequests>().0.lock().unwrap().get(&id).cloned() else {
+        return (StatusCode::NOT_FOUND, format!("No active request '{}'", id)).into_response();
+    };
+    let (replayed, mut output) = {
+        let replay = entry.replay.lock().unwrap();
+        (replay.chunks.clone(), entry.output.subscribe())
+    };
+    // Holding the entry would keep the channel open after the request ends.
+    drop(entry);
+
+    let stream = async_stream::stream! {
+        for chunk in replayed {
+            yield Ok::<_, Infallible>(chunk.to_event());
+        }
+        loop {
+            match output.recv().await {
+                Ok(chunk) => yield Ok(chunk.to_event()),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    yield Ok(Event::default().event("lagged").data(missed.to_string()));
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+    Sse::new(stream).into_response()
+}
+
+#[tauri::command]
+pub fn list_active_requests(state: State<'_, ActiveRequests>) -> Vec<ActiveRequest> {
+    state.list()
+}
+
+#[tauri::command]
+pub fn cancel_active_request(id: String, state: State<'_, ActiveRequests>) -> Result<(), String> {
+    if state.cancel(&id) {
+        Ok(())
+    } else {
+        Err(format!("No active request '{}'", id))
+    }
+}
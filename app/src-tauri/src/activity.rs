@@ -0,0 +1,148 @@
+// In src-tauri/src/activity.rs
+//
+// Active-window tracker. Every few seconds we sample the focused window and
+// extend (or start) a span in the `activity` table of the history DB.
+// Consecutive samples of the same app are merged into one span, and a gap
+// longer than MAX_GAP (sleep, locked screen, tracker paused) closes it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::incognito;
+use crate::privacy;
+use crate::storage;
+
+const SETTINGS_FILE: &str = "activity.json";
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_GAP_SECONDS: i64 = 15;
+
+// Off until the user opts in, since it records every window title.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivitySettings {
+    pub enabled: bool,
+    // App name -> category, overriding the built-in guesses in analytics.rs.
+    pub categories: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveWindow {
+    pub app_name: String,
+    pub title: String,
+}
+
+struct OpenSpan {
+    id: i64,
+    app_name: String,
+    last_seen: DateTime<Utc>,
+}
+
+#[derive(Default)]
+pub struct ActivityTracker {
+    pub settings: Mutex<ActivitySettings>,
+    pub current: Mutex<Option<ActiveWindow>>,
+    span: Mutex<Option<OpenSpan>>,
+}
+
+fn sample_active_window() -> Option<ActiveWindow> {
+    match active_win_pos_rs::get_active_window() {
+        Ok(window) => Some(ActiveWindow {
+            app_name: window.app_name,
+            title: window.title,
+        }),
+        Err(()) => None,
+    }
+}
+
+fn record_sample(db: &HistoryDb, tracker: &ActivityTracker, window: &ActiveWindow) -> Result<(), String> {
+    let now = Utc::now();
+    let mut span = tracker.span.lock().unwrap();
+    // Nothing is kept while incognito, and a span from before isn't extended.
+    if incognito::is_active() {
+        *span = None;
+        return Ok(());
+    }
+    let conn = db.0.lock().unwrap();
+
+    if let Some(open) = span.as_mut() {
+        let continues = open.app_name == window.app_name
+            && (now - open.last_seen).num_seconds() <= MAX_GAP_SECONDS;
+        if continues {
+            conn.execute(
+                "UPDATE activity SET ended_at = ?1, title = ?2 WHERE id = ?3",
+                rusqlite::params![now.timestamp_millis(), window.title, open.id],
+            )
+            .map_err(|e| e.to_string())?;
+            open.last_seen = now;
+            return Ok(());
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO activity (app_name, title, started_at, ended_at) VALUES (?1, ?2, ?3, ?3)",
+        rusqlite::params![window.app_name, window.title, now.timestamp_millis()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    *span = Some(OpenSpan {
+        id: conn.last_insert_rowid(),
+        app_name: window.app_name.clone(),
+        last_seen: now,
+    });
+    Ok(())
+}
+
+pub fn start_tracker(app: AppHandle) {
+    *app.state::<ActivityTracker>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+
+            let tracker = app.state::<ActivityTracker>();
+            if !tracker.settings.lock().unwrap().enabled || privacy::is_engaged(&app) {
+                *tracker.span.lock().unwrap() = None;
+                continue;
+            }
+
+            let window = tokio::task::spawn_blocking(sample_active_window)
+                .await
+                .ok()
+                .flatten();
+            *tracker.current.lock().unwrap() = window.clone();
+
+            if let Some(window) = window {
+                if let Err(e) = record_sample(&app.state::<HistoryDb>(), &tracker, &window) {
+                    log::error!("Failed to record activity sample: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_activity_settings(tracker: State<'_, ActivityTracker>) -> ActivitySettings {
+    tracker.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_activity_settings(
+    app: AppHandle,
+    settings: ActivitySettings,
+    tracker: State<'_, ActivityTracker>,
+) -> Result<(), String> {
+    log::info!("Activity tracking enabled: {}", settings.enabled);
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *tracker.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_active_window(tracker: State<'_, ActivityTracker>) -> Option<ActiveWindow> {
+    tracker.current.lock().unwrap().clone()
+}
@@ -0,0 +1,244 @@
+// In src-tauri/src/adapters.rs
+//
+// LoRA adapters for Ollama models. Users register the adapters they
+// fine-tuned (a GGUF file, or a Safetensors file or directory) with the base
+// model they were trained on; `create_adapted_model` then builds a new model
+// from the base with one or more adapters attached. The adapter files are
+// uploaded to Ollama as blobs (`/api/blobs`), so this works with a backend
+// on another machine too, and the model is created through `/api/create`.
+// The equivalent Modelfile, with its `FROM` and `ADAPTER` lines, is
+// returned alongside (and by `adapter_modelfile`) for `ollama create` by
+// hand. `list_model_adapters` reads the adapters of an existing model from
+// its Modelfile.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{llm, model_manager, policy, storage};
+
+const ADAPTERS_FILE: &str = "adapters.json";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(1800);
+const CREATE_TIMEOUT: Duration = Duration::from_secs(1800);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Adapter {
+    pub name: String,
+    pub path: String,
+    pub base_model: String,
+    #[serde(default)]
+    pub description: String,
+    // Models created with this adapter.
+    #[serde(default)]
+    pub models: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct AdapterState {
+    adapters: Mutex<BTreeMap<String, Adapter>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdaptedModel {
+    pub name: String,
+    pub base_model: String,
+    pub adapters: Vec<String>,
+    pub modelfile: String,
+}
+
+fn save(app: &AppHandle, adapters: &BTreeMap<String, Adapter>) -> Result<(), String> {
+    storage::save_json(app, ADAPTERS_FILE, adapters)
+}
+
+fn lookup(app: &AppHandle, names: &[String]) -> Result<Vec<Adapter>, String> {
+    let adapters = app.state::<AdapterState>().adapters.lock().unwrap().clone();
+    names
+        .iter()
+        .map(|name| adapters.get(name).cloned().ok_or_else(|| format!("Unknown adapter '{}'", name)))
+        .collect()
+}
+
+fn modelfile(base_model: &str, adapters: &[Adapter], system: Option<&str>) -> String {
+    let mut lines = vec![format!("FROM {}", base_model)];
+    lines.extend(adapters.iter().map(|adapter| format!("ADAPTER {}", adapter.path)));
+    if let Some(system) = system.filter(|s| !s.trim().is_empty()) {
+        lines.push(format!("SYSTEM \"\"\"{}\"\"\"", system));
+    }
+    lines.join("\n") + "\n"
+}
+
+// The files that make up an adapter: the file itself, or a directory's files.
+fn adapter_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+        .map_err(|e| format!("Failed to read adapter {:?}: {}", path, e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| file.is_file())
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("Adapter {:?} has no files", path));
+    }
+    Ok(files)
+}
+
+// Uploads `file` unless Ollama already has it; returns its digest.
+async fn upload_blob(base_url: &str, file: &Path) -> Result<String, String> {
+    let path = file.to_path_buf();
+    let digest = tokio::task::spawn_blocking(move || model_manager::sha256_file(&path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+    let url = format!("{}/api/blobs/{}", base_url, digest);
+    let exists = llm::client().head(&url).timeout(REQUEST_TIMEOUT).send().await;
+    if exists.is_ok_and(|response| response.status().is_success()) {
+        return Ok(digest);
+    }
+    let bytes = tokio::fs::read(file).await.map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+    let response = llm::client()
+        .post(&url)
+        .timeout(UPLOAD_TIMEOUT)
+        .body(bytes)
+        .send()
+        .await
+        .map_err(|e| format!("Uploading {:?} failed: {}", file, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Uploading {:?} failed: {}", file, response.status()));
+    }
+    Ok(digest)
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<AdapterState>().adapters.lock().unwrap() = storage::load_json(app, ADAPTERS_FILE);
+}
+
+#[tauri::command]
+pub fn list_adapters(state: State<'_, AdapterState>) -> Vec<Adapter> {
+    state.adapters.lock().unwrap().values().cloned().collect()
+}
+
+#[tauri::command]
+pub fn add_adapter(app: AppHandle, adapter: Adapter, state: State<'_, AdapterState>) -> Result<(), String> {
+    if adapter.name.trim().is_empty() || adapter.base_model.trim().is_empty() {
+        return Err("An adapter needs a name and the base model it was trained on".to_string());
+    }
+    adapter_files(Path::new(&adapter.path))?;
+    let mut adapters = state.adapters.lock().unwrap().clone();
+    adapters.insert(adapter.name.clone(), adapter);
+    save(&app, &adapters)?;
+    *state.adapters.lock().unwrap() = adapters;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remove_adapter(app: AppHandle, name: String, state: State<'_, AdapterState>) -> Result<(), String> {
+    let mut adapters = state.adapters.lock().unwrap().clone();
+    if adapters.remove(&name).is_none() {
+        return Err(format!("Unknown adapter '{}'", name));
+    }
+    save(&app, &adapters)?;
+    *state.adapters.lock().unwrap() = adapters;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn adapter_modelfile(
+    app: AppHandle,
+    base_model: String,
+    adapters: Vec<String>,
+    system: Option<String>,
+) -> Result<String, String> {
+    Ok(modelfile(&base_model, &lookup(&app, &adapters)?, system.as_deref()))
+}
+
+#[tauri::command]
+pub async fn create_adapted_model(
+    app: AppHandle,
+    name: String,
+    base_model: String,
+    adapters: Vec<String>,
+    system: Option<String>,
+) -> Result<AdaptedModel, String> {
+    if adapters.is_empty() {
+        return Err("Pick at least one adapter".to_string());
+    }
+    let chosen = lookup(&app, &adapters)?;
+    for adapter in chosen.iter().filter(|adapter| adapter.base_model != base_model) {
+        log::warn!("Adapter {} was trained on {}, not {}", adapter.name, adapter.base_model, base_model);
+    }
+    let base_url = llm::ollama_base_url(&app);
+    policy::check_backend(&base_url)?;
+
+    // File name -> blob digest, as /api/create expects.
+    let mut files = BTreeMap::new();
+    for adapter in &chosen {
+        for file in adapter_files(Path::new(&adapter.path))? {
+            let digest = upload_blob(&base_url, &file).await?;
+            let file_name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            files.insert(file_name, digest);
+        }
+    }
+    let mut request = json!({ "model": name, "from": base_model, "adapters": files, "stream": false });
+    if let Some(system) = system.as_deref().filter(|s| !s.trim().is_empty()) {
+        request["system"] = Value::String(system.to_string());
+    }
+    let url = format!("{}/api/create", base_url);
+    let response = llm::client()
+        .post(&url)
+        .timeout(CREATE_TIMEOUT)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Creating {} failed ({}): {}", name, status, text));
+    }
+    log::info!("Created {} from {} with adapters {:?}", name, base_model, adapters);
+
+    let state = app.state::<AdapterState>();
+    let mut registered = state.adapters.lock().unwrap().clone();
+    for adapter_name in &adapters {
+        if let Some(adapter) = registered.get_mut(adapter_name) {
+            if !adapter.models.contains(&name) {
+                adapter.models.push(name.clone());
+            }
+        }
+    }
+    save(&app, &registered)?;
+    *state.adapters.lock().unwrap() = registered;
+
+    Ok(AdaptedModel { modelfile: modelfile(&base_model, &chosen, system.as_deref()), name, base_model, adapters })
+}
+
+// The `ADAPTER` lines of an installed model's Modelfile.
+#[tauri::command]
+pub async fn list_model_adapters(app: AppHandle, model: String) -> Result<Vec<String>, String> {
+    let url = format!("{}/api/show", llm::ollama_base_url(&app));
+    let response = llm::client()
+        .post(&url)
+        .timeout(REQUEST_TIMEOUT)
+        .json(&json!({ "model": model }))
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Model '{}' returned {}", model, response.status()));
+    }
+    let show: Value = response.json().await.map_err(|e| format!("Invalid response from {}: {}", url, e))?;
+    Ok(show["modelfile"]
+        .as_str()
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("ADAPTER "))
+        .map(|adapter| adapter.trim().to_string())
+        .collect())
+}
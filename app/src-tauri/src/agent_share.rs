@@ -0,0 +1,281 @@
+// In src-tauri/src/agent_share.rs
+//
+// Portable agent files. `export_agent` turns a registered agent into a
+// self-describing JSON package (prompt, schedule, triggers, required
+// permissions, model hints). Importing is two-step so users can review what
+// an agent is allowed to do before it lands in their list:
+//
+//   import_agent(path_or_json)      -> ImportPreview (nothing installed yet)
+//   confirm_agent_import(token)     -> installs the agent
+//
+// Permissions are always derived from the prompt and code themselves; the
+// ones declared in the package are only compared against them for warnings.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::agents::{self, AgentDefinition, AgentRegistry};
+
+pub const PACKAGE_FORMAT: &str = "observer-agent";
+pub const PACKAGE_VERSION: u32 = 1;
+const MAX_PACKAGE_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Screen,
+    Clipboard,
+    Microphone,
+    SystemAudio,
+    AgentMemory,
+    Notifications,
+    ExternalMessaging,
+    ControlAgents,
+    Recording,
+}
+
+impl Permission {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Permission::Screen => "Capture your screen (screenshots / OCR)",
+            Permission::Clipboard => "Read your clipboard",
+            Permission::Microphone => "Listen to your microphone",
+            Permission::SystemAudio => "Listen to system / tab audio",
+            Permission::AgentMemory => "Read or write other agents' memory",
+            Permission::Notifications => "Show notifications",
+            Permission::ExternalMessaging => "Send email, SMS, WhatsApp, Discord or Pushover messages",
+            Permission::ControlAgents => "Start and stop other agents",
+            Permission::Recording => "Record video clips",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelHints {
+    pub preferred: String,
+    #[serde(default)]
+    pub requires_vision: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentPackage {
+    pub format: String,
+    pub version: u32,
+    pub agent: AgentDefinition,
+    // Seconds between loop iterations, duplicated from the agent for readability.
+    pub schedule_seconds: f64,
+    // Sensor variables the prompt reacts to, e.g. "$SCREEN_64".
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+    pub model_hints: ModelHints,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionReview {
+    pub permission: Permission,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub token: String,
+    pub agent: AgentDefinition,
+    pub permissions: Vec<PermissionReview>,
+    pub model_hints: ModelHints,
+    pub replaces_existing: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct PendingImports(pub Mutex<HashMap<String, AgentPackage>>);
+
+const SENSORS: &[(&str, Permission)] = &[
+    ("$SCREEN_64", Permission::Screen),
+    ("$SCREEN_OCR", Permission::Screen),
+    ("$SCREEN_UI", Permission::Screen),
+    ("$CLIPBOARD", Permission::Clipboard),
+    ("$MICROPHONE", Permission::Microphone),
+    ("$SCREEN_AUDIO", Permission::SystemAudio),
+    ("$ALL_AUDIO", Permission::SystemAudio),
+    ("$ALL_AUDIO", Permission::Microphone),
+    ("$MEMORY@", Permission::AgentMemory),
+];
+
+const TOOLS: &[(&str, Permission)] = &[
+    ("notify(", Permission::Notifications),
+    ("sendEmail(", Permission::ExternalMessaging),
+    ("sendSms(", Permission::ExternalMessaging),
+    ("sendWhatsapp(", Permission::ExternalMessaging),
+    ("sendDiscordBot(", Permission::ExternalMessaging),
+    ("sendPushover(", Permission::ExternalMessaging),
+    ("startAgent(", Permission::ControlAgents),
+    ("stopAgent(", Permission::ControlAgents),
+    ("startClip(", Permission::Recording),
+    ("getMemory(", Permission::AgentMemory),
+    ("setMemory(", Permission::AgentMemory),
+    ("appendMemory(", Permission::AgentMemory),
+];
+
+pub fn triggers_for(agent: &AgentDefinition) -> Vec<String> {
+    let triggers: BTreeSet<&str> = SENSORS
+        .iter()
+        .filter(|(sensor, _)| agent.system_prompt.contains(sensor))
+        .map(|(sensor, _)| sensor.trim_end_matches('@'))
+        .collect();
+    triggers.into_iter().map(str::to_string).collect()
+}
+
+pub fn permissions_for(agent: &AgentDefinition) -> Vec<Permission> {
+    let mut permissions = BTreeSet::new();
+    for (sensor, permission) in SENSORS {
+        if agent.system_prompt.contains(sensor) {
+            permissions.insert(*permission);
+        }
+    }
+    for (tool, permission) in TOOLS {
+        if agent.code.contains(tool) {
+            permissions.insert(*permission);
+        }
+    }
+    permissions.into_iter().collect()
+}
+
+pub fn build_package(agent: &AgentDefinition) -> AgentPackage {
+    AgentPackage {
+        format: PACKAGE_FORMAT.to_string(),
+        version: PACKAGE_VERSION,
+        agent: agent.clone(),
+        schedule_seconds: agent.loop_interval_seconds,
+        triggers: triggers_for(agent),
+        permissions: permissions_for(agent),
+        model_hints: ModelHints {
+            preferred: agent.model_name.clone(),
+            requires_vision: agent.system_prompt.contains("$SCREEN_64"),
+        },
+    }
+}
+
+fn read_source(path_or_json: &str) -> Result<String, String> {
+    let trimmed = path_or_json.trim_start();
+    let text = if trimmed.starts_with('{') {
+        trimmed.to_string()
+    } else {
+        std::fs::read_to_string(path_or_json.trim())
+            .map_err(|e| format!("Failed to read agent file '{}': {}", path_or_json.trim(), e))?
+    };
+    if text.len() > MAX_PACKAGE_BYTES {
+        return Err(format!("Agent package is too large ({} bytes)", text.len()));
+    }
+    Ok(text)
+}
+
+pub fn parse_package(text: &str) -> Result<AgentPackage, String> {
+    let package: AgentPackage =
+        serde_json::from_str(text).map_err(|e| format!("Invalid agent package: {}", e))?;
+
+    if package.format != PACKAGE_FORMAT {
+        return Err(format!("Unknown package format '{}'", package.format));
+    }
+    if package.version > PACKAGE_VERSION {
+        return Err(format!(
+            "Agent package version {} is newer than this app supports ({})",
+            package.version, PACKAGE_VERSION
+        ));
+    }
+    agents::validate(&package.agent)?;
+    Ok(package)
+}
+
+pub fn preview(package: AgentPackage, registry: &AgentRegistry, pending: &PendingImports) -> ImportPreview {
+    let actual = permissions_for(&package.agent);
+    let mut warnings = Vec::new();
+
+    let undeclared: Vec<_> = actual.iter().filter(|p| !package.permissions.contains(p)).collect();
+    if !undeclared.is_empty() {
+        warnings.push(format!(
+            "The package does not declare all permissions it uses: {:?}",
+            undeclared
+        ));
+    }
+    if package.schedule_seconds != package.agent.loop_interval_seconds {
+        warnings.push("The declared schedule differs from the agent's loop interval".to_string());
+    }
+    if package.agent.loop_interval_seconds < 5.0 {
+        warnings.push(format!(
+            "This agent runs every {}s, which can keep your GPU busy",
+            package.agent.loop_interval_seconds
+        ));
+    }
+
+    let token = uuid::Uuid::new_v4().to_string();
+    let preview = ImportPreview {
+        token: token.clone(),
+        agent: package.agent.clone(),
+        permissions: actual
+            .into_iter()
+            .map(|permission| PermissionReview {
+                permission,
+                description: permission.describe(),
+            })
+            .collect(),
+        model_hints: package.model_hints.clone(),
+        replaces_existing: registry.get(&package.agent.id).is_some(),
+        warnings,
+    };
+    pending.0.lock().unwrap().insert(token, package);
+    preview
+}
+
+#[tauri::command]
+pub fn export_agent(
+    id: String,
+    path: Option<String>,
+    registry: State<'_, AgentRegistry>,
+) -> Result<String, String> {
+    let agent = registry.get(&id).ok_or_else(|| format!("Agent {} not found", id))?;
+    let json = serde_json::to_string_pretty(&build_package(&agent)).map_err(|e| e.to_string())?;
+
+    if let Some(path) = path {
+        std::fs::write(&path, &json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        log::info!("Exported agent {} to {}", id, path);
+    }
+    Ok(json)
+}
+
+#[tauri::command]
+pub fn import_agent(
+    path_or_json: String,
+    registry: State<'_, AgentRegistry>,
+    pending: State<'_, PendingImports>,
+) -> Result<ImportPreview, String> {
+    let package = parse_package(&read_source(&path_or_json)?)?;
+    log::info!("Previewing import of agent {}", package.agent.id);
+    Ok(preview(package, &registry, &pending))
+}
+
+#[tauri::command]
+pub fn confirm_agent_import(
+    app: AppHandle,
+    token: String,
+    pending: State<'_, PendingImports>,
+) -> Result<AgentDefinition, String> {
+    let package = pending
+        .0
+        .lock()
+        .unwrap()
+        .remove(&token)
+        .ok_or_else(|| "Import not found or already completed".to_string())?;
+
+    log::info!("Installing imported agent {}", package.agent.id);
+    agents::upsert(&app, package.agent.clone())?;
+    Ok(package.agent)
+}
+
+#[tauri::command]
+pub fn cancel_agent_import(token: String, pending: State<'_, PendingImports>) {
+    pending.0.lock().unwrap().remove(&token);
+}
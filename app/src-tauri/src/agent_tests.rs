@@ -0,0 +1,337 @@
+// In src-tauri/src/agent_tests.rs
+//
+// Canary tests for agents. Each agent can keep example inputs with
+// assertions about the answer: its prompt gets the example text in place of
+// the `$PLACEHOLDER`s it would normally capture, goes to the agent's current
+// model, and the answer has to
+//   - match a regex,
+//   - parse as JSON, optionally with a value at a JSON pointer (equal to an
+//     expected value if one is given),
+//   - or be at least `threshold` similar (cosine of embeddings, see
+//     vector_store.rs) to an expected answer.
+// `run_agent_tests` stores each run with the model's digest in the history
+// DB and reports regressions: tests that passed last time and fail now. A
+// model download that replaces a model in use runs the tests of the agents
+// using it and emits "agent-tests-finished".
+
+use chrono::{DateTime, TimeZone, Utc};
+use regex::Regex;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::agents::{AgentDefinition, AgentRegistry};
+use crate::history::HistoryDb;
+use crate::{llm, storage, vector_store};
+
+const TESTS_FILE: &str = "agent_tests.json";
+const TAGS_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Assertion {
+    Regex { pattern: String },
+    Json {
+        #[serde(default)]
+        pointer: Option<String>,
+        #[serde(default)]
+        equals: Option<Value>,
+    },
+    Similarity { expected: String, threshold: f32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentTest {
+    pub name: String,
+    // Placeholder (without the `$`) -> the example text that replaces it.
+    #[serde(default)]
+    pub inputs: BTreeMap<String, String>,
+    pub assertions: Vec<Assertion>,
+}
+
+#[derive(Default)]
+pub struct AgentTestState {
+    // Agent id -> its tests.
+    tests: Mutex<BTreeMap<String, Vec<AgentTest>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestResult {
+    pub name: String,
+    pub passed: bool,
+    pub failures: Vec<String>,
+    pub output: String,
+    // Failed after passing in the previous run.
+    #[serde(default)]
+    pub regression: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestRun {
+    pub agent_id: String,
+    pub model: String,
+    pub model_digest: Option<String>,
+    // The digest of the previous run, when the model changed since.
+    pub previous_digest: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub results: Vec<TestResult>,
+    pub regressions: Vec<String>,
+}
+
+fn prompt_for(agent: &AgentDefinition, test: &AgentTest) -> String {
+    // Longest placeholders first, so `$SCREEN_OCR` isn't cut short by `$SCREEN`.
+    let mut inputs: Vec<(&String, &String)> = test.inputs.iter().collect();
+    inputs.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+    inputs.iter().fold(agent.system_prompt.clone(), |prompt, (name, value)| {
+        prompt.replace(&format!("${}", name.trim_start_matches('$')), value)
+    })
+}
+
+async fn check(app: &AppHandle, assertion: &Assertion, output: &str) -> Result<(), String> {
+    match assertion {
+        Assertion::Regex { pattern } => {
+            let regex = Regex::new(pattern).map_err(|e| format!("Invalid regex {}: {}", pattern, e))?;
+            if regex.is_match(output) {
+                Ok(())
+            } else {
+                Err(format!("Doesn't match {}", pattern))
+            }
+        }
+        Assertion::Json { pointer, equals } => {
+            let trimmed = output.trim().trim_start_matches("```json").trim_matches('`').trim();
+            let value: Value = serde_json::from_str(trimmed).map_err(|e| format!("Not JSON: {}", e))?;
+            let Some(pointer) = pointer else {
+                return Ok(());
+            };
+            let found = value.pointer(pointer).ok_or_else(|| format!("No value at {}", pointer))?;
+            match equals {
+                Some(expected) if found != expected => Err(format!("{} is {}, expected {}", pointer, found, expected)),
+                _ => Ok(()),
+            }
+        }
+        Assertion::Similarity { expected, threshold } => {
+            let model = vector_store::embedding_model(app);
+            let vectors = llm::embed(app, &model, &[expected.clone(), output.to_string()]).await?;
+            let [a, b] = vectors.as_slice() else {
+                return Err(format!("{} returned {} embeddings, expected 2", model, vectors.len()));
+            };
+            let similarity = vector_store::cosine(a, b);
+            if similarity >= *threshold {
+                Ok(())
+            } else {
+                Err(format!("Similarity {:.2} is below {:.2}", similarity, threshold))
+            }
+        }
+    }
+}
+
+async fn run_test(app: &AppHandle, agent: &AgentDefinition, test: &AgentTest) -> TestResult {
+    let prompt = prompt_for(agent, test);
+    let messages = vec![llm::ChatMessage::new("user", prompt)];
+    let (output, mut failures) = match llm::chat(app, &agent.model_name, messages).await {
+        Ok(output) => (output, Vec::new()),
+        Err(e) => (String::new(), vec![e]),
+    };
+    if failures.is_empty() {
+        for assertion in &test.assertions {
+            if let Err(e) = check(app, assertion, &output).await {
+                failures.push(e);
+            }
+        }
+    }
+    TestResult { name: test.name.clone(), passed: failures.is_empty(), failures, output, regression: false }
+}
+
+fn normalize(model: &str) -> String {
+    if model.contains(':') {
+        model.to_string()
+    } else {
+        format!("{}:latest", model)
+    }
+}
+
+async fn model_digest(app: &AppHandle, model: &str) -> Option<String> {
+    let url = format!("{}/api/tags", llm::ollama_base_url(app));
+    let tags: Value = llm::client().get(&url).timeout(TAGS_TIMEOUT).send().await.ok()?.json().await.ok()?;
+    let model = normalize(model);
+    tags["models"]
+        .as_array()?
+        .iter()
+        .find(|m| m["name"].as_str().map(normalize).as_deref() == Some(model.as_str()))
+        .and_then(|m| m["digest"].as_str().map(str::to_string))
+}
+
+// The most recent run of the agent: its digest and the tests that passed.
+fn previous_run(app: &AppHandle, agent_id: &str) -> Option<(Option<String>, HashSet<String>)> {
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    let (digest, results): (Option<String>, String) = conn
+        .query_row(
+            "SELECT model_digest, results FROM agent_test_runs WHERE agent_id = ?1 ORDER BY created_at DESC LIMIT 1",
+            params![agent_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok()?;
+    let results: Vec<TestResult> = serde_json::from_str(&results).unwrap_or_default();
+    Some((digest, results.into_iter().filter(|r| r.passed).map(|r| r.name).collect()))
+}
+
+fn store_run(app: &AppHandle, run: &TestRun) {
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    let passed = run.results.iter().filter(|r| r.passed).count() as i64;
+    if let Err(e) = conn.execute(
+        "INSERT INTO agent_test_runs (agent_id, model, model_digest, created_at, passed, failed, results)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            run.agent_id,
+            run.model,
+            run.model_digest,
+            run.created_at.timestamp_millis(),
+            passed,
+            run.results.len() as i64 - passed,
+            serde_json::to_string(&run.results).unwrap_or_default(),
+        ],
+    ) {
+        log::error!("Failed to store the test run of {}: {}", run.agent_id, e);
+    }
+}
+
+pub async fn run(app: &AppHandle, agent_id: &str) -> Result<TestRun, String> {
+    let agent = app
+        .state::<AgentRegistry>()
+        .get(agent_id)
+        .ok_or_else(|| format!("Agent {} not found", agent_id))?;
+    let tests = app.state::<AgentTestState>().tests.lock().unwrap().get(agent_id).cloned().unwrap_or_default();
+    if tests.is_empty() {
+        return Err(format!("Agent {} has no tests", agent_id));
+    }
+
+    let model_digest = model_digest(app, &agent.model_name).await;
+    let (previous_digest, previously_passed) = previous_run(app, agent_id).unwrap_or_default();
+    let mut results = Vec::new();
+    for test in &tests {
+        let mut result = run_test(app, &agent, test).await;
+        result.regression = !result.passed && previously_passed.contains(&result.name);
+        results.push(result);
+    }
+    let regressions: Vec<String> = results.iter().filter(|r| r.regression).map(|r| r.name.clone()).collect();
+    let run = TestRun {
+        agent_id: agent_id.to_string(),
+        model: agent.model_name.clone(),
+        previous_digest: previous_digest.filter(|previous| Some(previous) != model_digest.as_ref()),
+        model_digest,
+        created_at: Utc::now(),
+        results,
+        regressions,
+    };
+    store_run(app, &run);
+    if run.regressions.is_empty() {
+        log::info!("Tests of agent {} on {}: no regressions", agent_id, run.model);
+    } else {
+        log::warn!("Tests of agent {} on {} regressed: {:?}", agent_id, run.model, run.regressions);
+    }
+    Ok(run)
+}
+
+// Runs the tests of every agent using `model`, after it was downloaded again.
+pub fn after_model_update(app: &AppHandle, model: &str) {
+    let model = normalize(model);
+    let tested: Vec<String> = {
+        let tests = app.state::<AgentTestState>().tests.lock().unwrap();
+        app.state::<AgentRegistry>()
+            .list()
+            .into_iter()
+            .filter(|agent| normalize(&agent.model_name) == model)
+            .filter(|agent| tests.get(&agent.id).is_some_and(|tests| !tests.is_empty()))
+            .map(|agent| agent.id)
+            .collect()
+    };
+    if tested.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for agent_id in tested {
+            match run(&app, &agent_id).await {
+                Ok(run) => {
+                    if let Err(e) = app.emit("agent-tests-finished", &run) {
+                        log::error!("Failed to emit agent-tests-finished event: {}", e);
+                    }
+                }
+                Err(e) => log::error!("Tests of agent {} failed to run: {}", agent_id, e),
+            }
+        }
+    });
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<AgentTestState>().tests.lock().unwrap() = storage::load_json(app, TESTS_FILE);
+}
+
+#[tauri::command]
+pub fn get_agent_tests(agent_id: String, state: State<'_, AgentTestState>) -> Vec<AgentTest> {
+    state.tests.lock().unwrap().get(&agent_id).cloned().unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn set_agent_tests(
+    app: AppHandle,
+    agent_id: String,
+    tests: Vec<AgentTest>,
+    state: State<'_, AgentTestState>,
+) -> Result<(), String> {
+    for assertion in tests.iter().flat_map(|test| &test.assertions) {
+        if let Assertion::Regex { pattern } = assertion {
+            Regex::new(pattern).map_err(|e| format!("Invalid regex {}: {}", pattern, e))?;
+        }
+    }
+    let mut all = state.tests.lock().unwrap().clone();
+    if tests.is_empty() {
+        all.remove(&agent_id);
+    } else {
+        all.insert(agent_id, tests);
+    }
+    storage::save_json(&app, TESTS_FILE, &all)?;
+    *state.tests.lock().unwrap() = all;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn run_agent_tests(app: AppHandle, agent_id: String) -> Result<TestRun, String> {
+    run(&app, &agent_id).await
+}
+
+#[tauri::command]
+pub fn list_agent_test_runs(
+    agent_id: String,
+    limit: Option<u32>,
+    db: State<'_, HistoryDb>,
+) -> Result<Vec<TestRun>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT model, model_digest, created_at, results FROM agent_test_runs
+             WHERE agent_id = ?1 ORDER BY created_at DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![agent_id, limit.unwrap_or(20)], |row| {
+            let results: Vec<TestResult> = serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default();
+            Ok(TestRun {
+                agent_id: agent_id.clone(),
+                model: row.get(0)?,
+                model_digest: row.get(1)?,
+                previous_digest: None,
+                created_at: Utc.timestamp_millis_opt(row.get(2)?).single().unwrap_or_default(),
+                regressions: results.iter().filter(|r| r.regression).map(|r| r.name.clone()).collect(),
+                results,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| format!("Failed to read test runs: {}", e))
+}
@@ -0,0 +1,216 @@
+// In src-tauri/src/agents.rs
+//
+// Backend mirror of the agents the frontend keeps in IndexedDB. The frontend
+// pushes every save/delete, and its whole list once at startup, so backend
+// features (sharing, summaries, the OpenAI facade, ...) can see agent
+// definitions without round-tripping through the webview. The web app talks
+// to the embedded server:
+//
+//   PUT    /agents           the complete list; agents not in it are dropped
+//   PUT    /agents/:id       one agent, after a save
+//   DELETE /agents/:id       after a delete
+//   POST   /agents/imported  takes the agents the app added itself
+//
+// `sync_agent` / `remove_agent` do the same for callers inside the app.
+//
+// Agents added on the backend's side (imports, onboarding) are kept in
+// `ImportedAgents` until the web app has taken them into IndexedDB, so a
+// complete list sent before then doesn't drop them.
+
+use axum::{
+    extract::{Path as AxumPath, State as AxumState},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{storage, AppState};
+
+const AGENTS_FILE: &str = "agents.json";
+const IMPORTED_FILE: &str = "agents_imported.json";
+
+// Same shape as `AgentExport` in app/src/utils/agent_database.ts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentDefinition {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub model_name: String,
+    pub system_prompt: String,
+    pub loop_interval_seconds: f64,
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub memory: String,
+}
+
+#[derive(Default)]
+pub struct AgentRegistry(pub Mutex<BTreeMap<String, AgentDefinition>>);
+
+impl AgentRegistry {
+    pub fn get(&self, id: &str) -> Option<AgentDefinition> {
+        self.0.lock().unwrap().get(id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<AgentDefinition> {
+        self.0.lock().unwrap().values().cloned().collect()
+    }
+}
+
+pub fn validate(agent: &AgentDefinition) -> Result<(), String> {
+    if agent.id.is_empty() || !agent.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!(
+            "Invalid agent ID '{}'. Use only letters, numbers, and underscores.",
+            agent.id
+        ));
+    }
+    if agent.name.trim().is_empty() {
+        return Err(format!("Agent '{}' has no name", agent.id));
+    }
+    if agent.loop_interval_seconds.is_nan() || agent.loop_interval_seconds <= 0.0 {
+        return Err(format!("Agent '{}' needs a positive loop interval", agent.id));
+    }
+    Ok(())
+}
+
+// Agents the web app hasn't taken yet.
+#[derive(Default)]
+pub struct ImportedAgents(Mutex<BTreeMap<String, AgentDefinition>>);
+
+fn persist(app: &AppHandle, registry: &AgentRegistry) -> Result<(), String> {
+    let agents = registry.0.lock().unwrap().clone();
+    storage::save_json(app, AGENTS_FILE, &agents)
+}
+
+fn persist_imported(app: &AppHandle, imported: &BTreeMap<String, AgentDefinition>) -> Result<(), String> {
+    storage::save_json(app, IMPORTED_FILE, imported)
+}
+
+pub fn init(app: &AppHandle) {
+    let agents: BTreeMap<String, AgentDefinition> = storage::load_json(app, AGENTS_FILE);
+    log::info!("Loaded {} agent definitions", agents.len());
+    *app.state::<AgentRegistry>().0.lock().unwrap() = agents;
+    *app.state::<ImportedAgents>().0.lock().unwrap() = storage::load_json(app, IMPORTED_FILE);
+}
+
+// Adds or replaces an agent and tells the frontend about it.
+pub fn upsert(app: &AppHandle, agent: AgentDefinition) -> Result<(), String> {
+    validate(&agent)?;
+    let registry = app.state::<AgentRegistry>();
+    registry.0.lock().unwrap().insert(agent.id.clone(), agent.clone());
+    persist(app, &registry)?;
+    {
+        let pending = app.state::<ImportedAgents>();
+        let mut imported = pending.0.lock().unwrap();
+        imported.insert(agent.id.clone(), agent.clone());
+        persist_imported(app, &imported)?;
+    }
+    if let Err(e) = app.emit("agent-upserted", agent) {
+        log::error!("Failed to emit agent-upserted event: {}", e);
+    }
+    Ok(())
+}
+
+// Stores an agent the frontend saved. Unlike `upsert`, the frontend isn't
+// told, since it's where the change came from.
+fn sync(app: &AppHandle, agent: AgentDefinition) -> Result<(), String> {
+    validate(&agent)?;
+    let registry = app.state::<AgentRegistry>();
+    registry.0.lock().unwrap().insert(agent.id.clone(), agent);
+    persist(app, &registry)
+}
+
+fn remove(app: &AppHandle, id: &str) -> Result<bool, String> {
+    {
+        let pending = app.state::<ImportedAgents>();
+        let mut imported = pending.0.lock().unwrap();
+        if imported.remove(id).is_some() {
+            persist_imported(app, &imported)?;
+        }
+    }
+    let registry = app.state::<AgentRegistry>();
+    let removed = registry.0.lock().unwrap().remove(id).is_some();
+    if removed {
+        persist(app, &registry)?;
+    }
+    Ok(removed)
+}
+
+// Replaces the mirror with the frontend's complete list, keeping the agents
+// it hasn't taken yet.
+fn replace(app: &AppHandle, agents: Vec<AgentDefinition>) -> Result<usize, String> {
+    agents.iter().try_for_each(validate)?;
+    let mut agents: BTreeMap<String, AgentDefinition> =
+        agents.into_iter().map(|agent| (agent.id.clone(), agent)).collect();
+    let count = agents.len();
+    for (id, agent) in app.state::<ImportedAgents>().0.lock().unwrap().iter() {
+        agents.entry(id.clone()).or_insert_with(|| agent.clone());
+    }
+    let registry = app.state::<AgentRegistry>();
+    *registry.0.lock().unwrap() = agents;
+    persist(app, &registry)?;
+    log::info!("Synced {} agent definitions from the frontend", count);
+    Ok(count)
+}
+
+pub async fn replace_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(agents): Json<Vec<AgentDefinition>>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    replace(&state.app_handle, agents).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Hands the agents added on the backend's side over to the web app.
+pub async fn imported_handler(
+    AxumState(state): AxumState<AppState>,
+) -> Result<Json<Vec<AgentDefinition>>, (StatusCode, String)> {
+    let app = &state.app_handle;
+    let pending = app.state::<ImportedAgents>();
+    let mut imported = pending.0.lock().unwrap();
+    let taken: Vec<AgentDefinition> = std::mem::take(&mut *imported).into_values().collect();
+    persist_imported(app, &imported).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(Json(taken))
+}
+
+pub async fn sync_handler(
+    AxumState(state): AxumState<AppState>,
+    AxumPath(id): AxumPath<String>,
+    Json(agent): Json<AgentDefinition>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if agent.id != id {
+        return Err((StatusCode::BAD_REQUEST, format!("Agent ID '{}' doesn't match the path", agent.id)));
+    }
+    sync(&state.app_handle, agent).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn remove_handler(AxumState(state): AxumState<AppState>, AxumPath(id): AxumPath<String>) -> StatusCode {
+    match remove(&state.app_handle, &id) {
+        Ok(true) => StatusCode::NO_CONTENT,
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(e) => {
+            log::error!("Failed to remove agent {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[tauri::command]
+pub fn sync_agent(app: AppHandle, agent: AgentDefinition) -> Result<(), String> {
+    sync(&app, agent)
+}
+
+#[tauri::command]
+pub fn remove_agent(app: AppHandle, id: String) -> Result<bool, String> {
+    remove(&app, &id)
+}
+
+#[tauri::command]
+pub fn list_agents(registry: State<'_, AgentRegistry>) -> Vec<AgentDefinition> {
+    registry.list()
+}
@@ -0,0 +1,240 @@
+// In src-tauri/src/analytics.rs
+//
+// Time-tracking analytics computed from the activity spans recorded by
+// activity.rs: time per app and per category for each local day. Served at
+// `/analytics/time` for the UI charts and used by the daily summary.
+
+use axum::{
+    extract::{Query, State as AxumState},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::{AppHandle, Manager};
+
+use crate::activity::ActivityTracker;
+use crate::history::HistoryDb;
+use crate::AppState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppUsage {
+    pub app: String,
+    pub category: String,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryUsage {
+    pub category: String,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DayUsage {
+    pub date: NaiveDate,
+    pub total_seconds: i64,
+    pub apps: Vec<AppUsage>,
+    pub categories: Vec<CategoryUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub days: Vec<DayUsage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TimeParams {
+    // Inclusive local dates, "YYYY-MM-DD". Defaults to the last `days` days (7).
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub days: Option<u32>,
+}
+
+pub fn categorize(app: &str, overrides: &HashMap<String, String>) -> String {
+    if let Some(category) = overrides.get(app) {
+        return category.clone();
+    }
+
+    let name = app.to_lowercase();
+    let matches = |needles: &[&str]| needles.iter().any(|n| name.contains(n));
+
+    let category = if matches(&["chrome", "firefox", "safari", "edge", "brave", "opera", "vivaldi", "arc"]) {
+        "browser"
+    } else if matches(&[
+        "code", "idea", "pycharm", "webstorm", "xcode", "terminal", "iterm", "alacritty", "kitty",
+        "wezterm", "vim", "zed", "sublime", "android studio",
+    ]) {
+        "development"
+    } else if matches(&[
+        "slack", "discord", "teams", "zoom", "mail", "outlook", "thunderbird", "telegram", "whatsapp",
+        "signal", "messages",
+    ]) {
+        "communication"
+    } else if matches(&[
+        "word", "excel", "powerpoint", "pages", "numbers", "keynote", "libreoffice", "notion",
+        "obsidian", "acrobat", "preview",
+    ]) {
+        "productivity"
+    } else if matches(&["spotify", "vlc", "music", "netflix", "mpv", "iina"]) {
+        "media"
+    } else if matches(&["observer"]) {
+        "observer"
+    } else {
+        "other"
+    };
+    category.to_string()
+}
+
+pub fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|t| Local.from_local_datetime(&t).earliest())
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now)
+}
+
+pub fn resolve_range(params: &TimeParams) -> (NaiveDate, NaiveDate) {
+    let today = Local::now().date_naive();
+    let to = params.to.unwrap_or(today);
+    let from = params
+        .from
+        .unwrap_or_else(|| to - ChronoDuration::days(params.days.unwrap_or(7).max(1) as i64 - 1));
+    (from.min(to), to)
+}
+
+pub fn time_report(
+    db: &HistoryDb,
+    overrides: &HashMap<String, String>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<TimeReport, String> {
+    let range_start = local_midnight(from);
+    let range_end = local_midnight(to + ChronoDuration::days(1));
+
+    let spans: Vec<(String, i64, i64)> = {
+        let conn = db.0.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT app_name, started_at, ended_at FROM activity
+                 WHERE ended_at >= ?1 AND started_at < ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![range_start.timestamp_millis(), range_end.timestamp_millis()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<_>>().map_err(|e| e.to_string())?
+    };
+
+    // date -> app -> seconds, splitting spans that cross midnight.
+    let mut per_day: BTreeMap<NaiveDate, HashMap<String, i64>> = BTreeMap::new();
+    for (app, started_at, ended_at) in spans {
+        let mut start = started_at.max(range_start.timestamp_millis());
+        let end = ended_at.min(range_end.timestamp_millis());
+
+        while start < end {
+            let Some(start_time) = Utc.timestamp_millis_opt(start).single() else {
+                break;
+            };
+            let date = start_time.with_timezone(&Local).date_naive();
+            let day_end = local_midnight(date + ChronoDuration::days(1)).timestamp_millis();
+            let chunk_end = end.min(day_end).max(start + 1);
+
+            *per_day.entry(date).or_default().entry(app.clone()).or_default() += (chunk_end - start) / 1000;
+            start = chunk_end;
+        }
+    }
+
+    let days = per_day
+        .into_iter()
+        .map(|(date, apps)| {
+            let mut categories: HashMap<String, i64> = HashMap::new();
+            let mut apps: Vec<AppUsage> = apps
+                .into_iter()
+                .map(|(app, seconds)| {
+                    let category = categorize(&app, overrides);
+                    *categories.entry(category.clone()).or_default() += seconds;
+                    AppUsage { app, category, seconds }
+                })
+                .collect();
+            apps.sort_by(|a, b| b.seconds.cmp(&a.seconds));
+
+            let mut categories: Vec<CategoryUsage> = categories
+                .into_iter()
+                .map(|(category, seconds)| CategoryUsage { category, seconds })
+                .collect();
+            categories.sort_by(|a, b| b.seconds.cmp(&a.seconds));
+
+            DayUsage {
+                date,
+                total_seconds: apps.iter().map(|a| a.seconds).sum(),
+                apps,
+                categories,
+            }
+        })
+        .collect();
+
+    Ok(TimeReport { from, to, days })
+}
+
+fn format_duration(seconds: i64) -> String {
+    if seconds >= 3600 {
+        format!("{:.1}h", seconds as f64 / 3600.0)
+    } else {
+        format!("{}min", seconds / 60)
+    }
+}
+
+// One-line digest for the daily summary, e.g.
+// "Time spent today: browser 3.5h, development 2.1h, communication 40min".
+pub fn today_summary_line(app: &AppHandle) -> Option<String> {
+    let overrides = app.state::<ActivityTracker>().settings.lock().unwrap().categories.clone();
+    let today = Local::now().date_naive();
+    let report = time_report(&app.state::<HistoryDb>(), &overrides, today, today).ok()?;
+    let day = report.days.into_iter().next()?;
+
+    let parts: Vec<String> = day
+        .categories
+        .iter()
+        .filter(|c| c.seconds >= 60)
+        .take(5)
+        .map(|c| format!("{} {}", c.category, format_duration(c.seconds)))
+        .collect();
+    if parts.is_empty() {
+        return None;
+    }
+    Some(format!("Time spent today: {}", parts.join(", ")))
+}
+
+fn report_for(app: &AppHandle, params: &TimeParams) -> Result<TimeReport, String> {
+    let overrides = app.state::<ActivityTracker>().settings.lock().unwrap().categories.clone();
+    let (from, to) = resolve_range(params);
+    time_report(&app.state::<HistoryDb>(), &overrides, from, to)
+}
+
+pub async fn time_handler(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<TimeParams>,
+) -> Result<Json<TimeReport>, (StatusCode, String)> {
+    report_for(&state.app_handle, &params)
+        .map(Json)
+        .map_err(|e| {
+            log::error!("Failed to compute time analytics: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e)
+        })
+}
+
+#[tauri::command]
+pub fn get_time_analytics(
+    app: AppHandle,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    days: Option<u32>,
+) -> Result<TimeReport, String> {
+    report_for(&app, &TimeParams { from, to, days })
+}
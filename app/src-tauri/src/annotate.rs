@@ -0,0 +1,267 @@
+// In src-tauri/src/annotate.rs
+//
+// Screenshot annotation before an image goes to a model: crop to the part
+// that matters, blur sensitive regions, draw arrows and highlight boxes so
+// the user can point the model at exactly what they mean.
+//
+// `open_annotation_overlay` shows a small window (the frontend renders it
+// for `#annotate`) where the user marks up the image; `finish_annotation`
+// applies the marks here and emits "annotation-ready". The same operations
+// are available directly through `annotate_image` and `POST /annotate`.
+// All coordinates are in pixels of the original image.
+
+use axum::{extract::State as AxumState, http::StatusCode, Json};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use image::{imageops, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::AppState;
+
+const OVERLAY_LABEL: &str = "annotate";
+const DEFAULT_ARROW_COLOR: &str = "#ff3b30";
+const DEFAULT_HIGHLIGHT_COLOR: &str = "#ffd60a";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    Crop { rect: Rect },
+    Blur { rect: Rect },
+    Arrow { from: Point, to: Point, color: Option<String> },
+    Highlight { rect: Rect, color: Option<String> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnotatedImage {
+    pub mime: String,
+    pub base64: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnnotateRequest {
+    pub image: String,
+    pub annotations: Vec<Annotation>,
+}
+
+// The image being annotated in the overlay, and who asked for it.
+#[derive(Default)]
+pub struct AnnotationSession(Mutex<Option<(String, Option<String>)>>);
+
+fn parse_color(color: Option<&str>, default: &str) -> Rgba<u8> {
+    let hex = color.unwrap_or(default).trim_start_matches('#');
+    let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+    match (channel(0), channel(2), channel(4)) {
+        (Some(r), Some(g), Some(b)) => Rgba([r, g, b, 255]),
+        _ => parse_color(None, default),
+    }
+}
+
+// Clamps a rect to the image; None if nothing is left.
+fn clamp(rect: Rect, image: &RgbaImage) -> Option<Rect> {
+    let x = rect.x.min(image.width());
+    let y = rect.y.min(image.height());
+    let width = rect.width.min(image.width() - x);
+    let height = rect.height.min(image.height() - y);
+    (width > 0 && height > 0).then_some(Rect { x, y, width, height })
+}
+
+fn blend(pixel: &mut Rgba<u8>, color: Rgba<u8>, alpha: f32) {
+    for (channel, target) in pixel.0.iter_mut().zip(color.0).take(3) {
+        *channel = (*channel as f32 * (1.0 - alpha) + target as f32 * alpha) as u8;
+    }
+}
+
+fn blur(image: &mut RgbaImage, rect: Rect) {
+    let region = imageops::crop_imm(&*image, rect.x, rect.y, rect.width, rect.height).to_image();
+    // Strong enough that text in the region is unreadable.
+    let sigma = (rect.width.min(rect.height) as f32 / 8.0).max(6.0);
+    imageops::replace(image, &imageops::blur(&region, sigma), rect.x as i64, rect.y as i64);
+}
+
+fn highlight(image: &mut RgbaImage, rect: Rect, color: Rgba<u8>) {
+    let border = (image.width().max(image.height()) / 400).max(2);
+    for y in rect.y..rect.y + rect.height {
+        for x in rect.x..rect.x + rect.width {
+            let on_border = x < rect.x + border
+                || y < rect.y + border
+                || x >= rect.x + rect.width - border.min(rect.width)
+                || y >= rect.y + rect.height - border.min(rect.height);
+            blend(image.get_pixel_mut(x, y), color, if on_border { 1.0 } else { 0.3 });
+        }
+    }
+}
+
+fn draw_line(image: &mut RgbaImage, from: Point, to: Point, thickness: f32, color: Rgba<u8>) {
+    let length = ((to.x - from.x).powi(2) + (to.y - from.y).powi(2)).sqrt();
+    let steps = length.ceil().max(1.0) as u32;
+    let radius = thickness / 2.0;
+    let r = radius.ceil() as i64;
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let cx = from.x + (to.x - from.x) * t;
+        let cy = from.y + (to.y - from.y) * t;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if ((dx * dx + dy * dy) as f32) > radius * radius {
+                    continue;
+                }
+                let (x, y) = (cx as i64 + dx, cy as i64 + dy);
+                if x >= 0 && y >= 0 && (x as u32) < image.width() && (y as u32) < image.height() {
+                    image.put_pixel(x as u32, y as u32, color);
+                }
+            }
+        }
+    }
+}
+
+fn arrow(image: &mut RgbaImage, from: Point, to: Point, color: Rgba<u8>) {
+    // Scale with the screenshot so arrows stay visible on 4K captures.
+    let thickness = (image.width().max(image.height()) as f32 / 300.0).max(3.0);
+    draw_line(image, from, to, thickness, color);
+
+    let angle = (to.y - from.y).atan2(to.x - from.x);
+    let head = thickness * 5.0;
+    for side in [-0.5f32, 0.5] {
+        let back = angle + std::f32::consts::PI + side;
+        let tip = Point {
+            x: to.x + head * back.cos(),
+            y: to.y + head * back.sin(),
+        };
+        draw_line(image, to, tip, thickness, color);
+    }
+}
+
+pub fn decode(data: &str) -> Result<RgbaImage, String> {
+    // Accept data URLs as well as bare base64.
+    let encoded = data.split_once(',').map_or(data, |(_, rest)| rest);
+    let bytes = STANDARD.decode(encoded).map_err(|e| format!("Invalid base64 image: {}", e))?;
+    let image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+    Ok(image.to_rgba8())
+}
+
+pub fn encode(image: RgbaImage) -> Result<AnnotatedImage, String> {
+    let (width, height) = image.dimensions();
+    let mut bytes = Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgb8(image::DynamicImage::ImageRgba8(image).to_rgb8())
+        .write_to(&mut bytes, image::ImageOutputFormat::Jpeg(90))
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(AnnotatedImage {
+        mime: "image/jpeg".to_string(),
+        base64: STANDARD.encode(bytes.into_inner()),
+        width,
+        height,
+    })
+}
+
+// Marks are drawn first and the crop applied last, so every annotation uses
+// the original image's coordinates.
+pub fn apply(mut image: RgbaImage, annotations: &[Annotation]) -> RgbaImage {
+    let mut crop = None;
+    for annotation in annotations {
+        match annotation {
+            Annotation::Crop { rect } => crop = Some(*rect),
+            Annotation::Blur { rect } => {
+                if let Some(rect) = clamp(*rect, &image) {
+                    blur(&mut image, rect);
+                }
+            }
+            Annotation::Highlight { rect, color } => {
+                if let Some(rect) = clamp(*rect, &image) {
+                    highlight(&mut image, rect, parse_color(color.as_deref(), DEFAULT_HIGHLIGHT_COLOR));
+                }
+            }
+            Annotation::Arrow { from, to, color } => {
+                arrow(&mut image, *from, *to, parse_color(color.as_deref(), DEFAULT_ARROW_COLOR));
+            }
+        }
+    }
+    match crop.and_then(|rect| clamp(rect, &image)) {
+        Some(rect) => imageops::crop_imm(&image, rect.x, rect.y, rect.width, rect.height).to_image(),
+        None => image,
+    }
+}
+
+pub fn annotate(image: &str, annotations: &[Annotation]) -> Result<AnnotatedImage, String> {
+    encode(apply(decode(image)?, annotations))
+}
+
+pub async fn annotate_handler(
+    AxumState(_state): AxumState<AppState>,
+    Json(request): Json<AnnotateRequest>,
+) -> Result<Json<AnnotatedImage>, (StatusCode, String)> {
+    tokio::task::spawn_blocking(move || annotate(&request.image, &request.annotations))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+#[tauri::command]
+pub async fn annotate_image(image: String, annotations: Vec<Annotation>) -> Result<AnnotatedImage, String> {
+    tokio::task::spawn_blocking(move || annotate(&image, &annotations))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// `agent_id` is passed back with the result so the right agent picks it up.
+#[tauri::command]
+pub async fn open_annotation_overlay(
+    app: AppHandle,
+    image: String,
+    agent_id: Option<String>,
+    session: State<'_, AnnotationSession>,
+) -> Result<(), String> {
+    *session.0.lock().unwrap() = Some((image, agent_id));
+    if let Some(window) = app.get_webview_window(OVERLAY_LABEL) {
+        let _ = app.emit("annotation-source-changed", ());
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(&app, OVERLAY_LABEL, WebviewUrl::App("index.html#annotate".into()))
+        .title("Annotate screenshot")
+        .inner_size(1100.0, 750.0)
+        .always_on_top(true)
+        .build()
+        .map_err(|e| format!("Failed to open annotation window: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_annotation_source(session: State<'_, AnnotationSession>) -> Option<String> {
+    session.0.lock().unwrap().as_ref().map(|(image, _)| image.clone())
+}
+
+// `annotations` of None means the user cancelled.
+#[tauri::command]
+pub async fn finish_annotation(app: AppHandle, annotations: Option<Vec<Annotation>>) -> Result<(), String> {
+    let session = app.state::<AnnotationSession>().0.lock().unwrap().take();
+    if let Some(window) = app.get_webview_window(OVERLAY_LABEL) {
+        let _ = window.close();
+    }
+    let (Some((image, agent_id)), Some(annotations)) = (session, annotations) else {
+        return Ok(());
+    };
+
+    let annotated = tokio::task::spawn_blocking(move || annotate(&image, &annotations))
+        .await
+        .map_err(|e| e.to_string())??;
+    let payload = serde_json::json!({ "agent_id": agent_id, "image": annotated });
+    app.emit("annotation-ready", payload).map_err(|e| e.to_string())
+}
@@ -0,0 +1,410 @@
+// In src-tauri/src/attachments.rs
+//
+// Content-addressed storage for files referenced in conversations (images,
+// audio, documents). Files are stored once under their SHA-256 in
+// `attachments/<first two hex chars>/<hash>` and served at
+// `/attachments/:hash`. Each conversation that uses a file holds a reference;
+// deleting a conversation drops its references and files nobody references
+// any more are removed, once they're older than `UNREFERENCED_GRACE_HOURS`
+// so an upload isn't collected before the request that uses it arrives.
+// Total and per-file sizes are capped by a quota.
+//
+// `POST /upload` (observer-core's server.rs, with `store` behind it) takes
+// files as multipart form data, so clients can send images and audio as raw
+// bytes instead of base64 inside a JSON body. A chat request then refers to
+// an upload as `attachment:<hash>` wherever it would have put the base64
+// (`images` entries, an `image_url` URL, `input_audio` data) and the proxy
+// inlines the file before sending it upstream.
+
+use axum::http::StatusCode;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::{incognito, storage};
+
+const SETTINGS_FILE: &str = "attachments.json";
+const ATTACHMENTS_DIR: &str = "attachments";
+pub const REFERENCE_PREFIX: &str = "attachment:";
+const UNREFERENCED_GRACE_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachmentSettings {
+    pub max_file_bytes: u64,
+    pub max_total_bytes: u64,
+}
+
+impl Default for AttachmentSettings {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 100 * 1024 * 1024,
+            max_total_bytes: 2 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AttachmentState(pub Mutex<AttachmentSettings>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    pub hash: String,
+    pub mime: String,
+    pub size: u64,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentUsage {
+    pub files: u64,
+    pub total_bytes: u64,
+    pub max_total_bytes: u64,
+}
+
+fn is_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase())
+}
+
+fn file_path(app: &AppHandle, hash: &str) -> Result<PathBuf, String> {
+    Ok(storage::data_dir(app)?.join(ATTACHMENTS_DIR).join(&hash[..2]).join(hash))
+}
+
+fn total_bytes(conn: &Connection) -> Result<u64, String> {
+    conn.query_row("SELECT COALESCE(SUM(size), 0) FROM attachments", [], |row| row.get::<_, i64>(0))
+        .map(|v| v as u64)
+        .map_err(|e| e.to_string())
+}
+
+fn to_attachment(hash: String, mime: String, size: u64) -> Attachment {
+    Attachment {
+        url: format!("/attachments/{}", hash),
+        hash,
+        mime,
+        size,
+    }
+}
+
+pub fn store(
+    app: &AppHandle,
+    bytes: &[u8],
+    mime: &str,
+    conversation_id: Option<i64>,
+) -> Result<Attachment, String> {
+    incognito::ensure_persistent()?;
+    let settings = app.state::<AttachmentState>().0.lock().unwrap().clone();
+    let size = bytes.len() as u64;
+    if size > settings.max_file_bytes {
+        return Err(format!(
+            "Attachment is {} bytes, the limit is {}",
+            size, settings.max_file_bytes
+        ));
+    }
+
+    let hash: String = Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect();
+    let db = app.state::<HistoryDb>();
+    let stored = |conn: &Connection| -> Result<Option<String>, String> {
+        conn.query_row("SELECT mime FROM attachments WHERE hash = ?1", params![hash], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())
+    };
+
+    // The file is written without holding the database, which everything
+    // else shares; a file of the same hash written meanwhile is identical.
+    if stored(&db.0.lock().unwrap())?.is_none() {
+        let used = total_bytes(&db.0.lock().unwrap())?;
+        if used + size > settings.max_total_bytes {
+            return Err(format!(
+                "Attachment storage is full ({} of {} bytes used)",
+                used, settings.max_total_bytes
+            ));
+        }
+        let path = file_path(app, &hash)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        }
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, bytes).map_err(|e| format!("Failed to write {:?}: {}", partial, e))?;
+        std::fs::rename(&partial, &path).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    }
+
+    let conn = db.0.lock().unwrap();
+    let mime = match stored(&conn)? {
+        // Already stored; only the reference is new. Uploading it again
+        // restarts its grace period.
+        Some(mime) => {
+            conn.execute(
+                "UPDATE attachments SET created_at = ?2 WHERE hash = ?1",
+                params![hash, Utc::now().timestamp_millis()],
+            )
+            .map_err(|e| e.to_string())?;
+            mime
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO attachments (hash, mime, size, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![hash, mime, size as i64, Utc::now().timestamp_millis()],
+            )
+            .map_err(|e| e.to_string())?;
+            mime.to_string()
+        }
+    };
+
+    if let Some(conversation_id) = conversation_id {
+        conn.execute(
+            "INSERT OR IGNORE INTO attachment_refs (hash, conversation_id) VALUES (?1, ?2)",
+            params![hash, conversation_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(to_attachment(hash, mime, size))
+}
+
+// Removes files that no conversation references. Returns the bytes freed.
+pub fn collect_garbage(app: &AppHandle, conn: &Connection) -> Result<u64, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT hash, size FROM attachments
+             WHERE hash NOT IN (SELECT hash FROM attachment_refs) AND created_at < ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let cutoff = (Utc::now() - chrono::Duration::hours(UNREFERENCED_GRACE_HOURS)).timestamp_millis();
+    let orphans = stmt
+        .query_map(params![cutoff], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut freed = 0;
+    for (hash, size) in orphans {
+        let path = file_path(app, &hash)?;
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                log::warn!("Failed to remove attachment {:?}: {}", path, e);
+                continue;
+            }
+        }
+        conn.execute("DELETE FROM attachments WHERE hash = ?1", params![hash])
+            .map_err(|e| e.to_string())?;
+        freed += size as u64;
+    }
+    if freed > 0 {
+        log::info!("Attachment garbage collection freed {} bytes", freed);
+    }
+    Ok(freed)
+}
+
+// Called when a conversation is deleted.
+pub fn release_conversation(app: &AppHandle, conn: &Connection, conversation_id: i64) -> Result<u64, String> {
+    conn.execute(
+        "DELETE FROM attachment_refs WHERE conversation_id = ?1",
+        params![conversation_id],
+    )
+    .map_err(|e| e.to_string())?;
+    collect_garbage(app, conn)
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<AttachmentState>().0.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+// A stored file and its type, for `/attachments/:hash`.
+pub async fn load(app: &AppHandle, hash: &str) -> Result<(Vec<u8>, String), StatusCode> {
+    if !is_hash(hash) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let mime: String = {
+        let db = app.state::<HistoryDb>();
+        let conn = db.0.lock().unwrap();
+        conn.query_row("SELECT mime FROM attachments WHERE hash = ?1", params![hash], |row| row.get(0))
+            .optional()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?
+    };
+
+    let path = file_path(app, hash).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let bytes = tokio::fs::read(&path).await.map_err(|e| {
+        log::error!("Attachment {} is indexed but unreadable: {}", hash, e);
+        StatusCode::NOT_FOUND
+    })?;
+    Ok((bytes, mime))
+}
+
+pub fn max_file_bytes(app: &AppHandle) -> u64 {
+    app.state::<AttachmentState>().0.lock().unwrap().max_file_bytes
+}
+
+// Where a chat request can refer to an upload, and whether the spot takes a
+// data URL (`image_url`) rather than bare base64.
+fn reference_slots(request: &mut Value) -> Vec<(&mut Value, bool)> {
+    let mut slots = Vec::new();
+    for (key, value) in request.as_object_mut().into_iter().flatten() {
+        match (key.as_str(), value) {
+            ("images", Value::Array(images)) => slots.extend(images.iter_mut().map(|image| (image, false))),
+            ("messages", Value::Array(messages)) => {
+                for message in messages.iter_mut().filter_map(Value::as_object_mut) {
+                    message_slots(message, &mut slots);
+                }
+            }
+            _ => {}
+        }
+    }
+    slots
+}
+
+fn message_slots<'a>(message: &'a mut serde_json::Map<String, Value>, slots: &mut Vec<(&'a mut Value, bool)>) {
+    for (key, value) in message.iter_mut() {
+        match (key.as_str(), value) {
+            ("images", Value::Array(images)) => slots.extend(images.iter_mut().map(|image| (image, false))),
+            ("content", Value::Array(parts)) => {
+                for part in parts.iter_mut().filter_map(Value::as_object_mut) {
+                    for (key, value) in part.iter_mut() {
+                        match (key.as_str(), value) {
+                            ("image_url", Value::Object(image_url)) => {
+                                slots.extend(image_url.get_mut("url").map(|url| (url, true)));
+                            }
+                            ("image_url", url) if url.is_string() => slots.push((url, true)),
+                            ("input_audio", Value::Object(audio)) => {
+                                slots.extend(audio.get_mut("data").map(|data| (data, false)));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn reference(value: &Value) -> Option<&str> {
+    value.as_str()?.strip_prefix(REFERENCE_PREFIX)
+}
+
+// The request with its `attachment:<hash>` references replaced by the
+// files' contents; None when it has none.
+pub async fn inline_request(app: &AppHandle, body: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let marker = REFERENCE_PREFIX.as_bytes();
+    if !body.windows(marker.len()).any(|w| w == marker) {
+        return Ok(None);
+    }
+    let Ok(mut request) = serde_json::from_slice::<Value>(body) else {
+        return Ok(None);
+    };
+    let hashes: Vec<String> = reference_slots(&mut request)
+        .into_iter()
+        .filter_map(|(slot, _)| reference(slot).map(str::to_string))
+        .collect();
+    if hashes.is_empty() {
+        return Ok(None);
+    }
+
+    let mut files: HashMap<String, (String, String)> = HashMap::new();
+    for hash in hashes {
+        if files.contains_key(&hash) {
+            continue;
+        }
+        if !is_hash(&hash) {
+            return Err(format!("'{}{}' isn't an attachment reference", REFERENCE_PREFIX, hash));
+        }
+        let mime: Option<String> = {
+            let db = app.state::<HistoryDb>();
+            let conn = db.0.lock().unwrap();
+            conn.query_row("SELECT mime FROM attachments WHERE hash = ?1", params![hash], |row| row.get(0))
+                .optional()
+                .map_err(|e| e.to_string())?
+        };
+        let mime = mime.ok_or_else(|| format!("No attachment {}", hash))?;
+        let path = file_path(app, &hash)?;
+        let bytes = tokio::fs::read(&path)
+            .await
+            .map_err(|e| format!("Attachment {} is unreadable: {}", hash, e))?;
+        files.insert(hash, (mime, general_purpose::STANDARD.encode(bytes)));
+    }
+
+    for (slot, data_url) in reference_slots(&mut request) {
+        let Some((mime, encoded)) = reference(slot).and_then(|hash| files.get(hash)) else {
+            continue;
+        };
+        *slot = Value::String(if data_url {
+            format!("data:{};base64,{}", mime, encoded)
+        } else {
+            encoded.clone()
+        });
+    }
+    serde_json::to_vec(&request).map(Some).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn store_attachment(
+    app: AppHandle,
+    data: String,
+    mime: String,
+    conversation_id: Option<i64>,
+) -> Result<Attachment, String> {
+    // Accept data URLs as well as bare base64.
+    let encoded = data.split_once(',').map_or(data.as_str(), |(_, rest)| rest);
+    let bytes = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Invalid base64 attachment: {}", e))?;
+    store(&app, &bytes, &mime, conversation_id)
+}
+
+#[tauri::command]
+pub fn store_attachment_file(
+    app: AppHandle,
+    path: String,
+    mime: Option<String>,
+    conversation_id: Option<i64>,
+) -> Result<Attachment, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mime = mime.unwrap_or_else(|| "application/octet-stream".to_string());
+    store(&app, &bytes, &mime, conversation_id)
+}
+
+#[tauri::command]
+pub fn get_attachment_usage(
+    db: State<'_, HistoryDb>,
+    state: State<'_, AttachmentState>,
+) -> Result<AttachmentUsage, String> {
+    let conn = db.0.lock().unwrap();
+    let files = conn
+        .query_row("SELECT COUNT(*) FROM attachments", [], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?;
+    Ok(AttachmentUsage {
+        files: files as u64,
+        total_bytes: total_bytes(&conn)?,
+        max_total_bytes: state.0.lock().unwrap().max_total_bytes,
+    })
+}
+
+#[tauri::command]
+pub fn collect_attachment_garbage(app: AppHandle, db: State<'_, HistoryDb>) -> Result<u64, String> {
+    collect_garbage(&app, &db.0.lock().unwrap())
+}
+
+#[tauri::command]
+pub fn get_attachment_settings(state: State<'_, AttachmentState>) -> AttachmentSettings {
+    state.0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_attachment_settings(
+    app: AppHandle,
+    settings: AttachmentSettings,
+    state: State<'_, AttachmentState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.0.lock().unwrap() = settings;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+// In src-tauri/src/audit.rs
+//
+// Append-only audit log of security-relevant actions (approved or denied
+// shell commands, ...). One JSON object per line in `audit.jsonl` so it can
+// be inspected with any text tool and is never rewritten by the app.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::{request_id, storage};
+
+const AUDIT_FILE: &str = "audit.jsonl";
+const DEFAULT_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub agent_id: Option<String>,
+    // e.g. "shell.approved", "shell.denied"
+    pub action: String,
+    pub detail: serde_json::Value,
+    // The server request that led to this, see request_id.rs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+// Serializes appends from concurrent tasks.
+#[derive(Default)]
+pub struct AuditLog(Mutex<()>);
+
+pub fn record(app: &AppHandle, agent_id: Option<&str>, action: &str, detail: serde_json::Value) {
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        agent_id: agent_id.map(str::to_string),
+        action: action.to_string(),
+        detail,
+        request_id: request_id::current(),
+    };
+    if let Err(e) = append(app, &entry) {
+        log::error!("Failed to write audit entry '{}': {}", action, e);
+    }
+}
+
+fn append(app: &AppHandle, entry: &AuditEntry) -> Result<(), String> {
+    let path = storage::data_path(app, AUDIT_FILE)?;
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+
+    let state = app.state::<AuditLog>();
+    let _guard = state.0.lock().unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+// Newest first.
+#[tauri::command]
+pub fn get_audit_log(app: AppHandle, limit: Option<usize>) -> Result<Vec<AuditEntry>, String> {
+    let path = storage::data_path(&app, AUDIT_FILE)?;
+    let file = match std::fs::File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to open {:?}: {}", path, e)),
+    };
+    let mut entries: Vec<AuditEntry> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(entries)
+}
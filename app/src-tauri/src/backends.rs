@@ -0,0 +1,290 @@
+// In src-tauri/src/backends.rs
+//
+// Automatic backend selection. The user lists the Ollama backends they use
+// (the desktop GPU at home, localhost, ...) in order of preference; whenever
+// the machine wakes up, moves to another network or loses its backend (see
+// wake.rs and offline.rs), every backend is probed and the most preferred one
+// that answers becomes the Ollama URL, so agents stop timing out against a
+// backend that's out of reach. Each switch is logged and emitted as
+// "backend-selected". A managed policy that pins the Ollama server turns the
+// selection off.
+//
+// The backends are also measured continuously: every `MEASURE_INTERVAL` each
+// one is probed for its round-trip latency, and every proxied answer reports
+// the backend's generation speed (see usage.rs). Both are smoothed averages.
+// With the `fastest` routing policy the proxy picks a backend per request
+// from those numbers instead of always using the Ollama URL: interactive
+// requests (the ones without an agent) go to the reachable backend with the
+// lowest latency, agents to the one generating the most tokens per second.
+
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast;
+
+use crate::wake::{self, SystemChange};
+use crate::{llm, policy, storage, AppSettings};
+
+const SETTINGS_FILE: &str = "backends.json";
+const PROBE_TIMEOUT: Duration = Duration::from_millis(2500);
+const MEASURE_INTERVAL: Duration = Duration::from_secs(30);
+// Weight of a new measurement in the running averages.
+const SMOOTHING: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingPolicy {
+    // Everything goes to the Ollama URL, the preferred reachable backend.
+    #[default]
+    Preference,
+    // Lowest latency for interactive requests, highest throughput for agents.
+    Fastest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendProfile {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendSettings {
+    pub auto_select: bool,
+    pub routing: RoutingPolicy,
+    // Most preferred first. Empty leaves the Ollama URL to the user.
+    pub backends: Vec<BackendProfile>,
+}
+
+impl Default for BackendSettings {
+    fn default() -> Self {
+        Self { auto_select: true, routing: RoutingPolicy::default(), backends: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackendStats {
+    pub reachable: bool,
+    pub latency_ms: Option<f64>,
+    pub tokens_per_second: Option<f64>,
+    pub measured_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+pub struct BackendState {
+    settings: Mutex<BackendSettings>,
+    // Keyed by the backend's URL without a trailing slash.
+    stats: Mutex<HashMap<String, BackendStats>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendProbe {
+    pub name: String,
+    pub url: String,
+    pub reachable: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendSelection {
+    // None when no backend answered; the Ollama URL is then left alone.
+    pub selected: Option<BackendProfile>,
+    pub changed: bool,
+    pub probes: Vec<BackendProbe>,
+}
+
+fn key(url: &str) -> &str {
+    url.trim_end_matches('/')
+}
+
+fn smooth(average: Option<f64>, sample: f64) -> f64 {
+    match average {
+        Some(average) => average + SMOOTHING * (sample - average),
+        None => sample,
+    }
+}
+
+// Probes `url` and records the outcome; true if it answered.
+async fn reachable(app: &AppHandle, url: &str) -> bool {
+    let check_url = format!("{}/api/version", key(url));
+    let started = Instant::now();
+    let answered = match llm::client().get(&check_url).timeout(PROBE_TIMEOUT).send().await {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    };
+    let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let state = app.state::<BackendState>();
+    let mut stats = state.stats.lock().unwrap();
+    let entry = stats.entry(key(url).to_string()).or_default();
+    entry.reachable = answered;
+    if answered {
+        entry.latency_ms = Some(smooth(entry.latency_ms, elapsed_ms));
+    }
+    entry.measured_at = Some(Utc::now());
+    answered
+}
+
+async fn measure_all(app: &AppHandle) {
+    let backends = app.state::<BackendState>().settings.lock().unwrap().backends.clone();
+    join_all(backends.iter().map(|backend| reachable(app, &backend.url))).await;
+}
+
+// Called by the usage meter after each proxied answer.
+pub fn record_throughput(app: &AppHandle, url: &str, completion_tokens: u64, duration_ms: u64) {
+    if completion_tokens == 0 || duration_ms == 0 {
+        return;
+    }
+    let tokens_per_second = completion_tokens as f64 * 1000.0 / duration_ms as f64;
+    let state = app.state::<BackendState>();
+    let mut stats = state.stats.lock().unwrap();
+    let entry = stats.entry(key(url).to_string()).or_default();
+    entry.tokens_per_second = Some(smooth(entry.tokens_per_second, tokens_per_second));
+}
+
+// The backend a proxied request goes to: the Ollama URL, or under the
+// `fastest` policy the best measured backend for the kind of request.
+pub fn route(app: &AppHandle, from_agent: bool) -> String {
+    let base_url = llm::ollama_base_url(app);
+    let state = app.state::<BackendState>();
+    let settings = state.settings.lock().unwrap().clone();
+    if settings.routing != RoutingPolicy::Fastest || policy::pinned_ollama_url().is_some() {
+        return base_url;
+    }
+    let stats = state.stats.lock().unwrap();
+    let candidates: Vec<(&BackendProfile, &BackendStats)> = settings
+        .backends
+        .iter()
+        .filter(|backend| policy::check_backend(&backend.url).is_ok())
+        .filter_map(|backend| stats.get(key(&backend.url)).map(|stats| (backend, stats)))
+        .filter(|(_, stats)| stats.reachable)
+        .collect();
+    let fastest = candidates
+        .iter()
+        .filter(|(_, stats)| stats.latency_ms.is_some())
+        .min_by(|a, b| a.1.latency_ms.unwrap_or(f64::MAX).total_cmp(&b.1.latency_ms.unwrap_or(f64::MAX)));
+    let busiest = candidates
+        .iter()
+        .filter(|(_, stats)| stats.tokens_per_second.is_some())
+        .max_by(|a, b| a.1.tokens_per_second.unwrap_or(0.0).total_cmp(&b.1.tokens_per_second.unwrap_or(0.0)));
+    // Agents fall back to latency until some backend has served them.
+    let chosen = if from_agent { busiest.or(fastest) } else { fastest };
+    chosen.map(|(backend, _)| key(&backend.url).to_string()).unwrap_or(base_url)
+}
+
+// Probes every backend at once and switches to the most preferred one that
+// answers.
+pub async fn select(app: &AppHandle) -> Result<BackendSelection, String> {
+    if let Some(pinned) = policy::pinned_ollama_url() {
+        return Err(format!("Your organisation's policy pins the Ollama server to {}", pinned));
+    }
+    let backends: Vec<BackendProfile> = app
+        .state::<BackendState>()
+        .settings
+        .lock()
+        .unwrap()
+        .backends
+        .iter()
+        .filter(|backend| policy::check_backend(&backend.url).is_ok())
+        .cloned()
+        .collect();
+    let results = join_all(backends.iter().map(|backend| reachable(app, &backend.url))).await;
+    let probes: Vec<BackendProbe> = backends
+        .iter()
+        .zip(results)
+        .map(|(backend, reachable)| BackendProbe { name: backend.name.clone(), url: backend.url.clone(), reachable })
+        .collect();
+
+    let selected = backends.into_iter().zip(&probes).find(|(_, probe)| probe.reachable).map(|(backend, _)| backend);
+    let mut changed = false;
+    if let Some(backend) = &selected {
+        let current = llm::ollama_base_url(app);
+        if key(&current) != key(&backend.url) {
+            log::info!("Switching the Ollama backend from {} to {} ({})", current, backend.url, backend.name);
+            *app.state::<AppSettings>().ollama_url.lock().unwrap() = Some(backend.url.clone());
+            changed = true;
+        }
+    } else {
+        log::warn!("None of the configured Ollama backends answered");
+    }
+
+    let selection = BackendSelection { selected, changed, probes };
+    if changed {
+        if let Err(e) = app.emit("backend-selected", &selection) {
+            log::error!("Failed to emit backend-selected event: {}", e);
+        }
+    }
+    Ok(selection)
+}
+
+async fn reselect(app: &AppHandle, reason: &str) {
+    let settings = app.state::<BackendState>().settings.lock().unwrap().clone();
+    if !settings.auto_select || settings.backends.is_empty() || policy::pinned_ollama_url().is_some() {
+        return;
+    }
+    log::info!("Re-selecting the Ollama backend after {}", reason);
+    if let Err(e) = select(app).await {
+        log::error!("Backend selection failed: {}", e);
+    }
+}
+
+pub fn start_monitor(app: AppHandle) {
+    *app.state::<BackendState>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    let measuring = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            measure_all(&measuring).await;
+            tokio::time::sleep(MEASURE_INTERVAL).await;
+        }
+    });
+
+    tauri::async_runtime::spawn(async move {
+        let mut changes = wake::subscribe(&app);
+        reselect(&app, "startup").await;
+        loop {
+            let event = match changes.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            match event.change {
+                SystemChange::Resumed { .. } => reselect(&app, "waking up").await,
+                SystemChange::NetworkChanged { .. } => reselect(&app, "a network change").await,
+                SystemChange::BackendOffline { .. } => reselect(&app, "losing the backend").await,
+                SystemChange::BackendOnline { .. } | SystemChange::SoundDetected { .. } => {}
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_backend_settings(state: State<'_, BackendState>) -> BackendSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_backend_settings(
+    app: AppHandle,
+    settings: BackendSettings,
+    state: State<'_, BackendState>,
+) -> Result<(), String> {
+    for backend in &settings.backends {
+        policy::check_backend(&backend.url)?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_backend_stats(state: State<'_, BackendState>) -> HashMap<String, BackendStats> {
+    state.stats.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub async fn select_backend(app: AppHandle) -> Result<BackendSelection, String> {
+    select(&app).await
+}
@@ -0,0 +1,516 @@
+// In src-tauri/src/backup.rs
+//
+// Full backups to an external drive: the history database, agents and
+// settings, plus whichever Ollama models are picked, so a new machine (or a
+// reinstalled one) is back to where it was without downloading 40 GB again.
+//
+// `backup_to(path, models)` writes a folder under `path`:
+//
+//   observer-backup-20250101-120000/
+//     manifest.json      every file with its size and sha256
+//     data/              the profile's data directory, history.db included
+//     models/            manifests/ and blobs/ laid out like Ollama's store
+//
+// `restore_from(path)` checks every file against the manifest before it
+// touches anything, so a damaged backup fails instead of half-restoring.
+// The history database is restored into the open connection; settings take
+// effect after a restart. Both report progress as "backup-progress".
+//
+// The same backups, without models, are also taken on a schedule
+// (backup_schedule.json, daily by default) into the data directory's
+// `backups/` folder or one the user picks, keeping the newest `keep`.
+// `list_backups` and `restore_backup` work on those.
+
+use chrono::{DateTime, Local, Utc};
+use rusqlite::DatabaseName;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::history::{self, HistoryDb};
+use crate::model_manager::{self, blob_path, layers, ModelRef};
+use crate::storage;
+
+const SCHEDULE_FILE: &str = "backup_schedule.json";
+const SNAPSHOTS_DIR: &str = "backups";
+const FOLDER_PREFIX: &str = "observer-backup-";
+const SCHEDULE_CHECK: Duration = Duration::from_secs(10 * 60);
+
+const FORMAT: &str = "observer-backup";
+const VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const CHUNK_SIZE: usize = 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupFile {
+    // Relative to the backup folder, always with '/'.
+    path: String,
+    size: u64,
+    sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    version: u32,
+    app_version: String,
+    created_at: DateTime<Utc>,
+    models: Vec<String>,
+    files: Vec<BackupFile>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupPhase {
+    Copying,
+    Verifying,
+    Restoring,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupProgress {
+    // "backup" or "restore".
+    pub operation: &'static str,
+    pub phase: BackupPhase,
+    pub file: String,
+    pub completed: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupReport {
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub created_at: DateTime<Utc>,
+    pub files: usize,
+    pub models: Vec<String>,
+    pub restart_required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSchedule {
+    pub enabled: bool,
+    pub interval_hours: u64,
+    // Older scheduled backups are deleted.
+    pub keep: usize,
+    // Where they go; the data directory's `backups/` when unset.
+    pub dir: Option<String>,
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        Self { enabled: true, interval_hours: 24, keep: 7, dir: None }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSnapshot {
+    // The folder name, which `restore_backup` takes.
+    pub id: String,
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    pub size: u64,
+}
+
+// Throttled "backup-progress" events for one operation.
+struct Progress<'a> {
+    app: &'a AppHandle,
+    operation: &'static str,
+    total: u64,
+    completed: u64,
+    last: Instant,
+}
+
+impl<'a> Progress<'a> {
+    fn new(app: &'a AppHandle, operation: &'static str, total: u64) -> Self {
+        Self { app, operation, total, completed: 0, last: Instant::now() }
+    }
+
+    fn advance(&mut self, phase: BackupPhase, file: &str, bytes: u64) {
+        self.completed += bytes;
+        if self.last.elapsed() >= PROGRESS_INTERVAL || matches!(phase, BackupPhase::Done) {
+            self.last = Instant::now();
+            self.emit(phase, file);
+        }
+    }
+
+    fn emit(&self, phase: BackupPhase, file: &str) {
+        let progress = BackupProgress {
+            operation: self.operation,
+            phase,
+            file: file.to_string(),
+            completed: self.completed.min(self.total),
+            total: self.total,
+        };
+        if let Err(e) = self.app.emit("backup-progress", progress) {
+            log::error!("Failed to emit backup-progress event: {}", e);
+        }
+    }
+}
+
+// Copies `from` to `to` (or only reads it, without `to`), returning its
+// sha256 and reporting each chunk.
+fn copy_hashed(
+    from: &Path,
+    to: Option<&Path>,
+    progress: &mut Progress,
+    phase: BackupPhase,
+    label: &str,
+) -> Result<String, String> {
+    let mut input = std::fs::File::open(from).map_err(|e| format!("Failed to open {:?}: {}", from, e))?;
+    let mut output = match to {
+        Some(to) => {
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+            }
+            Some(std::fs::File::create(to).map_err(|e| format!("Failed to create {:?}: {}", to, e))?)
+        }
+        None => None,
+    };
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let n = input.read(&mut buffer).map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        if let (Some(output), Some(to)) = (output.as_mut(), to) {
+            output.write_all(&buffer[..n]).map_err(|e| format!("Failed to write {:?}: {}", to, e))?;
+        }
+        progress.advance(phase, label, n as u64);
+    }
+    if let (Some(output), Some(to)) = (output, to) {
+        output.sync_all().map_err(|e| format!("Failed to write {:?}: {}", to, e))?;
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// A manifest path, refused if it could point outside the backup.
+fn relative(path: &str) -> Result<PathBuf, String> {
+    let relative = PathBuf::from(path);
+    if path.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("Invalid path '{}' in the backup manifest", path));
+    }
+    Ok(relative)
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+// The top-level files of the data directory except the live database,
+// which is copied through SQLite instead.
+fn data_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| !path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(history::DB_FILE)))
+        .collect();
+    files.sort();
+    files
+}
+
+// (source, path in the backup) for each file of `models`.
+fn model_files(models_dir: &Path, models: &[String]) -> Result<Vec<(PathBuf, String)>, String> {
+    let mut files = Vec::new();
+    for name in models {
+        let model = ModelRef::parse(name)?;
+        let manifest_path = models_dir.join("manifests").join(model.manifest_path());
+        let manifest: Value = std::fs::read(&manifest_path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| format!("No model '{}' in {:?}", name, models_dir))?;
+        let manifest_name: Vec<String> =
+            model.manifest_path().iter().map(|part| part.to_string_lossy().to_string()).collect();
+        files.push((manifest_path, format!("models/manifests/{}", manifest_name.join("/"))));
+        for (digest, _) in layers(&manifest) {
+            let path = blob_path(models_dir, &digest);
+            let entry = format!("models/blobs/{}", digest.replace(':', "-"));
+            if !files.iter().any(|(_, existing)| *existing == entry) {
+                files.push((path, entry));
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn backup(app: &AppHandle, target: &Path, models: &[String]) -> Result<BackupReport, String> {
+    let data_dir = storage::data_dir(app)?;
+    let model_files = match models {
+        [] => Vec::new(),
+        _ => {
+            let dir = model_manager::models_dir(app).ok_or("No Ollama model directory found")?;
+            model_files(&dir, models)?
+        }
+    };
+    let folder = target.join(format!("{}{}", FOLDER_PREFIX, Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::create_dir_all(folder.join("data")).map_err(|e| format!("Failed to create {:?}: {}", folder, e))?;
+
+    // A consistent copy of the database, taken through the open connection.
+    let db_entry = format!("data/{}", history::DB_FILE);
+    let db_path = folder.join(&db_entry);
+    app.state::<HistoryDb>()
+        .0
+        .lock()
+        .unwrap()
+        .backup(DatabaseName::Main, &db_path, None)
+        .map_err(|e| format!("Failed to back up the history database: {}", e))?;
+
+    let mut sources: Vec<(PathBuf, String)> = data_files(&data_dir)
+        .into_iter()
+        .map(|path| {
+            let entry = format!("data/{}", path.file_name().unwrap_or_default().to_string_lossy());
+            (path, entry)
+        })
+        .collect();
+    sources.extend(model_files);
+    let total = sources.iter().map(|(path, _)| file_size(path)).sum::<u64>() + file_size(&db_path);
+    log::info!("Backing up {} files ({} bytes) to {:?}", sources.len() + 1, total, folder);
+
+    let mut progress = Progress::new(app, "backup", total);
+    let mut files = Vec::new();
+    let sha256 = copy_hashed(&db_path, None, &mut progress, BackupPhase::Copying, &db_entry)?;
+    files.push(BackupFile { path: db_entry, size: file_size(&db_path), sha256 });
+    for (source, entry) in sources {
+        let sha256 = copy_hashed(&source, Some(&folder.join(&entry)), &mut progress, BackupPhase::Copying, &entry)?;
+        files.push(BackupFile { size: file_size(&source), path: entry, sha256 });
+    }
+
+    // Written last: a backup without a manifest is an unfinished one.
+    let manifest = Manifest {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        app_version: app.package_info().version.to_string(),
+        created_at: Utc::now(),
+        models: models.to_vec(),
+        files,
+    };
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(folder.join(MANIFEST_FILE), json).map_err(|e| format!("Failed to write the manifest: {}", e))?;
+    progress.advance(BackupPhase::Done, "", 0);
+    Ok(BackupReport {
+        path: folder.to_string_lossy().to_string(),
+        files: manifest.files.len(),
+        bytes: total,
+        models: manifest.models,
+    })
+}
+
+fn restore(app: &AppHandle, folder: &Path) -> Result<RestoreReport, String> {
+    let bytes = std::fs::read(folder.join(MANIFEST_FILE))
+        .map_err(|e| format!("{:?} isn't a finished Observer backup: {}", folder, e))?;
+    let manifest: Manifest = serde_json::from_slice(&bytes).map_err(|e| format!("Bad backup manifest: {}", e))?;
+    if manifest.format != FORMAT {
+        return Err(format!("{:?} isn't an Observer backup", folder));
+    }
+    if manifest.version > VERSION {
+        return Err(format!("This backup is from a newer version of Observer ({})", manifest.app_version));
+    }
+    let total: u64 = manifest.files.iter().map(|file| file.size).sum();
+
+    let mut progress = Progress::new(app, "restore", total);
+    for file in &manifest.files {
+        let path = folder.join(relative(&file.path)?);
+        let sha256 = copy_hashed(&path, None, &mut progress, BackupPhase::Verifying, &file.path)?;
+        if sha256 != file.sha256 || file_size(&path) != file.size {
+            return Err(format!("{} is damaged in this backup; nothing was restored", file.path));
+        }
+    }
+
+    let data_dir = storage::data_dir(app)?;
+    let models_dir = model_manager::models_dir(app);
+    let mut progress = Progress::new(app, "restore", total);
+    for file in &manifest.files {
+        let relative = relative(&file.path)?;
+        let source = folder.join(&relative);
+        let mut parts = relative.iter();
+        let (Some(area), rest) = (parts.next(), parts.as_path()) else {
+            continue;
+        };
+        if file.path == format!("data/{}", history::DB_FILE) {
+            app.state::<HistoryDb>()
+                .0
+                .lock()
+                .unwrap()
+                .restore(DatabaseName::Main, &source, None::<fn(rusqlite::backup::Progress)>)
+                .map_err(|e| format!("Failed to restore the history database: {}", e))?;
+            progress.advance(BackupPhase::Restoring, &file.path, file.size);
+            continue;
+        }
+        let target = match area.to_str() {
+            Some("data") => data_dir.join(rest),
+            Some("models") => models_dir.as_ref().ok_or("No Ollama model directory to restore into")?.join(rest),
+            _ => return Err(format!("Unexpected file {} in the backup", file.path)),
+        };
+        // Blobs are named by their digest, so one that's there is the same.
+        if file.path.starts_with("models/blobs/") && target.exists() {
+            progress.advance(BackupPhase::Restoring, &file.path, file.size);
+            continue;
+        }
+        copy_hashed(&source, Some(&target), &mut progress, BackupPhase::Restoring, &file.path)?;
+    }
+    progress.advance(BackupPhase::Done, "", 0);
+    log::info!("Restored {} files from {:?} (made {})", manifest.files.len(), folder, manifest.created_at);
+    Ok(RestoreReport {
+        created_at: manifest.created_at,
+        files: manifest.files.len(),
+        models: manifest.models,
+        restart_required: true,
+    })
+}
+
+// Backs up the history, agents and settings, plus `models`, to a new folder
+// under `path` (typically an external drive).
+#[tauri::command]
+pub async fn backup_to(app: AppHandle, path: String, models: Option<Vec<String>>) -> Result<BackupReport, String> {
+    let target = PathBuf::from(path);
+    if !target.is_dir() {
+        return Err(format!("{:?} isn't a folder", target));
+    }
+    tokio::task::spawn_blocking(move || backup(&app, &target, &models.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// `path` is a folder `backup_to` made.
+#[tauri::command]
+pub async fn restore_from(app: AppHandle, path: String) -> Result<RestoreReport, String> {
+    let folder = PathBuf::from(path);
+    tokio::task::spawn_blocking(move || restore(&app, &folder))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+pub fn schedule(app: &AppHandle) -> BackupSchedule {
+    storage::load_json(app, SCHEDULE_FILE)
+}
+
+fn snapshots_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match schedule(app).dir.filter(|dir| !dir.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(storage::data_dir(app)?.join(SNAPSHOTS_DIR)),
+    }
+}
+
+// Finished backups in the snapshot folder, newest first.
+pub fn list_snapshots(app: &AppHandle) -> Result<Vec<BackupSnapshot>, String> {
+    let dir = snapshots_dir(app)?;
+    let mut snapshots: Vec<BackupSnapshot> = std::fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(FOLDER_PREFIX))
+        .filter_map(|entry| {
+            let bytes = std::fs::read(entry.path().join(MANIFEST_FILE)).ok()?;
+            let manifest: Manifest = serde_json::from_slice(&bytes).ok()?;
+            Some(BackupSnapshot {
+                id: entry.file_name().to_string_lossy().to_string(),
+                path: entry.path().to_string_lossy().to_string(),
+                created_at: manifest.created_at,
+                size: manifest.files.iter().map(|file| file.size).sum(),
+            })
+        })
+        .collect();
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(snapshots)
+}
+
+// `data/<name>` from a snapshot, if it's there and still matches its checksum.
+pub fn verified_data_file(snapshot: &BackupSnapshot, name: &str) -> Option<PathBuf> {
+    let folder = PathBuf::from(&snapshot.path);
+    let manifest: Manifest = serde_json::from_slice(&std::fs::read(folder.join(MANIFEST_FILE)).ok()?).ok()?;
+    let entry = format!("data/{}", name);
+    let file = manifest.files.iter().find(|file| file.path == entry)?;
+    let path = folder.join(relative(&entry).ok()?);
+    let mut input = std::fs::File::open(&path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut input, &mut hasher).ok()?;
+    let sha256: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    (sha256 == file.sha256).then_some(path)
+}
+
+// Takes a scheduled-style backup now and drops the ones past `keep`.
+fn snapshot(app: &AppHandle) -> Result<BackupReport, String> {
+    let dir = snapshots_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let report = backup(app, &dir, &[])?;
+    let keep = schedule(app).keep.max(1);
+    for old in list_snapshots(app)?.into_iter().skip(keep) {
+        log::info!("Removing old backup {}", old.id);
+        if let Err(e) = std::fs::remove_dir_all(&old.path) {
+            log::warn!("Failed to remove old backup {:?}: {}", old.path, e);
+        }
+    }
+    Ok(report)
+}
+
+pub fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let schedule = schedule(&app);
+            let interval = chrono::Duration::hours(schedule.interval_hours.max(1) as i64);
+            let latest = list_snapshots(&app).ok().and_then(|snapshots| snapshots.first().map(|s| s.created_at));
+            if schedule.enabled && !latest.is_some_and(|at| Utc::now() - at < interval) {
+                let handle = app.clone();
+                match tokio::task::spawn_blocking(move || snapshot(&handle)).await {
+                    Ok(Ok(report)) => log::info!("Scheduled backup written to {}", report.path),
+                    Ok(Err(e)) => log::error!("Scheduled backup failed: {}", e),
+                    Err(e) => log::error!("Scheduled backup failed: {}", e),
+                }
+            }
+            tokio::time::sleep(SCHEDULE_CHECK).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_backup_schedule(app: AppHandle) -> BackupSchedule {
+    schedule(&app)
+}
+
+#[tauri::command]
+pub fn set_backup_schedule(app: AppHandle, schedule: BackupSchedule) -> Result<(), String> {
+    if schedule.interval_hours == 0 || schedule.keep == 0 {
+        return Err("Backups need an interval and at least one to keep".to_string());
+    }
+    storage::save_json(&app, SCHEDULE_FILE, &schedule)
+}
+
+#[tauri::command]
+pub fn list_backups(app: AppHandle) -> Result<Vec<BackupSnapshot>, String> {
+    list_snapshots(&app)
+}
+
+#[tauri::command]
+pub async fn backup_now(app: AppHandle) -> Result<BackupReport, String> {
+    tokio::task::spawn_blocking(move || snapshot(&app)).await.map_err(|e| e.to_string())?
+}
+
+// `id` is one from `list_backups`.
+#[tauri::command]
+pub async fn restore_backup(app: AppHandle, id: String) -> Result<RestoreReport, String> {
+    if !id.starts_with(FOLDER_PREFIX) || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("No backup '{}'", id));
+    }
+    let folder = snapshots_dir(&app)?.join(&id);
+    tokio::task::spawn_blocking(move || restore(&app, &folder))
+        .await
+        .map_err(|e| e.to_string())?
+}
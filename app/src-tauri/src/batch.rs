@@ -0,0 +1,296 @@
+// In src-tauri/src/batch.rs
+//
+// Offline batch inference for bulk classification/summarization jobs. A job
+// is a list of prompts (inline, or read from a CSV/JSONL file) run through one
+// model with bounded concurrency. Results are written as JSONL, one line per
+// prompt in input order, and progress is reported with "batch-progress"
+// events. Jobs are started with `run_batch` or `POST /batch`, and run again
+// from the start if the app stops before they finish (see jobs.rs).
+//
+// Any web page can reach `POST /batch`, so it only takes inline prompts and
+// always writes under the data directory; files are for `run_batch`. Each
+// prompt goes through the proxy pipeline (`llm::proxied`), so the kill
+// switch and policy hold, and with an `agent_id` that agent's token budget.
+
+use axum::{extract::State as AxumState, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::llm::{self, ChatMessage};
+use crate::{jobs, storage, AppState};
+
+const DEFAULT_CONCURRENCY: usize = 2;
+const MAX_CONCURRENCY: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchJob {
+    pub model: String,
+    #[serde(default)]
+    pub system: Option<String>,
+    #[serde(default)]
+    pub prompts: Vec<String>,
+    // A .csv or .jsonl file to read prompts from instead of `prompts`.
+    #[serde(default)]
+    pub input_path: Option<String>,
+    // CSV column / JSONL field holding the prompt. Defaults to "prompt", or
+    // the first column of a CSV without one.
+    #[serde(default)]
+    pub prompt_field: Option<String>,
+    #[serde(default)]
+    pub output_path: Option<String>,
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    // The agent the prompts are sent for, whose budget they count against.
+    #[serde(default)]
+    pub agent_id: Option<String>,
+}
+
+// A job as `POST /batch` takes it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRequest {
+    pub model: String,
+    #[serde(default)]
+    pub system: Option<String>,
+    pub prompts: Vec<String>,
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+}
+
+impl From<BatchRequest> for BatchJob {
+    fn from(request: BatchRequest) -> Self {
+        BatchJob {
+            model: request.model,
+            system: request.system,
+            prompts: request.prompts,
+            input_path: None,
+            prompt_field: None,
+            output_path: None,
+            concurrency: request.concurrency,
+            agent_id: request.agent_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchState {
+    Running,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchStatus {
+    pub job_id: String,
+    pub model: String,
+    pub state: BatchState,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub output_path: PathBuf,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+pub struct BatchJobs(Mutex<BTreeMap<String, BatchStatus>>);
+
+impl BatchJobs {
+    fn update(&self, job_id: &str, f: impl FnOnce(&mut BatchStatus)) -> Option<BatchStatus> {
+        let mut jobs = self.0.lock().unwrap();
+        let status = jobs.get_mut(job_id)?;
+        f(status);
+        Some(status.clone())
+    }
+}
+
+fn read_prompts(path: &Path, field: Option<&str>) -> Result<Vec<String>, String> {
+    let field = field.unwrap_or("prompt");
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+
+    if extension == "csv" {
+        let mut reader = csv::Reader::from_path(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+        let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+        let column = headers.iter().position(|h| h == field).unwrap_or(0);
+        return reader
+            .records()
+            .map(|record| {
+                record
+                    .map(|r| r.get(column).unwrap_or("").to_string())
+                    .map_err(|e| format!("Invalid CSV in {:?}: {}", path, e))
+            })
+            .collect();
+    }
+
+    // JSONL: each line is a string or an object with the prompt field.
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let value: Value =
+                serde_json::from_str(line).map_err(|e| format!("Invalid JSON on line {}: {}", i + 1, e))?;
+            match &value {
+                Value::String(prompt) => Ok(prompt.clone()),
+                _ => value[field]
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("Line {} has no '{}' field", i + 1, field)),
+            }
+        })
+        .collect()
+}
+
+fn emit_progress(app: &AppHandle, status: &BatchStatus) {
+    if let Err(e) = app.emit("batch-progress", status) {
+        log::error!("Failed to emit batch-progress event: {}", e);
+    }
+}
+
+async fn run_job(app: AppHandle, job_id: String, job: BatchJob, prompts: Vec<String>, output_path: PathBuf) {
+    let concurrency = job.concurrency.unwrap_or(DEFAULT_CONCURRENCY).clamp(1, MAX_CONCURRENCY);
+    let system = job.system.clone().filter(|s| !s.trim().is_empty());
+
+    let mut results: Vec<(usize, Value)> = futures::stream::iter(prompts.into_iter().enumerate())
+        .map(|(index, prompt)| {
+            let app = app.clone();
+            let model = job.model.clone();
+            let system = system.clone();
+            let agent_id = job.agent_id.clone();
+            async move {
+                let mut messages = Vec::new();
+                if let Some(system) = system {
+                    messages.push(ChatMessage::new("system", system));
+                }
+                messages.push(ChatMessage::new("user", prompt.clone()));
+                let request = json!({ "model": model, "messages": messages, "stream": false });
+                let result = llm::proxied(&app, "/api/chat", agent_id.as_deref(), &request)
+                    .await
+                    .map(|answer| answer["message"]["content"].as_str().unwrap_or_default().to_string());
+                (index, prompt, result)
+            }
+        })
+        .buffer_unordered(concurrency)
+        .map(|(index, prompt, result)| {
+            let ok = result.is_ok();
+            if let Some(status) = app.state::<BatchJobs>().update(&job_id, |s| {
+                if ok {
+                    s.completed += 1;
+                } else {
+                    s.failed += 1;
+                }
+            }) {
+                emit_progress(&app, &status);
+            }
+            let line = match result {
+                Ok(response) => json!({ "index": index, "prompt": prompt, "response": response }),
+                Err(e) => json!({ "index": index, "prompt": prompt, "error": e }),
+            };
+            (index, line)
+        })
+        .collect()
+        .await;
+
+    results.sort_by_key(|(index, _)| *index);
+    let output: String = results.iter().map(|(_, line)| format!("{}\n", line)).collect();
+    let write_result = std::fs::write(&output_path, output)
+        .map_err(|e| format!("Failed to write results to {:?}: {}", output_path, e));
+    jobs::complete(&app, &job_id);
+
+    if let Some(status) = app.state::<BatchJobs>().update(&job_id, |s| {
+        s.finished_at = Some(Utc::now());
+        match &write_result {
+            Ok(()) => s.state = BatchState::Finished,
+            Err(e) => {
+                s.state = BatchState::Failed;
+                s.error = Some(e.clone());
+            }
+        }
+    }) {
+        log::info!(
+            "Batch {} done: {} ok, {} failed, written to {:?}",
+            job_id,
+            status.completed,
+            status.failed,
+            status.output_path
+        );
+        emit_progress(&app, &status);
+    }
+}
+
+pub fn start(app: &AppHandle, job: BatchJob) -> Result<BatchStatus, String> {
+    start_as(app, uuid::Uuid::new_v4().to_string(), job)
+}
+
+// Runs a job interrupted by a restart again under its old id.
+pub fn resume(app: &AppHandle, job_id: &str, job: BatchJob) -> Result<(), String> {
+    start_as(app, job_id.to_string(), job).map(|_| ())
+}
+
+fn start_as(app: &AppHandle, job_id: String, job: BatchJob) -> Result<BatchStatus, String> {
+    let prompts = match &job.input_path {
+        Some(path) => read_prompts(Path::new(path), job.prompt_field.as_deref())?,
+        None => job.prompts.clone(),
+    };
+    if prompts.is_empty() {
+        return Err("Batch job has no prompts".to_string());
+    }
+
+    let output_path = match &job.output_path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let dir = storage::data_path(app, "batches")?;
+            std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+            dir.join(format!("{}.jsonl", job_id))
+        }
+    };
+
+    let status = BatchStatus {
+        job_id: job_id.clone(),
+        model: job.model.clone(),
+        state: BatchState::Running,
+        total: prompts.len(),
+        completed: 0,
+        failed: 0,
+        output_path: output_path.clone(),
+        error: None,
+        started_at: Utc::now(),
+        finished_at: None,
+    };
+    app.state::<BatchJobs>().0.lock().unwrap().insert(job_id.clone(), status.clone());
+    jobs::persist(app, jobs::KIND_BATCH, &job_id, &job);
+
+    log::info!("Starting batch {} with {} prompts on {}", job_id, prompts.len(), job.model);
+    tauri::async_runtime::spawn(run_job(app.clone(), job_id, job, prompts, output_path));
+    Ok(status)
+}
+
+pub async fn batch_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Json<BatchStatus>, (StatusCode, String)> {
+    start(&state.app_handle, request.into()).map(Json).map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+pub async fn batch_list_handler(AxumState(state): AxumState<AppState>) -> Json<Vec<BatchStatus>> {
+    Json(state.app_handle.state::<BatchJobs>().0.lock().unwrap().values().cloned().collect())
+}
+
+#[tauri::command]
+pub fn run_batch(app: AppHandle, job: BatchJob) -> Result<BatchStatus, String> {
+    start(&app, job)
+}
+
+#[tauri::command]
+pub fn list_batches(jobs: State<'_, BatchJobs>) -> Vec<BatchStatus> {
+    jobs.0.lock().unwrap().values().cloned().collect()
+}
@@ -0,0 +1,252 @@
+// In src-tauri/src/browser_bridge.rs
+//
+// Connector for the companion browser extension. The extension opens a
+// WebSocket to `/browser/ws` and must authenticate before anything else:
+//   - first time: `{"type": "pair", "code": "123456", "name": "Firefox"}` with
+//     the code shown in the app after `start_browser_pairing`; the reply
+//     carries a token the extension keeps,
+//   - afterwards: `{"type": "hello", "token": "..."}`.
+// Paired extensions then send `{"type": "tab", "url", "title", "selection"}`
+// whenever the active tab or selection changes, and receive whatever agents
+// push with `push_to_browser` (e.g. a page summary to show in the popup).
+
+use axum::{
+    extract::{
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+        State as AxumState,
+    },
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast;
+
+use crate::{storage, AppState};
+
+const PAIRED_FILE: &str = "browser_pairings.json";
+const PAIRING_CODE_TTL: Duration = Duration::from_secs(300);
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedBrowser {
+    pub id: String,
+    pub name: String,
+    // Blanked (and so skipped) in everything sent to the frontend.
+    #[serde(skip_serializing_if = "String::is_empty", default)]
+    token: String,
+    pub paired_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BrowserContext {
+    pub url: String,
+    pub title: String,
+    #[serde(default)]
+    pub selection: String,
+    #[serde(default)]
+    pub browser: String,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+pub struct BrowserBridge {
+    paired: Mutex<Vec<PairedBrowser>>,
+    pairing_code: Mutex<Option<(String, Instant)>>,
+    context: Mutex<Option<BrowserContext>>,
+    connected: Mutex<Vec<String>>,
+    outbound: broadcast::Sender<Value>,
+}
+
+impl Default for BrowserBridge {
+    fn default() -> Self {
+        Self {
+            paired: Mutex::default(),
+            pairing_code: Mutex::default(),
+            context: Mutex::default(),
+            connected: Mutex::default(),
+            outbound: broadcast::channel(32).0,
+        }
+    }
+}
+
+impl BrowserBridge {
+    pub fn context(&self) -> Option<BrowserContext> {
+        self.context.lock().unwrap().clone()
+    }
+
+    pub fn connected_browsers(&self) -> Vec<String> {
+        self.connected.lock().unwrap().clone()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Incoming {
+    Pair { code: String, name: String },
+    Hello { token: String },
+    Tab(BrowserContext),
+}
+
+fn persist(app: &AppHandle, bridge: &BrowserBridge) -> Result<(), String> {
+    let paired = bridge.paired.lock().unwrap().clone();
+    storage::save_json(app, PAIRED_FILE, &paired)
+}
+
+// Checks the first message of a connection. Returns the browser's name.
+fn authenticate(app: &AppHandle, message: Incoming) -> Result<(String, Option<String>), String> {
+    let bridge = app.state::<BrowserBridge>();
+    match message {
+        Incoming::Hello { token } => {
+            let paired = bridge.paired.lock().unwrap();
+            let browser = paired.iter().find(|p| !token.is_empty() && p.token == token);
+            browser
+                .map(|p| (p.name.clone(), None))
+                .ok_or_else(|| "Unknown token, pair the extension again".to_string())
+        }
+        Incoming::Pair { code, name } => {
+            let valid = matches!(
+                bridge.pairing_code.lock().unwrap().take(),
+                Some((expected, created)) if expected == code && created.elapsed() < PAIRING_CODE_TTL
+            );
+            if !valid {
+                return Err("Invalid or expired pairing code".to_string());
+            }
+            let token = uuid::Uuid::new_v4().to_string();
+            bridge.paired.lock().unwrap().push(PairedBrowser {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: name.clone(),
+                token: token.clone(),
+                paired_at: Utc::now(),
+            });
+            persist(app, &bridge)?;
+            log::info!("Paired browser extension '{}'", name);
+            if let Err(e) = app.emit("browser-paired", &name) {
+                log::error!("Failed to emit browser-paired event: {}", e);
+            }
+            Ok((name, Some(token)))
+        }
+        Incoming::Tab(_) => Err("Authenticate before sending tab updates".to_string()),
+    }
+}
+
+async fn handle_socket(app: AppHandle, socket: WebSocket) {
+    let (mut sender, mut receiver) = socket.split();
+
+    let first = match tokio::time::timeout(AUTH_TIMEOUT, receiver.next()).await {
+        Ok(Some(Ok(WsMessage::Text(text)))) => serde_json::from_str::<Incoming>(&text).map_err(|e| e.to_string()),
+        _ => Err("Expected a pair or hello message".to_string()),
+    };
+    let name = match first.and_then(|message| authenticate(&app, message)) {
+        Ok((name, token)) => {
+            let reply = json!({ "type": "paired", "token": token });
+            if sender.send(WsMessage::Text(reply.to_string())).await.is_err() {
+                return;
+            }
+            name
+        }
+        Err(e) => {
+            log::warn!("Rejected browser extension connection: {}", e);
+            let _ = sender.send(WsMessage::Text(json!({ "type": "error", "message": e }).to_string())).await;
+            return;
+        }
+    };
+
+    let bridge = app.state::<BrowserBridge>();
+    bridge.connected.lock().unwrap().push(name.clone());
+    let mut outbound = bridge.outbound.subscribe();
+
+    loop {
+        tokio::select! {
+            incoming = receiver.next() => match incoming {
+                Some(Ok(WsMessage::Text(text))) => match serde_json::from_str::<Incoming>(&text) {
+                    Ok(Incoming::Tab(mut context)) => {
+                        context.browser = name.clone();
+                        context.updated_at = Some(Utc::now());
+                        *bridge.context.lock().unwrap() = Some(context.clone());
+                        if let Err(e) = app.emit("browser-context", context) {
+                            log::error!("Failed to emit browser-context event: {}", e);
+                        }
+                    }
+                    Ok(_) => log::debug!("Ignoring repeated handshake from '{}'", name),
+                    Err(e) => log::warn!("Invalid message from browser '{}': {}", name, e),
+                },
+                Some(Ok(WsMessage::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => {}
+            },
+            outgoing = outbound.recv() => match outgoing {
+                Ok(message) => {
+                    if sender.send(WsMessage::Text(message.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Browser '{}' missed {} messages", name, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+
+    let mut connected = bridge.connected.lock().unwrap();
+    if let Some(index) = connected.iter().position(|n| *n == name) {
+        connected.remove(index);
+    }
+    log::info!("Browser extension '{}' disconnected", name);
+}
+
+pub async fn ws_handler(AxumState(state): AxumState<AppState>, ws: WebSocketUpgrade) -> Response {
+    let app = state.app_handle.clone();
+    ws.on_upgrade(move |socket| handle_socket(app, socket))
+}
+
+pub fn init(app: &AppHandle) {
+    let paired: Vec<PairedBrowser> = storage::load_json(app, PAIRED_FILE);
+    *app.state::<BrowserBridge>().paired.lock().unwrap() = paired;
+}
+
+#[tauri::command]
+pub fn start_browser_pairing(bridge: State<'_, BrowserBridge>) -> String {
+    let code = format!("{:06}", uuid::Uuid::new_v4().as_u128() % 1_000_000);
+    *bridge.pairing_code.lock().unwrap() = Some((code.clone(), Instant::now()));
+    code
+}
+
+#[tauri::command]
+pub fn list_paired_browsers(bridge: State<'_, BrowserBridge>) -> Vec<PairedBrowser> {
+    bridge
+        .paired
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|p| PairedBrowser { token: String::new(), ..p.clone() })
+        .collect()
+}
+
+#[tauri::command]
+pub fn unpair_browser(app: AppHandle, id: String, bridge: State<'_, BrowserBridge>) -> Result<(), String> {
+    bridge.paired.lock().unwrap().retain(|p| p.id != id);
+    persist(&app, &bridge)
+}
+
+#[tauri::command]
+pub fn get_connected_browsers(bridge: State<'_, BrowserBridge>) -> Vec<String> {
+    bridge.connected_browsers()
+}
+
+#[tauri::command]
+pub fn get_browser_context(bridge: State<'_, BrowserBridge>) -> Option<BrowserContext> {
+    bridge.context()
+}
+
+#[tauri::command]
+pub fn push_to_browser(kind: String, content: Value, bridge: State<'_, BrowserBridge>) -> Result<usize, String> {
+    bridge
+        .outbound
+        .send(json!({ "type": kind, "content": content }))
+        .map_err(|_| "No browser extension is connected".to_string())
+}
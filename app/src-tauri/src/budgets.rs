@@ -0,0 +1,182 @@
+// In src-tauri/src/budgets.rs
+//
+// Per-agent daily token budgets, so a misconfigured high-frequency agent
+// can't keep the GPU to itself. Usage is what the proxy metered today (see
+// usage.rs), prompts included. Once an agent has used its budget, its
+// requests are either rejected with a 429 explaining why, or held until
+// local midnight and sent then, as the budget says. Agents without a budget
+// are never limited.
+//
+// The first time an agent runs out on a given day it's audited and emitted
+// as "token-budget-exhausted" for the UI.
+
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    response::Response,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{analytics, audit, storage, usage};
+
+const SETTINGS_FILE: &str = "token_budgets.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhenExhausted {
+    #[default]
+    Reject,
+    // Hold requests until the budget resets at midnight.
+    Queue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenBudget {
+    pub daily_tokens: u64,
+    #[serde(default)]
+    pub when_exhausted: WhenExhausted,
+}
+
+#[derive(Default)]
+pub struct BudgetState {
+    // Agent id -> budget.
+    budgets: Mutex<BTreeMap<String, TokenBudget>>,
+    // Agents already reported as out of budget, and on which day.
+    reported: Mutex<HashSet<(String, NaiveDate)>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub agent_id: String,
+    pub budget: TokenBudget,
+    pub used: u64,
+    pub remaining: u64,
+    pub resets_at: DateTime<Utc>,
+}
+
+fn today_start() -> DateTime<Utc> {
+    analytics::local_midnight(Local::now().date_naive())
+}
+
+fn next_reset() -> DateTime<Utc> {
+    analytics::local_midnight(Local::now().date_naive() + ChronoDuration::days(1))
+}
+
+fn status(app: &AppHandle, agent_id: &str, budget: TokenBudget) -> Result<BudgetStatus, String> {
+    let used = usage::agent_tokens_since(app, agent_id, today_start())?;
+    Ok(BudgetStatus {
+        agent_id: agent_id.to_string(),
+        remaining: budget.daily_tokens.saturating_sub(used),
+        budget,
+        used,
+        resets_at: next_reset(),
+    })
+}
+
+fn report_exhausted(app: &AppHandle, status: &BudgetStatus) {
+    let first_today = app
+        .state::<BudgetState>()
+        .reported
+        .lock()
+        .unwrap()
+        .insert((status.agent_id.clone(), Local::now().date_naive()));
+    if !first_today {
+        return;
+    }
+    log::warn!(
+        "Agent {} used its daily budget of {} tokens ({} used)",
+        status.agent_id,
+        status.budget.daily_tokens,
+        status.used
+    );
+    let detail = serde_json::to_value(status).unwrap_or_default();
+    audit::record(app, Some(&status.agent_id), "budget.exhausted", detail);
+    if let Err(e) = app.emit("token-budget-exhausted", status) {
+        log::error!("Failed to emit token-budget-exhausted event: {}", e);
+    }
+}
+
+fn exhausted_response(status: &BudgetStatus) -> Response {
+    let body = serde_json::json!({
+        "error": format!(
+            "Agent '{}' has used its daily budget of {} tokens; it resets at {}",
+            status.agent_id,
+            status.budget.daily_tokens,
+            status.resets_at.with_timezone(&Local).format("%H:%M")
+        ),
+        "code": "token_budget_exhausted",
+        "used": status.used,
+        "budget": status.budget.daily_tokens,
+        "resets_at": status.resets_at,
+    });
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::RETRY_AFTER, (status.resets_at - Utc::now()).num_seconds().max(1))
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+// Ok when `agent_id` may send a request now, after waiting for the next day
+// if its budget says so; Err with the response to send otherwise.
+pub async fn admit(app: &AppHandle, agent_id: &str) -> Result<(), Response> {
+    loop {
+        let Some(budget) = app.state::<BudgetState>().budgets.lock().unwrap().get(agent_id).cloned() else {
+            return Ok(());
+        };
+        let status = match status(app, agent_id, budget) {
+            Ok(status) => status,
+            Err(e) => {
+                // Not knowing the usage shouldn't stop the agent.
+                log::error!("{}", e);
+                return Ok(());
+            }
+        };
+        if status.remaining > 0 {
+            return Ok(());
+        }
+        report_exhausted(app, &status);
+        if status.budget.when_exhausted == WhenExhausted::Reject {
+            return Err(exhausted_response(&status));
+        }
+        log::info!("Holding a request from agent {} until its budget resets", agent_id);
+        let wait = (status.resets_at - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<BudgetState>().budgets.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_token_budgets(app: AppHandle, state: State<'_, BudgetState>) -> Result<Vec<BudgetStatus>, String> {
+    let budgets = state.budgets.lock().unwrap().clone();
+    budgets.into_iter().map(|(agent_id, budget)| status(&app, &agent_id, budget)).collect()
+}
+
+// `budget` of None removes the agent's budget.
+#[tauri::command]
+pub fn set_token_budget(
+    app: AppHandle,
+    agent_id: String,
+    budget: Option<TokenBudget>,
+    state: State<'_, BudgetState>,
+) -> Result<(), String> {
+    let budgets = {
+        let mut budgets = state.budgets.lock().unwrap();
+        match budget {
+            Some(budget) => budgets.insert(agent_id.clone(), budget),
+            None => budgets.remove(&agent_id),
+        };
+        budgets.clone()
+    };
+    storage::save_json(&app, SETTINGS_FILE, &budgets)?;
+    // A raised budget should be reported again when it runs out.
+    state.reported.lock().unwrap().retain(|(agent, _)| *agent != agent_id);
+    Ok(())
+}
@@ -0,0 +1,478 @@
+// In src-tauri/src/calendar.rs
+//
+// Calendar awareness. Events are synced from ICS URLs or CalDAV calendars
+// every `sync_minutes` and kept in memory for the next few days. A scheduler
+// then turns them into triggers for the frontend:
+//   - "meeting-starting" shortly before an event begins (the UI can start
+//     meeting transcription),
+//   - "calendar-pause-changed" while an event whose title matches one of
+//     `pause_keywords` (1:1s, interviews, ...) is running, so screen
+//     observation can be paused.
+//
+// The ICS parser covers what calendar servers actually emit for meetings:
+// UTC, floating and TZID times (TZID is read as local time), all-day dates and
+// simple DAILY/WEEKLY recurrence, with EXDATE exclusions and RECURRENCE-ID
+// overrides (a moved or cancelled occurrence replaces the one the rule gives).
+
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{llm, secrets, storage};
+
+const SETTINGS_FILE: &str = "calendar.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const HORIZON_DAYS: i64 = 7;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+// Occurrences expanded per recurring event, whatever its rule says.
+const MAX_OCCURRENCES: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    Ics,
+    Caldav,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarSource {
+    pub name: String,
+    pub kind: SourceKind,
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+}
+
+impl CalendarSource {
+    fn secret_key(&self) -> String {
+        format!("caldav:{}@{}", self.username, self.url)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarSettings {
+    pub sources: Vec<CalendarSource>,
+    pub sync_minutes: u32,
+    // Minutes before a meeting to emit "meeting-starting"; 0 disables it.
+    pub meeting_lead_minutes: u32,
+    pub pause_keywords: Vec<String>,
+}
+
+impl Default for CalendarSettings {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            sync_minutes: 15,
+            meeting_lead_minutes: 2,
+            pause_keywords: vec!["1:1".to_string(), "1-1".to_string(), "one on one".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub calendar: String,
+    pub summary: String,
+    pub location: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub all_day: bool,
+}
+
+impl CalendarEvent {
+    // Occurrences of the same recurring event share a UID.
+    fn key(&self) -> String {
+        format!("{}@{}", self.uid, self.start.timestamp())
+    }
+}
+
+#[derive(Default)]
+pub struct CalendarState {
+    settings: Mutex<CalendarSettings>,
+    events: Mutex<Vec<CalendarEvent>>,
+    last_sync: Mutex<Option<DateTime<Utc>>>,
+    announced: Mutex<HashSet<String>>,
+    pausing: Mutex<Option<String>>,
+}
+
+// RFC 5545 folds long lines with CRLF + space.
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", "\n")
+        .replace("\\N", "\n")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+// Returns the time and whether it was a date-only (all-day) value.
+fn parse_time(params: &str, value: &str) -> Option<(DateTime<Utc>, bool)> {
+    if params.contains("VALUE=DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        let local = Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?;
+        return Some((local.with_timezone(&Utc), true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive), false));
+    }
+    // Floating or TZID time: read as local.
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((Local.from_local_datetime(&naive).earliest()?.with_timezone(&Utc), false))
+}
+
+struct Recurrence {
+    step: ChronoDuration,
+    count: Option<u32>,
+    until: Option<DateTime<Utc>>,
+}
+
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut freq = None;
+    let mut interval = 1;
+    let mut count = None;
+    let mut until = None;
+    for part in value.split(';') {
+        let (key, val) = part.split_once('=')?;
+        match key {
+            "FREQ" => freq = Some(val.to_string()),
+            "INTERVAL" => interval = val.parse::<i64>().ok().filter(|interval| (1..=1000).contains(interval))?,
+            "COUNT" => count = val.parse().ok(),
+            "UNTIL" => until = parse_time("", val).map(|(t, _)| t),
+            _ => {}
+        }
+    }
+    let days = match freq?.as_str() {
+        "DAILY" => 1,
+        "WEEKLY" => 7,
+        // Monthly/yearly meetings rarely matter for a week-long horizon.
+        _ => return None,
+    };
+    Some(Recurrence { step: ChronoDuration::days(days * interval), count, until })
+}
+
+type Props = Vec<(String, String, String)>;
+
+fn prop<'a>(props: &'a [(String, String, String)], name: &str) -> Option<&'a (String, String, String)> {
+    props.iter().find(|(n, _, _)| n == name)
+}
+
+pub fn parse_ics(ics: &str, calendar: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<CalendarEvent> {
+    let mut vevents: Vec<Props> = Vec::new();
+    let mut current: Option<Props> = None;
+
+    for line in unfold(ics) {
+        match line.as_str() {
+            "BEGIN:VEVENT" => current = Some(Vec::new()),
+            "END:VEVENT" => vevents.extend(current.take()),
+            _ => {
+                if let (Some(props), Some((name, value))) = (current.as_mut(), line.split_once(':')) {
+                    let (name, params) = name.split_once(';').unwrap_or((name, ""));
+                    props.push((name.to_uppercase(), params.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+
+    // Occurrences replaced by an override, per UID.
+    let overridden: HashSet<(String, DateTime<Utc>)> = vevents
+        .iter()
+        .filter_map(|props| {
+            let uid = prop(props, "UID")?.2.clone();
+            let (_, params, value) = prop(props, "RECURRENCE-ID")?;
+            Some((uid, parse_time(params, value)?.0))
+        })
+        .collect();
+    let mut events = Vec::new();
+    for props in &vevents {
+        expand_event(props, calendar, from, to, &overridden, &mut events);
+    }
+    events
+}
+
+// The EXDATE times of an event, which may be spread over several lines.
+fn excluded(props: &[(String, String, String)]) -> HashSet<DateTime<Utc>> {
+    props
+        .iter()
+        .filter(|(name, _, _)| name == "EXDATE")
+        .flat_map(|(_, params, value)| value.split(',').filter_map(|v| parse_time(params, v.trim())))
+        .map(|(time, _)| time)
+        .collect()
+}
+
+fn expand_event(
+    props: &[(String, String, String)],
+    calendar: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    overridden: &HashSet<(String, DateTime<Utc>)>,
+    events: &mut Vec<CalendarEvent>,
+) {
+    let prop = |name: &str| prop(props, name);
+    let Some((start, all_day)) = prop("DTSTART").and_then(|(_, p, v)| parse_time(p, v)) else {
+        return;
+    };
+    // A cancelled occurrence is an override with STATUS:CANCELLED: it's
+    // dropped here and its time is in `overridden`, so the rule skips it too.
+    if prop("STATUS").is_some_and(|(_, _, v)| v == "CANCELLED") {
+        return;
+    }
+    let end = prop("DTEND")
+        .and_then(|(_, p, v)| parse_time(p, v))
+        .map(|(t, _)| t)
+        .unwrap_or(start + if all_day { ChronoDuration::days(1) } else { ChronoDuration::hours(1) });
+    let duration = end - start;
+
+    let make = |start: DateTime<Utc>| CalendarEvent {
+        uid: prop("UID").map(|(_, _, v)| v.clone()).unwrap_or_default(),
+        calendar: calendar.to_string(),
+        summary: prop("SUMMARY").map(|(_, _, v)| unescape(v)).unwrap_or_default(),
+        location: prop("LOCATION").map(|(_, _, v)| unescape(v)).unwrap_or_default(),
+        start,
+        end: start + duration,
+        all_day,
+    };
+
+    // Overrides are expanded as single events; only the base event recurs.
+    let rule = prop("RRULE").filter(|_| prop("RECURRENCE-ID").is_none()).and_then(|(_, _, v)| parse_rrule(v));
+    match rule {
+        Some(rule) => {
+            let uid = prop("UID").map(|(_, _, v)| v.clone()).unwrap_or_default();
+            let excluded = excluded(props);
+            // Jump over the occurrences that ended before the window.
+            let skipped = if start + duration < from {
+                ((from - duration - start).num_seconds() / rule.step.num_seconds()).max(0) as u32
+            } else {
+                0
+            };
+            let mut occurrence = start + rule.step * skipped as i32;
+            let mut n = skipped;
+            while occurrence < to
+                && n - skipped < MAX_OCCURRENCES
+                && !matches!(rule.count, Some(count) if n >= count)
+                && !matches!(rule.until, Some(until) if occurrence > until)
+            {
+                let replaced = excluded.contains(&occurrence) || overridden.contains(&(uid.clone(), occurrence));
+                if occurrence + duration > from && !replaced {
+                    events.push(make(occurrence));
+                }
+                occurrence += rule.step;
+                n += 1;
+            }
+        }
+        None if end > from && start < to => events.push(make(start)),
+        None => {}
+    }
+}
+
+fn decode_xml_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#13;", "\r")
+        .replace("&amp;", "&")
+}
+
+async fn fetch_source(source: &CalendarSource, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<String, String> {
+    let client = llm::client();
+    let request = match source.kind {
+        SourceKind::Ics => client.get(source.url.replace("webcal://", "https://")),
+        SourceKind::Caldav => {
+            let body = format!(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT">
+    <c:time-range start="{}" end="{}"/>
+  </c:comp-filter></c:comp-filter></c:filter>
+</c:calendar-query>"#,
+                from.format("%Y%m%dT%H%M%SZ"),
+                to.format("%Y%m%dT%H%M%SZ")
+            );
+            let password = secrets::get(&source.secret_key())?.unwrap_or_default();
+            client
+                .request(reqwest::Method::from_bytes(b"REPORT").unwrap(), &source.url)
+                .basic_auth(&source.username, Some(password))
+                .header("Depth", "1")
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .body(body)
+        }
+    };
+
+    let response = request
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch calendar '{}': {}", source.name, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Calendar '{}' returned {}", source.name, response.status()));
+    }
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    // A CalDAV multistatus embeds one escaped VCALENDAR per event; the
+    // parser only looks at VEVENT blocks so they can simply be concatenated.
+    Ok(match source.kind {
+        SourceKind::Ics => text,
+        SourceKind::Caldav => decode_xml_entities(&text),
+    })
+}
+
+pub async fn sync(app: &AppHandle) -> Result<usize, String> {
+    let settings = app.state::<CalendarState>().settings.lock().unwrap().clone();
+    let from = Utc::now() - ChronoDuration::hours(12);
+    let to = Utc::now() + ChronoDuration::days(HORIZON_DAYS);
+
+    let mut events = Vec::new();
+    for source in &settings.sources {
+        match fetch_source(source, from, to).await {
+            Ok(ics) => events.extend(parse_ics(&ics, &source.name, from, to)),
+            // Keep going so one broken calendar doesn't hide the others.
+            Err(e) => log::error!("{}", e),
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    events.dedup_by(|a, b| a.key() == b.key());
+
+    let count = events.len();
+    let state = app.state::<CalendarState>();
+    *state.events.lock().unwrap() = events;
+    *state.last_sync.lock().unwrap() = Some(Utc::now());
+    log::info!("Synced {} calendar events from {} sources", count, settings.sources.len());
+    Ok(count)
+}
+
+// The timed (not all-day) event happening right now, if any.
+pub fn current_meeting(app: &AppHandle) -> Option<CalendarEvent> {
+    let now = Utc::now();
+    app.state::<CalendarState>()
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|e| !e.all_day && e.start <= now && now < e.end)
+        .cloned()
+}
+
+fn check_triggers(app: &AppHandle) {
+    let state = app.state::<CalendarState>();
+    let settings = state.settings.lock().unwrap().clone();
+    let events = state.events.lock().unwrap().clone();
+    let now = Utc::now();
+
+    if settings.meeting_lead_minutes > 0 {
+        let lead = ChronoDuration::minutes(settings.meeting_lead_minutes as i64);
+        let mut announced = state.announced.lock().unwrap();
+        for event in events.iter().filter(|e| !e.all_day && e.start > now && e.start - now <= lead) {
+            if announced.insert(event.key()) {
+                log::info!("Meeting '{}' starts at {}", event.summary, event.start);
+                if let Err(e) = app.emit("meeting-starting", event) {
+                    log::error!("Failed to emit meeting-starting event: {}", e);
+                }
+            }
+        }
+        announced.retain(|key| events.iter().any(|e| e.key() == *key && e.end > now));
+    }
+
+    let pausing_event = events.iter().find(|e| {
+        !e.all_day
+            && e.start <= now
+            && now < e.end
+            && settings
+                .pause_keywords
+                .iter()
+                .any(|k| !k.is_empty() && e.summary.to_lowercase().contains(&k.to_lowercase()))
+    });
+    let mut pausing = state.pausing.lock().unwrap();
+    let key = pausing_event.map(CalendarEvent::key);
+    if *pausing != key {
+        *pausing = key;
+        let payload = serde_json::json!({ "paused": pausing_event.is_some(), "event": pausing_event });
+        if let Err(e) = app.emit("calendar-pause-changed", payload) {
+            log::error!("Failed to emit calendar-pause-changed event: {}", e);
+        }
+    }
+}
+
+pub fn start_scheduler(app: AppHandle) {
+    *app.state::<CalendarState>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (has_sources, interval) = {
+                let settings = app.state::<CalendarState>().settings.lock().unwrap();
+                (
+                    !settings.sources.is_empty(),
+                    ChronoDuration::minutes(settings.sync_minutes.max(1) as i64),
+                )
+            };
+            let last_sync = *app.state::<CalendarState>().last_sync.lock().unwrap();
+            if has_sources && !matches!(last_sync, Some(last) if Utc::now() - last < interval) {
+                if let Err(e) = sync(&app).await {
+                    log::error!("Calendar sync failed: {}", e);
+                }
+            }
+            check_triggers(&app);
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_calendar_settings(state: State<'_, CalendarState>) -> CalendarSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+// `passwords` maps a CalDAV source URL to its password; they only go to the keyring.
+#[tauri::command]
+pub async fn set_calendar_settings(
+    app: AppHandle,
+    settings: CalendarSettings,
+    passwords: Option<std::collections::HashMap<String, String>>,
+) -> Result<usize, String> {
+    for (url, password) in passwords.unwrap_or_default() {
+        let source = settings
+            .sources
+            .iter()
+            .find(|s| s.url == url)
+            .ok_or_else(|| format!("No calendar with URL {}", url))?;
+        secrets::set(&source.secret_key(), &password)?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *app.state::<CalendarState>().settings.lock().unwrap() = settings;
+    sync(&app).await
+}
+
+#[tauri::command]
+pub async fn sync_calendars_now(app: AppHandle) -> Result<usize, String> {
+    sync(&app).await
+}
+
+#[tauri::command]
+pub fn get_upcoming_events(hours: Option<i64>, state: State<'_, CalendarState>) -> Vec<CalendarEvent> {
+    let now = Utc::now();
+    let until = now + ChronoDuration::hours(hours.unwrap_or(24));
+    state
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|e| e.end > now && e.start < until)
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,239 @@
+// In src-tauri/src/captions.rs
+//
+// Live translated captions for calls and videos, all on this machine.
+// `open_caption_overlay` (or the tray's "Live Captions" item) shows a small
+// borderless window pinned above other windows at the bottom of the screen
+// (the frontend renders it for `#captions`). The window captures the screen's audio or the microphone,
+// transcribes it in short chunks with Whisper and passes each chunk to
+// `translate_caption`, which translates it from `source_language` (detected
+// per chunk when unset) to `target_language` and emits a "caption" event
+// for the overlay to show.
+//
+// `chunk_ms` trades latency for accuracy: shorter chunks show up sooner but
+// give Whisper and the translation model less context. Each caption carries
+// the time from the end of its audio chunk to its translation.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::features::{self, Feature};
+use crate::{storage, transcription};
+
+const SETTINGS_FILE: &str = "captions.json";
+const OVERLAY_LABEL: &str = "captions";
+pub const TRAY_ITEM_ID: &str = "live_captions";
+const OVERLAY_WIDTH: f64 = 960.0;
+const OVERLAY_HEIGHT: f64 = 160.0;
+const MIN_CHUNK_MS: u32 = 1000;
+const MAX_CHUNK_MS: u32 = 15000;
+const RECENT_CAPTIONS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptionAudio {
+    ScreenAudio,
+    Microphone,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptionSettings {
+    pub audio: CaptionAudio,
+    // ISO 639-1 codes; no source language means it's detected per chunk.
+    pub source_language: Option<String>,
+    pub target_language: String,
+    // Falls back to the transcription settings' translation model.
+    pub translation_model: Option<String>,
+    // Falls back to the route for the source language, then Whisper tiny.
+    pub whisper_model: Option<String>,
+    pub chunk_ms: u32,
+    pub max_lines: u32,
+    pub font_size: u32,
+    // Shows the untranslated text under each caption.
+    pub show_original: bool,
+}
+
+impl Default for CaptionSettings {
+    fn default() -> Self {
+        Self {
+            audio: CaptionAudio::ScreenAudio,
+            source_language: None,
+            target_language: "en".to_string(),
+            translation_model: None,
+            whisper_model: None,
+            chunk_ms: 4000,
+            max_lines: 2,
+            font_size: 26,
+            show_original: false,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CaptionState {
+    settings: Mutex<CaptionSettings>,
+    recent: Mutex<VecDeque<Caption>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Caption {
+    pub created_at: DateTime<Utc>,
+    pub language: Option<String>,
+    pub original: String,
+    pub text: String,
+    // From the end of the audio chunk to the translation.
+    pub latency_ms: Option<i64>,
+}
+
+fn settings(app: &AppHandle) -> CaptionSettings {
+    app.state::<CaptionState>().settings.lock().unwrap().clone()
+}
+
+// Bottom centre of the primary monitor, in physical pixels.
+fn overlay_position(app: &AppHandle) -> Option<PhysicalPosition<i32>> {
+    let monitor = app.primary_monitor().ok().flatten()?;
+    let (size, origin, scale) = (monitor.size(), monitor.position(), monitor.scale_factor());
+    let x = origin.x + (size.width as i32 - (OVERLAY_WIDTH * scale) as i32) / 2;
+    let y = origin.y + size.height as i32 - ((OVERLAY_HEIGHT + 80.0) * scale) as i32;
+    Some(PhysicalPosition::new(x, y))
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<CaptionState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_caption_settings(state: State<'_, CaptionState>) -> CaptionSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_caption_settings(
+    app: AppHandle,
+    mut settings: CaptionSettings,
+    state: State<'_, CaptionState>,
+) -> Result<(), String> {
+    if settings.target_language.trim().is_empty() {
+        return Err("Pick a language to translate to".to_string());
+    }
+    settings.chunk_ms = settings.chunk_ms.clamp(MIN_CHUNK_MS, MAX_CHUNK_MS);
+    settings.max_lines = settings.max_lines.max(1);
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings.clone();
+    if let Err(e) = app.emit("caption-settings-changed", settings) {
+        log::error!("Failed to emit caption-settings-changed event: {}", e);
+    }
+    Ok(())
+}
+
+fn open_overlay(app: &AppHandle) -> Result<(), String> {
+    features::ensure(Feature::Audio)?;
+    if let Some(window) = app.get_webview_window(OVERLAY_LABEL) {
+        return window.show().map_err(|e| e.to_string());
+    }
+    let window = WebviewWindowBuilder::new(app, OVERLAY_LABEL, WebviewUrl::App("index.html#captions".into()))
+        .title("Live captions")
+        .inner_size(OVERLAY_WIDTH, OVERLAY_HEIGHT)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .build()
+        .map_err(|e| format!("Failed to open caption window: {}", e))?;
+    if let Some(position) = overlay_position(app) {
+        let _ = window.set_position(position);
+    }
+    Ok(())
+}
+
+// From the tray: opens the overlay, or closes it when it's open.
+pub fn toggle(app: &AppHandle) {
+    let result = match app.get_webview_window(OVERLAY_LABEL) {
+        Some(window) => window.close().map_err(|e| e.to_string()),
+        None => open_overlay(app),
+    };
+    if let Err(e) = result {
+        log::error!("Failed to toggle live captions: {}", e);
+    }
+}
+
+// Window creation deadlocks in synchronous commands on Windows, hence async.
+#[tauri::command]
+pub async fn open_caption_overlay(app: AppHandle) -> Result<(), String> {
+    open_overlay(&app)
+}
+
+#[tauri::command]
+pub fn close_caption_overlay(app: AppHandle) -> Result<(), String> {
+    match app.get_webview_window(OVERLAY_LABEL) {
+        Some(window) => window.close().map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+// The Whisper model for captions: the configured one, or the one routed to
+// the source language; None means the default.
+#[tauri::command]
+pub fn caption_whisper_model(app: AppHandle) -> Option<String> {
+    let settings = settings(&app);
+    settings
+        .whisper_model
+        .or_else(|| settings.source_language.and_then(|language| transcription::whisper_model_for(&app, &language)))
+}
+
+// `captured_at` is when the chunk's audio ended, in milliseconds since the epoch.
+#[tauri::command]
+pub async fn translate_caption(app: AppHandle, text: String, captured_at: Option<i64>) -> Result<Caption, String> {
+    let settings = settings(&app);
+    let text = text.trim().to_string();
+    let language = settings.source_language.clone().or_else(|| {
+        let detection = transcription::detect(&text);
+        detection.language.filter(|_| detection.confidence >= transcription::MIN_CONFIDENCE)
+    });
+    let target = settings.target_language.clone();
+
+    let translated = match language.as_deref() {
+        // Audio may not leave the machine: the overlay keeps showing the original.
+        Some(language) if language != target && transcription::check_locality(&app).is_err() => text.clone(),
+        Some(language) if language != target => {
+            let model = settings.translation_model.clone();
+            transcription::translate(&app, model.as_deref(), &text, language, &target).await?
+        }
+        // Already in the target language, or too short to tell.
+        _ => text.clone(),
+    };
+    let created_at = Utc::now();
+    let caption = Caption {
+        created_at,
+        language,
+        original: text,
+        text: translated,
+        latency_ms: captured_at.map(|at| created_at.timestamp_millis() - at),
+    };
+
+    {
+        let state = app.state::<CaptionState>();
+        let mut recent = state.recent.lock().unwrap();
+        recent.push_back(caption.clone());
+        while recent.len() > RECENT_CAPTIONS {
+            recent.pop_front();
+        }
+    }
+    if let Err(e) = app.emit("caption", caption.clone()) {
+        log::error!("Failed to emit caption event: {}", e);
+    }
+    Ok(caption)
+}
+
+// The captions of this session, oldest first, e.g. to save a call's transcript.
+#[tauri::command]
+pub fn list_captions(state: State<'_, CaptionState>) -> Vec<Caption> {
+    state.recent.lock().unwrap().iter().cloned().collect()
+}
+
+#[tauri::command]
+pub fn clear_captions(state: State<'_, CaptionState>) {
+    state.recent.lock().unwrap().clear();
+}
@@ -0,0 +1,405 @@
+// In src-tauri/src/capture.rs
+//
+// Native screen capture, so agents get screenshots without the browser's
+// getDisplayMedia prompt and its tab-sharing quirks. Each platform API sits
+// behind `CaptureBackend`; the backends are tried in order of preference:
+//
+//   Windows  Windows Graphics Capture, then GDI/DXGI
+//   macOS    ScreenCaptureKit, then CoreGraphics
+//   Linux    xdg-desktop-portal on Wayland, then X11
+//
+// The fallbacks go through the `screenshots` crate. A backend that isn't
+// usable on this machine (an old OS, no Wayland session) is skipped, and one
+// that fails at runtime, e.g. because the user denied the permission, is
+// demoted for the rest of the session so the next one takes over.
+//
+// Over HTTP a capture needs the session's token in `TOKEN_HEADER`. The token
+// comes from `GET /capture/token`, which like `/capture/screen` is only
+// readable from the app's own origins (see server.rs), so other web pages
+// the user visits can neither get it nor trigger a capture.
+
+use axum::{
+    extract::{Query, State as AxumState},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Manager};
+
+use crate::annotate::{self, AnnotatedImage};
+use crate::features::{self, Feature};
+use crate::{privacy, tunnel, AppState};
+
+pub const TOKEN_HEADER: &str = "x-observer-capture-token";
+
+pub trait CaptureBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    // Why the backend can't be used here, or None if it can.
+    fn unavailable_reason(&self) -> Option<String>;
+    // Captures the display with the given index (0 is the primary one).
+    // Blocking; called from `spawn_blocking`.
+    fn capture(&self, display: usize) -> Result<RgbaImage, String>;
+}
+
+#[derive(Default)]
+pub struct CaptureState {
+    // Runtime failures by backend name, cleared on restart.
+    failed: Mutex<HashMap<&'static str, String>>,
+    in_progress: AtomicUsize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureCapability {
+    pub backend: &'static str,
+    pub available: bool,
+    pub reason: Option<String>,
+    // The backend `capture_screen` would use right now.
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturedScreen {
+    pub backend: &'static str,
+    #[serde(flatten)]
+    pub image: AnnotatedImage,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CaptureQuery {
+    pub display: Option<usize>,
+}
+
+// --- Backends ---
+
+// GDI/DXGI, CoreGraphics or X11, depending on the platform.
+struct ScreenshotsBackend;
+
+impl CaptureBackend for ScreenshotsBackend {
+    fn name(&self) -> &'static str {
+        if cfg!(target_os = "windows") {
+            "gdi"
+        } else if cfg!(target_os = "macos") {
+            "core_graphics"
+        } else {
+            "x11"
+        }
+    }
+
+    fn unavailable_reason(&self) -> Option<String> {
+        if cfg!(target_os = "linux") && std::env::var_os("DISPLAY").is_none() {
+            return Some("No X server (DISPLAY is not set)".to_string());
+        }
+        None
+    }
+
+    fn capture(&self, display: usize) -> Result<RgbaImage, String> {
+        let screens = screenshots::Screen::all().map_err(|e| format!("Failed to list displays: {}", e))?;
+        let screen = screens.get(display).ok_or_else(|| format!("No display {}", display))?;
+        screen.capture().map_err(|e| format!("Failed to capture display {}: {}", display, e))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod wgc {
+    use image::RgbaImage;
+    use std::sync::{Arc, Mutex};
+    use windows_capture::{
+        capture::{Context, GraphicsCaptureApiHandler},
+        frame::Frame,
+        graphics_capture_api::{GraphicsCaptureApi, InternalCaptureControl},
+        monitor::Monitor,
+        settings::{ColorFormat, CursorCaptureSettings, DrawBorderSettings, Settings},
+    };
+
+    type Slot = Arc<Mutex<Option<RgbaImage>>>;
+
+    // Keeps the first frame and stops the session.
+    struct FirstFrame(Slot);
+
+    impl GraphicsCaptureApiHandler for FirstFrame {
+        type Flags = Slot;
+        type Error = Box<dyn std::error::Error + Send + Sync>;
+
+        fn new(ctx: Context<Self::Flags>) -> Result<Self, Self::Error> {
+            Ok(Self(ctx.flags))
+        }
+
+        fn on_frame_arrived(&mut self, frame: &mut Frame, control: InternalCaptureControl) -> Result<(), Self::Error> {
+            let mut buffer = frame.buffer()?;
+            let (width, height) = (buffer.width(), buffer.height());
+            let pixels = buffer.as_nopadding_buffer()?.to_vec();
+            *self.0.lock().unwrap() = RgbaImage::from_raw(width, height, pixels);
+            control.stop();
+            Ok(())
+        }
+    }
+
+    pub struct WgcBackend;
+
+    impl super::CaptureBackend for WgcBackend {
+        fn name(&self) -> &'static str {
+            "windows_graphics_capture"
+        }
+
+        fn unavailable_reason(&self) -> Option<String> {
+            match GraphicsCaptureApi::is_supported() {
+                Ok(true) => None,
+                _ => Some("Windows Graphics Capture needs Windows 10 1903 or later".to_string()),
+            }
+        }
+
+        fn capture(&self, display: usize) -> Result<RgbaImage, String> {
+            // Monitor indices are 1-based.
+            let monitor = Monitor::from_index(display + 1).map_err(|e| format!("No display {}: {}", display, e))?;
+            let slot: Slot = Arc::default();
+            let settings = Settings::new(
+                monitor,
+                CursorCaptureSettings::WithCursor,
+                DrawBorderSettings::WithoutBorder,
+                ColorFormat::Rgba8,
+                slot.clone(),
+            );
+            FirstFrame::start(settings).map_err(|e| format!("Windows Graphics Capture failed: {}", e))?;
+            let image = slot.lock().unwrap().take();
+            image.ok_or_else(|| "Windows Graphics Capture returned no frame".to_string())
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod sck {
+    use image::RgbaImage;
+    use std::process::Command;
+
+    // Goes through the system `screencapture` tool, which is built on
+    // ScreenCaptureKit from macOS 12.3 on. The Screen Recording permission
+    // is then asked for on behalf of this app.
+    pub struct ScreenCaptureKitBackend;
+
+    fn macos_version() -> Option<(u32, u32)> {
+        let output = Command::new("sw_vers").arg("-productVersion").output().ok()?;
+        let version = String::from_utf8_lossy(&output.stdout);
+        let mut parts = version.trim().split('.').map(|p| p.parse::<u32>().ok());
+        Some((parts.next()??, parts.next().flatten().unwrap_or(0)))
+    }
+
+    impl super::CaptureBackend for ScreenCaptureKitBackend {
+        fn name(&self) -> &'static str {
+            "screencapturekit"
+        }
+
+        fn unavailable_reason(&self) -> Option<String> {
+            match macos_version() {
+                Some(version) if version >= (12, 3) => None,
+                Some((major, minor)) => Some(format!("ScreenCaptureKit needs macOS 12.3 (found {}.{})", major, minor)),
+                None => Some("Could not determine the macOS version".to_string()),
+            }
+        }
+
+        fn capture(&self, display: usize) -> Result<RgbaImage, String> {
+            let path = std::env::temp_dir().join(format!("observer-capture-{}.png", uuid::Uuid::new_v4()));
+            // -x: no shutter sound; -D is 1-based.
+            let output = Command::new("/usr/sbin/screencapture")
+                .args(["-x", "-t", "png", "-D"])
+                .arg((display + 1).to_string())
+                .arg(&path)
+                .output()
+                .map_err(|e| format!("Failed to run screencapture: {}", e))?;
+            let image = if output.status.success() {
+                image::open(&path)
+                    .map(|image| image.to_rgba8())
+                    .map_err(|e| format!("Failed to read the capture (is Screen Recording allowed?): {}", e))
+            } else {
+                Err(format!("screencapture failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+            };
+            let _ = std::fs::remove_file(&path);
+            image
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod portal {
+    use ashpd::desktop::screenshot::Screenshot;
+    use image::RgbaImage;
+
+    // The Screenshot portal of xdg-desktop-portal, the only way to capture a
+    // Wayland session. The compositor grabs the whole desktop, so `display`
+    // is ignored. The first request may show the compositor's permission
+    // dialog.
+    pub struct PortalBackend;
+
+    async fn request_uri() -> Result<String, ashpd::Error> {
+        let response = Screenshot::request().interactive(false).modal(false).send().await?.response()?;
+        Ok(response.uri().to_string())
+    }
+
+    impl super::CaptureBackend for PortalBackend {
+        fn name(&self) -> &'static str {
+            "xdg_desktop_portal"
+        }
+
+        fn unavailable_reason(&self) -> Option<String> {
+            if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+                return Some("Not a Wayland session".to_string());
+            }
+            None
+        }
+
+        fn capture(&self, _display: usize) -> Result<RgbaImage, String> {
+            let uri = tauri::async_runtime::block_on(request_uri())
+                .map_err(|e| format!("Screenshot portal request failed: {}", e))?;
+            let path = url_path(&uri).ok_or_else(|| format!("Unexpected screenshot location '{}'", uri))?;
+            let image = image::open(&path).map(|image| image.to_rgba8());
+            // The portal saves into the user's Pictures folder; it's our file.
+            let _ = std::fs::remove_file(&path);
+            image.map_err(|e| format!("Failed to read {:?}: {}", path, e))
+        }
+    }
+
+    fn url_path(uri: &str) -> Option<std::path::PathBuf> {
+        let path = uri.strip_prefix("file://")?;
+        let mut decoded = Vec::with_capacity(path.len());
+        let mut bytes = path.bytes();
+        while let Some(b) = bytes.next() {
+            if b == b'%' {
+                let hex = [bytes.next()?, bytes.next()?];
+                decoded.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            } else {
+                decoded.push(b);
+            }
+        }
+        Some(String::from_utf8(decoded).ok()?.into())
+    }
+}
+
+// In order of preference for this platform.
+fn backends() -> Vec<Box<dyn CaptureBackend>> {
+    #[allow(unused_mut)]
+    let mut backends: Vec<Box<dyn CaptureBackend>> = Vec::new();
+    #[cfg(target_os = "windows")]
+    backends.push(Box::new(wgc::WgcBackend));
+    #[cfg(target_os = "macos")]
+    backends.push(Box::new(sck::ScreenCaptureKitBackend));
+    #[cfg(target_os = "linux")]
+    backends.push(Box::new(portal::PortalBackend));
+    backends.push(Box::new(ScreenshotsBackend));
+    backends
+}
+
+pub fn capabilities(app: &AppHandle) -> Vec<CaptureCapability> {
+    let failed = app.state::<CaptureState>().failed.lock().unwrap().clone();
+    let mut found_active = false;
+    backends()
+        .iter()
+        .map(|backend| {
+            let unavailable = backend.unavailable_reason();
+            let available = unavailable.is_none();
+            let reason = unavailable.or_else(|| failed.get(backend.name()).cloned());
+            let active = reason.is_none() && !found_active;
+            found_active |= active;
+            CaptureCapability {
+                backend: backend.name(),
+                available,
+                reason,
+                active,
+            }
+        })
+        .collect()
+}
+
+fn capture_blocking(app: &AppHandle, display: usize) -> Result<(&'static str, RgbaImage), String> {
+    let state = app.state::<CaptureState>();
+    let mut errors = Vec::new();
+    for backend in backends() {
+        if let Some(reason) = backend.unavailable_reason() {
+            log::debug!("Skipping capture backend {}: {}", backend.name(), reason);
+            continue;
+        }
+        if state.failed.lock().unwrap().contains_key(backend.name()) {
+            continue;
+        }
+        match backend.capture(display) {
+            Ok(image) => return Ok((backend.name(), image)),
+            Err(e) => {
+                log::warn!("Capture backend {} failed, falling back: {}", backend.name(), e);
+                errors.push(format!("{}: {}", backend.name(), e));
+                state.failed.lock().unwrap().insert(backend.name(), e);
+            }
+        }
+    }
+    if errors.is_empty() {
+        return Err("No screen capture backend is available".to_string());
+    }
+    Err(format!("Screen capture failed ({})", errors.join("; ")))
+}
+
+pub fn in_progress(app: &AppHandle) -> bool {
+    app.state::<CaptureState>().in_progress.load(Ordering::SeqCst) > 0
+}
+
+pub async fn capture(app: &AppHandle, display: usize) -> Result<CapturedScreen, String> {
+    features::ensure(Feature::Capture)?;
+    privacy::ensure_allowed(app)?;
+    let app = app.clone();
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<CaptureState>();
+        state.in_progress.fetch_add(1, Ordering::SeqCst);
+        let result = capture_blocking(&app, display);
+        state.in_progress.fetch_sub(1, Ordering::SeqCst);
+        let (backend, image) = result?;
+        Ok(CapturedScreen { backend, image: annotate::encode(image)? })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Random for every run of the app.
+fn session_token() -> &'static str {
+    static TOKEN: OnceLock<String> = OnceLock::new();
+    TOKEN.get_or_init(|| uuid::Uuid::new_v4().simple().to_string())
+}
+
+pub async fn token_handler() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "token": session_token() }))
+}
+
+pub async fn capture_handler(
+    AxumState(state): AxumState<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CaptureQuery>,
+) -> Result<Json<CapturedScreen>, (StatusCode, String)> {
+    let token = headers.get(TOKEN_HEADER).and_then(|v| v.to_str().ok());
+    if !token.is_some_and(|token| tunnel::same_token(token, session_token())) {
+        return Err((StatusCode::UNAUTHORIZED, "Screen capture needs the session's capture token".to_string()));
+    }
+    capture(&state.app_handle, query.display.unwrap_or(0))
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e))
+}
+
+pub async fn capabilities_handler(AxumState(state): AxumState<AppState>) -> Json<Vec<CaptureCapability>> {
+    Json(capabilities(&state.app_handle))
+}
+
+#[tauri::command]
+pub fn get_capture_capabilities(app: AppHandle) -> Vec<CaptureCapability> {
+    capabilities(&app)
+}
+
+#[tauri::command]
+pub async fn capture_screen(app: AppHandle, display: Option<usize>) -> Result<CapturedScreen, String> {
+    capture(&app, display.unwrap_or(0)).await
+}
+
+// Gives demoted backends another chance, e.g. after the user granted the
+// permission.
+#[tauri::command]
+pub fn reset_capture_backends(state: tauri::State<'_, CaptureState>) {
+    state.failed.lock().unwrap().clear();
+}
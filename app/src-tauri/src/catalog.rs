@@ -0,0 +1,334 @@
+// In src-tauri/src/catalog.rs
+//
+// Community agent catalog. The index at `index_url` is either
+//
+//   - a signed index: {"entries": [{ "package": <AgentPackage>, "publisher_key": <base64
+//     ed25519 public key>, "signature": <base64 signature> , "author": ... }]}
+//     where the signature covers the package serialized as compact JSON with
+//     sorted keys, or
+//   - the plain marketplace listing (an array of agents), which is unsigned.
+//
+// Entries are only "verified" when the signature checks out AND the key
+// belongs to a publisher the user trusts. Installing goes through the normal
+// import preview (agent_share.rs), and unverified agents additionally
+// require `allow_unsigned`.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::agent_share::{self, AgentPackage, ImportPreview, PendingImports};
+use crate::agents::{AgentDefinition, AgentRegistry};
+use crate::{llm, storage};
+
+const SETTINGS_FILE: &str = "catalog.json";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedPublisher {
+    pub name: String,
+    // base64 ed25519 public key
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CatalogSettings {
+    pub index_url: String,
+    pub trusted_publishers: Vec<TrustedPublisher>,
+    pub allow_unsigned: bool,
+}
+
+impl Default for CatalogSettings {
+    fn default() -> Self {
+        Self {
+            index_url: "https://api.observer-ai.com/agents".to_string(),
+            trusted_publishers: Vec::new(),
+            allow_unsigned: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SignedEntry {
+    package: serde_json::Value,
+    publisher_key: String,
+    signature: String,
+    #[serde(default)]
+    author: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CatalogIndex {
+    Signed { entries: Vec<SignedEntry> },
+    Marketplace(Vec<MarketplaceAgent>),
+}
+
+#[derive(Debug, Deserialize)]
+struct MarketplaceAgent {
+    #[serde(flatten)]
+    agent: AgentDefinition,
+    #[serde(default)]
+    author: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub author: Option<String>,
+    pub model: String,
+    pub verified: bool,
+    // Name of the trusted publisher that signed it.
+    pub publisher: Option<String>,
+    // Why verification failed, if it did.
+    pub verification_error: Option<String>,
+    #[serde(skip)]
+    package: Option<AgentPackage>,
+}
+
+#[derive(Default)]
+pub struct Catalog {
+    pub settings: Mutex<CatalogSettings>,
+    entries: Mutex<Vec<CatalogEntry>>,
+}
+
+fn decode_key(key: &str) -> Result<VerifyingKey, String> {
+    let bytes = STANDARD.decode(key.trim()).map_err(|e| format!("Invalid publisher key: {}", e))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| "Publisher key must be 32 bytes".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid publisher key: {}", e))
+}
+
+// The signed form of a package: compact JSON with every object's keys sorted
+// by their UTF-8 bytes. Built by hand rather than with `serde_json::to_vec`,
+// whose key order depends on serde_json's map type (insertion order once any
+// dependency turns on `preserve_order`).
+fn canonical_json(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                canonical_json(value, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+pub fn verify_package(package: &serde_json::Value, key: &str, signature: &str) -> Result<(), String> {
+    let key = decode_key(key)?;
+    let signature = STANDARD
+        .decode(signature.trim())
+        .map_err(|e| format!("Invalid signature encoding: {}", e))?;
+    let signature =
+        Signature::from_slice(&signature).map_err(|e| format!("Invalid signature: {}", e))?;
+    let mut message = String::new();
+    canonical_json(package, &mut message);
+    key.verify(message.as_bytes(), &signature)
+        .map_err(|_| "Signature does not match the package".to_string())
+}
+
+fn signed_entry(entry: SignedEntry, settings: &CatalogSettings) -> Option<CatalogEntry> {
+    let package: AgentPackage = match serde_json::from_value(entry.package.clone()) {
+        Ok(package) => package,
+        Err(e) => {
+            log::warn!("Skipping malformed catalog entry: {}", e);
+            return None;
+        }
+    };
+
+    let publisher = settings
+        .trusted_publishers
+        .iter()
+        .find(|p| p.key.trim() == entry.publisher_key.trim())
+        .map(|p| p.name.clone());
+
+    let verification_error = match verify_package(&entry.package, &entry.publisher_key, &entry.signature) {
+        Err(e) => Some(e),
+        Ok(()) if publisher.is_none() => Some("Signed by an untrusted publisher".to_string()),
+        Ok(()) => None,
+    };
+
+    Some(CatalogEntry {
+        id: package.agent.id.clone(),
+        name: package.agent.name.clone(),
+        description: package.agent.description.clone(),
+        author: entry.author,
+        model: package.model_hints.preferred.clone(),
+        verified: verification_error.is_none(),
+        publisher,
+        verification_error,
+        package: Some(package),
+    })
+}
+
+fn marketplace_entry(item: MarketplaceAgent) -> CatalogEntry {
+    let package = agent_share::build_package(&item.agent);
+    CatalogEntry {
+        id: item.agent.id.clone(),
+        name: item.agent.name.clone(),
+        description: item.agent.description.clone(),
+        author: item.author,
+        model: item.agent.model_name.clone(),
+        verified: false,
+        publisher: None,
+        verification_error: Some("Not signed".to_string()),
+        package: Some(package),
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<Catalog>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_catalog_settings(catalog: State<'_, Catalog>) -> CatalogSettings {
+    catalog.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_catalog_settings(
+    app: AppHandle,
+    settings: CatalogSettings,
+    catalog: State<'_, Catalog>,
+) -> Result<(), String> {
+    for publisher in &settings.trusted_publishers {
+        decode_key(&publisher.key).map_err(|e| format!("{}: {}", publisher.name, e))?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *catalog.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn fetch_catalog(catalog: State<'_, Catalog>) -> Result<Vec<CatalogEntry>, String> {
+    let settings = catalog.settings.lock().unwrap().clone();
+    log::info!("Fetching agent catalog from {}", settings.index_url);
+
+    let response = llm::client()
+        .get(&settings.index_url)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch catalog: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Catalog server returned {}", response.status()));
+    }
+    let index: CatalogIndex = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid catalog index: {}", e))?;
+
+    let entries: Vec<CatalogEntry> = match index {
+        CatalogIndex::Signed { entries } => entries
+            .into_iter()
+            .filter_map(|entry| signed_entry(entry, &settings))
+            .collect(),
+        CatalogIndex::Marketplace(items) => items.into_iter().map(marketplace_entry).collect(),
+    };
+
+    log::info!(
+        "Catalog has {} agents ({} verified)",
+        entries.len(),
+        entries.iter().filter(|e| e.verified).count()
+    );
+    *catalog.entries.lock().unwrap() = entries.clone();
+    Ok(entries)
+}
+
+// Starts the regular import review for a catalog agent; the user still has
+// to call `confirm_agent_import` with the returned token.
+#[tauri::command]
+pub fn install_catalog_agent(
+    id: String,
+    catalog: State<'_, Catalog>,
+    registry: State<'_, AgentRegistry>,
+    pending: State<'_, PendingImports>,
+) -> Result<ImportPreview, String> {
+    let allow_unsigned = catalog.settings.lock().unwrap().allow_unsigned;
+    let entry = catalog
+        .entries
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|e| e.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Agent {} is not in the catalog, fetch it first", id))?;
+
+    if !entry.verified && !allow_unsigned {
+        return Err(format!(
+            "Agent {} could not be verified ({}). Enable unsigned agents to install it anyway.",
+            id,
+            entry.verification_error.unwrap_or_default()
+        ));
+    }
+
+    let package = entry.package.ok_or_else(|| format!("Agent {} has no package", id))?;
+    crate::agents::validate(&package.agent)?;
+
+    let mut preview = agent_share::preview(package, &registry, &pending);
+    if !entry.verified {
+        preview
+            .warnings
+            .insert(0, "This agent is not signed by a trusted publisher".to_string());
+    }
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::json;
+
+    fn canonical(value: &serde_json::Value) -> String {
+        let mut out = String::new();
+        canonical_json(value, &mut out);
+        out
+    }
+
+    #[test]
+    fn canonical_json_sorts_keys_at_every_level() {
+        let package: serde_json::Value =
+            serde_json::from_str(r#"{"name": "a\"b", "agent": {"z": 1, "b": [true, null, {"y": 2.5, "x": "é"}]}}"#)
+                .unwrap();
+        assert_eq!(canonical(&package), r#"{"agent":{"b":[true,null,{"x":"é","y":2.5}],"z":1},"name":"a\"b"}"#);
+    }
+
+    #[test]
+    fn verifies_signatures_over_the_canonical_form() {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let key = STANDARD.encode(signing_key.verifying_key().as_bytes());
+        let package = json!({ "version": 1, "agent": { "name": "Notes", "id": "notes" } });
+        let signature = STANDARD.encode(signing_key.sign(canonical(&package).as_bytes()).to_bytes());
+
+        assert_eq!(verify_package(&package, &key, &signature), Ok(()));
+        let tampered = json!({ "version": 1, "agent": { "name": "Notes!", "id": "notes" } });
+        assert!(verify_package(&tampered, &key, &signature).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+// In src-tauri/src/compaction.rs
+//
+// Context compaction for long-running agents. When a conversation gets close
+// to the model's context window, older turns are summarized by a small model
+// and replaced with a single "memory" system message. The running summary is
+// kept per agent in the history DB so it survives restarts and keeps growing
+// across compactions instead of being lost.
+//
+// The memory records how many leading turns it covers and a hash of them.
+// An agent that keeps sending its whole history gets only the turns after
+// those summarized (merged into the stored summary), and the stored summary
+// as-is when nothing new has aged out of the recent window.
+//
+// Agents opt in either by calling `compact_conversation` themselves or by
+// sending `X-Observer-Agent-Id` + `X-Observer-Compact: auto` on proxied chat
+// requests, in which case the proxy rewrites the body before forwarding.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::{incognito, llm, storage, tokenizer};
+
+const SETTINGS_FILE: &str = "compaction.json";
+pub const AGENT_HEADER: &str = "x-observer-agent-id";
+pub const COMPACT_HEADER: &str = "x-observer-compact";
+
+const SYSTEM_PROMPT: &str = "You compress conversation history for an AI agent. Merge the previous \
+memory (if any) with the new conversation turns into one concise summary. Keep facts about the \
+user, decisions, open tasks and anything the agent will need later. Drop chit-chat and repetition. \
+Answer with the summary only.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompactionSettings {
+    // Small, fast model used for summarizing.
+    pub model: String,
+    // Compact once the prompt uses this share of the context window.
+    pub trigger_ratio: f32,
+    // Number of most recent non-system messages that are always kept verbatim.
+    pub keep_recent: usize,
+}
+
+impl Default for CompactionSettings {
+    fn default() -> Self {
+        Self {
+            model: "gemma3:1b".to_string(),
+            trigger_ratio: 0.75,
+            keep_recent: 6,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CompactionState(pub Mutex<CompactionSettings>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextMemory {
+    pub agent_id: String,
+    pub summary: String,
+    // Leading non-system turns of the conversation the summary covers.
+    pub compacted_turns: i64,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    covered_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactionResult {
+    pub messages: Vec<Value>,
+    pub compacted: bool,
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+    pub summary: Option<String>,
+}
+
+pub fn load_memory(db: &HistoryDb, agent_id: &str) -> Result<Option<ContextMemory>, String> {
+    let conn = db.0.lock().unwrap();
+    conn.query_row(
+        "SELECT agent_id, summary, compacted_turns, updated_at, covered_hash FROM context_memory
+         WHERE agent_id = ?1",
+        params![agent_id],
+        |row| {
+            Ok(ContextMemory {
+                agent_id: row.get(0)?,
+                summary: row.get(1)?,
+                compacted_turns: row.get(2)?,
+                updated_at: Utc.timestamp_millis_opt(row.get(3)?).single().unwrap_or_default(),
+                covered_hash: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+// While incognito the summary still compacts the request, but isn't kept.
+fn save_memory(db: &HistoryDb, agent_id: &str, summary: &str, covered: &[Value]) -> Result<(), String> {
+    if incognito::is_active() {
+        return Ok(());
+    }
+    let conn = db.0.lock().unwrap();
+    conn.execute(
+        "INSERT INTO context_memory (agent_id, summary, compacted_turns, updated_at, covered_hash)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(agent_id) DO UPDATE SET
+             summary = excluded.summary,
+             compacted_turns = excluded.compacted_turns,
+             updated_at = excluded.updated_at,
+             covered_hash = excluded.covered_hash",
+        params![agent_id, summary, covered.len() as i64, Utc::now().timestamp_millis(), turns_hash(covered)],
+    )
+    .map_err(|e| format!("Failed to store context memory: {}", e))?;
+    Ok(())
+}
+
+pub fn message_text(message: &Value) -> String {
+    match &message["content"] {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn turns_hash(turns: &[Value]) -> String {
+    let mut hasher = Sha256::new();
+    for turn in turns {
+        hasher.update(turn["role"].as_str().unwrap_or("").as_bytes());
+        hasher.update([0]);
+        hasher.update(message_text(turn).as_bytes());
+        hasher.update([0]);
+    }
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// How many of `turns` the memory already covers: all the turns it was made
+// from when the conversation still starts with them, none otherwise.
+fn covered_turns(memory: &ContextMemory, turns: &[Value]) -> usize {
+    let covered = usize::try_from(memory.compacted_turns).unwrap_or(usize::MAX);
+    let matches = covered <= turns.len()
+        && memory.covered_hash.as_deref().is_some_and(|hash| hash == turns_hash(&turns[..covered]));
+    if matches {
+        covered
+    } else {
+        0
+    }
+}
+
+fn count_messages(messages: &[Value]) -> usize {
+    tokenizer::count_request(&json!({ "messages": messages }))
+}
+
+pub async fn compact(
+    app: &AppHandle,
+    agent_id: &str,
+    messages: Vec<Value>,
+    context_window: u32,
+    force: bool,
+) -> Result<CompactionResult, String> {
+    let settings = app.state::<CompactionState>().0.lock().unwrap().clone();
+    let tokens_before = count_messages(&messages);
+
+    let unchanged = |messages: Vec<Value>| CompactionResult {
+        messages,
+        compacted: false,
+        tokens_before,
+        tokens_after: tokens_before,
+        summary: None,
+    };
+
+    let threshold = context_window as f32 * settings.trigger_ratio;
+    if !force && (tokens_before as f32) < threshold {
+        return Ok(unchanged(messages));
+    }
+
+    // Leading system messages (the agent's instructions) are never summarized.
+    let system_count = messages.iter().take_while(|m| m["role"] == "system").count();
+    let turns = messages.len() - system_count;
+    if turns <= settings.keep_recent {
+        log::warn!("Agent {} is over its context budget but has nothing old enough to compact", agent_id);
+        return Ok(unchanged(messages));
+    }
+    let split = messages.len() - settings.keep_recent;
+
+    let old = &messages[system_count..split];
+    let previous = load_memory(&app.state::<HistoryDb>(), agent_id)?;
+    let covered = previous.as_ref().map_or(0, |previous| covered_turns(previous, old));
+
+    let summary = match &previous {
+        Some(previous) if covered == old.len() => {
+            log::info!("Reusing the stored memory of agent {} ({} turns)", agent_id, covered);
+            previous.summary.clone()
+        }
+        _ => {
+            let mut prompt = String::new();
+            if let Some(previous) = &previous {
+                prompt.push_str(&format!("Previous memory:\n{}\n\n", previous.summary));
+            }
+            prompt.push_str("New conversation turns:\n");
+            for message in &old[covered..] {
+                prompt.push_str(&format!(
+                    "{}: {}\n",
+                    message["role"].as_str().unwrap_or("user"),
+                    message_text(message)
+                ));
+            }
+
+            log::info!(
+                "Compacting {} turns for agent {} ({} tokens, window {})",
+                old.len() - covered,
+                agent_id,
+                tokens_before,
+                context_window
+            );
+            let summary = llm::generate(app, &settings.model, SYSTEM_PROMPT, &prompt).await?;
+            save_memory(&app.state::<HistoryDb>(), agent_id, &summary, old)?;
+            summary
+        }
+    };
+
+    let mut compacted: Vec<Value> = messages[..system_count].to_vec();
+    compacted.push(json!({
+        "role": "system",
+        "content": format!("Memory of the earlier conversation:\n{}", summary),
+    }));
+    compacted.extend_from_slice(&messages[split..]);
+
+    let result = CompactionResult {
+        tokens_after: count_messages(&compacted),
+        messages: compacted,
+        compacted: true,
+        tokens_before,
+        summary: Some(summary),
+    };
+    if let Err(e) = app.emit(
+        "context-compacted",
+        json!({ "agent_id": agent_id, "tokens_before": result.tokens_before, "tokens_after": result.tokens_after }),
+    ) {
+        log::error!("Failed to emit context-compacted event: {}", e);
+    }
+    Ok(result)
+}
+
+// Proxy hook: rewrites a chat request body in place when it is close to the
+// limit. Returns the new body, or None when nothing changed.
+pub async fn compact_request(
+    app: &AppHandle,
+    agent_id: &str,
+    body: &[u8],
+    count: &tokenizer::TokenCount,
+) -> Option<Vec<u8>> {
+    let mut json: Value = serde_json::from_slice(body).ok()?;
+    let messages = json["messages"].as_array()?.clone();
+
+    match compact(app, agent_id, messages, count.context_window.effective, false).await {
+        Ok(result) if result.compacted => {
+            json["messages"] = Value::Array(result.messages);
+            serde_json::to_vec(&json).ok()
+        }
+        Ok(_) => None,
+        Err(e) => {
+            log::error!("Context compaction for agent {} failed, forwarding as-is: {}", agent_id, e);
+            None
+        }
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<CompactionState>().0.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub async fn compact_conversation(
+    app: AppHandle,
+    agent_id: String,
+    model: String,
+    messages: Vec<Value>,
+    force: Option<bool>,
+) -> Result<CompactionResult, String> {
+    let window = tokenizer::context_window(&app, &model).await;
+    compact(&app, &agent_id, messages, window.effective, force.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub fn get_context_memory(agent_id: String, db: State<'_, HistoryDb>) -> Result<Option<ContextMemory>, String> {
+    load_memory(&db, &agent_id)
+}
+
+#[tauri::command]
+pub fn clear_context_memory(agent_id: String, db: State<'_, HistoryDb>) -> Result<(), String> {
+    let conn = db.0.lock().unwrap();
+    conn.execute("DELETE FROM context_memory WHERE agent_id = ?1", params![agent_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_compaction_settings(state: State<'_, CompactionState>) -> CompactionSettings {
+    state.0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_compaction_settings(
+    app: AppHandle,
+    settings: CompactionSettings,
+    state: State<'_, CompactionState>,
+) -> Result<(), String> {
+    if !(0.1..=1.0).contains(&settings.trigger_ratio) {
+        return Err("trigger_ratio must be between 0.1 and 1.0".to_string());
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.0.lock().unwrap() = settings;
+    Ok(())
+}
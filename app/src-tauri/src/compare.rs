@@ -0,0 +1,242 @@
+// In src-tauri/src/compare.rs
+//
+// A/B model comparison. `compare_models` sends the same prompt to several
+// models (optionally on different backends) at once and streams every answer
+// back as "compare-chunk" events tagged with the model, so the UI can show them
+// side by side. When all are done the latency and token stats are stored in
+// the history DB and sent as "compare-finished".
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures::StreamExt;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::{incognito, llm, policy};
+
+const COMPARE_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompareTarget {
+    pub model: String,
+    // Another Ollama-compatible server; defaults to the one the proxy uses.
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct CompareChunk<'a> {
+    run_id: &'a str,
+    model: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompareResult {
+    pub run_id: String,
+    pub model: String,
+    pub base_url: String,
+    pub response: String,
+    pub first_token_ms: Option<u64>,
+    pub total_ms: u64,
+    pub prompt_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub tokens_per_second: Option<f64>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+async fn run_one(
+    app: &AppHandle,
+    run_id: &str,
+    target: &CompareTarget,
+    messages: &[Value],
+) -> CompareResult {
+    let base_url = target
+        .base_url
+        .clone()
+        .unwrap_or_else(|| llm::ollama_base_url(app))
+        .trim_end_matches('/')
+        .to_string();
+    let mut result = CompareResult {
+        run_id: run_id.to_string(),
+        model: target.model.clone(),
+        base_url: base_url.clone(),
+        created_at: Utc::now(),
+        ..Default::default()
+    };
+
+    if let Err(e) = policy::check_backend(&base_url) {
+        result.error = Some(e);
+        return result;
+    }
+
+    let started = Instant::now();
+    if let Err(e) = stream_chat(app, &base_url, target, messages, started, &mut result).await {
+        log::warn!("Comparison run {} failed for {}: {}", run_id, target.model, e);
+        result.error = Some(e);
+    }
+    result.total_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+async fn stream_chat(
+    app: &AppHandle,
+    base_url: &str,
+    target: &CompareTarget,
+    messages: &[Value],
+    started: Instant,
+    result: &mut CompareResult,
+) -> Result<(), String> {
+    let url = format!("{}/api/chat", base_url);
+    let response = llm::client()
+        .post(&url)
+        .timeout(COMPARE_TIMEOUT)
+        .json(&json!({ "model": target.model, "messages": messages, "stream": true }))
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("{} returned {}: {}", url, status, text));
+    }
+
+    // Ollama streams one JSON object per line.
+    let mut stream = response.bytes_stream();
+    let mut buffer = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+        while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let parsed: Value = serde_json::from_slice(&line).map_err(|e| format!("Invalid stream line: {}", e))?;
+            if let Some(error) = parsed["error"].as_str() {
+                return Err(error.to_string());
+            }
+
+            let content = parsed["message"]["content"].as_str().unwrap_or("");
+            if !content.is_empty() {
+                if result.first_token_ms.is_none() {
+                    result.first_token_ms = Some(started.elapsed().as_millis() as u64);
+                }
+                result.response.push_str(content);
+                let event = CompareChunk { run_id: &result.run_id, model: &result.model, content };
+                if let Err(e) = app.emit("compare-chunk", event) {
+                    log::error!("Failed to emit compare-chunk event: {}", e);
+                }
+            }
+
+            if parsed["done"].as_bool() == Some(true) {
+                result.prompt_tokens = parsed["prompt_eval_count"].as_u64();
+                result.output_tokens = parsed["eval_count"].as_u64();
+                result.tokens_per_second = match (result.output_tokens, parsed["eval_duration"].as_u64()) {
+                    (Some(tokens), Some(nanos)) if nanos > 0 => Some(tokens as f64 / (nanos as f64 / 1e9)),
+                    _ => None,
+                };
+            }
+        }
+    }
+    Ok(())
+}
+
+// While incognito the results are only sent to the UI.
+fn save_results(db: &HistoryDb, prompt: &str, results: &[CompareResult]) -> Result<(), String> {
+    if incognito::is_active() {
+        return Ok(());
+    }
+    let conn = db.0.lock().unwrap();
+    for r in results {
+        conn.execute(
+            "INSERT INTO comparisons (run_id, model, base_url, prompt, response, first_token_ms, total_ms,
+                                      prompt_tokens, output_tokens, tokens_per_second, error, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                r.run_id,
+                r.model,
+                r.base_url,
+                prompt,
+                r.response,
+                r.first_token_ms.map(|v| v as i64),
+                r.total_ms as i64,
+                r.prompt_tokens.map(|v| v as i64),
+                r.output_tokens.map(|v| v as i64),
+                r.tokens_per_second,
+                r.error,
+                r.created_at.timestamp_millis(),
+            ],
+        )
+        .map_err(|e| format!("Failed to store comparison: {}", e))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn compare_models(
+    app: AppHandle,
+    prompt: String,
+    models: Vec<CompareTarget>,
+    system: Option<String>,
+) -> Result<String, String> {
+    if models.len() < 2 {
+        return Err("Pick at least two models to compare".to_string());
+    }
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let mut messages = Vec::new();
+    if let Some(system) = system.filter(|s| !s.trim().is_empty()) {
+        messages.push(json!({ "role": "system", "content": system }));
+    }
+    messages.push(json!({ "role": "user", "content": prompt }));
+
+    log::info!("Starting comparison {} across {} models", run_id, models.len());
+    let id = run_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let runs = models.iter().map(|target| run_one(&app, &id, target, &messages));
+        let results = futures::future::join_all(runs).await;
+
+        if let Err(e) = save_results(&app.state::<HistoryDb>(), &prompt, &results) {
+            log::error!("{}", e);
+        }
+        if let Err(e) = app.emit("compare-finished", json!({ "run_id": id, "results": results })) {
+            log::error!("Failed to emit compare-finished event: {}", e);
+        }
+    });
+    Ok(run_id)
+}
+
+#[tauri::command]
+pub fn list_comparisons(limit: Option<u32>, db: State<'_, HistoryDb>) -> Result<Vec<CompareResult>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT run_id, model, base_url, response, first_token_ms, total_ms, prompt_tokens,
+                    output_tokens, tokens_per_second, error, created_at
+             FROM comparisons ORDER BY created_at DESC LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![limit.unwrap_or(100)], |row| {
+            Ok(CompareResult {
+                run_id: row.get(0)?,
+                model: row.get(1)?,
+                base_url: row.get(2)?,
+                response: row.get(3)?,
+                first_token_ms: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+                total_ms: row.get::<_, i64>(5)? as u64,
+                prompt_tokens: row.get::<_, Option<i64>>(6)?.map(|v| v as u64),
+                output_tokens: row.get::<_, Option<i64>>(7)?.map(|v| v as u64),
+                tokens_per_second: row.get(8)?,
+                error: row.get(9)?,
+                created_at: Utc.timestamp_millis_opt(row.get(10)?).single().unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
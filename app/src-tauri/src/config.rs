@@ -0,0 +1,415 @@
+// In src-tauri/src/config.rs
+//
+// `config.toml` for settings that belong to the process rather than to a
+// profile: where the server listens, which Ollama to use, how much to log.
+// It's read from the app data directory, or from `--config <path>`, and any
+// value can be overridden by an `OBSERVER_<SECTION>_<KEY>` environment
+// variable (e.g. `OBSERVER_SERVER_PORT=4000`, `OBSERVER_OLLAMA_URL=...`).
+//
+// The file is polled for changes and hot-reloaded: the Ollama URL and log
+// level apply immediately, while the listen address only takes effect on
+// restart, which the reload reports in `restart_required` (and in the
+// "config-reloaded" event) instead of silently ignoring it.
+//
+// Example:
+//
+//   [server]
+//   port = 3838
+//   bind = ["127.0.0.1", "::1", "192.168.1.20:4000"]
+//
+//   [ollama]
+//   url = "http://192.168.1.20:11434"
+//
+//   [upstream]
+//   pool_max_idle_per_host = 16
+//   keep_alive_secs = 90
+//   http2_prior_knowledge = false
+//
+//   [upstream.hosts]
+//   "gpu-box.internal" = "10.0.0.42"
+//
+//   [logging]
+//   level = "debug"
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{policy, storage, AppSettings};
+
+const CONFIG_FILE: &str = "config.toml";
+const ENV_PREFIX: &str = "OBSERVER_";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+static UPSTREAM: OnceLock<UpstreamConfig> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    // Listen on these instead of `host`: IPv4 or IPv6 addresses, optionally
+    // with their own port ("::1", "[::1]:4000", "192.168.1.20:4000").
+    pub bind: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 3838,
+            bind: Vec::new(),
+        }
+    }
+}
+
+impl ServerConfig {
+    // Every address to listen on, each parsed on its own so one typo doesn't
+    // hide the others.
+    pub fn addresses(&self) -> Vec<(String, Result<SocketAddr, String>)> {
+        let entries = if self.bind.is_empty() { std::slice::from_ref(&self.host) } else { &self.bind[..] };
+        entries
+            .iter()
+            .map(|entry| {
+                let entry = entry.trim();
+                let parsed = entry
+                    .parse::<SocketAddr>()
+                    .or_else(|_| {
+                        entry
+                            .trim_start_matches('[')
+                            .trim_end_matches(']')
+                            .parse::<IpAddr>()
+                            .map(|ip| SocketAddr::new(ip, self.port))
+                    })
+                    .map_err(|_| format!("'{}' isn't an IP address or address:port", entry));
+                (entry.to_string(), parsed)
+            })
+            .collect()
+    }
+}
+
+// How to reach a listening address in a browser; IPv6 needs brackets and
+// "any" addresses aren't reachable as such.
+pub fn server_url(addr: SocketAddr) -> String {
+    let ip = match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+    format!("http://{}", SocketAddr::new(ip, addr.port()))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OllamaConfig {
+    // None keeps whatever the launcher selects.
+    pub url: Option<String>,
+}
+
+// How the HTTP client that talks to Ollama (and everything else upstream)
+// reuses connections. HTTPS backends negotiate HTTP/2 on their own; plain
+// http:// ones only speak it with `http2_prior_knowledge`, which breaks
+// servers that don't support h2c, so it's off by default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpstreamConfig {
+    pub http2_prior_knowledge: bool,
+    pub pool_max_idle_per_host: usize,
+    // How long an idle pooled connection is kept; 0 closes them after use.
+    pub keep_alive_secs: u64,
+    // TCP and HTTP/2 pings that keep connections through NATs and proxies.
+    pub tcp_keepalive_secs: u64,
+    pub http2_keep_alive_interval_secs: u64,
+    pub connect_timeout_secs: u64,
+    // Hostname -> IP address, used instead of DNS for those names, like
+    // /etc/hosts but only for Observer.
+    pub hosts: BTreeMap<String, String>,
+}
+
+impl Default for UpstreamConfig {
+    fn default() -> Self {
+        Self {
+            http2_prior_knowledge: false,
+            pool_max_idle_per_host: 16,
+            keep_alive_secs: 90,
+            tcp_keepalive_secs: 60,
+            http2_keep_alive_interval_secs: 30,
+            connect_timeout_secs: 10,
+            hosts: BTreeMap::new(),
+        }
+    }
+}
+
+impl UpstreamConfig {
+    pub fn client(&self) -> reqwest::Client {
+        let mut builder = reqwest::Client::builder()
+            .pool_max_idle_per_host(if self.keep_alive_secs == 0 { 0 } else { self.pool_max_idle_per_host })
+            .pool_idle_timeout(Duration::from_secs(self.keep_alive_secs))
+            .connect_timeout(Duration::from_secs(self.connect_timeout_secs.max(1)))
+            .tcp_keepalive((self.tcp_keepalive_secs > 0).then(|| Duration::from_secs(self.tcp_keepalive_secs)))
+            .http2_adaptive_window(true);
+        if self.http2_keep_alive_interval_secs > 0 {
+            builder = builder
+                .http2_keep_alive_interval(Duration::from_secs(self.http2_keep_alive_interval_secs))
+                .http2_keep_alive_while_idle(true);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        for (host, ip) in &self.hosts {
+            match ip.trim().trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+                // Port 0 keeps the port from each request's URL.
+                Ok(ip) => builder = builder.resolve(host, SocketAddr::new(ip, 0)),
+                Err(_) => log::error!("Ignoring [upstream.hosts] entry {} = '{}': not an IP address", host, ip),
+            }
+        }
+        builder.build().unwrap_or_else(|e| {
+            log::error!("Invalid [upstream] settings, using defaults: {}", e);
+            reqwest::Client::new()
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub level: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self { level: "info".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub ollama: OllamaConfig,
+    pub upstream: UpstreamConfig,
+    pub logging: LoggingConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigStatus {
+    pub path: Option<String>,
+    pub config: AppConfig,
+    // Environment variables that overrode the file.
+    pub env_overrides: Vec<String>,
+    pub error: Option<String>,
+    // Settings changed since startup that need a restart to apply.
+    pub restart_required: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct ConfigState {
+    current: Mutex<AppConfig>,
+    // What the server was started with.
+    startup: Mutex<AppConfig>,
+    env_overrides: Mutex<Vec<String>>,
+    error: Mutex<Option<String>>,
+    modified: Mutex<Option<SystemTime>>,
+}
+
+// `--config <path>` / `--config=<path>`, else config.toml in app data.
+fn config_path(app: &AppHandle) -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    storage::root_dir(app).ok().map(|dir| dir.join(CONFIG_FILE))
+}
+
+fn parse_env_value(raw: &str) -> toml::Value {
+    format!("v = {}", raw)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+// Applies OBSERVER_* variables onto the parsed file. Sections are matched
+// against the known ones, so keys may contain underscores.
+fn apply_env(table: &mut toml::Table) -> Vec<String> {
+    let sections: Vec<String> = match toml::Value::try_from(AppConfig::default()) {
+        Ok(toml::Value::Table(defaults)) => defaults.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    let mut applied = Vec::new();
+    for (name, raw) in std::env::vars() {
+        let Some(rest) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let rest = rest.to_ascii_lowercase();
+        let Some((section, key)) = sections
+            .iter()
+            .find_map(|s| rest.strip_prefix(&format!("{}_", s)).map(|key| (s.clone(), key.to_string())))
+        else {
+            continue;
+        };
+        let section = table
+            .entry(section)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if let Some(section) = section.as_table_mut() {
+            section.insert(key, parse_env_value(&raw));
+            applied.push(name);
+        }
+    }
+    applied.sort();
+    applied
+}
+
+// The effective config; errors leave the defaults plus env overrides.
+fn load(path: Option<&PathBuf>) -> (AppConfig, Vec<String>, Option<String>) {
+    let mut error = None;
+    let mut table = match path.map(std::fs::read_to_string) {
+        Some(Ok(text)) => text.parse::<toml::Table>().unwrap_or_else(|e| {
+            error = Some(format!("Invalid config.toml: {}", e));
+            toml::Table::new()
+        }),
+        Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => {
+            error = Some(format!("Failed to read config.toml: {}", e));
+            toml::Table::new()
+        }
+        _ => toml::Table::new(),
+    };
+    let overrides = apply_env(&mut table);
+    let config = toml::Value::Table(table).try_into::<AppConfig>().unwrap_or_else(|e| {
+        error = Some(format!("Invalid value in config.toml: {}", e));
+        AppConfig::default()
+    });
+    (config, overrides, error)
+}
+
+fn apply(app: &AppHandle, config: &AppConfig, previous: Option<&AppConfig>) {
+    if let Some(url) = config.ollama.url.as_ref().filter(|_| policy::pinned_ollama_url().is_none()) {
+        if !previous.is_some_and(|p| p.ollama.url.as_ref() == Some(url)) {
+            log::info!("Using Ollama at {} (from config)", url);
+            *app.state::<AppSettings>().ollama_url.lock().unwrap() = Some(url.clone());
+        }
+    }
+    match config.logging.level.parse::<log::LevelFilter>() {
+        Ok(level) => log::set_max_level(level),
+        Err(_) => log::warn!("Unknown log level '{}' in config", config.logging.level),
+    }
+}
+
+fn modified(path: Option<&PathBuf>) -> Option<SystemTime> {
+    path.and_then(|p| std::fs::metadata(p).ok()).and_then(|m| m.modified().ok())
+}
+
+// The shared upstream client, built from the [upstream] settings read at
+// startup.
+pub fn upstream_client() -> reqwest::Client {
+    UPSTREAM.get().cloned().unwrap_or_default().client()
+}
+
+pub fn current(app: &AppHandle) -> AppConfig {
+    app.state::<ConfigState>().current.lock().unwrap().clone()
+}
+
+pub fn status(app: &AppHandle) -> ConfigStatus {
+    let state = app.state::<ConfigState>();
+    let config = state.current.lock().unwrap().clone();
+    let startup = state.startup.lock().unwrap().clone();
+    let mut restart_required = Vec::new();
+    if config.server.host != startup.server.host {
+        restart_required.push("server.host".to_string());
+    }
+    if config.server.port != startup.server.port {
+        restart_required.push("server.port".to_string());
+    }
+    if config.server.bind != startup.server.bind {
+        restart_required.push("server.bind".to_string());
+    }
+    if config.upstream != startup.upstream {
+        restart_required.push("upstream".to_string());
+    }
+    ConfigStatus {
+        path: config_path(app).map(|p| p.to_string_lossy().to_string()),
+        config,
+        env_overrides: state.env_overrides.lock().unwrap().clone(),
+        error: state.error.lock().unwrap().clone(),
+        restart_required,
+    }
+}
+
+fn reload(app: &AppHandle) -> ConfigStatus {
+    let path = config_path(app);
+    let state = app.state::<ConfigState>();
+    let (config, overrides, error) = load(path.as_ref());
+    if let Some(error) = &error {
+        log::error!("{}", error);
+    }
+    let previous = state.current.lock().unwrap().clone();
+    apply(app, &config, Some(&previous));
+    *state.current.lock().unwrap() = config;
+    *state.env_overrides.lock().unwrap() = overrides;
+    *state.error.lock().unwrap() = error;
+    *state.modified.lock().unwrap() = modified(path.as_ref());
+
+    let status = status(app);
+    if !status.restart_required.is_empty() {
+        log::warn!(
+            "config.toml changed {}; restart Observer for this to take effect",
+            status.restart_required.join(", ")
+        );
+    }
+    if let Err(e) = app.emit("config-reloaded", &status) {
+        log::error!("Failed to emit config-reloaded event: {}", e);
+    }
+    status
+}
+
+// Loads the config before the server starts.
+pub fn init(app: &AppHandle) {
+    let path = config_path(app);
+    let (config, overrides, error) = load(path.as_ref());
+    if let Some(error) = &error {
+        log::error!("{}", error);
+    }
+    if !overrides.is_empty() {
+        log::info!("Config overridden by {}", overrides.join(", "));
+    }
+    apply(app, &config, None);
+    let _ = UPSTREAM.set(config.upstream.clone());
+    let state = app.state::<ConfigState>();
+    *state.startup.lock().unwrap() = config.clone();
+    *state.current.lock().unwrap() = config;
+    *state.env_overrides.lock().unwrap() = overrides;
+    *state.error.lock().unwrap() = error;
+    *state.modified.lock().unwrap() = modified(path.as_ref());
+}
+
+pub fn start_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let seen = *app.state::<ConfigState>().modified.lock().unwrap();
+            if modified(config_path(&app).as_ref()) != seen {
+                log::info!("config.toml changed, reloading");
+                reload(&app);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_config(app: AppHandle) -> ConfigStatus {
+    status(&app)
+}
+
+#[tauri::command]
+pub fn reload_config(app: AppHandle) -> ConfigStatus {
+    reload(&app)
+}
@@ -0,0 +1,224 @@
+// In src-tauri/src/config_archive.rs
+//
+// Settings export/import and schema migrations. `export_config` writes the
+// active profile's settings files, agents and channel configs (email,
+// calendar, feeds, MQTT, ...) into one JSON archive; `import_config` writes
+// them back, migrating archives from older versions first. Secrets live in
+// the keyring and are never exported, so the import report lists the
+// credentials to re-enter. Runtime state (pairing tokens, poll cursors)
+// isn't exported either.
+//
+// The same migrations run over the data directory at startup (see
+// `migrate_data_dir`), so settings files survive upgrades whose schema
+// changes go beyond what `#[serde(default)]` absorbs. Imported settings
+// take effect after a restart, when the modules load them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::storage;
+
+const FORMAT: &str = "observer-config";
+// Bump together with a new entry in `MIGRATIONS`.
+pub const CONFIG_VERSION: u32 = 1;
+const VERSION_FILE: &str = "config_version.json";
+
+// Files in the archive, and the credentials each one needs re-entered.
+const CONFIG_FILES: &[(&str, Option<&str>)] = &[
+    ("access_log.json", None),
+    ("activity.json", None),
+    ("adapters.json", None),
+    ("agent_tests.json", None),
+    ("agents.json", None),
+    ("attachments.json", None),
+    ("backends.json", None),
+    ("backup_schedule.json", None),
+    ("break_schedules.json", None),
+    ("calendar.json", Some("calendar account passwords")),
+    ("captions.json", None),
+    ("catalog.json", None),
+    ("compaction.json", None),
+    ("email.json", Some("the IMAP password")),
+    ("evaluation.json", None),
+    ("fallback.json", None),
+    ("features.json", None),
+    ("feeds.json", None),
+    ("file_scopes.json", None),
+    ("focus.json", None),
+    ("git.json", None),
+    ("imaging.json", None),
+    ("injection.json", None),
+    ("input_control.json", None),
+    ("locality.json", None),
+    ("memory.json", None),
+    ("model_downloads.json", Some("the registry mirror password")),
+    ("model_share.json", None),
+    ("moderation.json", None),
+    ("ocr.json", None),
+    ("offline.json", None),
+    ("parameter_presets.json", None),
+    ("mqtt.json", Some("the MQTT password")),
+    ("power_profiles.json", None),
+    ("prompt_templates.json", None),
+    ("race.json", None),
+    ("redaction.json", None),
+    ("replay.json", None),
+    ("response_cache.json", None),
+    ("self_monitor.json", None),
+    ("sound_events.json", None),
+    ("summary.json", None),
+    ("token_budgets.json", None),
+    ("transcript_index.json", None),
+    ("transcription.json", None),
+    ("tunnel.json", None),
+    ("ui_elements.json", None),
+    ("usage_costs.json", None),
+    ("vector_store.json", None),
+    ("vram_manager.json", None),
+];
+
+type Files = BTreeMap<String, Value>;
+
+// Upgrades files from version `index` to `index + 1`.
+type Migration = fn(&mut Files);
+
+// 0 is everything written before settings were versioned; its layout is
+// what version 1 still uses.
+const MIGRATIONS: &[Migration] = &[|_files| {}];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Archive {
+    format: String,
+    version: u32,
+    app_version: String,
+    exported_at: DateTime<Utc>,
+    files: Files,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VersionMarker {
+    version: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportReport {
+    pub path: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub from_version: u32,
+    pub imported: Vec<String>,
+    // Unknown files in the archive, left alone.
+    pub skipped: Vec<String>,
+    pub reenter_secrets: Vec<String>,
+    pub restart_required: bool,
+}
+
+fn migrate(files: &mut Files, from: u32) -> Result<(), String> {
+    if from > CONFIG_VERSION {
+        return Err(format!(
+            "These settings are from a newer version of Observer (config version {}, this app reads up to {})",
+            from, CONFIG_VERSION
+        ));
+    }
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        log::info!("Migrating settings from config version {} to {}", version, version + 1);
+        migration(files);
+    }
+    Ok(())
+}
+
+fn read_files(app: &AppHandle) -> Result<Files, String> {
+    let mut files = Files::new();
+    for (name, _) in CONFIG_FILES {
+        let path = storage::data_path(app, name)?;
+        let Ok(bytes) = std::fs::read(&path) else {
+            continue;
+        };
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => {
+                files.insert(name.to_string(), value);
+            }
+            Err(e) => log::warn!("Skipping unreadable {:?}: {}", path, e),
+        }
+    }
+    Ok(files)
+}
+
+fn write_files(app: &AppHandle, files: &Files) -> Result<(), String> {
+    for (name, value) in files {
+        storage::save_json(app, name, value)?;
+    }
+    Ok(())
+}
+
+// Brings the data directory's settings up to `CONFIG_VERSION`. Runs before
+// any module loads its settings.
+pub fn migrate_data_dir(app: &AppHandle) -> Result<(), String> {
+    let marker: VersionMarker = storage::load_json(app, VERSION_FILE);
+    if marker.version == CONFIG_VERSION {
+        return Ok(());
+    }
+    let mut files = read_files(app)?;
+    migrate(&mut files, marker.version)?;
+    write_files(app, &files)?;
+    storage::save_json(app, VERSION_FILE, &VersionMarker { version: CONFIG_VERSION })
+}
+
+#[tauri::command]
+pub fn export_config(app: AppHandle, path: String) -> Result<ExportReport, String> {
+    let files = read_files(&app)?;
+    let archive = Archive {
+        format: FORMAT.to_string(),
+        version: CONFIG_VERSION,
+        app_version: app.package_info().version.to_string(),
+        exported_at: Utc::now(),
+        files,
+    };
+    let bytes = serde_json::to_vec_pretty(&archive).map_err(|e| e.to_string())?;
+    std::fs::write(&path, bytes).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    log::info!("Exported {} settings files to {}", archive.files.len(), path);
+    Ok(ExportReport {
+        path,
+        files: archive.files.into_keys().collect(),
+    })
+}
+
+#[tauri::command]
+pub fn import_config(app: AppHandle, path: String) -> Result<ImportReport, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let raw: Map<String, Value> =
+        serde_json::from_slice(&bytes).map_err(|e| format!("{} is not a settings archive: {}", path, e))?;
+    if raw.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err(format!("{} is not an Observer settings archive", path));
+    }
+    let archive: Archive =
+        serde_json::from_value(Value::Object(raw)).map_err(|e| format!("Damaged settings archive: {}", e))?;
+
+    let from_version = archive.version;
+    let (mut files, skipped): (Files, Files) = archive
+        .files
+        .into_iter()
+        .partition(|(name, _)| CONFIG_FILES.iter().any(|(known, _)| known == name));
+    migrate(&mut files, from_version)?;
+    write_files(&app, &files)?;
+
+    let reenter_secrets = CONFIG_FILES
+        .iter()
+        .filter(|(name, _)| files.contains_key(*name))
+        .filter_map(|(_, secret)| secret.map(str::to_string))
+        .collect();
+    log::info!("Imported {} settings files from {} (config version {})", files.len(), path, from_version);
+    Ok(ImportReport {
+        from_version,
+        imported: files.into_keys().collect(),
+        skipped: skipped.into_keys().collect(),
+        reenter_secrets,
+        restart_required: true,
+    })
+}
@@ -0,0 +1,150 @@
+// In src-tauri/src/control.rs
+//
+// The HTTP side of `observerctl` (see app/observerctl): list agents, start
+// and stop them, and read the app log, all without the webview.
+//
+//   GET  /observer/agents
+//   POST /observer/agents/:id/run
+//   POST /observer/agents/:id/stop
+//   GET  /observer/logs?lines=100&follow=1
+//   GET  /observer/logs/query?level=warn&module=...&follow=1 (log_store.rs)
+//
+// Agents run in the frontend, so run and stop go through the same queue as
+// observer:// links (see deep_link.rs). Model pulls need nothing here; the
+// CLI uses the proxy's `/api/pull`.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State as AxumState},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path as FsPath, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::agents::{AgentDefinition, AgentRegistry};
+use crate::deep_link::{self, DeepLinkAction};
+use crate::features::{self, Feature};
+use crate::{portable, AppState};
+
+const DEFAULT_LINES: usize = 100;
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Deserialize)]
+pub struct LogParams {
+    lines: Option<usize>,
+    follow: Option<String>,
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+// The file tauri-plugin-log writes to (see portable::log_targets).
+fn log_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = match portable::log_dir() {
+        Some(dir) => dir,
+        None => app
+            .path()
+            .app_log_dir()
+            .map_err(|e| format!("Failed to resolve the log directory: {}", e))?,
+    };
+    Ok(dir.join(format!("{}.log", app.package_info().name)))
+}
+
+// The last `lines` lines of the file, and where it ended.
+fn tail(path: &FsPath, lines: usize) -> std::io::Result<(String, u64)> {
+    let bytes = std::fs::read(path)?;
+    let contents = String::from_utf8_lossy(&bytes);
+    let skip = contents.lines().count().saturating_sub(lines);
+    let text: String = contents.lines().skip(skip).flat_map(|line| [line, "\n"]).collect();
+    Ok((text, bytes.len() as u64))
+}
+
+// Whatever was written past `offset`, moving it along.
+fn read_appended(path: &FsPath, offset: &mut u64) -> std::io::Result<Option<String>> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    // Rotated or truncated: start from the top of the new file.
+    if len < *offset {
+        *offset = 0;
+    }
+    if len == *offset {
+        return Ok(None);
+    }
+    file.seek(SeekFrom::Start(*offset))?;
+    let mut appended = Vec::new();
+    file.read_to_end(&mut appended)?;
+    *offset += appended.len() as u64;
+    Ok(Some(String::from_utf8_lossy(&appended).into_owned()))
+}
+
+pub async fn agents_handler(AxumState(state): AxumState<AppState>) -> Json<Vec<AgentDefinition>> {
+    Json(state.app_handle.state::<AgentRegistry>().list())
+}
+
+fn agent_action(state: &AppState, id: String, run: bool) -> Response {
+    if state.app_handle.state::<AgentRegistry>().get(&id).is_none() {
+        return error_response(StatusCode::NOT_FOUND, &format!("No agent '{}'", id));
+    }
+    if run {
+        if let Err(e) = features::ensure(Feature::Agents) {
+            return error_response(StatusCode::SERVICE_UNAVAILABLE, &e);
+        }
+    }
+    log::info!("{} agent '{}' over HTTP", if run { "Starting" } else { "Stopping" }, id);
+    let action = if run {
+        DeepLinkAction::RunAgent { id: id.clone() }
+    } else {
+        DeepLinkAction::StopAgent { id: id.clone() }
+    };
+    deep_link::queue(&state.app_handle, action);
+    (StatusCode::ACCEPTED, Json(json!({ "id": id, "queued": true }))).into_response()
+}
+
+pub async fn run_agent_handler(AxumState(state): AxumState<AppState>, Path(id): Path<String>) -> Response {
+    agent_action(&state, id, true)
+}
+
+pub async fn stop_agent_handler(AxumState(state): AxumState<AppState>, Path(id): Path<String>) -> Response {
+    agent_action(&state, id, false)
+}
+
+// Plain text; with `follow` the response stays open and new lines are
+// appended as they're written, like `tail -f`.
+pub async fn logs_handler(AxumState(state): AxumState<AppState>, Query(params): Query<LogParams>) -> Response {
+    let path = match log_file(&state.app_handle) {
+        Ok(path) => path,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e),
+    };
+    let (text, mut offset) = match tail(&path, params.lines.unwrap_or(DEFAULT_LINES)) {
+        Ok(tail) => tail,
+        Err(e) => return error_response(StatusCode::NOT_FOUND, &format!("Failed to read {:?}: {}", path, e)),
+    };
+    let follow = matches!(params.follow.as_deref(), Some("1" | "true"));
+    let builder = Response::builder().header(header::CONTENT_TYPE, "text/plain; charset=utf-8");
+    if !follow {
+        return builder.body(Body::from(text)).unwrap();
+    }
+
+    let body = async_stream::stream! {
+        yield Ok::<_, std::io::Error>(text);
+        loop {
+            tokio::time::sleep(FOLLOW_INTERVAL).await;
+            match read_appended(&path, &mut offset) {
+                Ok(Some(appended)) => yield Ok(appended),
+                Ok(None) => {}
+                Err(e) => {
+                    yield Err(e);
+                    break;
+                }
+            }
+        }
+    };
+    builder.body(Body::from_stream(body)).unwrap()
+}
@@ -0,0 +1,409 @@
+// In src-tauri/src/conversations.rs
+//
+// Chat conversations stored as message trees. Every message points at its
+// parent, so answering or regenerating from an earlier message starts a new
+// branch instead of overwriting what came after it. A branch is identified by
+// its leaf message; `get_branch` walks back to the root.
+//
+// The web app creates, appends to and forks conversations over HTTP:
+//
+//   POST /conversations                {title?}
+//   POST /conversations/:id/messages   {parent_id?, role, content, model?}
+//   POST /conversations/fork           {message_id, title?}
+
+use axum::{
+    extract::{Path as AxumPath, Query, State as AxumState},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::{attachments, incognito, AppState};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Conversation {
+    pub id: i64,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Message {
+    pub id: i64,
+    pub conversation_id: i64,
+    pub parent_id: Option<i64>,
+    pub role: String,
+    pub content: String,
+    pub model: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Branch {
+    pub leaf_id: i64,
+    // Message where this branch splits off from another one, if any.
+    pub fork_point: Option<i64>,
+    pub length: usize,
+    pub preview: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffChunk {
+    pub tag: &'static str,
+    pub text: String,
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis).single().unwrap_or_default()
+}
+
+fn row_to_message(row: &rusqlite::Row) -> rusqlite::Result<Message> {
+    Ok(Message {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        parent_id: row.get(2)?,
+        role: row.get(3)?,
+        content: row.get(4)?,
+        model: row.get(5)?,
+        created_at: from_millis(row.get(6)?),
+    })
+}
+
+const MESSAGE_COLUMNS: &str = "id, conversation_id, parent_id, role, content, model, created_at";
+
+fn get_message(conn: &Connection, id: i64) -> Result<Option<Message>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM messages WHERE id = ?1", MESSAGE_COLUMNS),
+        params![id],
+        row_to_message,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn conversation_messages(conn: &Connection, conversation_id: i64) -> Result<Vec<Message>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM messages WHERE conversation_id = ?1 ORDER BY id",
+            MESSAGE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![conversation_id], row_to_message)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+// Root-to-leaf path ending at `leaf_id`.
+pub fn branch_path(conn: &Connection, leaf_id: i64) -> Result<Vec<Message>, String> {
+    let mut path = Vec::new();
+    let mut next = Some(leaf_id);
+    while let Some(id) = next {
+        let message = get_message(conn, id)?.ok_or_else(|| format!("Message {} not found", id))?;
+        next = message.parent_id;
+        path.push(message);
+    }
+    path.reverse();
+    Ok(path)
+}
+
+pub fn list_branches_in(conn: &Connection, conversation_id: i64) -> Result<Vec<Branch>, String> {
+    let messages = conversation_messages(conn, conversation_id)?;
+    let child_count = |id: i64| messages.iter().filter(|m| m.parent_id == Some(id)).count();
+
+    let mut branches = Vec::new();
+    for leaf in messages.iter().filter(|m| child_count(m.id) == 0) {
+        let path = branch_path(conn, leaf.id)?;
+        // The nearest ancestor with more than one child is where this branch forked.
+        let fork_point = path
+            .iter()
+            .rev()
+            .skip(1)
+            .find(|m| child_count(m.id) > 1)
+            .map(|m| m.id);
+        branches.push(Branch {
+            leaf_id: leaf.id,
+            fork_point,
+            length: path.len(),
+            preview: leaf.content.chars().take(120).collect(),
+            updated_at: leaf.created_at,
+        });
+    }
+    branches.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(branches)
+}
+
+pub fn diff(a: &str, b: &str) -> Vec<DiffChunk> {
+    let mut chunks: Vec<DiffChunk> = Vec::new();
+    for change in TextDiff::from_words(a, b).iter_all_changes() {
+        let tag = match change.tag() {
+            ChangeTag::Equal => "equal",
+            ChangeTag::Delete => "delete",
+            ChangeTag::Insert => "insert",
+        };
+        // Merge consecutive words with the same tag.
+        match chunks.last_mut() {
+            Some(last) if last.tag == tag => last.text.push_str(change.value()),
+            _ => chunks.push(DiffChunk { tag, text: change.value().to_string() }),
+        }
+    }
+    chunks
+}
+
+fn emit_changed(app: &AppHandle, conversation_id: i64) {
+    if let Err(e) = app.emit("conversation-changed", conversation_id) {
+        log::error!("Failed to emit conversation-changed event: {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn create_conversation(title: Option<String>, db: State<'_, HistoryDb>) -> Result<Conversation, String> {
+    incognito::ensure_persistent()?;
+    let conn = db.0.lock().unwrap();
+    let now = Utc::now();
+    let title = title.unwrap_or_else(|| "New conversation".to_string());
+    conn.execute(
+        "INSERT INTO conversations (title, created_at, updated_at) VALUES (?1, ?2, ?2)",
+        params![title, now.timestamp_millis()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(Conversation {
+        id: conn.last_insert_rowid(),
+        title,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+#[tauri::command]
+pub fn list_conversations(db: State<'_, HistoryDb>) -> Result<Vec<Conversation>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare("SELECT id, title, created_at, updated_at FROM conversations ORDER BY updated_at DESC")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(Conversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                created_at: from_millis(row.get(2)?),
+                updated_at: from_millis(row.get(3)?),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+// Appends a message under `parent_id`. Using a parent that already has
+// children (e.g. "try again" on an answer's prompt) starts a new branch.
+#[tauri::command]
+pub fn append_message(
+    app: AppHandle,
+    conversation_id: i64,
+    parent_id: Option<i64>,
+    role: String,
+    content: String,
+    model: Option<String>,
+    db: State<'_, HistoryDb>,
+) -> Result<Message, String> {
+    incognito::ensure_persistent()?;
+    let message = {
+        let conn = db.0.lock().unwrap();
+        if let Some(parent_id) = parent_id {
+            let parent = get_message(&conn, parent_id)?.ok_or_else(|| format!("Message {} not found", parent_id))?;
+            if parent.conversation_id != conversation_id {
+                return Err(format!("Message {} belongs to another conversation", parent_id));
+            }
+        }
+
+        let now = Utc::now().timestamp_millis();
+        conn.execute(
+            "INSERT INTO messages (conversation_id, parent_id, role, content, model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![conversation_id, parent_id, role, content, model, now],
+        )
+        .map_err(|e| format!("Failed to store message: {}", e))?;
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "UPDATE conversations SET updated_at = ?2 WHERE id = ?1",
+            params![conversation_id, now],
+        )
+        .map_err(|e| e.to_string())?;
+        get_message(&conn, id)?.ok_or("Stored message disappeared")?
+    };
+    emit_changed(&app, conversation_id);
+    Ok(message)
+}
+
+// Copies the path up to `message_id` into a new conversation.
+#[tauri::command]
+pub fn fork_conversation(
+    app: AppHandle,
+    message_id: i64,
+    title: Option<String>,
+    db: State<'_, HistoryDb>,
+) -> Result<Conversation, String> {
+    incognito::ensure_persistent()?;
+    let mut conn = db.0.lock().unwrap();
+    let path = branch_path(&conn, message_id)?;
+    let now = Utc::now();
+    let title = title.unwrap_or_else(|| {
+        let first = path.first().map(|m| m.content.chars().take(40).collect::<String>()).unwrap_or_default();
+        format!("Fork of {}", first)
+    });
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO conversations (title, created_at, updated_at) VALUES (?1, ?2, ?2)",
+        params![title, now.timestamp_millis()],
+    )
+    .map_err(|e| e.to_string())?;
+    let conversation_id = tx.last_insert_rowid();
+
+    let mut parent_id: Option<i64> = None;
+    for message in &path {
+        tx.execute(
+            "INSERT INTO messages (conversation_id, parent_id, role, content, model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                conversation_id,
+                parent_id,
+                message.role,
+                message.content,
+                message.model,
+                message.created_at.timestamp_millis()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        parent_id = Some(tx.last_insert_rowid());
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    drop(conn);
+
+    emit_changed(&app, conversation_id);
+    Ok(Conversation {
+        id: conversation_id,
+        title,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+#[tauri::command]
+pub fn get_branch(leaf_id: i64, db: State<'_, HistoryDb>) -> Result<Vec<Message>, String> {
+    branch_path(&db.0.lock().unwrap(), leaf_id)
+}
+
+#[tauri::command]
+pub fn list_branches(conversation_id: i64, db: State<'_, HistoryDb>) -> Result<Vec<Branch>, String> {
+    list_branches_in(&db.0.lock().unwrap(), conversation_id)
+}
+
+#[tauri::command]
+pub fn diff_messages(a: i64, b: i64, db: State<'_, HistoryDb>) -> Result<Vec<DiffChunk>, String> {
+    let conn = db.0.lock().unwrap();
+    let a = get_message(&conn, a)?.ok_or_else(|| format!("Message {} not found", a))?;
+    let b = get_message(&conn, b)?.ok_or_else(|| format!("Message {} not found", b))?;
+    Ok(diff(&a.content, &b.content))
+}
+
+#[tauri::command]
+pub fn delete_conversation(app: AppHandle, conversation_id: i64, db: State<'_, HistoryDb>) -> Result<(), String> {
+    {
+        let conn = db.0.lock().unwrap();
+        conn.execute("DELETE FROM messages WHERE conversation_id = ?1", params![conversation_id])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM conversations WHERE id = ?1", params![conversation_id])
+            .map_err(|e| e.to_string())?;
+        attachments::release_conversation(&app, &conn, conversation_id)?;
+    }
+    emit_changed(&app, conversation_id);
+    Ok(())
+}
+
+pub async fn branches_handler(
+    AxumState(state): AxumState<AppState>,
+    AxumPath(conversation_id): AxumPath<i64>,
+) -> Result<Json<Vec<Branch>>, (StatusCode, String)> {
+    let db = state.app_handle.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    list_branches_in(&conn, conversation_id)
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffParams {
+    a: i64,
+    b: i64,
+}
+
+pub async fn diff_handler(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<DiffParams>,
+) -> Result<Json<Vec<DiffChunk>>, (StatusCode, String)> {
+    diff_messages(params.a, params.b, state.app_handle.state::<HistoryDb>())
+        .map(Json)
+        .map_err(|e| (StatusCode::NOT_FOUND, e))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct NewConversation {
+    title: Option<String>,
+}
+
+pub async fn create_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<NewConversation>,
+) -> Result<Json<Conversation>, (StatusCode, String)> {
+    create_conversation(input.title, state.app_handle.state::<HistoryDb>())
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewMessage {
+    #[serde(default)]
+    parent_id: Option<i64>,
+    role: String,
+    content: String,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+pub async fn append_handler(
+    AxumState(state): AxumState<AppState>,
+    AxumPath(conversation_id): AxumPath<i64>,
+    Json(input): Json<NewMessage>,
+) -> Result<Json<Message>, (StatusCode, String)> {
+    let app = state.app_handle.clone();
+    let db = state.app_handle.state::<HistoryDb>();
+    append_message(app, conversation_id, input.parent_id, input.role, input.content, input.model, db)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForkParams {
+    message_id: i64,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+pub async fn fork_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<ForkParams>,
+) -> Result<Json<Conversation>, (StatusCode, String)> {
+    let app = state.app_handle.clone();
+    fork_conversation(app, input.message_id, input.title, state.app_handle.state::<HistoryDb>())
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
@@ -0,0 +1,424 @@
+// In src-tauri/src/dataset.rs
+//
+// Fine-tuning datasets from the user's own interactions. `export_dataset`
+// writes one JSON line per example, either ShareGPT style
+//
+//   {"conversations": [{"from": "system" | "human" | "gpt", "value": "..."}]}
+//
+// or in OpenAI's fine-tuning format
+//
+//   {"messages": [{"role": "system" | "user" | "assistant", "content": "..."}]}
+//
+// Every branch of a chosen conversation is an example (see conversations.rs).
+// An agent run is the agent's system prompt, the observation it was given
+// and the output it produced, paired up from the history in time order.
+// Text goes through the redaction settings (see redact.rs) unless the
+// export asks for it not to.
+//
+// Agent outputs can also be rated, thumbs up (1) or down (-1), optionally
+// with a corrected answer: `set_feedback` or `PUT /history/:id/feedback`.
+// `build_dataset` exports only the rated runs: those rated at least
+// `min_rating`, plus, when `include_corrections` is set, those with a
+// correction, which then replaces the model's answer.
+
+use axum::{
+    extract::{Path as AxumPath, State as AxumState},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Write;
+use tauri::{AppHandle, Manager, State};
+
+use crate::agents::AgentRegistry;
+use crate::conversations;
+use crate::history::{HistoryDb, KIND_AGENT_OUTPUT, KIND_OBSERVATION};
+use crate::redact::{self, RedactionSettings};
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub enum DatasetFormat {
+    #[default]
+    #[serde(rename = "sharegpt")]
+    ShareGpt,
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DatasetExportRequest {
+    pub path: String,
+    #[serde(default)]
+    pub format: DatasetFormat,
+    #[serde(default)]
+    pub conversation_ids: Vec<i64>,
+    #[serde(default)]
+    pub agent_ids: Vec<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    // Redaction is on unless this is explicitly false.
+    #[serde(default)]
+    pub redact: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetExportReport {
+    pub path: String,
+    pub examples: usize,
+    // Branches without an answer and outputs without an observation.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedbackInput {
+    // 1 for thumbs up, -1 for thumbs down.
+    pub rating: i8,
+    #[serde(default)]
+    pub correction: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Feedback {
+    pub entry_id: i64,
+    pub rating: i8,
+    pub correction: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DatasetFilter {
+    pub path: String,
+    #[serde(default)]
+    pub format: DatasetFormat,
+    // Empty means every agent.
+    #[serde(default)]
+    pub agent_ids: Vec<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default = "default_min_rating")]
+    pub min_rating: i8,
+    #[serde(default = "default_true")]
+    pub include_corrections: bool,
+    #[serde(default)]
+    pub redact: Option<bool>,
+}
+
+fn default_min_rating() -> i8 {
+    1
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// One turn, with OpenAI's role names.
+struct Turn {
+    role: &'static str,
+    content: String,
+}
+
+fn role(role: &str) -> Option<&'static str> {
+    match role {
+        "system" => Some("system"),
+        "user" => Some("user"),
+        "assistant" => Some("assistant"),
+        _ => None,
+    }
+}
+
+// Trailing prompts without an answer teach nothing; an example must end
+// with the assistant.
+fn trim_to_answer(mut turns: Vec<Turn>) -> Option<Vec<Turn>> {
+    while turns.last().is_some_and(|turn| turn.role != "assistant") {
+        turns.pop();
+    }
+    (!turns.is_empty()).then_some(turns)
+}
+
+fn to_line(format: DatasetFormat, turns: &[Turn], redaction: Option<&RedactionSettings>) -> Value {
+    let text = |content: &str| match redaction {
+        Some(settings) => redact::redact(settings, content),
+        None => content.to_string(),
+    };
+    match format {
+        DatasetFormat::ShareGpt => {
+            let conversations: Vec<Value> = turns
+                .iter()
+                .map(|turn| {
+                    let from = match turn.role {
+                        "user" => "human",
+                        "assistant" => "gpt",
+                        other => other,
+                    };
+                    json!({ "from": from, "value": text(&turn.content) })
+                })
+                .collect();
+            json!({ "conversations": conversations })
+        }
+        DatasetFormat::OpenAi => {
+            let messages: Vec<Value> =
+                turns.iter().map(|turn| json!({ "role": turn.role, "content": text(&turn.content) })).collect();
+            json!({ "messages": messages })
+        }
+    }
+}
+
+fn conversation_examples(conn: &Connection, conversation_id: i64) -> Result<Vec<Option<Vec<Turn>>>, String> {
+    conversations::list_branches_in(conn, conversation_id)?
+        .iter()
+        .map(|branch| {
+            let turns = conversations::branch_path(conn, branch.leaf_id)?
+                .into_iter()
+                .filter_map(|message| Some(Turn { role: role(&message.role)?, content: message.content }))
+                .collect();
+            Ok(trim_to_answer(turns))
+        })
+        .collect()
+}
+
+// (system prompt, observation, output) runs of one agent, oldest first.
+fn agent_examples(
+    conn: &Connection,
+    agent_id: &str,
+    system_prompt: Option<&str>,
+    request: &DatasetExportRequest,
+) -> Result<Vec<Option<Vec<Turn>>>, String> {
+    let since = request.since.map(|t| t.timestamp_millis()).unwrap_or(i64::MIN);
+    let until = request.until.map(|t| t.timestamp_millis()).unwrap_or(i64::MAX);
+    let mut stmt = conn
+        .prepare(
+            "SELECT kind, content FROM entries
+             WHERE agent_id = ?1 AND kind IN (?2, ?3) AND created_at >= ?4 AND created_at <= ?5
+             ORDER BY created_at, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![agent_id, KIND_OBSERVATION, KIND_AGENT_OUTPUT, since, until], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut examples = Vec::new();
+    let mut observation: Option<String> = None;
+    for row in rows {
+        let (kind, content) = row.map_err(|e| e.to_string())?;
+        if kind == KIND_OBSERVATION {
+            observation = Some(content);
+            continue;
+        }
+        let Some(input) = observation.take() else {
+            examples.push(None);
+            continue;
+        };
+        let mut turns = Vec::new();
+        if let Some(system) = system_prompt.filter(|s| !s.trim().is_empty()) {
+            turns.push(Turn { role: "system", content: system.to_string() });
+        }
+        turns.push(Turn { role: "user", content: input });
+        turns.push(Turn { role: "assistant", content });
+        examples.push(Some(turns));
+    }
+    Ok(examples)
+}
+
+fn write_examples(
+    path: &str,
+    format: DatasetFormat,
+    examples: &[Option<Vec<Turn>>],
+    redaction: Option<&RedactionSettings>,
+) -> Result<usize, String> {
+    let mut file = std::io::BufWriter::new(
+        std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?,
+    );
+    let mut written = 0;
+    for turns in examples.iter().flatten() {
+        let line = to_line(format, turns, redaction);
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        written += 1;
+    }
+    file.flush().map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(written)
+}
+
+fn save_feedback(conn: &Connection, entry_id: i64, input: FeedbackInput) -> Result<Feedback, String> {
+    if input.rating != 1 && input.rating != -1 {
+        return Err("The rating is 1 (thumbs up) or -1 (thumbs down)".to_string());
+    }
+    let kind: Option<String> = conn
+        .query_row("SELECT kind FROM entries WHERE id = ?1", params![entry_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match kind.as_deref() {
+        Some(KIND_AGENT_OUTPUT) => {}
+        Some(other) => return Err(format!("History entry {} is a {}, not an agent output", entry_id, other)),
+        None => return Err(format!("History entry {} not found", entry_id)),
+    }
+    let correction = input.correction.filter(|c| !c.trim().is_empty());
+    let updated_at = Utc::now();
+    conn.execute(
+        "INSERT OR REPLACE INTO feedback (entry_id, rating, correction, updated_at) VALUES (?1, ?2, ?3, ?4)",
+        params![entry_id, input.rating, correction, updated_at.timestamp_millis()],
+    )
+    .map_err(|e| format!("Failed to save feedback: {}", e))?;
+    Ok(Feedback { entry_id, rating: input.rating, correction, updated_at })
+}
+
+fn load_feedback(conn: &Connection, entry_id: i64) -> Result<Option<Feedback>, String> {
+    conn.query_row(
+        "SELECT rating, correction, updated_at FROM feedback WHERE entry_id = ?1",
+        params![entry_id],
+        |row| {
+            Ok(Feedback {
+                entry_id,
+                rating: row.get(0)?,
+                correction: row.get(1)?,
+                updated_at: Utc.timestamp_millis_opt(row.get(2)?).single().unwrap_or_default(),
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+// The rated runs matching the filter, as (system prompt, observation,
+// output or correction) examples.
+fn feedback_examples(
+    conn: &Connection,
+    registry: &AgentRegistry,
+    filter: &DatasetFilter,
+) -> Result<Vec<Option<Vec<Turn>>>, String> {
+    let since = filter.since.map(|t| t.timestamp_millis()).unwrap_or(i64::MIN);
+    let until = filter.until.map(|t| t.timestamp_millis()).unwrap_or(i64::MAX);
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.agent_id, e.content, e.created_at, f.rating, f.correction
+             FROM feedback f JOIN entries e ON e.id = f.entry_id
+             WHERE e.kind = ?1 AND e.agent_id IS NOT NULL AND e.created_at >= ?2 AND e.created_at <= ?3
+             ORDER BY e.created_at, e.id",
+        )
+        .map_err(|e| e.to_string())?;
+    type Row = (i64, String, String, i64, i8, Option<String>);
+    let rows: Vec<Row> = stmt
+        .query_map(params![KIND_AGENT_OUTPUT, since, until], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<_>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut examples = Vec::new();
+    for (id, agent_id, output, created_at, rating, correction) in rows {
+        if !filter.agent_ids.is_empty() && !filter.agent_ids.contains(&agent_id) {
+            continue;
+        }
+        let correction = correction.filter(|_| filter.include_corrections);
+        if rating < filter.min_rating && correction.is_none() {
+            continue;
+        }
+        // The observation the agent answered: the latest one before the output.
+        let observation: Option<String> = conn
+            .query_row(
+                "SELECT content FROM entries WHERE agent_id = ?1 AND kind = ?2
+                 AND (created_at < ?3 OR (created_at = ?3 AND id < ?4))
+                 ORDER BY created_at DESC, id DESC LIMIT 1",
+                params![agent_id, KIND_OBSERVATION, created_at, id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        let Some(input) = observation else {
+            examples.push(None);
+            continue;
+        };
+        let mut turns = Vec::new();
+        let system_prompt = registry.get(&agent_id).map(|agent| agent.system_prompt);
+        if let Some(system) = system_prompt.filter(|s| !s.trim().is_empty()) {
+            turns.push(Turn { role: "system", content: system });
+        }
+        turns.push(Turn { role: "user", content: input });
+        turns.push(Turn { role: "assistant", content: correction.unwrap_or(output) });
+        examples.push(Some(turns));
+    }
+    Ok(examples)
+}
+
+pub async fn get_feedback_handler(
+    AxumState(state): AxumState<AppState>,
+    AxumPath(entry_id): AxumPath<i64>,
+) -> Result<Json<Option<Feedback>>, (StatusCode, String)> {
+    let db = state.app_handle.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    load_feedback(&conn, entry_id).map(Json).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+pub async fn put_feedback_handler(
+    AxumState(state): AxumState<AppState>,
+    AxumPath(entry_id): AxumPath<i64>,
+    Json(input): Json<FeedbackInput>,
+) -> Result<Json<Feedback>, (StatusCode, String)> {
+    let db = state.app_handle.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    save_feedback(&conn, entry_id, input).map(Json).map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+#[tauri::command]
+pub fn set_feedback(entry_id: i64, feedback: FeedbackInput, db: State<'_, HistoryDb>) -> Result<Feedback, String> {
+    save_feedback(&db.0.lock().unwrap(), entry_id, feedback)
+}
+
+#[tauri::command]
+pub fn get_feedback(entry_id: i64, db: State<'_, HistoryDb>) -> Result<Option<Feedback>, String> {
+    load_feedback(&db.0.lock().unwrap(), entry_id)
+}
+
+#[tauri::command]
+pub fn build_dataset(
+    app: AppHandle,
+    filter: DatasetFilter,
+    db: State<'_, HistoryDb>,
+) -> Result<DatasetExportReport, String> {
+    let redaction = (filter.redact != Some(false)).then(|| redact::settings(&app));
+    let examples = {
+        let conn = db.0.lock().unwrap();
+        feedback_examples(&conn, &app.state::<AgentRegistry>(), &filter)?
+    };
+    let written = write_examples(&filter.path, filter.format, &examples, redaction.as_ref())?;
+    log::info!("Built a dataset of {} rated examples at {}", written, filter.path);
+    Ok(DatasetExportReport { path: filter.path, examples: written, skipped: examples.len() - written })
+}
+
+#[tauri::command]
+pub fn export_dataset(
+    app: AppHandle,
+    request: DatasetExportRequest,
+    db: State<'_, HistoryDb>,
+) -> Result<DatasetExportReport, String> {
+    if request.conversation_ids.is_empty() && request.agent_ids.is_empty() {
+        return Err("Pick at least one conversation or agent to export".to_string());
+    }
+    let redaction = (request.redact != Some(false)).then(|| redact::settings(&app));
+
+    let mut examples = Vec::new();
+    {
+        let conn = db.0.lock().unwrap();
+        for conversation_id in &request.conversation_ids {
+            examples.extend(conversation_examples(&conn, *conversation_id)?);
+        }
+        let registry = app.state::<AgentRegistry>();
+        for agent_id in &request.agent_ids {
+            let system_prompt = registry.get(agent_id).map(|agent| agent.system_prompt);
+            examples.extend(agent_examples(&conn, agent_id, system_prompt.as_deref(), &request)?);
+        }
+    }
+
+    let written = write_examples(&request.path, request.format, &examples, redaction.as_ref())?;
+    log::info!("Exported {} dataset examples to {}", written, request.path);
+    Ok(DatasetExportReport { path: request.path, examples: written, skipped: examples.len() - written })
+}
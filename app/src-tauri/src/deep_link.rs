@@ -0,0 +1,106 @@
+// In src-tauri/src/deep_link.rs
+//
+// Handles observer:// links coming from other apps, browser bookmarks or
+// launchers like Raycast/Alfred. Supported forms:
+//
+//   observer://show
+//   observer://agent/run?id=<agent_id>
+//   observer://agent/stop?id=<agent_id>
+//   observer://chat?prompt=<text>
+//
+// Agents run in the web app, which talks to the app over HTTP only, so the
+// backend just parses the link, brings the window up and queues the action.
+// The web app drains the queue by polling `POST /observer/deep-links`, which
+// also picks up links that arrived before it was open. Only the latest
+// `MAX_PENDING` actions are kept while nobody's polling.
+
+use axum::{extract::State as AxumState, Json};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Url};
+
+use crate::AppState;
+
+pub const SCHEME: &str = "observer";
+const MAX_PENDING: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeepLinkAction {
+    Show,
+    RunAgent { id: String },
+    StopAgent { id: String },
+    Chat { prompt: String },
+}
+
+#[derive(Default)]
+pub struct PendingDeepLinks(pub Mutex<Vec<DeepLinkAction>>);
+
+pub fn parse(url: &Url) -> Result<DeepLinkAction, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported scheme '{}'", url.scheme()));
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+            .filter(|value| !value.is_empty())
+    };
+    let missing = |name: &str| format!("Link '{}' is missing the '{}' parameter", url, name);
+
+    let host = url.host_str().unwrap_or("");
+    let path = url.path().trim_matches('/');
+
+    match (host, path) {
+        ("" | "show", "") => Ok(DeepLinkAction::Show),
+        ("agent", "run") => param("id")
+            .map(|id| DeepLinkAction::RunAgent { id })
+            .ok_or_else(|| missing("id")),
+        ("agent", "stop") => param("id")
+            .map(|id| DeepLinkAction::StopAgent { id })
+            .ok_or_else(|| missing("id")),
+        ("chat", "") => param("prompt")
+            .map(|prompt| DeepLinkAction::Chat { prompt })
+            .ok_or_else(|| missing("prompt")),
+        _ => Err(format!("Unknown deep link '{}'", url)),
+    }
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+pub fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        log::info!("Received deep link: {}", url);
+
+        let action = match parse(&url) {
+            Ok(action) => action,
+            Err(e) => {
+                log::warn!("Ignoring deep link: {}", e);
+                continue;
+            }
+        };
+
+        show_main_window(app);
+        queue(app, action);
+    }
+}
+
+// Hands an action to the web app, whether or not it's open yet.
+pub fn queue(app: &AppHandle, action: DeepLinkAction) {
+    let state = app.state::<PendingDeepLinks>();
+    let mut pending = state.0.lock().unwrap();
+    pending.push(action);
+    let excess = pending.len().saturating_sub(MAX_PENDING);
+    pending.drain(..excess);
+}
+
+pub async fn take_handler(AxumState(state): AxumState<AppState>) -> Json<Vec<DeepLinkAction>> {
+    Json(std::mem::take(&mut *state.app_handle.state::<PendingDeepLinks>().0.lock().unwrap()))
+}
@@ -0,0 +1,296 @@
+// In src-tauri/src/doctor.rs
+//
+// Self-test. `run_doctor` checks every subsystem Observer depends on (the
+// server port, Ollama and its binary, GPU, OS permissions, the history
+// database, disk space) and returns one structured report, each failing
+// check carrying a suggested fix. The same report is served at
+// `/healthz?verbose=1` (see health.rs) for the browser app and for scripts.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::history::HistoryDb;
+use crate::permissions::{self, PermissionState};
+use crate::{config, hardware, llm, onboarding, policy, privacy, storage};
+
+const OLLAMA_TIMEOUT: Duration = Duration::from_secs(3);
+// Models are several GB each.
+const LOW_DISK_MB: u64 = 5 * 1024;
+const CRITICAL_DISK_MB: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+    pub fn ok(id: &'static str, detail: impl Into<String>) -> Self {
+        Self { id, status: CheckStatus::Ok, detail: detail.into(), fix: None }
+    }
+
+    pub fn failed(id: &'static str, status: CheckStatus, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { id, status, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn skipped(id: &'static str, detail: impl Into<String>) -> Self {
+        Self { id, status: CheckStatus::Skipped, detail: detail.into(), fix: None }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    // False when any check is an error; warnings don't count.
+    pub healthy: bool,
+    pub generated_at: DateTime<Utc>,
+    pub checks: Vec<Check>,
+}
+
+async fn check_port(app: &AppHandle) -> Check {
+    const ID: &str = "port";
+    if cfg!(debug_assertions) {
+        return Check::skipped(ID, "Development builds are served by the dev server");
+    }
+    let mut listening = Vec::new();
+    for (entry, addr) in config::current(app).server.addresses() {
+        let addr = match addr {
+            Ok(addr) => addr,
+            Err(e) => {
+                return Check::failed(ID, CheckStatus::Error, e, "Fix the [server] bind list in config.toml")
+            }
+        };
+        if tokio::net::TcpStream::connect(addr).await.is_ok() {
+            listening.push(addr.to_string());
+            continue;
+        }
+        return match tokio::net::TcpListener::bind(addr).await {
+            Ok(_) => Check::failed(
+                ID,
+                CheckStatus::Error,
+                format!("Nothing is listening on {}", entry),
+                "Restart Observer; the startup log says why the server didn't start",
+            ),
+            Err(e) => Check::failed(
+                ID,
+                CheckStatus::Error,
+                format!("{} can't be used: {}", addr, e),
+                "Another program holds the port, or the address isn't on this machine; \
+                 change the [server] settings in config.toml",
+            ),
+        };
+    }
+    Check::ok(ID, format!("Listening on {}", listening.join(", ")))
+}
+
+pub async fn check_ollama(app: &AppHandle) -> Check {
+    const ID: &str = "ollama";
+    let base_url = llm::ollama_base_url(app);
+    if let Err(e) = policy::check_backend(&base_url) {
+        return Check::failed(ID, CheckStatus::Error, e, "Ask your administrator which backend to use");
+    }
+    let response = llm::client()
+        .get(format!("{}/api/version", base_url))
+        .timeout(OLLAMA_TIMEOUT)
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => {
+            let version = response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|v| v["version"].as_str().map(str::to_string))
+                .unwrap_or_else(|| "unknown version".to_string());
+            Check::ok(ID, format!("Ollama {} at {}", version, base_url))
+        }
+        Ok(response) => Check::failed(
+            ID,
+            CheckStatus::Error,
+            format!("{} answered {}", base_url, response.status()),
+            "Check that the URL points at an Ollama server, not another service",
+        ),
+        Err(e) => Check::failed(
+            ID,
+            CheckStatus::Error,
+            format!("{} is unreachable: {}", base_url, e),
+            "Start Ollama (`ollama serve`) or pick the right server in the launcher",
+        ),
+    }
+}
+
+fn check_binary(app: &AppHandle) -> Check {
+    const ID: &str = "ollama_binary";
+    match onboarding::find_ollama_binary() {
+        Some(path) => Check::ok(ID, format!("Found {}", path.display())),
+        // A remote server doesn't need a local binary.
+        None if !privacy::is_local_url(&llm::ollama_base_url(app)) => {
+            Check::skipped(ID, "Using a remote Ollama server")
+        }
+        None => Check::failed(
+            ID,
+            CheckStatus::Warning,
+            "The ollama binary isn't on PATH or in its usual install locations",
+            "Install Ollama from https://ollama.com/download; model management from the app needs it",
+        ),
+    }
+}
+
+fn check_gpu() -> Check {
+    const ID: &str = "gpu";
+    let hardware = hardware::probe();
+    if hardware.gpus.is_empty() {
+        return Check::failed(
+            ID,
+            CheckStatus::Warning,
+            "No GPU detected; models will run on the CPU",
+            "Install the GPU driver (and nvidia-smi for NVIDIA cards), or use small models",
+        );
+    }
+    let gpus: Vec<String> = hardware
+        .gpus
+        .iter()
+        .map(|g| match g.vram_mb {
+            Some(vram) => format!("{} ({} MB)", g.name, vram),
+            None => g.name.clone(),
+        })
+        .collect();
+    Check::ok(ID, gpus.join(", "))
+}
+
+fn check_permissions() -> Vec<Check> {
+    permissions::get_permission_status()
+        .into_iter()
+        .map(|status| {
+            let id = match status.permission {
+                permissions::Permission::ScreenRecording => "permission.screen_recording",
+                permissions::Permission::Microphone => "permission.microphone",
+                permissions::Permission::Accessibility => "permission.accessibility",
+            };
+            match status.state {
+                PermissionState::Granted => Check::ok(id, "Granted"),
+                PermissionState::NotApplicable => Check::skipped(id, "Not gated on this OS"),
+                PermissionState::Unknown => Check::skipped(id, "Can't be determined on this OS"),
+                PermissionState::NotDetermined => Check::failed(
+                    id,
+                    CheckStatus::Warning,
+                    "Not requested yet",
+                    "Grant it when Observer asks, or from the onboarding",
+                ),
+                PermissionState::Denied => Check::failed(
+                    id,
+                    CheckStatus::Warning,
+                    "Denied",
+                    match status.settings_url {
+                        Some(url) => format!("Allow Observer in the system settings ({})", url),
+                        None => "Allow Observer in the system settings".to_string(),
+                    },
+                ),
+            }
+        })
+        .collect()
+}
+
+fn check_database(app: &AppHandle) -> Check {
+    const ID: &str = "database";
+    let Some(db) = app.try_state::<HistoryDb>() else {
+        return Check::failed(ID, CheckStatus::Error, "The history database isn't open", "Check the startup log");
+    };
+    let conn = db.0.lock().unwrap();
+    let result: Result<String, _> = conn.query_row("PRAGMA quick_check", [], |row| row.get(0));
+    match result {
+        Ok(result) if result == "ok" => Check::ok(ID, "Integrity check passed"),
+        Ok(result) => Check::failed(
+            ID,
+            CheckStatus::Error,
+            format!("Integrity check failed: {}", result),
+            "Quit Observer and restore history.db from a backup",
+        ),
+        Err(e) => Check::failed(ID, CheckStatus::Error, format!("Integrity check failed: {}", e), "Check the log"),
+    }
+}
+
+// Free space on the volume holding `path`, in MB.
+fn free_disk_mb(path: &Path) -> Option<u64> {
+    #[cfg(not(target_os = "windows"))]
+    {
+        let output = std::process::Command::new("df").arg("-Pk").arg(path).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let kb: u64 = text.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+        Some(kb / 1024)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let drive = path.to_string_lossy().chars().next()?;
+        let output = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", &format!("(Get-PSDrive {}).Free", drive)])
+            .output()
+            .ok()?;
+        let bytes: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+        Some(bytes / 1024 / 1024)
+    }
+}
+
+fn check_disk(app: &AppHandle) -> Check {
+    const ID: &str = "disk_space";
+    let dir = match storage::root_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => return Check::failed(ID, CheckStatus::Error, e, "Check that the data directory is writable"),
+    };
+    match free_disk_mb(&dir) {
+        Some(free) if free < CRITICAL_DISK_MB => Check::failed(
+            ID,
+            CheckStatus::Error,
+            format!("Only {} MB free for {}", free, dir.display()),
+            "Free up disk space; settings and history can't be saved reliably",
+        ),
+        Some(free) if free < LOW_DISK_MB => Check::failed(
+            ID,
+            CheckStatus::Warning,
+            format!("{} MB free for {}", free, dir.display()),
+            "Free up disk space before pulling more models",
+        ),
+        Some(free) => Check::ok(ID, format!("{} MB free", free)),
+        None => Check::skipped(ID, "Free space can't be determined"),
+    }
+}
+
+pub async fn run(app: &AppHandle) -> DoctorReport {
+    let mut checks = vec![check_port(app).await, check_ollama(app).await];
+    let blocking_app = app.clone();
+    let blocking = tokio::task::spawn_blocking(move || {
+        let mut checks = vec![check_binary(&blocking_app), check_gpu()];
+        checks.extend(check_permissions());
+        checks.push(check_database(&blocking_app));
+        checks.push(check_disk(&blocking_app));
+        checks
+    })
+    .await;
+    match blocking {
+        Ok(more) => checks.extend(more),
+        Err(e) => log::error!("Doctor checks panicked: {}", e),
+    }
+    DoctorReport {
+        healthy: !checks.iter().any(|c| c.status == CheckStatus::Error),
+        generated_at: Utc::now(),
+        checks,
+    }
+}
+
+#[tauri::command]
+pub async fn run_doctor(app: AppHandle) -> DoctorReport {
+    run(&app).await
+}
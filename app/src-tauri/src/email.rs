@@ -0,0 +1,297 @@
+// In src-tauri/src/email.rs
+//
+// IMAP inbox polling as an input source for agents. Every `poll_minutes` the
+// configured folders are checked for messages newer than the last UID seen,
+// which are normalized (sender, subject, plain-text body), stored in the
+// history DB as "email" entries and sent to the frontend as "email-received"
+// so agents can triage them.
+//
+// Safe by default: folders are opened read-only (EXAMINE) and bodies fetched
+// with BODY.PEEK, so polling never marks, moves or deletes anything. The
+// password lives in the OS keyring (secrets.rs).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::history::{self, HistoryDb};
+use crate::{html, secrets, storage};
+
+const SETTINGS_FILE: &str = "email.json";
+const CURSOR_FILE: &str = "email_cursor.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_BODY_CHARS: usize = 20_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderFilter {
+    pub name: String,
+    // Case-insensitive substring filters; empty means "any".
+    pub from_contains: String,
+    pub subject_contains: String,
+}
+
+impl Default for FolderFilter {
+    fn default() -> Self {
+        Self {
+            name: "INBOX".to_string(),
+            from_contains: String::new(),
+            subject_contains: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub folders: Vec<FolderFilter>,
+    pub poll_minutes: u32,
+    pub max_per_poll: usize,
+}
+
+impl Default for EmailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 993,
+            username: String::new(),
+            folders: vec![FolderFilter::default()],
+            poll_minutes: 15,
+            max_per_poll: 20,
+        }
+    }
+}
+
+impl EmailSettings {
+    fn secret_key(&self) -> String {
+        format!("imap:{}@{}", self.username, self.host)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailMessage {
+    pub folder: String,
+    pub uid: u32,
+    pub message_id: Option<String>,
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub date: Option<DateTime<Utc>>,
+    pub text: String,
+}
+
+// Highest UID seen per folder. A first poll only records the current UID so
+// we don't flood agents with the whole mailbox.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Cursor {
+    last_uid: HashMap<String, u32>,
+}
+
+#[derive(Default)]
+pub struct EmailState {
+    settings: Mutex<EmailSettings>,
+    last_poll: Mutex<Option<DateTime<Utc>>>,
+}
+
+fn plain_text(mail: &mailparse::ParsedMail) -> String {
+    if mail.subparts.is_empty() {
+        let body = mail.get_body().unwrap_or_default();
+        return if mail.ctype.mimetype == "text/html" {
+            html::to_text(&body)
+        } else {
+            body
+        };
+    }
+    // Prefer text/plain alternatives over HTML.
+    mail.subparts
+        .iter()
+        .find(|p| p.ctype.mimetype == "text/plain")
+        .map(plain_text)
+        .or_else(|| mail.subparts.iter().map(plain_text).find(|t| !t.trim().is_empty()))
+        .unwrap_or_default()
+}
+
+fn normalize(folder: &str, uid: u32, raw: &[u8]) -> Result<EmailMessage, String> {
+    use mailparse::MailHeaderMap;
+    let mail = mailparse::parse_mail(raw).map_err(|e| format!("Unparseable message {}: {}", uid, e))?;
+    let header = |name: &str| mail.headers.get_first_value(name).unwrap_or_default();
+    let date = mail
+        .headers
+        .get_first_value("Date")
+        .and_then(|d| mailparse::dateparse(&d).ok())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0));
+
+    Ok(EmailMessage {
+        folder: folder.to_string(),
+        uid,
+        message_id: mail.headers.get_first_value("Message-ID"),
+        from: header("From"),
+        to: header("To"),
+        subject: header("Subject"),
+        date,
+        text: plain_text(&mail).trim().chars().take(MAX_BODY_CHARS).collect(),
+    })
+}
+
+fn matches(filter: &FolderFilter, message: &EmailMessage) -> bool {
+    let contains = |haystack: &str, needle: &str| {
+        needle.is_empty() || haystack.to_lowercase().contains(&needle.to_lowercase())
+    };
+    contains(&message.from, &filter.from_contains) && contains(&message.subject, &filter.subject_contains)
+}
+
+// Blocking: runs the whole IMAP conversation for one poll.
+fn fetch_new(settings: &EmailSettings, password: &str, cursor: &mut Cursor) -> Result<Vec<EmailMessage>, String> {
+    let tls = native_tls::TlsConnector::builder()
+        .build()
+        .map_err(|e| format!("TLS setup failed: {}", e))?;
+    let client = imap::connect((settings.host.as_str(), settings.port), &settings.host, &tls)
+        .map_err(|e| format!("Failed to connect to {}:{}: {}", settings.host, settings.port, e))?;
+    let mut session = client
+        .login(&settings.username, password)
+        .map_err(|(e, _)| format!("IMAP login failed: {}", e))?;
+
+    let mut messages = Vec::new();
+    for filter in &settings.folders {
+        // EXAMINE opens the folder read-only.
+        let mailbox = session
+            .examine(&filter.name)
+            .map_err(|e| format!("Failed to open folder '{}': {}", filter.name, e))?;
+        let newest = mailbox.uid_next.unwrap_or(1).saturating_sub(1);
+
+        let last = match cursor.last_uid.get(&filter.name) {
+            Some(last) => *last,
+            None => {
+                log::info!("Starting to watch '{}' from UID {}", filter.name, newest);
+                cursor.last_uid.insert(filter.name.clone(), newest);
+                continue;
+            }
+        };
+        if newest <= last {
+            continue;
+        }
+
+        let mut uids: Vec<u32> = session
+            .uid_search(format!("UID {}:*", last + 1))
+            .map_err(|e| format!("Search in '{}' failed: {}", filter.name, e))?
+            .into_iter()
+            .filter(|uid| *uid > last)
+            .collect();
+        uids.sort_unstable();
+        // Keep the oldest unread ones; the rest come on the next poll.
+        uids.truncate(settings.max_per_poll);
+        if uids.is_empty() {
+            continue;
+        }
+
+        let set = uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+        let fetches = session
+            .uid_fetch(&set, "(UID BODY.PEEK[])")
+            .map_err(|e| format!("Fetch from '{}' failed: {}", filter.name, e))?;
+        for fetch in fetches.iter() {
+            let (Some(uid), Some(body)) = (fetch.uid, fetch.body()) else {
+                continue;
+            };
+            match normalize(&filter.name, uid, body) {
+                Ok(message) if matches(filter, &message) => messages.push(message),
+                Ok(_) => {}
+                Err(e) => log::warn!("{}", e),
+            }
+        }
+        cursor.last_uid.insert(filter.name.clone(), *uids.last().unwrap());
+    }
+
+    let _ = session.logout();
+    Ok(messages)
+}
+
+pub async fn poll(app: &AppHandle) -> Result<Vec<EmailMessage>, String> {
+    let settings = app.state::<EmailState>().settings.lock().unwrap().clone();
+    if settings.host.is_empty() || settings.username.is_empty() {
+        return Err("Email is not configured".to_string());
+    }
+    let password = secrets::get(&settings.secret_key())?.ok_or("No IMAP password stored")?;
+
+    let mut cursor: Cursor = storage::load_json(app, CURSOR_FILE);
+    let (messages, cursor) = tokio::task::spawn_blocking(move || {
+        fetch_new(&settings, &password, &mut cursor).map(|messages| (messages, cursor))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    storage::save_json(app, CURSOR_FILE, &cursor)?;
+    *app.state::<EmailState>().last_poll.lock().unwrap() = Some(Utc::now());
+
+    if !messages.is_empty() {
+        log::info!("Fetched {} new emails", messages.len());
+        let db = app.state::<HistoryDb>();
+        for message in &messages {
+            let content = serde_json::to_string(message).map_err(|e| e.to_string())?;
+            db.insert(history::KIND_EMAIL, None, &content)?;
+        }
+        if let Err(e) = app.emit("email-received", &messages) {
+            log::error!("Failed to emit email-received event: {}", e);
+        }
+    }
+    Ok(messages)
+}
+
+pub fn start_poller(app: AppHandle) {
+    *app.state::<EmailState>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (enabled, interval) = {
+                let settings = app.state::<EmailState>().settings.lock().unwrap();
+                (settings.enabled, chrono::Duration::minutes(settings.poll_minutes.max(1) as i64))
+            };
+            let last_poll = *app.state::<EmailState>().last_poll.lock().unwrap();
+            let due = !matches!(last_poll, Some(last) if Utc::now() - last < interval);
+
+            if enabled && due {
+                if let Err(e) = poll(&app).await {
+                    log::error!("Email poll failed: {}", e);
+                    // Don't hammer a server that rejects us.
+                    *app.state::<EmailState>().last_poll.lock().unwrap() = Some(Utc::now());
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_email_settings(state: State<'_, EmailState>) -> EmailSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+// `password` is only written to the keyring; omit it to keep the stored one.
+#[tauri::command]
+pub fn set_email_settings(
+    app: AppHandle,
+    settings: EmailSettings,
+    password: Option<String>,
+    state: State<'_, EmailState>,
+) -> Result<(), String> {
+    if settings.enabled && (settings.host.trim().is_empty() || settings.username.trim().is_empty()) {
+        return Err("Host and username are required".to_string());
+    }
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        secrets::set(&settings.secret_key(), &password)?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn check_email_now(app: AppHandle) -> Result<Vec<EmailMessage>, String> {
+    poll(&app).await
+}
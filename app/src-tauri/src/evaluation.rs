@@ -0,0 +1,309 @@
+// In src-tauri/src/evaluation.rs
+//
+// Quality evaluation of agent outputs by a judge model. When enabled, every
+// `sample_every`-th agent output recorded in the history is sent to
+// `judge_model` with the agent's instructions, and scored from 1 (bad) to 5
+// (good) on each rubric: accuracy, hallucination (5 = nothing made up) and
+// formatting by default, or the user's own. Scores are stored per history
+// entry together with the model the agent used, and `/analytics/quality`
+// (or `get_quality_trends`) averages them per day, agent and model, so a
+// drop after a model update shows up in the charts.
+
+use axum::{
+    extract::{Query, State as AxumState},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::agents::AgentRegistry;
+use crate::analytics::{self, TimeParams};
+use crate::history::HistoryDb;
+use crate::{llm, storage, summary, AppState};
+
+const SETTINGS_FILE: &str = "evaluation.json";
+const MAX_OUTPUT_CHARS: usize = 6000;
+const MAX_INSTRUCTION_CHARS: usize = 3000;
+
+const JUDGE_PROMPT: &str = "You evaluate the output of an AI agent that watches the user's screen. You receive \
+the agent's instructions, its output and a list of rubrics. Score the output on each rubric from 1 (bad) to 5 \
+(good). Answer with JSON only: an object with one integer per rubric name and a \"notes\" string of at most two \
+sentences.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rubric {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvaluationSettings {
+    pub enabled: bool,
+    pub judge_model: Option<String>,
+    // Judge one output in this many.
+    pub sample_every: u32,
+    pub rubrics: Vec<Rubric>,
+}
+
+impl Default for EvaluationSettings {
+    fn default() -> Self {
+        let rubric = |name: &str, description: &str| Rubric {
+            name: name.to_string(),
+            description: description.to_string(),
+        };
+        Self {
+            enabled: false,
+            judge_model: None,
+            sample_every: 1,
+            rubrics: vec![
+                rubric("accuracy", "Does the output do what the instructions ask, correctly?"),
+                rubric("hallucination", "5 if nothing is made up, 1 if the output invents facts or events"),
+                rubric("formatting", "Does the output follow the format the instructions ask for?"),
+            ],
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct EvaluationState {
+    settings: Mutex<EvaluationSettings>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Evaluation {
+    pub entry_id: i64,
+    pub agent_id: String,
+    pub model: String,
+    pub judge_model: String,
+    pub scores: BTreeMap<String, u8>,
+    pub notes: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityPoint {
+    pub date: NaiveDate,
+    pub agent_id: String,
+    pub model: String,
+    pub evaluations: u32,
+    // Rubric -> average score.
+    pub averages: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub points: Vec<QualityPoint>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct QualityParams {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub days: Option<u32>,
+    pub agent_id: Option<String>,
+}
+
+fn parse_verdict(answer: &str, rubrics: &[Rubric]) -> Result<(BTreeMap<String, u8>, String), String> {
+    let start = answer.find('{').ok_or("The judge didn't answer with JSON")?;
+    let end = answer.rfind('}').filter(|&end| end > start).ok_or("The judge didn't answer with JSON")?;
+    let verdict: Value = serde_json::from_str(&answer[start..=end]).map_err(|e| format!("Invalid verdict: {}", e))?;
+    let mut scores = BTreeMap::new();
+    for rubric in rubrics {
+        let score = verdict[&rubric.name]
+            .as_f64()
+            .ok_or_else(|| format!("The judge didn't score {}", rubric.name))?;
+        scores.insert(rubric.name.clone(), score.round().clamp(1.0, 5.0) as u8);
+    }
+    Ok((scores, verdict["notes"].as_str().unwrap_or_default().to_string()))
+}
+
+fn store(app: &AppHandle, evaluation: &Evaluation) -> Result<(), String> {
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    conn.execute(
+        "INSERT OR REPLACE INTO evaluations (entry_id, agent_id, model, judge_model, scores, notes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            evaluation.entry_id,
+            evaluation.agent_id,
+            evaluation.model,
+            evaluation.judge_model,
+            serde_json::to_string(&evaluation.scores).unwrap_or_default(),
+            evaluation.notes,
+            evaluation.created_at.timestamp_millis(),
+        ],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("Failed to store the evaluation of entry {}: {}", evaluation.entry_id, e))
+}
+
+// Has the judge score one agent output and stores the result.
+pub async fn evaluate(app: &AppHandle, entry_id: i64, agent_id: &str, output: &str) -> Result<Evaluation, String> {
+    let settings = app.state::<EvaluationState>().settings.lock().unwrap().clone();
+    let judge_model = settings.judge_model.clone().ok_or("No judge model is configured")?;
+    let agent = app
+        .state::<AgentRegistry>()
+        .get(agent_id)
+        .ok_or_else(|| format!("Agent {} not found", agent_id))?;
+
+    let rubrics: Vec<String> =
+        settings.rubrics.iter().map(|rubric| format!("- {}: {}", rubric.name, rubric.description)).collect();
+    let prompt = format!(
+        "Instructions:\n{}\n\nOutput:\n{}\n\nRubrics:\n{}",
+        summary::truncate(&agent.system_prompt, MAX_INSTRUCTION_CHARS),
+        summary::truncate(output, MAX_OUTPUT_CHARS),
+        rubrics.join("\n")
+    );
+    let answer = llm::generate(app, &judge_model, JUDGE_PROMPT, &prompt).await?;
+    let (scores, notes) = parse_verdict(&answer, &settings.rubrics)?;
+    let evaluation = Evaluation {
+        entry_id,
+        agent_id: agent_id.to_string(),
+        model: agent.model_name,
+        judge_model,
+        scores,
+        notes,
+        created_at: Utc::now(),
+    };
+    store(app, &evaluation)?;
+    Ok(evaluation)
+}
+
+// Called for each agent output written to the history; judges it in the
+// background when it's in the sample.
+pub fn on_agent_output(app: &AppHandle, entry_id: i64, agent_id: &str, output: &str) {
+    let settings = app.state::<EvaluationState>().settings.lock().unwrap().clone();
+    if !settings.enabled || settings.judge_model.is_none() || entry_id % settings.sample_every.max(1) as i64 != 0 {
+        return;
+    }
+    let (app, agent_id, output) = (app.clone(), agent_id.to_string(), output.to_string());
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = evaluate(&app, entry_id, &agent_id, &output).await {
+            log::warn!("Couldn't evaluate the output of {} (entry {}): {}", agent_id, entry_id, e);
+        }
+    });
+}
+
+fn quality_report(app: &AppHandle, params: &QualityParams) -> Result<QualityReport, String> {
+    let time = TimeParams { from: params.from, to: params.to, days: params.days };
+    let (from, to) = analytics::resolve_range(&time);
+    let rows: Vec<(i64, String, String, String)> = {
+        let db = app.state::<HistoryDb>();
+        let conn = db.0.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT created_at, agent_id, model, scores FROM evaluations
+                 WHERE created_at >= ?1 AND created_at < ?2 AND (?3 IS NULL OR agent_id = ?3)
+                 ORDER BY created_at",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(
+                params![
+                    analytics::local_midnight(from).timestamp_millis(),
+                    analytics::local_midnight(to + ChronoDuration::days(1)).timestamp_millis(),
+                    params.agent_id,
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<_>>().map_err(|e| format!("Failed to read evaluations: {}", e))?
+    };
+
+    // (date, agent, model) -> rubric -> (sum, count), plus the number of evaluations.
+    type Sums = BTreeMap<String, (f64, u32)>;
+    let mut groups: BTreeMap<(NaiveDate, String, String), (u32, Sums)> = BTreeMap::new();
+    for (created_at, agent_id, model, scores) in rows {
+        let Some(created_at) = Utc.timestamp_millis_opt(created_at).single() else {
+            continue;
+        };
+        let date = created_at.with_timezone(&Local).date_naive();
+        let scores: BTreeMap<String, u8> = serde_json::from_str(&scores).unwrap_or_default();
+        let (count, sums) = groups.entry((date, agent_id, model)).or_default();
+        *count += 1;
+        for (rubric, score) in scores {
+            let (sum, n) = sums.entry(rubric).or_default();
+            *sum += score as f64;
+            *n += 1;
+        }
+    }
+    let points = groups
+        .into_iter()
+        .map(|((date, agent_id, model), (evaluations, sums))| QualityPoint {
+            date,
+            agent_id,
+            model,
+            evaluations,
+            averages: sums.into_iter().map(|(rubric, (sum, n))| (rubric, sum / n as f64)).collect(),
+        })
+        .collect();
+    Ok(QualityReport { from, to, points })
+}
+
+pub async fn quality_handler(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<QualityParams>,
+) -> Result<Json<QualityReport>, (StatusCode, String)> {
+    quality_report(&state.app_handle, &params).map(Json).map_err(|e| {
+        log::error!("Failed to compute quality analytics: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e)
+    })
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<EvaluationState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_evaluation_settings(state: State<'_, EvaluationState>) -> EvaluationSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_evaluation_settings(
+    app: AppHandle,
+    settings: EvaluationSettings,
+    state: State<'_, EvaluationState>,
+) -> Result<(), String> {
+    if settings.rubrics.is_empty() {
+        return Err("At least one rubric is needed".to_string());
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+// Judges a stored agent output now, whether or not it was in the sample.
+#[tauri::command]
+pub async fn evaluate_output(app: AppHandle, entry_id: i64) -> Result<Evaluation, String> {
+    let (agent_id, content): (Option<String>, String) = {
+        let db = app.state::<HistoryDb>();
+        let conn = db.0.lock().unwrap();
+        conn.query_row("SELECT agent_id, content FROM entries WHERE id = ?1", params![entry_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .map_err(|e| format!("History entry {} not found: {}", entry_id, e))?
+    };
+    let agent_id = agent_id.ok_or_else(|| format!("History entry {} isn't from an agent", entry_id))?;
+    evaluate(&app, entry_id, &agent_id, &content).await
+}
+
+#[tauri::command]
+pub fn get_quality_trends(
+    app: AppHandle,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    days: Option<u32>,
+    agent_id: Option<String>,
+) -> Result<QualityReport, String> {
+    quality_report(&app, &QualityParams { from, to, days, agent_id })
+}
@@ -0,0 +1,220 @@
+// In src-tauri/src/fallback.rs
+//
+// Per-agent fallback chains. An agent can list models and backends to try,
+// in order, after the one it asked for (e.g. `llava:13b` on the selected
+// server, then `moondream`, then a cloud vision model): when a chat or
+// generate call fails, is turned away as overloaded or answers with an
+// error before streaming anything, the next step gets the same request.
+// A step without a backend uses the selected server, one without a model
+// keeps the requested model. Every step is subject to the backend policy
+// and the data-locality rules.
+//
+// Each run records which step served it, or that all of them failed, in the
+// `fallback_runs` history table; the answer names the model and backend in
+// the `X-Observer-Served-By` header. Structured-output requests aren't
+// streamed and go to the selected server as before.
+
+use axum::http::{header, HeaderMap, HeaderValue};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::race::{self, Raced};
+use crate::{locality, policy, storage};
+
+const SETTINGS_FILE: &str = "fallback.json";
+const CHAINED_PATHS: &[&str] = &["/api/chat", "/api/generate"];
+pub const SERVED_BY_HEADER: &str = "x-observer-served-by";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackStep {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub backend: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FallbackSettings {
+    // Agent id -> the steps tried after the requested model fails.
+    pub agents: BTreeMap<String, Vec<FallbackStep>>,
+}
+
+#[derive(Default)]
+pub struct FallbackState {
+    settings: Mutex<FallbackSettings>,
+}
+
+// The answer of the step that served the request.
+pub struct Served {
+    pub raced: Raced,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FallbackRun {
+    pub created_at: DateTime<Utc>,
+    pub agent_id: String,
+    pub path: String,
+    // 0 is the requested model; None when every step failed.
+    pub step: Option<u32>,
+    pub model: Option<String>,
+    pub backend: Option<String>,
+    pub errors: Vec<String>,
+}
+
+fn record_run(app: &AppHandle, run: &FallbackRun) {
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    if let Err(e) = conn.execute(
+        "INSERT INTO fallback_runs (created_at, agent_id, path, step, model, backend, errors)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            run.created_at.timestamp_millis(),
+            run.agent_id,
+            run.path,
+            run.step,
+            run.model,
+            run.backend,
+            serde_json::to_string(&run.errors).unwrap_or_default(),
+        ],
+    ) {
+        log::error!("Failed to record the fallback run of {}: {}", run.agent_id, e);
+    }
+}
+
+// Tries the agent's chain when it has one; None means the request should be
+// proxied as usual, Err that every step failed, with their errors.
+pub async fn serve(
+    app: &AppHandle,
+    base_url: &str,
+    path: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    agent_id: Option<&str>,
+) -> Option<Result<Served, Vec<String>>> {
+    let agent_id = agent_id?;
+    let chain = app.state::<FallbackState>().settings.lock().unwrap().agents.get(agent_id).cloned()?;
+    if chain.is_empty() || !CHAINED_PATHS.contains(&path) {
+        return None;
+    }
+    let request: Value = serde_json::from_slice(body).ok()?;
+    let requested_model = request["model"].as_str()?.to_string();
+    let mut headers = headers.clone();
+    headers.remove(header::CONTENT_LENGTH);
+    headers.remove(header::HOST);
+
+    let requested = FallbackStep { model: None, backend: None };
+    let content = locality::sensitive_content(&headers, body);
+    let mut errors = Vec::new();
+    for (step, fallback) in std::iter::once(&requested).chain(&chain).enumerate() {
+        let backend = fallback.backend.as_deref().unwrap_or(base_url).trim().trim_end_matches('/').to_string();
+        let model = fallback.model.clone().unwrap_or_else(|| requested_model.clone());
+        if let Err(e) = policy::check_backend(&backend) {
+            errors.push(format!("{} on {}: {}", model, backend, e));
+            continue;
+        }
+        if let Err(violation) = locality::check(app, Some(agent_id), &backend, content.clone()) {
+            errors.push(format!("{} on {}: {}", model, backend, violation));
+            continue;
+        }
+        let mut step_body = request.clone();
+        step_body["model"] = Value::String(model.clone());
+        let step_body = step_body.to_string().into_bytes();
+        match race::contend(app, backend.clone(), path, headers.clone(), step_body).await {
+            Ok(mut raced) => {
+                if step > 0 {
+                    log::warn!("Agent {}: {} served by fallback step {} after {:?}", agent_id, model, step, errors);
+                }
+                if let Ok(value) = HeaderValue::from_str(&format!("{}@{}", model, backend)) {
+                    if let Some(headers) = raced.response.headers_mut() {
+                        headers.insert(SERVED_BY_HEADER, value);
+                    }
+                }
+                let run = FallbackRun {
+                    created_at: Utc::now(),
+                    agent_id: agent_id.to_string(),
+                    path: path.to_string(),
+                    step: Some(step as u32),
+                    model: Some(model.clone()),
+                    backend: Some(backend),
+                    errors,
+                };
+                record_run(app, &run);
+                return Some(Ok(Served { raced, model }));
+            }
+            Err(e) => errors.push(format!("{}: {}", model, e)),
+        }
+    }
+
+    log::error!("Agent {}: every step of the fallback chain failed: {:?}", agent_id, errors);
+    let run = FallbackRun {
+        created_at: Utc::now(),
+        agent_id: agent_id.to_string(),
+        path: path.to_string(),
+        step: None,
+        model: None,
+        backend: None,
+        errors: errors.clone(),
+    };
+    record_run(app, &run);
+    Some(Err(errors))
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<FallbackState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_fallback_settings(state: State<'_, FallbackState>) -> FallbackSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_fallback_settings(
+    app: AppHandle,
+    settings: FallbackSettings,
+    state: State<'_, FallbackState>,
+) -> Result<(), String> {
+    for backend in settings.agents.values().flatten().filter_map(|step| step.backend.as_deref()) {
+        policy::check_backend(backend.trim())?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_fallback_runs(
+    agent_id: Option<String>,
+    limit: Option<u32>,
+    db: State<'_, HistoryDb>,
+) -> Result<Vec<FallbackRun>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT created_at, agent_id, path, step, model, backend, errors FROM fallback_runs
+             WHERE ?1 IS NULL OR agent_id = ?1 ORDER BY created_at DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![agent_id, limit.unwrap_or(100)], |row| {
+            Ok(FallbackRun {
+                created_at: Utc.timestamp_millis_opt(row.get(0)?).single().unwrap_or_default(),
+                agent_id: row.get(1)?,
+                path: row.get(2)?,
+                step: row.get(3)?,
+                model: row.get(4)?,
+                backend: row.get(5)?,
+                errors: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| format!("Failed to read fallback runs: {}", e))
+}
@@ -0,0 +1,196 @@
+// In src-tauri/src/features.rs
+//
+// Per-subsystem switches, read once at startup, so a subsystem that
+// misbehaves can be kept from starting:
+//   - capture: native screenshots and screen recording,
+//   - audio: transcription, live captions and sound events,
+//   - agents: running agents over HTTP or links, agent tool calls, and the
+//     background services that act on their own (timers, resumed jobs,
+//     summaries, watchers),
+//   - exec: `/exec` and the agent shell tool,
+//   - lan: listening on anything but loopback.
+// They're saved in `features.json` and can be turned off for one run with
+// `--disable capture,agents` (or `--disable=...`).
+//
+// `--safe-mode` (or OBSERVER_SAFE_MODE=1) turns them all off and starts
+// nothing in the background: just the local server, the web app and its
+// settings, to recover from an agent or plugin that crashes the app on
+// launch. Changes made here apply at the next start.
+
+use axum::{extract::State as AxumState, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+use crate::{storage, AppState};
+
+const SETTINGS_FILE: &str = "features.json";
+const SAFE_MODE_SWITCH: &str = "--safe-mode";
+const SAFE_MODE_ENV: &str = "OBSERVER_SAFE_MODE";
+const DISABLE_SWITCH: &str = "--disable";
+
+static FLAGS: OnceLock<FeatureFlags> = OnceLock::new();
+static SAFE_MODE: OnceLock<bool> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    Capture,
+    Audio,
+    Agents,
+    Exec,
+    Lan,
+}
+
+impl Feature {
+    fn name(self) -> &'static str {
+        match self {
+            Feature::Capture => "capture",
+            Feature::Audio => "audio",
+            Feature::Agents => "agents",
+            Feature::Exec => "exec",
+            Feature::Lan => "lan",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Feature> {
+        [Feature::Capture, Feature::Audio, Feature::Agents, Feature::Exec, Feature::Lan]
+            .into_iter()
+            .find(|feature| feature.name() == name.trim())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureFlags {
+    pub capture: bool,
+    pub audio: bool,
+    pub agents: bool,
+    pub exec: bool,
+    pub lan: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self { capture: true, audio: true, agents: true, exec: true, lan: true }
+    }
+}
+
+impl FeatureFlags {
+    const NONE: FeatureFlags = FeatureFlags { capture: false, audio: false, agents: false, exec: false, lan: false };
+
+    fn get(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Capture => self.capture,
+            Feature::Audio => self.audio,
+            Feature::Agents => self.agents,
+            Feature::Exec => self.exec,
+            Feature::Lan => self.lan,
+        }
+    }
+
+    fn disable(&mut self, feature: Feature) {
+        match feature {
+            Feature::Capture => self.capture = false,
+            Feature::Audio => self.audio = false,
+            Feature::Agents => self.agents = false,
+            Feature::Exec => self.exec = false,
+            Feature::Lan => self.lan = false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureStatus {
+    pub safe_mode: bool,
+    // What this run uses.
+    pub active: FeatureFlags,
+    // What's saved, for the next start.
+    pub saved: FeatureFlags,
+}
+
+pub fn safe_mode() -> bool {
+    *SAFE_MODE.get_or_init(|| {
+        std::env::args().skip(1).any(|arg| arg == SAFE_MODE_SWITCH)
+            || std::env::var(SAFE_MODE_ENV).is_ok_and(|value| matches!(value.as_str(), "1" | "true"))
+    })
+}
+
+// Features named after `--disable`.
+fn disabled_on_command_line() -> Vec<Feature> {
+    let mut names = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == DISABLE_SWITCH {
+            names.extend(args.next());
+        } else if let Some(list) = arg.strip_prefix("--disable=") {
+            names.push(list.to_string());
+        }
+    }
+    names
+        .iter()
+        .flat_map(|list| list.split(','))
+        .filter_map(|name| {
+            let feature = Feature::parse(name);
+            if feature.is_none() {
+                log::warn!("Ignoring unknown feature '{}' in {}", name, DISABLE_SWITCH);
+            }
+            feature
+        })
+        .collect()
+}
+
+pub fn init(app: &AppHandle) {
+    let flags = if safe_mode() {
+        log::warn!("Safe mode: only the local server and settings are started");
+        FeatureFlags::NONE
+    } else {
+        let mut flags: FeatureFlags = storage::load_json(app, SETTINGS_FILE);
+        for feature in disabled_on_command_line() {
+            flags.disable(feature);
+        }
+        flags
+    };
+    for feature in [Feature::Capture, Feature::Audio, Feature::Agents, Feature::Exec, Feature::Lan] {
+        if !flags.get(feature) {
+            log::warn!("The {} subsystem is disabled", feature.name());
+        }
+    }
+    let _ = FLAGS.set(flags);
+}
+
+pub fn enabled(feature: Feature) -> bool {
+    FLAGS.get().copied().unwrap_or_default().get(feature)
+}
+
+pub fn ensure(feature: Feature) -> Result<(), String> {
+    if enabled(feature) {
+        return Ok(());
+    }
+    let reason = if safe_mode() { "the app was started in safe mode" } else { "it's turned off in the features" };
+    Err(format!("The {} subsystem is disabled: {}", feature.name(), reason))
+}
+
+fn status(app: &AppHandle) -> FeatureStatus {
+    FeatureStatus {
+        safe_mode: safe_mode(),
+        active: FLAGS.get().copied().unwrap_or_default(),
+        saved: storage::load_json(app, SETTINGS_FILE),
+    }
+}
+
+pub async fn status_handler(AxumState(state): AxumState<AppState>) -> Json<FeatureStatus> {
+    Json(status(&state.app_handle))
+}
+
+#[tauri::command]
+pub fn get_feature_flags(app: AppHandle) -> FeatureStatus {
+    status(&app)
+}
+
+// Applies at the next start.
+#[tauri::command]
+pub fn set_feature_flags(app: AppHandle, flags: FeatureFlags) -> Result<FeatureStatus, String> {
+    storage::save_json(&app, SETTINGS_FILE, &flags)?;
+    Ok(status(&app))
+}
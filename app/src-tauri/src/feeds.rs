@@ -0,0 +1,289 @@
+// In src-tauri/src/feeds.rs
+//
+// RSS/Atom feed watcher. Registered feeds are fetched every `poll_minutes`;
+// items not seen before (tracked per feed in the `feed_items` table) can have
+// their linked article pulled in as full text, and are then summarized by a
+// model — or by an agent's model and system prompt when `summarizer_agent_id`
+// is set. Item texts are screened for prompt injection first (injection.rs)
+// and tagged with their link, so the digest's claims can be traced back to
+// the article they came from (provenance.rs).
+// Digests are stored in history as "feed" entries, emitted as
+// "feed-digest" and sent as a notification.
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::agents::AgentRegistry;
+use crate::history::{self, HistoryDb};
+use crate::notifications::{self, Alert};
+use crate::{html, injection, llm, provenance, storage, summary};
+
+const SETTINGS_FILE: &str = "feeds.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_ITEMS_PER_DIGEST: usize = 15;
+const MAX_ARTICLE_CHARS: usize = 4_000;
+
+const SYSTEM_PROMPT: &str = "You summarize new items from the user's news and blog feeds. For each \
+item write one or two sentences with what is new or important. Group related items. Be concise.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feed {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    // Fetch the linked article instead of relying on the feed's excerpt.
+    #[serde(default)]
+    pub full_text: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedSettings {
+    pub feeds: Vec<Feed>,
+    pub poll_minutes: u32,
+    pub model: String,
+    pub summarizer_agent_id: Option<String>,
+    pub notify: bool,
+}
+
+impl Default for FeedSettings {
+    fn default() -> Self {
+        Self {
+            feeds: Vec::new(),
+            poll_minutes: 60,
+            model: "gemma3:4b".to_string(),
+            summarizer_agent_id: None,
+            notify: true,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FeedState {
+    settings: Mutex<FeedSettings>,
+    last_poll: Mutex<Option<DateTime<Utc>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedItem {
+    pub feed_id: String,
+    pub item_id: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedDigest {
+    pub history_id: i64,
+    pub items: Vec<FeedItem>,
+    pub summary: String,
+}
+
+async fn fetch_items(feed: &Feed) -> Result<Vec<FeedItem>, String> {
+    let bytes = llm::client()
+        .get(&feed.url)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch feed '{}': {}", feed.name, e))?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?;
+    let parsed = feed_rs::parser::parse(&bytes[..]).map_err(|e| format!("Invalid feed '{}': {}", feed.name, e))?;
+
+    Ok(parsed
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let excerpt = entry
+                .content
+                .and_then(|c| c.body)
+                .or_else(|| entry.summary.map(|s| s.content))
+                .unwrap_or_default();
+            FeedItem {
+                feed_id: feed.id.clone(),
+                item_id: entry.id,
+                title: entry.title.map(|t| t.content).unwrap_or_default(),
+                link: entry.links.first().map(|l| l.href.clone()),
+                published: entry.published.or(entry.updated),
+                text: html::to_text(&excerpt),
+            }
+        })
+        .collect())
+}
+
+async fn fetch_article(url: &str) -> Result<String, String> {
+    let page = llm::client()
+        .get(url)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?
+        .text()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(html::to_text(&page).chars().take(MAX_ARTICLE_CHARS).collect())
+}
+
+// Records the items and returns the ones that weren't known yet.
+fn dedupe(db: &HistoryDb, items: Vec<FeedItem>) -> Result<Vec<FeedItem>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut fresh = Vec::new();
+    for item in items {
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO feed_items (feed_id, item_id, title, link, fetched_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![item.feed_id, item.item_id, item.title, item.link, Utc::now().timestamp_millis()],
+            )
+            .map_err(|e| e.to_string())?;
+        if inserted > 0 {
+            fresh.push(item);
+        }
+    }
+    Ok(fresh)
+}
+
+// The summary, and the prompt it was made from for provenance.
+async fn summarize(
+    app: &AppHandle,
+    settings: &FeedSettings,
+    items: &[FeedItem],
+) -> Result<(String, provenance::Prompt), String> {
+    let agent_id = settings.summarizer_agent_id.as_deref();
+    let mut prompt = provenance::Prompt::default();
+    prompt.push_text("New feed items:\n\n");
+    for item in items {
+        let text = injection::scan(app, agent_id, "feed", &summary::truncate(&item.text, MAX_ARTICLE_CHARS)).await;
+        let label = item.link.as_deref().unwrap_or(&item.title);
+        prompt.push_text(&format!("## {}\n", item.title));
+        prompt.push_source("feed", label, &text.text);
+        prompt.push_text("\n");
+    }
+
+    let agent = settings
+        .summarizer_agent_id
+        .as_deref()
+        .and_then(|id| app.state::<AgentRegistry>().get(id));
+    let text = match agent {
+        Some(agent) => llm::generate(app, &agent.model_name, &agent.system_prompt, prompt.text()).await?,
+        None => llm::generate(app, &settings.model, SYSTEM_PROMPT, prompt.text()).await?,
+    };
+    Ok((text, prompt))
+}
+
+pub async fn poll(app: &AppHandle) -> Result<Option<FeedDigest>, String> {
+    let settings = app.state::<FeedState>().settings.lock().unwrap().clone();
+    *app.state::<FeedState>().last_poll.lock().unwrap() = Some(Utc::now());
+
+    let mut new_items = Vec::new();
+    for feed in &settings.feeds {
+        let items = match fetch_items(feed).await {
+            Ok(items) => items,
+            Err(e) => {
+                log::error!("{}", e);
+                continue;
+            }
+        };
+        let mut fresh = dedupe(&app.state::<HistoryDb>(), items)?;
+        if feed.full_text {
+            for item in fresh.iter_mut() {
+                if let Some(link) = &item.link {
+                    match fetch_article(link).await {
+                        Ok(text) if !text.is_empty() => item.text = text,
+                        Ok(_) => {}
+                        Err(e) => log::warn!("{}", e),
+                    }
+                }
+            }
+        }
+        new_items.extend(fresh);
+    }
+
+    if new_items.is_empty() {
+        return Ok(None);
+    }
+    new_items.sort_by(|a, b| b.published.cmp(&a.published));
+    new_items.truncate(MAX_ITEMS_PER_DIGEST);
+
+    log::info!("Summarizing {} new feed items", new_items.len());
+    let (text, prompt) = summarize(app, &settings, &new_items).await?;
+    let history_id = app
+        .state::<HistoryDb>()
+        .insert(history::KIND_FEED, settings.summarizer_agent_id.as_deref(), &text)?;
+    prompt.record(app, history_id);
+    let digest = FeedDigest { history_id, items: new_items, summary: text };
+
+    if let Err(e) = app.emit("feed-digest", &digest) {
+        log::error!("Failed to emit feed-digest event: {}", e);
+    }
+    if settings.notify {
+        let alert = Alert {
+            title: format!("{} new feed items", digest.items.len()),
+            body: summary::truncate(&digest.summary, 240),
+            agent_id: settings.summarizer_agent_id.clone(),
+            created_at: Utc::now(),
+        };
+        if let Err(e) = notifications::notify(app, alert) {
+            log::error!("{}", e);
+        }
+    }
+    Ok(Some(digest))
+}
+
+pub fn start_watcher(app: AppHandle) {
+    *app.state::<FeedState>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (has_feeds, interval) = {
+                let settings = app.state::<FeedState>().settings.lock().unwrap();
+                (
+                    !settings.feeds.is_empty(),
+                    chrono::Duration::minutes(settings.poll_minutes.max(5) as i64),
+                )
+            };
+            let last_poll = *app.state::<FeedState>().last_poll.lock().unwrap();
+            if has_feeds && !matches!(last_poll, Some(last) if Utc::now() - last < interval) {
+                if let Err(e) = poll(&app).await {
+                    log::error!("Feed poll failed: {}", e);
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_feed_settings(state: State<'_, FeedState>) -> FeedSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_feed_settings(app: AppHandle, mut settings: FeedSettings, state: State<'_, FeedState>) -> Result<(), String> {
+    for feed in settings.feeds.iter_mut() {
+        if feed.id.is_empty() {
+            feed.id = uuid::Uuid::new_v4().to_string();
+        }
+        if !feed.url.starts_with("http://") && !feed.url.starts_with("https://") {
+            return Err(format!("Feed '{}' needs an http(s) URL", feed.name));
+        }
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn check_feeds_now(app: AppHandle) -> Result<Option<FeedDigest>, String> {
+    poll(&app).await
+}
@@ -0,0 +1,261 @@
+// In src-tauri/src/file_drop.rs
+//
+// Files dropped onto the window are classified, processed into something a
+// model can consume and handed to the frontend:
+//
+//   image -> downscaled JPEG as base64 (vision prompt)
+//   pdf   -> extracted text (ingestion)
+//   text  -> file contents
+//   audio -> raw base64, transcribed by the frontend whisper pipeline
+//
+// Progress is reported per file on "file-drop-progress" and the finished
+// payload arrives on "file-drop-ready", addressed to the current drop target
+// (the active chat when no agent was chosen with `set_drop_target`).
+//
+// The web app can't hear those events, so finished payloads are also queued
+// for it to drain by polling `POST /files/dropped`, as with deep links. Only
+// the latest `MAX_PENDING` are kept while nobody's polling.
+
+use axum::{extract::State as AxumState, Json};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Serialize;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::AppState;
+
+const MAX_PENDING: usize = 20;
+const MAX_IMAGE_DIMENSION: u32 = 1920;
+const MAX_TEXT_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_AUDIO_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    Image,
+    Pdf,
+    Text,
+    Audio,
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DroppedContent {
+    Image { mime: String, base64: String },
+    Pdf { text: String },
+    Text { text: String },
+    Audio { mime: String, base64: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropStage {
+    Queued,
+    Processing,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DropProgress {
+    path: String,
+    file_name: String,
+    kind: FileKind,
+    stage: DropStage,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DropReady {
+    path: String,
+    file_name: String,
+    // None means "the active chat", otherwise an agent id.
+    target: Option<String>,
+    content: DroppedContent,
+}
+
+#[derive(Default)]
+pub struct DropTarget(pub Mutex<Option<String>>);
+
+#[derive(Default)]
+pub struct PendingDrops(Mutex<Vec<DropReady>>);
+
+fn queue(app: &AppHandle, ready: DropReady) {
+    let state = app.state::<PendingDrops>();
+    let mut pending = state.0.lock().unwrap();
+    pending.push(ready);
+    let excess = pending.len().saturating_sub(MAX_PENDING);
+    pending.drain(..excess);
+}
+
+pub async fn take_handler(AxumState(state): AxumState<AppState>) -> Json<Vec<DropReady>> {
+    Json(std::mem::take(&mut *state.app_handle.state::<PendingDrops>().0.lock().unwrap()))
+}
+
+#[tauri::command]
+pub fn set_drop_target(agent_id: Option<String>, target: State<'_, DropTarget>) {
+    log::info!("Setting file drop target to: {:?}", agent_id);
+    *target.0.lock().unwrap() = agent_id;
+}
+
+pub fn classify(path: &Path) -> FileKind {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "tif" | "tiff" => FileKind::Image,
+        "pdf" => FileKind::Pdf,
+        "txt" | "md" | "markdown" | "csv" | "tsv" | "json" | "yaml" | "yml" | "toml" | "xml"
+        | "html" | "htm" | "log" | "rs" | "py" | "js" | "ts" | "tsx" | "jsx" | "sh" => {
+            FileKind::Text
+        }
+        "wav" | "mp3" | "m4a" | "ogg" | "flac" | "webm" | "aac" => FileKind::Audio,
+        _ => FileKind::Unsupported,
+    }
+}
+
+fn audio_mime(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+        Some("wav") => "audio/wav",
+        Some("mp3") => "audio/mpeg",
+        Some("m4a") | Some("aac") => "audio/mp4",
+        Some("ogg") => "audio/ogg",
+        Some("flac") => "audio/flac",
+        Some("webm") => "audio/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+fn check_size(path: &Path, limit: u64) -> Result<(), String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > limit {
+        return Err(format!("File is too large ({} bytes, limit is {} bytes)", size, limit));
+    }
+    Ok(())
+}
+
+fn process_image(path: &Path) -> Result<DroppedContent, String> {
+    let image = image::open(path).map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    // Vision models don't benefit from 4K inputs, they just burn VRAM.
+    let image = if image.width() > MAX_IMAGE_DIMENSION || image.height() > MAX_IMAGE_DIMENSION {
+        image.resize(
+            MAX_IMAGE_DIMENSION,
+            MAX_IMAGE_DIMENSION,
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        image
+    };
+
+    let mut bytes = Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgb8(image.to_rgb8())
+        .write_to(&mut bytes, image::ImageOutputFormat::Jpeg(85))
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+
+    Ok(DroppedContent::Image {
+        mime: "image/jpeg".to_string(),
+        base64: STANDARD.encode(bytes.into_inner()),
+    })
+}
+
+pub fn process_file(path: &Path, kind: FileKind) -> Result<DroppedContent, String> {
+    match kind {
+        FileKind::Image => process_image(path),
+        FileKind::Pdf => {
+            let text = pdf_extract::extract_text(path)
+                .map_err(|e| format!("Failed to extract PDF text: {}", e))?;
+            Ok(DroppedContent::Pdf { text })
+        }
+        FileKind::Text => {
+            check_size(path, MAX_TEXT_BYTES)?;
+            let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+            Ok(DroppedContent::Text {
+                text: String::from_utf8_lossy(&bytes).into_owned(),
+            })
+        }
+        FileKind::Audio => {
+            check_size(path, MAX_AUDIO_BYTES)?;
+            let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+            Ok(DroppedContent::Audio {
+                mime: audio_mime(path).to_string(),
+                base64: STANDARD.encode(bytes),
+            })
+        }
+        FileKind::Unsupported => Err("Unsupported file type".to_string()),
+    }
+}
+
+fn emit_progress(app: &AppHandle, path: &Path, kind: FileKind, stage: DropStage, error: Option<String>) {
+    let progress = DropProgress {
+        path: path.display().to_string(),
+        file_name: file_name(path),
+        kind,
+        stage,
+        error,
+    };
+    if let Err(e) = app.emit("file-drop-progress", progress) {
+        log::error!("Failed to emit file-drop-progress event: {}", e);
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+pub fn handle_drop(app: &AppHandle, paths: Vec<PathBuf>) {
+    log::info!("Files dropped: {:?}", paths);
+
+    let target = app.state::<DropTarget>().0.lock().unwrap().clone();
+
+    for path in &paths {
+        emit_progress(app, path, classify(path), DropStage::Queued, None);
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for path in paths {
+            let kind = classify(&path);
+            if kind == FileKind::Unsupported {
+                log::warn!("Skipping unsupported dropped file: {:?}", path);
+                emit_progress(&app, &path, kind, DropStage::Failed, Some("Unsupported file type".to_string()));
+                continue;
+            }
+
+            emit_progress(&app, &path, kind, DropStage::Processing, None);
+
+            let worker_path = path.clone();
+            let result = tokio::task::spawn_blocking(move || process_file(&worker_path, kind))
+                .await
+                .unwrap_or_else(|e| Err(format!("Processing task panicked: {}", e)));
+
+            match result {
+                Ok(content) => {
+                    let ready = DropReady {
+                        path: path.display().to_string(),
+                        file_name: file_name(&path),
+                        target: target.clone(),
+                        content,
+                    };
+                    if let Err(e) = app.emit("file-drop-ready", ready.clone()) {
+                        log::error!("Failed to emit file-drop-ready event: {}", e);
+                    }
+                    queue(&app, ready);
+                    emit_progress(&app, &path, kind, DropStage::Done, None);
+                }
+                Err(e) => {
+                    log::error!("Failed to process dropped file {:?}: {}", path, e);
+                    emit_progress(&app, &path, kind, DropStage::Failed, Some(e));
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,339 @@
+// In src-tauri/src/files.rs
+//
+// `read_file`, `write_file` and `list_dir` tools, limited to directories the
+// user granted (optionally read-only). Every path is canonicalized before
+// it's checked against the canonical scope roots, so `..` segments and
+// symlinks can't escape a scope. Relative paths resolve against the first
+// scope, which lets agents keep e.g. "notes/today.md" without knowing where
+// the user put their folder.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::tools::{self, ToolSpec};
+use crate::{audit, storage};
+
+const SETTINGS_FILE: &str = "file_scopes.json";
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+const MAX_LIST_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileScope {
+    pub path: String,
+    #[serde(default)]
+    pub writable: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileScopes {
+    pub scopes: Vec<FileScope>,
+}
+
+#[derive(Default)]
+pub struct FileScopeState(Mutex<FileScopes>);
+
+#[derive(Deserialize)]
+struct PathArgs {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct WriteArgs {
+    path: String,
+    content: String,
+    #[serde(default)]
+    append: bool,
+}
+
+pub fn tools() -> Vec<ToolSpec> {
+    vec![
+        ToolSpec {
+            name: "read_file",
+            description: "Read a UTF-8 text file from a folder the user shared with agents.",
+            parameters: json!({
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }),
+        },
+        ToolSpec {
+            name: "write_file",
+            description: "Create or overwrite a text file (or append to it) in a folder the user shared with agents.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "content": { "type": "string" },
+                    "append": { "type": "boolean" }
+                },
+                "required": ["path", "content"]
+            }),
+        },
+        ToolSpec {
+            name: "list_dir",
+            description: "List files and folders in a folder the user shared with agents.",
+            parameters: json!({
+                "type": "object",
+                "properties": { "path": { "type": "string", "description": "Defaults to the first shared folder" } }
+            }),
+        },
+    ]
+}
+
+fn canonical_scopes(app: &AppHandle) -> Vec<(PathBuf, bool)> {
+    let scopes = app.state::<FileScopeState>().0.lock().unwrap().clone();
+    scopes
+        .scopes
+        .iter()
+        .filter_map(|scope| Some((canonicalize(Path::new(&scope.path)).ok()?, scope.writable)))
+        .collect()
+}
+
+// `canonicalize` on Windows returns `\\?\` paths; compare without the prefix.
+fn canonicalize(path: &Path) -> std::io::Result<PathBuf> {
+    let canonical = std::fs::canonicalize(path)?;
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(stripped) = canonical.to_str().and_then(|s| s.strip_prefix(r"\\?\")) {
+            return Ok(PathBuf::from(stripped));
+        }
+    }
+    Ok(canonical)
+}
+
+// Canonical form of a path that may not exist yet: the deepest existing
+// ancestor is canonicalized and the rest appended (no `..` allowed there).
+fn canonicalize_for_write(path: &Path) -> Result<PathBuf, String> {
+    let mut existing = path.to_path_buf();
+    let mut rest = Vec::new();
+    // symlink_metadata so a dangling symlink counts as existing (and then
+    // fails to canonicalize) instead of being written through.
+    while existing.symlink_metadata().is_err() {
+        let name = existing.file_name().ok_or("Invalid path")?.to_os_string();
+        rest.push(name);
+        existing = existing.parent().ok_or("Invalid path")?.to_path_buf();
+    }
+    let mut resolved = canonicalize(&existing).map_err(|e| e.to_string())?;
+    for part in rest.into_iter().rev() {
+        resolved.push(part);
+    }
+    Ok(resolved)
+}
+
+// Returns the canonical path if it lies inside a scope (a writable one when
+// `write` is set).
+pub fn resolve(app: &AppHandle, path: &str, write: bool) -> Result<PathBuf, String> {
+    resolve_in(&canonical_scopes(app), path, write)
+}
+
+// `resolve` against canonical (root, writable) scopes.
+fn resolve_in(scopes: &[(PathBuf, bool)], path: &str, write: bool) -> Result<PathBuf, String> {
+    let (first, _) = scopes.first().ok_or("No folders are shared with agents")?;
+
+    let requested = Path::new(path);
+    if requested.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err("Paths may not contain '..'".to_string());
+    }
+    let absolute = if requested.is_absolute() {
+        requested.to_path_buf()
+    } else {
+        first.join(requested)
+    };
+    let canonical = if write {
+        canonicalize_for_write(&absolute)?
+    } else {
+        canonicalize(&absolute).map_err(|e| format!("Cannot access '{}': {}", path, e))?
+    };
+
+    let scope = scopes.iter().find(|(root, _)| canonical.starts_with(root));
+    match scope {
+        Some((_, writable)) if write && !writable => Err(format!("'{}' is in a read-only folder", path)),
+        Some(_) => Ok(canonical),
+        None => Err(format!("'{}' is outside the folders shared with agents", path)),
+    }
+}
+
+fn read_file(app: &AppHandle, args: PathArgs) -> Result<Value, String> {
+    let path = resolve(app, &args.path, false)?;
+    let size = std::fs::metadata(&path).map_err(|e| e.to_string())?.len();
+    if size > MAX_READ_BYTES {
+        return Err(format!("'{}' is too large ({} bytes)", args.path, size));
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read '{}': {}", args.path, e))?;
+    Ok(json!({ "path": path, "content": content }))
+}
+
+fn write_file(app: &AppHandle, agent_id: Option<&str>, args: WriteArgs) -> Result<Value, String> {
+    let path = resolve(app, &args.path, true)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(args.append)
+        .truncate(!args.append)
+        .open(&path)
+        .map_err(|e| format!("Failed to open '{}': {}", args.path, e))?;
+    file.write_all(args.content.as_bytes())
+        .map_err(|e| format!("Failed to write '{}': {}", args.path, e))?;
+
+    audit::record(
+        app,
+        agent_id,
+        "file.write",
+        json!({ "path": path, "bytes": args.content.len(), "append": args.append }),
+    );
+    Ok(json!({ "path": path, "bytes_written": args.content.len() }))
+}
+
+fn list_dir(app: &AppHandle, path: Option<String>) -> Result<Value, String> {
+    let path = resolve(app, path.as_deref().unwrap_or(""), false)?;
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(&path).map_err(|e| e.to_string())?.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        entries.push(json!({
+            "name": entry.file_name().to_string_lossy(),
+            "is_dir": metadata.is_dir(),
+            "size": metadata.len(),
+        }));
+        if entries.len() >= MAX_LIST_ENTRIES {
+            break;
+        }
+    }
+    Ok(json!({ "path": path, "entries": entries }))
+}
+
+pub async fn call(app: &AppHandle, agent_id: Option<&str>, name: &str, args: Value) -> Result<Value, String> {
+    match name {
+        "read_file" => read_file(app, tools::parse_args(name, args)?),
+        "write_file" => write_file(app, agent_id, tools::parse_args(name, args)?),
+        "list_dir" => list_dir(app, args.get("path").and_then(Value::as_str).map(str::to_string)),
+        _ => Err(format!("Unknown tool '{}'", name)),
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<FileScopeState>().0.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_file_scopes(state: State<'_, FileScopeState>) -> FileScopes {
+    state.0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_file_scopes(app: AppHandle, mut scopes: FileScopes, state: State<'_, FileScopeState>) -> Result<(), String> {
+    for scope in scopes.scopes.iter_mut() {
+        let canonical = canonicalize(Path::new(&scope.path))
+            .map_err(|e| format!("Cannot share '{}': {}", scope.path, e))?;
+        if !canonical.is_dir() {
+            return Err(format!("'{}' is not a folder", scope.path));
+        }
+        scope.path = canonical.to_string_lossy().to_string();
+    }
+    storage::save_json(&app, SETTINGS_FILE, &scopes)?;
+    *state.0.lock().unwrap() = scopes;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh canonical directory, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("observer-files-{}", uuid::Uuid::new_v4().simple()));
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(canonicalize(&dir).unwrap())
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // A shared folder holding `notes.txt`, next to an unshared one holding
+    // `secret.txt`.
+    fn layout(writable: bool) -> (TempDir, PathBuf, Vec<(PathBuf, bool)>) {
+        let temp = TempDir::new();
+        let (shared, outside) = (temp.0.join("shared"), temp.0.join("outside"));
+        std::fs::create_dir_all(&shared).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(shared.join("notes.txt"), "notes").unwrap();
+        std::fs::write(outside.join("secret.txt"), "secret").unwrap();
+        (temp, shared.clone(), vec![(shared, writable)])
+    }
+
+    #[test]
+    fn resolves_relative_paths_against_the_first_scope() {
+        let (_temp, shared, scopes) = layout(false);
+        assert_eq!(resolve_in(&scopes, "notes.txt", false), Ok(shared.join("notes.txt")));
+        let absolute = shared.join("notes.txt").to_string_lossy().to_string();
+        assert_eq!(resolve_in(&scopes, &absolute, false), Ok(shared.join("notes.txt")));
+        assert!(resolve_in(&[], "notes.txt", false).is_err());
+    }
+
+    #[test]
+    fn rejects_parent_dir_traversal() {
+        let (_temp, shared, scopes) = layout(true);
+        for write in [false, true] {
+            assert!(resolve_in(&scopes, "../outside/secret.txt", write).unwrap_err().contains(".."));
+            let absolute = shared.join("..").join("outside").join("secret.txt");
+            assert!(resolve_in(&scopes, &absolute.to_string_lossy(), write).unwrap_err().contains(".."));
+        }
+    }
+
+    #[test]
+    fn rejects_absolute_paths_outside_every_scope() {
+        let (temp, _shared, scopes) = layout(true);
+        let secret = temp.0.join("outside").join("secret.txt").to_string_lossy().to_string();
+        assert!(resolve_in(&scopes, &secret, false).unwrap_err().contains("outside the folders"));
+        assert!(resolve_in(&scopes, &secret, true).unwrap_err().contains("outside the folders"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_cannot_escape_a_scope() {
+        let (temp, shared, scopes) = layout(true);
+        let outside = temp.0.join("outside");
+        std::os::unix::fs::symlink(&outside, shared.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.join("missing.txt"), shared.join("dangling")).unwrap();
+
+        assert!(resolve_in(&scopes, "link/secret.txt", false).unwrap_err().contains("outside the folders"));
+        // Neither a new file under the link nor one through a dangling link.
+        assert!(resolve_in(&scopes, "link/new.txt", true).unwrap_err().contains("outside the folders"));
+        assert!(resolve_in(&scopes, "dangling", true).is_err());
+        assert!(!outside.join("missing.txt").exists());
+    }
+
+    #[test]
+    fn read_only_scopes_refuse_writes() {
+        let (_temp, shared, scopes) = layout(false);
+        assert_eq!(resolve_in(&scopes, "notes.txt", false), Ok(shared.join("notes.txt")));
+        assert!(resolve_in(&scopes, "notes.txt", true).unwrap_err().contains("read-only"));
+        assert!(resolve_in(&scopes, "new.txt", true).unwrap_err().contains("read-only"));
+    }
+
+    #[test]
+    fn writes_may_name_paths_that_do_not_exist_yet() {
+        let (_temp, shared, scopes) = layout(true);
+        let expected = shared.join("new").join("dir").join("file.txt");
+        assert_eq!(resolve_in(&scopes, "new/dir/file.txt", true), Ok(expected.clone()));
+        assert_eq!(canonicalize_for_write(&expected), Ok(expected));
+        // Reads still need the file.
+        assert!(resolve_in(&scopes, "new/dir/file.txt", false).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+// In src-tauri/src/focus.rs
+//
+// Focus / do-not-disturb handling. Focus is active when any of these is true:
+//   - the user switched it on manually,
+//   - the current time falls into one of the in-app focus schedule windows,
+//   - the OS reports its own do-not-disturb mode (when `respect_os_focus` is set).
+// While focused, agent alerts are queued instead of shown. A background loop
+// delivers the queue once focus ends; `get_pending_alerts` lets the UI show
+// what is waiting.
+
+use chrono::{Datelike, Local, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::notifications::{self, Alert};
+use crate::storage;
+
+const SETTINGS_FILE: &str = "focus.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_PENDING_ALERTS: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusWindow {
+    // 0 = Monday ... 6 = Sunday. Empty means every day.
+    #[serde(default)]
+    pub days: Vec<u8>,
+    // "HH:MM" in local time. A window ending before it starts runs past midnight.
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusSettings {
+    pub manual: bool,
+    pub respect_os_focus: bool,
+    pub schedule: Vec<FocusWindow>,
+}
+
+impl Default for FocusSettings {
+    fn default() -> Self {
+        Self {
+            manual: false,
+            respect_os_focus: true,
+            schedule: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusReason {
+    Manual,
+    Schedule,
+    Os,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusStatus {
+    pub active: bool,
+    pub reason: Option<FocusReason>,
+    pub pending_alerts: usize,
+}
+
+#[derive(Default)]
+pub struct FocusState {
+    pub settings: Mutex<FocusSettings>,
+    pub pending: Mutex<Vec<Alert>>,
+    // Cached result of the last OS probe; probing spawns processes so it only runs in the loop.
+    pub os_focus: Mutex<bool>,
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", value))
+}
+
+fn window_active(window: &FocusWindow, now: chrono::DateTime<Local>) -> bool {
+    let (Ok(start), Ok(end)) = (parse_time(&window.start), parse_time(&window.end)) else {
+        return false;
+    };
+    let time = NaiveTime::from_hms_opt(now.hour(), now.minute(), 0).unwrap_or(start);
+    let today = now.weekday().num_days_from_monday() as u8;
+    let yesterday = (today + 6) % 7;
+    let runs_on = |day: u8| window.days.is_empty() || window.days.contains(&day);
+
+    if start <= end {
+        runs_on(today) && time >= start && time < end
+    } else {
+        // Overnight window: the part after midnight belongs to the day it started.
+        (runs_on(today) && time >= start) || (runs_on(yesterday) && time < end)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect_os_focus() -> bool {
+    // Focus assertions are stored here since Monterey; a non-empty record list means focus is on.
+    let Ok(home) = std::env::var("HOME") else {
+        return false;
+    };
+    let path = std::path::Path::new(&home).join("Library/DoNotDisturb/DB/Assertions.json");
+    let Ok(bytes) = std::fs::read(path) else {
+        return false;
+    };
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return false;
+    };
+    json["data"]
+        .as_array()
+        .map(|data| {
+            data.iter().any(|entry| {
+                entry["storeAssertionRecords"]
+                    .as_array()
+                    .map_or(false, |records| !records.is_empty())
+            })
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn detect_os_focus() -> bool {
+    // GNOME's "Do Not Disturb" toggle just turns banners off.
+    std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "false")
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn detect_os_focus() -> bool {
+    // Windows Focus Assist has no public API to query.
+    false
+}
+
+pub fn current_reason(state: &FocusState) -> Option<FocusReason> {
+    let settings = state.settings.lock().unwrap();
+    if settings.manual {
+        return Some(FocusReason::Manual);
+    }
+    let now = Local::now();
+    if settings.schedule.iter().any(|w| window_active(w, now)) {
+        return Some(FocusReason::Schedule);
+    }
+    if settings.respect_os_focus && *state.os_focus.lock().unwrap() {
+        return Some(FocusReason::Os);
+    }
+    None
+}
+
+fn status(state: &FocusState) -> FocusStatus {
+    let reason = current_reason(state);
+    FocusStatus {
+        active: reason.is_some(),
+        reason,
+        pending_alerts: state.pending.lock().unwrap().len(),
+    }
+}
+
+// Called by notifications::notify. Returns true when the alert was held back.
+pub fn queue_if_focused(app: &AppHandle, alert: &Alert) -> bool {
+    let state = app.state::<FocusState>();
+    if current_reason(&state).is_none() {
+        return false;
+    }
+
+    let mut pending = state.pending.lock().unwrap();
+    if pending.len() >= MAX_PENDING_ALERTS {
+        pending.remove(0);
+    }
+    pending.push(alert.clone());
+    true
+}
+
+fn flush_pending(app: &AppHandle) {
+    let state = app.state::<FocusState>();
+    let alerts = std::mem::take(&mut *state.pending.lock().unwrap());
+    if alerts.is_empty() {
+        return;
+    }
+
+    log::info!("Focus ended, delivering {} queued alerts", alerts.len());
+    for alert in &alerts {
+        if let Err(e) = notifications::deliver(app, alert) {
+            log::error!("{}", e);
+        }
+    }
+    if let Err(e) = app.emit("pending-alerts-delivered", alerts) {
+        log::error!("Failed to emit pending-alerts-delivered event: {}", e);
+    }
+}
+
+pub fn start_monitor(app: AppHandle) {
+    *app.state::<FocusState>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        let mut was_active = false;
+        loop {
+            let respect_os = app.state::<FocusState>().settings.lock().unwrap().respect_os_focus;
+            let os_focus = respect_os
+                && tokio::task::spawn_blocking(detect_os_focus).await.unwrap_or(false);
+            *app.state::<FocusState>().os_focus.lock().unwrap() = os_focus;
+
+            let current = status(&app.state::<FocusState>());
+            if current.active != was_active {
+                log::info!("Focus mode {} ({:?})", if current.active { "started" } else { "ended" }, current.reason);
+                if let Err(e) = app.emit("focus-changed", current.clone()) {
+                    log::error!("Failed to emit focus-changed event: {}", e);
+                }
+            }
+            if !current.active {
+                flush_pending(&app);
+            }
+            was_active = current.active;
+
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn set_focus_schedule(
+    app: AppHandle,
+    schedule: Vec<FocusWindow>,
+    respect_os_focus: Option<bool>,
+    state: State<'_, FocusState>,
+) -> Result<FocusStatus, String> {
+    for window in &schedule {
+        parse_time(&window.start)?;
+        parse_time(&window.end)?;
+        if let Some(day) = window.days.iter().find(|d| **d > 6) {
+            return Err(format!("Invalid day {}, expected 0 (Monday) to 6 (Sunday)", day));
+        }
+    }
+
+    let settings = {
+        let mut settings = state.settings.lock().unwrap();
+        settings.schedule = schedule;
+        if let Some(respect) = respect_os_focus {
+            settings.respect_os_focus = respect;
+        }
+        settings.clone()
+    };
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    log::info!("Focus schedule updated: {} windows", settings.schedule.len());
+    Ok(status(&state))
+}
+
+#[tauri::command]
+pub fn get_focus_schedule(state: State<'_, FocusState>) -> FocusSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_manual_focus(
+    app: AppHandle,
+    enabled: bool,
+    state: State<'_, FocusState>,
+) -> Result<FocusStatus, String> {
+    let settings = {
+        let mut settings = state.settings.lock().unwrap();
+        settings.manual = enabled;
+        settings.clone()
+    };
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    Ok(status(&state))
+}
+
+#[tauri::command]
+pub fn get_focus_status(state: State<'_, FocusState>) -> FocusStatus {
+    status(&state)
+}
+
+#[tauri::command]
+pub fn get_pending_alerts(clear: Option<bool>, state: State<'_, FocusState>) -> Vec<Alert> {
+    let mut pending = state.pending.lock().unwrap();
+    if clear.unwrap_or(false) {
+        std::mem::take(&mut *pending)
+    } else {
+        pending.clone()
+    }
+}
@@ -0,0 +1,236 @@
+// In src-tauri/src/git.rs
+//
+// Awareness of the git repositories the user is working in. For a watched
+// repo agents can get the current branch, the uncommitted diff and the recent
+// commit log, which is what "summarize what I changed today" or
+// commit-message-suggestion agents need. Shells out to the `git` CLI so the
+// user's own config (safe.directory, diff drivers, ...) applies.
+//
+// Only repos listed in the settings are readable through the tools; the first
+// one is the active project used when a call doesn't name a repo.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tokio::process::Command;
+
+use crate::tools::{self, ToolSpec};
+use crate::{storage, summary};
+
+const SETTINGS_FILE: &str = "git.json";
+const MAX_DIFF_CHARS: usize = 40_000;
+const DEFAULT_LOG_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitSettings {
+    pub repos: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct GitState {
+    settings: Mutex<GitSettings>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub subject: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoInfo {
+    pub path: String,
+    pub branch: String,
+    // `git status --porcelain` lines, e.g. " M src/lib.rs".
+    pub changes: Vec<String>,
+    pub recent_commits: Vec<Commit>,
+}
+
+#[derive(Deserialize)]
+struct RepoArgs {
+    repo: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DiffArgs {
+    repo: Option<String>,
+    #[serde(default)]
+    staged_only: bool,
+}
+
+#[derive(Deserialize)]
+struct LogArgs {
+    repo: Option<String>,
+    // Anything `git log --since` accepts: "midnight", "2 days ago", a date.
+    since: Option<String>,
+    limit: Option<usize>,
+}
+
+pub fn tools() -> Vec<ToolSpec> {
+    let repo = json!({ "type": "string", "description": "Path of a watched repository; defaults to the active project" });
+    vec![
+        ToolSpec {
+            name: "git_status",
+            description: "Current branch, uncommitted changes and the last few commits of the user's repository.",
+            parameters: json!({ "type": "object", "properties": { "repo": repo } }),
+        },
+        ToolSpec {
+            name: "git_diff",
+            description: "Diff of the uncommitted changes in the user's repository.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "repo": repo,
+                    "staged_only": { "type": "boolean", "description": "Only changes staged for commit" }
+                }
+            }),
+        },
+        ToolSpec {
+            name: "git_log",
+            description: "Recent commits in the user's repository.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "repo": repo,
+                    "since": { "type": "string", "description": "e.g. \"midnight\" or \"3 days ago\"" },
+                    "limit": { "type": "integer" }
+                }
+            }),
+        },
+    ]
+}
+
+async fn git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// Resolves `repo` against the watched list; `None` means the active project.
+fn resolve(app: &AppHandle, repo: Option<&str>) -> Result<PathBuf, String> {
+    let settings = app.state::<GitState>().settings.lock().unwrap().clone();
+    let path = match repo {
+        Some(repo) => settings
+            .repos
+            .iter()
+            .find(|watched| Path::new(watched) == Path::new(repo))
+            .ok_or_else(|| format!("'{}' is not a watched repository", repo))?,
+        None => settings.repos.first().ok_or("No repository is being watched")?,
+    };
+    Ok(PathBuf::from(path))
+}
+
+pub async fn branch(repo: &Path) -> Result<String, String> {
+    Ok(git(repo, &["rev-parse", "--abbrev-ref", "HEAD"]).await?.trim().to_string())
+}
+
+pub async fn diff(repo: &Path, staged_only: bool) -> Result<String, String> {
+    let args: &[&str] = if staged_only {
+        &["diff", "--cached", "--no-color"]
+    } else {
+        &["diff", "HEAD", "--no-color"]
+    };
+    let diff = git(repo, args).await?;
+    Ok(summary::truncate(&diff, MAX_DIFF_CHARS))
+}
+
+pub async fn log(repo: &Path, since: Option<&str>, limit: usize) -> Result<Vec<Commit>, String> {
+    let limit = format!("-n{}", limit.clamp(1, 200));
+    let since = since.map(|s| format!("--since={}", s));
+    let mut args = vec!["log", limit.as_str(), "--no-color", "--pretty=format:%h%x1f%an%x1f%aI%x1f%s"];
+    if let Some(since) = &since {
+        args.push(since.as_str());
+    }
+    Ok(git(repo, &args)
+        .await?
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+pub async fn info(repo: &Path) -> Result<RepoInfo, String> {
+    let changes = git(repo, &["status", "--porcelain=v1"])
+        .await?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    Ok(RepoInfo {
+        path: repo.to_string_lossy().to_string(),
+        branch: branch(repo).await?,
+        changes,
+        recent_commits: log(repo, None, 5).await?,
+    })
+}
+
+pub async fn call(app: &AppHandle, name: &str, args: Value) -> Result<Value, String> {
+    match name {
+        "git_status" => {
+            let args: RepoArgs = tools::parse_args(name, args)?;
+            let info = info(&resolve(app, args.repo.as_deref())?).await?;
+            serde_json::to_value(info).map_err(|e| e.to_string())
+        }
+        "git_diff" => {
+            let args: DiffArgs = tools::parse_args(name, args)?;
+            let repo = resolve(app, args.repo.as_deref())?;
+            Ok(json!({ "branch": branch(&repo).await?, "diff": diff(&repo, args.staged_only).await? }))
+        }
+        "git_log" => {
+            let args: LogArgs = tools::parse_args(name, args)?;
+            let repo = resolve(app, args.repo.as_deref())?;
+            let commits = log(&repo, args.since.as_deref(), args.limit.unwrap_or(DEFAULT_LOG_LIMIT)).await?;
+            serde_json::to_value(commits).map_err(|e| e.to_string())
+        }
+        _ => Err(format!("Unknown tool '{}'", name)),
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<GitState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_git_settings(state: State<'_, GitState>) -> GitSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub async fn set_git_settings(app: AppHandle, settings: GitSettings) -> Result<(), String> {
+    for repo in &settings.repos {
+        git(Path::new(repo), &["rev-parse", "--git-dir"])
+            .await
+            .map_err(|_| format!("'{}' is not a git repository", repo))?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *app.state::<GitState>().settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_repo_info(app: AppHandle, repo: Option<String>) -> Result<RepoInfo, String> {
+    info(&resolve(&app, repo.as_deref())?).await
+}
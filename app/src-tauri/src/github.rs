@@ -0,0 +1,217 @@
+// In src-tauri/src/github.rs
+//
+// GitHub tools for developer agents: list notifications, fetch a pull
+// request's diff and post a comment. Combined with a scheduled agent this is
+// enough for "review my assigned PRs each morning" on a local model. Requests
+// use a personal access token kept in the OS keyring (secrets.rs).
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::tools::{self, ToolSpec};
+use crate::{llm, secrets, summary};
+
+const API_URL: &str = "https://api.github.com";
+const TOKEN_KEY: &str = "github:token";
+const JSON_MEDIA_TYPE: &str = "application/vnd.github+json";
+const DIFF_MEDIA_TYPE: &str = "application/vnd.github.diff";
+const MAX_DIFF_CHARS: usize = 60_000;
+
+pub fn tools() -> Vec<ToolSpec> {
+    vec![
+        ToolSpec {
+            name: "github_list_notifications",
+            description: "List the user's unread GitHub notifications (review requests, mentions, assigned issues).",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "participating": { "type": "boolean", "description": "Only notifications where the user is directly involved" },
+                    "reason": { "type": "string", "description": "Only this reason, e.g. \"review_requested\" or \"mention\"" }
+                }
+            }),
+        },
+        ToolSpec {
+            name: "github_pr_diff",
+            description: "Fetch the unified diff of a pull request.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "repo": { "type": "string", "description": "owner/name" },
+                    "number": { "type": "integer" }
+                },
+                "required": ["repo", "number"]
+            }),
+        },
+        ToolSpec {
+            name: "github_post_comment",
+            description: "Post a comment on a pull request or issue.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "repo": { "type": "string", "description": "owner/name" },
+                    "number": { "type": "integer" },
+                    "body": { "type": "string", "description": "Markdown comment text" }
+                },
+                "required": ["repo", "number", "body"]
+            }),
+        },
+    ]
+}
+
+#[derive(Deserialize)]
+struct NotificationArgs {
+    #[serde(default)]
+    participating: bool,
+    reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PullArgs {
+    repo: String,
+    number: u64,
+}
+
+#[derive(Deserialize)]
+struct CommentArgs {
+    repo: String,
+    number: u64,
+    body: String,
+}
+
+fn token() -> Result<String, String> {
+    secrets::get(TOKEN_KEY)?.ok_or_else(|| "No GitHub token configured".to_string())
+}
+
+fn check_repo(repo: &str) -> Result<(), String> {
+    let valid = repo.split('/').count() == 2
+        && repo
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not an owner/name repository", repo))
+    }
+}
+
+fn request(method: reqwest::Method, path: &str, token: &str, accept: &str) -> reqwest::RequestBuilder {
+    llm::client()
+        .request(method, format!("{}{}", API_URL, path))
+        .bearer_auth(token)
+        .header("User-Agent", "observer-ai")
+        .header("Accept", accept)
+        .header("X-GitHub-Api-Version", "2022-11-28")
+}
+
+async fn send(builder: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+    let response = builder.send().await.map_err(|e| format!("GitHub request failed: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("GitHub returned {}: {}", status, summary::truncate(&body, 300)));
+    }
+    Ok(response)
+}
+
+async fn list_notifications(args: NotificationArgs) -> Result<Value, String> {
+    let token = token()?;
+    let path = format!("/notifications?participating={}", args.participating);
+    let notifications: Vec<Value> = send(request(reqwest::Method::GET, &path, &token, JSON_MEDIA_TYPE))
+        .await?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let items: Vec<Value> = notifications
+        .iter()
+        .filter(|n| match &args.reason {
+            Some(reason) => n["reason"] == reason.as_str(),
+            None => true,
+        })
+        .map(|n| {
+            // subject.url is the API URL; its last segment is the PR/issue number.
+            let number = n["subject"]["url"]
+                .as_str()
+                .and_then(|url| url.rsplit('/').next())
+                .and_then(|n| n.parse::<u64>().ok());
+            json!({
+                "id": n["id"],
+                "reason": n["reason"],
+                "repo": n["repository"]["full_name"],
+                "type": n["subject"]["type"],
+                "title": n["subject"]["title"],
+                "number": number,
+                "updated_at": n["updated_at"],
+            })
+        })
+        .collect();
+    Ok(Value::Array(items))
+}
+
+async fn pr_diff(args: PullArgs) -> Result<Value, String> {
+    check_repo(&args.repo)?;
+    let token = token()?;
+    let path = format!("/repos/{}/pulls/{}", args.repo, args.number);
+    let diff = send(request(reqwest::Method::GET, &path, &token, DIFF_MEDIA_TYPE))
+        .await?
+        .text()
+        .await
+        .map_err(|e| e.to_string())?;
+    let truncated = diff.chars().count() > MAX_DIFF_CHARS;
+    Ok(json!({
+        "repo": args.repo,
+        "number": args.number,
+        "diff": summary::truncate(&diff, MAX_DIFF_CHARS),
+        "truncated": truncated,
+    }))
+}
+
+async fn post_comment(args: CommentArgs) -> Result<Value, String> {
+    check_repo(&args.repo)?;
+    if args.body.trim().is_empty() {
+        return Err("Comment body is empty".to_string());
+    }
+    let token = token()?;
+    let path = format!("/repos/{}/issues/{}/comments", args.repo, args.number);
+    let builder = request(reqwest::Method::POST, &path, &token, JSON_MEDIA_TYPE).json(&json!({ "body": args.body }));
+    let comment: Value = send(builder)
+        .await?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    log::info!("Posted GitHub comment on {}#{}", args.repo, args.number);
+    Ok(json!({ "id": comment["id"], "url": comment["html_url"] }))
+}
+
+pub async fn call(_app: &AppHandle, name: &str, args: Value) -> Result<Value, String> {
+    match name {
+        "github_list_notifications" => list_notifications(tools::parse_args(name, args)?).await,
+        "github_pr_diff" => pr_diff(tools::parse_args(name, args)?).await,
+        "github_post_comment" => post_comment(tools::parse_args(name, args)?).await,
+        _ => Err(format!("Unknown tool '{}'", name)),
+    }
+}
+
+// Stores (or with `None`, removes) the personal access token.
+#[tauri::command]
+pub fn set_github_token(token: Option<String>) -> Result<(), String> {
+    match token.filter(|t| !t.trim().is_empty()) {
+        Some(token) => secrets::set(TOKEN_KEY, token.trim()),
+        None => secrets::delete(TOKEN_KEY),
+    }
+}
+
+// Checks the stored token; returns the login it belongs to.
+#[tauri::command]
+pub async fn get_github_user() -> Result<Option<String>, String> {
+    let Some(token) = secrets::get(TOKEN_KEY)? else {
+        return Ok(None);
+    };
+    let user: Value = send(request(reqwest::Method::GET, "/user", &token, JSON_MEDIA_TYPE))
+        .await?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(user["login"].as_str().map(str::to_string))
+}
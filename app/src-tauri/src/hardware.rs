@@ -0,0 +1,161 @@
+// In src-tauri/src/hardware.rs
+//
+// What the machine can run: memory, CPU cores and GPUs. Probed by shelling
+// out to the tools every system already has (nvidia-smi, sysctl,
+// PowerShell, /proc), so the numbers are best-effort; anything that can't
+// be determined is None rather than a guess.
+
+use serde::Serialize;
+use std::process::Command;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Gpu {
+    pub name: String,
+    pub vram_mb: Option<u64>,
+    // Apple Silicon shares system memory with the GPU.
+    pub unified_memory: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HardwareInfo {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub cpu_cores: usize,
+    pub memory_mb: Option<u64>,
+    pub gpus: Vec<Gpu>,
+}
+
+impl HardwareInfo {
+    // The memory a model can live in: the largest GPU, or system memory
+    // when there's no discrete GPU.
+    pub fn model_memory_mb(&self) -> Option<u64> {
+        self.gpus
+            .iter()
+            .filter_map(|g| if g.unified_memory { self.memory_mb } else { g.vram_mb })
+            .max()
+            .or(self.memory_mb)
+    }
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn memory_mb() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb / 1024)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let bytes: u64 = output("sysctl", &["-n", "hw.memsize"])?.trim().parse().ok()?;
+        Some(bytes / 1024 / 1024)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let bytes: u64 = output(
+            "powershell",
+            &["-NoProfile", "-Command", "(Get-CimInstance Win32_ComputerSystem).TotalPhysicalMemory"],
+        )?
+        .trim()
+        .parse()
+        .ok()?;
+        Some(bytes / 1024 / 1024)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    None
+}
+
+fn nvidia_gpus() -> Vec<Gpu> {
+    let Some(csv) = output("nvidia-smi", &["--query-gpu=name,memory.total", "--format=csv,noheader,nounits"]) else {
+        return Vec::new();
+    };
+    csv.lines()
+        .filter_map(|line| {
+            let (name, vram) = line.rsplit_once(',')?;
+            Some(Gpu {
+                name: name.trim().to_string(),
+                vram_mb: vram.trim().parse().ok(),
+                unified_memory: false,
+            })
+        })
+        .collect()
+}
+
+fn other_gpus() -> Vec<Gpu> {
+    #[cfg(target_os = "macos")]
+    {
+        if cfg!(target_arch = "aarch64") {
+            let name = output("sysctl", &["-n", "machdep.cpu.brand_string"])
+                .map(|s| s.trim().to_string())
+                .unwrap_or_else(|| "Apple Silicon".to_string());
+            return vec![Gpu { name, vram_mb: None, unified_memory: true }];
+        }
+        Vec::new()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // AdapterRAM is a 32-bit field and caps at 4 GB, so it's only a floor.
+        let Some(csv) = output(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-Command",
+                "Get-CimInstance Win32_VideoController | ForEach-Object { \"$($_.Name),$($_.AdapterRAM)\" }",
+            ],
+        ) else {
+            return Vec::new();
+        };
+        csv.lines()
+            .filter(|line| !line.to_ascii_lowercase().contains("nvidia"))
+            .filter_map(|line| {
+                let (name, ram) = line.rsplit_once(',')?;
+                Some(Gpu {
+                    name: name.trim().to_string(),
+                    vram_mb: ram.trim().parse::<u64>().ok().map(|b| b / 1024 / 1024),
+                    unified_memory: false,
+                })
+            })
+            .collect()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // AMD cards expose their VRAM through sysfs.
+        let Ok(cards) = std::fs::read_dir("/sys/class/drm") else {
+            return Vec::new();
+        };
+        cards
+            .filter_map(Result::ok)
+            // card0, not its connectors (card0-DP-1).
+            .filter(|card| !card.file_name().to_string_lossy().contains('-'))
+            .filter_map(|card| {
+                let device = card.path().join("device");
+                let bytes: u64 = std::fs::read_to_string(device.join("mem_info_vram_total")).ok()?.trim().parse().ok()?;
+                Some(Gpu {
+                    name: format!("AMD GPU ({})", card.file_name().to_string_lossy()),
+                    vram_mb: Some(bytes / 1024 / 1024),
+                    unified_memory: false,
+                })
+            })
+            .collect()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    Vec::new()
+}
+
+// Blocking; run it off the async runtime.
+pub fn probe() -> HardwareInfo {
+    let mut gpus = nvidia_gpus();
+    gpus.extend(other_gpus());
+    HardwareInfo {
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        cpu_cores: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        memory_mb: memory_mb(),
+        gpus,
+    }
+}
@@ -0,0 +1,116 @@
+// In src-tauri/src/health.rs
+//
+// `/healthz` and `/version` on the embedded server, so monitors, scripts
+// and the browser app can check on the backend without Tauri IPC. The
+// routes are observer-core's (see server.rs there); this is what they report.
+//
+// `/healthz` reports each component (server, proxy backend, scheduler,
+// database) and answers 503 when one is down; `?verbose=1` runs the full
+// doctor report instead (see doctor.rs). `/version` says exactly which
+// build is running.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager};
+
+use crate::doctor::{self, Check, CheckStatus};
+use crate::history::HistoryDb;
+use crate::timers;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Health {
+    pub status: CheckStatus,
+    pub components: BTreeMap<&'static str, Check>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    pub app_version: String,
+    pub commit: &'static str,
+    pub built_at: Option<DateTime<Utc>>,
+    pub tauri_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub debug_build: bool,
+}
+
+fn check_scheduler(app: &AppHandle) -> Check {
+    const ID: &str = "scheduler";
+    let limit = timers::tick_interval() * 3;
+    match timers::last_tick(app) {
+        Some(tick) if (Utc::now() - tick).to_std().unwrap_or_default() <= limit => {
+            Check::ok(ID, format!("Last tick at {}", tick.to_rfc3339()))
+        }
+        Some(tick) => Check::failed(
+            ID,
+            CheckStatus::Error,
+            format!("No tick since {}", tick.to_rfc3339()),
+            "Timers and break reminders are stuck; restart Observer",
+        ),
+        None => Check::ok(ID, "Starting"),
+    }
+}
+
+fn check_database(app: &AppHandle) -> Check {
+    const ID: &str = "database";
+    let Some(db) = app.try_state::<HistoryDb>() else {
+        return Check::failed(ID, CheckStatus::Error, "The history database isn't open", "Check the startup log");
+    };
+    let result: Result<i64, _> = db.0.lock().unwrap().query_row("SELECT 1", [], |row| row.get(0));
+    match result {
+        Ok(_) => Check::ok(ID, "Open"),
+        Err(e) => Check::failed(ID, CheckStatus::Error, e.to_string(), "Run the doctor for details"),
+    }
+}
+
+pub async fn health(app: &AppHandle) -> Health {
+    let mut components = BTreeMap::new();
+    components.insert("server", Check::ok("server", "Answering"));
+    let mut backend = doctor::check_ollama(app).await;
+    backend.id = "proxy_backend";
+    components.insert("proxy_backend", backend);
+    components.insert("scheduler", check_scheduler(app));
+    components.insert("database", check_database(app));
+
+    let status = if components.values().any(|c| c.status == CheckStatus::Error) {
+        CheckStatus::Error
+    } else {
+        CheckStatus::Ok
+    };
+    Health { status, components }
+}
+
+pub fn version(app: &AppHandle) -> VersionInfo {
+    VersionInfo {
+        app_version: app.package_info().version.to_string(),
+        commit: env!("OBSERVER_GIT_COMMIT"),
+        built_at: env!("OBSERVER_BUILD_TIMESTAMP")
+            .parse()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+        tauri_version: tauri::VERSION,
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        debug_build: cfg!(debug_assertions),
+    }
+}
+
+// What `/healthz` answers: whether the app is healthy, and the component
+// checks or, with `verbose`, the full doctor report.
+pub async fn report(app: &AppHandle, verbose: bool) -> (bool, Value) {
+    let (healthy, body) = if verbose {
+        let report = doctor::run(app).await;
+        (report.healthy, serde_json::to_value(report))
+    } else {
+        let health = health(app).await;
+        (health.status != CheckStatus::Error, serde_json::to_value(health))
+    };
+    (healthy, body.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn get_version_info(app: AppHandle) -> VersionInfo {
+    version(&app)
+}
@@ -0,0 +1,359 @@
+// In src-tauri/src/history.rs
+//
+// SQLite history of everything the observer produced: observations,
+// transcriptions, agent outputs and generated summaries. The frontend records
+// entries through `record_history`, or `POST /history` from the web app;
+// backend jobs read and write it directly.
+
+use axum::{extract::State as AxumState, http::StatusCode, Json};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{evaluation, incognito, storage, AppState};
+
+pub const DB_FILE: &str = "history.db";
+
+pub const KIND_OBSERVATION: &str = "observation";
+pub const KIND_TRANSCRIPTION: &str = "transcription";
+pub const KIND_AGENT_OUTPUT: &str = "agent_output";
+pub const KIND_SUMMARY: &str = "summary";
+pub const KIND_EMAIL: &str = "email";
+pub const KIND_FEED: &str = "feed";
+pub const KIND_VIDEO: &str = "video";
+
+const KINDS: &[&str] = &[
+    KIND_OBSERVATION,
+    KIND_TRANSCRIPTION,
+    KIND_AGENT_OUTPUT,
+    KIND_SUMMARY,
+    KIND_EMAIL,
+    KIND_FEED,
+    KIND_VIDEO,
+];
+
+pub struct HistoryDb(pub Mutex<Connection>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub kind: String,
+    pub agent_id: Option<String>,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryRecord {
+    pub kind: String,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    pub content: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct HistoryQuery {
+    pub kind: Option<String>,
+    pub agent_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+}
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+         CREATE TABLE IF NOT EXISTS entries (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             kind TEXT NOT NULL,
+             agent_id TEXT,
+             content TEXT NOT NULL,
+             created_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS entries_created_at ON entries(created_at);
+         CREATE INDEX IF NOT EXISTS entries_kind ON entries(kind, created_at);
+         CREATE TABLE IF NOT EXISTS activity (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             app_name TEXT NOT NULL,
+             title TEXT NOT NULL,
+             started_at INTEGER NOT NULL,
+             ended_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS activity_started_at ON activity(started_at);
+         CREATE TABLE IF NOT EXISTS context_memory (
+             agent_id TEXT PRIMARY KEY,
+             summary TEXT NOT NULL,
+             compacted_turns INTEGER NOT NULL,
+             updated_at INTEGER NOT NULL,
+             covered_hash TEXT
+         );
+         CREATE TABLE IF NOT EXISTS vectors (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             collection TEXT NOT NULL,
+             text TEXT NOT NULL,
+             metadata TEXT,
+             embedding BLOB,
+             model TEXT,
+             created_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS vectors_collection ON vectors(collection, created_at);
+         CREATE TABLE IF NOT EXISTS agent_kv (
+             agent_id TEXT NOT NULL,
+             key TEXT NOT NULL,
+             value TEXT NOT NULL,
+             updated_at INTEGER NOT NULL,
+             PRIMARY KEY (agent_id, key)
+         );
+         CREATE TABLE IF NOT EXISTS comparisons (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             run_id TEXT NOT NULL,
+             model TEXT NOT NULL,
+             base_url TEXT NOT NULL,
+             prompt TEXT NOT NULL,
+             response TEXT NOT NULL,
+             first_token_ms INTEGER,
+             total_ms INTEGER NOT NULL,
+             prompt_tokens INTEGER,
+             output_tokens INTEGER,
+             tokens_per_second REAL,
+             error TEXT,
+             created_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS comparisons_run ON comparisons(run_id);
+         CREATE TABLE IF NOT EXISTS conversations (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             title TEXT NOT NULL,
+             created_at INTEGER NOT NULL,
+             updated_at INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS messages (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             conversation_id INTEGER NOT NULL,
+             parent_id INTEGER,
+             role TEXT NOT NULL,
+             content TEXT NOT NULL,
+             model TEXT,
+             created_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS messages_conversation ON messages(conversation_id, id);
+         CREATE INDEX IF NOT EXISTS messages_parent ON messages(parent_id);
+         CREATE TABLE IF NOT EXISTS attachments (
+             hash TEXT PRIMARY KEY,
+             mime TEXT NOT NULL,
+             size INTEGER NOT NULL,
+             created_at INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS attachment_refs (
+             hash TEXT NOT NULL,
+             conversation_id INTEGER NOT NULL,
+             PRIMARY KEY (hash, conversation_id)
+         );
+         CREATE TABLE IF NOT EXISTS feed_items (
+             feed_id TEXT NOT NULL,
+             item_id TEXT NOT NULL,
+             title TEXT NOT NULL,
+             link TEXT,
+             fetched_at INTEGER NOT NULL,
+             PRIMARY KEY (feed_id, item_id)
+         );
+         CREATE TABLE IF NOT EXISTS usage (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             created_at INTEGER NOT NULL,
+             agent_id TEXT,
+             model TEXT NOT NULL,
+             backend TEXT NOT NULL,
+             local INTEGER NOT NULL,
+             prompt_tokens INTEGER NOT NULL,
+             completion_tokens INTEGER NOT NULL,
+             duration_ms INTEGER NOT NULL,
+             request_id TEXT
+         );
+         CREATE INDEX IF NOT EXISTS usage_created_at ON usage(created_at);
+         CREATE TABLE IF NOT EXISTS replay_runs (
+             run_id TEXT PRIMARY KEY,
+             created_at INTEGER NOT NULL,
+             agent_id TEXT,
+             model TEXT,
+             backend TEXT NOT NULL,
+             path TEXT NOT NULL,
+             request BLOB NOT NULL,
+             answer TEXT
+         );
+         CREATE INDEX IF NOT EXISTS replay_runs_created_at ON replay_runs(created_at);
+         CREATE TABLE IF NOT EXISTS provenance (
+             entry_id INTEGER NOT NULL,
+             source_id TEXT NOT NULL,
+             kind TEXT NOT NULL,
+             label TEXT NOT NULL,
+             content TEXT NOT NULL,
+             PRIMARY KEY (entry_id, source_id)
+         );
+         CREATE TABLE IF NOT EXISTS jobs (
+             job_id TEXT PRIMARY KEY,
+             kind TEXT NOT NULL,
+             payload TEXT NOT NULL,
+             status TEXT NOT NULL,
+             reason TEXT,
+             attempts INTEGER NOT NULL,
+             updated_at INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS fallback_runs (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             created_at INTEGER NOT NULL,
+             agent_id TEXT NOT NULL,
+             path TEXT NOT NULL,
+             step INTEGER,
+             model TEXT,
+             backend TEXT,
+             errors TEXT NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS fallback_runs_agent ON fallback_runs (agent_id, created_at);
+         CREATE TABLE IF NOT EXISTS agent_test_runs (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             agent_id TEXT NOT NULL,
+             model TEXT NOT NULL,
+             model_digest TEXT,
+             created_at INTEGER NOT NULL,
+             passed INTEGER NOT NULL,
+             failed INTEGER NOT NULL,
+             results TEXT NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS agent_test_runs_agent ON agent_test_runs (agent_id, created_at);
+         CREATE TABLE IF NOT EXISTS evaluations (
+             entry_id INTEGER PRIMARY KEY,
+             agent_id TEXT NOT NULL,
+             model TEXT NOT NULL,
+             judge_model TEXT NOT NULL,
+             scores TEXT NOT NULL,
+             notes TEXT NOT NULL,
+             created_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS evaluations_created_at ON evaluations (created_at);
+         CREATE TABLE IF NOT EXISTS feedback (
+             entry_id INTEGER PRIMARY KEY,
+             rating INTEGER NOT NULL,
+             correction TEXT,
+             updated_at INTEGER NOT NULL
+         );",
+    )?;
+    // Columns added after their table first shipped.
+    add_column(conn, "context_memory", "covered_hash", "TEXT")
+}
+
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1", table),
+        [column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+pub fn open(app: &AppHandle) -> Result<HistoryDb, String> {
+    let path = storage::data_path(app, DB_FILE)?;
+    log::info!("Opening history database at {:?}", path);
+    let conn = Connection::open(&path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    migrate(&conn).map_err(|e| format!("Failed to migrate history database: {}", e))?;
+    Ok(HistoryDb(Mutex::new(conn)))
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis).single().unwrap_or_default()
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        agent_id: row.get(2)?,
+        content: row.get(3)?,
+        created_at: from_millis(row.get(4)?),
+    })
+}
+
+impl HistoryDb {
+    pub fn insert(&self, kind: &str, agent_id: Option<&str>, content: &str) -> Result<i64, String> {
+        incognito::ensure_persistent()?;
+        let conn = self.0.lock().unwrap();
+        conn.execute(
+            "INSERT INTO entries (kind, agent_id, content, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![kind, agent_id, content, Utc::now().timestamp_millis()],
+        )
+        .map_err(|e| format!("Failed to write history entry: {}", e))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Result<Vec<HistoryEntry>, String> {
+        let conn = self.0.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, kind, agent_id, content, created_at FROM entries
+                 WHERE (?1 IS NULL OR kind = ?1)
+                   AND (?2 IS NULL OR agent_id = ?2)
+                   AND (?3 IS NULL OR created_at >= ?3)
+                   AND (?4 IS NULL OR created_at < ?4)
+                 ORDER BY created_at DESC
+                 LIMIT ?5",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map(
+                params![
+                    query.kind,
+                    query.agent_id,
+                    query.since.map(|t| t.timestamp_millis()),
+                    query.until.map(|t| t.timestamp_millis()),
+                    query.limit.unwrap_or(1000),
+                ],
+                row_to_entry,
+            )
+            .map_err(|e| e.to_string())?;
+
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| format!("Failed to read history: {}", e))
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let db = open(app)?;
+    app.manage(db);
+    Ok(())
+}
+
+fn record(app: &AppHandle, entry: &HistoryRecord) -> Result<i64, String> {
+    if !KINDS.contains(&entry.kind.as_str()) {
+        return Err(format!("Unknown history kind '{}', expected one of {:?}", entry.kind, KINDS));
+    }
+    let id = app.state::<HistoryDb>().insert(&entry.kind, entry.agent_id.as_deref(), &entry.content)?;
+    if let Some(agent_id) = entry.agent_id.as_deref().filter(|_| entry.kind == KIND_AGENT_OUTPUT) {
+        evaluation::on_agent_output(app, id, agent_id, &entry.content);
+    }
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn record_history(app: AppHandle, kind: String, agent_id: Option<String>, content: String) -> Result<i64, String> {
+    record(&app, &HistoryRecord { kind, agent_id, content })
+}
+
+pub async fn record_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(entry): Json<HistoryRecord>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    record(&state.app_handle, &entry)
+        .map(|id| Json(json!({ "id": id })))
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+#[tauri::command]
+pub fn query_history(query: HistoryQuery, db: State<'_, HistoryDb>) -> Result<Vec<HistoryEntry>, String> {
+    db.query(&query)
+}
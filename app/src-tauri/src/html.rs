@@ -0,0 +1,48 @@
+// In src-tauri/src/html.rs
+//
+// Rough HTML-to-text conversion for feeding web pages and HTML mail to a
+// model: drops scripts/styles and tags, decodes common entities and
+// collapses whitespace. Not a renderer, just enough to read.
+
+pub fn to_text(html: &str) -> String {
+    // ASCII lowercasing keeps byte offsets identical to `html`.
+    let lower = html.to_ascii_lowercase();
+    let mut text = String::with_capacity(html.len() / 2);
+    let mut i = 0;
+
+    while i < html.len() {
+        let rest = &lower[i..];
+        if rest.starts_with('<') {
+            // Skip the whole element for script/style, only the tag otherwise.
+            let skip_until = ["script", "style", "noscript"]
+                .iter()
+                .find(|tag| rest[1..].starts_with(**tag))
+                .map(|tag| format!("</{}>", tag));
+            let end = match skip_until {
+                Some(close) => rest.find(&close).map(|p| p + close.len()),
+                None => rest.find('>').map(|p| p + 1),
+            };
+            match end {
+                Some(end) => {
+                    i += end;
+                    text.push(' ');
+                }
+                None => break,
+            }
+            continue;
+        }
+        let next = rest.find('<').unwrap_or(rest.len());
+        text.push_str(&html[i..i + next]);
+        i += next;
+    }
+
+    let decoded = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
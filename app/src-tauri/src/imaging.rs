@@ -0,0 +1,384 @@
+// In src-tauri/src/imaging.rs
+//
+// Server-side preprocessing of images in vision requests passing through the
+// proxy: `images` of /api/chat messages and /api/generate, and base64 data
+// URLs in the `image_url` parts of OpenAI-style /v1/chat/completions.
+// Screenshots from 4K monitors are downscaled to a resolution vision models
+// actually use, which saves VRAM and upload time, and can get a contrast
+// boost that helps the model read small text. Images that are already small
+// enough but heavy on bytes (PNG screenshots, mostly) are re-encoded as JPEG
+// at `quality` when that makes them smaller.
+//
+// With tiling on, a single very large screenshot is instead cut into tiles
+// that are each sent with the original prompt; the answers are stitched into
+// one response in Ollama's format, so fine detail survives where a
+// downscale would have blurred it.
+
+use axum::{body::Body, http::StatusCode, response::Response};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Cursor;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::storage;
+
+const SETTINGS_FILE: &str = "imaging.json";
+const MAX_TILES: u32 = 9;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageSettings {
+    pub enabled: bool,
+    // Longest side after downscaling.
+    pub max_dimension: u32,
+    // JPEG quality of rewritten images, 30-100.
+    pub quality: u8,
+    // Images within `max_dimension` are still re-encoded above this size.
+    pub reencode_above_kb: u32,
+    pub contrast_boost: bool,
+    // Passed to `adjust_contrast`; positive values increase contrast.
+    pub contrast: f32,
+    pub tiling: bool,
+    // Images whose longest side exceeds this are tiled (when tiling is on).
+    pub tile_threshold: u32,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_dimension: 1568,
+            quality: 90,
+            reencode_above_kb: 512,
+            contrast_boost: false,
+            contrast: 20.0,
+            tiling: false,
+            tile_threshold: 3000,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ImagingState {
+    settings: Mutex<ImageSettings>,
+}
+
+fn settings(app: &AppHandle) -> ImageSettings {
+    app.state::<ImagingState>().settings.lock().unwrap().clone()
+}
+
+fn decode(encoded: &str) -> Result<DynamicImage, String> {
+    let bytes = STANDARD.decode(encoded).map_err(|e| format!("Invalid base64 image: {}", e))?;
+    image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+fn encode(image: &DynamicImage, quality: u8) -> Result<String, String> {
+    let mut bytes = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(image.to_rgb8())
+        .write_to(&mut bytes, image::ImageOutputFormat::Jpeg(quality))
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(STANDARD.encode(bytes.into_inner()))
+}
+
+fn enhance(image: DynamicImage, settings: &ImageSettings) -> DynamicImage {
+    let image = if image.width().max(image.height()) > settings.max_dimension {
+        image.resize(settings.max_dimension, settings.max_dimension, FilterType::Triangle)
+    } else {
+        image
+    };
+    if settings.contrast_boost {
+        image.adjust_contrast(settings.contrast)
+    } else {
+        image
+    }
+}
+
+// None when the image is already small and light and no boost is wanted,
+// when re-encoding wouldn't make it smaller, or when it's left whole for
+// `tiled_response`.
+fn process(encoded: &str, settings: &ImageSettings, tileable: bool) -> Result<Option<String>, String> {
+    let image = decode(encoded)?;
+    let longest = image.width().max(image.height());
+    if tileable && longest > settings.tile_threshold {
+        return Ok(None);
+    }
+    // Base64 is 4 characters for every 3 bytes.
+    let heavy = encoded.len() as u64 * 3 / 4 > settings.reencode_above_kb as u64 * 1024;
+    let reencode_only = longest <= settings.max_dimension && !settings.contrast_boost;
+    if reencode_only && !heavy {
+        return Ok(None);
+    }
+    let processed = encode(&enhance(image, settings), settings.quality)?;
+    if reencode_only && processed.len() >= encoded.len() {
+        return Ok(None);
+    }
+    Ok(Some(processed))
+}
+
+// The `images` arrays of a chat or generate request.
+fn image_arrays(request: &mut Value) -> Vec<&mut Vec<Value>> {
+    let mut arrays = Vec::new();
+    if let Some(Value::Array(messages)) = request.get_mut("messages") {
+        for message in messages {
+            if let Some(Value::Array(images)) = message.get_mut("images") {
+                arrays.push(images);
+            }
+        }
+    } else if let Some(Value::Array(images)) = request.get_mut("images") {
+        arrays.push(images);
+    }
+    arrays
+}
+
+// The base64 data URLs of an OpenAI-style request's `image_url` parts.
+fn data_urls(request: &mut Value) -> Vec<&mut Value> {
+    let mut urls = Vec::new();
+    let Some(Value::Array(messages)) = request.get_mut("messages") else {
+        return urls;
+    };
+    for message in messages {
+        let Some(Value::Array(parts)) = message.get_mut("content") else {
+            continue;
+        };
+        for part in parts {
+            let url = match part.get_mut("image_url") {
+                Some(Value::Object(image_url)) => image_url.get_mut("url"),
+                Some(url) if url.is_string() => Some(url),
+                _ => None,
+            };
+            if let Some(url) = url.filter(|url| url.as_str().is_some_and(|u| u.starts_with("data:image/"))) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+fn preprocess_blocking(body: &[u8], settings: &ImageSettings) -> Option<Vec<u8>> {
+    let mut request: Value = serde_json::from_slice(body).ok()?;
+    let mut changed = false;
+    // Tiling only handles requests with a single image.
+    let tileable = settings.tiling && image_arrays(&mut request).iter().map(|a| a.len()).sum::<usize>() == 1;
+    for images in image_arrays(&mut request) {
+        for image in images.iter_mut() {
+            let Some(encoded) = image.as_str() else {
+                continue;
+            };
+            match process(encoded, settings, tileable) {
+                Ok(Some(processed)) => {
+                    *image = Value::String(processed);
+                    changed = true;
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("Leaving image unprocessed: {}", e),
+            }
+        }
+    }
+    for url in data_urls(&mut request) {
+        let Some((_, encoded)) = url.as_str().and_then(|u| u.split_once(";base64,")) else {
+            continue;
+        };
+        match process(encoded, settings, false) {
+            Ok(Some(processed)) => {
+                *url = Value::String(format!("data:image/jpeg;base64,{}", processed));
+                changed = true;
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Leaving image unprocessed: {}", e),
+        }
+    }
+    if !changed {
+        return None;
+    }
+    serde_json::to_vec(&request).ok()
+}
+
+// Rewrites the request with preprocessed images; None if nothing changed.
+pub async fn preprocess_request(app: &AppHandle, body: &[u8]) -> Option<Vec<u8>> {
+    let settings = settings(app);
+    let mentions = |marker: &[u8]| body.windows(marker.len()).any(|w| w == marker);
+    if !settings.enabled || !(mentions(b"\"images\"") || mentions(b"\"image_url\"")) {
+        return None;
+    }
+    let original_len = body.len();
+    let body = body.to_vec();
+    let processed = tokio::task::spawn_blocking(move || preprocess_blocking(&body, &settings))
+        .await
+        .ok()
+        .flatten()?;
+    log::debug!("Images in the request rewritten: {} KB -> {} KB", original_len / 1024, processed.len() / 1024);
+    Some(processed)
+}
+
+fn position(col: u32, row: u32, cols: u32, rows: u32) -> String {
+    let vertical = match (row, rows) {
+        (_, 1) => "",
+        (0, _) => "top",
+        (r, n) if r == n - 1 => "bottom",
+        _ => "middle",
+    };
+    let horizontal = match (col, cols) {
+        (_, 1) => "",
+        (0, _) => "left",
+        (c, n) if c == n - 1 => "right",
+        _ => "center",
+    };
+    format!("{} {}", vertical, horizontal).trim().to_string()
+}
+
+// Splits the one large image of a request into per-tile requests.
+fn tile_requests(body: &[u8], settings: &ImageSettings) -> Option<Vec<(String, Value)>> {
+    let mut request: Value = serde_json::from_slice(body).ok()?;
+    let source = {
+        let mut images = image_arrays(&mut request).into_iter().flatten();
+        let first = images.next()?.as_str()?.to_string();
+        if images.next().is_some() {
+            return None;
+        }
+        first
+    };
+    let image = decode(&source).ok()?;
+    let (width, height) = image.dimensions();
+    if width.max(height) <= settings.tile_threshold {
+        return None;
+    }
+
+    let cols = width.div_ceil(settings.max_dimension).clamp(1, 3);
+    let rows = height.div_ceil(settings.max_dimension).clamp(1, 3);
+    if cols * rows > MAX_TILES || cols * rows == 1 {
+        return None;
+    }
+    let (tile_w, tile_h) = (width.div_ceil(cols), height.div_ceil(rows));
+
+    let mut requests = Vec::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            let (x, y) = (col * tile_w, row * tile_h);
+            let tile = image.crop_imm(x, y, tile_w.min(width - x), tile_h.min(height - y));
+            let encoded = encode(&enhance(tile, settings), settings.quality).ok()?;
+            let label = position(col, row, cols, rows);
+
+            let mut tile_request = request.clone();
+            for images in image_arrays(&mut tile_request) {
+                if !images.is_empty() {
+                    *images = vec![Value::String(encoded.clone())];
+                }
+            }
+            let note = format!(
+                "[This image is the {} part of a larger screenshot split into {} tiles.]\n",
+                label,
+                cols * rows
+            );
+            if let Some(Value::Array(messages)) = tile_request.get_mut("messages") {
+                if let Some(message) = messages.iter_mut().rev().find(|m| m.get("images").is_some()) {
+                    let content = message["content"].as_str().unwrap_or("").to_string();
+                    message["content"] = Value::String(format!("{}{}", note, content));
+                }
+            } else if let Some(prompt) = tile_request.get("prompt").and_then(Value::as_str) {
+                let prompt = format!("{}{}", note, prompt);
+                tile_request["prompt"] = Value::String(prompt);
+            }
+            tile_request["stream"] = Value::Bool(false);
+            requests.push((label, tile_request));
+        }
+    }
+    Some(requests)
+}
+
+// Runs a request with one oversized image as tiles and returns the stitched
+// answer, or None when tiling doesn't apply.
+pub async fn tiled_response(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    target_url: &str,
+    body: &[u8],
+) -> Option<Result<Response, StatusCode>> {
+    let settings = settings(app);
+    if !settings.enabled || !settings.tiling || !body.windows(8).any(|w| w == b"\"images\"") {
+        return None;
+    }
+    let original: Value = serde_json::from_slice(body).ok()?;
+    let body = body.to_vec();
+    let requests = tokio::task::spawn_blocking(move || tile_requests(&body, &settings))
+        .await
+        .ok()
+        .flatten()?;
+    log::info!("Splitting a large screenshot into {} tiles", requests.len());
+
+    let is_chat = original.get("messages").is_some();
+    let mut parts = Vec::new();
+    let mut last = Value::Null;
+    // Sequential on purpose: parallel requests would only queue up in Ollama
+    // while holding every tile's image in VRAM.
+    for (label, request) in requests {
+        let response = match client.post(target_url).json(&request).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                log::error!("Tile request failed with status {}", response.status());
+                return Some(Err(StatusCode::BAD_GATEWAY));
+            }
+            Err(e) => {
+                log::error!("Tile request failed: {}", e);
+                return Some(Err(StatusCode::BAD_GATEWAY));
+            }
+        };
+        let Ok(answer) = response.json::<Value>().await else {
+            return Some(Err(StatusCode::BAD_GATEWAY));
+        };
+        let text = if is_chat {
+            answer["message"]["content"].as_str()
+        } else {
+            answer["response"].as_str()
+        };
+        parts.push(format!("[{}]\n{}", label, text.unwrap_or("").trim()));
+        last = answer;
+    }
+
+    let stitched = parts.join("\n\n");
+    if is_chat {
+        last["message"] = json!({ "role": "assistant", "content": stitched });
+    } else {
+        last["response"] = Value::String(stitched);
+    }
+    last["done"] = Value::Bool(true);
+
+    // Ollama streams unless told otherwise; a single final NDJSON line is a
+    // valid stream.
+    let streaming = original.get("stream").and_then(Value::as_bool).unwrap_or(true);
+    let (content_type, body) = if streaming {
+        ("application/x-ndjson", format!("{}\n", last))
+    } else {
+        ("application/json", last.to_string())
+    };
+    Some(Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(axum::http::header::CONTENT_TYPE, content_type)
+        .body(Body::from(body))
+        .unwrap()))
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<ImagingState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_image_settings(state: State<'_, ImagingState>) -> ImageSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_image_settings(
+    app: AppHandle,
+    mut settings: ImageSettings,
+    state: State<'_, ImagingState>,
+) -> Result<(), String> {
+    settings.max_dimension = settings.max_dimension.clamp(256, 8192);
+    settings.quality = settings.quality.clamp(30, 100);
+    settings.tile_threshold = settings.tile_threshold.max(settings.max_dimension);
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+// In src-tauri/src/incognito.rs
+//
+// Incognito sessions: while one is on, nothing the observer sees or produces
+// is kept. History entries, conversations, attachments (screenshots among
+// them), screen recordings, token usage, window activity, replay runs,
+// provenance, model comparisons and agent memory (facts, key/values and
+// compacted context) aren't written, and the history database connection is
+// switched to read-only as a backstop, so a write that slips past the checks
+// fails instead of persisting. Settings changes are still saved, and the
+// audit log keeps recording.
+//
+// It's enforced here rather than in the web app and lasts until it's turned
+// off or the app exits; it's never saved. The tray tooltip and menu item
+// show it while it's on.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::menu::MenuItem;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::history::HistoryDb;
+use crate::recording;
+
+pub const TRAY_ITEM_ID: &str = "incognito";
+const TRAY_LABEL_OFF: &str = "Start Incognito Session";
+const TRAY_LABEL_ON: &str = "End Incognito Session";
+const TOOLTIP_OFF: &str = "Observer AI is running";
+const TOOLTIP_ON: &str = "Observer AI is running (incognito: nothing is saved)";
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Default)]
+pub struct IncognitoState {
+    tray_item: Mutex<Option<MenuItem<Wry>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncognitoStatus {
+    pub active: bool,
+}
+
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+// For code about to persist something: Err while a session is on.
+pub fn ensure_persistent() -> Result<(), String> {
+    if is_active() {
+        return Err("Not saved: an incognito session is active".to_string());
+    }
+    Ok(())
+}
+
+pub fn tray_label() -> &'static str {
+    if is_active() {
+        TRAY_LABEL_ON
+    } else {
+        TRAY_LABEL_OFF
+    }
+}
+
+pub fn tooltip() -> &'static str {
+    if is_active() {
+        TOOLTIP_ON
+    } else {
+        TOOLTIP_OFF
+    }
+}
+
+// The tray item, so its label can follow the session.
+pub fn set_tray_item(app: &AppHandle, item: MenuItem<Wry>) {
+    *app.state::<IncognitoState>().tray_item.lock().unwrap() = Some(item);
+}
+
+fn update_tray(app: &AppHandle) {
+    if let Some(item) = app.state::<IncognitoState>().tray_item.lock().unwrap().as_ref() {
+        if let Err(e) = item.set_text(tray_label()) {
+            log::error!("Failed to update the incognito tray item: {}", e);
+        }
+    }
+    if let Some(tray) = app.tray_by_id(crate::TRAY_ID) {
+        if let Err(e) = tray.set_tooltip(Some(tooltip())) {
+            log::error!("Failed to update the tray tooltip: {}", e);
+        }
+        // Shown next to the icon in the macOS menu bar.
+        let _ = tray.set_title(is_active().then_some("Incognito"));
+    }
+}
+
+pub fn set(app: &AppHandle, active: bool) -> Result<(), String> {
+    if active && recording::is_recording(app) {
+        return Err("Stop the screen recording before starting an incognito session".to_string());
+    }
+    {
+        let db = app.state::<HistoryDb>();
+        let conn = db.0.lock().unwrap();
+        conn.pragma_update(None, "query_only", active)
+            .map_err(|e| format!("Failed to switch the history database: {}", e))?;
+        ACTIVE.store(active, Ordering::SeqCst);
+    }
+    log::info!("Incognito session {}", if active { "started" } else { "ended" });
+    update_tray(app);
+    if let Err(e) = app.emit("incognito-changed", IncognitoStatus { active }) {
+        log::error!("Failed to emit incognito-changed event: {}", e);
+    }
+    Ok(())
+}
+
+// From the tray.
+pub fn toggle(app: &AppHandle) {
+    if let Err(e) = set(app, !is_active()) {
+        log::error!("Failed to toggle the incognito session: {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn get_incognito_status() -> IncognitoStatus {
+    IncognitoStatus { active: is_active() }
+}
+
+#[tauri::command]
+pub fn set_incognito(app: AppHandle, active: bool) -> Result<IncognitoStatus, String> {
+    set(&app, active)?;
+    Ok(IncognitoStatus { active })
+}
@@ -0,0 +1,220 @@
+// In src-tauri/src/ingest.rs
+//
+// Bulk ingestion of documents into a vector store collection. Files (PDF or
+// text, read as in file_drop.rs) and inline texts are split into chunks of
+// about `CHUNK_CHARS` at paragraph boundaries, and the chunks are embedded
+// many per request: each request takes up to `batch_size` chunks or
+// `max_batch_chars` characters, and `concurrency` requests are in flight at
+// once (see VectorSettings), which keeps the GPU busy instead of waiting on
+// one round trip per chunk. A batch whose embedding fails is stored without
+// vectors, as `vector_store::add` does.
+//
+// Progress goes out on "ingest-progress" with the throughput so far and an
+// estimate of the time left.
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::file_drop::{self, DroppedContent, FileKind};
+use crate::history::HistoryDb;
+use crate::{incognito, llm, vector_store};
+
+const CHUNK_CHARS: usize = 1500;
+
+// A chunk and its metadata.
+type Row = (String, Option<Value>);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IngestText {
+    pub text: String,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestProgress {
+    pub collection: String,
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+    pub chunks_per_sec: f64,
+    pub chars_per_sec: f64,
+    pub eta_secs: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestReport {
+    pub collection: String,
+    pub chunks: usize,
+    // Chunks stored without a vector because their batch failed to embed.
+    pub unembedded: usize,
+    pub requests: usize,
+    pub elapsed_ms: u64,
+    pub chunks_per_sec: f64,
+}
+
+// Paragraphs packed into chunks of about `max` characters; a paragraph
+// longer than that is split between words.
+fn chunks(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut push = |piece: &str, current: &mut String| {
+        if !current.is_empty() && current.chars().count() + piece.chars().count() > max {
+            chunks.push(std::mem::take(current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(piece);
+    };
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if paragraph.chars().count() <= max {
+            push(paragraph, &mut current);
+            continue;
+        }
+        for word in paragraph.split_whitespace() {
+            push(word, &mut current);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    match file_drop::classify(path) {
+        kind @ (FileKind::Pdf | FileKind::Text) => match file_drop::process_file(path, kind)? {
+            DroppedContent::Pdf { text } | DroppedContent::Text { text } => Ok(text),
+            _ => Err(format!("{} isn't a document", path.display())),
+        },
+        _ => Err(format!("{} isn't a PDF or text file", path.display())),
+    }
+}
+
+// Consecutive chunks grouped into embedding requests.
+fn batches(rows: Vec<Row>, batch_size: usize, max_chars: usize) -> Vec<Vec<Row>> {
+    let mut batches: Vec<Vec<Row>> = Vec::new();
+    let mut chars = 0;
+    for row in rows {
+        let len = row.0.chars().count();
+        match batches.last_mut() {
+            Some(batch) if batch.len() < batch_size && chars + len <= max_chars => {
+                chars += len;
+                batch.push(row);
+            }
+            _ => {
+                chars = len;
+                batches.push(vec![row]);
+            }
+        }
+    }
+    batches
+}
+
+pub async fn ingest(app: &AppHandle, collection: &str, texts: Vec<IngestText>) -> Result<IngestReport, String> {
+    incognito::ensure_persistent()?;
+    let settings = vector_store::settings(app);
+    let rows: Vec<Row> = texts
+        .iter()
+        .flat_map(|text| {
+            chunks(&text.text, CHUNK_CHARS).into_iter().enumerate().map(|(i, chunk)| {
+                let metadata = json!({ "source": text.source, "chunk": i });
+                (chunk, Some(metadata))
+            })
+        })
+        .collect();
+    let total = rows.len();
+    let batches = batches(rows, settings.batch_size.max(1), settings.max_batch_chars.max(1));
+    let requests = batches.len();
+    log::info!("Ingesting {} chunks into '{}' in {} requests", total, collection, requests);
+
+    let started = Instant::now();
+    let model = settings.embedding_model.clone();
+    let mut embedded = futures::stream::iter(batches)
+        .map(|batch| {
+            let model = model.clone();
+            async move {
+                let inputs: Vec<String> = batch.iter().map(|(text, _)| text.clone()).collect();
+                let embeddings = llm::embed(app, &model, &inputs).await;
+                (batch, embeddings)
+            }
+        })
+        .buffered(settings.concurrency.max(1));
+
+    let (mut done, mut chars, mut unembedded) = (0, 0, 0);
+    while let Some((batch, embeddings)) = embedded.next().await {
+        let embeddings = embeddings.unwrap_or_else(|e| {
+            log::warn!("Storing {} '{}' chunks without embeddings: {}", batch.len(), collection, e);
+            unembedded += batch.len();
+            Vec::new()
+        });
+        vector_store::insert_many(&app.state::<HistoryDb>(), collection, &batch, &model, &embeddings)?;
+
+        done += batch.len();
+        chars += batch.iter().map(|(text, _)| text.chars().count()).sum::<usize>();
+        let elapsed = started.elapsed().as_secs_f64().max(0.001);
+        let rate = done as f64 / elapsed;
+        let progress = IngestProgress {
+            collection: collection.to_string(),
+            chunks_done: done,
+            chunks_total: total,
+            chunks_per_sec: rate,
+            chars_per_sec: chars as f64 / elapsed,
+            eta_secs: (rate > 0.0).then(|| (total - done) as f64 / rate),
+        };
+        if let Err(e) = app.emit("ingest-progress", progress) {
+            log::error!("Failed to emit ingest-progress event: {}", e);
+        }
+    }
+
+    let elapsed = started.elapsed();
+    let report = IngestReport {
+        collection: collection.to_string(),
+        chunks: total,
+        unembedded,
+        requests,
+        elapsed_ms: elapsed.as_millis() as u64,
+        chunks_per_sec: total as f64 / elapsed.as_secs_f64().max(0.001),
+    };
+    log::info!(
+        "Ingested {} chunks into '{}' in {:.1}s ({:.1} chunks/s)",
+        total,
+        collection,
+        elapsed.as_secs_f64(),
+        report.chunks_per_sec
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn ingest_texts(
+    app: AppHandle,
+    collection: String,
+    texts: Vec<IngestText>,
+) -> Result<IngestReport, String> {
+    ingest(&app, &collection, texts).await
+}
+
+#[tauri::command]
+pub async fn ingest_files(
+    app: AppHandle,
+    collection: String,
+    paths: Vec<String>,
+) -> Result<IngestReport, String> {
+    let texts = tokio::task::spawn_blocking(move || {
+        paths
+            .iter()
+            .map(|path| {
+                let text = read_file(Path::new(path))?;
+                Ok(IngestText { text, source: Some(path.clone()) })
+            })
+            .collect::<Result<Vec<_>, String>>()
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    ingest(&app, &collection, texts).await
+}
@@ -0,0 +1,342 @@
+// In src-tauri/src/input_control.rs
+//
+// Mouse and keyboard tools for supervised screen automation: `move_mouse`,
+// `click` and `type_text`, driven with enigo. They're off until the user
+// enables them, and then each action waits for the user's approval, as shell
+// commands do: it's sent as "input-approval-requested" to the launcher window,
+// which is brought forward to ask and hides again before an approved action
+// runs, and runs only after `respond_input_approval`, never on timeout.
+//
+// Agents in `autonomous_agents` skip the approval. While any agent holds that
+// grant, a small always-on-top indicator window is shown, from which the
+// grant can be revoked; the kill switch stops all input too. Every request,
+// decision and outcome goes to the audit log.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, State, WebviewUrl, WebviewWindowBuilder};
+use tokio::sync::oneshot;
+
+use crate::tools::{self, ToolSpec};
+use crate::{audit, deep_link, privacy, storage};
+
+const SETTINGS_FILE: &str = "input_control.json";
+const INDICATOR_LABEL: &str = "automation";
+const INDICATOR_WIDTH: f64 = 420.0;
+const INDICATOR_HEIGHT: f64 = 56.0;
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(120);
+pub const TOOL_NAMES: &[&str] = &["move_mouse", "click", "type_text"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputSettings {
+    pub enabled: bool,
+    // Agents whose actions run without asking.
+    pub autonomous_agents: Vec<String>,
+    pub max_text_chars: usize,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self { enabled: false, autonomous_agents: Vec::new(), max_text_chars: 500 }
+    }
+}
+
+#[derive(Default)]
+pub struct InputState {
+    settings: Mutex<InputSettings>,
+    pending: Mutex<HashMap<String, oneshot::Sender<bool>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "tool", rename_all = "snake_case")]
+pub enum InputAction {
+    MoveMouse {
+        x: i32,
+        y: i32,
+    },
+    Click {
+        // Where to click; the current position without.
+        x: Option<i32>,
+        y: Option<i32>,
+        #[serde(default)]
+        button: MouseButton,
+        #[serde(default)]
+        double: bool,
+    },
+    TypeText {
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InputApprovalRequest {
+    pub id: String,
+    pub agent_id: Option<String>,
+    pub action: InputAction,
+    pub justification: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PerformedAction<'a> {
+    agent_id: Option<&'a str>,
+    action: &'a InputAction,
+    autonomous: bool,
+}
+
+pub fn tools() -> Vec<ToolSpec> {
+    let justification = json!({ "type": "string", "description": "Why this action is needed" });
+    vec![
+        ToolSpec {
+            name: "move_mouse",
+            description: "Move the mouse pointer to a position on screen, in pixels from the top left. The user \
+must approve it unless they granted this agent autonomy.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "x": { "type": "integer" },
+                    "y": { "type": "integer" },
+                    "justification": justification
+                },
+                "required": ["x", "y", "justification"]
+            }),
+        },
+        ToolSpec {
+            name: "click",
+            description: "Click at a position on screen (or where the pointer is). Positions come from the \
+screen's UI elements. The user must approve it unless they granted this agent autonomy.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "x": { "type": "integer" },
+                    "y": { "type": "integer" },
+                    "button": { "type": "string", "enum": ["left", "right", "middle"] },
+                    "double": { "type": "boolean" },
+                    "justification": justification
+                },
+                "required": ["justification"]
+            }),
+        },
+        ToolSpec {
+            name: "type_text",
+            description: "Type text with the keyboard into the focused field. The user must approve it unless \
+they granted this agent autonomy.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string" },
+                    "justification": justification
+                },
+                "required": ["text", "justification"]
+            }),
+        },
+    ]
+}
+
+fn settings(app: &AppHandle) -> InputSettings {
+    app.state::<InputState>().settings.lock().unwrap().clone()
+}
+
+pub fn enabled(app: &AppHandle) -> bool {
+    app.state::<InputState>().settings.lock().unwrap().enabled
+}
+
+fn indicator_position(app: &AppHandle) -> Option<PhysicalPosition<i32>> {
+    let monitor = app.primary_monitor().ok().flatten()?;
+    let width = (INDICATOR_WIDTH * monitor.scale_factor()) as i32;
+    let x = monitor.position().x + (monitor.size().width as i32 - width) / 2;
+    Some(PhysicalPosition::new(x, monitor.position().y + 12))
+}
+
+// Shows the indicator while any agent may act without asking, hides it otherwise.
+fn update_indicator(app: &AppHandle) {
+    let settings = settings(app);
+    let needed = settings.enabled && !settings.autonomous_agents.is_empty();
+    let result = match (app.get_webview_window(INDICATOR_LABEL), needed) {
+        (Some(window), false) => window.close().map_err(|e| e.to_string()),
+        (None, true) => {
+            WebviewWindowBuilder::new(app, INDICATOR_LABEL, WebviewUrl::App("index.html#automation".into()))
+                .title("Automation active")
+                .inner_size(INDICATOR_WIDTH, INDICATOR_HEIGHT)
+                .decorations(false)
+                .always_on_top(true)
+                .skip_taskbar(true)
+                .resizable(false)
+                .build()
+                .map(|window| {
+                    if let Some(position) = indicator_position(app) {
+                        let _ = window.set_position(position);
+                    }
+                })
+                .map_err(|e| e.to_string())
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        log::error!("Failed to update the automation indicator: {}", e);
+    }
+}
+
+async fn request_approval(app: &AppHandle, request: &InputApprovalRequest) -> Result<bool, String> {
+    let state = app.state::<InputState>();
+    let (tx, rx) = oneshot::channel();
+    state.pending.lock().unwrap().insert(request.id.clone(), tx);
+    app.emit("input-approval-requested", request).map_err(|e| e.to_string())?;
+    deep_link::show_main_window(app);
+
+    let answer = tokio::time::timeout(APPROVAL_TIMEOUT, rx).await;
+    state.pending.lock().unwrap().remove(&request.id);
+    // A timeout or a dropped request counts as a denial.
+    Ok(matches!(answer, Ok(Ok(true))))
+}
+
+fn perform(action: &InputAction) -> Result<(), String> {
+    use enigo::{Button, Coordinate, Direction, Enigo, Keyboard, Mouse, Settings};
+
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| format!("Input control is unavailable: {}", e))?;
+    let result = match action {
+        InputAction::MoveMouse { x, y } => enigo.move_mouse(*x, *y, Coordinate::Abs),
+        InputAction::Click { x, y, button, double } => {
+            let button = match button {
+                MouseButton::Left => Button::Left,
+                MouseButton::Right => Button::Right,
+                MouseButton::Middle => Button::Middle,
+            };
+            let moved = match (x, y) {
+                (Some(x), Some(y)) => enigo.move_mouse(*x, *y, Coordinate::Abs),
+                _ => Ok(()),
+            };
+            moved
+                .and_then(|_| enigo.button(button, Direction::Click))
+                .and_then(|_| if *double { enigo.button(button, Direction::Click) } else { Ok(()) })
+        }
+        InputAction::TypeText { text } => enigo.text(text),
+    };
+    result.map_err(|e| format!("Input action failed: {}", e))
+}
+
+fn parse(name: &str, mut args: Value) -> Result<(InputAction, String), String> {
+    let justification = args
+        .get("justification")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| format!("{} needs a justification", name))?;
+    if let Some(object) = args.as_object_mut() {
+        object.remove("justification");
+        object.insert("tool".to_string(), json!(name));
+    }
+    Ok((tools::parse_args(name, args)?, justification))
+}
+
+pub async fn call(app: &AppHandle, agent_id: Option<&str>, name: &str, args: Value) -> Result<Value, String> {
+    let settings = settings(app);
+    if !settings.enabled {
+        return Err("Mouse and keyboard control is turned off in the settings".to_string());
+    }
+    privacy::ensure_allowed(app)?;
+    let (action, justification) = parse(name, args)?;
+    if let InputAction::TypeText { text } = &action {
+        if text.chars().count() > settings.max_text_chars {
+            return Err(format!("type_text is limited to {} characters", settings.max_text_chars));
+        }
+    }
+
+    let autonomous = agent_id.is_some_and(|id| settings.autonomous_agents.iter().any(|a| a == id));
+    let detail = json!({ "action": action, "justification": justification, "autonomous": autonomous });
+    if !autonomous {
+        let request = InputApprovalRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            agent_id: agent_id.map(str::to_string),
+            action: action.clone(),
+            justification,
+        };
+        log::info!("Agent {:?} asks to {}, waiting for the user", agent_id, name);
+        if !request_approval(app, &request).await? {
+            audit::record(app, agent_id, "input.denied", detail);
+            return Err(format!("The user did not approve this {} action", name));
+        }
+        audit::record(app, agent_id, "input.approved", detail);
+    }
+    // The switch may have been engaged while waiting.
+    privacy::ensure_allowed(app)?;
+
+    let performed = action.clone();
+    let result = tokio::task::spawn_blocking(move || perform(&performed)).await.map_err(|e| e.to_string())?;
+    let outcome = match &result {
+        Ok(()) => json!({ "action": action, "autonomous": autonomous }),
+        Err(e) => json!({ "action": action, "autonomous": autonomous, "error": e }),
+    };
+    audit::record(app, agent_id, "input.performed", outcome);
+    if let Err(e) = app.emit("input-action", PerformedAction { agent_id, action: &action, autonomous }) {
+        log::error!("Failed to emit input-action event: {}", e);
+    }
+    result.map(|_| json!({ "done": true }))
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<InputState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+    update_indicator(app);
+}
+
+fn save(app: &AppHandle, settings: InputSettings) -> Result<(), String> {
+    storage::save_json(app, SETTINGS_FILE, &settings)?;
+    *app.state::<InputState>().settings.lock().unwrap() = settings;
+    update_indicator(app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_input_settings(state: State<'_, InputState>) -> InputSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+// Async: the indicator window may be created, which deadlocks in synchronous
+// commands on Windows.
+#[tauri::command]
+pub async fn set_input_settings(app: AppHandle, settings: InputSettings) -> Result<(), String> {
+    let previous = self::settings(&app);
+    for agent in settings.autonomous_agents.iter().filter(|a| !previous.autonomous_agents.contains(a)) {
+        audit::record(&app, Some(agent), "input.autonomy_granted", json!({}));
+    }
+    for agent in previous.autonomous_agents.iter().filter(|a| !settings.autonomous_agents.contains(a)) {
+        audit::record(&app, Some(agent), "input.autonomy_revoked", json!({}));
+    }
+    if settings.enabled != previous.enabled {
+        audit::record(&app, None, "input.enabled", json!({ "enabled": settings.enabled }));
+    }
+    save(&app, settings)
+}
+
+// From the indicator: takes autonomy away from every agent at once.
+#[tauri::command]
+pub async fn revoke_input_autonomy(app: AppHandle) -> Result<(), String> {
+    let mut settings = settings(&app);
+    for agent in settings.autonomous_agents.drain(..) {
+        audit::record(&app, Some(&agent), "input.autonomy_revoked", json!({}));
+    }
+    save(&app, settings)
+}
+
+#[tauri::command]
+pub fn respond_input_approval(id: String, approved: bool, state: State<'_, InputState>) -> Result<(), String> {
+    let sender = state
+        .pending
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| "Approval request not found or expired".to_string())?;
+    let _ = sender.send(approved);
+    Ok(())
+}
@@ -0,0 +1,179 @@
+// In src-tauri/src/jobs.rs
+//
+// Pending work that has to survive a crash or reboot: one-shot timers,
+// batch jobs and managed model downloads. Each is written to the `jobs`
+// table in the history DB when it starts and removed when it ends, so
+// whatever is still there at startup was interrupted. `resume` picks those
+// up again: timers are re-armed (and fire right away if they came due while
+// the app was down), batch jobs run again from the start and downloads
+// continue from their partial blobs. A job interrupted `MAX_ATTEMPTS` times,
+// or one that can't be restarted, is marked failed with the reason instead;
+// `list_failed_jobs` shows those until they're cleared.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::{batch, model_manager, timers};
+
+pub const KIND_TIMER: &str = "timer";
+pub const KIND_BATCH: &str = "batch";
+pub const KIND_DOWNLOAD: &str = "download";
+
+// Counting the first run.
+const MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedJob {
+    pub job_id: String,
+    pub kind: String,
+    pub reason: String,
+    pub attempts: u32,
+    pub payload: Value,
+    pub updated_at: DateTime<Utc>,
+}
+
+struct PendingJob {
+    job_id: String,
+    kind: String,
+    payload: String,
+    attempts: u32,
+}
+
+// Records a job as pending. Calling it again for the same job updates the
+// payload and keeps the attempt count.
+pub fn persist<T: Serialize>(app: &AppHandle, kind: &str, job_id: &str, payload: &T) {
+    let payload = match serde_json::to_string(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            log::error!("Failed to serialize {} job {}: {}", kind, job_id, e);
+            return;
+        }
+    };
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    if let Err(e) = conn.execute(
+        "INSERT INTO jobs (job_id, kind, payload, status, attempts, updated_at) VALUES (?1, ?2, ?3, 'pending', 1, ?4)
+         ON CONFLICT(job_id) DO UPDATE SET payload = excluded.payload, updated_at = excluded.updated_at",
+        params![job_id, kind, payload, Utc::now().timestamp_millis()],
+    ) {
+        log::error!("Failed to persist {} job {}: {}", kind, job_id, e);
+    }
+}
+
+// Forgets a job that ended, however it ended.
+pub fn complete(app: &AppHandle, job_id: &str) {
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    if let Err(e) = conn.execute("DELETE FROM jobs WHERE job_id = ?1 AND status = 'pending'", params![job_id]) {
+        log::error!("Failed to remove job {}: {}", job_id, e);
+    }
+}
+
+pub fn fail(app: &AppHandle, job_id: &str, reason: &str) {
+    log::warn!("Job {} failed: {}", job_id, reason);
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    if let Err(e) = conn.execute(
+        "UPDATE jobs SET status = 'failed', reason = ?2, updated_at = ?3 WHERE job_id = ?1",
+        params![job_id, reason, Utc::now().timestamp_millis()],
+    ) {
+        log::error!("Failed to mark job {} as failed: {}", job_id, e);
+    }
+}
+
+fn pending(app: &AppHandle) -> Result<Vec<PendingJob>, String> {
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare("SELECT job_id, kind, payload, attempts FROM jobs WHERE status = 'pending' ORDER BY updated_at")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(PendingJob { job_id: row.get(0)?, kind: row.get(1)?, payload: row.get(2)?, attempts: row.get(3)? })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| format!("Failed to read pending jobs: {}", e))
+}
+
+fn count_attempt(app: &AppHandle, job_id: &str) {
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    if let Err(e) = conn.execute("UPDATE jobs SET attempts = attempts + 1 WHERE job_id = ?1", params![job_id]) {
+        log::error!("Failed to update job {}: {}", job_id, e);
+    }
+}
+
+impl PendingJob {
+    fn payload<T: DeserializeOwned>(&self) -> Result<T, String> {
+        serde_json::from_str(&self.payload).map_err(|e| format!("Damaged job record: {}", e))
+    }
+}
+
+fn restart(app: &AppHandle, job: &PendingJob) -> Result<(), String> {
+    match job.kind.as_str() {
+        KIND_TIMER => timers::restore(app, job.payload()?),
+        KIND_BATCH => batch::resume(app, &job.job_id, job.payload()?),
+        KIND_DOWNLOAD => model_manager::resume(app, &job.job_id, job.payload()?),
+        other => Err(format!("Unknown job kind '{}'", other)),
+    }
+}
+
+// Restarts the jobs that were pending when the app last stopped.
+pub fn resume(app: &AppHandle) {
+    let jobs = match pending(app) {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            log::error!("{}", e);
+            return;
+        }
+    };
+    for job in jobs {
+        // A timer outlives every restart by design, so only work that can crash counts against it.
+        let counted = job.kind != KIND_TIMER;
+        if counted && job.attempts >= MAX_ATTEMPTS {
+            fail(app, &job.job_id, &format!("Interrupted {} times, not resumed again", job.attempts));
+            continue;
+        }
+        log::info!("Resuming interrupted {} job {}", job.kind, job.job_id);
+        if counted {
+            count_attempt(app, &job.job_id);
+        }
+        if let Err(e) = restart(app, &job) {
+            fail(app, &job.job_id, &format!("Couldn't resume after a restart: {}", e));
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_failed_jobs(db: State<'_, HistoryDb>) -> Result<Vec<FailedJob>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT job_id, kind, reason, attempts, payload, updated_at FROM jobs
+             WHERE status = 'failed' ORDER BY updated_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(FailedJob {
+                job_id: row.get(0)?,
+                kind: row.get(1)?,
+                reason: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                attempts: row.get(3)?,
+                payload: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+                updated_at: Utc.timestamp_millis_opt(row.get(5)?).single().unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| format!("Failed to read failed jobs: {}", e))
+}
+
+#[tauri::command]
+pub fn clear_failed_jobs(db: State<'_, HistoryDb>) -> Result<usize, String> {
+    let conn = db.0.lock().unwrap();
+    conn.execute("DELETE FROM jobs WHERE status = 'failed'", []).map_err(|e| e.to_string())
+}
@@ -2,37 +2,132 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod access_log;
+mod active;
+mod activity;
+mod adapters;
+mod agent_share;
+mod agent_tests;
+mod agents;
+mod analytics;
+mod annotate;
+mod attachments;
+mod audit;
+mod backends;
+mod backup;
+mod batch;
+mod browser_bridge;
+mod budgets;
+mod calendar;
+mod captions;
+mod capture;
+mod catalog;
+mod compaction;
+mod compare;
+mod config;
+mod config_archive;
+mod control;
+mod conversations;
+mod dataset;
+mod deep_link;
+mod doctor;
+mod email;
+mod evaluation;
+mod fallback;
+mod features;
+mod feeds;
+mod file_drop;
+mod files;
+mod focus;
+mod git;
+mod github;
+mod health;
+mod hardware;
+mod history;
+mod html;
+mod imaging;
+mod incognito;
+mod ingest;
+mod injection;
+mod input_control;
+mod jobs;
+mod llm;
+mod locality;
+mod log_store;
+mod memory;
+mod mock;
+mod model_manager;
+mod model_share;
+mod moderation;
+mod mqtt;
+mod notifications;
+mod ocr_languages;
+mod offline;
+mod onboarding;
+mod openwebui_import;
+mod openai_facade;
+mod permissions;
+mod portable;
+mod policy;
+mod power;
+mod presets;
+mod privacy;
+mod profiles;
+mod provenance;
+mod race;
+mod recording;
+mod recovery;
+mod redact;
+mod replay;
+mod response_cache;
+mod request_id;
+mod secrets;
+mod self_monitor;
+mod server;
+mod shell;
+mod sound_events;
+mod spreadsheet;
+mod storage;
+mod structured;
+mod summary;
+mod templates;
+mod timers;
+mod tokenizer;
+mod tools;
+mod transcript_index;
+mod transcription;
+mod tunnel;
+mod ui_elements;
+mod usage;
+mod vector_store;
+mod video;
+mod vram;
+mod wake;
+
 // ---- Final, Corrected Imports ----
 use axum::{
     body::Body,
-    extract::{Query, State as AxumState},
-    http::{HeaderMap, Method, StatusCode, Uri},
-    response::Response,
-    response::sse::{Event, Sse},
-    routing::{any, get},
-    Router,
+    http::{HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Response},
 };
-use futures::stream::Stream;
-use http_body_util::BodyExt;
+use futures::stream::{BoxStream, Stream};
+use futures::StreamExt;
 use reqwest::Client;
-use serde::Deserialize;
-use std::convert::Infallible;
 use std::sync::Mutex;
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{IsMenuItem, Menu, MenuItem, Submenu},
     tray::TrayIconBuilder,
     AppHandle, Manager, State,
 };
 use tauri_plugin_shell::ShellExt;
-use tower_http::{cors::{Any, CorsLayer}, services::ServeDir};
-use tokio::{
-    self, 
-    io::{AsyncBufReadExt, BufReader},
-    process::Command as TokioCommand,
-};
 use futures::future::join_all;
+use observer_core::server::{self as core_server, ProxyRequest};
+use observer_core::{exec, proxy};
 use futures::stream::select as stream_select;
 
+// The tray icon's id, for modules that update it (incognito.rs).
+pub(crate) const TRAY_ID: &str = "observer";
+
 struct AppSettings {
   ollama_url: Mutex<Option<String>>,
 }
@@ -43,6 +138,12 @@ async fn set_ollama_url(
     settings: State<'_, AppSettings>,
 ) -> Result<(), String> {
     log::info!("Setting Ollama URL to: {:?}", new_url);
+    if let Some(pinned) = policy::pinned_ollama_url() {
+        return Err(format!("Your organisation's policy pins the Ollama server to {}", pinned));
+    }
+    if let Some(url) = &new_url {
+        policy::check_backend(url)?;
+    }
     // Lock the mutex to get exclusive access and update the value.
     *settings.ollama_url.lock().unwrap() = new_url;
     Ok(()) // Return Ok to signal success to the frontend
@@ -61,10 +162,10 @@ async fn get_ollama_url(
 
 #[tauri::command]
 async fn check_ollama_servers(urls: Vec<String>) -> Result<Vec<String>, String> { // <-- No State parameter
-    log::info!("Rust backend received request to check servers (using dedicated client): {:?}", urls);
+    log::info!("Rust backend received request to check servers: {:?}", urls);
 
-    // Create a new, temporary client just for this operation.
-    let client = Client::new();
+    // The upstream client, so [upstream.hosts] overrides apply to the check too.
+    let client = llm::client().clone();
 
     // The rest of the logic is identical.
     let checks = urls.into_iter().map(|url| {
@@ -108,170 +209,530 @@ struct AppState {
     http_client: Client,
 }
 
-#[derive(Debug, Deserialize)]
-struct ExecParams {
-    cmd: String,
-}
-
-async fn exec_handler(
-    AxumState(state): AxumState<AppState>,
-    Query(params): Query<ExecParams>,
-) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    log::info!("Received command to execute: '{}'", params.cmd);
-
+// `/exec`: the command's output, tracked in the in-flight list where it can
+// be followed and cancelled.
+fn exec_events(app: AppHandle, cmd: String) -> impl Stream<Item = (Option<&'static str>, String)> + Send + 'static {
     let stream = async_stream::stream! {
         const UNAUTHORIZED_MESSAGE: &str = "[unauthorized]";
 
-        if params.cmd.chars().any(|c| "&;|<>()`$".contains(c)) {
-            log::warn!("Unauthorized command blocked: contains forbidden characters ('{}').", params.cmd);
-            yield Ok(Event::default().event("error").data(UNAUTHORIZED_MESSAGE));
+        if let Err(e) = features::ensure(features::Feature::Exec) {
+            yield (Some("error"), e);
             return;
         }
 
-        let parts: Vec<&str> = params.cmd.split_whitespace().collect();
-        if parts.is_empty() {
-            yield Ok(Event::default().event("error").data("Empty command received."));
-            return;
+        let args = match exec::parse_command(&cmd, policy::allows_exec) {
+            Ok(args) => args,
+            Err(exec::ExecRejection::Empty) => {
+                yield (Some("error"), "Empty command received.".to_string());
+                return;
+            }
+            Err(rejection) => {
+                log::warn!("Unauthorized command blocked: {} ('{}').", rejection, cmd);
+                yield (Some("error"), UNAUTHORIZED_MESSAGE.to_string());
+                return;
+            }
+        };
+
+        // Registry mirror settings apply to `ollama pull` too.
+        let args = model_manager::exec_args(&app, args);
+        let events = exec::run(exec::PROGRAM, args, model_manager::exec_env(&app));
+        futures::pin_mut!(events);
+        let tracked = active::register(&app, active::ActiveKind::Exec, cmd.clone(), None, None);
+        let cancelled = tracked.cancelled();
+        futures::pin_mut!(cancelled);
+        loop {
+            // Cancelling drops `events`, which kills the process.
+            let (event, cancelled_now) = tokio::select! {
+                event = events.next() => (event, false),
+                _ = &mut cancelled => (Some(exec::ExecEvent::Failed("[cancelled]".to_string())), true),
+            };
+            let Some(event) = event else {
+                break;
+            };
+            if matches!(event, exec::ExecEvent::Output(_)) {
+                tracked.add_received(1);
+            }
+            let (name, data) = core_server::exec_message(event);
+            // Others may be following the job at /active/:id/stream.
+            tracked.publish(name, &data);
+            yield (name, data);
+            if cancelled_now {
+                log::info!("Cancelled '{}'", cmd);
+                break;
+            }
         }
+    };
 
-        if parts[0] != "ollama" {
-            log::warn!("Unauthorized command blocked: only 'ollama' is permitted ('{}').", params.cmd);
-            yield Ok(Event::default().event("error").data(UNAUTHORIZED_MESSAGE));
-            return;
+    request_id::scoped(stream)
+}
+
+// What a proxied request carries from `prepare_proxy` to `respond_proxy`.
+#[derive(Default)]
+struct Flight {
+    remote: bool,
+    remote_guard: Option<privacy::RemoteRequestGuard>,
+    agent_id: Option<String>,
+    model: Option<String>,
+    token_count: Option<tokenizer::TokenCount>,
+    structure: Option<(structured::StructureSpec, axum::body::Bytes)>,
+    recorder: Option<replay::Recorder>,
+    cache_key: Option<response_cache::CacheKey>,
+    tracked: Option<active::ActiveGuard>,
+}
+
+fn cancelled_response(path: &str, base_url: &str) -> Response {
+    log::info!("Cancelled the {} request to {}", path, base_url);
+    Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body(Body::from("Request cancelled")).unwrap()
+}
+
+fn bad_request(path: &str, error: String) -> Response {
+    log::warn!("Refusing to proxy {}: {}", path, error);
+    Response::builder().status(StatusCode::BAD_REQUEST).body(Body::from(error)).unwrap()
+}
+
+// Everything done to a prompt before it's sent: attachments, locality,
+// budgets, presets, memory, imaging, compaction, structured output and
+// replay. Cache hits, tiling, races and fallback chains answer here instead.
+async fn prepare_proxy(
+    app: &AppHandle,
+    client: &Client,
+    request: &mut ProxyRequest,
+    mut body_bytes: axum::body::Bytes,
+) -> Result<(axum::body::Bytes, Flight), Response> {
+    let method = request.method.clone();
+    let path = request.path.clone();
+    let path = path.as_str();
+    let base_url = request.base_url.clone();
+    let target_url = request.target_url();
+    let remote = !privacy::is_local_url(&base_url);
+    let headers = &mut request.headers;
+
+    // Uploaded files referenced by ID go in before anything looks at the body.
+    if method == Method::POST {
+        match attachments::inline_request(app, &body_bytes).await {
+            Ok(Some(new_body)) => {
+                body_bytes = new_body.into();
+                headers.remove(axum::http::header::CONTENT_LENGTH);
+            }
+            Ok(None) => {}
+            Err(e) => return Err(bad_request(path, e)),
         }
+    }
 
-        let args: &[&str];
-        if parts.len() == 1 {
-            args = &[];
-        } else {
-            let subcommand = parts[1];
-            let allowed_subcommands = [
-                "serve", "create", "show", "run", "stop", "pull",
-                "push", "list", "ps", "cp", "rm", "help", "--version", "--help"
-            ];
-            if !allowed_subcommands.contains(&subcommand) {
-                log::warn!("Unauthorized command blocked: subcommand '{}' is not permitted.", subcommand);
-                yield Ok(Event::default().event("error").data(UNAUTHORIZED_MESSAGE));
-                return;
+    if method == Method::POST && path == "/api/pull" {
+        if let Some(new_body) = model_manager::rewrite_pull_request(app, &body_bytes) {
+            body_bytes = new_body.into();
+            headers.remove(axum::http::header::CONTENT_LENGTH);
+        }
+    }
+
+    let agent_id = headers
+        .get(compaction::AGENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let tracked_agent = agent_id.clone();
+
+    // Screenshots, audio and clipboard content only go to local backends.
+    if method == Method::POST {
+        let content = locality::sensitive_content(headers, &body_bytes);
+        if let Err(violation) = locality::check(app, agent_id.as_deref(), &base_url, content) {
+            return Err(locality::violation_response(&violation));
+        }
+    }
+
+    // While the backend is unreachable agent requests are held, not sent.
+    if method == Method::POST {
+        if let Some(response) = offline::hold_request(app, agent_id.as_deref()) {
+            return Err(response);
+        }
+    }
+
+    // Agents over their daily token budget wait for the next day or are turned away.
+    if let Some(agent_id) = agent_id.as_ref().filter(|_| method == Method::POST) {
+        budgets::admit(app, agent_id).await?;
+    }
+
+    // Generation parameters from a named preset or the agent's defaults.
+    if method == Method::POST {
+        let preset = headers.get(presets::PRESET_HEADER).and_then(|v| v.to_str().ok());
+        match presets::apply_request(app, path, preset, agent_id.as_deref(), &body_bytes) {
+            Ok(Some(new_body)) => {
+                body_bytes = new_body.into();
+                headers.remove(axum::http::header::CONTENT_LENGTH);
             }
-            args = &parts[1..];
+            Ok(None) => {}
+            Err(e) => return Err(bad_request(path, e)),
+        }
+    }
+
+    // Recalled memories go in first so they count towards the context window.
+    if let Some(agent_id) = agent_id.as_ref().filter(|_| method == Method::POST) {
+        if let Some(new_body) = memory::inject_request(app, agent_id, &body_bytes).await {
+            body_bytes = new_body.into();
+            headers.remove(axum::http::header::CONTENT_LENGTH);
         }
+    }
 
-        #[cfg(target_os = "windows")]
-        let program = "ollama";
-
-        #[cfg(not(target_os = "windows"))]
-        let program = "/usr/local/bin/ollama";
-
-        log::info!("Executing validated command using TokioCommand: {} with args {:?}", program, args);
-
-        let mut command = TokioCommand::new(program);
-        command.args(args);
-        command.stdout(std::process::Stdio::piped());
-        command.stderr(std::process::Stdio::piped());
-
-        match command.spawn() {
-            Ok(mut child) => {
-                let stdout = child.stdout.take().expect("Failed to capture stdout");
-                let stderr = child.stderr.take().expect("Failed to capture stderr");
-
-                let mut stdout_reader = BufReader::new(stdout).lines();
-                let mut stderr_reader = BufReader::new(stderr).lines();
-
-                loop {
-                    tokio::select! {
-                        // Read from stdout
-                        Ok(Some(line)) = stdout_reader.next_line() => {
-                            log::info!("RAW STDOUT BYTES AS STRING: {:?}", line);
-                            yield Ok(Event::default().data(line));
-                        },
-                        Ok(Some(line)) = stderr_reader.next_line() => {
-                            log::info!("RAW STDERR BYTES AS STRING: {:?}", line);
-                            yield Ok(Event::default().data(line));
-                        },
-                        else => break,
-                    }
+    // Oversized screenshots are downscaled before anything else looks at them.
+    if method == Method::POST {
+        if let Some(new_body) = imaging::preprocess_request(app, &body_bytes).await {
+            body_bytes = new_body.into();
+            headers.remove(axum::http::header::CONTENT_LENGTH);
+        }
+    }
+
+    // Only POSTs carry prompts worth counting.
+    let token_count = if method == Method::POST {
+        tokenizer::check_request(app, &body_bytes).await
+    } else {
+        None
+    };
+
+    // Agents that opted into compaction get their history summarized before it overflows.
+    let compact_agent = agent_id.filter(|_| headers.contains_key(compaction::COMPACT_HEADER));
+    if let (Some(agent_id), Some(count)) = (compact_agent, &token_count) {
+        if let Some(new_body) = compaction::compact_request(app, &agent_id, &body_bytes, count).await {
+            body_bytes = new_body.into();
+            headers.remove(axum::http::header::CONTENT_LENGTH);
+        }
+    }
+
+    // Structured output needs the whole answer, so it's requested without streaming.
+    let structure = structured::StructureSpec::from_headers(headers).filter(|_| method == Method::POST);
+    if let Some(spec) = &structure {
+        if let Some(new_body) = structured::prepare_request(&body_bytes, spec) {
+            body_bytes = new_body.into();
+            headers.remove(axum::http::header::CONTENT_LENGTH);
+        }
+    }
+
+    // In replay mode agent requests get a fixed seed and are recorded as sent.
+    let recorder = if method == Method::POST {
+        let recorded = replay::record_request(app, path, tracked_agent.as_deref(), &base_url, &body_bytes);
+        recorded.map(|(new_body, recorder)| {
+            body_bytes = new_body.into();
+            headers.remove(axum::http::header::CONTENT_LENGTH);
+            recorder
+        })
+    } else {
+        None
+    };
+    let structure = structure.map(|spec| (spec, body_bytes.clone()));
+
+    // Agents re-asking about an unchanged screen may already have their answer.
+    let cache_key = if structure.is_none() && method == Method::POST {
+        match response_cache::lookup(app, path, &body_bytes).await {
+            response_cache::Lookup::Hit(response) => return Err(response),
+            response_cache::Lookup::Miss(key) => Some(key),
+            response_cache::Lookup::Uncached => None,
+        }
+    } else {
+        None
+    };
+
+    // Tiling replaces the single upstream request with one per tile.
+    if structure.is_none() && method == Method::POST {
+        if let Some(response) = imaging::tiled_response(app, client, &target_url, &body_bytes).await {
+            return Err(response.unwrap_or_else(IntoResponse::into_response));
+        }
+    }
+
+    let model = serde_json::from_slice::<serde_json::Value>(&body_bytes)
+        .ok()
+        .and_then(|body| body["model"].as_str().map(str::to_string))
+        .filter(|_| method == Method::POST);
+
+    // Idle models may be unloaded first so this one fits in VRAM.
+    if let Some(model) = &model {
+        vram::prepare(app, model).await;
+    }
+
+    // Generations show up in the in-flight dashboard, which can cancel them.
+    let tracked = (method == Method::POST).then(|| {
+        let description = path.to_string();
+        active::register(app, active::ActiveKind::Generation, description, model.clone(), tracked_agent.clone())
+    });
+
+    // Short prompts may go to a second backend at the same time.
+    if structure.is_none() && method == Method::POST {
+        let raced = tokio::select! {
+            raced = race::race(app, path, headers, &body_bytes, tracked_agent.as_deref()) => raced,
+            _ = active::cancelled(tracked.as_ref()) => return Err(cancelled_response(path, &base_url)),
+        };
+        if let Some(raced) = raced {
+            let mut response_builder = raced.response;
+            if let Some(headers) = response_builder.headers_mut() {
+                if let Some(count) = &token_count {
+                    tokenizer::insert_headers(headers, count);
                 }
+            }
+            let remote = !privacy::is_local_url(&raced.backend);
+            let remote_guard = remote.then(|| privacy::RemoteRequestGuard::new(app));
+            let mut upstream = raced.stream;
+            if let Some(key) = cache_key {
+                let answer_type = content_type(response_builder.headers_ref());
+                upstream = response_cache::record(app, key, answer_type, upstream);
+            }
+            let meter = model.map(|model| usage::Meter::new(app, tracked_agent, model, &raced.backend));
+            let body = stream_body(app, upstream, meter, recorder, tracked, remote_guard);
+            return Err(response_builder.body(body).unwrap());
+        }
+    }
 
-                match child.wait().await {
-                    Ok(status) => {
-                        yield Ok(Event::default().event("done").data(format!("[COMMAND_FINISHED code={:?}]", status.code())));
-                    }
-                    Err(e) => {
-                        yield Ok(Event::default().event("error").data(format!("[ERROR: Failed to wait for command. Error: {}]", e)));
+    // Agents with a fallback chain try its steps in turn until one answers.
+    if structure.is_none() && method == Method::POST {
+        let agent = tracked_agent.as_deref();
+        let served = tokio::select! {
+            served = fallback::serve(app, &base_url, path, headers, &body_bytes, agent) => served,
+            _ = active::cancelled(tracked.as_ref()) => return Err(cancelled_response(path, &base_url)),
+        };
+        match served {
+            Some(Ok(served)) => {
+                let mut response_builder = served.raced.response;
+                if let Some(headers) = response_builder.headers_mut() {
+                    if let Some(count) = &token_count {
+                        tokenizer::insert_headers(headers, count);
                     }
                 }
-            },
-            Err(e) => {
-                yield Ok(Event::default().event("error").data(format!("[ERROR: Failed to spawn command '{}'. Error: {}]", program, e)));
+                let remote = !privacy::is_local_url(&served.raced.backend);
+                let remote_guard = remote.then(|| privacy::RemoteRequestGuard::new(app));
+                let mut upstream = served.raced.stream;
+                if let Some(key) = cache_key {
+                    let answer_type = content_type(response_builder.headers_ref());
+                    upstream = response_cache::record(app, key, answer_type, upstream);
+                }
+                let backend = served.raced.backend;
+                let meter = usage::Meter::new(app, tracked_agent.clone(), served.model, &backend);
+                let body = stream_body(app, upstream, Some(meter), recorder, tracked, remote_guard);
+                return Err(response_builder.body(body).unwrap());
+            }
+            Some(Err(errors)) => {
+                let body = serde_json::json!({
+                    "error": format!("Every model in the fallback chain failed: {}", errors.join("; ")),
+                    "code": "fallback_exhausted",
+                });
+                return Err(Response::builder()
+                    .status(StatusCode::BAD_GATEWAY)
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap());
             }
+            None => {}
         }
+    }
+
+    // Uploads to a non-local backend are abandoned when the kill switch is engaged.
+    let flight = Flight {
+        remote,
+        remote_guard: remote.then(|| privacy::RemoteRequestGuard::new(app)),
+        agent_id: tracked_agent,
+        model,
+        token_count,
+        structure,
+        recorder,
+        cache_key,
+        tracked,
     };
+    Ok((body_bytes, flight))
+}
+
+// The upstream answer as the client's: with token counts, post-processed
+// for structured output, and otherwise streamed through `stream_body`.
+async fn respond_proxy(
+    app: &AppHandle,
+    request: ProxyRequest,
+    flight: Flight,
+    upstream_response: reqwest::Response,
+) -> Response {
+    let mut response_builder = proxy::response_builder(&upstream_response);
+    if let Some(headers) = response_builder.headers_mut() {
+        if let Some(count) = &flight.token_count {
+            tokenizer::insert_headers(headers, count);
+        }
+    }
+
+    if let Some((spec, request_body)) = flight.structure.filter(|_| upstream_response.status().is_success()) {
+        let response_bytes = tokio::select! {
+            bytes = upstream_response.bytes() => match bytes {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    log::error!("Failed to read upstream response: {}", e);
+                    return StatusCode::BAD_GATEWAY.into_response();
+                }
+            },
+            _ = active::cancelled(flight.tracked.as_ref()) => {
+                return cancelled_response(&request.path, &request.base_url);
+            }
+        };
+        let body = structured::process_response(app, &request_body, &response_bytes, &spec)
+            .await
+            .unwrap_or_else(|| response_bytes.to_vec());
+        if let Some(headers) = response_builder.headers_mut() {
+            headers.remove(axum::http::header::CONTENT_LENGTH);
+        }
+        return response_builder.body(Body::from(body)).unwrap();
+    }
 
-    Sse::new(stream)
+    let cache_key = flight.cache_key.filter(|_| upstream_response.status().is_success());
+    let answer_type = content_type(Some(upstream_response.headers()));
+    let mut upstream = upstream_response.bytes_stream().boxed();
+    if let Some(key) = cache_key {
+        upstream = response_cache::record(app, key, answer_type, upstream);
+    }
+    // Answers are metered for the usage and cost reports.
+    let meter = flight.model.map(|model| usage::Meter::new(app, flight.agent_id, model, &request.base_url));
+    let response_body = stream_body(app, upstream, meter, flight.recorder, flight.tracked, flight.remote_guard);
+    response_builder.body(response_body).unwrap()
 }
 
-async fn proxy_handler(
-    AxumState(state): AxumState<AppState>,
-    method: Method,
-    headers: HeaderMap,
-    uri: Uri,
-    body: Body,
-) -> Result<Response, StatusCode> {
-    let path = uri.path();
-    let query = uri.query().unwrap_or("");
-
-    let target_url = {
-        // This whole block will evaluate to a single String value.
-
-        let settings = state.app_handle.state::<AppSettings>();
-        let ollama_url_guard = settings.ollama_url.lock().unwrap();
-        
-        let base_url = ollama_url_guard
-            .as_deref()
-            .unwrap_or("http://127.0.0.1:11434");
-
-        // 2. This is the last line. With no semicolon, its value is "returned"
-        //    from the block and assigned to `target_url`.
-        format!("{}{}?{}", base_url, path, query)
+// The embedded server's core routes (see observer-core's server.rs) with the
+// app behind them.
+impl core_server::Host for AppState {
+    type Flight = Flight;
+    type Attachment = attachments::Attachment;
 
-    };
+    fn http_client(&self) -> &Client {
+        &self.http_client
+    }
 
-    log::info!("Proxying {} request to: {}", method, target_url);
+    async fn health(&self, verbose: bool) -> (bool, serde_json::Value) {
+        health::report(&self.app_handle, verbose).await
+    }
 
-    let body_bytes = match body.collect().await {
-        Ok(collected) => collected.to_bytes(),
-        Err(e) => {
-            log::error!("Failed to collect request body: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    fn version(&self) -> serde_json::Value {
+        serde_json::to_value(health::version(&self.app_handle)).unwrap_or_default()
+    }
+
+    fn exec(&self, cmd: String) -> BoxStream<'static, (Option<&'static str>, String)> {
+        exec_events(self.app_handle.clone(), cmd).boxed()
+    }
+
+    fn max_attachment_bytes(&self) -> u64 {
+        attachments::max_file_bytes(&self.app_handle)
+    }
+
+    fn store_attachment(
+        &self,
+        bytes: Vec<u8>,
+        mime: String,
+        conversation_id: Option<i64>,
+    ) -> Result<attachments::Attachment, String> {
+        attachments::store(&self.app_handle, &bytes, &mime, conversation_id)
+    }
+
+    async fn attachment(&self, hash: String) -> Result<(Vec<u8>, String), StatusCode> {
+        attachments::load(&self.app_handle, &hash).await
+    }
+
+    fn backend(&self, headers: &HeaderMap) -> String {
+        backends::route(&self.app_handle, headers.contains_key(compaction::AGENT_HEADER))
+    }
+
+    fn mock_enabled(&self) -> bool {
+        mock::is_enabled(&self.app_handle)
+    }
+
+    fn admit(&self, request: &ProxyRequest) -> Result<(), Response> {
+        if privacy::is_engaged(&self.app_handle) {
+            log::warn!("Refusing to proxy {} while the privacy kill switch is engaged", request.path);
+            return Err(StatusCode::LOCKED.into_response());
         }
-    };
+        // The mock backend answers instead, whatever the backend is.
+        if mock::is_enabled(&self.app_handle) {
+            return Ok(());
+        }
+        policy::check_backend(&request.base_url).map_err(|e| {
+            log::warn!("Refusing to proxy {}: {}", request.path, e);
+            policy::forbidden_response(&e)
+        })
+    }
 
-    let reqwest_request = state
-        .http_client
-        .request(method, &target_url)
-        .headers(headers)
-        .body(body_bytes);
-
-    match reqwest_request.send().await {
-        Ok(upstream_response) => {
-            let mut response_builder = Response::builder()
-                .status(upstream_response.status())
-                .version(upstream_response.version());
-            
-            if let Some(headers) = response_builder.headers_mut() {
-                headers.extend(upstream_response.headers().clone());
-            }
+    fn pass_through(&self, request: &ProxyRequest) -> Flight {
+        let remote = !privacy::is_local_url(&request.base_url);
+        Flight {
+            remote,
+            remote_guard: remote.then(|| privacy::RemoteRequestGuard::new(&self.app_handle)),
+            ..Flight::default()
+        }
+    }
 
-            let response_stream = upstream_response.bytes_stream();
-            let response_body = Body::from_stream(response_stream);
+    async fn prepare(
+        &self,
+        request: &mut ProxyRequest,
+        body: axum::body::Bytes,
+    ) -> Result<(axum::body::Bytes, Flight), Response> {
+        prepare_proxy(&self.app_handle, &self.http_client, request, body).await
+    }
 
-            Ok(response_builder.body(response_body).unwrap())
+    async fn abandoned(&self, request: &ProxyRequest, flight: &Flight) -> Response {
+        let kill_switch = async {
+            if flight.remote {
+                privacy::engaged(&self.app_handle).await
+            } else {
+                std::future::pending().await
+            }
+        };
+        tokio::select! {
+            _ = kill_switch => {
+                log::warn!("Cancelled a request to {} (privacy kill switch)", request.base_url);
+                StatusCode::LOCKED.into_response()
+            }
+            _ = active::cancelled(flight.tracked.as_ref()) => cancelled_response(&request.path, &request.base_url),
         }
-        Err(e) => {
-            log::error!("Proxy request to Ollama failed: {}", e);
-            Err(StatusCode::BAD_GATEWAY)
+    }
+
+    async fn respond(&self, request: ProxyRequest, flight: Flight, upstream: reqwest::Response) -> Response {
+        respond_proxy(&self.app_handle, request, flight, upstream).await
+    }
+}
+
+fn content_type(headers: Option<&HeaderMap>) -> Option<String> {
+    headers?.get(axum::http::header::CONTENT_TYPE)?.to_str().ok().map(str::to_string)
+}
+
+// The upstream answer as the proxy's response body: metered, recorded for
+// replay, counted and broadcast for the in-flight list, and cut off when it's cancelled or, for
+// non-local backends, by the privacy kill switch.
+fn stream_body(
+    app: &AppHandle,
+    upstream: BoxStream<'static, reqwest::Result<axum::body::Bytes>>,
+    meter: Option<usage::Meter>,
+    recorder: Option<replay::Recorder>,
+    tracked: Option<active::ActiveGuard>,
+    remote_guard: Option<privacy::RemoteRequestGuard>,
+) -> Body {
+    let upstream = match meter {
+        Some(meter) => meter.wrap(upstream),
+        None => upstream,
+    };
+    let upstream = match recorder {
+        Some(recorder) => recorder.wrap(upstream),
+        None => upstream,
+    };
+    let response_stream = match tracked {
+        Some(tracked) => upstream
+            .take_until(Box::pin(tracked.cancelled()))
+            .map(move |chunk| {
+                if let Ok(bytes) = &chunk {
+                    tracked.add_received(bytes.len() as u64);
+                    tracked.publish(None, &String::from_utf8_lossy(bytes));
+                }
+                chunk
+            })
+            .boxed(),
+        None => upstream,
+    };
+    match remote_guard {
+        Some(guard) => {
+            let app = app.clone();
+            let kill_switch = async move { privacy::engaged(&app).await };
+            let guarded = response_stream.take_until(Box::pin(kill_switch)).map(move |chunk| {
+                let _ = &guard;
+                chunk
+            });
+            Body::from_stream(request_id::scoped(guarded))
         }
+        None => Body::from_stream(request_id::scoped(response_stream)),
     }
 }
 
@@ -284,76 +745,310 @@ fn get_server_url(server_url: State<Mutex<ServerUrl>>) -> String {
     server_url.lock().unwrap().0.clone()
 }
 
+// The addresses the server listens on beyond this machine, each with the
+// access token it demands (a fresh one every time the server starts).
+#[derive(Default)]
+struct LanAccess(Mutex<Vec<String>>);
+
+#[tauri::command]
+fn get_lan_access_urls(lan_access: State<LanAccess>) -> Vec<String> {
+    lan_access.0.lock().unwrap().clone()
+}
+
+// Runs until the servers stop on their own. A restart asked for by the
+// self-monitor shuts the whole runtime down, dropping whatever tasks and
+// sockets it held, and starts over on a fresh one.
 #[cfg(not(debug_assertions))]
 fn start_static_server(app_handle: tauri::AppHandle) {
-    let rt = tokio::runtime::Runtime::new().unwrap();
-    rt.block_on(async {
-        const SERVER_PORT: u16 = 3838;
-        let url = format!("http://127.0.0.1:{}", SERVER_PORT);
-        let addr_str = url.replace("http://", "");
-
-        let server_url_state = app_handle.state::<Mutex<ServerUrl>>();
-        *server_url_state.lock().unwrap() = ServerUrl(url.clone());
-
-        let resource_path = app_handle
-            .path()
-            .resource_dir()
-            .expect("failed to get resource directory")
-            .join("_up_/dist");
-
-        log::info!("Serving static files from: {:?}", resource_path);
-
-        let cors = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods(Any)
-            .allow_headers(Any);
-
-        let state = AppState {
-            app_handle: app_handle.clone(),
-            http_client: Client::new(),
-        };
+    loop {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let restart = rt.block_on(async {
+            self_monitor::set_server_runtime(Some(tokio::runtime::Handle::current()));
+            tokio::select! {
+                _ = serve(&app_handle) => false,
+                _ = self_monitor::server_restart_requested() => true,
+            }
+        });
+        self_monitor::set_server_runtime(None);
+        rt.shutdown_timeout(std::time::Duration::from_secs(5));
+        if !restart {
+            break;
+        }
+        log::info!("Restarting the embedded server");
+    }
+}
 
-        let app = Router::new()
-            .route("/exec", get(exec_handler))
-            .route("/v1/*path", any(proxy_handler))
-            .route("/api/*path", any(proxy_handler))
-            .fallback_service(ServeDir::new(resource_path))
-            .with_state(state)
-            .layer(cors);
-
-        let listener = tokio::net::TcpListener::bind(&addr_str).await;
-
-        match listener {
-            Ok(l) => {
-                log::info!("Web server listening on {}", url);
-                if let Err(e) = axum::serve(l, app.into_make_service()).await {
-                    log::error!("Server error: {}", e);
-                }
+#[cfg(not(debug_assertions))]
+async fn serve(app_handle: &tauri::AppHandle) {
+    let resource_path = server::static_dir(app_handle);
+
+    log::info!("Serving static files from: {:?}", resource_path);
+
+    let state = AppState {
+        app_handle: app_handle.clone(),
+        // Shares the tuned connection pool with everything else upstream.
+        http_client: llm::client().clone(),
+    };
+    let app = server::build_router(state, resource_path);
+    let lan_token = std::sync::Arc::new(uuid::Uuid::new_v4().simple().to_string());
+    let mut lan_urls = Vec::new();
+
+    // Each address stands alone: one that's taken or mistyped is
+    // reported and skipped, and the rest still serve.
+    let mut servers = Vec::new();
+    for (entry, addr) in config::current(app_handle).server.addresses() {
+        let addr = match addr {
+            Ok(addr) => addr,
+            Err(e) => {
+                log::error!("Not listening on {}: {}", entry, e);
+                continue;
             }
+        };
+        if !addr.ip().is_loopback() && !features::enabled(features::Feature::Lan) {
+            log::warn!("Not listening on {}: the lan subsystem is disabled", addr);
+            continue;
+        }
+        let listener = match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => listener,
             Err(e) => {
-                log::error!(
-                    "FATAL: Failed to bind to address {}. Is another instance running? Error: {}",
-                    addr_str,
-                    e
-                );
+                log::error!("Failed to bind to {}. Is another instance running? Error: {}", addr, e);
+                continue;
             }
+        };
+        let url = config::server_url(addr);
+        log::info!("Web server listening on {}", url);
+        // Other machines get nothing but the model shares without the token.
+        let (router, url) = if addr.ip().is_loopback() {
+            (app.clone(), url)
+        } else {
+            let guard = axum::middleware::from_fn_with_state(lan_token.clone(), tunnel::require_token_except_share);
+            let url = format!("{}/?token={}", url, lan_token);
+            lan_urls.push(url.clone());
+            (app.clone().layer(guard), url)
+        };
+        // The UI links to the first address that works.
+        if servers.is_empty() {
+            *app_handle.state::<Mutex<ServerUrl>>().lock().unwrap() = ServerUrl(url);
         }
-    });
+        let service = router.into_make_service_with_connect_info::<std::net::SocketAddr>();
+        servers.push(tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, service).await {
+                log::error!("Server error on {}: {}", addr, e);
+            }
+        }));
+    }
+
+    *app_handle.state::<LanAccess>().0.lock().unwrap() = lan_urls;
+    if servers.is_empty() {
+        log::error!("FATAL: The web server couldn't bind to any configured address");
+    }
+    join_all(servers).await;
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    #[allow(unused_mut)]
+    let mut builder = tauri::Builder::default();
+
+    // Must be the first plugin: a second launch (e.g. from an observer:// link)
+    // forwards its URL to this instance instead of starting a new one.
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            deep_link::show_main_window(app);
+        }));
+    }
+
+    builder
         .manage(Mutex::new(ServerUrl("".to_string())))
+        .manage(LanAccess::default())
         .manage(AppSettings {
             ollama_url: Mutex::new(None),
         })
+        .manage(deep_link::PendingDeepLinks::default())
+        .manage(file_drop::DropTarget::default())
+        .manage(file_drop::PendingDrops::default())
+        .manage(power::PowerMonitor::default())
+        .manage(focus::FocusState::default())
+        .manage(summary::SummaryState::default())
+        .manage(activity::ActivityTracker::default())
+        .manage(timers::TimerService::default())
+        .manage(agents::AgentRegistry::default())
+        .manage(agents::ImportedAgents::default())
+        .manage(agent_share::PendingImports::default())
+        .manage(catalog::Catalog::default())
+        .manage(tokenizer::ContextWindowCache::default())
+        .manage(compaction::CompactionState::default())
+        .manage(vector_store::VectorState::default())
+        .manage(memory::MemoryState::default())
+        .manage(batch::BatchJobs::default())
+        .manage(attachments::AttachmentState::default())
+        .manage(browser_bridge::BrowserBridge::default())
+        .manage(email::EmailState::default())
+        .manage(calendar::CalendarState::default())
+        .manage(feeds::FeedState::default())
+        .manage(mqtt::MqttBridge::default())
+        .manage(git::GitState::default())
+        .manage(audit::AuditLog::default())
+        .manage(shell::ShellApprovals::default())
+        .manage(files::FileScopeState::default())
+        .manage(annotate::AnnotationSession::default())
+        .manage(imaging::ImagingState::default())
+        .manage(recording::RecordingState::default())
+        .manage(capture::CaptureState::default())
+        .manage(privacy::PrivacyState::default())
+        .manage(locality::LocalityState::default())
+        .manage(config::ConfigState::default())
+        .manage(access_log::AccessLogState::default())
+        .manage(mock::MockState::default())
+        .manage(tunnel::TunnelState::default())
+        .manage(model_manager::DownloadState::default())
+        .manage(recovery::RecoveryState::default())
+        .manage(active::ActiveRequests::default())
+        .manage(race::RaceState::default())
+        .manage(fallback::FallbackState::default())
+        .manage(agent_tests::AgentTestState::default())
+        .manage(evaluation::EvaluationState::default())
+        .manage(adapters::AdapterState::default())
+        .manage(transcription::TranscriptionState::default())
+        .manage(transcript_index::TranscriptIndexState::default())
+        .manage(captions::CaptionState::default())
+        .manage(sound_events::SoundEventState::default())
+        .manage(ocr_languages::OcrState::default())
+        .manage(ui_elements::UiState::default())
+        .manage(response_cache::ResponseCache::default())
+        .manage(budgets::BudgetState::default())
+        .manage(vram::VramState::default())
+        .manage(presets::PresetState::default())
+        .manage(replay::ReplayState::default())
+        .manage(injection::InjectionState::default())
+        .manage(moderation::ModerationState::default())
+        .manage(incognito::IncognitoState::default())
+        .manage(input_control::InputState::default())
+        .manage(wake::WakeState::default())
+        .manage(offline::OfflineState::default())
+        .manage(self_monitor::SelfMonitorState::default())
+        .manage(backends::BackendState::default())
         .setup(|app| {
             app.handle().plugin(
                 tauri_plugin_log::Builder::default()
+                    .targets(portable::log_targets())
                     .level(log::LevelFilter::Info)
+                    // The default format, plus the request being handled (see request_id.rs).
+                    // Each record is also kept for the log console (see log_store.rs).
+                    .format(|out, message, record| {
+                        let request_id = request_id::current();
+                        log_store::capture(record, request_id.clone());
+                        let request = request_id.map(|id| format!("[req {}]", id)).unwrap_or_default();
+                        out.finish(format_args!(
+                            "{}[{}][{}]{} {}",
+                            chrono::Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                            record.target(),
+                            record.level(),
+                            request,
+                            message
+                        ))
+                    })
                     .build(),
             )?;
+            if let Some(dir) = portable::data_root() {
+                log::info!("Portable mode, keeping data in {:?}", dir);
+            }
+
+            // Restricts much of what follows, so it comes first.
+            policy::init()?;
+            // Decides which profile's data directory everything below reads.
+            profiles::init(app.handle())?;
+            if let Err(e) = config_archive::migrate_data_dir(app.handle()) {
+                log::error!("Failed to migrate settings: {}", e);
+            }
+            // Before the server starts, which listens where it says.
+            config::init(app.handle());
+            features::init(app.handle());
+
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                // Linux and Windows only know about the scheme once we register it at runtime.
+                // A portable copy leaves the system's handlers alone.
+                #[cfg(any(windows, target_os = "linux"))]
+                if portable::data_root().is_some() {
+                    log::info!("Portable mode, not registering the observer:// scheme");
+                } else if let Err(e) = app.deep_link().register_all() {
+                    log::warn!("Failed to register observer:// scheme: {}", e);
+                }
+
+                let deep_link_handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    deep_link::handle_urls(&deep_link_handle, event.urls());
+                });
+
+                // The link that launched us (if any) arrives before the listener exists.
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    deep_link::handle_urls(app.handle(), urls);
+                }
+            }
+
+            if let Err(e) = recovery::run(app.handle()) {
+                log::error!("Startup integrity checks failed: {}", e);
+            }
+            history::init(app.handle())?;
+            agents::init(app.handle());
+            catalog::init(app.handle());
+            compaction::init(app.handle());
+            vector_store::init(app.handle());
+            memory::init(app.handle());
+            attachments::init(app.handle());
+            browser_bridge::init(app.handle());
+            git::init(app.handle());
+            files::init(app.handle());
+            imaging::init(app.handle());
+            locality::init(app.handle());
+            race::init(app.handle());
+            fallback::init(app.handle());
+            agent_tests::init(app.handle());
+            evaluation::init(app.handle());
+            adapters::init(app.handle());
+            transcription::init(app.handle());
+            transcript_index::init(app.handle());
+            captions::init(app.handle());
+            sound_events::init(app.handle());
+            ocr_languages::init(app.handle());
+            ui_elements::init(app.handle());
+            response_cache::init(app.handle());
+            usage::init(app.handle());
+            budgets::init(app.handle());
+            vram::init(app.handle());
+            presets::init(app.handle());
+            replay::init(app.handle());
+            injection::init(app.handle());
+            moderation::init(app.handle());
+            input_control::init(app.handle());
+            access_log::init(app.handle());
+            mock::init(app.handle());
+
+            // Safe mode starts nothing in the background.
+            if !features::safe_mode() {
+                power::start_monitor(app.handle().clone());
+                focus::start_monitor(app.handle().clone());
+                backup::start_scheduler(app.handle().clone());
+                activity::start_tracker(app.handle().clone());
+                config::start_watcher(app.handle().clone());
+                offline::start_monitor(app.handle().clone());
+                backends::start_monitor(app.handle().clone());
+                self_monitor::start_monitor(app.handle().clone());
+            }
+            // These act on their own, so they go with the agents.
+            if features::enabled(features::Feature::Agents) {
+                summary::start_scheduler(app.handle().clone());
+                timers::start_service(app.handle().clone());
+                email::start_poller(app.handle().clone());
+                calendar::start_scheduler(app.handle().clone());
+                feeds::start_watcher(app.handle().clone());
+                mqtt::start(app.handle().clone());
+                wake::start_monitor(app.handle().clone());
+                jobs::resume(app.handle());
+            }
 
             #[cfg(not(debug_assertions))]
             {
@@ -373,11 +1068,60 @@ pub fn run() {
             let handle = app.handle();
             
             let show = MenuItem::with_id(handle, "show", "Show Launcher", true, None::<&str>)?;
+            let kill_switch = MenuItem::with_id(
+                handle,
+                privacy::TRAY_ITEM_ID,
+                "Privacy Kill Switch (toggle)",
+                true,
+                Some(privacy::SHORTCUT),
+            )?;
+            let captions_item =
+                MenuItem::with_id(handle, captions::TRAY_ITEM_ID, "Live Captions (toggle)", true, None::<&str>)?;
+            let incognito_item =
+                MenuItem::with_id(handle, incognito::TRAY_ITEM_ID, incognito::tray_label(), true, None::<&str>)?;
+            incognito::set_tray_item(handle, incognito_item.clone());
             let quit = MenuItem::with_id(handle, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(handle, &[&show, &quit])?;
 
-            let _tray = TrayIconBuilder::new()
-                .tooltip("Observer AI is running")
+            let profile_items = profiles::load_registry(handle)
+                .profiles
+                .into_iter()
+                .map(|profile| {
+                    let label = if profile.id == profiles::active_id() {
+                        format!("{} (active)", profile.name)
+                    } else {
+                        profile.name
+                    };
+                    let id = format!("{}{}", profiles::TRAY_ID_PREFIX, profile.id);
+                    MenuItem::with_id(handle, id, label, true, None::<&str>)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let profile_refs: Vec<&dyn IsMenuItem<_>> =
+                profile_items.iter().map(|item| item as &dyn IsMenuItem<_>).collect();
+            let profiles_menu = Submenu::with_items(handle, "Profile", true, &profile_refs)?;
+
+            let menu = Menu::with_items(
+                handle,
+                &[&show, &profiles_menu, &kill_switch, &incognito_item, &captions_item, &quit],
+            )?;
+
+            #[cfg(desktop)]
+            {
+                use tauri_plugin_global_shortcut::ShortcutState;
+
+                app.handle().plugin(
+                    tauri_plugin_global_shortcut::Builder::new()
+                        .with_shortcuts([privacy::SHORTCUT])?
+                        .with_handler(|app, _shortcut, event| {
+                            if event.state == ShortcutState::Pressed {
+                                privacy::toggle(app);
+                            }
+                        })
+                        .build(),
+                )?;
+            }
+
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
+                .tooltip(incognito::tooltip())
                 .icon(app.default_window_icon().cloned().unwrap())
                 .menu(&menu)
                 .on_menu_event(move |app, event| {
@@ -393,6 +1137,19 @@ pub fn run() {
                                 window.set_focus().unwrap();
                             }
                         }
+                        privacy::TRAY_ITEM_ID => privacy::toggle(app),
+                        captions::TRAY_ITEM_ID => captions::toggle(app),
+                        incognito::TRAY_ITEM_ID => incognito::toggle(app),
+                        id if id.starts_with(profiles::TRAY_ID_PREFIX) => {
+                            let app = app.clone();
+                            let profile = id[profiles::TRAY_ID_PREFIX.len()..].to_string();
+                            // The OS prompt for locked profiles blocks.
+                            std::thread::spawn(move || {
+                                if let Err(e) = profiles::switch(&app, &profile) {
+                                    log::error!("Failed to switch to profile '{}': {}", profile, e);
+                                }
+                            });
+                        }
                         _ => {}
                     }
                 })
@@ -405,14 +1162,315 @@ pub fn run() {
                 window.hide().unwrap();
                 api.prevent_close();
             }
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                file_drop::handle_drop(window.app_handle(), paths.clone());
+            }
             _ => {}
         })
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             get_server_url,
+            get_lan_access_urls,
             set_ollama_url,
             get_ollama_url,
-            check_ollama_servers
+            check_ollama_servers,
+            file_drop::set_drop_target,
+            power::get_power_state,
+            power::get_power_profiles,
+            power::set_power_profiles,
+            notifications::send_notification,
+            focus::set_focus_schedule,
+            focus::get_focus_schedule,
+            focus::set_manual_focus,
+            focus::get_focus_status,
+            focus::get_pending_alerts,
+            history::record_history,
+            history::query_history,
+            summary::get_summary_settings,
+            summary::set_summary_settings,
+            summary::run_summary_now,
+            summary::list_summaries,
+            activity::get_activity_settings,
+            activity::set_activity_settings,
+            activity::get_active_window,
+            analytics::get_time_analytics,
+            timers::start_timer,
+            timers::cancel_timer,
+            timers::list_timers,
+            timers::set_break_schedules,
+            timers::list_break_schedules,
+            timers::get_idle_status,
+            agents::sync_agent,
+            agents::remove_agent,
+            agents::list_agents,
+            agent_share::export_agent,
+            agent_share::import_agent,
+            agent_share::confirm_agent_import,
+            agent_share::cancel_agent_import,
+            catalog::get_catalog_settings,
+            catalog::set_catalog_settings,
+            catalog::fetch_catalog,
+            catalog::install_catalog_agent,
+            tokenizer::count_tokens,
+            compaction::compact_conversation,
+            compaction::get_context_memory,
+            compaction::clear_context_memory,
+            compaction::get_compaction_settings,
+            compaction::set_compaction_settings,
+            vector_store::get_vector_settings,
+            vector_store::set_vector_settings,
+            ingest::ingest_texts,
+            ingest::ingest_files,
+            memory::remember,
+            memory::recall,
+            memory::list_memories,
+            memory::forget,
+            memory::memory_set,
+            memory::memory_get,
+            memory::memory_delete,
+            memory::memory_entries,
+            memory::get_memory_settings,
+            memory::set_memory_settings,
+            compare::compare_models,
+            compare::list_comparisons,
+            batch::run_batch,
+            batch::list_batches,
+            structured::extract_structured,
+            conversations::create_conversation,
+            conversations::list_conversations,
+            conversations::append_message,
+            conversations::fork_conversation,
+            conversations::get_branch,
+            conversations::list_branches,
+            conversations::diff_messages,
+            conversations::delete_conversation,
+            attachments::store_attachment,
+            attachments::store_attachment_file,
+            attachments::get_attachment_usage,
+            attachments::collect_attachment_garbage,
+            attachments::get_attachment_settings,
+            attachments::set_attachment_settings,
+            browser_bridge::start_browser_pairing,
+            browser_bridge::list_paired_browsers,
+            browser_bridge::unpair_browser,
+            browser_bridge::get_connected_browsers,
+            browser_bridge::get_browser_context,
+            browser_bridge::push_to_browser,
+            email::get_email_settings,
+            email::set_email_settings,
+            email::check_email_now,
+            calendar::get_calendar_settings,
+            calendar::set_calendar_settings,
+            calendar::sync_calendars_now,
+            calendar::get_upcoming_events,
+            feeds::get_feed_settings,
+            feeds::set_feed_settings,
+            feeds::check_feeds_now,
+            mqtt::get_mqtt_settings,
+            mqtt::set_mqtt_settings,
+            mqtt::get_mqtt_status,
+            mqtt::mqtt_publish,
+            tools::list_tools,
+            tools::call_tool,
+            github::set_github_token,
+            github::get_github_user,
+            git::get_git_settings,
+            git::set_git_settings,
+            git::get_repo_info,
+            shell::respond_shell_approval,
+            audit::get_audit_log,
+            files::get_file_scopes,
+            files::set_file_scopes,
+            annotate::annotate_image,
+            annotate::open_annotation_overlay,
+            annotate::get_annotation_source,
+            annotate::finish_annotation,
+            imaging::get_image_settings,
+            imaging::set_image_settings,
+            video::summarize_video,
+            recording::start_screen_recording,
+            recording::stop_screen_recording,
+            recording::add_recording_highlight,
+            recording::list_screen_recordings,
+            capture::get_capture_capabilities,
+            capture::capture_screen,
+            capture::reset_capture_backends,
+            permissions::get_permission_status,
+            permissions::request_permission,
+            permissions::open_permission_settings,
+            privacy::set_privacy_kill_switch,
+            privacy::get_privacy_status,
+            locality::get_locality_settings,
+            locality::set_locality_settings,
+            locality::set_backend_locality,
+            locality::get_backend_locality,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::delete_profile,
+            profiles::set_profile_locked,
+            profiles::switch_profile,
+            config_archive::export_config,
+            config_archive::import_config,
+            config::get_config,
+            config::reload_config,
+            portable::get_portable_status,
+            policy::get_policy,
+            onboarding::get_onboarding_state,
+            onboarding::onboarding_detect_ollama,
+            onboarding::onboarding_probe_hardware,
+            onboarding::onboarding_pull_model,
+            onboarding::onboarding_request_permissions,
+            onboarding::onboarding_create_agent,
+            onboarding::skip_onboarding_step,
+            onboarding::reset_onboarding,
+            doctor::run_doctor,
+            health::get_version_info,
+            access_log::get_access_log_settings,
+            access_log::set_access_log_settings,
+            access_log::get_access_log_path,
+            mock::get_mock_ollama,
+            mock::set_mock_ollama,
+            tunnel::get_tunnel_status,
+            tunnel::get_tunnel_settings,
+            tunnel::set_tunnel_settings,
+            tunnel::start_tunnel,
+            tunnel::stop_tunnel,
+            model_share::get_model_share_settings,
+            model_share::set_model_share_settings,
+            model_share::list_local_models,
+            model_share::list_peer_models,
+            model_share::pull_shared_model,
+            model_manager::get_download_settings,
+            model_manager::set_download_settings,
+            model_manager::list_downloads,
+            model_manager::download_model,
+            model_manager::pause_download,
+            model_manager::resume_download,
+            model_manager::cancel_download,
+            model_manager::clear_finished_downloads,
+            backup::backup_to,
+            backup::restore_from,
+            backup::get_backup_schedule,
+            backup::set_backup_schedule,
+            backup::list_backups,
+            backup::backup_now,
+            backup::restore_backup,
+            recovery::get_recovery_report,
+            active::list_active_requests,
+            active::cancel_active_request,
+            race::get_race_settings,
+            race::set_race_settings,
+            templates::list_prompt_templates,
+            templates::save_prompt_template,
+            templates::delete_prompt_template,
+            openwebui_import::import_openwebui,
+            redact::get_redaction_settings,
+            redact::set_redaction_settings,
+            redact::preview_redaction,
+            dataset::export_dataset,
+            response_cache::get_response_cache_settings,
+            response_cache::set_response_cache_settings,
+            response_cache::list_response_cache,
+            response_cache::clear_response_cache,
+            usage::get_usage_cost,
+            usage::get_cost_settings,
+            usage::set_cost_settings,
+            budgets::get_token_budgets,
+            budgets::set_token_budget,
+            vram::get_vram_settings,
+            vram::set_vram_settings,
+            vram::pin_model,
+            vram::unpin_model,
+            vram::get_vram_status,
+            presets::list_parameter_presets,
+            presets::save_parameter_preset,
+            presets::delete_parameter_preset,
+            presets::set_agent_parameters,
+            replay::get_replay_settings,
+            replay::set_replay_settings,
+            replay::list_replay_runs,
+            replay::replay_run,
+            injection::get_injection_settings,
+            injection::set_injection_settings,
+            injection::scan_untrusted_content,
+            provenance::get_provenance,
+            provenance::trace_claim,
+            moderation::get_moderation_settings,
+            moderation::set_moderation_settings,
+            moderation::respond_action_approval,
+            input_control::get_input_settings,
+            input_control::set_input_settings,
+            input_control::revoke_input_autonomy,
+            input_control::respond_input_approval,
+            incognito::get_incognito_status,
+            incognito::set_incognito,
+            log_store::query_logs,
+            log_store::list_log_modules,
+            features::get_feature_flags,
+            features::set_feature_flags,
+            jobs::list_failed_jobs,
+            jobs::clear_failed_jobs,
+            offline::get_connectivity_status,
+            offline::get_offline_settings,
+            offline::set_offline_settings,
+            self_monitor::get_self_monitor_status,
+            self_monitor::get_self_monitor_settings,
+            self_monitor::set_self_monitor_settings,
+            backends::get_backend_settings,
+            backends::set_backend_settings,
+            backends::get_backend_stats,
+            backends::select_backend,
+            fallback::get_fallback_settings,
+            fallback::set_fallback_settings,
+            fallback::list_fallback_runs,
+            agent_tests::get_agent_tests,
+            agent_tests::set_agent_tests,
+            agent_tests::run_agent_tests,
+            agent_tests::list_agent_test_runs,
+            evaluation::get_evaluation_settings,
+            evaluation::set_evaluation_settings,
+            evaluation::evaluate_output,
+            evaluation::get_quality_trends,
+            dataset::set_feedback,
+            dataset::get_feedback,
+            dataset::build_dataset,
+            adapters::list_adapters,
+            adapters::add_adapter,
+            adapters::remove_adapter,
+            adapters::adapter_modelfile,
+            adapters::create_adapted_model,
+            adapters::list_model_adapters,
+            transcription::get_transcription_settings,
+            transcription::set_transcription_settings,
+            transcription::detect_language,
+            transcription::process_transcript,
+            transcript_index::search_transcript,
+            transcript_index::end_transcript_session,
+            transcript_index::get_transcript_index_settings,
+            transcript_index::set_transcript_index_settings,
+            captions::get_caption_settings,
+            captions::set_caption_settings,
+            captions::open_caption_overlay,
+            captions::close_caption_overlay,
+            captions::caption_whisper_model,
+            captions::translate_caption,
+            captions::list_captions,
+            captions::clear_captions,
+            sound_events::get_sound_event_settings,
+            sound_events::set_sound_event_settings,
+            sound_events::list_sound_events,
+            ocr_languages::get_ocr_settings,
+            ocr_languages::set_ocr_settings,
+            ocr_languages::list_ocr_languages,
+            ocr_languages::download_ocr_language,
+            ocr_languages::remove_ocr_language,
+            ocr_languages::get_ocr_plan,
+            ui_elements::get_ui_detection_settings,
+            ui_elements::set_ui_detection_settings,
+            ui_elements::detect_ui_elements,
+            ui_elements::detect_screen_ui_elements
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,191 @@
+// In src-tauri/src/llm.rs
+//
+// Minimal Ollama client for backend jobs (summaries, compaction, ...) that
+// need a model answer without going through the frontend. Jobs run on a
+// user's or an agent's behalf (batches, the OpenAI facade) use `proxied`
+// instead, which sends the request through the app's own proxy pipeline.
+
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderValue, Method, Uri};
+use http_body_util::BodyExt;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::locality::{self, SensitiveContent};
+use crate::{compaction, config, policy, privacy};
+use crate::{AppSettings, AppState};
+
+pub const DEFAULT_OLLAMA_URL: &str = "http://127.0.0.1:11434";
+const GENERATION_TIMEOUT: Duration = Duration::from_secs(600);
+
+pub fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(config::upstream_client)
+}
+
+// The Ollama server the managed policy pins, the one the user selected in
+// the launcher, or the local default.
+pub fn ollama_base_url(app: &AppHandle) -> String {
+    if let Some(url) = policy::pinned_ollama_url() {
+        return url.to_string();
+    }
+    let settings = app.state::<AppSettings>();
+    let url = settings.ollama_url.lock().unwrap();
+    url.as_deref().unwrap_or(DEFAULT_OLLAMA_URL).to_string()
+}
+
+// Checks `base_url` against the managed policy and, for a backend off this
+// machine, the kill switch. The guard counts the request as remote while held.
+fn admit(app: &AppHandle, base_url: &str) -> Result<Option<privacy::RemoteRequestGuard>, String> {
+    policy::check_backend(base_url)?;
+    if privacy::is_local_url(base_url) {
+        return Ok(None);
+    }
+    privacy::ensure_allowed(app)?;
+    Ok(Some(privacy::RemoteRequestGuard::new(app)))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+    // Base64 images for vision models.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.into(),
+            images: Vec::new(),
+        }
+    }
+
+    pub fn with_images(mut self, images: Vec<String>) -> Self {
+        self.images = images;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: ChatMessage,
+}
+
+pub async fn chat(app: &AppHandle, model: &str, messages: Vec<ChatMessage>) -> Result<String, String> {
+    let base_url = ollama_base_url(app);
+    let _remote = admit(app, &base_url)?;
+    if messages.iter().any(|m| !m.images.is_empty()) {
+        locality::check(app, None, &base_url, vec![SensitiveContent::Image]).map_err(|v| v.to_string())?;
+    }
+    let url = format!("{}/api/chat", base_url);
+    let body = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "stream": false,
+    });
+
+    let response = client()
+        .post(&url)
+        .timeout(GENERATION_TIMEOUT)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Model '{}' returned {}: {}", model, status, text));
+    }
+
+    let parsed: ChatResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", url, e))?;
+    Ok(parsed.message.content)
+}
+
+pub async fn generate(app: &AppHandle, model: &str, system: &str, prompt: &str) -> Result<String, String> {
+    chat(
+        app,
+        model,
+        vec![ChatMessage::new("system", system), ChatMessage::new("user", prompt)],
+    )
+    .await
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+pub async fn embed(app: &AppHandle, model: &str, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let base_url = ollama_base_url(app);
+    let _remote = admit(app, &base_url)?;
+    let url = format!("{}/api/embed", base_url);
+    let response = client()
+        .post(&url)
+        .timeout(GENERATION_TIMEOUT)
+        .json(&serde_json::json!({ "model": model, "input": inputs }))
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Embedding model '{}' returned {}: {}", model, status, text));
+    }
+
+    let parsed: EmbedResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid embedding response from {}: {}", url, e))?;
+    if parsed.embeddings.len() != inputs.len() {
+        return Err(format!(
+            "Expected {} embeddings, got {}",
+            inputs.len(),
+            parsed.embeddings.len()
+        ));
+    }
+    Ok(parsed.embeddings)
+}
+
+// Sends a request through the proxy as a client would, so the kill switch,
+// policy, locality, budget and every other proxy check apply to it; with
+// `agent_id` it's that agent's request. The answer must not be streamed.
+pub async fn proxied(
+    app: &AppHandle,
+    path: &str,
+    agent_id: Option<&str>,
+    body: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let mut headers = HeaderMap::new();
+    headers.insert(axum::http::header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    if let Some(agent_id) = agent_id {
+        let value = HeaderValue::from_str(agent_id).map_err(|_| format!("Invalid agent ID '{}'", agent_id))?;
+        headers.insert(compaction::AGENT_HEADER, value);
+    }
+    let uri: Uri = path.parse().map_err(|_| format!("Invalid path '{}'", path))?;
+    let state = AppState { app_handle: app.clone(), http_client: client().clone() };
+    let response =
+        observer_core::server::proxy(&state, Method::POST, headers, uri, Body::from(body.to_string())).await;
+
+    let status = response.status();
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .map_err(|e| format!("Failed to read the answer to {}: {}", path, e))?
+        .to_bytes();
+    if !status.is_success() {
+        return Err(format!("{} returned {}: {}", path, status, String::from_utf8_lossy(&bytes)));
+    }
+    serde_json::from_slice(&bytes).map_err(|e| format!("Invalid answer to {}: {}", path, e))
+}
@@ -0,0 +1,245 @@
+// In src-tauri/src/locality.rs
+//
+// Data-locality policy. Each backend is either "local" (fine to receive
+// anything) or "cloud"; by default only loopback addresses count as local,
+// and the user can mark others, e.g. an Ollama box on the LAN, either way.
+// Screenshots and camera images, audio transcripts and clipboard contents
+// are never sent to a cloud backend, whatever the agent asks for: the proxy
+// answers such requests with a 403 explaining how to fix it, and backend
+// jobs get the same message as their error.
+//
+// Images and audio are found in the request body itself. Clipboard text is
+// indistinguishable from any other prompt text there, so the web app lists
+// what it put into the prompt in the `X-Observer-Content` header.
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{audit, policy, privacy, storage};
+
+const SETTINGS_FILE: &str = "locality.json";
+pub const CONTENT_HEADER: &str = "x-observer-content";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Locality {
+    Local,
+    Cloud,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalitySettings {
+    pub enabled: bool,
+    // Origin (scheme://host:port) -> the user's classification.
+    pub backends: BTreeMap<String, Locality>,
+}
+
+impl Default for LocalitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            backends: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LocalityState {
+    settings: Mutex<LocalitySettings>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensitiveContent {
+    Image,
+    Audio,
+    Clipboard,
+}
+
+impl SensitiveContent {
+    fn describe(self) -> &'static str {
+        match self {
+            SensitiveContent::Image => "screenshots/camera images",
+            SensitiveContent::Audio => "audio",
+            SensitiveContent::Clipboard => "clipboard content",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub backend: String,
+    pub content: Vec<SensitiveContent>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let content: Vec<&str> = self.content.iter().map(|c| c.describe()).collect();
+        write!(
+            f,
+            "Refusing to send {} to {}, which is not marked as a local backend. Mark it as local in the \
+             privacy settings if it runs on hardware you trust, or switch to a local model.",
+            content.join(" and "),
+            self.backend
+        )
+    }
+}
+
+// scheme://host[:port], lowercased, so marks survive differing paths.
+pub fn origin(url: &str) -> String {
+    let url = url.trim().to_ascii_lowercase();
+    let (scheme, rest) = url.split_once("://").unwrap_or(("http", url.as_str()));
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    format!("{}://{}", scheme, host)
+}
+
+pub fn classify(app: &AppHandle, url: &str) -> Locality {
+    // A managed policy decides what's local, not the user's marks.
+    if policy::forces_data_locality() {
+        return if policy::is_local_backend(url) { Locality::Local } else { Locality::Cloud };
+    }
+    let marked = app.state::<LocalityState>().settings.lock().unwrap().backends.get(&origin(url)).copied();
+    marked.unwrap_or(if privacy::is_local_url(url) { Locality::Local } else { Locality::Cloud })
+}
+
+fn has_items(value: Option<&Value>) -> bool {
+    value.and_then(Value::as_array).is_some_and(|items| !items.is_empty())
+}
+
+// Sensitive content in an Ollama or OpenAI-style request.
+pub fn sensitive_content(headers: &HeaderMap, body: &[u8]) -> Vec<SensitiveContent> {
+    let mut found = Vec::new();
+    if let Some(declared) = headers.get(CONTENT_HEADER).and_then(|v| v.to_str().ok()) {
+        for kind in declared.split(',').map(str::trim) {
+            match kind {
+                "image" | "screen" | "camera" => found.push(SensitiveContent::Image),
+                "audio" => found.push(SensitiveContent::Audio),
+                "clipboard" => found.push(SensitiveContent::Clipboard),
+                _ => {}
+            }
+        }
+    }
+
+    let markers: [&[u8]; 3] = [b"\"images\"", b"\"image_url\"", b"\"input_audio\""];
+    let mentions = |marker: &[u8]| body.windows(marker.len()).any(|w| w == marker);
+    if markers.iter().any(|m| mentions(m)) {
+        if let Ok(request) = serde_json::from_slice::<Value>(body) {
+            if has_items(request.get("images")) {
+                found.push(SensitiveContent::Image);
+            }
+            for message in request.get("messages").and_then(Value::as_array).into_iter().flatten() {
+                if has_items(message.get("images")) {
+                    found.push(SensitiveContent::Image);
+                }
+                for part in message.get("content").and_then(Value::as_array).into_iter().flatten() {
+                    match part.get("type").and_then(Value::as_str) {
+                        Some("image_url") => found.push(SensitiveContent::Image),
+                        Some("input_audio") => found.push(SensitiveContent::Audio),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+// Err when `content` may not go to `backend_url`. Violations are audited and
+// emitted as "data-locality-violation" for the UI.
+pub fn check(
+    app: &AppHandle,
+    agent_id: Option<&str>,
+    backend_url: &str,
+    content: Vec<SensitiveContent>,
+) -> Result<(), Violation> {
+    let enabled = app.state::<LocalityState>().settings.lock().unwrap().enabled || policy::forces_data_locality();
+    if !enabled || content.is_empty() || classify(app, backend_url) == Locality::Local {
+        return Ok(());
+    }
+    let violation = Violation { backend: origin(backend_url), content };
+    log::warn!("Data-locality violation: {}", violation);
+    audit::record(app, agent_id, "locality.blocked", serde_json::to_value(&violation).unwrap_or_default());
+    let payload = serde_json::json!({ "agent_id": agent_id, "violation": violation, "message": violation.to_string() });
+    if let Err(e) = app.emit("data-locality-violation", payload) {
+        log::error!("Failed to emit data-locality-violation event: {}", e);
+    }
+    Err(violation)
+}
+
+pub fn violation_response(violation: &Violation) -> Response {
+    let body = serde_json::json!({
+        "error": violation.to_string(),
+        "code": "data_locality_violation",
+        "backend": violation.backend,
+        "content": violation.content,
+    });
+    Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<LocalityState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_locality_settings(state: State<'_, LocalityState>) -> LocalitySettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_locality_settings(
+    app: AppHandle,
+    mut settings: LocalitySettings,
+    state: State<'_, LocalityState>,
+) -> Result<(), String> {
+    if !settings.enabled && policy::forces_data_locality() {
+        return Err("The data-locality policy is enforced by your organisation".to_string());
+    }
+    settings.backends = settings.backends.into_iter().map(|(url, locality)| (origin(&url), locality)).collect();
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+// `locality` of None removes the mark, falling back to the default.
+#[tauri::command]
+pub fn set_backend_locality(
+    app: AppHandle,
+    url: String,
+    locality: Option<Locality>,
+    state: State<'_, LocalityState>,
+) -> Result<Locality, String> {
+    if policy::forces_data_locality() {
+        return Err("Backend classifications are managed by your organisation".to_string());
+    }
+    let settings = {
+        let mut settings = state.settings.lock().unwrap();
+        match locality {
+            Some(locality) => settings.backends.insert(origin(&url), locality),
+            None => settings.backends.remove(&origin(&url)),
+        };
+        settings.clone()
+    };
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    Ok(classify(&app, &url))
+}
+
+#[tauri::command]
+pub fn get_backend_locality(app: AppHandle, url: String) -> Locality {
+    classify(&app, &url)
+}
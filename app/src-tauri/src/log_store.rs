@@ -0,0 +1,294 @@
+// In src-tauri/src/log_store.rs
+//
+// Structured, queryable copy of the app log for the in-app log console.
+// Every record that passes the log level is also kept in an in-memory SQLite
+// table (timestamp, level, module, request ID, message) indexed on the
+// fields the console filters by, up to the last `MAX_RECORDS`. The log file
+// stays the complete record; this is for finding things quickly.
+//
+// `query_logs` and `GET /observer/logs/query` filter by minimum level,
+// module (a module matches its submodules too, so `observer_lib` covers
+// `observer_lib::server`), time range, request ID and text, and page with
+// `before_id`. Tailing is done with `after_id`, which returns what came
+// since in order; over HTTP, `follow=1` keeps the response open and streams
+// new records as JSON lines. `list_log_modules` gives the module tree.
+
+use axum::{
+    body::Body,
+    extract::Query,
+    http::{header, StatusCode},
+    response::Response,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+const MAX_RECORDS: i64 = 20_000;
+// Old records are pruned every this many inserts.
+const PRUNE_EVERY: i64 = 500;
+const DEFAULT_LIMIT: usize = 200;
+const MAX_LIMIT: usize = 2000;
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+static STORE: OnceLock<Option<Mutex<Connection>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub module: String,
+    pub request_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    // Minimum severity, e.g. "warn" for warnings and errors.
+    pub level: Option<String>,
+    pub module: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub request_id: Option<String>,
+    // Case-insensitive substring of the message.
+    pub text: Option<String>,
+    // Older records than this, for the next page.
+    pub before_id: Option<i64>,
+    // Newer records than this, oldest first, for tailing.
+    pub after_id: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    pub records: Vec<LogRecord>,
+    // Pass as `before_id` for the next page; None when there's no more.
+    pub next_before_id: Option<i64>,
+    // The newest record's id, to tail from with `after_id`.
+    pub latest_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogModule {
+    pub module: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FollowParams {
+    pub follow: Option<String>,
+}
+
+fn open() -> rusqlite::Result<Connection> {
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(
+        "CREATE TABLE logs (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             created_at INTEGER NOT NULL,
+             level INTEGER NOT NULL,
+             module TEXT NOT NULL,
+             request_id TEXT,
+             message TEXT NOT NULL
+         );
+         CREATE INDEX logs_created_at ON logs (created_at);
+         CREATE INDEX logs_level ON logs (level);
+         CREATE INDEX logs_module ON logs (module);
+         CREATE INDEX logs_request_id ON logs (request_id);",
+    )?;
+    Ok(conn)
+}
+
+fn store() -> Option<&'static Mutex<Connection>> {
+    STORE
+        .get_or_init(|| match open() {
+            Ok(conn) => Some(Mutex::new(conn)),
+            Err(e) => {
+                // Not log::error!: that would come straight back here.
+                eprintln!("Failed to create the log store: {}", e);
+                None
+            }
+        })
+        .as_ref()
+}
+
+// Called from the log format (see lib.rs) for every record that's written.
+pub fn capture(record: &log::Record, request_id: Option<String>) {
+    let Some(store) = store() else {
+        return;
+    };
+    // A record logged while the store is busy would deadlock; it's in the file anyway.
+    let Ok(conn) = store.try_lock() else {
+        return;
+    };
+    let result = conn
+        .execute(
+            "INSERT INTO logs (created_at, level, module, request_id, message) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                Utc::now().timestamp_millis(),
+                record.level() as i64,
+                record.target(),
+                request_id,
+                record.args().to_string()
+            ],
+        )
+        .and_then(|_| {
+            let id = conn.last_insert_rowid();
+            if id % PRUNE_EVERY == 0 {
+                conn.execute("DELETE FROM logs WHERE id <= ?1", params![id - MAX_RECORDS])?;
+            }
+            Ok(())
+        });
+    if let Err(e) = result {
+        eprintln!("Failed to store a log record: {}", e);
+    }
+}
+
+fn level_number(level: &str) -> Result<i64, String> {
+    level
+        .parse::<log::Level>()
+        .map(|level| level as i64)
+        .map_err(|_| format!("Unknown log level '{}', expected error, warn, info, debug or trace", level))
+}
+
+fn row_to_record(row: &rusqlite::Row) -> rusqlite::Result<LogRecord> {
+    let level: i64 = row.get(2)?;
+    let level = [log::Level::Error, log::Level::Warn, log::Level::Info, log::Level::Debug, log::Level::Trace]
+        .into_iter()
+        .find(|l| *l as i64 == level)
+        .map_or("unknown".to_string(), |l| l.as_str().to_lowercase());
+    Ok(LogRecord {
+        id: row.get(0)?,
+        timestamp: Utc.timestamp_millis_opt(row.get(1)?).single().unwrap_or_default(),
+        level,
+        module: row.get(3)?,
+        request_id: row.get(4)?,
+        message: row.get(5)?,
+    })
+}
+
+pub fn query(query: &LogQuery) -> Result<LogPage, String> {
+    let store = store().ok_or("The log store is unavailable")?;
+    let level = query.level.as_deref().map(level_number).transpose()?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    // Tailing reads forward so nothing is skipped when many records arrived.
+    let order = if query.after_id.is_some() { "ASC" } else { "DESC" };
+    let sql = format!(
+        "SELECT id, created_at, level, module, request_id, message FROM logs
+         WHERE (?1 IS NULL OR level <= ?1)
+           AND (?2 IS NULL OR module = ?2 OR substr(module, 1, length(?2) + 2) = ?2 || '::')
+           AND (?3 IS NULL OR created_at >= ?3)
+           AND (?4 IS NULL OR created_at < ?4)
+           AND (?5 IS NULL OR request_id = ?5)
+           AND (?6 IS NULL OR instr(lower(message), lower(?6)) > 0)
+           AND (?7 IS NULL OR id < ?7)
+           AND (?8 IS NULL OR id > ?8)
+         ORDER BY id {}
+         LIMIT ?9",
+        order
+    );
+
+    let conn = store.lock().unwrap();
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            params![
+                level,
+                query.module.as_deref().filter(|m| !m.is_empty()),
+                query.since.map(|t| t.timestamp_millis()),
+                query.until.map(|t| t.timestamp_millis()),
+                query.request_id.as_deref().filter(|id| !id.is_empty()),
+                query.text.as_deref().filter(|text| !text.is_empty()),
+                query.before_id,
+                query.after_id,
+                limit as i64,
+            ],
+            row_to_record,
+        )
+        .map_err(|e| e.to_string())?;
+    let records = rows
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to query the logs: {}", e))?;
+    let latest_id: Option<i64> =
+        conn.query_row("SELECT MAX(id) FROM logs", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+
+    let next_before_id = match query.after_id {
+        None if records.len() == limit => records.last().map(|record| record.id),
+        _ => None,
+    };
+    Ok(LogPage { records, next_before_id, latest_id })
+}
+
+pub fn modules() -> Result<Vec<LogModule>, String> {
+    let store = store().ok_or("The log store is unavailable")?;
+    let conn = store.lock().unwrap();
+    let mut stmt = conn
+        .prepare("SELECT module, COUNT(*) FROM logs GROUP BY module ORDER BY module")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok(LogModule { module: row.get(0)?, count: row.get::<_, i64>(1)? as u64 }))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to list log modules: {}", e))
+}
+
+fn json_lines(records: &[LogRecord]) -> String {
+    records
+        .iter()
+        .filter_map(|record| serde_json::to_string(record).ok())
+        .flat_map(|line| [line, "\n".to_string()])
+        .collect()
+}
+
+// A page as JSON; with `follow=1`, JSON lines of the page (oldest first)
+// and then of each new record as it's logged.
+pub async fn query_handler(
+    Query(params): Query<LogQuery>,
+    Query(follow): Query<FollowParams>,
+) -> Result<Response, (StatusCode, String)> {
+    let page = query(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if !matches!(follow.follow.as_deref(), Some("1" | "true")) {
+        let body = serde_json::to_vec(&page).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        return Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+    }
+
+    let mut tail = LogQuery { before_id: None, after_id: page.latest_id.or(Some(0)), ..params };
+    let mut first: Vec<LogRecord> = page.records;
+    first.sort_by_key(|record| record.id);
+    let body = async_stream::stream! {
+        yield Ok::<_, std::io::Error>(json_lines(&first));
+        loop {
+            tokio::time::sleep(FOLLOW_INTERVAL).await;
+            match query(&tail) {
+                Ok(page) if !page.records.is_empty() => {
+                    tail.after_id = page.records.last().map(|record| record.id);
+                    yield Ok(json_lines(&page.records));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    yield Err(std::io::Error::other(e));
+                    break;
+                }
+            }
+        }
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from_stream(body))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+#[tauri::command]
+pub fn query_logs(query: LogQuery) -> Result<LogPage, String> {
+    self::query(&query)
+}
+
+#[tauri::command]
+pub fn list_log_modules() -> Result<Vec<LogModule>, String> {
+    modules()
+}
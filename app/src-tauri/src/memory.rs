@@ -0,0 +1,222 @@
+// In src-tauri/src/memory.rs
+//
+// Persistent agent memory that outlives a single run:
+//   - facts: free text remembered with `remember` and found again with
+//     `recall` through semantic search (vector_store.rs, collection
+//     "memory:<agent_id>"),
+//   - key/value pairs for structured state ("user_name" -> "Ana").
+//
+// When `auto_inject` is on, chat requests proxied with an
+// `X-Observer-Agent-Id` header get the agent's key/values and the facts most
+// relevant to the latest user message added as a system message.
+//
+// This is separate from the frontend's plain-text $MEMORY@agent store, which
+// stays as-is.
+
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::vector_store::{self, SearchHit, VectorRecord};
+use crate::{compaction, incognito, storage};
+
+const SETTINGS_FILE: &str = "memory.json";
+const MAX_INJECTED_KEYS: u32 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemorySettings {
+    pub auto_inject: bool,
+    pub max_facts: usize,
+    // Facts scoring below this similarity are not injected.
+    pub min_score: f32,
+}
+
+impl Default for MemorySettings {
+    fn default() -> Self {
+        Self {
+            auto_inject: true,
+            max_facts: 5,
+            min_score: 0.35,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryState(pub Mutex<MemorySettings>);
+
+pub fn collection(agent_id: &str) -> String {
+    format!("memory:{}", agent_id)
+}
+
+pub fn kv_list(db: &HistoryDb, agent_id: &str, limit: u32) -> Result<Vec<(String, String)>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM agent_kv WHERE agent_id = ?1 ORDER BY updated_at DESC LIMIT ?2")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![agent_id, limit], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+pub async fn memory_block(app: &AppHandle, agent_id: &str, query: &str) -> Result<Option<String>, String> {
+    let settings = app.state::<MemoryState>().0.lock().unwrap().clone();
+
+    let pairs = kv_list(&app.state::<HistoryDb>(), agent_id, MAX_INJECTED_KEYS)?;
+    let facts: Vec<SearchHit> = if query.trim().is_empty() {
+        Vec::new()
+    } else {
+        vector_store::search(app, &collection(agent_id), query, settings.max_facts)
+            .await?
+            .into_iter()
+            .filter(|hit| hit.score >= settings.min_score)
+            .collect()
+    };
+
+    if pairs.is_empty() && facts.is_empty() {
+        return Ok(None);
+    }
+
+    let mut block = String::from("Things you remember from earlier runs:\n");
+    for (key, value) in pairs {
+        block.push_str(&format!("- {}: {}\n", key, value));
+    }
+    for fact in facts {
+        block.push_str(&format!("- {}\n", fact.record.text));
+    }
+    Ok(Some(block))
+}
+
+// Proxy hook. Returns the rewritten body when memory was injected.
+pub async fn inject_request(app: &AppHandle, agent_id: &str, body: &[u8]) -> Option<Vec<u8>> {
+    if !app.state::<MemoryState>().0.lock().unwrap().auto_inject {
+        return None;
+    }
+
+    let mut json: Value = serde_json::from_slice(body).ok()?;
+    let query = if let Some(messages) = json["messages"].as_array() {
+        messages
+            .iter()
+            .rev()
+            .find(|m| m["role"] == "user")
+            .map(compaction::message_text)
+            .unwrap_or_default()
+    } else {
+        json["prompt"].as_str()?.to_string()
+    };
+
+    let block = match memory_block(app, agent_id, &query).await {
+        Ok(Some(block)) => block,
+        Ok(None) => return None,
+        Err(e) => {
+            log::error!("Failed to load memory for agent {}: {}", agent_id, e);
+            return None;
+        }
+    };
+
+    if let Some(messages) = json["messages"].as_array_mut() {
+        // After the agent's own system prompt, before the conversation.
+        let position = messages.iter().take_while(|m| m["role"] == "system").count();
+        messages.insert(position, json!({ "role": "system", "content": block }));
+    } else {
+        let system = json["system"].as_str().unwrap_or("").to_string();
+        json["system"] = Value::String(format!("{}\n\n{}", system, block).trim().to_string());
+    }
+    serde_json::to_vec(&json).ok()
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<MemoryState>().0.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub async fn remember(app: AppHandle, agent_id: String, fact: String) -> Result<i64, String> {
+    if fact.trim().is_empty() {
+        return Err("Cannot remember an empty fact".to_string());
+    }
+    vector_store::add(&app, &collection(&agent_id), fact.trim(), None).await
+}
+
+#[tauri::command]
+pub async fn recall(
+    app: AppHandle,
+    agent_id: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    vector_store::search(&app, &collection(&agent_id), &query, limit.unwrap_or(5)).await
+}
+
+#[tauri::command]
+pub fn list_memories(
+    agent_id: String,
+    limit: Option<u32>,
+    db: State<'_, HistoryDb>,
+) -> Result<Vec<VectorRecord>, String> {
+    vector_store::list(&db, &collection(&agent_id), limit.unwrap_or(200))
+}
+
+#[tauri::command]
+pub fn forget(agent_id: String, id: i64, db: State<'_, HistoryDb>) -> Result<bool, String> {
+    vector_store::delete(&db, &collection(&agent_id), id)
+}
+
+#[tauri::command]
+pub fn memory_set(agent_id: String, key: String, value: String, db: State<'_, HistoryDb>) -> Result<(), String> {
+    incognito::ensure_persistent()?;
+    let conn = db.0.lock().unwrap();
+    conn.execute(
+        "INSERT INTO agent_kv (agent_id, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(agent_id, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        params![agent_id, key, value, Utc::now().timestamp_millis()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn memory_get(agent_id: String, key: String, db: State<'_, HistoryDb>) -> Result<Option<String>, String> {
+    let conn = db.0.lock().unwrap();
+    conn.query_row(
+        "SELECT value FROM agent_kv WHERE agent_id = ?1 AND key = ?2",
+        params![agent_id, key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn memory_delete(agent_id: String, key: String, db: State<'_, HistoryDb>) -> Result<bool, String> {
+    let conn = db.0.lock().unwrap();
+    let deleted = conn
+        .execute("DELETE FROM agent_kv WHERE agent_id = ?1 AND key = ?2", params![agent_id, key])
+        .map_err(|e| e.to_string())?;
+    Ok(deleted > 0)
+}
+
+#[tauri::command]
+pub fn memory_entries(agent_id: String, db: State<'_, HistoryDb>) -> Result<Vec<(String, String)>, String> {
+    kv_list(&db, &agent_id, 1000)
+}
+
+#[tauri::command]
+pub fn get_memory_settings(state: State<'_, MemoryState>) -> MemorySettings {
+    state.0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_memory_settings(
+    app: AppHandle,
+    settings: MemorySettings,
+    state: State<'_, MemoryState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.0.lock().unwrap() = settings;
+    Ok(())
+}
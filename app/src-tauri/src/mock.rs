@@ -0,0 +1,61 @@
+// In src-tauri/src/mock.rs
+//
+// Built-in mock Ollama for offline development and demos. When enabled
+// (`--mock-ollama`, or `set_mock_ollama` from the settings) the proxy
+// answers from here instead of forwarding: canned model lists for
+// `/v1/models` and `/api/tags`, and synthetic replies streamed token by
+// token with a realistic time-to-first-token and per-token pace, in the
+// Ollama or OpenAI format the request asked for. Nothing leaves the
+// machine, so no GPU or network is needed.
+//
+// The replies themselves come from observer_core::mock; this module only
+// decides whether the proxy uses them.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, State};
+
+use crate::storage;
+
+pub use observer_core::mock::respond;
+
+const SETTINGS_FILE: &str = "mock_ollama.json";
+const CLI_SWITCH: &str = "--mock-ollama";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockSettings {
+    pub enabled: bool,
+}
+
+#[derive(Default)]
+pub struct MockState {
+    enabled: AtomicBool,
+}
+
+pub fn is_enabled(app: &AppHandle) -> bool {
+    app.state::<MockState>().enabled.load(Ordering::SeqCst)
+}
+
+pub fn init(app: &AppHandle) {
+    let settings: MockSettings = storage::load_json(app, SETTINGS_FILE);
+    let switched = std::env::args().skip(1).any(|arg| arg == CLI_SWITCH);
+    let enabled = settings.enabled || switched;
+    if enabled {
+        log::warn!("Mock Ollama enabled: model requests get synthetic replies");
+    }
+    app.state::<MockState>().enabled.store(enabled, Ordering::SeqCst);
+}
+
+#[tauri::command]
+pub fn get_mock_ollama(state: State<'_, MockState>) -> bool {
+    state.enabled.load(Ordering::SeqCst)
+}
+
+#[tauri::command]
+pub fn set_mock_ollama(app: AppHandle, enabled: bool, state: State<'_, MockState>) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &MockSettings { enabled })?;
+    log::info!("Mock Ollama {}", if enabled { "enabled" } else { "disabled" });
+    state.enabled.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
@@ -0,0 +1,823 @@
+// In src-tauri/src/model_manager.rs
+//
+// Model downloads that Observer manages itself instead of leaving them to
+// `ollama pull`: from the Ollama registry or a LAN peer (see
+// model_share.rs) straight into Ollama's model store, which Ollama picks up
+// without a restart.
+//
+// Managing them means they can be throttled (`max_kbps`), paused and
+// resumed (partial blobs are kept and continued with a Range request), and
+// held to a time window such as "only at night". Each blob is checked
+// against its sha256 digest before it's moved into place, and the manifest
+// is written last so Ollama never sees a half-downloaded model. Every job
+// change is emitted as "model-download-progress". Downloads still running
+// when the app stops continue after the next start (see jobs.rs).
+//
+// [registry] points model pulls at a mirror (a corporate proxy registry or
+// a local one) instead of registry.ollama.ai. It applies to these managed
+// downloads, to `/api/pull` through the proxy (see `rewrite_pull_request`)
+// and to `ollama pull` run via `/exec` (see `exec_args`, `exec_env`). The
+// mirror's password is kept in the OS keyring (secrets.rs). Managed pulls
+// store models under their usual names; Ollama's own pulls name them after
+// the mirror, e.g. "models.corp:5000/library/llama3:8b".
+
+use chrono::{Local, NaiveTime};
+use futures::StreamExt;
+use reqwest::header::HeaderName;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Notify;
+
+use crate::{agent_tests, jobs, llm, secrets, storage};
+
+const SETTINGS_FILE: &str = "model_downloads.json";
+const DEFAULT_REGISTRY: &str = "registry.ollama.ai";
+const DEFAULT_NAMESPACE: &str = "library";
+const MANIFEST_ACCEPT: &str = "application/vnd.docker.distribution.manifest.v2+json";
+const WINDOW_POLL: Duration = Duration::from_secs(30);
+// Progress events at most this often per job.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadWindow {
+    // "HH:MM" local time; a window may wrap past midnight ("22:00"-"07:00").
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadSettings {
+    // None downloads as fast as the connection allows.
+    pub max_kbps: Option<u64>,
+    pub window: Option<DownloadWindow>,
+    // The Ollama model directory; OLLAMA_MODELS or the default when unset.
+    pub models_dir: Option<String>,
+    pub registry: RegistrySettings,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistrySettings {
+    // host[:port] pulled from in place of registry.ollama.ai.
+    pub mirror: Option<String>,
+    // Plain HTTP and unverified certificates, for registries on the LAN.
+    pub insecure: bool,
+    pub username: Option<String>,
+    // Also passed to `ollama` as HTTPS_PROXY.
+    pub https_proxy: Option<String>,
+}
+
+impl RegistrySettings {
+    fn mirror(&self) -> Option<&str> {
+        self.mirror.as_deref().map(str::trim).filter(|m| !m.is_empty())
+    }
+
+    fn secret_key(&self) -> Option<String> {
+        let username = self.username.as_deref().filter(|u| !u.is_empty())?;
+        Some(format!("registry:{}@{}", username, self.mirror().unwrap_or(DEFAULT_REGISTRY)))
+    }
+
+    fn credentials(&self) -> Option<(String, String)> {
+        let password = secrets::get(&self.secret_key()?).ok().flatten()?;
+        Some((self.username.clone()?, password))
+    }
+
+    // Models from the default registry are fetched from the mirror instead.
+    fn resolve(&self, model: &ModelRef) -> ModelRef {
+        match self.mirror() {
+            Some(mirror) if model.registry == DEFAULT_REGISTRY => {
+                ModelRef { registry: mirror.to_string(), ..model.clone() }
+            }
+            _ => model.clone(),
+        }
+    }
+
+    fn client(&self) -> Result<reqwest::Client, String> {
+        let proxy = self.https_proxy.as_deref().filter(|p| !p.is_empty());
+        if !self.insecure && proxy.is_none() {
+            return Ok(llm::client().clone());
+        }
+        let mut builder = reqwest::Client::builder().danger_accept_invalid_certs(self.insecure);
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy).map_err(|e| format!("Bad HTTPS proxy: {}", e))?);
+        }
+        builder.build().map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Source {
+    Registry,
+    Peer(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    // Outside the download window.
+    Waiting,
+    Downloading,
+    Paused,
+    Verifying,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadJob {
+    pub id: String,
+    pub model: String,
+    pub source: String,
+    pub status: JobStatus,
+    pub completed: u64,
+    pub total: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct JobControl {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+    wake: Notify,
+}
+
+#[derive(Default)]
+pub struct DownloadState {
+    jobs: Mutex<BTreeMap<String, DownloadJob>>,
+    controls: Mutex<HashMap<String, Arc<JobControl>>>,
+}
+
+// A model name split the way Ollama stores it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelRef {
+    pub registry: String,
+    pub namespace: String,
+    pub repo: String,
+    pub tag: String,
+}
+
+impl ModelRef {
+    // "llama3" is registry.ollama.ai/library/llama3:latest.
+    pub fn parse(model: &str) -> Result<Self, String> {
+        let (name, tag) = match model.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, tag),
+            _ => (model, "latest"),
+        };
+        let parts: Vec<&str> = name.split('/').collect();
+        let (registry, namespace, repo) = match parts.as_slice() {
+            [repo] => (DEFAULT_REGISTRY, DEFAULT_NAMESPACE, *repo),
+            [namespace, repo] => (DEFAULT_REGISTRY, *namespace, *repo),
+            [registry, namespace, repo] => (*registry, *namespace, *repo),
+            _ => return Err(format!("'{}' isn't a model name", model)),
+        };
+        let valid = |part: &str| {
+            !part.is_empty()
+                && part != "."
+                && part != ".."
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))
+        };
+        if ![registry, namespace, repo, tag].iter().all(|part| valid(part)) {
+            return Err(format!("'{}' isn't a model name", model));
+        }
+        Ok(Self {
+            registry: registry.to_string(),
+            namespace: namespace.to_string(),
+            repo: repo.to_string(),
+            tag: tag.to_string(),
+        })
+    }
+
+    // The inverse of `manifest_path`.
+    pub fn from_manifest_path(relative: &Path) -> Option<Self> {
+        let parts: Vec<String> = relative.iter().map(|p| p.to_string_lossy().to_string()).collect();
+        let [registry, namespace, repo, tag] = parts.as_slice() else {
+            return None;
+        };
+        Some(Self { registry: registry.clone(), namespace: namespace.clone(), repo: repo.clone(), tag: tag.clone() })
+    }
+
+    // Where the manifest lives under `manifests/`.
+    pub fn manifest_path(&self) -> PathBuf {
+        [&self.registry, &self.namespace, &self.repo, &self.tag].iter().collect()
+    }
+
+    // The short form Ollama shows, leaving out the defaults.
+    pub fn name(&self) -> String {
+        let name = match (self.registry.as_str(), self.namespace.as_str()) {
+            (DEFAULT_REGISTRY, DEFAULT_NAMESPACE) => self.repo.clone(),
+            (DEFAULT_REGISTRY, _) => format!("{}/{}", self.namespace, self.repo),
+            _ => format!("{}/{}/{}", self.registry, self.namespace, self.repo),
+        };
+        format!("{}:{}", name, self.tag)
+    }
+}
+
+pub fn settings(app: &AppHandle) -> DownloadSettings {
+    storage::load_json(app, SETTINGS_FILE)
+}
+
+// Ollama's model store.
+pub fn models_dir(app: &AppHandle) -> Option<PathBuf> {
+    if let Some(dir) = settings(app).models_dir {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("OLLAMA_MODELS") {
+        return Some(PathBuf::from(dir));
+    }
+    let home = std::env::var_os(if cfg!(target_os = "windows") { "USERPROFILE" } else { "HOME" })?;
+    let default = PathBuf::from(home).join(".ollama").join("models");
+    // Where the Linux install script's service keeps them.
+    #[cfg(target_os = "linux")]
+    {
+        let service = PathBuf::from("/usr/share/ollama/.ollama/models");
+        if !default.join("manifests").is_dir() && service.join("manifests").is_dir() {
+            return Some(service);
+        }
+    }
+    Some(default)
+}
+
+pub fn valid_digest(digest: &str) -> bool {
+    digest
+        .strip_prefix("sha256:")
+        .is_some_and(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase()))
+}
+
+// Blobs are stored as `sha256-<hex>`.
+pub fn blob_path(dir: &Path, digest: &str) -> PathBuf {
+    dir.join("blobs").join(digest.replace(':', "-"))
+}
+
+// Every blob a manifest refers to, with its size.
+pub fn layers(manifest: &Value) -> Vec<(String, u64)> {
+    std::iter::once(&manifest["config"])
+        .chain(manifest["layers"].as_array().into_iter().flatten())
+        .filter_map(|layer| Some((layer["digest"].as_str()?.to_string(), layer["size"].as_u64().unwrap_or(0))))
+        .collect()
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()
+}
+
+fn in_window(window: Option<&DownloadWindow>) -> bool {
+    let Some((start, end)) = window.and_then(|w| Some((parse_time(&w.start)?, parse_time(&w.end)?))) else {
+        return true;
+    };
+    let now = Local::now().time();
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+fn update(app: &AppHandle, id: &str, change: impl FnOnce(&mut DownloadJob)) {
+    let job = {
+        let state = app.state::<DownloadState>();
+        let mut jobs = state.jobs.lock().unwrap();
+        let Some(job) = jobs.get_mut(id) else {
+            return;
+        };
+        change(job);
+        job.clone()
+    };
+    if let Err(e) = app.emit("model-download-progress", job) {
+        log::error!("Failed to emit model-download-progress event: {}", e);
+    }
+}
+
+// The name `ollama pull` should be given for `model`.
+pub fn mirrored_name(app: &AppHandle, model: &str) -> String {
+    match ModelRef::parse(model) {
+        Ok(parsed) => settings(app).registry.resolve(&parsed).name(),
+        Err(_) => model.to_string(),
+    }
+}
+
+// A proxied `/api/pull` body pointed at the mirror, or None to send it as is.
+pub fn rewrite_pull_request(app: &AppHandle, body: &[u8]) -> Option<Vec<u8>> {
+    let registry = settings(app).registry;
+    if registry.mirror().is_none() {
+        return None;
+    }
+    let mut request: Value = serde_json::from_slice(body).ok()?;
+    let key = if request["model"].is_string() { "model" } else { "name" };
+    let model = request[key].as_str()?.to_string();
+    let mirrored = mirrored_name(app, &model);
+    if mirrored == model {
+        return None;
+    }
+    log::info!("Pulling {} from the mirror as {}", model, mirrored);
+    request[key] = Value::String(mirrored);
+    if registry.insecure {
+        request["insecure"] = Value::Bool(true);
+    }
+    serde_json::to_vec(&request).ok()
+}
+
+// `ollama pull` arguments with the mirror applied.
+pub fn exec_args(app: &AppHandle, mut args: Vec<String>) -> Vec<String> {
+    if args.first().map(String::as_str) != Some("pull") {
+        return args;
+    }
+    let registry = settings(app).registry;
+    if let Some(model) = args.iter_mut().skip(1).find(|arg| !arg.starts_with('-')) {
+        *model = mirrored_name(app, model);
+    }
+    if registry.insecure && registry.mirror().is_some() && !args.iter().any(|arg| arg == "--insecure") {
+        args.push("--insecure".to_string());
+    }
+    args
+}
+
+// Environment for `ollama` commands Observer runs.
+pub fn exec_env(app: &AppHandle) -> Vec<(String, String)> {
+    settings(app)
+        .registry
+        .https_proxy
+        .filter(|p| !p.is_empty())
+        .map(|proxy| vec![("HTTPS_PROXY".to_string(), proxy)])
+        .unwrap_or_default()
+}
+
+// `key="value"` pairs from a `WWW-Authenticate: Bearer ...` challenge.
+fn challenge_params(challenge: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut rest = challenge.trim();
+    while let Some((key, after)) = rest.split_once("=\"") {
+        let Some((value, after)) = after.split_once('"') else {
+            break;
+        };
+        params.insert(key.trim().trim_start_matches(',').trim().to_string(), value.to_string());
+        rest = after;
+    }
+    params
+}
+
+// HTTP for one download: where the model comes from, the registry's client
+// and credentials, and the bearer token a registry hands out on its first 401.
+struct Fetcher {
+    source: Source,
+    // The model as the source knows it; mirrors have their own host.
+    remote: ModelRef,
+    scheme: &'static str,
+    client: reqwest::Client,
+    credentials: Option<(String, String)>,
+    token: tokio::sync::Mutex<Option<String>>,
+}
+
+impl Fetcher {
+    fn new(app: &AppHandle, source: &Source, model: &ModelRef) -> Result<Self, String> {
+        let registry = settings(app).registry;
+        let (remote, scheme, client, credentials) = match source {
+            Source::Registry => (
+                registry.resolve(model),
+                if registry.insecure { "http" } else { "https" },
+                registry.client()?,
+                registry.credentials(),
+            ),
+            Source::Peer(_) => (model.clone(), "", llm::client().clone(), None),
+        };
+        Ok(Self { source: source.clone(), remote, scheme, client, credentials, token: tokio::sync::Mutex::new(None) })
+    }
+
+    fn manifest_url(&self) -> String {
+        let model = &self.remote;
+        match &self.source {
+            Source::Registry => format!(
+                "{}://{}/v2/{}/{}/manifests/{}",
+                self.scheme, model.registry, model.namespace, model.repo, model.tag
+            ),
+            Source::Peer(peer) => format!("{}/share/manifests/{}", peer, model.name()),
+        }
+    }
+
+    fn blob_url(&self, digest: &str) -> String {
+        let model = &self.remote;
+        match &self.source {
+            Source::Registry => {
+                format!("{}://{}/v2/{}/{}/blobs/{}", self.scheme, model.registry, model.namespace, model.repo, digest)
+            }
+            Source::Peer(peer) => format!("{}/share/blobs/{}", peer, digest),
+        }
+    }
+
+    async fn get(&self, url: &str, header: (HeaderName, String)) -> reqwest::Result<reqwest::Response> {
+        let request = |token: Option<&str>| {
+            let request = self.client.get(url).header(header.0.clone(), header.1.clone());
+            match (token, &self.credentials) {
+                (Some(token), _) => request.bearer_auth(token),
+                (None, Some((username, password))) => request.basic_auth(username, Some(password)),
+                (None, None) => request,
+            }
+        };
+        let token = self.token.lock().await.clone();
+        let response = request(token.as_deref()).send().await?;
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        let challenge = response
+            .headers()
+            .get(reqwest::header::WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(challenge_params);
+        let Some(token) = self.fetch_token(challenge).await else {
+            return Ok(response);
+        };
+        *self.token.lock().await = Some(token.clone());
+        request(Some(&token)).send().await
+    }
+
+    async fn fetch_token(&self, challenge: Option<HashMap<String, String>>) -> Option<String> {
+        let challenge = challenge?;
+        let query: Vec<(&str, &str)> = ["service", "scope"]
+            .iter()
+            .filter_map(|key| Some((*key, challenge.get(*key)?.as_str())))
+            .collect();
+        let mut request = self.client.get(challenge.get("realm")?).query(&query);
+        if let Some((username, password)) = &self.credentials {
+            request = request.basic_auth(username, Some(password));
+        }
+        let body: Value = match request.send().await.and_then(|r| r.error_for_status()) {
+            Ok(response) => response.json().await.ok()?,
+            Err(e) => {
+                log::warn!("Registry token request failed: {}", e);
+                return None;
+            }
+        };
+        body["token"].as_str().or(body["access_token"].as_str()).map(str::to_string)
+    }
+}
+
+// Why a transfer stopped before the blob was complete.
+enum Interrupted {
+    Paused,
+    OutsideWindow,
+    Cancelled,
+}
+
+// Blocks while the job is paused or outside the window; Err once cancelled.
+async fn wait_until_allowed(app: &AppHandle, id: &str, control: &JobControl) -> Result<(), String> {
+    loop {
+        if control.cancelled.load(Ordering::SeqCst) {
+            return Err("Cancelled".to_string());
+        }
+        if control.paused.load(Ordering::SeqCst) {
+            update(app, id, |job| job.status = JobStatus::Paused);
+            control.wake.notified().await;
+            continue;
+        }
+        if !in_window(settings(app).window.as_ref()) {
+            update(app, id, |job| job.status = JobStatus::Waiting);
+            tokio::select! {
+                _ = control.wake.notified() => {}
+                _ = tokio::time::sleep(WINDOW_POLL) => {}
+            }
+            continue;
+        }
+        update(app, id, |job| job.status = JobStatus::Downloading);
+        return Ok(());
+    }
+}
+
+// Appends to `partial` from where it ends, at the configured rate, until
+// the blob is complete or the job has to stop.
+async fn transfer(
+    app: &AppHandle,
+    id: &str,
+    control: &JobControl,
+    fetcher: &Fetcher,
+    url: &str,
+    partial: &Path,
+    done_before: u64,
+) -> Result<Option<Interrupted>, String> {
+    let offset = std::fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    let response = fetcher
+        .get(url, (reqwest::header::RANGE, format!("bytes={}-", offset)))
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    // Everything's already here; verification decides whether it's right.
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        return Ok(None);
+    }
+    let response = response.error_for_status().map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    // A server that ignores Range sends the whole blob again.
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(partial)
+        .await
+        .map_err(|e| format!("Failed to open {:?}: {}", partial, e))?;
+    let mut written = if resumed { offset } else { 0 };
+
+    let started = Instant::now();
+    let mut sent_since_start = 0u64;
+    let mut last_progress = Instant::now();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Download broke off: {}", e))?;
+        file.write_all(&chunk).await.map_err(|e| format!("Failed to write {:?}: {}", partial, e))?;
+        written += chunk.len() as u64;
+        sent_since_start += chunk.len() as u64;
+
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            update(app, id, |job| job.completed = done_before + written);
+        }
+        if control.cancelled.load(Ordering::SeqCst) {
+            return Ok(Some(Interrupted::Cancelled));
+        }
+        if control.paused.load(Ordering::SeqCst) {
+            return Ok(Some(Interrupted::Paused));
+        }
+        let settings = settings(app);
+        if !in_window(settings.window.as_ref()) {
+            return Ok(Some(Interrupted::OutsideWindow));
+        }
+        // Sleep off whatever got ahead of the limit, read fresh so changes apply mid-download.
+        if let Some(kbps) = settings.max_kbps.filter(|kbps| *kbps > 0) {
+            let due = Duration::from_secs_f64(sent_since_start as f64 / (kbps as f64 * 1024.0));
+            if let Some(ahead) = due.checked_sub(started.elapsed()) {
+                tokio::time::sleep(ahead).await;
+            }
+        }
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    update(app, id, |job| job.completed = done_before + written);
+    Ok(None)
+}
+
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(format!("sha256:{}", hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect::<String>()))
+}
+
+async fn download_blob(
+    app: &AppHandle,
+    id: &str,
+    control: &JobControl,
+    fetcher: &Fetcher,
+    dir: &Path,
+    digest: &str,
+    done_before: u64,
+) -> Result<(), String> {
+    let url = fetcher.blob_url(digest);
+    let target = blob_path(dir, digest);
+    let partial = target.with_extension("partial");
+    loop {
+        wait_until_allowed(app, id, control).await?;
+        match transfer(app, id, control, fetcher, &url, &partial, done_before).await? {
+            None => break,
+            Some(Interrupted::Cancelled) => return Err("Cancelled".to_string()),
+            Some(Interrupted::Paused | Interrupted::OutsideWindow) => continue,
+        }
+    }
+
+    update(app, id, |job| job.status = JobStatus::Verifying);
+    let hashed = partial.clone();
+    let actual = tokio::task::spawn_blocking(move || sha256_file(&hashed))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to read {:?}: {}", partial, e))?;
+    if actual != digest {
+        let _ = std::fs::remove_file(&partial);
+        return Err(format!("{} failed verification (got {})", digest, actual));
+    }
+    std::fs::rename(&partial, &target).map_err(|e| format!("Failed to move {:?} into place: {}", target, e))
+}
+
+async fn run(
+    app: &AppHandle,
+    id: &str,
+    control: &JobControl,
+    model: &ModelRef,
+    source: &Source,
+) -> Result<(), String> {
+    let dir = models_dir(app).ok_or("No Ollama model directory; set one in the download settings")?;
+    let fetcher = Fetcher::new(app, source, model)?;
+    let manifest_bytes = fetcher
+        .get(&fetcher.manifest_url(), (reqwest::header::ACCEPT, MANIFEST_ACCEPT.to_string()))
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Couldn't get the manifest for {}: {}", model.name(), e))?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?;
+    let manifest: Value = serde_json::from_slice(&manifest_bytes).map_err(|e| format!("Bad manifest: {}", e))?;
+    let blobs = layers(&manifest);
+    if blobs.is_empty() || blobs.iter().any(|(digest, _)| !valid_digest(digest)) {
+        return Err(format!("The manifest for {} has missing or invalid digests", model.name()));
+    }
+
+    std::fs::create_dir_all(dir.join("blobs")).map_err(|e| e.to_string())?;
+    let total: u64 = blobs.iter().map(|(_, size)| size).sum();
+    update(app, id, |job| job.total = total);
+    log::info!("Downloading {} ({} bytes in {} blobs)", model.name(), total, blobs.len());
+
+    let mut done = 0;
+    for (digest, size) in &blobs {
+        if !blob_path(&dir, digest).exists() {
+            download_blob(app, id, control, &fetcher, &dir, digest, done).await?;
+        }
+        done += size;
+        update(app, id, |job| job.completed = done);
+    }
+
+    let manifest_file = dir.join("manifests").join(model.manifest_path());
+    if let Some(parent) = manifest_file.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&manifest_file, &manifest_bytes)
+        .map_err(|e| format!("Failed to write {:?}: {}", manifest_file, e))
+}
+
+// What a download needs to be picked up again after a restart (jobs.rs).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingDownload {
+    pub model: String,
+    // None for the registry.
+    pub peer: Option<String>,
+}
+
+// Downloads `model` as a managed job and waits for it to finish.
+pub async fn pull(app: &AppHandle, model: &str, source: Source) -> Result<String, String> {
+    pull_as(app, uuid::Uuid::new_v4().to_string(), model, source).await
+}
+
+// Continues a download interrupted by a restart, under its old id.
+pub fn resume(app: &AppHandle, id: &str, pending: PendingDownload) -> Result<(), String> {
+    ModelRef::parse(&pending.model)?;
+    let (app, id) = (app.clone(), id.to_string());
+    tauri::async_runtime::spawn(async move {
+        let source = pending.peer.map_or(Source::Registry, Source::Peer);
+        let _ = pull_as(&app, id, &pending.model, source).await;
+    });
+    Ok(())
+}
+
+async fn pull_as(app: &AppHandle, id: String, model: &str, source: Source) -> Result<String, String> {
+    let model = ModelRef::parse(model)?;
+    let pending = PendingDownload {
+        model: model.name(),
+        peer: match &source {
+            Source::Registry => None,
+            Source::Peer(peer) => Some(peer.clone()),
+        },
+    };
+    jobs::persist(app, jobs::KIND_DOWNLOAD, &id, &pending);
+    let control = Arc::new(JobControl::default());
+    let state = app.state::<DownloadState>();
+    state.controls.lock().unwrap().insert(id.clone(), control.clone());
+    state.jobs.lock().unwrap().insert(
+        id.clone(),
+        DownloadJob {
+            id: id.clone(),
+            model: model.name(),
+            source: match &source {
+                Source::Registry => settings(app).registry.resolve(&model).registry,
+                Source::Peer(peer) => peer.clone(),
+            },
+            status: JobStatus::Queued,
+            completed: 0,
+            total: 0,
+            error: None,
+        },
+    );
+
+    let result = run(app, &id, &control, &model, &source).await;
+    state.controls.lock().unwrap().remove(&id);
+    jobs::complete(app, &id);
+    match &result {
+        Ok(()) => {
+            log::info!("Downloaded {}", model.name());
+            update(app, &id, |job| job.status = JobStatus::Done);
+            agent_tests::after_model_update(app, &model.name());
+        }
+        Err(e) if control.cancelled.load(Ordering::SeqCst) => {
+            log::info!("Download of {} cancelled", model.name());
+            update(app, &id, |job| {
+                job.status = JobStatus::Cancelled;
+                job.error = Some(e.clone());
+            });
+        }
+        Err(e) => {
+            log::error!("Download of {} failed: {}", model.name(), e);
+            update(app, &id, |job| {
+                job.status = JobStatus::Failed;
+                job.error = Some(e.clone());
+            });
+        }
+    }
+    result.map(|()| id)
+}
+
+fn control(state: &DownloadState, id: &str) -> Result<Arc<JobControl>, String> {
+    state.controls.lock().unwrap().get(id).cloned().ok_or_else(|| format!("No running download '{}'", id))
+}
+
+#[tauri::command]
+pub fn get_download_settings(app: AppHandle) -> DownloadSettings {
+    settings(&app)
+}
+
+// `password` is the registry's, only written to the keyring; omit it to
+// keep the stored one.
+#[tauri::command]
+pub fn set_download_settings(
+    app: AppHandle,
+    settings: DownloadSettings,
+    password: Option<String>,
+    state: State<'_, DownloadState>,
+) -> Result<(), String> {
+    if let Some(window) = &settings.window {
+        if parse_time(&window.start).is_none() || parse_time(&window.end).is_none() {
+            return Err("The download window needs HH:MM times".to_string());
+        }
+    }
+    if let Some(mirror) = settings.registry.mirror() {
+        if mirror.contains('/') {
+            return Err("The registry mirror is a host[:port], without a scheme or path".to_string());
+        }
+    }
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        let key = settings.registry.secret_key().ok_or("A registry password needs a username")?;
+        secrets::set(&key, &password)?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    // Jobs waiting for the window re-check it now.
+    for control in state.controls.lock().unwrap().values() {
+        control.wake.notify_one();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_downloads(state: State<'_, DownloadState>) -> Vec<DownloadJob> {
+    state.jobs.lock().unwrap().values().cloned().collect()
+}
+
+// Starts a download from the Ollama registry and returns right away; follow
+// it with "model-download-progress" or `list_downloads`.
+#[tauri::command]
+pub fn download_model(app: AppHandle, model: String) -> Result<(), String> {
+    ModelRef::parse(&model)?;
+    tauri::async_runtime::spawn(async move {
+        let _ = pull(&app, &model, Source::Registry).await;
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn pause_download(id: String, state: State<'_, DownloadState>) -> Result<(), String> {
+    control(&state, &id)?.paused.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resume_download(id: String, state: State<'_, DownloadState>) -> Result<(), String> {
+    let control = control(&state, &id)?;
+    control.paused.store(false, Ordering::SeqCst);
+    control.wake.notify_one();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn cancel_download(id: String, state: State<'_, DownloadState>) -> Result<(), String> {
+    let control = control(&state, &id)?;
+    control.cancelled.store(true, Ordering::SeqCst);
+    control.wake.notify_one();
+    Ok(())
+}
+
+// Forgets finished, failed and cancelled jobs.
+#[tauri::command]
+pub fn clear_finished_downloads(state: State<'_, DownloadState>) {
+    let controls = state.controls.lock().unwrap();
+    state.jobs.lock().unwrap().retain(|id, _| controls.contains_key(id));
+}
@@ -0,0 +1,219 @@
+// In src-tauri/src/model_share.rs
+//
+// Sharing downloaded Ollama models between machines on the LAN, so a 40 GB
+// model is fetched from the internet once. With sharing on, the embedded
+// server exposes the local Ollama model store read-only:
+//
+//   GET /share/models                     models with their manifests
+//   GET /share/manifests/*model           one manifest, e.g. llama3:8b
+//   GET /share/blobs/:digest              a layer, e.g. sha256:abc...
+//
+// Another instance pulls with `pull_shared_model(peer, model)`, a managed
+// download (see model_manager.rs) that fetches only the blobs it lacks and
+// verifies each against its sha256 digest.
+//
+// The server only listens on loopback unless [server] bind says otherwise
+// (see config.rs), so sharing across machines also needs a LAN address.
+
+use axum::{
+    body::Body,
+    extract::{Path as AxumPath, State as AxumState},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::model_manager::{self, blob_path, layers, valid_digest, ModelRef, Source};
+use crate::{llm, storage, AppState};
+
+const SETTINGS_FILE: &str = "model_share.json";
+const CHUNK_SIZE: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelShareSettings {
+    // Serve this machine's models to peers.
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedModel {
+    pub name: String,
+    pub size: u64,
+    pub manifest: Value,
+}
+
+fn list_local(dir: &Path) -> Vec<SharedModel> {
+    let root = dir.join("manifests");
+    let mut files = Vec::new();
+    let mut pending = vec![root.clone()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    let mut models: Vec<SharedModel> = files
+        .into_iter()
+        .filter_map(|path| {
+            let name = ModelRef::from_manifest_path(path.strip_prefix(&root).ok()?)?.name();
+            let manifest: Value = serde_json::from_slice(&std::fs::read(&path).ok()?).ok()?;
+            let size = layers(&manifest).iter().map(|(_, size)| size).sum();
+            Some(SharedModel { name, size, manifest })
+        })
+        .collect();
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    models
+}
+
+fn settings(app: &AppHandle) -> ModelShareSettings {
+    storage::load_json(app, SETTINGS_FILE)
+}
+
+// The model store, if sharing is on.
+fn shared_dir(app: &AppHandle) -> Result<PathBuf, Response> {
+    let settings = settings(app);
+    if !settings.enabled {
+        return Err((StatusCode::FORBIDDEN, "Model sharing is off on this machine").into_response());
+    }
+    model_manager::models_dir(app).ok_or_else(|| (StatusCode::NOT_FOUND, "No Ollama model directory").into_response())
+}
+
+pub async fn models_handler(AxumState(state): AxumState<AppState>) -> Response {
+    match shared_dir(&state.app_handle) {
+        Ok(dir) => Json(list_local(&dir)).into_response(),
+        Err(response) => response,
+    }
+}
+
+pub async fn manifest_handler(AxumState(state): AxumState<AppState>, AxumPath(model): AxumPath<String>) -> Response {
+    let dir = match shared_dir(&state.app_handle) {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    let path = match ModelRef::parse(&model) {
+        Ok(model) => dir.join("manifests").join(model.manifest_path()),
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    match std::fs::read(&path) {
+        Ok(bytes) => ([(header::CONTENT_TYPE, "application/json")], bytes).into_response(),
+        Err(_) => (StatusCode::NOT_FOUND, format!("No model '{}' here", model)).into_response(),
+    }
+}
+
+// "bytes=<start>-", the only form resumed downloads send.
+fn range_start(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(header::RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes=")?
+        .strip_suffix('-')?
+        .parse()
+        .ok()
+}
+
+pub async fn blob_handler(
+    AxumState(state): AxumState<AppState>,
+    AxumPath(digest): AxumPath<String>,
+    headers: HeaderMap,
+) -> Response {
+    let dir = match shared_dir(&state.app_handle) {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    if !valid_digest(&digest) {
+        return (StatusCode::BAD_REQUEST, "Not a sha256 digest").into_response();
+    }
+    let mut file = match tokio::fs::File::open(blob_path(&dir, &digest)).await {
+        Ok(file) => file,
+        Err(_) => return (StatusCode::NOT_FOUND, "No such blob").into_response(),
+    };
+    let size = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+    let start = range_start(&headers).unwrap_or(0);
+    if start > size {
+        return StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+    }
+    if start > 0 {
+        if let Err(e) = file.seek(SeekFrom::Start(start)).await {
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    }
+    log::info!("Sharing blob {} ({} bytes from {})", digest, size - start, start);
+    let body = async_stream::stream! {
+        let mut buffer = vec![0; CHUNK_SIZE];
+        loop {
+            match file.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(n) => yield Ok(buffer[..n].to_vec()),
+                Err(e) => {
+                    yield Err(e);
+                    break;
+                }
+            }
+        }
+    };
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_LENGTH, size - start)
+        .header(header::ACCEPT_RANGES, "bytes");
+    let builder = if start > 0 {
+        builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, size.saturating_sub(1), size))
+    } else {
+        builder
+    };
+    builder.body(Body::from_stream(body)).unwrap()
+}
+
+#[tauri::command]
+pub fn get_model_share_settings(app: AppHandle) -> ModelShareSettings {
+    settings(&app)
+}
+
+#[tauri::command]
+pub fn set_model_share_settings(app: AppHandle, settings: ModelShareSettings) -> Result<(), String> {
+    log::info!("Model sharing {}", if settings.enabled { "enabled" } else { "disabled" });
+    storage::save_json(&app, SETTINGS_FILE, &settings)
+}
+
+#[tauri::command]
+pub async fn list_peer_models(peer: String) -> Result<Vec<SharedModel>, String> {
+    llm::client()
+        .get(format!("{}/share/models", peer.trim_end_matches('/')))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Couldn't list {}'s models: {}", peer, e))?
+        .json()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Copies `model` from `peer` as a managed download (see model_manager.rs),
+// so it's throttled, pausable and verified like any other.
+#[tauri::command]
+pub async fn pull_shared_model(app: AppHandle, peer: String, model: String) -> Result<(), String> {
+    let peer = peer.trim_end_matches('/').to_string();
+    log::info!("Pulling {} from {}", model, peer);
+    model_manager::pull(&app, &model, Source::Peer(peer)).await.map(|_| ())
+}
+
+// For the settings page: what this machine would share.
+#[tauri::command]
+pub fn list_local_models(app: AppHandle) -> Result<Vec<SharedModel>, String> {
+    let dir = model_manager::models_dir(&app).ok_or("No Ollama model directory found")?;
+    Ok(list_local(&dir))
+}
+
@@ -0,0 +1,174 @@
+// In src-tauri/src/moderation.rs
+//
+// Moderation of high-impact tool calls (writing files, posting comments and
+// anything else listed in `policies`) before they run. Each listed tool has a
+// policy:
+//   - auto_approve: runs, unless the critic objects,
+//   - require_approval: the user decides, as for shell commands: the call is
+//     sent to the launcher window as "action-approval-requested", which is
+//     brought forward to ask, and waits for `respond_action_approval` or the
+//     timeout,
+//   - deny: never runs.
+// With `critic_model` set, a second model reviews every call that isn't
+// denied outright; an objection sends an auto-approved call to the user and
+// is shown next to calls that need approval anyway. Tools not listed run as
+// before. Every decision goes to the audit log.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::oneshot;
+
+use crate::{audit, deep_link, llm, storage, summary};
+
+const SETTINGS_FILE: &str = "moderation.json";
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+const MAX_ARGUMENT_CHARS: usize = 2000;
+
+const CRITIC_PROMPT: &str = "You review actions an AI agent wants to take on the user's computer before they \
+run. You receive the tool name and its arguments. Answer OK if the action looks safe and proportionate, or \
+CONCERN: followed by one sentence explaining what could go wrong.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionPolicy {
+    AutoApprove,
+    RequireApproval,
+    Deny,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModerationSettings {
+    // Tool name -> policy, for the tools that are moderated.
+    pub policies: BTreeMap<String, ActionPolicy>,
+    pub critic_model: Option<String>,
+}
+
+impl Default for ModerationSettings {
+    fn default() -> Self {
+        let policies = ["write_file", "github_post_comment"]
+            .into_iter()
+            .map(|tool| (tool.to_string(), ActionPolicy::AutoApprove))
+            .collect();
+        Self { policies, critic_model: None }
+    }
+}
+
+#[derive(Default)]
+pub struct ModerationState {
+    settings: Mutex<ModerationSettings>,
+    pending: Mutex<HashMap<String, oneshot::Sender<bool>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionApprovalRequest {
+    pub id: String,
+    pub agent_id: Option<String>,
+    pub tool: String,
+    pub arguments: Value,
+    // The critic's objection, if it had one.
+    pub concern: Option<String>,
+}
+
+// The critic's objection to the call, if any.
+async fn critique(app: &AppHandle, model: &str, tool: &str, arguments: &str) -> Option<String> {
+    let prompt = format!("Tool: {}\nArguments: {}", tool, arguments);
+    match llm::generate(app, model, CRITIC_PROMPT, &prompt).await {
+        Ok(answer) if answer.trim().to_uppercase().starts_with("OK") => None,
+        Ok(answer) => {
+            let answer = answer.trim();
+            Some(answer.strip_prefix("CONCERN:").unwrap_or(answer).trim().to_string())
+        }
+        // Without a review the user decides.
+        Err(e) => Some(format!("The critic could not review this action: {}", e)),
+    }
+}
+
+async fn request_approval(app: &AppHandle, request: &ActionApprovalRequest) -> Result<bool, String> {
+    let state = app.state::<ModerationState>();
+    let (tx, rx) = oneshot::channel();
+    state.pending.lock().unwrap().insert(request.id.clone(), tx);
+    app.emit("action-approval-requested", request).map_err(|e| e.to_string())?;
+    deep_link::show_main_window(app);
+
+    let answer = tokio::time::timeout(APPROVAL_TIMEOUT, rx).await;
+    state.pending.lock().unwrap().remove(&request.id);
+    // A timeout or a dropped request counts as a denial.
+    Ok(matches!(answer, Ok(Ok(true))))
+}
+
+// Ok when `tool` may run with `arguments`; Err with the reason otherwise.
+pub async fn review(app: &AppHandle, agent_id: Option<&str>, tool: &str, arguments: &Value) -> Result<(), String> {
+    let settings = app.state::<ModerationState>().settings.lock().unwrap().clone();
+    let Some(&policy) = settings.policies.get(tool) else {
+        return Ok(());
+    };
+    let logged_arguments = summary::truncate(&arguments.to_string(), MAX_ARGUMENT_CHARS);
+    let mut detail = json!({ "tool": tool, "arguments": logged_arguments, "policy": policy });
+    if policy == ActionPolicy::Deny {
+        audit::record(app, agent_id, "action.denied", detail);
+        return Err(format!("{} is not allowed for agents", tool));
+    }
+
+    let concern = match settings.critic_model.as_deref() {
+        Some(model) => critique(app, model, tool, &logged_arguments).await,
+        None => None,
+    };
+    detail["concern"] = json!(concern);
+    if policy == ActionPolicy::AutoApprove && concern.is_none() {
+        audit::record(app, agent_id, "action.approved", detail);
+        return Ok(());
+    }
+
+    let request = ActionApprovalRequest {
+        id: uuid::Uuid::new_v4().to_string(),
+        agent_id: agent_id.map(str::to_string),
+        tool: tool.to_string(),
+        arguments: arguments.clone(),
+        concern,
+    };
+    log::info!("Agent {:?} asks to run {}, waiting for the user", agent_id, tool);
+    detail["by"] = json!("user");
+    if !request_approval(app, &request).await? {
+        audit::record(app, agent_id, "action.denied", detail);
+        return Err(format!("The user did not approve this {} call", tool));
+    }
+    audit::record(app, agent_id, "action.approved", detail);
+    Ok(())
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<ModerationState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_moderation_settings(state: State<'_, ModerationState>) -> ModerationSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_moderation_settings(
+    app: AppHandle,
+    settings: ModerationSettings,
+    state: State<'_, ModerationState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn respond_action_approval(id: String, approved: bool, state: State<'_, ModerationState>) -> Result<(), String> {
+    let sender = state
+        .pending
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| "Approval request not found or expired".to_string())?;
+    let _ = sender.send(approved);
+    Ok(())
+}
@@ -0,0 +1,260 @@
+// In src-tauri/src/mqtt.rs
+//
+// MQTT output for Home Assistant and other home automation. While enabled we
+// keep a connection to the configured broker and publish retained states
+// under `<base_topic>/`:
+//   - `user_focused` and `meeting_in_progress` ("ON"/"OFF") when they change,
+//   - `daily_summary` whenever a summary is generated,
+//   - anything agents send with `mqtt_publish`.
+// With `discovery` on, Home Assistant discovery configs are published so the
+// states show up as entities without YAML. Messages on `subscriptions` are
+// forwarded to the frontend as "mqtt-message" to trigger agents.
+
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+
+use crate::focus::{self, FocusState};
+use crate::{calendar, secrets, storage};
+
+const SETTINGS_FILE: &str = "mqtt.json";
+const STATE_INTERVAL: Duration = Duration::from_secs(15);
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub client_id: String,
+    pub base_topic: String,
+    pub discovery: bool,
+    pub subscriptions: Vec<String>,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 1883,
+            username: String::new(),
+            client_id: "observer-ai".to_string(),
+            base_topic: "observer".to_string(),
+            discovery: true,
+            subscriptions: Vec::new(),
+        }
+    }
+}
+
+impl MqttSettings {
+    fn secret_key(&self) -> String {
+        format!("mqtt:{}@{}", self.username, self.host)
+    }
+
+    fn topic(&self, name: &str) -> String {
+        format!("{}/{}", self.base_topic.trim_end_matches('/'), name)
+    }
+}
+
+#[derive(Default)]
+pub struct MqttBridge {
+    settings: Mutex<MqttSettings>,
+    client: Mutex<Option<AsyncClient>>,
+    connected: AtomicBool,
+    // Bumped on every settings change so the old connection task exits.
+    generation: AtomicU64,
+    // Last published value per topic, to only publish changes.
+    published: Mutex<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MqttStatus {
+    pub enabled: bool,
+    pub connected: bool,
+}
+
+fn publish(app: &AppHandle, name: &str, payload: &str, retain: bool) -> Result<(), String> {
+    let bridge = app.state::<MqttBridge>();
+    let topic = bridge.settings.lock().unwrap().topic(name);
+    let client = bridge.client.lock().unwrap().clone().ok_or("MQTT is not connected")?;
+    client
+        .try_publish(&topic, QoS::AtLeastOnce, retain, payload.as_bytes().to_vec())
+        .map_err(|e| format!("Failed to publish to {}: {}", topic, e))?;
+    bridge.published.lock().unwrap().insert(topic, payload.to_string());
+    Ok(())
+}
+
+fn publish_if_changed(app: &AppHandle, name: &str, payload: &str) {
+    let bridge = app.state::<MqttBridge>();
+    let topic = bridge.settings.lock().unwrap().topic(name);
+    if bridge.published.lock().unwrap().get(&topic).map(String::as_str) == Some(payload) {
+        return;
+    }
+    if let Err(e) = publish(app, name, payload, true) {
+        log::debug!("{}", e);
+    }
+}
+
+fn publish_discovery(app: &AppHandle, settings: &MqttSettings) {
+    let device = json!({ "identifiers": [settings.client_id], "name": "Observer AI" });
+    let entities = [
+        ("binary_sensor", "user_focused", "User focused"),
+        ("binary_sensor", "meeting_in_progress", "Meeting in progress"),
+        ("sensor", "daily_summary", "Daily summary"),
+    ];
+    let bridge = app.state::<MqttBridge>();
+    let Some(client) = bridge.client.lock().unwrap().clone() else {
+        return;
+    };
+    for (component, object_id, name) in entities {
+        let config = json!({
+            "name": name,
+            "unique_id": format!("{}_{}", settings.client_id, object_id),
+            "state_topic": settings.topic(object_id),
+            "device": device,
+        });
+        let topic = format!("homeassistant/{}/{}/{}/config", component, settings.client_id, object_id);
+        if let Err(e) = client.try_publish(topic, QoS::AtLeastOnce, true, config.to_string().into_bytes()) {
+            log::warn!("Failed to publish Home Assistant discovery for {}: {}", object_id, e);
+        }
+    }
+}
+
+fn publish_states(app: &AppHandle) {
+    let focused = focus::current_reason(&app.state::<FocusState>()).is_some();
+    let in_meeting = calendar::current_meeting(app).is_some();
+    publish_if_changed(app, "user_focused", if focused { "ON" } else { "OFF" });
+    publish_if_changed(app, "meeting_in_progress", if in_meeting { "ON" } else { "OFF" });
+}
+
+async fn run_connection(app: AppHandle, settings: MqttSettings, generation: u64) {
+    let bridge = app.state::<MqttBridge>();
+    let mut options = MqttOptions::new(&settings.client_id, &settings.host, settings.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if !settings.username.is_empty() {
+        let password = secrets::get(&settings.secret_key()).ok().flatten().unwrap_or_default();
+        options.set_credentials(&settings.username, password);
+    }
+
+    let (client, mut eventloop) = AsyncClient::new(options, 64);
+    *bridge.client.lock().unwrap() = Some(client.clone());
+    let mut ticker = tokio::time::interval(STATE_INTERVAL);
+
+    while bridge.generation.load(Ordering::SeqCst) == generation {
+        tokio::select! {
+            event = eventloop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    log::info!("Connected to MQTT broker {}:{}", settings.host, settings.port);
+                    bridge.connected.store(true, Ordering::SeqCst);
+                    // Retained states must be re-sent after a reconnect.
+                    bridge.published.lock().unwrap().clear();
+                    for topic in &settings.subscriptions {
+                        if let Err(e) = client.subscribe(topic, QoS::AtLeastOnce).await {
+                            log::warn!("Failed to subscribe to {}: {}", topic, e);
+                        }
+                    }
+                    if settings.discovery {
+                        publish_discovery(&app, &settings);
+                    }
+                    publish_states(&app);
+                }
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    let payload = String::from_utf8_lossy(&message.payload).to_string();
+                    let event = json!({ "topic": message.topic, "payload": payload });
+                    if let Err(e) = app.emit("mqtt-message", event) {
+                        log::error!("Failed to emit mqtt-message event: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    if bridge.connected.swap(false, Ordering::SeqCst) {
+                        log::warn!("MQTT connection lost: {}", e);
+                    } else {
+                        log::debug!("MQTT connection failed: {}", e);
+                    }
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            },
+            _ = ticker.tick() => publish_states(&app),
+        }
+    }
+
+    let _ = client.disconnect().await;
+    log::info!("MQTT connection to {} closed", settings.host);
+}
+
+// (Re)connects with the current settings, replacing any running connection.
+fn restart(app: &AppHandle) {
+    let bridge = app.state::<MqttBridge>();
+    let generation = bridge.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    *bridge.client.lock().unwrap() = None;
+    bridge.connected.store(false, Ordering::SeqCst);
+
+    let settings = bridge.settings.lock().unwrap().clone();
+    if settings.enabled && !settings.host.is_empty() {
+        tauri::async_runtime::spawn(run_connection(app.clone(), settings, generation));
+    }
+}
+
+pub fn start(app: AppHandle) {
+    *app.state::<MqttBridge>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    let handle = app.clone();
+    app.listen_any("summary-generated", move |event| {
+        let text = serde_json::from_str::<Value>(event.payload())
+            .ok()
+            .and_then(|summary| summary["text"].as_str().map(str::to_string));
+        if let Some(text) = text {
+            // Home Assistant caps sensor states at 255 characters.
+            let state: String = text.chars().take(255).collect();
+            if let Err(e) = publish(&handle, "daily_summary", &state, true) {
+                log::debug!("{}", e);
+            }
+        }
+    });
+
+    restart(&app);
+}
+
+#[tauri::command]
+pub fn get_mqtt_settings(bridge: State<'_, MqttBridge>) -> MqttSettings {
+    bridge.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_mqtt_settings(
+    app: AppHandle,
+    settings: MqttSettings,
+    password: Option<String>,
+    bridge: State<'_, MqttBridge>,
+) -> Result<(), String> {
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        secrets::set(&settings.secret_key(), &password)?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *bridge.settings.lock().unwrap() = settings;
+    restart(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_mqtt_status(bridge: State<'_, MqttBridge>) -> MqttStatus {
+    MqttStatus {
+        enabled: bridge.settings.lock().unwrap().enabled,
+        connected: bridge.connected.load(Ordering::SeqCst),
+    }
+}
+
+// `topic` is relative to the base topic, e.g. "agents/focus_tracker/state".
+#[tauri::command]
+pub fn mqtt_publish(app: AppHandle, topic: String, payload: String, retain: Option<bool>) -> Result<(), String> {
+    publish(&app, &topic, &payload, retain.unwrap_or(false))
+}
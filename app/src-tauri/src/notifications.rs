@@ -0,0 +1,78 @@
+// In src-tauri/src/notifications.rs
+//
+// Native notifications sent on behalf of agents. Everything goes through
+// `notify` so focus mode (see focus.rs) can hold alerts back and deliver them
+// once the user is available again, and offline mode (see offline.rs) can
+// hold them until the backend is back. The web app's agents reach it through
+// `POST /notify`.
+
+use axum::{extract::State as AxumState, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{focus, offline, AppState};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+// Shows the alert right away, ignoring focus mode.
+pub fn deliver(app: &AppHandle, alert: &Alert) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title(&alert.title)
+        .body(&alert.body)
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))
+}
+
+// Returns true when the alert was shown, false when offline or focus mode
+// held it back.
+pub fn notify(app: &AppHandle, alert: Alert) -> Result<bool, String> {
+    if offline::hold_if_offline(app, &alert) {
+        log::info!("Offline, held alert '{}'", alert.title);
+        return Ok(false);
+    }
+    if focus::queue_if_focused(app, &alert) {
+        log::info!("Focus mode active, queued alert '{}'", alert.title);
+        return Ok(false);
+    }
+    deliver(app, &alert)?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn send_notification(
+    app: AppHandle,
+    title: String,
+    body: String,
+    agent_id: Option<String>,
+) -> Result<bool, String> {
+    notify(
+        &app,
+        Alert {
+            title,
+            body,
+            agent_id,
+            created_at: Utc::now(),
+        },
+    )
+}
+
+pub async fn notify_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(alert): Json<Alert>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    notify(&state.app_handle, alert)
+        .map(|shown| Json(json!({ "shown": shown })))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
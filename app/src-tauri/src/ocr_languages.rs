@@ -0,0 +1,280 @@
+// In src-tauri/src/ocr_languages.rs
+//
+// OCR language packs. Screen OCR runs in the frontend with Tesseract.js,
+// which fetches `<code>.traineddata.gz` for each language it's asked for.
+// Packs downloaded here are kept in `tessdata/` under the app data
+// directory (shared by all profiles) and served at `/ocr/tessdata/<file>`,
+// so non-English screens work offline and without a third-party fetch.
+//
+// `languages` are the default ones; an agent can have its own, e.g. `jpn`
+// for an agent that watches a Japanese game. With `auto_detect` on, a
+// result below the confidence threshold is retried with every installed
+// pack. `/ocr/languages?agent_id=` tells the frontend which languages to use
+// and where to load them from.
+
+use axum::{
+    body::Body,
+    extract::{Path as AxumPath, Query, State as AxumState},
+    http::{header, StatusCode},
+    response::Response,
+    Json,
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::AsyncWriteExt;
+
+use crate::{llm, storage, AppState};
+
+const SETTINGS_FILE: &str = "ocr.json";
+const TESSDATA_DIR: &str = "tessdata";
+const PACK_SUFFIX: &str = ".traineddata.gz";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+// More languages at once slow Tesseract down a lot.
+const MAX_FALLBACK_LANGUAGES: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrSettings {
+    // Tesseract codes, e.g. "eng", "deu", "chi_sim".
+    pub languages: Vec<String>,
+    // Agent id -> languages for that agent.
+    pub agents: BTreeMap<String, Vec<String>>,
+    pub auto_detect: bool,
+    // Where packs are downloaded from.
+    pub source_url: String,
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            languages: vec!["eng".to_string()],
+            agents: BTreeMap::new(),
+            auto_detect: true,
+            source_url: "https://tessdata.projectnaptha.com/4.0.0".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct OcrState {
+    settings: Mutex<OcrSettings>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguagePack {
+    pub code: String,
+    pub installed: bool,
+    pub size_bytes: Option<u64>,
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrPlan {
+    // Joined with '+', as Tesseract takes them.
+    pub languages: String,
+    // Tried when the result isn't confident enough.
+    pub fallback: Option<String>,
+    // Whether every language is installed here; otherwise load from `source_url`.
+    pub local: bool,
+    pub source_url: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PlanParams {
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DownloadProgress {
+    code: String,
+    completed: u64,
+    total: Option<u64>,
+    done: bool,
+}
+
+fn valid_code(code: &str) -> bool {
+    !code.is_empty() && code.len() <= 20 && code.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+}
+
+fn tessdata_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = storage::root_dir(app)?.join(TESSDATA_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir)
+}
+
+fn installed(app: &AppHandle) -> Vec<(String, u64)> {
+    let Ok(entries) = tessdata_dir(app).and_then(|dir| std::fs::read_dir(dir).map_err(|e| e.to_string())) else {
+        return Vec::new();
+    };
+    let mut packs: Vec<(String, u64)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let code = name.strip_suffix(PACK_SUFFIX).filter(|code| valid_code(code))?.to_string();
+            Some((code, entry.metadata().map(|m| m.len()).unwrap_or(0)))
+        })
+        .collect();
+    packs.sort();
+    packs
+}
+
+fn settings(app: &AppHandle) -> OcrSettings {
+    app.state::<OcrState>().settings.lock().unwrap().clone()
+}
+
+pub fn plan(app: &AppHandle, agent_id: Option<&str>) -> OcrPlan {
+    let settings = settings(app);
+    let languages = agent_id
+        .and_then(|id| settings.agents.get(id))
+        .filter(|languages| !languages.is_empty())
+        .unwrap_or(&settings.languages)
+        .clone();
+    let installed: Vec<String> = installed(app).into_iter().map(|(code, _)| code).collect();
+    let local = languages.iter().all(|code| installed.contains(code));
+
+    let others: Vec<&String> = installed
+        .iter()
+        .filter(|code| !languages.contains(code))
+        .take(MAX_FALLBACK_LANGUAGES.saturating_sub(languages.len()))
+        .collect();
+    let fallback = (settings.auto_detect && !others.is_empty())
+        .then(|| languages.iter().chain(others).map(String::as_str).collect::<Vec<_>>().join("+"));
+    OcrPlan {
+        languages: languages.join("+"),
+        fallback,
+        local,
+        source_url: settings.source_url,
+    }
+}
+
+pub async fn plan_handler(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<PlanParams>,
+) -> Json<OcrPlan> {
+    Json(plan(&state.app_handle, params.agent_id.as_deref()))
+}
+
+pub async fn tessdata_handler(
+    AxumState(state): AxumState<AppState>,
+    AxumPath(file): AxumPath<String>,
+) -> Result<Response, StatusCode> {
+    if !file.strip_suffix(PACK_SUFFIX).is_some_and(valid_code) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let dir = tessdata_dir(&state.app_handle).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let bytes = tokio::fs::read(dir.join(&file)).await.map_err(|_| StatusCode::NOT_FOUND)?;
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/gzip")
+        .body(Body::from(bytes))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn emit_progress(app: &AppHandle, progress: DownloadProgress) {
+    if let Err(e) = app.emit("ocr-language-progress", progress) {
+        log::error!("Failed to emit ocr-language-progress event: {}", e);
+    }
+}
+
+async fn download(app: &AppHandle, code: &str) -> Result<u64, String> {
+    let url = format!("{}/{}{}", settings(app).source_url.trim_end_matches('/'), code, PACK_SUFFIX);
+    let response = llm::client()
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    let total = response.content_length();
+
+    let dir = tessdata_dir(app)?;
+    let partial = dir.join(format!("{}{}.part", code, PACK_SUFFIX));
+    let mut file =
+        tokio::fs::File::create(&partial).await.map_err(|e| format!("Failed to create {:?}: {}", partial, e))?;
+    let mut completed = 0u64;
+    let mut last_progress = Instant::now();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Download of {} broke off: {}", code, e))?;
+        file.write_all(&chunk).await.map_err(|e| format!("Failed to write {:?}: {}", partial, e))?;
+        completed += chunk.len() as u64;
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            emit_progress(app, DownloadProgress { code: code.to_string(), completed, total, done: false });
+        }
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    drop(file);
+    let path = dir.join(format!("{}{}", code, PACK_SUFFIX));
+    tokio::fs::rename(&partial, &path).await.map_err(|e| format!("Failed to move {:?}: {}", partial, e))?;
+    emit_progress(app, DownloadProgress { code: code.to_string(), completed, total, done: true });
+    log::info!("Installed OCR language {} ({} bytes)", code, completed);
+    Ok(completed)
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<OcrState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_ocr_settings(state: State<'_, OcrState>) -> OcrSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_ocr_settings(app: AppHandle, settings: OcrSettings, state: State<'_, OcrState>) -> Result<(), String> {
+    if settings.languages.is_empty() {
+        return Err("Select at least one OCR language".to_string());
+    }
+    let mut codes = settings.languages.iter().chain(settings.agents.values().flatten());
+    if let Some(code) = codes.find(|code| !valid_code(code)) {
+        return Err(format!("'{}' isn't a Tesseract language code", code));
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+// The installed and the selected languages; the frontend knows the full list.
+#[tauri::command]
+pub fn list_ocr_languages(app: AppHandle) -> Vec<LanguagePack> {
+    let settings = settings(&app);
+    let selected = |code: &str| {
+        settings.languages.iter().chain(settings.agents.values().flatten()).any(|selected| selected == code)
+    };
+    let mut packs: Vec<LanguagePack> = installed(&app)
+        .into_iter()
+        .map(|(code, size)| LanguagePack { selected: selected(&code), installed: true, size_bytes: Some(size), code })
+        .collect();
+    for code in settings.languages.iter().chain(settings.agents.values().flatten()) {
+        if !packs.iter().any(|pack| &pack.code == code) {
+            packs.push(LanguagePack { code: code.clone(), installed: false, size_bytes: None, selected: true });
+        }
+    }
+    packs
+}
+
+#[tauri::command]
+pub async fn download_ocr_language(app: AppHandle, code: String) -> Result<u64, String> {
+    if !valid_code(&code) {
+        return Err(format!("'{}' isn't a Tesseract language code", code));
+    }
+    download(&app, &code).await
+}
+
+#[tauri::command]
+pub fn remove_ocr_language(app: AppHandle, code: String) -> Result<(), String> {
+    if !valid_code(&code) {
+        return Err(format!("'{}' isn't a Tesseract language code", code));
+    }
+    let path = tessdata_dir(&app)?.join(format!("{}{}", code, PACK_SUFFIX));
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {:?}: {}", path, e))
+}
+
+#[tauri::command]
+pub fn get_ocr_plan(app: AppHandle, agent_id: Option<String>) -> OcrPlan {
+    plan(&app, agent_id.as_deref())
+}
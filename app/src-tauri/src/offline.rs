@@ -0,0 +1,243 @@
+// In src-tauri/src/offline.rs
+//
+// Explicit offline mode. The Ollama backend is probed every `probe_seconds`
+// (and right after a wake or network change, see wake.rs); when it stops
+// answering the app goes offline instead of letting every agent time out:
+//   - agent requests through the proxy are answered at once with a 503
+//     ("code": "offline") and the agent is queued, once however often it
+//     asked; agents in `urgent_agents` are never held back,
+//   - notifications from agents are held, as in focus mode,
+//   - the frontend is told whether to keep capturing locally
+//     (`keep_capturing`) while it skips the model calls.
+// When the backend answers again the held notifications are delivered and
+// "backend-online" names the queued agents, which the frontend runs once.
+// Both transitions go out as events and on `/system/events`.
+
+use axum::{
+    body::Body,
+    extract::State as AxumState,
+    http::{header, StatusCode},
+    response::Response,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::broadcast;
+
+use crate::notifications::{self, Alert};
+use crate::wake::{self, ChangeEvent, SystemChange};
+use crate::{llm, storage, AppState};
+
+const SETTINGS_FILE: &str = "offline.json";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_HELD_ALERTS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OfflineSettings {
+    pub enabled: bool,
+    pub probe_seconds: u64,
+    pub urgent_agents: Vec<String>,
+    pub keep_capturing: bool,
+    pub hold_notifications: bool,
+}
+
+impl Default for OfflineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            probe_seconds: 15,
+            urgent_agents: Vec::new(),
+            keep_capturing: false,
+            hold_notifications: true,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct OfflineState {
+    settings: Mutex<OfflineSettings>,
+    // When the backend stopped answering; None while online.
+    offline_since: Mutex<Option<DateTime<Utc>>>,
+    queued_agents: Mutex<BTreeSet<String>>,
+    held_alerts: Mutex<Vec<Alert>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectivityStatus {
+    pub online: bool,
+    pub offline_since: Option<DateTime<Utc>>,
+    pub keep_capturing: bool,
+    pub queued_agents: Vec<String>,
+    pub held_notifications: usize,
+}
+
+fn status(app: &AppHandle) -> ConnectivityStatus {
+    let state = app.state::<OfflineState>();
+    let offline_since = *state.offline_since.lock().unwrap();
+    ConnectivityStatus {
+        online: offline_since.is_none(),
+        offline_since,
+        keep_capturing: state.settings.lock().unwrap().keep_capturing,
+        queued_agents: state.queued_agents.lock().unwrap().iter().cloned().collect(),
+        held_notifications: state.held_alerts.lock().unwrap().len(),
+    }
+}
+
+fn is_urgent(settings: &OfflineSettings, agent_id: Option<&str>) -> bool {
+    agent_id.is_some_and(|id| settings.urgent_agents.iter().any(|urgent| urgent == id))
+}
+
+async fn probe(app: &AppHandle) -> bool {
+    let url = format!("{}/api/version", llm::ollama_base_url(app));
+    match llm::client().get(&url).timeout(PROBE_TIMEOUT).send().await {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+async fn go_offline(app: &AppHandle) {
+    let state = app.state::<OfflineState>();
+    *state.offline_since.lock().unwrap() = Some(Utc::now());
+    log::warn!("The Ollama backend at {} isn't answering, going offline", llm::ollama_base_url(app));
+    let keep_capturing = state.settings.lock().unwrap().keep_capturing;
+    wake::publish(app, SystemChange::BackendOffline { keep_capturing }).await;
+}
+
+async fn go_online(app: &AppHandle) {
+    let state = app.state::<OfflineState>();
+    let since = state.offline_since.lock().unwrap().take();
+    let queued_agents: Vec<String> = std::mem::take(&mut *state.queued_agents.lock().unwrap()).into_iter().collect();
+    let alerts = std::mem::take(&mut *state.held_alerts.lock().unwrap());
+    log::info!(
+        "The Ollama backend is back after {}s; {} queued agents, {} held notifications",
+        since.map(|since| (Utc::now() - since).num_seconds()).unwrap_or_default(),
+        queued_agents.len(),
+        alerts.len()
+    );
+    for alert in alerts {
+        if let Err(e) = notifications::notify(app, alert) {
+            log::error!("{}", e);
+        }
+    }
+    wake::publish(app, SystemChange::BackendOnline { queued_agents }).await;
+}
+
+// Whether the app is in offline mode.
+pub fn is_offline(app: &AppHandle) -> bool {
+    app.state::<OfflineState>().offline_since.lock().unwrap().is_some()
+}
+
+// The response for a proxied request while offline; None when it may go
+// through. Agent requests queue the agent.
+pub fn hold_request(app: &AppHandle, agent_id: Option<&str>) -> Option<Response> {
+    if !is_offline(app) {
+        return None;
+    }
+    let state = app.state::<OfflineState>();
+    let settings = state.settings.lock().unwrap().clone();
+    if is_urgent(&settings, agent_id) {
+        return None;
+    }
+    if let Some(agent_id) = agent_id {
+        state.queued_agents.lock().unwrap().insert(agent_id.to_string());
+    }
+    let body = serde_json::json!({
+        "error": "The Ollama backend isn't reachable; Observer is offline",
+        "code": "offline",
+        "queued": agent_id.is_some(),
+    });
+    Some(
+        Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::RETRY_AFTER, settings.probe_seconds.max(1))
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+    )
+}
+
+// Returns true when the alert was held for later.
+pub fn hold_if_offline(app: &AppHandle, alert: &Alert) -> bool {
+    let state = app.state::<OfflineState>();
+    let settings = state.settings.lock().unwrap().clone();
+    if !is_offline(app) || !settings.hold_notifications || is_urgent(&settings, alert.agent_id.as_deref()) {
+        return false;
+    }
+    let mut held = state.held_alerts.lock().unwrap();
+    if held.len() >= MAX_HELD_ALERTS {
+        held.remove(0);
+    }
+    held.push(alert.clone());
+    true
+}
+
+pub fn start_monitor(app: AppHandle) {
+    *app.state::<OfflineState>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        let mut changes = wake::subscribe(&app);
+        loop {
+            let settings = app.state::<OfflineState>().settings.lock().unwrap().clone();
+            if settings.enabled {
+                let reachable = probe(&app).await;
+                if reachable && is_offline(&app) {
+                    go_online(&app).await;
+                } else if !reachable && !is_offline(&app) {
+                    go_offline(&app).await;
+                }
+            } else if is_offline(&app) {
+                go_online(&app).await;
+            }
+            wait(&mut changes, Duration::from_secs(settings.probe_seconds.max(1))).await;
+        }
+    });
+}
+
+// Sleeps for `duration`, or until the machine wakes up or moves networks,
+// which is a good moment to look again.
+async fn wait(changes: &mut broadcast::Receiver<ChangeEvent>, duration: Duration) {
+    let sleep = tokio::time::sleep(duration);
+    tokio::pin!(sleep);
+    loop {
+        tokio::select! {
+            _ = &mut sleep => return,
+            change = changes.recv() => match change {
+                Ok(ChangeEvent { change: SystemChange::Resumed { .. } | SystemChange::NetworkChanged { .. }, .. }) => {
+                    return;
+                }
+                Err(broadcast::error::RecvError::Closed) => return sleep.await,
+                _ => {}
+            },
+        }
+    }
+}
+
+pub async fn status_handler(AxumState(state): AxumState<AppState>) -> Json<ConnectivityStatus> {
+    Json(status(&state.app_handle))
+}
+
+#[tauri::command]
+pub fn get_connectivity_status(app: AppHandle) -> ConnectivityStatus {
+    status(&app)
+}
+
+#[tauri::command]
+pub fn get_offline_settings(state: State<'_, OfflineState>) -> OfflineSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_offline_settings(
+    app: AppHandle,
+    settings: OfflineSettings,
+    state: State<'_, OfflineState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
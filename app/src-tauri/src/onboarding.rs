@@ -0,0 +1,326 @@
+// In src-tauri/src/onboarding.rs
+//
+// First-run onboarding, driven from the backend so every frontend (the
+// webview, the browser app) walks the same steps in the same order:
+//
+//   detect_ollama -> probe_hardware -> starter_model -> permissions
+//     -> first_agent -> done
+//
+// Each `onboarding_*` command performs its step and advances the state,
+// which is saved in onboarding.json so an interrupted onboarding resumes
+// where it stopped. Any step can be skipped. Long steps (the model pull)
+// report on "onboarding-progress"; "onboarding-state-changed" carries the
+// new state after every transition.
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::agents::{self, AgentDefinition};
+use crate::hardware::{self, HardwareInfo};
+use crate::permissions::{self, Permission, PermissionStatus};
+use crate::{llm, model_manager, policy, storage, AppSettings};
+
+const STATE_FILE: &str = "onboarding.json";
+const EXAMPLE_AGENT_ID: &str = "activity_tracker";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    #[default]
+    DetectOllama,
+    ProbeHardware,
+    StarterModel,
+    Permissions,
+    FirstAgent,
+    Done,
+}
+
+impl OnboardingStep {
+    fn next(self) -> Self {
+        match self {
+            OnboardingStep::DetectOllama => OnboardingStep::ProbeHardware,
+            OnboardingStep::ProbeHardware => OnboardingStep::StarterModel,
+            OnboardingStep::StarterModel => OnboardingStep::Permissions,
+            OnboardingStep::Permissions => OnboardingStep::FirstAgent,
+            OnboardingStep::FirstAgent | OnboardingStep::Done => OnboardingStep::Done,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OnboardingState {
+    pub step: OnboardingStep,
+    pub skipped: Vec<OnboardingStep>,
+    pub ollama_url: Option<String>,
+    pub recommended_model: Option<String>,
+    // The model the first agent will use.
+    pub model: Option<String>,
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaDetection {
+    pub binary: Option<String>,
+    pub servers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelRecommendation {
+    pub model: String,
+    // Whether it can look at screenshots.
+    pub vision: bool,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HardwareReport {
+    pub hardware: HardwareInfo,
+    pub recommendation: ModelRecommendation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Progress {
+    step: OnboardingStep,
+    message: String,
+    completed: Option<u64>,
+    total: Option<u64>,
+}
+
+fn progress(app: &AppHandle, step: OnboardingStep, message: impl Into<String>, completed: Option<u64>, total: Option<u64>) {
+    let payload = Progress { step, message: message.into(), completed, total };
+    if let Err(e) = app.emit("onboarding-progress", payload) {
+        log::error!("Failed to emit onboarding-progress event: {}", e);
+    }
+}
+
+fn load(app: &AppHandle) -> OnboardingState {
+    storage::load_json(app, STATE_FILE)
+}
+
+// Saves the state and tells the frontend.
+fn save(app: &AppHandle, state: &OnboardingState) -> Result<(), String> {
+    storage::save_json(app, STATE_FILE, state)?;
+    if let Err(e) = app.emit("onboarding-state-changed", state) {
+        log::error!("Failed to emit onboarding-state-changed event: {}", e);
+    }
+    Ok(())
+}
+
+// Marks `step` finished; finishing a later step than the current one (the
+// user went ahead) moves straight past it.
+fn complete(app: &AppHandle, step: OnboardingStep, update: impl FnOnce(&mut OnboardingState)) -> Result<OnboardingState, String> {
+    let mut state = load(app);
+    update(&mut state);
+    if state.step <= step {
+        state.step = step.next();
+    }
+    save(app, &state)?;
+    Ok(state)
+}
+
+// The ollama executable, from PATH or where the installers put it.
+pub fn find_ollama_binary() -> Option<PathBuf> {
+    let name = if cfg!(target_os = "windows") { "ollama.exe" } else { "ollama" };
+    let mut candidates: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).map(|dir| dir.join(name)).collect())
+        .unwrap_or_default();
+    #[cfg(target_os = "windows")]
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        candidates.push(PathBuf::from(local).join("Programs").join("Ollama").join(name));
+    }
+    #[cfg(not(target_os = "windows"))]
+    candidates.extend(["/usr/local/bin/ollama", "/usr/bin/ollama", "/opt/homebrew/bin/ollama"].map(PathBuf::from));
+    #[cfg(target_os = "macos")]
+    candidates.push(PathBuf::from("/Applications/Ollama.app/Contents/Resources/ollama"));
+    candidates.into_iter().find(|p| p.is_file())
+}
+
+pub fn recommend_model(hardware: &HardwareInfo) -> ModelRecommendation {
+    let memory_gb = hardware.model_memory_mb().unwrap_or(0) / 1024;
+    let (model, vision, reason) = match memory_gb {
+        16.. => ("gemma3:12b", true, format!("{} GB available for models fits a 12B vision model", memory_gb)),
+        6.. => ("gemma3:4b", true, format!("{} GB available for models fits a 4B vision model", memory_gb)),
+        _ => (
+            "gemma3:1b",
+            false,
+            "Little memory available for models; this small model reads text (OCR) but can't see screenshots"
+                .to_string(),
+        ),
+    };
+    ModelRecommendation { model: model.to_string(), vision, reason }
+}
+
+#[tauri::command]
+pub fn get_onboarding_state(app: AppHandle) -> OnboardingState {
+    load(&app)
+}
+
+// Looks for the ollama binary and running servers. Advances only once a
+// server answers, and selects it unless the policy pins another one.
+#[tauri::command]
+pub async fn onboarding_detect_ollama(app: AppHandle) -> Result<OllamaDetection, String> {
+    let step = OnboardingStep::DetectOllama;
+    progress(&app, step, "Looking for Ollama", None, None);
+    let binary = tokio::task::spawn_blocking(find_ollama_binary).await.map_err(|e| e.to_string())?;
+
+    let mut candidates = vec![llm::ollama_base_url(&app), llm::DEFAULT_OLLAMA_URL.to_string()];
+    candidates.push("http://localhost:11434".to_string());
+    candidates.dedup();
+    let servers = crate::check_ollama_servers(candidates).await?;
+
+    let detection = OllamaDetection {
+        binary: binary.map(|p| p.to_string_lossy().to_string()),
+        servers,
+    };
+    match detection.servers.first() {
+        Some(url) => {
+            if policy::pinned_ollama_url().is_none() {
+                *app.state::<AppSettings>().ollama_url.lock().unwrap() = Some(url.clone());
+            }
+            progress(&app, step, format!("Found Ollama at {}", url), None, None);
+            complete(&app, step, |state| state.ollama_url = Some(url.clone()))?;
+        }
+        None if detection.binary.is_some() => {
+            progress(&app, step, "Ollama is installed but not running; start it and try again", None, None)
+        }
+        None => progress(&app, step, "Ollama isn't installed", None, None),
+    }
+    Ok(detection)
+}
+
+#[tauri::command]
+pub async fn onboarding_probe_hardware(app: AppHandle) -> Result<HardwareReport, String> {
+    let step = OnboardingStep::ProbeHardware;
+    progress(&app, step, "Checking this computer's memory and GPU", None, None);
+    let hardware = tokio::task::spawn_blocking(hardware::probe).await.map_err(|e| e.to_string())?;
+    let recommendation = recommend_model(&hardware);
+    complete(&app, step, |state| {
+        state.recommended_model = Some(recommendation.model.clone());
+    })?;
+    Ok(HardwareReport { hardware, recommendation })
+}
+
+// Pulls `model` (the recommended one by default), streaming Ollama's
+// progress. A model that's already present finishes immediately.
+#[tauri::command]
+pub async fn onboarding_pull_model(app: AppHandle, model: Option<String>) -> Result<OnboardingState, String> {
+    let step = OnboardingStep::StarterModel;
+    let model = model
+        .or_else(|| load(&app).recommended_model)
+        .ok_or("No model chosen; probe the hardware first or pick one")?;
+    let base_url = llm::ollama_base_url(&app);
+    policy::check_backend(&base_url)?;
+
+    log::info!("Onboarding: pulling {} from {}", model, base_url);
+    progress(&app, step, format!("Downloading {}", model), None, None);
+    let request = serde_json::to_vec(&serde_json::json!({ "model": model, "stream": true })).unwrap_or_default();
+    let request = model_manager::rewrite_pull_request(&app, &request).unwrap_or(request);
+    let response = llm::client()
+        .post(format!("{}/api/pull", base_url))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach Ollama at {}: {}", base_url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Pulling {} failed ({}): {}", model, status, text));
+    }
+
+    // One JSON object per line.
+    let mut stream = response.bytes_stream();
+    let mut buffer = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk.map_err(|e| format!("Download of {} interrupted: {}", model, e))?);
+        while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            let Ok(update) = serde_json::from_slice::<Value>(&line) else {
+                continue;
+            };
+            if let Some(error) = update.get("error").and_then(Value::as_str) {
+                return Err(format!("Pulling {} failed: {}", model, error));
+            }
+            let status = update.get("status").and_then(Value::as_str).unwrap_or("downloading");
+            progress(
+                &app,
+                step,
+                status,
+                update.get("completed").and_then(Value::as_u64),
+                update.get("total").and_then(Value::as_u64),
+            );
+        }
+    }
+    progress(&app, step, format!("{} is ready", model), None, None);
+    complete(&app, step, |state| state.model = Some(model))
+}
+
+// Asks for what agents commonly need: screen recording and the microphone.
+#[tauri::command]
+pub async fn onboarding_request_permissions(app: AppHandle) -> Result<Vec<PermissionStatus>, String> {
+    let step = OnboardingStep::Permissions;
+    let mut statuses = Vec::new();
+    for permission in [Permission::ScreenRecording, Permission::Microphone] {
+        progress(&app, step, format!("Requesting {:?} access", permission), None, None);
+        let status = tokio::task::spawn_blocking(move || permissions::request_permission(permission))
+            .await
+            .map_err(|e| e.to_string())??;
+        statuses.push(status);
+    }
+    complete(&app, step, |_| {})?;
+    Ok(statuses)
+}
+
+// Creates a small example agent that notes what the user is working on.
+#[tauri::command]
+pub fn onboarding_create_agent(app: AppHandle) -> Result<AgentDefinition, String> {
+    let step = OnboardingStep::FirstAgent;
+    let state = load(&app);
+    let model = state
+        .model
+        .or(state.recommended_model)
+        .ok_or("No model chosen; pull a starter model first")?;
+    let vision = model != "gemma3:1b";
+    let screen = if vision { "$SCREEN_64" } else { "$SCREEN_OCR" };
+    let agent = AgentDefinition {
+        id: EXAMPLE_AGENT_ID.to_string(),
+        name: "Activity Tracker".to_string(),
+        description: "Your first agent: writes a one-line note about what you're doing every minute.".to_string(),
+        model_name: model,
+        system_prompt: format!(
+            "You are an activity tracker. Look at the screen and describe in one short sentence what \
+             the user is working on.\n\n{}",
+            screen
+        ),
+        loop_interval_seconds: 60.0,
+        code: "// Appends the model's response to this agent's memory, with a timestamp.\n\
+               const timestamp = time();\n\
+               appendMemory(agentId, `\\n[${timestamp}] ${response}`);\n"
+            .to_string(),
+        memory: String::new(),
+    };
+    agents::upsert(&app, agent.clone())?;
+    progress(&app, step, format!("Created the agent '{}'", agent.name), None, None);
+    complete(&app, step, |state| state.agent_id = Some(agent.id.clone()))?;
+    Ok(agent)
+}
+
+#[tauri::command]
+pub fn skip_onboarding_step(app: AppHandle, step: OnboardingStep) -> Result<OnboardingState, String> {
+    complete(&app, step, |state| {
+        if !state.skipped.contains(&step) {
+            state.skipped.push(step);
+        }
+    })
+}
+
+#[tauri::command]
+pub fn reset_onboarding(app: AppHandle) -> Result<OnboardingState, String> {
+    let state = OnboardingState::default();
+    save(&app, &state)?;
+    Ok(state)
+}
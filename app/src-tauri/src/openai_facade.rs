@@ -0,0 +1,147 @@
+// In src-tauri/src/openai_facade.rs
+//
+// OpenAI-compatible endpoint where the "models" are the user's agents:
+// `GET /observer/v1/models` lists them and `POST /observer/v1/chat/completions`
+// with `"model": "<agent id>"` runs the conversation through that agent's
+// model with its system prompt applied. Any OpenAI client (IDE plugins,
+// scripts) can talk to agents this way.
+//
+// The request goes through the app's proxy pipeline as the agent, so the
+// kill switch, backend policy, data locality, the agent's budget, presets and
+// memory apply as they do in the agent loop. Only the conversation is run:
+// tool calls the model makes are returned to the client like any other
+// answer, and running them is up to the client (see `POST /tools/call`,
+// where moderation applies).
+//
+// Sensor variables like $SCREEN_64 only have meaning inside the agent loop,
+// so they are dropped from the system prompt here; $MEMORY@<agent> is filled
+// from the synced agent definitions.
+
+use axum::{
+    body::Body,
+    extract::State as AxumState,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tauri::Manager;
+
+use crate::agents::{AgentDefinition, AgentRegistry};
+use crate::{compaction, AppState};
+
+// Variables the frontend fills from sensors; see app/src/utils/pre-processor.ts.
+const SENSOR_VARIABLES: &[&str] = &[
+    "$SCREEN_64",
+    "$SCREEN_OCR",
+    "$SCREEN_UI",
+    "$CAMERA",
+    "$SCREEN_AUDIO",
+    "$MICROPHONE",
+    "$ALL_AUDIO",
+    "$CLIPBOARD",
+];
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    let body = json!({ "error": { "message": message, "type": "invalid_request_error" } });
+    (status, Json(body)).into_response()
+}
+
+fn resolve_system_prompt(agent: &AgentDefinition, registry: &AgentRegistry) -> String {
+    let mut prompt = agent.system_prompt.clone();
+    for variable in SENSOR_VARIABLES {
+        prompt = prompt.replace(variable, "");
+    }
+
+    const MEMORY_PREFIX: &str = "$MEMORY@";
+    let mut resolved = String::with_capacity(prompt.len());
+    let mut rest = prompt.as_str();
+    while let Some(start) = rest.find(MEMORY_PREFIX) {
+        resolved.push_str(&rest[..start]);
+        let after = &rest[start + MEMORY_PREFIX.len()..];
+        let id_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let memory = registry.get(&after[..id_len]).map(|a| a.memory).unwrap_or_default();
+        resolved.push_str(&memory);
+        rest = &after[id_len..];
+    }
+    resolved.push_str(rest);
+    resolved
+}
+
+pub async fn models_handler(AxumState(state): AxumState<AppState>) -> Json<Value> {
+    let agents = state.app_handle.state::<AgentRegistry>().list();
+    let data: Vec<Value> = agents
+        .iter()
+        .map(|agent| {
+            json!({
+                "id": agent.id,
+                "object": "model",
+                "owned_by": "observer",
+                "name": agent.name,
+                "description": agent.description,
+            })
+        })
+        .collect();
+    Json(json!({ "object": "list", "data": data }))
+}
+
+pub async fn chat_completions_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(mut body): Json<Value>,
+) -> Response {
+    let agent_id = match body["model"].as_str() {
+        Some(id) => id.to_string(),
+        None => return error_response(StatusCode::BAD_REQUEST, "`model` must be an agent id"),
+    };
+    let registry = state.app_handle.state::<AgentRegistry>();
+    let agent = match registry.get(&agent_id) {
+        Some(agent) => agent,
+        None => return error_response(StatusCode::NOT_FOUND, &format!("No agent with id '{}'", agent_id)),
+    };
+    let messages = match body["messages"].as_array() {
+        Some(messages) => messages.clone(),
+        None => return error_response(StatusCode::BAD_REQUEST, "`messages` is required"),
+    };
+
+    // The agent's own instructions first, then the caller's messages.
+    let mut full = Vec::with_capacity(messages.len() + 1);
+    let system_prompt = resolve_system_prompt(&agent, &registry);
+    if !system_prompt.trim().is_empty() {
+        full.push(json!({ "role": "system", "content": system_prompt }));
+    }
+    full.extend(messages);
+
+    body["model"] = Value::String(agent.model_name.clone());
+    body["messages"] = Value::Array(full);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    match HeaderValue::from_str(&agent_id) {
+        Ok(value) => headers.insert(compaction::AGENT_HEADER, value),
+        Err(_) => return error_response(StatusCode::BAD_REQUEST, &format!("Invalid agent id '{}'", agent_id)),
+    };
+    log::info!("Running agent {} on {} for an OpenAI client", agent_id, agent.model_name);
+    let uri = Uri::from_static("/v1/chat/completions");
+    let response = observer_core::server::proxy(&state, Method::POST, headers, uri, Body::from(body.to_string())).await;
+
+    // Streams and errors pass through untouched.
+    if body["stream"].as_bool().unwrap_or(false) || !response.status().is_success() {
+        return response;
+    }
+    // Report the agent, not the underlying model, as the model that answered.
+    let status = response.status();
+    let completion = match response.into_body().collect().await {
+        Ok(collected) => serde_json::from_slice::<Value>(&collected.to_bytes()).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match completion {
+        Ok(mut completion) => {
+            completion["model"] = Value::String(agent_id);
+            (status, Json(completion)).into_response()
+        }
+        Err(e) => error_response(StatusCode::BAD_GATEWAY, &format!("Invalid backend response: {}", e)),
+    }
+}
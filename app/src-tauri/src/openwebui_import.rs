@@ -0,0 +1,259 @@
+// In src-tauri/src/openwebui_import.rs
+//
+// Importing an Open WebUI export, so switching tools keeps the history.
+// `import_openwebui(path)` takes any of Open WebUI's JSON exports:
+//
+//   chats    Settings > Chats > Export: [{ "title", "chat": { "history":
+//            { "messages": { id: { "parentId", "role", "content", ... } } } } }]
+//   prompts  Workspace > Prompts > Export: [{ "command", "title", "content" }]
+//   models   Workspace > Models > Export: [{ "id", "name", "base_model_id",
+//            "params": { "system" } }]
+//
+// or a file combining them. Chats become conversations with their branches
+// intact (see conversations.rs); prompts, and the system prompts of custom
+// models, go into the template store (see templates.rs). Chats already
+// imported are skipped, so importing the same export twice is harmless.
+
+use rusqlite::{params, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use tauri::{AppHandle, State};
+
+use crate::history::HistoryDb;
+use crate::storage;
+use crate::templates::{self, PromptTemplate, TemplateKind};
+
+// IDs of the chats imported so far.
+const IMPORTED_FILE: &str = "openwebui_import.json";
+const SOURCE: &str = "open-webui";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Imported {
+    chats: BTreeSet<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct OpenWebUiImportReport {
+    pub conversations: usize,
+    pub messages: usize,
+    pub prompts: usize,
+    pub models: usize,
+    // Already imported, or nothing usable in them.
+    pub skipped: usize,
+}
+
+// Open WebUI mixes seconds and milliseconds between versions.
+fn to_millis(value: &Value) -> Option<i64> {
+    let value = value.as_f64()? as i64;
+    Some(if value < 100_000_000_000 { value * 1000 } else { value })
+}
+
+fn entries(export: Value) -> Vec<Value> {
+    match export {
+        Value::Array(items) => items,
+        Value::Object(mut object) => {
+            let sections: Vec<Value> =
+                ["chats", "prompts", "models"].iter().filter_map(|key| object.remove(*key)).collect();
+            if sections.is_empty() {
+                vec![Value::Object(object)]
+            } else {
+                sections.into_iter().flat_map(entries).collect()
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+struct ImportedMessage {
+    id: String,
+    parent: Option<String>,
+    role: String,
+    content: String,
+    model: Option<String>,
+    created_at: Option<i64>,
+}
+
+// The chat's message tree, or its flat message list in older exports.
+fn chat_messages(chat: &Value) -> Vec<ImportedMessage> {
+    let message = |id: String, parent: Option<String>, m: &Value| {
+        Some(ImportedMessage {
+            id,
+            parent,
+            role: m["role"].as_str()?.to_string(),
+            content: m["content"].as_str().unwrap_or_default().to_string(),
+            model: m["model"].as_str().map(str::to_string),
+            created_at: to_millis(&m["timestamp"]),
+        })
+    };
+    if let Some(tree) = chat["history"]["messages"].as_object().filter(|tree| !tree.is_empty()) {
+        return tree
+            .iter()
+            .filter_map(|(id, m)| message(id.clone(), m["parentId"].as_str().map(str::to_string), m))
+            .collect();
+    }
+    let mut parent = None;
+    chat["messages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(i, m)| {
+            let id = m["id"].as_str().map(str::to_string).unwrap_or_else(|| i.to_string());
+            message(id.clone(), parent.replace(id), m)
+        })
+        .collect()
+}
+
+// Inserts one chat; returns how many messages it had.
+fn import_chat(tx: &Transaction, entry: &Value) -> Result<usize, String> {
+    let chat = if entry["chat"].is_object() { &entry["chat"] } else { entry };
+    let mut messages = chat_messages(chat);
+    if messages.is_empty() {
+        return Ok(0);
+    }
+    let title = entry["title"]
+        .as_str()
+        .or(chat["title"].as_str())
+        .filter(|t| !t.is_empty())
+        .unwrap_or("Open WebUI chat");
+    let created_at = to_millis(&entry["created_at"]).or(to_millis(&chat["timestamp"])).unwrap_or_default();
+    let updated_at = to_millis(&entry["updated_at"]).unwrap_or(created_at);
+    tx.execute(
+        "INSERT INTO conversations (title, created_at, updated_at) VALUES (?1, ?2, ?3)",
+        params![title, created_at, updated_at],
+    )
+    .map_err(|e| e.to_string())?;
+    let conversation_id = tx.last_insert_rowid();
+
+    // Parents before children: repeatedly insert whatever has its parent in.
+    let known: BTreeSet<String> = messages.iter().map(|m| m.id.clone()).collect();
+    for message in &mut messages {
+        if message.parent.as_ref().is_some_and(|parent| !known.contains(parent)) {
+            message.parent = None;
+        }
+    }
+    messages.sort_by_key(|m| m.created_at.unwrap_or(created_at));
+    let mut ids: HashMap<String, i64> = HashMap::new();
+    let count = messages.len();
+    while !messages.is_empty() {
+        let before = messages.len();
+        let mut remaining = Vec::new();
+        for message in messages {
+            let parent_id = match &message.parent {
+                Some(parent) => match ids.get(parent) {
+                    Some(id) => Some(*id),
+                    None => {
+                        remaining.push(message);
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            tx.execute(
+                "INSERT INTO messages (conversation_id, parent_id, role, content, model, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    conversation_id,
+                    parent_id,
+                    message.role,
+                    message.content,
+                    message.model,
+                    message.created_at.unwrap_or(created_at)
+                ],
+            )
+            .map_err(|e| format!("Failed to store message: {}", e))?;
+            ids.insert(message.id, tx.last_insert_rowid());
+        }
+        // A parent cycle; nothing left can be placed.
+        if remaining.len() == before {
+            return Err(format!("Chat '{}' has a broken message tree", title));
+        }
+        messages = remaining;
+    }
+    Ok(count)
+}
+
+fn prompt_template(entry: &Value) -> Option<PromptTemplate> {
+    let command = entry["command"].as_str()?;
+    let content = entry["content"].as_str().filter(|c| !c.trim().is_empty())?;
+    let command = format!("/{}", command.trim_start_matches('/'));
+    Some(PromptTemplate {
+        id: format!("{}:prompt:{}", SOURCE, command),
+        title: entry["title"].as_str().filter(|t| !t.is_empty()).unwrap_or(&command).to_string(),
+        kind: TemplateKind::Prompt,
+        command: Some(command),
+        content: content.to_string(),
+        model: None,
+        source: Some(SOURCE.to_string()),
+    })
+}
+
+fn model_template(entry: &Value) -> Option<PromptTemplate> {
+    let id = entry["id"].as_str()?;
+    let system = entry["params"]["system"].as_str().filter(|s| !s.trim().is_empty())?;
+    Some(PromptTemplate {
+        id: format!("{}:model:{}", SOURCE, id),
+        title: entry["name"].as_str().filter(|n| !n.is_empty()).unwrap_or(id).to_string(),
+        kind: TemplateKind::System,
+        command: None,
+        content: system.to_string(),
+        model: entry["base_model_id"].as_str().map(str::to_string),
+        source: Some(SOURCE.to_string()),
+    })
+}
+
+#[tauri::command]
+pub fn import_openwebui(
+    app: AppHandle,
+    path: String,
+    db: State<'_, HistoryDb>,
+) -> Result<OpenWebUiImportReport, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let export: Value = serde_json::from_slice(&bytes).map_err(|e| format!("{} isn't a JSON export: {}", path, e))?;
+
+    let mut report = OpenWebUiImportReport::default();
+    let mut imported: Imported = storage::load_json(&app, IMPORTED_FILE);
+    let mut templates = templates::load(&app);
+    {
+        let mut conn = db.0.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for entry in entries(export) {
+            if entry["chat"].is_object() || entry["history"].is_object() {
+                let chat_id = entry["id"].as_str().or(entry["chat"]["id"].as_str()).map(str::to_string);
+                if chat_id.as_ref().is_some_and(|id| imported.chats.contains(id)) {
+                    report.skipped += 1;
+                    continue;
+                }
+                match import_chat(&tx, &entry)? {
+                    0 => report.skipped += 1,
+                    messages => {
+                        report.conversations += 1;
+                        report.messages += messages;
+                        imported.chats.extend(chat_id);
+                    }
+                }
+            } else if let Some(template) = prompt_template(&entry) {
+                templates.insert(template.id.clone(), template);
+                report.prompts += 1;
+            } else if let Some(template) = model_template(&entry) {
+                templates.insert(template.id.clone(), template);
+                report.models += 1;
+            } else {
+                report.skipped += 1;
+            }
+        }
+        tx.commit().map_err(|e| format!("Failed to save the imported chats: {}", e))?;
+    }
+    templates::save_all(&app, &templates)?;
+    storage::save_json(&app, IMPORTED_FILE, &imported)?;
+    log::info!(
+        "Imported {} chats ({} messages), {} prompts and {} models from Open WebUI",
+        report.conversations,
+        report.messages,
+        report.prompts,
+        report.models
+    );
+    Ok(report)
+}
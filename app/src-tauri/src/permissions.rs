@@ -0,0 +1,303 @@
+// In src-tauri/src/permissions.rs
+//
+// OS permissions the sensors depend on. macOS gates screen recording,
+// the microphone and accessibility (needed for input automation and the
+// active window title) behind TCC prompts; Windows has per-app consent
+// switches for the microphone and programmatic screen capture. Linux
+// desktops don't gate any of these, so they're reported as not applicable.
+//
+// `get_permission_status` is meant for an onboarding/settings checklist;
+// `request_permission` triggers the OS prompt where there is one and
+// otherwise opens the matching system settings pane, which is also the
+// only way back once the user has denied a permission.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ScreenRecording,
+    Microphone,
+    Accessibility,
+}
+
+const ALL: [Permission; 3] = [Permission::ScreenRecording, Permission::Microphone, Permission::Accessibility];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    // The user hasn't been asked yet.
+    NotDetermined,
+    // The OS doesn't gate this.
+    NotApplicable,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionStatus {
+    pub permission: Permission,
+    pub state: PermissionState,
+    // The system settings pane to send the user to when denied.
+    pub settings_url: Option<&'static str>,
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{Permission, PermissionState};
+    use std::ffi::{c_char, c_void};
+
+    type Id = *mut c_void;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+        fn AXIsProcessTrustedWithOptions(options: Id) -> bool;
+        static kAXTrustedCheckOptionPrompt: Id;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFBooleanTrue: Id;
+        static kCFTypeDictionaryKeyCallBacks: c_void;
+        static kCFTypeDictionaryValueCallBacks: c_void;
+        fn CFDictionaryCreate(
+            allocator: Id,
+            keys: *const Id,
+            values: *const Id,
+            count: isize,
+            key_callbacks: *const c_void,
+            value_callbacks: *const c_void,
+        ) -> Id;
+        fn CFRelease(object: Id);
+    }
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: Id;
+    }
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Id;
+        fn objc_msgSend();
+    }
+
+    // AVAuthorizationStatus: 0 not determined, 1 restricted, 2 denied,
+    // 3 authorized.
+    fn microphone() -> PermissionState {
+        unsafe {
+            let class = objc_getClass(c"AVCaptureDevice".as_ptr());
+            if class.is_null() {
+                return PermissionState::Unknown;
+            }
+            let selector = sel_registerName(c"authorizationStatusForMediaType:".as_ptr());
+            let send: unsafe extern "C" fn(Id, Id, Id) -> isize =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            match send(class, selector, AVMediaTypeAudio) {
+                0 => PermissionState::NotDetermined,
+                1 | 2 => PermissionState::Denied,
+                3 => PermissionState::Granted,
+                _ => PermissionState::Unknown,
+            }
+        }
+    }
+
+    pub fn status(permission: Permission) -> PermissionState {
+        let granted = match permission {
+            // macOS has no "not asked yet" for these two.
+            Permission::ScreenRecording => unsafe { CGPreflightScreenCaptureAccess() },
+            Permission::Accessibility => unsafe { AXIsProcessTrusted() },
+            Permission::Microphone => return microphone(),
+        };
+        if granted {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        }
+    }
+
+    // Shows the system prompt; it only appears the first time, later calls
+    // just return the current state. The microphone prompt comes from the
+    // webview's getUserMedia.
+    pub fn request(permission: Permission) -> bool {
+        match permission {
+            Permission::ScreenRecording => unsafe { CGRequestScreenCaptureAccess() },
+            Permission::Accessibility => unsafe {
+                let keys = [kAXTrustedCheckOptionPrompt];
+                let values = [kCFBooleanTrue];
+                let options = CFDictionaryCreate(
+                    std::ptr::null_mut(),
+                    keys.as_ptr(),
+                    values.as_ptr(),
+                    1,
+                    &kCFTypeDictionaryKeyCallBacks,
+                    &kCFTypeDictionaryValueCallBacks,
+                );
+                let trusted = AXIsProcessTrustedWithOptions(options);
+                CFRelease(options);
+                trusted
+            },
+            Permission::Microphone => false,
+        }
+    }
+
+    pub fn settings_url(permission: Permission) -> Option<&'static str> {
+        Some(match permission {
+            Permission::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+            Permission::Microphone => "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone",
+            Permission::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod win {
+    use super::{Permission, PermissionState};
+    use std::process::Command;
+
+    const CONSENT_STORE: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore";
+
+    // "Allow"/"Deny" of a consent store key, e.g. `microphone\NonPackaged`.
+    fn consent(key: &str) -> Option<String> {
+        let output = Command::new("reg")
+            .args(["query", &format!(r"{}\{}", CONSENT_STORE, key), "/v", "Value"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .find(|line| line.trim_start().starts_with("Value"))
+            .and_then(|line| line.split_whitespace().last())
+            .map(str::to_string)
+    }
+
+    // The app-wide switch and the one for desktop (non-packaged) apps both
+    // have to allow access.
+    fn capability(name: &str) -> PermissionState {
+        let global = consent(name);
+        let desktop = consent(&format!(r"{}\NonPackaged", name));
+        match (global.as_deref(), desktop.as_deref()) {
+            (None, None) => PermissionState::NotApplicable,
+            (Some("Deny"), _) | (_, Some("Deny")) => PermissionState::Denied,
+            _ => PermissionState::Granted,
+        }
+    }
+
+    pub fn status(permission: Permission) -> PermissionState {
+        match permission {
+            Permission::Microphone => capability("microphone"),
+            // Windows 11 asks before apps capture the screen without the
+            // yellow border; older versions don't gate capture at all.
+            Permission::ScreenRecording => capability("graphicsCaptureProgrammatic"),
+            Permission::Accessibility => PermissionState::NotApplicable,
+        }
+    }
+
+    pub fn request(_permission: Permission) -> bool {
+        false
+    }
+
+    pub fn settings_url(permission: Permission) -> Option<&'static str> {
+        match permission {
+            Permission::Microphone => Some("ms-settings:privacy-microphone"),
+            Permission::ScreenRecording => Some("ms-settings:privacy-graphicsCaptureProgrammatic"),
+            Permission::Accessibility => None,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod other {
+    use super::{Permission, PermissionState};
+
+    pub fn status(_permission: Permission) -> PermissionState {
+        PermissionState::NotApplicable
+    }
+
+    pub fn request(_permission: Permission) -> bool {
+        false
+    }
+
+    pub fn settings_url(_permission: Permission) -> Option<&'static str> {
+        None
+    }
+}
+
+#[cfg(target_os = "macos")]
+use macos as platform;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use other as platform;
+#[cfg(target_os = "windows")]
+use win as platform;
+
+pub fn status(permission: Permission) -> PermissionStatus {
+    PermissionStatus {
+        permission,
+        state: platform::status(permission),
+        settings_url: platform::settings_url(permission),
+    }
+}
+
+fn open_url(url: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = std::process::Command::new("xdg-open");
+    command.arg(url).spawn().map_err(|e| format!("Failed to open {}: {}", url, e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_permission_status() -> Vec<PermissionStatus> {
+    ALL.iter().map(|p| status(*p)).collect()
+}
+
+// Prompts for the permission, or opens its settings pane when there's no
+// prompt left to show. Returns the status afterwards; macOS only applies a
+// new screen recording grant after a restart.
+#[tauri::command]
+pub fn request_permission(permission: Permission) -> Result<PermissionStatus, String> {
+    let before = status(permission);
+    if matches!(before.state, PermissionState::Granted | PermissionState::NotApplicable) {
+        return Ok(before);
+    }
+    if platform::request(permission) {
+        return Ok(status(permission));
+    }
+    let after = status(permission);
+    if after.state == PermissionState::Denied {
+        log::info!("{:?} permission denied, opening system settings", permission);
+        if let Some(url) = after.settings_url {
+            open_url(url)?;
+        }
+    }
+    Ok(after)
+}
+
+#[tauri::command]
+pub fn open_permission_settings(permission: Permission) -> Result<(), String> {
+    let url = platform::settings_url(permission).ok_or("This platform has no settings pane for that permission")?;
+    open_url(url)
+}
@@ -0,0 +1,167 @@
+// In src-tauri/src/policy.rs
+//
+// Managed policy for organisations. An administrator drops `policy.json`
+// into the system-wide location below (writable only by admins); its
+// settings sit on top of everything the user configures and can't be
+// changed from inside the app:
+//
+//   {
+//     "ollama_url": "http://ollama.corp.internal:11434",
+//     "local_backends_only": true,
+//     "local_backends": ["http://ollama.corp.internal:11434"],
+//     "force_data_locality": true,
+//     "exec_allowlist": ["list", "ps", "show"],
+//     "shell_allowlist": ["git", "ls"]
+//   }
+//
+// The policy is read once at startup. Unknown keys and unreadable files
+// stop the app rather than starting it unrestricted, so a typo can't
+// silently lift a restriction.
+
+use axum::{
+    body::Body,
+    http::StatusCode,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::{locality, privacy};
+
+const POLICY_FILE: &str = "policy.json";
+
+static POLICY: OnceLock<Option<Policy>> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    // Pins the model backend; the launcher and config.toml can't change it.
+    pub ollama_url: Option<String>,
+    // Refuses every backend that isn't loopback or in `local_backends`.
+    pub local_backends_only: bool,
+    // Origins the organisation considers local, e.g. an on-prem server.
+    pub local_backends: Vec<String>,
+    // The data-locality policy can't be disabled, and only the
+    // organisation's `local_backends` count as local.
+    pub force_data_locality: bool,
+    // Ollama subcommands `/exec` may run; None keeps the built-in list.
+    pub exec_allowlist: Option<Vec<String>>,
+    // Programs the agent shell tool may propose; an empty list disables it.
+    pub shell_allowlist: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyStatus {
+    pub path: Option<String>,
+    pub policy: Option<Policy>,
+}
+
+fn policy_path() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let dir = std::env::var_os("ProgramData").map(|dir| PathBuf::from(dir).join("Observer"));
+    #[cfg(target_os = "macos")]
+    let dir = Some(PathBuf::from("/Library/Application Support/Observer"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let dir = Some(PathBuf::from("/etc/observer"));
+    dir.map(|dir| dir.join(POLICY_FILE))
+}
+
+fn load() -> Result<Option<Policy>, String> {
+    let Some(path) = policy_path() else {
+        return Ok(None);
+    };
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read the managed policy {:?}: {}", path, e)),
+    };
+    let mut policy: Policy =
+        serde_json::from_slice(&bytes).map_err(|e| format!("Invalid managed policy {:?}: {}", path, e))?;
+    policy.local_backends = policy.local_backends.iter().map(|url| locality::origin(url)).collect();
+    Ok(Some(policy))
+}
+
+// Must run before anything consults the policy.
+pub fn init() -> Result<(), String> {
+    let policy = load()?;
+    if let Some(policy) = &policy {
+        log::info!("Managed policy in effect: {:?}", policy);
+    }
+    let _ = POLICY.set(policy);
+    Ok(())
+}
+
+pub fn get() -> Option<&'static Policy> {
+    POLICY.get().and_then(Option::as_ref)
+}
+
+pub fn pinned_ollama_url() -> Option<&'static str> {
+    get().and_then(|p| p.ollama_url.as_deref())
+}
+
+pub fn forces_data_locality() -> bool {
+    get().is_some_and(|p| p.force_data_locality)
+}
+
+// Whether the organisation considers `url` local. Only meaningful while a
+// policy is loaded.
+pub fn is_local_backend(url: &str) -> bool {
+    let origin = locality::origin(url);
+    privacy::is_local_url(url) || get().is_some_and(|p| p.local_backends.contains(&origin))
+}
+
+// Err when the policy doesn't allow sending requests to `url`.
+pub fn check_backend(url: &str) -> Result<(), String> {
+    match get() {
+        Some(policy) if policy.local_backends_only && !is_local_backend(url) => Err(format!(
+            "Your organisation's policy only allows local model backends; {} is not one of them",
+            locality::origin(url)
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn allows_exec(subcommand: &str) -> bool {
+    match get().and_then(|p| p.exec_allowlist.as_ref()) {
+        Some(allowed) => allowed.iter().any(|s| s == subcommand),
+        None => true,
+    }
+}
+
+// Err when the policy restricts the shell tool and `command` isn't a single
+// allowlisted program.
+pub fn check_shell_command(command: &str) -> Result<(), String> {
+    let Some(allowed) = get().and_then(|p| p.shell_allowlist.as_ref()) else {
+        return Ok(());
+    };
+    if allowed.is_empty() {
+        return Err("Your organisation's policy disables the shell tool".to_string());
+    }
+    let program = command.split_whitespace().next().unwrap_or("");
+    let chained = command.chars().any(|c| "&;|<>()`$\n".contains(c));
+    if chained || !allowed.iter().any(|p| p == program) {
+        return Err(format!(
+            "Your organisation's policy only allows these commands: {}",
+            allowed.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+pub fn forbidden_response(message: &str) -> Response {
+    let body = serde_json::json!({ "error": message, "code": "policy_violation" });
+    Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tauri::command]
+pub fn get_policy() -> PolicyStatus {
+    PolicyStatus {
+        path: policy_path().map(|p| p.to_string_lossy().to_string()),
+        policy: get().cloned(),
+    }
+}
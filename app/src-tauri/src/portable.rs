@@ -0,0 +1,86 @@
+// In src-tauri/src/portable.rs
+//
+// Portable mode: with a `portable.flag` file next to the executable, or when
+// started with `--portable`, settings, databases, catalog metadata and logs
+// all live in an `ObserverData` folder beside it instead of the system
+// app-data and log directories, so Observer can run from a USB stick or a
+// synced folder without leaving anything behind on the machine.
+//
+// "Beside the executable" means beside what the user sees: the .app bundle
+// on macOS and the AppImage file on Linux, not the binary inside them.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const FLAG_FILE: &str = "portable.flag";
+const CLI_SWITCH: &str = "--portable";
+const DATA_DIR: &str = "ObserverData";
+const LOG_DIR: &str = "logs";
+
+static DATA_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortableStatus {
+    pub enabled: bool,
+    pub data_dir: Option<String>,
+}
+
+// The folder the user put Observer in.
+fn install_dir() -> Option<PathBuf> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return PathBuf::from(appimage).parent().map(PathBuf::from);
+    }
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?.to_path_buf();
+    // Observer.app/Contents/MacOS/observer -> the folder holding Observer.app
+    if dir.ends_with("Contents/MacOS") {
+        return dir.ancestors().nth(3).map(PathBuf::from);
+    }
+    Some(dir)
+}
+
+fn detect() -> Option<PathBuf> {
+    let dir = install_dir()?;
+    let switched = std::env::args().skip(1).any(|arg| arg == CLI_SWITCH);
+    if !switched && !dir.join(FLAG_FILE).exists() {
+        return None;
+    }
+    Some(dir.join(DATA_DIR))
+}
+
+// Where all data goes in portable mode; None when running installed.
+pub fn data_root() -> Option<PathBuf> {
+    DATA_ROOT.get_or_init(detect).clone()
+}
+
+pub fn log_dir() -> Option<PathBuf> {
+    data_root().map(|root| root.join(LOG_DIR))
+}
+
+// Logs go to the portable folder instead of the system log directory.
+pub fn log_targets() -> Vec<tauri_plugin_log::Target> {
+    use tauri_plugin_log::{Target, TargetKind};
+
+    match log_dir() {
+        Some(path) => {
+            if let Err(e) = std::fs::create_dir_all(&path) {
+                eprintln!("Failed to create {:?}: {}", path, e);
+            }
+            vec![
+                Target::new(TargetKind::Stdout),
+                Target::new(TargetKind::Folder { path, file_name: None }),
+            ]
+        }
+        None => vec![Target::new(TargetKind::Stdout), Target::new(TargetKind::LogDir { file_name: None })],
+    }
+}
+
+#[tauri::command]
+pub fn get_portable_status() -> PortableStatus {
+    let root = data_root();
+    PortableStatus {
+        enabled: root.is_some(),
+        data_dir: root.map(|p| p.to_string_lossy().to_string()),
+    }
+}
@@ -0,0 +1,273 @@
+// In src-tauri/src/power.rs
+//
+// Continuous capture + inference drains a laptop battery fast. This module
+// polls the power state (AC vs battery, charge level, thermal pressure), picks
+// the matching throttle rule from the user's power profiles and broadcasts it
+// on "power-state-changed". The agent loop in the web app polls it from
+// `GET /power/state`, stretches its capture intervals by `interval_multiplier`
+// and pauses heavy (vision/audio) agents when `pause_heavy_agents` is set.
+
+use axum::{extract::State as AxumState, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{storage, AppState};
+
+const PROFILES_FILE: &str = "power_profiles.json";
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+#[cfg(target_os = "linux")]
+const THERMAL_LIMIT_CELSIUS: f32 = 90.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThrottleRule {
+    pub interval_multiplier: f32,
+    pub pause_heavy_agents: bool,
+}
+
+impl ThrottleRule {
+    fn new(interval_multiplier: f32, pause_heavy_agents: bool) -> Self {
+        Self { interval_multiplier, pause_heavy_agents }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerProfiles {
+    pub enabled: bool,
+    pub low_battery_percent: f32,
+    pub ac: ThrottleRule,
+    pub battery: ThrottleRule,
+    pub low_battery: ThrottleRule,
+    pub thermal: ThrottleRule,
+}
+
+impl Default for PowerProfiles {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            low_battery_percent: 20.0,
+            ac: ThrottleRule::new(1.0, false),
+            battery: ThrottleRule::new(2.0, false),
+            low_battery: ThrottleRule::new(4.0, true),
+            thermal: ThrottleRule::new(3.0, true),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerProfileKind {
+    Ac,
+    Battery,
+    LowBattery,
+    Thermal,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PowerState {
+    pub on_battery: bool,
+    pub battery_percent: Option<f32>,
+    pub thermal_pressure: bool,
+    pub profile: PowerProfileKind,
+    pub throttle: ThrottleRule,
+}
+
+impl Default for PowerState {
+    fn default() -> Self {
+        Self {
+            on_battery: false,
+            battery_percent: None,
+            thermal_pressure: false,
+            profile: PowerProfileKind::Ac,
+            throttle: ThrottleRule::new(1.0, false),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PowerMonitor {
+    pub profiles: Mutex<PowerProfiles>,
+    pub state: Mutex<PowerState>,
+}
+
+struct Reading {
+    on_battery: bool,
+    battery_percent: Option<f32>,
+    thermal_pressure: bool,
+}
+
+fn read_battery() -> (bool, Option<f32>) {
+    let manager = match starship_battery::Manager::new() {
+        Ok(manager) => manager,
+        Err(e) => {
+            log::debug!("Battery info unavailable: {}", e);
+            return (false, None);
+        }
+    };
+
+    let batteries = match manager.batteries() {
+        Ok(batteries) => batteries.flatten().collect::<Vec<_>>(),
+        Err(e) => {
+            log::debug!("Failed to enumerate batteries: {}", e);
+            return (false, None);
+        }
+    };
+
+    // Desktops simply have no batteries and always count as "on AC".
+    if batteries.is_empty() {
+        return (false, None);
+    }
+
+    let on_battery = batteries
+        .iter()
+        .any(|b| b.state() == starship_battery::State::Discharging);
+    let percent = batteries
+        .iter()
+        .map(|b| b.state_of_charge().value * 100.0)
+        .sum::<f32>()
+        / batteries.len() as f32;
+
+    (on_battery, Some(percent))
+}
+
+#[cfg(target_os = "linux")]
+fn read_thermal_pressure() -> bool {
+    let Ok(zones) = std::fs::read_dir("/sys/class/thermal") else {
+        return false;
+    };
+
+    zones
+        .flatten()
+        .filter(|zone| zone.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|zone| std::fs::read_to_string(zone.path().join("temp")).ok())
+        .filter_map(|temp| temp.trim().parse::<f32>().ok())
+        .any(|millidegrees| millidegrees / 1000.0 >= THERMAL_LIMIT_CELSIUS)
+}
+
+#[cfg(target_os = "macos")]
+fn read_thermal_pressure() -> bool {
+    // `pmset -g therm` reports CPU_Speed_Limit < 100 while the OS is throttling.
+    let Ok(output) = std::process::Command::new("pmset").args(["-g", "therm"]).output() else {
+        return false;
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.contains("CPU_Speed_Limit"))
+        .filter_map(|line| line.split('=').nth(1))
+        .filter_map(|value| value.trim().parse::<u32>().ok())
+        .any(|limit| limit < 100)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn read_thermal_pressure() -> bool {
+    false
+}
+
+fn read_power() -> Reading {
+    let (on_battery, battery_percent) = read_battery();
+    Reading {
+        on_battery,
+        battery_percent,
+        thermal_pressure: read_thermal_pressure(),
+    }
+}
+
+fn resolve(reading: &Reading, profiles: &PowerProfiles) -> PowerState {
+    let low_battery = reading.on_battery
+        && reading
+            .battery_percent
+            .map_or(false, |p| p <= profiles.low_battery_percent);
+
+    let (profile, rule) = if reading.thermal_pressure {
+        (PowerProfileKind::Thermal, &profiles.thermal)
+    } else if low_battery {
+        (PowerProfileKind::LowBattery, &profiles.low_battery)
+    } else if reading.on_battery {
+        (PowerProfileKind::Battery, &profiles.battery)
+    } else {
+        (PowerProfileKind::Ac, &profiles.ac)
+    };
+
+    let throttle = if profiles.enabled { rule.clone() } else { profiles.ac.clone() };
+
+    PowerState {
+        on_battery: reading.on_battery,
+        battery_percent: reading.battery_percent,
+        thermal_pressure: reading.thermal_pressure,
+        profile,
+        throttle,
+    }
+}
+
+async fn refresh(app: &AppHandle) {
+    let reading = match tokio::task::spawn_blocking(read_power).await {
+        Ok(reading) => reading,
+        Err(e) => {
+            log::error!("Power state probe panicked: {}", e);
+            return;
+        }
+    };
+
+    let monitor = app.state::<PowerMonitor>();
+    let new_state = resolve(&reading, &monitor.profiles.lock().unwrap());
+
+    let changed = {
+        let mut state = monitor.state.lock().unwrap();
+        let changed = *state != new_state;
+        *state = new_state.clone();
+        changed
+    };
+
+    if changed {
+        log::info!(
+            "Power state changed: profile={:?}, battery={:?}, thermal={}",
+            new_state.profile,
+            new_state.battery_percent,
+            new_state.thermal_pressure
+        );
+        if let Err(e) = app.emit("power-state-changed", new_state) {
+            log::error!("Failed to emit power-state-changed event: {}", e);
+        }
+    }
+}
+
+pub fn start_monitor(app: AppHandle) {
+    *app.state::<PowerMonitor>().profiles.lock().unwrap() = storage::load_json(&app, PROFILES_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&app).await;
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_power_state(monitor: State<'_, PowerMonitor>) -> PowerState {
+    monitor.state.lock().unwrap().clone()
+}
+
+pub async fn state_handler(AxumState(state): AxumState<AppState>) -> Json<PowerState> {
+    Json(state.app_handle.state::<PowerMonitor>().state.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub fn get_power_profiles(monitor: State<'_, PowerMonitor>) -> PowerProfiles {
+    monitor.profiles.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub async fn set_power_profiles(
+    app: AppHandle,
+    profiles: PowerProfiles,
+    monitor: State<'_, PowerMonitor>,
+) -> Result<(), String> {
+    log::info!("Updating power profiles: {:?}", profiles);
+    storage::save_json(&app, PROFILES_FILE, &profiles)?;
+    *monitor.profiles.lock().unwrap() = profiles;
+    refresh(&app).await;
+    Ok(())
+}
@@ -0,0 +1,192 @@
+// In src-tauri/src/presets.rs
+//
+// Named generation parameter presets (temperature, top_p, num_ctx, seed,
+// stop sequences) kept in the backend, so tuning lives in one place instead
+// of in every agent's frontend code. A proxied request picks one with
+// `X-Observer-Preset: <name>`; without the header an agent's own default
+// preset is used. Each agent may also override single parameters on top of
+// the preset. Parameters the request sets itself always win.
+//
+// Native Ollama routes get the parameters in `options`; the OpenAI-compatible
+// `/v1` routes take them at the top level and have no `num_ctx`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::storage;
+
+const SETTINGS_FILE: &str = "parameter_presets.json";
+pub const PRESET_HEADER: &str = "x-observer-preset";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParameterPreset {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+}
+
+impl ParameterPreset {
+    fn is_empty(&self) -> bool {
+        self.temperature.is_none()
+            && self.top_p.is_none()
+            && self.num_ctx.is_none()
+            && self.seed.is_none()
+            && self.stop.is_none()
+    }
+
+    // `self` with whatever `over` sets replacing it.
+    fn overlay(&self, over: &ParameterPreset) -> ParameterPreset {
+        ParameterPreset {
+            temperature: over.temperature.or(self.temperature),
+            top_p: over.top_p.or(self.top_p),
+            num_ctx: over.num_ctx.or(self.num_ctx),
+            seed: over.seed.or(self.seed),
+            stop: over.stop.clone().or_else(|| self.stop.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentParameters {
+    // Used when the request names no preset.
+    pub preset: Option<String>,
+    pub overrides: ParameterPreset,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresetSettings {
+    pub presets: BTreeMap<String, ParameterPreset>,
+    pub agents: BTreeMap<String, AgentParameters>,
+}
+
+#[derive(Default)]
+pub struct PresetState {
+    settings: Mutex<PresetSettings>,
+}
+
+// Sets each parameter in `target` unless it's there already.
+fn fill(target: &mut Map<String, Value>, preset: &ParameterPreset, native: bool) {
+    let Ok(Value::Object(values)) = serde_json::to_value(preset) else {
+        return;
+    };
+    for (name, value) in values {
+        if name == "num_ctx" && !native {
+            continue;
+        }
+        target.entry(name).or_insert(value);
+    }
+}
+
+// The request body with the preset's parameters filled in; None when no
+// preset applies. Err when the request names a preset that doesn't exist.
+pub fn apply_request(
+    app: &AppHandle,
+    path: &str,
+    requested: Option<&str>,
+    agent_id: Option<&str>,
+    body: &[u8],
+) -> Result<Option<Vec<u8>>, String> {
+    let settings = app.state::<PresetState>().settings.lock().unwrap().clone();
+    let agent = agent_id.and_then(|id| settings.agents.get(id)).cloned().unwrap_or_default();
+    let base = match requested.or(agent.preset.as_deref()) {
+        Some(name) => {
+            settings.presets.get(name).cloned().ok_or_else(|| format!("Unknown parameter preset '{}'", name))?
+        }
+        None => ParameterPreset::default(),
+    };
+    let parameters = base.overlay(&agent.overrides);
+    if parameters.is_empty() {
+        return Ok(None);
+    }
+
+    let Ok(Value::Object(mut request)) = serde_json::from_slice::<Value>(body) else {
+        return Ok(None);
+    };
+    let native = path.starts_with("/api/");
+    if native {
+        let options = request.entry("options").or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(options) = options {
+            fill(options, &parameters, true);
+        }
+    } else {
+        fill(&mut request, &parameters, false);
+    }
+    Ok(serde_json::to_vec(&request).ok())
+}
+
+fn save(app: &AppHandle, state: &PresetState, settings: PresetSettings) -> Result<(), String> {
+    storage::save_json(app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<PresetState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn list_parameter_presets(state: State<'_, PresetState>) -> PresetSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn save_parameter_preset(
+    app: AppHandle,
+    name: String,
+    preset: ParameterPreset,
+    state: State<'_, PresetState>,
+) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("A preset needs a name".to_string());
+    }
+    let mut settings = state.settings.lock().unwrap().clone();
+    settings.presets.insert(name, preset);
+    save(&app, &state, settings)
+}
+
+#[tauri::command]
+pub fn delete_parameter_preset(app: AppHandle, name: String, state: State<'_, PresetState>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap().clone();
+    let user = settings.agents.iter().find(|(_, agent)| agent.preset.as_deref() == Some(name.as_str()));
+    if let Some((agent_id, _)) = user {
+        return Err(format!("Preset '{}' is still the default of agent {}", name, agent_id));
+    }
+    settings.presets.remove(&name);
+    save(&app, &state, settings)
+}
+
+// `parameters` of None returns the agent to the request's own parameters.
+#[tauri::command]
+pub fn set_agent_parameters(
+    app: AppHandle,
+    agent_id: String,
+    parameters: Option<AgentParameters>,
+    state: State<'_, PresetState>,
+) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap().clone();
+    match parameters {
+        Some(parameters) => {
+            if let Some(name) = parameters.preset.as_ref().filter(|name| !settings.presets.contains_key(*name)) {
+                return Err(format!("Unknown parameter preset '{}'", name));
+            }
+            settings.agents.insert(agent_id, parameters);
+        }
+        None => {
+            settings.agents.remove(&agent_id);
+        }
+    }
+    save(&app, &state, settings)
+}
@@ -0,0 +1,229 @@
+// In src-tauri/src/privacy.rs
+//
+// The global kill switch and the sensor overview behind the privacy
+// dashboard. Engaging the switch (command, tray item or Ctrl/Cmd+Shift+F12)
+// takes effect in the backend rather than relying on the web app to
+// comply: the screen recording is stopped, native capture and active window
+// tracking are refused, proxied model requests are rejected with 423, and
+// requests already streaming to a non-local backend are cut off. The web app
+// polls `GET /privacy/status` and stops its agents and streams when it sees
+// the switch engaged.
+//
+// The browser's own streams (getDisplayMedia, camera, microphone) aren't
+// visible from here, so the web app reports them via `POST /privacy/sensors`
+// and they're listed alongside the backend's sensors.
+
+use axum::{extract::State as AxumState, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::watch;
+
+use crate::activity::ActivityTracker;
+use crate::browser_bridge::BrowserBridge;
+use crate::{audit, recording, AppState};
+
+pub const SHORTCUT: &str = "CmdOrCtrl+Shift+F12";
+pub const TRAY_ITEM_ID: &str = "kill_switch";
+
+// Streams held by the web app, as last reported by it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserSensors {
+    pub screen_video: bool,
+    pub screen_audio: bool,
+    pub camera: bool,
+    pub microphone: bool,
+}
+
+pub struct PrivacyState {
+    engaged: watch::Sender<bool>,
+    browser: Mutex<Option<(BrowserSensors, DateTime<Utc>)>>,
+    // Proxied requests currently talking to a non-local backend.
+    remote_in_flight: AtomicUsize,
+}
+
+impl Default for PrivacyState {
+    fn default() -> Self {
+        Self {
+            engaged: watch::channel(false).0,
+            browser: Mutex::default(),
+            remote_in_flight: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SensorStatus {
+    pub sensor: &'static str,
+    pub active: bool,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivacyStatus {
+    pub kill_switch: bool,
+    pub sensors: Vec<SensorStatus>,
+    pub remote_requests_in_flight: usize,
+    pub browser_reported_at: Option<DateTime<Utc>>,
+}
+
+pub fn is_engaged(app: &AppHandle) -> bool {
+    *app.state::<PrivacyState>().engaged.borrow()
+}
+
+// Resolves once the kill switch is engaged.
+pub async fn engaged(app: &AppHandle) {
+    let mut receiver = app.state::<PrivacyState>().engaged.subscribe();
+    let _ = receiver.wait_for(|engaged| *engaged).await;
+}
+
+pub fn ensure_allowed(app: &AppHandle) -> Result<(), String> {
+    if is_engaged(app) {
+        return Err("The privacy kill switch is engaged".to_string());
+    }
+    Ok(())
+}
+
+// Whether a backend URL points at this machine.
+pub fn is_local_url(url: &str) -> bool {
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?'])
+        .next()
+        .unwrap_or("");
+    // Strip the port, keeping bracketed IPv6 addresses intact.
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !name.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+    host == "localhost"
+        || host.ends_with(".localhost")
+        || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+// Counts a request to a non-local backend for as long as it's alive.
+pub struct RemoteRequestGuard(AppHandle);
+
+impl RemoteRequestGuard {
+    pub fn new(app: &AppHandle) -> Self {
+        app.state::<PrivacyState>().remote_in_flight.fetch_add(1, Ordering::SeqCst);
+        Self(app.clone())
+    }
+}
+
+impl Drop for RemoteRequestGuard {
+    fn drop(&mut self) {
+        self.0.state::<PrivacyState>().remote_in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub async fn set_kill_switch(app: &AppHandle, engage: bool) {
+    let previous = app.state::<PrivacyState>().engaged.send_replace(engage);
+    if previous == engage {
+        return;
+    }
+    if engage {
+        log::warn!("Privacy kill switch engaged");
+        if let Err(e) = recording::stop(app).await {
+            log::debug!("No screen recording to stop: {}", e);
+        }
+    } else {
+        log::info!("Privacy kill switch released");
+    }
+    audit::record(
+        app,
+        None,
+        if engage { "privacy.kill_switch_engaged" } else { "privacy.kill_switch_released" },
+        serde_json::Value::Null,
+    );
+    if let Err(e) = app.emit("privacy-kill-switch", engage) {
+        log::error!("Failed to emit privacy-kill-switch event: {}", e);
+    }
+}
+
+// For the tray item and the shortcut.
+pub fn toggle(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let engage = !is_engaged(&app);
+        set_kill_switch(&app, engage).await;
+    });
+}
+
+pub fn status(app: &AppHandle) -> PrivacyStatus {
+    let state = app.state::<PrivacyState>();
+    let kill_switch = is_engaged(app);
+    let mut sensors = vec![
+        SensorStatus {
+            sensor: "screen_recording",
+            active: recording::is_recording(app),
+            detail: None,
+        },
+        SensorStatus {
+            sensor: "native_screen_capture",
+            active: crate::capture::in_progress(app),
+            detail: None,
+        },
+        SensorStatus {
+            sensor: "active_window",
+            active: !kill_switch && app.state::<ActivityTracker>().settings.lock().unwrap().enabled,
+            detail: None,
+        },
+    ];
+    let browsers = app.state::<BrowserBridge>().connected_browsers();
+    sensors.push(SensorStatus {
+        sensor: "browser_tabs",
+        active: !browsers.is_empty(),
+        detail: (!browsers.is_empty()).then(|| browsers.join(", ")),
+    });
+
+    let browser = state.browser.lock().unwrap().clone();
+    let (reported, reported_at) = match browser {
+        Some((sensors, at)) => (sensors, Some(at)),
+        None => (BrowserSensors::default(), None),
+    };
+    for (sensor, active) in [
+        ("screen_video", reported.screen_video),
+        ("screen_audio", reported.screen_audio),
+        ("camera", reported.camera),
+        ("microphone", reported.microphone),
+    ] {
+        sensors.push(SensorStatus { sensor, active, detail: Some("reported by the web app".to_string()) });
+    }
+
+    PrivacyStatus {
+        kill_switch,
+        sensors,
+        remote_requests_in_flight: state.remote_in_flight.load(Ordering::SeqCst),
+        browser_reported_at: reported_at,
+    }
+}
+
+pub async fn status_handler(AxumState(state): AxumState<AppState>) -> Json<PrivacyStatus> {
+    Json(status(&state.app_handle))
+}
+
+pub async fn sensors_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(sensors): Json<BrowserSensors>,
+) -> Json<PrivacyStatus> {
+    let app = &state.app_handle;
+    *app.state::<PrivacyState>().browser.lock().unwrap() = Some((sensors, Utc::now()));
+    Json(status(app))
+}
+
+#[tauri::command]
+pub async fn set_privacy_kill_switch(app: AppHandle, engaged: bool) -> PrivacyStatus {
+    set_kill_switch(&app, engaged).await;
+    status(&app)
+}
+
+#[tauri::command]
+pub fn get_privacy_status(app: AppHandle) -> PrivacyStatus {
+    status(&app)
+}
@@ -0,0 +1,260 @@
+// In src-tauri/src/profiles.rs
+//
+// Separate profiles (work/personal) with their own settings, agents,
+// history database and keyring namespace. The default profile keeps using
+// the app data directory as before; any other profile gets
+// `profiles/<id>/` beneath it, which storage.rs resolves transparently, so
+// modules don't know about profiles at all.
+//
+// Every module loads its settings once at startup, so switching restarts
+// the app into the new profile rather than trying to reload each one. A
+// profile can be locked, in which case the OS authentication prompt
+// (Windows Hello, the macOS/polkit password dialog) must succeed before it
+// opens.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tauri::AppHandle;
+
+use crate::storage;
+
+pub const PROFILES_DIR: &str = "profiles";
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_FILE: &str = "profiles.json";
+pub const TRAY_ID_PREFIX: &str = "profile:";
+
+// None while the default profile is active.
+static ACTIVE: RwLock<Option<String>> = RwLock::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub locked: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileRegistry {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileRegistry {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Default".to_string(),
+                locked: false,
+            }],
+        }
+    }
+}
+
+impl ProfileRegistry {
+    fn get(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+}
+
+// The active profile's directory under `profiles/`, or None for the default.
+pub fn active_dir_name() -> Option<String> {
+    ACTIVE.read().unwrap().clone()
+}
+
+pub fn active_id() -> String {
+    active_dir_name().unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+fn registry_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(storage::root_dir(app)?.join(PROFILES_FILE))
+}
+
+// Read directly rather than through `storage::load_json`, which resolves
+// paths inside the active profile.
+pub fn load_registry(app: &AppHandle) -> ProfileRegistry {
+    let mut registry: ProfileRegistry = registry_path(app)
+        .ok()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    if registry.get(DEFAULT_PROFILE).is_none() {
+        registry.profiles.insert(0, ProfileRegistry::default().profiles.remove(0));
+    }
+    registry
+}
+
+fn save_registry(app: &AppHandle, registry: &ProfileRegistry) -> Result<(), String> {
+    let path = registry_path(app)?;
+    let bytes = serde_json::to_vec_pretty(registry).map_err(|e| e.to_string())?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, bytes).map_err(|e| format!("Failed to write {:?}: {}", tmp_path, e))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to replace {:?}: {}", path, e))
+}
+
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+// Shows the OS authentication prompt; true if the user passed it.
+fn authenticate(reason: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    let status = {
+        let script = format!(
+            "Add-Type -AssemblyName System.Runtime.WindowsRuntime; \
+             $asTask = ([System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object {{ \
+               $_.Name -eq 'AsTask' -and $_.GetParameters().Count -eq 1 -and \
+               $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1' }})[0]; \
+             [Windows.Security.Credentials.UI.UserConsentVerifier,Windows.Security.Credentials.UI,ContentType=WindowsRuntime] | Out-Null; \
+             $op = [Windows.Security.Credentials.UI.UserConsentVerifier]::RequestVerificationAsync('{}'); \
+             $task = $asTask.MakeGenericMethod([Windows.Security.Credentials.UI.UserConsentVerificationResult]).Invoke($null, @($op)); \
+             $task.Wait(-1) | Out-Null; \
+             if ($task.Result -eq 'Verified') {{ exit 0 }} else {{ exit 1 }}",
+            reason.replace('\'', "''")
+        );
+        std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .status()
+    };
+    #[cfg(target_os = "macos")]
+    let status = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(format!(
+            "do shell script \"true\" with prompt \"{}\" with administrator privileges",
+            reason.replace('"', "'")
+        ))
+        .status();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let status = {
+        log::info!("Asking polkit to authenticate: {}", reason);
+        std::process::Command::new("pkexec").arg("true").status()
+    };
+
+    match status {
+        Ok(status) => status.success(),
+        Err(e) => {
+            log::error!("Failed to show the authentication prompt: {}", e);
+            false
+        }
+    }
+}
+
+// Picks the profile to start in; must run before anything reads storage.
+// A locked profile whose prompt is dismissed falls back to the first
+// unlocked one.
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let mut registry = load_registry(app);
+    let mut profile = registry.get(&registry.active).cloned().unwrap_or_else(|| registry.profiles[0].clone());
+    if profile.locked && !authenticate(&format!("Unlock the Observer profile \"{}\"", profile.name)) {
+        log::warn!("Authentication for profile '{}' failed", profile.id);
+        profile = registry
+            .profiles
+            .iter()
+            .find(|p| !p.locked)
+            .cloned()
+            .ok_or("Every profile is locked and authentication failed")?;
+        registry.active = profile.id.clone();
+        save_registry(app, &registry)?;
+    }
+    *ACTIVE.write().unwrap() = (profile.id != DEFAULT_PROFILE).then(|| profile.id.clone());
+    log::info!("Using profile '{}'", profile.id);
+    Ok(())
+}
+
+pub fn switch(app: &AppHandle, id: &str) -> Result<(), String> {
+    let mut registry = load_registry(app);
+    let profile = registry.get(id).cloned().ok_or_else(|| format!("Unknown profile '{}'", id))?;
+    if profile.id == active_id() {
+        return Ok(());
+    }
+    if profile.locked && !authenticate(&format!("Switch to the Observer profile \"{}\"", profile.name)) {
+        return Err("Authentication failed".to_string());
+    }
+    registry.active = profile.id.clone();
+    save_registry(app, &registry)?;
+    log::info!("Switching to profile '{}', restarting", profile.id);
+    app.restart();
+}
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> ProfileRegistry {
+    let mut registry = load_registry(&app);
+    // What's running, even if the file says otherwise.
+    registry.active = active_id();
+    registry
+}
+
+#[tauri::command]
+pub fn create_profile(app: AppHandle, name: String, locked: Option<bool>) -> Result<Profile, String> {
+    let id = slug(&name);
+    if id.is_empty() {
+        return Err("Profile names need at least one letter or digit".to_string());
+    }
+    let mut registry = load_registry(&app);
+    if registry.get(&id).is_some() {
+        return Err(format!("A profile '{}' already exists", id));
+    }
+    let profile = Profile {
+        id,
+        name: name.trim().to_string(),
+        locked: locked.unwrap_or(false),
+    };
+    registry.profiles.push(profile.clone());
+    save_registry(&app, &registry)?;
+    Ok(profile)
+}
+
+// Deletes the profile's directory too. The keyring entries stay behind; the
+// OS keyring can't list them by prefix.
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, id: String) -> Result<(), String> {
+    if id == DEFAULT_PROFILE || id == active_id() {
+        return Err("The default and the active profile can't be deleted".to_string());
+    }
+    let mut registry = load_registry(&app);
+    let profile = registry.get(&id).cloned().ok_or_else(|| format!("Unknown profile '{}'", id))?;
+    if profile.locked && !authenticate(&format!("Delete the Observer profile \"{}\"", profile.name)) {
+        return Err("Authentication failed".to_string());
+    }
+    registry.profiles.retain(|p| p.id != id);
+    save_registry(&app, &registry)?;
+    let dir = storage::root_dir(&app)?.join(PROFILES_DIR).join(&id);
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("Failed to remove {:?}: {}", dir, e))?;
+    }
+    Ok(())
+}
+
+// Changing the lock of a locked profile needs authentication as well.
+#[tauri::command]
+pub fn set_profile_locked(app: AppHandle, id: String, locked: bool) -> Result<(), String> {
+    let mut registry = load_registry(&app);
+    let profile = registry
+        .profiles
+        .iter_mut()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Unknown profile '{}'", id))?;
+    if profile.locked && !locked && !authenticate(&format!("Unlock the Observer profile \"{}\"", profile.name)) {
+        return Err("Authentication failed".to_string());
+    }
+    profile.locked = locked;
+    save_registry(&app, &registry)
+}
+
+// Restarts the app on success.
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, id: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || switch(&app, &id))
+        .await
+        .map_err(|e| e.to_string())?
+}
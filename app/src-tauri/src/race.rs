@@ -0,0 +1,222 @@
+// In src-tauri/src/race.rs
+//
+// Optional race mode for short interactive prompts: a chat or generate call
+// goes to the selected Ollama server and to a challenger (typically a small
+// local model against a big remote one, or the other way round) at the same
+// time. Whichever first streams back something that isn't an error wins and
+// is proxied; the other request is dropped, which makes Ollama stop
+// generating. It trades compute for responsiveness, so it's off by default
+// and only applies to prompts up to `max_prompt_chars` without images.
+//
+// The challenger is subject to the same backend policy and data-locality
+// rules as the selected server. The winner is named in the
+// `X-Observer-Race-Winner` response header.
+
+use axum::{
+    body::Bytes,
+    http::{header, response::Builder, HeaderMap, HeaderValue},
+};
+use futures::future::{self, Either};
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{llm, locality, policy, privacy, storage};
+use observer_core::proxy;
+
+const SETTINGS_FILE: &str = "race.json";
+const RACED_PATHS: &[&str] = &["/api/chat", "/api/generate"];
+pub const WINNER_HEADER: &str = "x-observer-race-winner";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RaceSettings {
+    pub enabled: bool,
+    pub challenger_url: String,
+    // The model to ask there; None asks for the same model.
+    pub challenger_model: Option<String>,
+    pub max_prompt_chars: usize,
+}
+
+impl Default for RaceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            challenger_url: String::new(),
+            challenger_model: None,
+            max_prompt_chars: 2000,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RaceState {
+    settings: Mutex<RaceSettings>,
+}
+
+type Upstream = BoxStream<'static, reqwest::Result<Bytes>>;
+
+// The winning response, ready to be streamed back.
+pub struct Raced {
+    pub response: Builder,
+    pub stream: Upstream,
+    // The winner's base URL.
+    pub backend: String,
+}
+
+// Prompt text length, or None for requests with images.
+fn prompt_chars(body: &Value) -> Option<usize> {
+    let has_images = |value: &Value| value["images"].as_array().is_some_and(|images| !images.is_empty());
+    if has_images(body) {
+        return None;
+    }
+    let mut chars = body["prompt"].as_str().map_or(0, |prompt| prompt.chars().count());
+    for message in body["messages"].as_array().into_iter().flatten() {
+        if has_images(message) {
+            return None;
+        }
+        chars += message["content"].as_str().map_or(0, |content| content.chars().count());
+    }
+    Some(chars)
+}
+
+// Sends the request and waits for the first chunk with content. Err if the
+// backend fails, answers with an error, or sends nothing.
+async fn first_chunk(url: &str, headers: HeaderMap, body: Vec<u8>) -> Result<(Builder, Upstream), String> {
+    let response = llm::client()
+        .post(url)
+        .headers(headers)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("{}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{} answered {}", url, response.status()));
+    }
+    let builder = proxy::response_builder(&response);
+    let mut rest = response.bytes_stream();
+    let first = loop {
+        match rest.next().await {
+            Some(Ok(chunk)) if chunk.iter().all(u8::is_ascii_whitespace) => continue,
+            Some(Ok(chunk)) => break chunk,
+            Some(Err(e)) => return Err(format!("{}: {}", url, e)),
+            None => return Err(format!("{} sent an empty response", url)),
+        }
+    };
+    let first_line = first.split(|b| *b == b'\n').next().unwrap_or_default();
+    if let Ok(line) = serde_json::from_slice::<Value>(first_line) {
+        if let Some(error) = line["error"].as_str() {
+            return Err(format!("{}: {}", url, error));
+        }
+    }
+    Ok((builder, stream::once(future::ready(Ok(first))).chain(rest).boxed()))
+}
+
+pub async fn contend(
+    app: &AppHandle,
+    backend: String,
+    path: &str,
+    headers: HeaderMap,
+    body: Vec<u8>,
+) -> Result<Raced, String> {
+    let url = format!("{}{}", backend, path);
+    let remote = !privacy::is_local_url(&url);
+    let _guard = remote.then(|| privacy::RemoteRequestGuard::new(app));
+    tokio::select! {
+        result = first_chunk(&url, headers, body) => {
+            result.map(|(response, stream)| Raced { response, stream, backend })
+        }
+        _ = privacy::engaged(app), if remote => Err(format!("{}: the privacy kill switch is engaged", url)),
+    }
+}
+
+// Races `body` against the challenger when race mode applies to it; None
+// means the request should be proxied as usual.
+pub async fn race(
+    app: &AppHandle,
+    path: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    agent_id: Option<&str>,
+) -> Option<Raced> {
+    let settings = app.state::<RaceState>().settings.lock().unwrap().clone();
+    if !settings.enabled || settings.challenger_url.trim().is_empty() || !RACED_PATHS.contains(&path) {
+        return None;
+    }
+    let request: Value = serde_json::from_slice(body).ok()?;
+    if prompt_chars(&request)? > settings.max_prompt_chars {
+        return None;
+    }
+
+    let challenger_base = settings.challenger_url.trim().trim_end_matches('/').to_string();
+    if let Err(e) = policy::check_backend(&challenger_base) {
+        log::warn!("Not racing {}: {}", path, e);
+        return None;
+    }
+    let content = locality::sensitive_content(headers, body);
+    if locality::check(app, agent_id, &challenger_base, content).is_err() {
+        return None;
+    }
+    let mut challenger_body = request;
+    if let Some(model) = &settings.challenger_model {
+        challenger_body["model"] = Value::String(model.clone());
+    }
+
+    let mut primary_headers = headers.clone();
+    primary_headers.remove(header::CONTENT_LENGTH);
+    let mut challenger_headers = primary_headers.clone();
+    challenger_headers.remove(header::HOST);
+
+    let started = Instant::now();
+    let primary = Box::pin(contend(app, llm::ollama_base_url(app), path, primary_headers, body.to_vec()));
+    let challenger_body = challenger_body.to_string().into_bytes();
+    let challenger = Box::pin(contend(app, challenger_base, path, challenger_headers, challenger_body));
+    // Dropping the loser closes its connection.
+    let (result, winner) = match future::select(primary, challenger).await {
+        Either::Left((Ok(won), _)) => (Ok(won), "primary"),
+        Either::Right((Ok(won), _)) => (Ok(won), "challenger"),
+        Either::Left((Err(e), challenger)) => {
+            log::warn!("Race for {}: the selected server failed ({})", path, e);
+            (challenger.await, "challenger")
+        }
+        Either::Right((Err(e), primary)) => {
+            log::warn!("Race for {}: the challenger failed ({})", path, e);
+            (primary.await, "primary")
+        }
+    };
+    let mut raced = match result {
+        Ok(won) => won,
+        Err(e) => {
+            log::warn!("Race for {}: both backends failed ({}); proxying as usual", path, e);
+            return None;
+        }
+    };
+    log::info!("Race for {}: {} answered first after {}ms", path, winner, started.elapsed().as_millis());
+    if let Some(headers) = raced.response.headers_mut() {
+        headers.insert(WINNER_HEADER, HeaderValue::from_static(winner));
+    }
+    Some(raced)
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<RaceState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_race_settings(state: State<'_, RaceState>) -> RaceSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_race_settings(app: AppHandle, settings: RaceSettings, state: State<'_, RaceState>) -> Result<(), String> {
+    if settings.enabled {
+        policy::check_backend(settings.challenger_url.trim())?;
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    log::info!("Race mode {}", if settings.enabled { "enabled" } else { "disabled" });
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
@@ -0,0 +1,224 @@
+// In src-tauri/src/recording.rs
+//
+// Native screen recording to an mp4 in app data, done by an ffmpeg child
+// process using the platform's grabber (gdigrab, avfoundation, x11grab).
+// While a recording runs, agents can mark highlights ("error dialog
+// appeared") via `add_recording_highlight` or `POST /recordings/highlight`;
+// they're stored as offsets into the video in `<name>.highlights.json` next
+// to it, so the player can jump straight to the interesting moments.
+
+use axum::{extract::State as AxumState, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, Command};
+
+use crate::features::{self, Feature};
+use crate::{incognito, privacy, storage, AppState};
+
+const RECORDINGS_DIR: &str = "recordings";
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Highlight {
+    // Seconds from the start of the recording.
+    pub offset_seconds: f64,
+    pub label: String,
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub id: String,
+    pub path: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub highlights: Vec<Highlight>,
+}
+
+struct ActiveRecording {
+    recording: Recording,
+    child: Child,
+}
+
+#[derive(Default)]
+pub struct RecordingState {
+    // Held across the ffmpeg start/stop awaits, hence the async mutex.
+    active: tokio::sync::Mutex<Option<ActiveRecording>>,
+    started_at: Mutex<Option<DateTime<Utc>>>,
+    highlights: Mutex<Vec<Highlight>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HighlightRequest {
+    pub label: String,
+    pub agent_id: Option<String>,
+}
+
+fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = storage::data_path(app, RECORDINGS_DIR)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir)
+}
+
+fn highlights_path(video: &Path) -> PathBuf {
+    video.with_extension("highlights.json")
+}
+
+// ffmpeg input arguments for grabbing the whole desktop.
+fn grab_args(fps: u32) -> Vec<String> {
+    let fps = fps.to_string();
+    #[cfg(target_os = "windows")]
+    let args = ["-f", "gdigrab", "-framerate", fps.as_str(), "-i", "desktop"];
+    #[cfg(target_os = "macos")]
+    let args = ["-f", "avfoundation", "-framerate", fps.as_str(), "-capture_cursor", "1", "-i", "1:none"];
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    let display = std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string());
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    let args = ["-f", "x11grab", "-framerate", fps.as_str(), "-i", display.as_str()];
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+pub fn add_highlight(app: &AppHandle, label: String, agent_id: Option<String>) -> Result<Highlight, String> {
+    let state = app.state::<RecordingState>();
+    let started_at = state.started_at.lock().unwrap().ok_or("No screen recording is running")?;
+
+    let highlight = Highlight {
+        offset_seconds: (Utc::now() - started_at).num_milliseconds() as f64 / 1000.0,
+        label,
+        agent_id,
+    };
+    state.highlights.lock().unwrap().push(highlight.clone());
+    if let Err(e) = app.emit("recording-highlight", &highlight) {
+        log::error!("Failed to emit recording-highlight event: {}", e);
+    }
+    Ok(highlight)
+}
+
+pub async fn highlight_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(request): Json<HighlightRequest>,
+) -> Result<Json<Highlight>, (StatusCode, String)> {
+    add_highlight(&state.app_handle, request.label, request.agent_id)
+        .map(Json)
+        .map_err(|e| (StatusCode::CONFLICT, e))
+}
+
+pub fn is_recording(app: &AppHandle) -> bool {
+    app.state::<RecordingState>().started_at.lock().unwrap().is_some()
+}
+
+#[tauri::command]
+pub async fn start_screen_recording(
+    app: AppHandle,
+    fps: Option<u32>,
+    state: State<'_, RecordingState>,
+) -> Result<Recording, String> {
+    features::ensure(Feature::Capture)?;
+    privacy::ensure_allowed(&app)?;
+    incognito::ensure_persistent()?;
+    let mut active = state.active.lock().await;
+    if active.is_some() {
+        return Err("A screen recording is already running".to_string());
+    }
+
+    let started_at = Utc::now();
+    let id = started_at.format("%Y%m%d-%H%M%S").to_string();
+    let path = recordings_dir(&app)?.join(format!("screen-{}.mp4", id));
+    let mut child = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-y"])
+        .args(grab_args(fps.unwrap_or(15).clamp(1, 60)))
+        // Even dimensions are required by libx264's yuv420p.
+        .args(["-vf", "scale=trunc(iw/2)*2:trunc(ih/2)*2"])
+        .args(["-c:v", "libx264", "-preset", "veryfast", "-crf", "28", "-pix_fmt", "yuv420p"])
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start ffmpeg (is it installed?): {}", e))?;
+
+    // A grabber that can't open the screen (e.g. x11grab on Wayland) exits
+    // right away.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    if let Ok(Some(status)) = child.try_wait() {
+        return Err(format!("ffmpeg could not capture the screen ({})", status));
+    }
+
+    let recording = Recording {
+        id,
+        path: path.to_string_lossy().to_string(),
+        started_at,
+        ended_at: None,
+        highlights: Vec::new(),
+    };
+    log::info!("Screen recording started: {}", recording.path);
+    state.highlights.lock().unwrap().clear();
+    *state.started_at.lock().unwrap() = Some(started_at);
+    *active = Some(ActiveRecording { recording: recording.clone(), child });
+    Ok(recording)
+}
+
+pub async fn stop(app: &AppHandle) -> Result<Recording, String> {
+    let state = app.state::<RecordingState>();
+    let ActiveRecording { mut recording, mut child } =
+        state.active.lock().await.take().ok_or("No screen recording is running")?;
+    *state.started_at.lock().unwrap() = None;
+
+    // "q" asks ffmpeg to finish the file properly; killing it would leave
+    // an mp4 without its index.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(b"q").await;
+    }
+    match tokio::time::timeout(STOP_TIMEOUT, child.wait()).await {
+        Ok(Ok(status)) if !status.success() => log::warn!("ffmpeg exited with {}", status),
+        Ok(Err(e)) => log::warn!("Failed to wait for ffmpeg: {}", e),
+        Err(_) => {
+            log::warn!("ffmpeg did not stop in time, killing it");
+            let _ = child.kill().await;
+        }
+        _ => {}
+    }
+
+    recording.ended_at = Some(Utc::now());
+    recording.highlights = std::mem::take(&mut *state.highlights.lock().unwrap());
+    let json = serde_json::to_vec_pretty(&recording).map_err(|e| e.to_string())?;
+    let sidecar = highlights_path(Path::new(&recording.path));
+    std::fs::write(&sidecar, json).map_err(|e| format!("Failed to write {:?}: {}", sidecar, e))?;
+    log::info!(
+        "Screen recording saved: {} ({} highlights)",
+        recording.path,
+        recording.highlights.len()
+    );
+    Ok(recording)
+}
+
+#[tauri::command]
+pub async fn stop_screen_recording(app: AppHandle) -> Result<Recording, String> {
+    stop(&app).await
+}
+
+#[tauri::command]
+pub fn add_recording_highlight(app: AppHandle, label: String, agent_id: Option<String>) -> Result<Highlight, String> {
+    add_highlight(&app, label, agent_id)
+}
+
+// Finished recordings, newest first.
+#[tauri::command]
+pub fn list_screen_recordings(app: AppHandle) -> Result<Vec<Recording>, String> {
+    let mut recordings: Vec<Recording> = std::fs::read_dir(recordings_dir(&app)?)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.to_string_lossy().ends_with(".highlights.json"))
+        .filter_map(|p| serde_json::from_slice(&std::fs::read(p).ok()?).ok())
+        .collect();
+    recordings.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(recordings)
+}
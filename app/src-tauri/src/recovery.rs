@@ -0,0 +1,174 @@
+// In src-tauri/src/recovery.rs
+//
+// Startup checks that keep a crash or a bad disk write from costing data.
+// Before the history database is opened, it gets a SQLite quick_check, and
+// every settings file in the data directory must parse. Anything damaged is
+// moved aside as `<name>.corrupt-<time>`, never deleted, and replaced from
+// the newest scheduled backup (see backup.rs) whose copy still matches its
+// checksum. Without such a backup the app starts with a fresh database or
+// default settings instead of refusing to start, or quietly overwriting the
+// damaged file with defaults later.
+//
+// What happened is kept for the frontend (`get_recovery_report`) and
+// emitted as "recovery-report" when anything was wrong.
+
+use chrono::{DateTime, Local, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::backup::{self, BackupSnapshot};
+use crate::{history, storage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    // Put back from the backup in `backup`.
+    Restored,
+    // No usable backup; the file starts over empty.
+    Reset,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveredFile {
+    pub file: String,
+    pub problem: String,
+    pub action: RecoveryAction,
+    pub backup: Option<String>,
+    // Where the damaged file was moved.
+    pub moved_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryReport {
+    pub checked_at: DateTime<Utc>,
+    pub files_checked: usize,
+    pub recovered: Vec<RecoveredFile>,
+}
+
+#[derive(Default)]
+pub struct RecoveryState(Mutex<Option<RecoveryReport>>);
+
+// Err with whatever SQLite found wrong.
+fn check_database(path: &Path) -> Result<(), String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE).map_err(|e| e.to_string())?;
+    let result: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+    if result == "ok" {
+        Ok(())
+    } else {
+        Err(result)
+    }
+}
+
+fn check_json(path: &Path) -> Result<(), String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice::<serde_json::Value>(&bytes).map(|_| ()).map_err(|e| e.to_string())
+}
+
+// Renames `path` (and SQLite's -wal/-shm next to it) out of the way.
+fn move_aside(path: &Path) -> Option<PathBuf> {
+    let suffix = format!("corrupt-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let moved = path.with_file_name(format!("{}.{}", name, suffix));
+    if let Err(e) = std::fs::rename(path, &moved) {
+        log::error!("Failed to move damaged {:?} aside: {}", path, e);
+        return None;
+    }
+    for extra in ["-wal", "-shm"] {
+        let sidecar = path.with_file_name(format!("{}{}", name, extra));
+        if sidecar.exists() {
+            let _ = std::fs::rename(&sidecar, path.with_file_name(format!("{}{}.{}", name, extra, suffix)));
+        }
+    }
+    Some(moved)
+}
+
+// Replaces a damaged `path` with the newest good copy of `name`, checked
+// with `valid` after it's copied.
+fn recover(
+    path: &Path,
+    name: &str,
+    problem: String,
+    snapshots: &[BackupSnapshot],
+    valid: fn(&Path) -> Result<(), String>,
+) -> RecoveredFile {
+    log::error!("{} is damaged ({}); recovering", name, problem);
+    let moved_to = move_aside(path);
+    for snapshot in snapshots {
+        let Some(copy) = backup::verified_data_file(snapshot, name) else {
+            continue;
+        };
+        if valid(&copy).is_err() {
+            continue;
+        }
+        match std::fs::copy(&copy, path) {
+            Ok(_) => {
+                log::info!("Restored {} from the backup of {}", name, snapshot.created_at);
+                return RecoveredFile {
+                    file: name.to_string(),
+                    problem,
+                    action: RecoveryAction::Restored,
+                    backup: Some(snapshot.id.clone()),
+                    moved_to: moved_to.map(|p| p.to_string_lossy().to_string()),
+                };
+            }
+            Err(e) => log::error!("Failed to restore {} from {}: {}", name, snapshot.id, e),
+        }
+    }
+    log::warn!("No good backup of {}; starting it over", name);
+    RecoveredFile {
+        file: name.to_string(),
+        problem,
+        action: RecoveryAction::Reset,
+        backup: None,
+        moved_to: moved_to.map(|p| p.to_string_lossy().to_string()),
+    }
+}
+
+// Runs before history::init and the modules that load settings.
+pub fn run(app: &AppHandle) -> Result<(), String> {
+    let dir = storage::data_dir(app)?;
+    let snapshots = backup::list_snapshots(app).unwrap_or_default();
+    let mut recovered = Vec::new();
+    let mut files_checked = 0;
+
+    let db_path = dir.join(history::DB_FILE);
+    if db_path.exists() {
+        files_checked += 1;
+        if let Err(problem) = check_database(&db_path) {
+            recovered.push(recover(&db_path, history::DB_FILE, problem, &snapshots, check_database));
+        }
+    }
+
+    let mut settings: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    settings.sort();
+    for path in settings {
+        files_checked += 1;
+        if let Err(problem) = check_json(&path) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            recovered.push(recover(&path, &name, problem, &snapshots, check_json));
+        }
+    }
+
+    let report = RecoveryReport { checked_at: Utc::now(), files_checked, recovered };
+    if !report.recovered.is_empty() {
+        if let Err(e) = app.emit("recovery-report", &report) {
+            log::error!("Failed to emit recovery-report event: {}", e);
+        }
+    }
+    *app.state::<RecoveryState>().0.lock().unwrap() = Some(report);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_recovery_report(state: State<'_, RecoveryState>) -> Option<RecoveryReport> {
+    state.0.lock().unwrap().clone()
+}
@@ -0,0 +1,134 @@
+// In src-tauri/src/redact.rs
+//
+// Redaction of personal details and secrets from text that leaves the app,
+// such as dataset exports (see dataset.rs). Detection is deliberately
+// simple and word-based: email addresses, long digit runs (phone, card and
+// account numbers), API keys and tokens with well-known prefixes or that look
+// random, and any terms the user lists (names, project codenames). Terms
+// match case-insensitively for ASCII.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::storage;
+
+const SETTINGS_FILE: &str = "redaction.json";
+const SECRET_PREFIXES: &[&str] = &["sk-", "sk_", "pk_", "ghp_", "gho_", "github_pat_", "xox", "AKIA", "AIza", "hf_"];
+// Digits in one word before it counts as a number worth hiding.
+const MIN_DIGITS: usize = 9;
+// Letters and digits mixed over this length look like a generated token.
+const MIN_TOKEN_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionSettings {
+    pub emails: bool,
+    pub numbers: bool,
+    pub secrets: bool,
+    pub terms: Vec<String>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self { emails: true, numbers: true, secrets: true, terms: Vec::new() }
+    }
+}
+
+pub fn settings(app: &AppHandle) -> RedactionSettings {
+    storage::load_json(app, SETTINGS_FILE)
+}
+
+fn is_email(word: &str) -> bool {
+    let Some((local, domain)) = word.split_once('@') else {
+        return false;
+    };
+    !local.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.')
+}
+
+fn is_number(word: &str) -> bool {
+    word.chars().filter(char::is_ascii_digit).count() >= MIN_DIGITS
+        && word.chars().all(|c| c.is_ascii_digit() || "-.+()/".contains(c))
+}
+
+fn is_secret(word: &str) -> bool {
+    if SECRET_PREFIXES.iter().any(|prefix| word.starts_with(prefix) && word.len() > prefix.len() + 8) {
+        return true;
+    }
+    // JSON web tokens: three base64 parts, the first an encoded '{"'.
+    if word.starts_with("eyJ") && word.matches('.').count() == 2 {
+        return true;
+    }
+    word.len() >= MIN_TOKEN_LEN
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || "-_+/=".contains(c))
+        && word.chars().any(|c| c.is_ascii_digit())
+        && word.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+// Replaces each of `terms` wherever it occurs.
+fn redact_terms(text: &str, terms: &[String]) -> String {
+    let mut text = text.to_string();
+    for term in terms.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        let needle = term.to_ascii_lowercase();
+        let mut result = String::with_capacity(text.len());
+        let lower = text.to_ascii_lowercase();
+        let mut rest = 0;
+        while let Some(found) = lower[rest..].find(&needle) {
+            result.push_str(&text[rest..rest + found]);
+            result.push_str("[REDACTED]");
+            rest += found + needle.len();
+        }
+        result.push_str(&text[rest..]);
+        text = result;
+    }
+    text
+}
+
+pub fn redact(settings: &RedactionSettings, text: &str) -> String {
+    let text = redact_terms(text, &settings.terms);
+    let mut result = String::with_capacity(text.len());
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let trailing = &piece[word.len()..];
+        // Leave surrounding punctuation, e.g. "(me@example.com)," keeps "(" and "),".
+        let core = word.trim_matches(|c: char| "()[]{}<>\"'`,;:!?".contains(c));
+        let core = core.strip_suffix('.').unwrap_or(core);
+        let start = core.as_ptr() as usize - word.as_ptr() as usize;
+        let replacement = if core.is_empty() {
+            None
+        } else if settings.emails && is_email(core) {
+            Some("[EMAIL]")
+        } else if settings.secrets && is_secret(core) {
+            Some("[SECRET]")
+        } else if settings.numbers && is_number(core) {
+            Some("[NUMBER]")
+        } else {
+            None
+        };
+        match replacement {
+            Some(replacement) => {
+                result.push_str(&word[..start]);
+                result.push_str(replacement);
+                result.push_str(&word[start + core.len()..]);
+            }
+            None => result.push_str(word),
+        }
+        result.push_str(trailing);
+    }
+    result
+}
+
+#[tauri::command]
+pub fn get_redaction_settings(app: AppHandle) -> RedactionSettings {
+    settings(&app)
+}
+
+#[tauri::command]
+pub fn set_redaction_settings(app: AppHandle, settings: RedactionSettings) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)
+}
+
+// Shows what `redact` would make of `text`, for trying the settings out.
+#[tauri::command]
+pub fn preview_redaction(app: AppHandle, text: String) -> String {
+    redact(&settings(&app), &text)
+}
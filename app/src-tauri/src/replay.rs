@@ -0,0 +1,271 @@
+// In src-tauri/src/replay.rs
+//
+// Deterministic replay, for reproducing and reporting bad agent behaviour.
+// With the debug mode on, every proxied request from an agent gets a fixed
+// seed unless it sets its own, and is recorded as it was sent: backend,
+// path and the final body after every rewrite the proxy made, together with
+// the answer that came back. Runs are keyed by their request ID (see
+// request_id.rs), which also tags their log lines.
+//
+// `replay_run` sends the recorded request again, unchanged, and says whether
+// the answer text came out the same. Only the newest `max_runs` are kept.
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::{incognito, llm, policy, privacy, request_id, storage};
+
+const SETTINGS_FILE: &str = "replay.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplaySettings {
+    pub enabled: bool,
+    pub seed: i64,
+    pub max_runs: usize,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        Self { enabled: false, seed: 42, max_runs: 1000 }
+    }
+}
+
+#[derive(Default)]
+pub struct ReplayState {
+    settings: Mutex<ReplaySettings>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayRun {
+    pub run_id: String,
+    pub created_at: DateTime<Utc>,
+    pub agent_id: Option<String>,
+    pub model: Option<String>,
+    pub backend: String,
+    pub path: String,
+    pub request: Value,
+    pub answer: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayResult {
+    pub run_id: String,
+    pub original: Option<String>,
+    pub replayed: String,
+    pub identical: bool,
+}
+
+// Stores the answer to a recorded request once it has been read.
+pub struct Recorder {
+    app: AppHandle,
+    run_id: String,
+}
+
+// The generated text in an answer, streamed or not, native or OpenAI style.
+fn answer_text(body: &[u8]) -> String {
+    let mut text = String::new();
+    for line in String::from_utf8_lossy(body).lines() {
+        let line = line.trim();
+        let line = line.strip_prefix("data:").map_or(line, str::trim_start);
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let parts = [
+            &value["response"],
+            &value["message"]["content"],
+            &value["choices"][0]["delta"]["content"],
+            &value["choices"][0]["message"]["content"],
+            &value["choices"][0]["text"],
+        ];
+        text.extend(parts.iter().filter_map(|part| part.as_str()));
+    }
+    text
+}
+
+// Pins the seed in `request` unless it has one.
+fn pin_seed(request: &mut Map<String, Value>, native: bool, seed: i64) {
+    let target = if native {
+        match request.entry("options").or_insert_with(|| Value::Object(Map::new())) {
+            Value::Object(options) => options,
+            _ => return,
+        }
+    } else {
+        request
+    };
+    target.entry("seed").or_insert(Value::from(seed));
+}
+
+// For agent requests while the debug mode is on: the body with its seed
+// pinned, and the recorder for the answer.
+pub fn record_request(
+    app: &AppHandle,
+    path: &str,
+    agent_id: Option<&str>,
+    backend: &str,
+    body: &[u8],
+) -> Option<(Vec<u8>, Recorder)> {
+    let settings = app.state::<ReplayState>().settings.lock().unwrap().clone();
+    // Runs aren't recorded while incognito.
+    let agent_id = agent_id.filter(|_| settings.enabled && !incognito::is_active())?;
+    let Ok(Value::Object(mut request)) = serde_json::from_slice::<Value>(body) else {
+        return None;
+    };
+    pin_seed(&mut request, path.starts_with("/api/"), settings.seed);
+    let body = serde_json::to_vec(&request).ok()?;
+
+    let run_id = request_id::current().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let model = request.get("model").and_then(Value::as_str);
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    let result = conn
+        .execute(
+            "INSERT OR REPLACE INTO replay_runs (run_id, created_at, agent_id, model, backend, path, request)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![run_id, Utc::now().timestamp_millis(), agent_id, model, backend, path, body],
+        )
+        .and_then(|_| {
+            conn.execute(
+                "DELETE FROM replay_runs WHERE run_id NOT IN
+                 (SELECT run_id FROM replay_runs ORDER BY created_at DESC LIMIT ?1)",
+                params![settings.max_runs as i64],
+            )
+        });
+    if let Err(e) = result {
+        log::error!("Failed to record run {} for replay: {}", run_id, e);
+        return None;
+    }
+    Some((body, Recorder { app: app.clone(), run_id }))
+}
+
+impl Recorder {
+    pub fn wrap(
+        self,
+        upstream: BoxStream<'static, reqwest::Result<axum::body::Bytes>>,
+    ) -> BoxStream<'static, reqwest::Result<axum::body::Bytes>> {
+        async_stream::stream! {
+            let mut upstream = upstream;
+            let mut answer = Vec::new();
+            while let Some(chunk) = upstream.next().await {
+                if let Ok(bytes) = &chunk {
+                    answer.extend_from_slice(bytes);
+                }
+                yield chunk;
+            }
+            // A session started while the answer streamed.
+            if incognito::is_active() {
+                return;
+            }
+            let db = self.app.state::<HistoryDb>();
+            let conn = db.0.lock().unwrap();
+            if let Err(e) = conn.execute(
+                "UPDATE replay_runs SET answer = ?1 WHERE run_id = ?2",
+                params![String::from_utf8_lossy(&answer), self.run_id],
+            ) {
+                log::error!("Failed to record the answer of run {}: {}", self.run_id, e);
+            }
+        }
+        .boxed()
+    }
+}
+
+fn row_to_run(row: &rusqlite::Row) -> rusqlite::Result<ReplayRun> {
+    let request: Vec<u8> = row.get(6)?;
+    Ok(ReplayRun {
+        run_id: row.get(0)?,
+        created_at: Utc.timestamp_millis_opt(row.get(1)?).single().unwrap_or_default(),
+        agent_id: row.get(2)?,
+        model: row.get(3)?,
+        backend: row.get(4)?,
+        path: row.get(5)?,
+        request: serde_json::from_slice(&request).unwrap_or_default(),
+        answer: row.get(7)?,
+    })
+}
+
+const RUN_COLUMNS: &str = "run_id, created_at, agent_id, model, backend, path, request, answer";
+
+fn get_run(db: &HistoryDb, run_id: &str) -> Result<ReplayRun, String> {
+    let conn = db.0.lock().unwrap();
+    conn.query_row(
+        &format!("SELECT {} FROM replay_runs WHERE run_id = ?1", RUN_COLUMNS),
+        params![run_id],
+        row_to_run,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("No recorded run '{}'", run_id))
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<ReplayState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_replay_settings(state: State<'_, ReplayState>) -> ReplaySettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_replay_settings(
+    app: AppHandle,
+    settings: ReplaySettings,
+    state: State<'_, ReplayState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    log::info!("Replay recording {}", if settings.enabled { "enabled" } else { "disabled" });
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_replay_runs(
+    agent_id: Option<String>,
+    limit: Option<u32>,
+    db: State<'_, HistoryDb>,
+) -> Result<Vec<ReplayRun>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM replay_runs WHERE (?1 IS NULL OR agent_id = ?1) ORDER BY created_at DESC LIMIT ?2",
+            RUN_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![agent_id, limit.unwrap_or(100)], row_to_run).map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| format!("Failed to read replay runs: {}", e))
+}
+
+// Sends a recorded request again, exactly as it went out the first time.
+#[tauri::command]
+pub async fn replay_run(app: AppHandle, run_id: String) -> Result<ReplayResult, String> {
+    let run = get_run(&app.state::<HistoryDb>(), &run_id)?;
+    policy::check_backend(&run.backend)?;
+    privacy::ensure_allowed(&app)?;
+    let url = format!("{}{}", run.backend, run.path);
+    log::info!("Replaying run {} against {}", run_id, url);
+    let response = llm::client()
+        .post(&url)
+        .json(&run.request)
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    let status = response.status();
+    let body = response.bytes().await.map_err(|e| format!("Failed to read the answer from {}: {}", url, e))?;
+    if !status.is_success() {
+        return Err(format!("{} answered {}: {}", url, status, String::from_utf8_lossy(&body)));
+    }
+    let replayed = answer_text(&body);
+    let original = run.answer.as_deref().map(|answer| answer_text(answer.as_bytes()));
+    Ok(ReplayResult {
+        identical: original.as_deref() == Some(replayed.as_str()),
+        run_id,
+        original,
+        replayed,
+    })
+}
@@ -0,0 +1,62 @@
+// In src-tauri/src/request_id.rs
+//
+// An ID for every request to the embedded server, so an error the frontend
+// sees can be found in the backend log with one grep. A client may send its
+// own `X-Request-Id` (printable ASCII, at most 128 characters); otherwise
+// one is generated. It's passed upstream, returned in the response header,
+// tagged onto every log line written while the request is handled (see the
+// log format in lib.rs), and recorded with audit entries, access log lines
+// and the in-flight list (see active.rs), whose ID it is.
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use futures::stream::{Stream, StreamExt};
+
+pub const HEADER: &str = "x-request-id";
+const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+// The ID of the request being handled on this task, if any.
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+fn valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+pub async fn middleware(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| valid(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let value = HeaderValue::from_str(&id).expect("request IDs are printable ASCII");
+    request.headers_mut().insert(HeaderName::from_static(HEADER), value.clone());
+    let mut response = REQUEST_ID.scope(id, next.run(request)).await;
+    response.headers_mut().insert(HeaderName::from_static(HEADER), value);
+    response
+}
+
+// Response bodies are polled after the handler returns; this keeps the ID
+// on log lines written while they stream.
+pub fn scoped<S>(stream: S) -> impl Stream<Item = S::Item>
+where
+    S: Stream + Send + 'static,
+{
+    let id = current();
+    let mut stream = Box::pin(stream);
+    futures::stream::poll_fn(move |cx| match &id {
+        Some(id) => REQUEST_ID.sync_scope(id.clone(), || stream.poll_next_unpin(cx)),
+        None => stream.poll_next_unpin(cx),
+    })
+}
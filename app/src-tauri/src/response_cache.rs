@@ -0,0 +1,338 @@
+// In src-tauri/src/response_cache.rs
+//
+// A cache of recent answers, so agents that keep evaluating an unchanged
+// screen or document get them back instantly instead of waiting on the GPU.
+// Chat and generate requests are matched exactly on the model, the API path
+// and the whole request body, images and options included. Optionally, a
+// request without images may also reuse the answer to an earlier one whose
+// prompt text is semantically close enough, compared by embedding with
+// `embedding_model`.
+//
+// Only complete answers (the last line says `"done": true`) are kept, for
+// `ttl_secs`, up to `max_entries` across all models. Hits carry
+// `X-Observer-Cache: exact` or `semantic`. The cache lives in memory and is
+// off by default; `list_response_cache` shows what's in it.
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, StatusCode},
+    response::Response,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{llm, storage, vector_store};
+
+const SETTINGS_FILE: &str = "response_cache.json";
+const CACHED_PATHS: &[&str] = &["/api/chat", "/api/generate"];
+pub const CACHE_HEADER: &str = "x-observer-cache";
+const PREVIEW_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseCacheSettings {
+    pub enabled: bool,
+    pub ttl_secs: u64,
+    pub max_entries: usize,
+    // Models to cache; empty caches all of them.
+    pub models: Vec<String>,
+    pub semantic: bool,
+    pub embedding_model: String,
+    // Cosine similarity a prompt needs to reuse another's answer.
+    pub semantic_threshold: f32,
+}
+
+impl Default for ResponseCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 600,
+            max_entries: 500,
+            models: Vec::new(),
+            semantic: false,
+            embedding_model: "nomic-embed-text".to_string(),
+            semantic_threshold: 0.97,
+        }
+    }
+}
+
+struct CacheEntry {
+    key: String,
+    model: String,
+    path: String,
+    preview: String,
+    embedding: Option<Vec<f32>>,
+    content_type: Option<String>,
+    body: Bytes,
+    created_at: DateTime<Utc>,
+    hits: u64,
+}
+
+#[derive(Default)]
+pub struct ResponseCache {
+    settings: Mutex<ResponseCacheSettings>,
+    // Oldest first.
+    entries: Mutex<VecDeque<CacheEntry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+// What a missed request is stored under once its answer is complete.
+pub struct CacheKey {
+    key: String,
+    model: String,
+    path: String,
+    preview: String,
+    embedding: Option<Vec<f32>>,
+}
+
+pub enum Lookup {
+    Hit(Response),
+    Miss(CacheKey),
+    // Not a request the cache applies to.
+    Uncached,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedResponse {
+    pub model: String,
+    pub path: String,
+    pub preview: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub bytes: usize,
+    pub hits: u64,
+    pub semantic: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseCacheReport {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: Vec<CachedResponse>,
+}
+
+// The prompt text of a chat or generate request, or None when it has images.
+fn prompt_text(body: &Value) -> Option<String> {
+    let has_images = |value: &Value| value["images"].as_array().is_some_and(|images| !images.is_empty());
+    if has_images(body) {
+        return None;
+    }
+    let mut text = body["system"].as_str().unwrap_or_default().to_string();
+    if let Some(prompt) = body["prompt"].as_str() {
+        text.push('\n');
+        text.push_str(prompt);
+    }
+    for message in body["messages"].as_array().into_iter().flatten() {
+        if has_images(message) {
+            return None;
+        }
+        text.push('\n');
+        text.push_str(message["role"].as_str().unwrap_or_default());
+        text.push_str(": ");
+        text.push_str(message["content"].as_str().unwrap_or_default());
+    }
+    Some(text.trim().to_string())
+}
+
+fn expired(entry: &CacheEntry, now: DateTime<Utc>, ttl_secs: u64) -> bool {
+    now - entry.created_at > ChronoDuration::seconds(ttl_secs as i64)
+}
+
+fn hit_response(entry: &CacheEntry, kind: &'static str) -> Response {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CACHE_HEADER, kind)
+        .header(header::CONTENT_TYPE, entry.content_type.as_deref().unwrap_or("application/x-ndjson"))
+        .header(header::CONTENT_LENGTH, entry.body.len())
+        .body(Body::from(entry.body.clone()))
+        .unwrap()
+}
+
+pub async fn lookup(app: &AppHandle, path: &str, body: &[u8]) -> Lookup {
+    let cache = app.state::<ResponseCache>();
+    let settings = cache.settings.lock().unwrap().clone();
+    if !settings.enabled || !CACHED_PATHS.contains(&path) {
+        return Lookup::Uncached;
+    }
+    let Ok(request) = serde_json::from_slice::<Value>(body) else {
+        return Lookup::Uncached;
+    };
+    let Some(model) = request["model"].as_str().map(str::to_string) else {
+        return Lookup::Uncached;
+    };
+    if !settings.models.is_empty() && !settings.models.contains(&model) {
+        return Lookup::Uncached;
+    }
+
+    // serde_json sorts object keys, so equal requests serialize the same.
+    let key: String = Sha256::digest(format!("{}\n{}", path, request).as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let text = prompt_text(&request);
+    let preview = text.as_deref().unwrap_or("(with images)").chars().take(PREVIEW_CHARS).collect();
+    let now = Utc::now();
+    {
+        let mut entries = cache.entries.lock().unwrap();
+        entries.retain(|entry| !expired(entry, now, settings.ttl_secs));
+        if let Some(entry) = entries.iter_mut().find(|entry| entry.key == key) {
+            entry.hits += 1;
+            cache.hits.fetch_add(1, Ordering::Relaxed);
+            log::info!("Answering {} for {} from the cache", path, model);
+            return Lookup::Hit(hit_response(entry, "exact"));
+        }
+    }
+
+    let mut embedding = None;
+    if let Some(text) = text.filter(|text| settings.semantic && !text.is_empty()) {
+        match llm::embed(app, &settings.embedding_model, &[text]).await {
+            Ok(mut vectors) => embedding = vectors.pop(),
+            Err(e) => log::warn!("Failed to embed a prompt for the response cache: {}", e),
+        }
+    }
+    if let Some(query) = &embedding {
+        let mut entries = cache.entries.lock().unwrap();
+        let best = entries
+            .iter_mut()
+            .filter(|entry| entry.model == model && entry.path == path)
+            .filter_map(|entry| {
+                let similarity = vector_store::cosine(query, entry.embedding.as_deref()?);
+                Some((similarity, entry))
+            })
+            .filter(|(similarity, _)| *similarity >= settings.semantic_threshold)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        if let Some((similarity, entry)) = best {
+            entry.hits += 1;
+            cache.hits.fetch_add(1, Ordering::Relaxed);
+            log::info!("Answering {} for {} from the cache (similarity {:.3})", path, model, similarity);
+            return Lookup::Hit(hit_response(entry, "semantic"));
+        }
+    }
+
+    cache.misses.fetch_add(1, Ordering::Relaxed);
+    Lookup::Miss(CacheKey { key, model, path: path.to_string(), preview, embedding })
+}
+
+fn complete(body: &[u8]) -> bool {
+    body.split(|b| *b == b'\n')
+        .rev()
+        .find(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .and_then(|line| serde_json::from_slice::<Value>(line).ok())
+        .is_some_and(|line| line["done"].as_bool() == Some(true))
+}
+
+// Passes the answer through, keeping a copy for the cache if it completes.
+pub fn record(
+    app: &AppHandle,
+    key: CacheKey,
+    content_type: Option<String>,
+    upstream: BoxStream<'static, reqwest::Result<Bytes>>,
+) -> BoxStream<'static, reqwest::Result<Bytes>> {
+    let app = app.clone();
+    async_stream::stream! {
+        let mut upstream = upstream;
+        let mut collected = Vec::new();
+        while let Some(chunk) = upstream.next().await {
+            if let Ok(bytes) = &chunk {
+                collected.extend_from_slice(bytes);
+            }
+            yield chunk;
+        }
+        if complete(&collected) {
+            insert(&app, key, content_type, collected.into());
+        }
+    }
+    .boxed()
+}
+
+fn insert(app: &AppHandle, key: CacheKey, content_type: Option<String>, body: Bytes) {
+    let cache = app.state::<ResponseCache>();
+    let max_entries = cache.settings.lock().unwrap().max_entries;
+    let mut entries = cache.entries.lock().unwrap();
+    entries.retain(|entry| entry.key != key.key);
+    entries.push_back(CacheEntry {
+        key: key.key,
+        model: key.model,
+        path: key.path,
+        preview: key.preview,
+        embedding: key.embedding,
+        content_type,
+        body,
+        created_at: Utc::now(),
+        hits: 0,
+    });
+    while entries.len() > max_entries {
+        entries.pop_front();
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<ResponseCache>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_response_cache_settings(state: State<'_, ResponseCache>) -> ResponseCacheSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_response_cache_settings(
+    app: AppHandle,
+    settings: ResponseCacheSettings,
+    state: State<'_, ResponseCache>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    if !settings.enabled {
+        state.entries.lock().unwrap().clear();
+    }
+    log::info!("Response cache {}", if settings.enabled { "enabled" } else { "disabled" });
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_response_cache(state: State<'_, ResponseCache>) -> ResponseCacheReport {
+    let ttl = ChronoDuration::seconds(state.settings.lock().unwrap().ttl_secs as i64);
+    let entries = state
+        .entries
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .map(|entry| CachedResponse {
+            model: entry.model.clone(),
+            path: entry.path.clone(),
+            preview: entry.preview.clone(),
+            created_at: entry.created_at,
+            expires_at: entry.created_at + ttl,
+            bytes: entry.body.len(),
+            hits: entry.hits,
+            semantic: entry.embedding.is_some(),
+        })
+        .collect();
+    ResponseCacheReport {
+        hits: state.hits.load(Ordering::Relaxed),
+        misses: state.misses.load(Ordering::Relaxed),
+        entries,
+    }
+}
+
+// Empties the cache, or just one model's answers.
+#[tauri::command]
+pub fn clear_response_cache(model: Option<String>, state: State<'_, ResponseCache>) -> usize {
+    let mut entries = state.entries.lock().unwrap();
+    let before = entries.len();
+    match model {
+        Some(model) => entries.retain(|entry| entry.model != model),
+        None => entries.clear(),
+    }
+    before - entries.len()
+}
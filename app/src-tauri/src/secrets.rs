@@ -0,0 +1,39 @@
+// In src-tauri/src/secrets.rs
+//
+// Credentials (IMAP passwords, API tokens, ...) live in the OS keyring, never
+// in the JSON settings files. Keys are namespaced per integration, e.g.
+// "imap:me@example.com@imap.example.com". Profiles other than the default
+// get their own prefix, so work and personal credentials never mix.
+
+use crate::profiles;
+
+const SERVICE: &str = "observer-ai";
+
+fn entry(key: &str) -> Result<keyring::Entry, String> {
+    let key = match profiles::active_dir_name() {
+        Some(profile) => format!("profile:{}:{}", profile, key),
+        None => key.to_string(),
+    };
+    keyring::Entry::new(SERVICE, &key).map_err(|e| format!("Keyring unavailable: {}", e))
+}
+
+pub fn get(key: &str) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read '{}' from the keyring: {}", key, e)),
+    }
+}
+
+pub fn set(key: &str, secret: &str) -> Result<(), String> {
+    entry(key)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to store '{}' in the keyring: {}", key, e))
+}
+
+pub fn delete(key: &str) -> Result<(), String> {
+    match entry(key)?.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to remove '{}' from the keyring: {}", key, e)),
+    }
+}
@@ -0,0 +1,280 @@
+// In src-tauri/src/self_monitor.rs
+//
+// The app watching itself over long sessions. Every `interval_secs` it reads
+// its resident memory, open file descriptors (handles on Windows), threads
+// and the tasks alive on the app's and the embedded server's runtimes, and
+// keeps the last `MAX_SAMPLES` readings so a slow climb shows up.
+//
+// A reading past one of the `max_*` thresholds is logged as a warning (so it
+// appears in the log console, see log_store.rs) and sent as a
+// "self-monitor-warning" event. With `restart_server` on, it also restarts
+// the embedded HTTP server: its runtime is shut down, which drops every task
+// and socket it held, and a fresh one binds the same addresses. That's done
+// at most once per `RESTART_COOLDOWN`, since memory held elsewhere won't go
+// down with it. Readings are best-effort: whatever a platform can't report
+// is left out.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Notify;
+
+use crate::storage;
+
+const SETTINGS_FILE: &str = "self_monitor.json";
+// A day at the default interval.
+const MAX_SAMPLES: usize = 1440;
+const RESTART_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+
+static SERVER_RUNTIME: Mutex<Option<tokio::runtime::Handle>> = Mutex::new(None);
+static SERVER_RESTART: OnceLock<Notify> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelfMonitorSettings {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub max_rss_mb: Option<u64>,
+    pub max_open_files: Option<u64>,
+    // Tasks alive on either runtime.
+    pub max_tasks: Option<u64>,
+    pub restart_server: bool,
+}
+
+impl Default for SelfMonitorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            max_rss_mb: Some(2048),
+            max_open_files: Some(2048),
+            max_tasks: Some(10_000),
+            restart_server: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Sample {
+    pub timestamp: DateTime<Utc>,
+    pub rss_mb: Option<u64>,
+    pub open_files: Option<u64>,
+    pub threads: Option<u64>,
+    pub app_tasks: Option<u64>,
+    pub server_tasks: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Warning {
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+    pub server_restarted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfMonitorStatus {
+    pub started_at: DateTime<Utc>,
+    pub samples: Vec<Sample>,
+    pub warnings: Vec<Warning>,
+    pub server_restarts: u32,
+}
+
+pub struct SelfMonitorState {
+    settings: Mutex<SelfMonitorSettings>,
+    samples: Mutex<VecDeque<Sample>>,
+    warnings: Mutex<VecDeque<Warning>>,
+    started_at: DateTime<Utc>,
+    server_restarts: Mutex<u32>,
+}
+
+impl Default for SelfMonitorState {
+    fn default() -> Self {
+        Self {
+            settings: Mutex::new(SelfMonitorSettings::default()),
+            samples: Mutex::new(VecDeque::new()),
+            warnings: Mutex::new(VecDeque::new()),
+            started_at: Utc::now(),
+            server_restarts: Mutex::new(0),
+        }
+    }
+}
+
+// Called by the embedded server from inside its runtime (see lib.rs).
+pub fn set_server_runtime(handle: Option<tokio::runtime::Handle>) {
+    *SERVER_RUNTIME.lock().unwrap() = handle;
+}
+
+// Resolves when the embedded server should be restarted.
+pub async fn server_restart_requested() {
+    SERVER_RESTART.get_or_init(Notify::new).notified().await
+}
+
+fn request_server_restart() {
+    SERVER_RESTART.get_or_init(Notify::new).notify_one();
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn status_field(name: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with(name))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+// Resident memory, open files and threads, as far as the platform tells.
+fn process_usage() -> (Option<u64>, Option<u64>, Option<u64>) {
+    #[cfg(target_os = "linux")]
+    {
+        let rss_mb = status_field("VmRSS:").map(|kb| kb / 1024);
+        let open_files = std::fs::read_dir("/proc/self/fd").ok().map(|dir| dir.count() as u64);
+        (rss_mb, open_files, status_field("Threads:"))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let pid = std::process::id().to_string();
+        let rss_mb = output("ps", &["-o", "rss=", "-p", &pid])
+            .and_then(|kb| kb.trim().parse::<u64>().ok())
+            .map(|kb| kb / 1024);
+        let open_files = std::fs::read_dir("/dev/fd").ok().map(|dir| dir.count() as u64);
+        let threads = output("ps", &["-M", "-p", &pid]).map(|out| out.lines().count().saturating_sub(1) as u64);
+        (rss_mb, open_files, threads)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let script = format!(
+            "$p = Get-Process -Id {}; \"$($p.WorkingSet64) $($p.HandleCount) $($p.Threads.Count)\"",
+            std::process::id()
+        );
+        let Some(out) = output("powershell", &["-NoProfile", "-Command", &script]) else {
+            return (None, None, None);
+        };
+        let mut fields = out.split_whitespace().map(|field| field.parse::<u64>().ok());
+        let rss_mb = fields.next().flatten().map(|bytes| bytes / 1024 / 1024);
+        (rss_mb, fields.next().flatten(), fields.next().flatten())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    (None, None, None)
+}
+
+fn sample() -> Sample {
+    let (rss_mb, open_files, threads) = process_usage();
+    let app_tasks = tokio::runtime::Handle::try_current()
+        .ok()
+        .map(|handle| handle.metrics().num_alive_tasks() as u64);
+    let server_tasks = SERVER_RUNTIME
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|handle| handle.metrics().num_alive_tasks() as u64);
+    Sample { timestamp: Utc::now(), rss_mb, open_files, threads, app_tasks, server_tasks }
+}
+
+// What's over its threshold, in words.
+fn exceeded(sample: &Sample, settings: &SelfMonitorSettings) -> Vec<String> {
+    let tasks = match (sample.app_tasks, sample.server_tasks) {
+        (None, None) => None,
+        (app, server) => Some(app.unwrap_or(0) + server.unwrap_or(0)),
+    };
+    [
+        ("memory", sample.rss_mb, settings.max_rss_mb, " MB"),
+        ("open files", sample.open_files, settings.max_open_files, ""),
+        ("tasks", tasks, settings.max_tasks, ""),
+    ]
+    .into_iter()
+    .filter_map(|(what, value, max, unit)| {
+        let (value, max) = (value?, max?);
+        (value > max).then(|| format!("{} at {}{} (limit {}{})", what, value, unit, max, unit))
+    })
+    .collect()
+}
+
+fn check(app: &AppHandle, settings: &SelfMonitorSettings, last_restart: &mut Option<Instant>) {
+    let state = app.state::<SelfMonitorState>();
+    let sample = sample();
+    let problems = exceeded(&sample, settings);
+    {
+        let mut samples = state.samples.lock().unwrap();
+        samples.push_back(sample);
+        while samples.len() > MAX_SAMPLES {
+            samples.pop_front();
+        }
+    }
+    if problems.is_empty() {
+        return;
+    }
+
+    let can_restart = SERVER_RUNTIME.lock().unwrap().is_some();
+    let restart = settings.restart_server
+        && can_restart
+        && !last_restart.is_some_and(|at| at.elapsed() < RESTART_COOLDOWN);
+    let message = format!("Resource usage is high: {}", problems.join(", "));
+    if restart {
+        log::warn!("{}; restarting the embedded server", message);
+        *last_restart = Some(Instant::now());
+        *state.server_restarts.lock().unwrap() += 1;
+        request_server_restart();
+    } else {
+        log::warn!("{}", message);
+    }
+
+    let warning = Warning { timestamp: Utc::now(), message, server_restarted: restart };
+    {
+        let mut warnings = state.warnings.lock().unwrap();
+        warnings.push_back(warning.clone());
+        while warnings.len() > MAX_SAMPLES {
+            warnings.pop_front();
+        }
+    }
+    if let Err(e) = app.emit("self-monitor-warning", &warning) {
+        log::error!("Failed to emit self-monitor-warning event: {}", e);
+    }
+}
+
+pub fn start_monitor(app: AppHandle) {
+    *app.state::<SelfMonitorState>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        let mut last_restart = None;
+        loop {
+            let settings = app.state::<SelfMonitorState>().settings.lock().unwrap().clone();
+            if settings.enabled {
+                check(&app, &settings, &mut last_restart);
+            }
+            tokio::time::sleep(Duration::from_secs(settings.interval_secs.max(1))).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_self_monitor_status(state: State<'_, SelfMonitorState>) -> SelfMonitorStatus {
+    SelfMonitorStatus {
+        started_at: state.started_at,
+        samples: state.samples.lock().unwrap().iter().cloned().collect(),
+        warnings: state.warnings.lock().unwrap().iter().cloned().collect(),
+        server_restarts: *state.server_restarts.lock().unwrap(),
+    }
+}
+
+#[tauri::command]
+pub fn get_self_monitor_settings(state: State<'_, SelfMonitorState>) -> SelfMonitorSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_self_monitor_settings(
+    app: AppHandle,
+    settings: SelfMonitorSettings,
+    state: State<'_, SelfMonitorState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
@@ -0,0 +1,147 @@
+// In src-tauri/src/server.rs
+//
+// Construction of the embedded HTTP server. `build_router` assembles every
+// route without binding a port, and `start_static_server` in lib.rs only
+// picks the address and serves it. The routes that need no more of the app
+// than `AppState`'s `Host` impl in lib.rs gives them (exec, health, the proxy,
+// uploads) are observer-core's, so they're tested without Tauri.
+//
+// CORS lets any origin in, since the web app may be served from elsewhere,
+// except for the routes in `own_origin_routes`, which only the app's own
+// origins (the addresses it serves the web app on) may read.
+//
+// The server speaks HTTP/1.1 and cleartext HTTP/2 (h2c) on the same port,
+// so clients that make many small calls can multiplex them over one
+// connection. Upstream connection reuse is tuned by [upstream] in
+// config.toml (see config.rs).
+
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{header, HeaderName, HeaderValue, Method},
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tower_http::{
+    cors::{AllowOrigin, Any, CorsLayer},
+    services::ServeDir,
+};
+
+use crate::{
+    access_log, active, agents, analytics, annotate, batch, browser_bridge, capture, config, control, conversations,
+    dataset, deep_link, evaluation, features, file_drop, history, injection, log_store, model_share, notifications,
+    ocr_languages, offline, openai_facade, power, privacy, recording, request_id, sound_events, tools, transcript_index,
+    transcription, ui_elements, usage, wake, AppState,
+};
+
+// The built web app, served for anything that isn't an API route.
+pub fn static_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .resource_dir()
+        .expect("failed to get resource directory")
+        .join("_up_/dist")
+}
+
+// The origins the web app is served from by this server.
+fn own_origins(app: &AppHandle) -> Vec<HeaderValue> {
+    let mut origins = Vec::new();
+    for (_, addr) in config::current(app).server.addresses() {
+        let Ok(addr) = addr else {
+            continue;
+        };
+        origins.push(config::server_url(addr));
+        if addr.ip().is_loopback() || addr.ip().is_unspecified() {
+            origins.push(format!("http://localhost:{}", addr.port()));
+        }
+    }
+    origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok()).collect()
+}
+
+// Routes that hand out what's on the screen or in dropped files, or act on the machine.
+fn own_origin_routes(app: &AppHandle) -> Router<AppState> {
+    let cors = CorsLayer::new()
+        .allow_origin(AllowOrigin::list(own_origins(app)))
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::CONTENT_TYPE, HeaderName::from_static(capture::TOKEN_HEADER)]);
+    Router::new()
+        .route("/capture/token", get(capture::token_handler))
+        .route("/capture/screen", get(capture::capture_handler))
+        .route("/tools", get(tools::list_handler))
+        .route("/tools/call", post(tools::call_handler))
+        .route("/files/dropped", post(file_drop::take_handler))
+        .layer(cors)
+}
+
+// Every route the app serves, with the web app's files as the fallback.
+pub fn build_router(state: AppState, static_dir: PathBuf) -> Router {
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any)
+        .expose_headers(Any);
+
+    // /exec, /healthz, /version, the proxy, /upload and /attachments.
+    observer_core::server::routes::<AppState>()
+        .route("/features", get(features::status_handler))
+        .route("/active", get(active::list_handler))
+        .route("/active/:id", delete(active::cancel_handler))
+        .route("/active/:id/stream", get(active::stream_handler))
+        .route("/analytics/time", get(analytics::time_handler))
+        .route("/analytics/quality", get(evaluation::quality_handler))
+        .route("/transcription/process", post(transcription::process_handler))
+        .route("/transcription/sessions/:id/end", post(transcript_index::end_handler))
+        .route("/transcription/sessions/:id/search", get(transcript_index::search_handler))
+        .route("/sound/settings", get(sound_events::settings_handler))
+        .route("/sound/detections", post(sound_events::detections_handler))
+        .route("/ocr/languages", get(ocr_languages::plan_handler))
+        .route("/ocr/tessdata/:file", get(ocr_languages::tessdata_handler))
+        .route("/usage/cost", get(usage::cost_handler))
+        .route("/batch", get(batch::batch_list_handler).post(batch::batch_handler))
+        .route("/conversations", post(conversations::create_handler))
+        .route("/conversations/:id/messages", post(conversations::append_handler))
+        .route("/conversations/:id/branches", get(conversations::branches_handler))
+        .route("/conversations/fork", post(conversations::fork_handler))
+        .route("/conversations/diff", get(conversations::diff_handler))
+        .route("/history", post(history::record_handler))
+        .route("/notify", post(notifications::notify_handler))
+        .route("/history/:id/feedback", get(dataset::get_feedback_handler).put(dataset::put_feedback_handler))
+        .route("/observer/v1/models", get(openai_facade::models_handler))
+        .route("/observer/v1/chat/completions", post(openai_facade::chat_completions_handler))
+        .route("/agents", put(agents::replace_handler))
+        .route("/agents/imported", post(agents::imported_handler))
+        .route("/agents/:id", put(agents::sync_handler).delete(agents::remove_handler))
+        .route("/observer/agents", get(control::agents_handler))
+        .route("/observer/agents/:id/run", post(control::run_agent_handler))
+        .route("/observer/agents/:id/stop", post(control::stop_agent_handler))
+        .route("/observer/logs", get(control::logs_handler))
+        .route("/observer/deep-links", post(deep_link::take_handler))
+        .route("/observer/logs/query", get(log_store::query_handler))
+        .route("/share/models", get(model_share::models_handler))
+        .route("/share/manifests/*model", get(model_share::manifest_handler))
+        .route("/share/blobs/:digest", get(model_share::blob_handler))
+        .route("/browser/ws", get(browser_bridge::ws_handler))
+        .route("/recordings/highlight", post(recording::highlight_handler))
+        .route("/capture/capabilities", get(capture::capabilities_handler))
+        .route("/privacy/status", get(privacy::status_handler))
+        .route("/privacy/sensors", post(privacy::sensors_handler))
+        .route("/injection/scan", post(injection::scan_handler))
+        .route("/system/events", get(wake::events_handler))
+        .route("/offline/status", get(offline::status_handler))
+        .route("/power/state", get(power::state_handler))
+        // Full-resolution screenshots as base64 exceed axum's 2 MB default.
+        .route(
+            "/annotate",
+            post(annotate::annotate_handler).layer(DefaultBodyLimit::max(64 * 1024 * 1024)),
+        )
+        .route(
+            "/ui/elements",
+            post(ui_elements::detect_handler).layer(DefaultBodyLimit::max(64 * 1024 * 1024)),
+        )
+        .fallback_service(ServeDir::new(static_dir))
+        .layer(cors)
+        .merge(own_origin_routes(&state.app_handle))
+        .layer(axum::middleware::from_fn(request_id::middleware))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), access_log::middleware))
+        .with_state(state)
+}
@@ -0,0 +1,147 @@
+// In src-tauri/src/shell.rs
+//
+// General shell tool for agents. Unlike the ollama-only `/exec` endpoint any
+// command can be proposed, but nothing runs without the user's say-so: each
+// proposal is sent to the launcher window as "shell-approval-requested"
+// (command, working directory and the agent's justification), which is brought
+// forward to ask, and the call waits until the user answers through
+// `respond_shell_approval` or the request times out.
+// Every decision and its outcome goes to the audit log.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::process::Command;
+use tokio::sync::oneshot;
+
+use crate::tools::{self, ToolSpec};
+use crate::features::{self, Feature};
+use crate::{audit, deep_link, policy, summary};
+
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+const RUN_TIMEOUT: Duration = Duration::from_secs(120);
+const MAX_OUTPUT_CHARS: usize = 20_000;
+
+#[derive(Default)]
+pub struct ShellApprovals(Mutex<HashMap<String, oneshot::Sender<bool>>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub agent_id: Option<String>,
+    pub command: String,
+    pub cwd: Option<String>,
+    pub justification: String,
+}
+
+#[derive(serde::Deserialize)]
+struct RunArgs {
+    command: String,
+    justification: String,
+    cwd: Option<String>,
+}
+
+pub fn tools() -> Vec<ToolSpec> {
+    vec![ToolSpec {
+        name: "shell_run",
+        description: "Run a shell command on the user's computer. The user sees the command and your \
+justification and must approve it first; explain clearly why it is needed.",
+        parameters: json!({
+            "type": "object",
+            "properties": {
+                "command": { "type": "string" },
+                "justification": { "type": "string", "description": "Why this command is needed" },
+                "cwd": { "type": "string", "description": "Working directory" }
+            },
+            "required": ["command", "justification"]
+        }),
+    }]
+}
+
+async fn request_approval(app: &AppHandle, request: &ApprovalRequest) -> Result<bool, String> {
+    let (tx, rx) = oneshot::channel();
+    app.state::<ShellApprovals>().0.lock().unwrap().insert(request.id.clone(), tx);
+    app.emit("shell-approval-requested", request).map_err(|e| e.to_string())?;
+    deep_link::show_main_window(app);
+
+    let answer = tokio::time::timeout(APPROVAL_TIMEOUT, rx).await;
+    app.state::<ShellApprovals>().0.lock().unwrap().remove(&request.id);
+    // A timeout or a dropped request counts as a denial.
+    Ok(matches!(answer, Ok(Ok(true))))
+}
+
+async fn execute(command: &str, cwd: Option<&str>) -> Result<Value, String> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    cmd.kill_on_drop(true);
+
+    let output = tokio::time::timeout(RUN_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| format!("Command timed out after {}s", RUN_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("Failed to run command: {}", e))?;
+    Ok(json!({
+        "exit_code": output.status.code(),
+        "stdout": summary::truncate(&String::from_utf8_lossy(&output.stdout), MAX_OUTPUT_CHARS),
+        "stderr": summary::truncate(&String::from_utf8_lossy(&output.stderr), MAX_OUTPUT_CHARS),
+    }))
+}
+
+pub async fn run(app: &AppHandle, agent_id: Option<&str>, args: Value) -> Result<Value, String> {
+    features::ensure(Feature::Exec)?;
+    let args: RunArgs = tools::parse_args("shell_run", args)?;
+    if let Err(e) = policy::check_shell_command(&args.command) {
+        audit::record(app, agent_id, "shell.blocked", json!({ "command": args.command, "reason": e }));
+        return Err(e);
+    }
+    let request = ApprovalRequest {
+        id: uuid::Uuid::new_v4().to_string(),
+        agent_id: agent_id.map(str::to_string),
+        command: args.command,
+        cwd: args.cwd,
+        justification: args.justification,
+    };
+    log::info!("Agent {:?} asks to run '{}'", agent_id, request.command);
+
+    let detail = json!({ "command": request.command, "cwd": request.cwd, "justification": request.justification });
+    if !request_approval(app, &request).await? {
+        audit::record(app, agent_id, "shell.denied", detail);
+        return Err("The user did not approve this command".to_string());
+    }
+    audit::record(app, agent_id, "shell.approved", detail);
+
+    let result = execute(&request.command, request.cwd.as_deref()).await;
+    let outcome = match &result {
+        Ok(output) => json!({ "command": request.command, "exit_code": output["exit_code"] }),
+        Err(e) => json!({ "command": request.command, "error": e }),
+    };
+    audit::record(app, agent_id, "shell.finished", outcome);
+    result
+}
+
+#[tauri::command]
+pub fn respond_shell_approval(id: String, approved: bool, approvals: State<'_, ShellApprovals>) -> Result<(), String> {
+    let sender = approvals
+        .0
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| "Approval request not found or expired".to_string())?;
+    let _ = sender.send(approved);
+    Ok(())
+}
@@ -0,0 +1,202 @@
+// In src-tauri/src/sound_events.rs
+//
+// Ambient sound events as agent triggers: a doorbell, an alarm, a baby
+// crying or the user's name being said. The frontend classifies short
+// microphone chunks with a small AudioSet model in a web worker, and only
+// transcribes (also in the worker) chunks that contain speech when names are
+// configured. It then reports one score per class to `/sound/detections`.
+//
+// Nothing about the audio leaves the machine. The audio itself never
+// reaches this process; it only gets class scores, and only from loopback
+// clients. Scores go no further than the agents on this machine.
+//
+// A class fires when its score reaches 1 - `sensitivity`, at most once per
+// `cooldown_secs`. Each firing is published as "sound-detected" on
+// `/system/events` (see wake.rs) with the agents it triggers, and kept in a
+// short list for the UI.
+
+use axum::{
+    extract::{ConnectInfo, State as AxumState},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::wake::{self, SystemChange};
+use crate::features::{self, Feature};
+use crate::{storage, AppState};
+
+const SETTINGS_FILE: &str = "sound_events.json";
+const RECENT_EVENTS: usize = 100;
+pub const NAME_MENTIONED: &str = "name_mentioned";
+const CLASSES: &[&str] = &["doorbell", "alarm", "baby_crying", NAME_MENTIONED];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundClassSettings {
+    pub enabled: bool,
+    // 0 to 1; higher fires on fainter or less certain sounds.
+    pub sensitivity: f32,
+    // Agents to run when this class fires.
+    pub agents: Vec<String>,
+}
+
+impl Default for SoundClassSettings {
+    fn default() -> Self {
+        Self { enabled: true, sensitivity: 0.5, agents: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundEventSettings {
+    pub enabled: bool,
+    pub classes: BTreeMap<String, SoundClassSettings>,
+    // Names that fire `name_mentioned`, matched as whole words.
+    pub names: Vec<String>,
+    pub chunk_ms: u32,
+    pub cooldown_secs: u32,
+}
+
+impl Default for SoundEventSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            classes: CLASSES.iter().map(|class| (class.to_string(), SoundClassSettings::default())).collect(),
+            names: Vec::new(),
+            chunk_ms: 2000,
+            cooldown_secs: 30,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SoundEventState {
+    settings: Mutex<SoundEventSettings>,
+    last_fired: Mutex<HashMap<String, DateTime<Utc>>>,
+    recent: Mutex<VecDeque<SoundEvent>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SoundEvent {
+    pub at: DateTime<Utc>,
+    pub class: String,
+    pub score: f32,
+    // The name that was said, for `name_mentioned`.
+    pub name: Option<String>,
+    pub agents: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DetectionReport {
+    // Class -> score from 0 to 1.
+    pub scores: BTreeMap<String, f32>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+fn fire(app: &AppHandle, report: &DetectionReport) -> Vec<SoundEvent> {
+    let state = app.state::<SoundEventState>();
+    let settings = state.settings.lock().unwrap().clone();
+    if !settings.enabled {
+        return Vec::new();
+    }
+    let now = Utc::now();
+    let cooldown = ChronoDuration::seconds(settings.cooldown_secs as i64);
+    let mut last_fired = state.last_fired.lock().unwrap();
+    let mut fired = Vec::new();
+    for (class, &score) in &report.scores {
+        let Some(class_settings) = settings.classes.get(class).filter(|c| c.enabled) else {
+            continue;
+        };
+        if score < 1.0 - class_settings.sensitivity.clamp(0.0, 1.0) {
+            continue;
+        }
+        if last_fired.get(class).is_some_and(|&at| now - at < cooldown) {
+            continue;
+        }
+        last_fired.insert(class.clone(), now);
+        fired.push(SoundEvent {
+            at: now,
+            class: class.clone(),
+            score,
+            name: report.name.clone().filter(|_| class == NAME_MENTIONED),
+            agents: class_settings.agents.clone(),
+        });
+    }
+    drop(last_fired);
+
+    let mut recent = state.recent.lock().unwrap();
+    for event in &fired {
+        log::info!("Heard {} ({:.2}), triggering {:?}", event.class, event.score, event.agents);
+        recent.push_back(event.clone());
+    }
+    while recent.len() > RECENT_EVENTS {
+        recent.pop_front();
+    }
+    fired
+}
+
+pub async fn detections_handler(
+    AxumState(state): AxumState<AppState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Json(report): Json<DetectionReport>,
+) -> Result<Json<Vec<SoundEvent>>, (StatusCode, String)> {
+    if !client.ip().is_loopback() {
+        return Err((StatusCode::FORBIDDEN, "Sound detections are only accepted from this machine".to_string()));
+    }
+    features::ensure(Feature::Audio).map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let fired = fire(&state.app_handle, &report);
+    for event in &fired {
+        let change = SystemChange::SoundDetected {
+            class: event.class.clone(),
+            score: event.score,
+            name: event.name.clone(),
+            agents: event.agents.clone(),
+        };
+        wake::publish(&state.app_handle, change).await;
+    }
+    Ok(Json(fired))
+}
+
+// What the frontend's classifier needs; the agents stay here.
+pub async fn settings_handler(AxumState(state): AxumState<AppState>) -> Json<SoundEventSettings> {
+    let mut settings = state.app_handle.state::<SoundEventState>().settings.lock().unwrap().clone();
+    for class in settings.classes.values_mut() {
+        class.agents.clear();
+    }
+    Json(settings)
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<SoundEventState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_sound_event_settings(state: State<'_, SoundEventState>) -> SoundEventSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_sound_event_settings(
+    app: AppHandle,
+    settings: SoundEventSettings,
+    state: State<'_, SoundEventState>,
+) -> Result<(), String> {
+    if let Some(class) = settings.classes.keys().find(|class| !CLASSES.contains(&class.as_str())) {
+        return Err(format!("Unknown sound class '{}', expected one of {:?}", class, CLASSES));
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_sound_events(state: State<'_, SoundEventState>) -> Vec<SoundEvent> {
+    state.recent.lock().unwrap().iter().rev().cloned().collect()
+}
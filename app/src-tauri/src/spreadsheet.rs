@@ -0,0 +1,394 @@
+// In src-tauri/src/spreadsheet.rs
+//
+// CSV/XLSX analysis tools. Small models are bad at arithmetic over pasted
+// tables, so instead the model sees the schema and a few sample rows
+// (`table_schema`) and asks for a declarative query (`table_query`: filter,
+// group, aggregate, sort) that we execute exactly here. Files are read
+// through the same directory scopes as the file tools.
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::files;
+use crate::tools::{self, ToolSpec};
+
+const MAX_ROWS: usize = 200_000;
+const SAMPLE_ROWS: usize = 5;
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+struct Table {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct SchemaArgs {
+    path: String,
+    sheet: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Filter {
+    column: String,
+    // eq, ne, gt, gte, lt, lte, contains
+    op: String,
+    value: Value,
+}
+
+#[derive(Deserialize)]
+struct Aggregate {
+    column: String,
+    // count, count_distinct, sum, avg, min, max
+    function: String,
+}
+
+#[derive(Deserialize)]
+struct Sort {
+    column: String,
+    #[serde(default)]
+    descending: bool,
+}
+
+#[derive(Deserialize)]
+struct QueryArgs {
+    path: String,
+    sheet: Option<String>,
+    #[serde(default)]
+    filters: Vec<Filter>,
+    #[serde(default)]
+    group_by: Vec<String>,
+    #[serde(default)]
+    aggregates: Vec<Aggregate>,
+    #[serde(default)]
+    columns: Vec<String>,
+    sort: Option<Sort>,
+    limit: Option<usize>,
+}
+
+pub fn tools() -> Vec<ToolSpec> {
+    let path = json!({ "type": "string", "description": "CSV or XLSX file in a shared folder" });
+    let sheet = json!({ "type": "string", "description": "XLSX sheet name; defaults to the first sheet" });
+    vec![
+        ToolSpec {
+            name: "table_schema",
+            description: "Column names, inferred types, row count and sample rows of a CSV/XLSX file. \
+Call this before table_query.",
+            parameters: json!({
+                "type": "object",
+                "properties": { "path": path, "sheet": sheet },
+                "required": ["path"]
+            }),
+        },
+        ToolSpec {
+            name: "table_query",
+            description: "Run an exact query over a CSV/XLSX file: filter rows, group them and compute \
+aggregates, then sort. Without aggregates the matching rows are returned.",
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": path,
+                    "sheet": sheet,
+                    "filters": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "column": { "type": "string" },
+                                "op": { "type": "string", "enum": ["eq", "ne", "gt", "gte", "lt", "lte", "contains"] },
+                                "value": {}
+                            },
+                            "required": ["column", "op", "value"]
+                        }
+                    },
+                    "group_by": { "type": "array", "items": { "type": "string" } },
+                    "aggregates": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "column": { "type": "string" },
+                                "function": { "type": "string", "enum": ["count", "count_distinct", "sum", "avg", "min", "max"] }
+                            },
+                            "required": ["column", "function"]
+                        }
+                    },
+                    "columns": { "type": "array", "items": { "type": "string" }, "description": "Columns to return when not aggregating" },
+                    "sort": {
+                        "type": "object",
+                        "properties": { "column": { "type": "string" }, "descending": { "type": "boolean" } },
+                        "required": ["column"]
+                    },
+                    "limit": { "type": "integer" }
+                },
+                "required": ["path"]
+            }),
+        },
+    ]
+}
+
+fn load_csv(path: &Path, delimiter: u8) -> Result<Table, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let columns = reader
+        .headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let mut rows = Vec::new();
+    for record in reader.records().take(MAX_ROWS) {
+        let record = record.map_err(|e| format!("Invalid CSV in {:?}: {}", path, e))?;
+        rows.push(record.iter().map(str::to_string).collect());
+    }
+    Ok(Table { columns, rows })
+}
+
+fn load_xlsx(path: &Path, sheet: Option<&str>) -> Result<Table, String> {
+    use calamine::Reader;
+    let mut workbook = calamine::open_workbook_auto(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let sheet = match sheet {
+        Some(sheet) => sheet.to_string(),
+        None => workbook.sheet_names().first().cloned().ok_or("The workbook has no sheets")?,
+    };
+    let range = workbook
+        .worksheet_range(&sheet)
+        .map_err(|e| format!("Failed to read sheet '{}': {}", sheet, e))?;
+
+    let mut rows = range.rows().map(|row| row.iter().map(|cell| cell.to_string()).collect::<Vec<_>>());
+    let columns = rows.next().unwrap_or_default();
+    Ok(Table { columns, rows: rows.take(MAX_ROWS).collect() })
+}
+
+fn load(app: &AppHandle, path: &str, sheet: Option<&str>) -> Result<Table, String> {
+    let path = files::resolve(app, path, false)?;
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "csv" | "txt" => load_csv(&path, b','),
+        "tsv" => load_csv(&path, b'\t'),
+        "xlsx" | "xlsm" | "xls" | "ods" => load_xlsx(&path, sheet),
+        _ => Err(format!("Unsupported file type '.{}'", extension)),
+    }
+}
+
+impl Table {
+    fn column(&self, name: &str) -> Result<usize, String> {
+        self.columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown column '{}'. Columns: {}", name, self.columns.join(", ")))
+    }
+}
+
+fn number(value: &str) -> Option<f64> {
+    value.trim().replace(',', "").parse().ok()
+}
+
+// A total order for sorting and filtering: numbers before text, numbers by
+// value and text lexically. Mixing the two per pair, as comparing numbers
+// numerically but a number and a word as text would, isn't transitive, and
+// sorting with such an order can panic.
+fn compare(a: &str, b: &str) -> Ordering {
+    match (number(a), number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+fn infer_type(table: &Table, column: usize) -> &'static str {
+    let values: Vec<&str> = table
+        .rows
+        .iter()
+        .filter_map(|row| row.get(column).map(String::as_str))
+        .filter(|v| !v.trim().is_empty())
+        .take(1000)
+        .collect();
+    if values.is_empty() {
+        "empty"
+    } else if values.iter().all(|v| number(v).is_some()) {
+        "number"
+    } else {
+        "text"
+    }
+}
+
+fn schema(table: &Table) -> Value {
+    let columns: Vec<Value> = table
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, name)| json!({ "name": name, "type": infer_type(table, i) }))
+        .collect();
+    json!({
+        "columns": columns,
+        "row_count": table.rows.len(),
+        "sample_rows": table.rows.iter().take(SAMPLE_ROWS).collect::<Vec<_>>(),
+    })
+}
+
+fn matches_filter(row: &[String], column: usize, filter: &Filter) -> Result<bool, String> {
+    let cell = row.get(column).map(String::as_str).unwrap_or("");
+    let value = match &filter.value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let ordering = compare(cell, &value);
+    Ok(match filter.op.as_str() {
+        "eq" => ordering == Ordering::Equal || cell.eq_ignore_ascii_case(&value),
+        "ne" => ordering != Ordering::Equal && !cell.eq_ignore_ascii_case(&value),
+        "gt" => ordering == Ordering::Greater,
+        "gte" => ordering != Ordering::Less,
+        "lt" => ordering == Ordering::Less,
+        "lte" => ordering != Ordering::Greater,
+        "contains" => cell.to_lowercase().contains(&value.to_lowercase()),
+        other => return Err(format!("Unknown filter operator '{}'", other)),
+    })
+}
+
+fn aggregate(values: &[&str], function: &str) -> Result<Value, String> {
+    let numbers = || values.iter().filter_map(|v| number(v));
+    Ok(match function {
+        "count" => json!(values.iter().filter(|v| !v.trim().is_empty()).count()),
+        "count_distinct" => json!(values.iter().collect::<HashSet<_>>().len()),
+        "sum" => json!(numbers().sum::<f64>()),
+        "avg" => {
+            let count = numbers().count();
+            if count == 0 {
+                Value::Null
+            } else {
+                json!(numbers().sum::<f64>() / count as f64)
+            }
+        }
+        "min" => values.iter().min_by(|a, b| compare(a, b)).map_or(Value::Null, |v| json!(v)),
+        "max" => values.iter().max_by(|a, b| compare(a, b)).map_or(Value::Null, |v| json!(v)),
+        other => return Err(format!("Unknown aggregate function '{}'", other)),
+    })
+}
+
+fn query(table: &Table, args: &QueryArgs) -> Result<Value, String> {
+    let filters = args
+        .filters
+        .iter()
+        .map(|f| Ok((table.column(&f.column)?, f)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut rows: Vec<&Vec<String>> = Vec::new();
+    for row in &table.rows {
+        let mut keep = true;
+        for (column, filter) in &filters {
+            if !matches_filter(row, *column, filter)? {
+                keep = false;
+                break;
+            }
+        }
+        if keep {
+            rows.push(row);
+        }
+    }
+    let limit = args.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    // Plain row selection.
+    if args.group_by.is_empty() && args.aggregates.is_empty() {
+        let columns: Vec<usize> = if args.columns.is_empty() {
+            (0..table.columns.len()).collect()
+        } else {
+            args.columns.iter().map(|c| table.column(c)).collect::<Result<_, _>>()?
+        };
+        if let Some(sort) = &args.sort {
+            let column = table.column(&sort.column)?;
+            let cell = |row: &Vec<String>| row.get(column).cloned().unwrap_or_default();
+            rows.sort_by(|a, b| compare(&cell(*a), &cell(*b)));
+            if sort.descending {
+                rows.reverse();
+            }
+        }
+        let output: Vec<Value> = rows
+            .iter()
+            .take(limit)
+            .map(|row| {
+                let object: Map<String, Value> = columns
+                    .iter()
+                    .map(|&c| (table.columns[c].clone(), json!(row.get(c).cloned().unwrap_or_default())))
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+        return Ok(json!({ "matched_rows": rows.len(), "rows": output }));
+    }
+
+    // Grouped aggregation; no group_by means one group over all matching rows.
+    let group_columns = args.group_by.iter().map(|c| table.column(c)).collect::<Result<Vec<_>, _>>()?;
+    let aggregates = args
+        .aggregates
+        .iter()
+        .map(|a| Ok((table.column(&a.column)?, a)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut groups: BTreeMap<Vec<String>, Vec<&Vec<String>>> = BTreeMap::new();
+    for &row in &rows {
+        let key = group_columns.iter().map(|&c| row.get(c).cloned().unwrap_or_default()).collect();
+        groups.entry(key).or_default().push(row);
+    }
+
+    let mut output = Vec::new();
+    for (key, members) in &groups {
+        let mut object = Map::new();
+        for (i, &c) in group_columns.iter().enumerate() {
+            object.insert(table.columns[c].clone(), json!(key[i]));
+        }
+        object.insert("row_count".to_string(), json!(members.len()));
+        for (column, agg) in &aggregates {
+            let values: Vec<&str> = members
+                .iter()
+                .map(|row| row.get(*column).map(String::as_str).unwrap_or(""))
+                .collect();
+            let name = format!("{}_{}", agg.function, table.columns[*column]);
+            object.insert(name, aggregate(&values, &agg.function)?);
+        }
+        output.push(Value::Object(object));
+    }
+
+    if let Some(sort) = &args.sort {
+        let key = |v: &Value| match &v[&sort.column] {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        output.sort_by(|a, b| compare(&key(a), &key(b)));
+        if sort.descending {
+            output.reverse();
+        }
+    }
+    let group_count = output.len();
+    output.truncate(limit);
+    Ok(json!({ "matched_rows": rows.len(), "group_count": group_count, "groups": output }))
+}
+
+pub async fn call(app: &AppHandle, name: &str, args: Value) -> Result<Value, String> {
+    match name {
+        "table_schema" => {
+            let args: SchemaArgs = tools::parse_args(name, args)?;
+            let app = app.clone();
+            tokio::task::spawn_blocking(move || Ok(schema(&load(&app, &args.path, args.sheet.as_deref())?)))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        "table_query" => {
+            let args: QueryArgs = tools::parse_args(name, args)?;
+            let app = app.clone();
+            tokio::task::spawn_blocking(move || query(&load(&app, &args.path, args.sheet.as_deref())?, &args))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        _ => Err(format!("Unknown tool '{}'", name)),
+    }
+}
@@ -0,0 +1,56 @@
+// In src-tauri/src/storage.rs
+//
+// Small helpers for the JSON files we keep in the app data directory.
+// Everything lives in the active profile's directory (see profiles.rs); the
+// default profile uses the app data directory itself.
+
+use observer_core::storage::{read_json, write_json};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::{portable, profiles};
+
+// The app data directory (or the portable folder, see portable.rs), shared
+// by all profiles.
+pub fn root_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = match portable::data_root() {
+        Some(dir) => dir,
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data directory: {}", e))?,
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir)
+}
+
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let root = root_dir(app)?;
+    let dir = match profiles::active_dir_name() {
+        Some(name) => root.join(profiles::PROFILES_DIR).join(name),
+        None => return Ok(root),
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir)
+}
+
+pub fn data_path(app: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    Ok(data_dir(app)?.join(file_name))
+}
+
+// Missing or unreadable files fall back to the default so a bad file never
+// keeps the app from starting.
+pub fn load_json<T: DeserializeOwned + Default>(app: &AppHandle, file_name: &str) -> T {
+    match data_path(app, file_name) {
+        Ok(path) => read_json(&path),
+        Err(e) => {
+            log::error!("{}", e);
+            T::default()
+        }
+    }
+}
+
+pub fn save_json<T: Serialize>(app: &AppHandle, file_name: &str, value: &T) -> Result<(), String> {
+    write_json(&data_path(app, file_name)?, value)
+}
@@ -0,0 +1,302 @@
+// In src-tauri/src/structured.rs
+//
+// Coerces free-form model answers into structures that can be written to a
+// file or posted to a webhook: a JSON value, markdown table rows or CSV rows.
+// Answers that don't parse (or miss fields the schema requires) are sent back
+// to the model with the parse error for a bounded number of retries.
+//
+// Proxied requests opt in with `X-Observer-Structured: json|table|csv` and an
+// optional `X-Observer-Schema` (JSON schema, or `{"columns": [...]}` for
+// tables). The proxy then forces a non-streaming answer and adds a
+// `structured` (or `structured_error`) field to the response JSON. Agents
+// can also call `extract_structured` directly.
+
+use axum::http::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::AppHandle;
+
+use crate::compaction;
+use crate::llm::{self, ChatMessage};
+
+pub const FORMAT_HEADER: &str = "x-observer-structured";
+pub const SCHEMA_HEADER: &str = "x-observer-schema";
+const DEFAULT_RETRIES: u32 = 2;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    Json,
+    Table,
+    Csv,
+}
+
+impl OutputFormat {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "table" | "markdown" => Some(Self::Table),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    fn instructions(&self) -> &'static str {
+        match self {
+            Self::Json => "Reply with a single valid JSON value and nothing else.",
+            Self::Table => "Reply with a single markdown table (header row, separator row, data rows) and nothing else.",
+            Self::Csv => "Reply with CSV only: a header row followed by data rows, no code fences or commentary.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StructureSpec {
+    pub format: OutputFormat,
+    #[serde(default)]
+    pub schema: Option<Value>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+}
+
+impl StructureSpec {
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let format = OutputFormat::parse(headers.get(FORMAT_HEADER)?.to_str().ok()?)?;
+        let schema = headers
+            .get(SCHEMA_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| serde_json::from_str(v).ok());
+        Some(Self { format, schema, max_retries: None })
+    }
+
+    // Required object keys / table columns named by the schema.
+    fn required(&self) -> Vec<String> {
+        let schema = match &self.schema {
+            Some(schema) => schema,
+            None => return Vec::new(),
+        };
+        let list = if schema["columns"].is_array() {
+            &schema["columns"]
+        } else if schema["type"] == "array" {
+            &schema["items"]["required"]
+        } else {
+            &schema["required"]
+        };
+        list.as_array()
+            .map(|keys| keys.iter().filter_map(|k| k.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    }
+}
+
+// Drops ```lang fences models like to wrap answers in.
+fn strip_fences(text: &str) -> &str {
+    let trimmed = text.trim();
+    match trimmed.strip_prefix("```") {
+        Some(rest) => {
+            let body = rest.split_once('\n').map_or("", |(_, body)| body);
+            body.trim_end().trim_end_matches("```").trim()
+        }
+        None => trimmed,
+    }
+}
+
+fn parse_json(text: &str) -> Result<Value, String> {
+    let text = strip_fences(text);
+    if let Ok(value) = serde_json::from_str(text) {
+        return Ok(value);
+    }
+    // Fall back to the outermost {...} or [...] in the answer.
+    let start = text.find(['{', '[']).ok_or("No JSON object or array found")?;
+    let close = if text[start..].starts_with('{') { '}' } else { ']' };
+    let end = text.rfind(close).filter(|end| *end > start).ok_or("Unterminated JSON value")?;
+    serde_json::from_str(&text[start..=end]).map_err(|e| format!("Invalid JSON: {}", e))
+}
+
+fn split_row(line: &str) -> Vec<String> {
+    line.trim()
+        .trim_start_matches('|')
+        .trim_end_matches('|')
+        .split('|')
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+fn rows_to_value(header: &[String], rows: Vec<Vec<String>>) -> Value {
+    Value::Array(
+        rows.into_iter()
+            .map(|row| {
+                let object: Map<String, Value> = header
+                    .iter()
+                    .cloned()
+                    .zip(row.into_iter().map(Value::String).chain(std::iter::repeat(Value::Null)))
+                    .collect();
+                Value::Object(object)
+            })
+            .collect(),
+    )
+}
+
+fn parse_table(text: &str) -> Result<Value, String> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| l.starts_with('|')).collect();
+    if lines.len() < 2 {
+        return Err("No markdown table found".to_string());
+    }
+    fn is_separator(line: &str) -> bool {
+        line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+    }
+    let header = split_row(lines[0]);
+    let rows: Vec<Vec<String>> = lines[1..]
+        .iter()
+        .copied()
+        .filter(|line| !is_separator(line))
+        .map(split_row)
+        .collect();
+    if rows.is_empty() {
+        return Err("Markdown table has no data rows".to_string());
+    }
+    Ok(rows_to_value(&header, rows))
+}
+
+fn parse_csv(text: &str) -> Result<Value, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(strip_fences(text).as_bytes());
+    let header: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Invalid CSV header: {}", e))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    let rows = reader
+        .records()
+        .map(|r| r.map(|r| r.iter().map(str::to_string).collect()))
+        .collect::<Result<Vec<Vec<String>>, _>>()
+        .map_err(|e| format!("Invalid CSV: {}", e))?;
+    if rows.is_empty() {
+        return Err("CSV has no data rows".to_string());
+    }
+    Ok(rows_to_value(&header, rows))
+}
+
+fn check_required(value: &Value, required: &[String]) -> Result<(), String> {
+    let objects: Vec<&Value> = match value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    for object in objects {
+        if let Some(missing) = required.iter().find(|key| object.get(key.as_str()).is_none()) {
+            return Err(format!("Missing required field '{}'", missing));
+        }
+    }
+    Ok(())
+}
+
+pub fn extract(spec: &StructureSpec, text: &str) -> Result<Value, String> {
+    let value = match spec.format {
+        OutputFormat::Json => parse_json(text)?,
+        OutputFormat::Table => parse_table(text)?,
+        OutputFormat::Csv => parse_csv(text)?,
+    };
+    check_required(&value, &spec.required())?;
+    Ok(value)
+}
+
+// Parses `answer`, asking the model to fix it when parsing fails. `history`
+// is the conversation that produced the answer.
+pub async fn coerce(
+    app: &AppHandle,
+    model: &str,
+    mut history: Vec<ChatMessage>,
+    mut answer: String,
+    spec: &StructureSpec,
+) -> Result<Value, String> {
+    let retries = spec.max_retries.unwrap_or(DEFAULT_RETRIES);
+    let mut attempt = 0;
+    loop {
+        let error = match extract(spec, &answer) {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= retries => return Err(e),
+            Err(e) => e,
+        };
+        attempt += 1;
+        log::info!("Structured output from {} failed to parse ({}), retry {}/{}", model, error, attempt, retries);
+
+        let mut correction = format!("Your answer could not be used: {}. {}", error, spec.format.instructions());
+        if let Some(schema) = &spec.schema {
+            correction.push_str(&format!(" It must match this schema: {}", schema));
+        }
+        history.push(ChatMessage::new("assistant", answer));
+        history.push(ChatMessage::new("user", correction));
+        answer = llm::chat(app, model, history.clone()).await?;
+    }
+}
+
+// Proxy hook for the request: structured answers need the whole response,
+// so streaming is turned off. JSON requests also get Ollama's own `format`
+// constraint unless the caller set one.
+pub fn prepare_request(body: &[u8], spec: &StructureSpec) -> Option<Vec<u8>> {
+    let Ok(Value::Object(mut json)) = serde_json::from_slice::<Value>(body) else {
+        return None;
+    };
+    json.insert("stream".to_string(), Value::Bool(false));
+    if spec.format == OutputFormat::Json && !json.contains_key("format") && json.contains_key("messages") {
+        json.insert("format".to_string(), spec.schema.clone().unwrap_or_else(|| json!("json")));
+    }
+    serde_json::to_vec(&json).ok()
+}
+
+// Conversation of an Ollama or OpenAI request as plain chat messages.
+fn request_history(request: &Value) -> Vec<ChatMessage> {
+    let mut history = Vec::new();
+    if let Some(system) = request["system"].as_str() {
+        history.push(ChatMessage::new("system", system));
+    }
+    if let Some(messages) = request["messages"].as_array() {
+        for message in messages {
+            let role = message["role"].as_str().unwrap_or("user");
+            history.push(ChatMessage::new(role, compaction::message_text(message)));
+        }
+    }
+    if let Some(prompt) = request["prompt"].as_str() {
+        history.push(ChatMessage::new("user", prompt));
+    }
+    history
+}
+
+// Proxy hook for the response: adds `structured` or `structured_error` to a
+// non-streaming Ollama (/api/chat, /api/generate) or OpenAI response.
+pub async fn process_response(app: &AppHandle, request: &[u8], response: &[u8], spec: &StructureSpec) -> Option<Vec<u8>> {
+    let request: Value = serde_json::from_slice(request).ok()?;
+    let mut response: Value = serde_json::from_slice(response).ok()?;
+    let model = request["model"].as_str()?.to_string();
+
+    let answer = response["message"]["content"]
+        .as_str()
+        .or_else(|| response["response"].as_str())
+        .or_else(|| response["choices"][0]["message"]["content"].as_str())?
+        .to_string();
+
+    match coerce(app, &model, request_history(&request), answer, spec).await {
+        Ok(value) => response["structured"] = value,
+        Err(e) => {
+            log::warn!("Could not coerce {} answer into {:?}: {}", model, spec.format, e);
+            response["structured_error"] = Value::String(e);
+        }
+    }
+    serde_json::to_vec(&response).ok()
+}
+
+#[tauri::command]
+pub async fn extract_structured(
+    app: AppHandle,
+    text: String,
+    spec: StructureSpec,
+    model: Option<String>,
+) -> Result<Value, String> {
+    match model {
+        // With a model, bad answers are repaired by asking it again.
+        Some(model) => coerce(&app, &model, Vec::new(), text, &spec).await,
+        None => extract(&spec, &text),
+    }
+}
@@ -0,0 +1,295 @@
+// In src-tauri/src/summary.rs
+//
+// Built-in summarizer job. At the configured local time it gathers the day's
+// (or week's) observations, transcriptions and agent outputs from the history
+// DB, asks the chosen model for a narrative summary, stores the result as a
+// "summary" history entry and optionally delivers it as a notification.
+
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::history::{self, HistoryDb, HistoryQuery};
+use crate::locality::{self, SensitiveContent};
+use crate::notifications::{self, Alert};
+use crate::{analytics, llm, storage, transcription, usage};
+
+const SETTINGS_FILE: &str = "summary.json";
+const RUNS_FILE: &str = "summary_runs.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_ENTRY_CHARS: usize = 600;
+const MAX_DIGEST_CHARS: usize = 24_000;
+
+const SYSTEM_PROMPT: &str = "You write short, friendly end-of-period summaries for the user of a \
+screen observation assistant. You receive a chronological log of what their agents observed, \
+transcribed and reported. Write a narrative summary in a few paragraphs: main activities, notable \
+events, anything that needs follow-up. Do not invent details that are not in the log.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummarySettings {
+    pub enabled: bool,
+    // "HH:MM" local time.
+    pub time: String,
+    pub weekly: bool,
+    // 0 = Monday ... 6 = Sunday
+    pub weekly_day: u8,
+    pub model: String,
+    pub notify: bool,
+}
+
+impl Default for SummarySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: "21:00".to_string(),
+            weekly: false,
+            weekly_day: 6,
+            model: "gemma3:4b".to_string(),
+            notify: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct SummaryRuns {
+    last_daily: Option<NaiveDate>,
+    last_weekly: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryPeriod {
+    Daily,
+    Weekly,
+}
+
+impl SummaryPeriod {
+    pub fn agent_id(self) -> &'static str {
+        match self {
+            SummaryPeriod::Daily => "daily-summary",
+            SummaryPeriod::Weekly => "weekly-summary",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            SummaryPeriod::Daily => "Daily summary",
+            SummaryPeriod::Weekly => "Weekly summary",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub id: i64,
+    pub period: SummaryPeriod,
+    pub text: String,
+}
+
+#[derive(Default)]
+pub struct SummaryState {
+    pub settings: Mutex<SummarySettings>,
+    running: tokio::sync::Mutex<()>,
+}
+
+fn period_start(period: SummaryPeriod) -> chrono::DateTime<Utc> {
+    let today = Local::now().date_naive();
+    let first_day = match period {
+        SummaryPeriod::Daily => today,
+        SummaryPeriod::Weekly => today - ChronoDuration::days(6),
+    };
+    first_day
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now)
+}
+
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+// Oldest first, one line per entry, capped so the prompt fits a small model's context.
+fn build_digest(mut entries: Vec<history::HistoryEntry>) -> String {
+    entries.reverse();
+
+    let mut digest = String::new();
+    for entry in entries {
+        let line = format!(
+            "[{}] {}{}: {}\n",
+            entry.created_at.with_timezone(&Local).format("%a %H:%M"),
+            entry.kind,
+            entry.agent_id.map(|id| format!(" ({})", id)).unwrap_or_default(),
+            truncate(entry.content.trim(), MAX_ENTRY_CHARS).replace('\n', " "),
+        );
+        if digest.len() + line.len() > MAX_DIGEST_CHARS {
+            digest.push_str("[... log truncated ...]\n");
+            break;
+        }
+        digest.push_str(&line);
+    }
+    digest
+}
+
+pub async fn run_summary(app: &AppHandle, period: SummaryPeriod) -> Result<Summary, String> {
+    let state = app.state::<SummaryState>();
+    let _running = state.running.lock().await;
+    let settings = state.settings.lock().unwrap().clone();
+
+    let entries = app
+        .state::<HistoryDb>()
+        .query(&HistoryQuery {
+            since: Some(period_start(period)),
+            limit: Some(5000),
+            ..Default::default()
+        })?
+        .into_iter()
+        .filter(|e| e.kind != history::KIND_SUMMARY)
+        .collect::<Vec<_>>();
+
+    let text = if entries.is_empty() {
+        "Nothing was recorded in this period.".to_string()
+    } else {
+        // Observations describe what was on screen; transcriptions are what was said.
+        let mut content = Vec::new();
+        if entries.iter().any(|e| e.kind == history::KIND_OBSERVATION) {
+            content.push(SensitiveContent::Image);
+        }
+        if entries.iter().any(|e| e.kind == history::KIND_TRANSCRIPTION) {
+            content.push(SensitiveContent::Audio);
+        }
+        let base_url = llm::ollama_base_url(app);
+        locality::check(app, None, &base_url, content).map_err(|v| v.to_string())?;
+        log::info!("Generating {:?} summary from {} entries with {}", period, entries.len(), settings.model);
+        let mut prompt = format!("Activity log:\n\n{}", build_digest(entries));
+        if period == SummaryPeriod::Daily {
+            if let Some(time_line) = analytics::today_summary_line(app) {
+                prompt.push_str(&format!("\n{}\n", time_line));
+            }
+            if let Some(usage_line) = usage::today_summary_line(app) {
+                prompt.push_str(&format!("\n{}\n", usage_line));
+            }
+        }
+        llm::generate(app, &settings.model, SYSTEM_PROMPT, &prompt).await?
+    };
+    let text = transcription::to_preferred_language(app, &text).await.unwrap_or(text);
+
+    let id = app
+        .state::<HistoryDb>()
+        .insert(history::KIND_SUMMARY, Some(period.agent_id()), &text)?;
+    let summary = Summary { id, period, text };
+
+    if let Err(e) = app.emit("summary-generated", summary.clone()) {
+        log::error!("Failed to emit summary-generated event: {}", e);
+    }
+
+    if settings.notify {
+        let alert = Alert {
+            title: period.title().to_string(),
+            body: truncate(&summary.text, 240),
+            agent_id: Some(period.agent_id().to_string()),
+            created_at: Utc::now(),
+        };
+        if let Err(e) = notifications::notify(app, alert) {
+            log::error!("{}", e);
+        }
+    }
+
+    Ok(summary)
+}
+
+fn due_periods(settings: &SummarySettings, runs: &SummaryRuns) -> Vec<SummaryPeriod> {
+    let Ok(at) = NaiveTime::parse_from_str(&settings.time, "%H:%M") else {
+        return Vec::new();
+    };
+    let now = Local::now();
+    if !settings.enabled || now.time() < at {
+        return Vec::new();
+    }
+
+    let today = now.date_naive();
+    let mut due = Vec::new();
+    if runs.last_daily != Some(today) {
+        due.push(SummaryPeriod::Daily);
+    }
+    if settings.weekly
+        && now.weekday().num_days_from_monday() as u8 == settings.weekly_day
+        && runs.last_weekly != Some(today)
+    {
+        due.push(SummaryPeriod::Weekly);
+    }
+    due
+}
+
+pub fn start_scheduler(app: AppHandle) {
+    *app.state::<SummaryState>().settings.lock().unwrap() = storage::load_json(&app, SETTINGS_FILE);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = app.state::<SummaryState>().settings.lock().unwrap().clone();
+            let mut runs: SummaryRuns = storage::load_json(&app, RUNS_FILE);
+
+            for period in due_periods(&settings, &runs) {
+                // Mark the run first so a failing model doesn't retry every minute.
+                let today = Local::now().date_naive();
+                match period {
+                    SummaryPeriod::Daily => runs.last_daily = Some(today),
+                    SummaryPeriod::Weekly => runs.last_weekly = Some(today),
+                }
+                if let Err(e) = storage::save_json(&app, RUNS_FILE, &runs) {
+                    log::error!("{}", e);
+                }
+
+                if let Err(e) = run_summary(&app, period).await {
+                    log::error!("Scheduled {:?} summary failed: {}", period, e);
+                }
+            }
+
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_summary_settings(state: State<'_, SummaryState>) -> SummarySettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_summary_settings(
+    app: AppHandle,
+    settings: SummarySettings,
+    state: State<'_, SummaryState>,
+) -> Result<(), String> {
+    NaiveTime::parse_from_str(&settings.time, "%H:%M")
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", settings.time))?;
+    if settings.weekly_day > 6 {
+        return Err(format!("Invalid weekly_day {}, expected 0 (Monday) to 6 (Sunday)", settings.weekly_day));
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn run_summary_now(app: AppHandle, period: SummaryPeriod) -> Result<Summary, String> {
+    run_summary(&app, period).await
+}
+
+#[tauri::command]
+pub fn list_summaries(
+    limit: Option<u32>,
+    db: State<'_, HistoryDb>,
+) -> Result<Vec<history::HistoryEntry>, String> {
+    db.query(&HistoryQuery {
+        kind: Some(history::KIND_SUMMARY.to_string()),
+        limit: Some(limit.unwrap_or(30)),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,78 @@
+// In src-tauri/src/templates.rs
+//
+// Saved prompts: reusable user prompts ("/summarize ...") and system prompts
+// tied to a model, kept in prompt_templates.json. The frontend offers them
+// in the chat box; importers (see openwebui_import.rs) add to them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::storage;
+
+const TEMPLATES_FILE: &str = "prompt_templates.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateKind {
+    #[default]
+    Prompt,
+    System,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub kind: TemplateKind,
+    // Slash command that inserts it, e.g. "/summarize".
+    #[serde(default)]
+    pub command: Option<String>,
+    pub content: String,
+    // The model a system prompt was written for.
+    #[serde(default)]
+    pub model: Option<String>,
+    // Where it was imported from, if anywhere.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+pub fn load(app: &AppHandle) -> BTreeMap<String, PromptTemplate> {
+    storage::load_json(app, TEMPLATES_FILE)
+}
+
+pub fn save_all(app: &AppHandle, templates: &BTreeMap<String, PromptTemplate>) -> Result<(), String> {
+    storage::save_json(app, TEMPLATES_FILE, templates)
+}
+
+#[tauri::command]
+pub fn list_prompt_templates(app: AppHandle) -> Vec<PromptTemplate> {
+    load(&app).into_values().collect()
+}
+
+// Adds a template, or replaces the one with the same ID; an empty ID gets a
+// new one.
+#[tauri::command]
+pub fn save_prompt_template(app: AppHandle, mut template: PromptTemplate) -> Result<PromptTemplate, String> {
+    if template.title.trim().is_empty() || template.content.trim().is_empty() {
+        return Err("A template needs a title and content".to_string());
+    }
+    if template.id.is_empty() {
+        template.id = uuid::Uuid::new_v4().to_string();
+    }
+    let mut templates = load(&app);
+    templates.insert(template.id.clone(), template.clone());
+    save_all(&app, &templates)?;
+    Ok(template)
+}
+
+#[tauri::command]
+pub fn delete_prompt_template(app: AppHandle, id: String) -> Result<bool, String> {
+    let mut templates = load(&app);
+    let removed = templates.remove(&id).is_some();
+    if removed {
+        save_all(&app, &templates)?;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,319 @@
+// In src-tauri/src/timers.rs
+//
+// Timer service for wellbeing agents (pomodoro, break reminders):
+//   - one-shot timers: `start_timer(label, seconds)` fires once after wall-clock time,
+//   - break schedules: fire after N minutes of *active* screen time, where the
+//     system idle time decides what counts as active. A long enough idle
+//     period counts as the break having been taken and resets the counter.
+// Firing emits "timer-fired" and sends a notification (subject to focus mode).
+// One-shot timers are persisted (jobs.rs) and re-armed after a restart.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::notifications::{self, Alert};
+use crate::{jobs, storage};
+
+const SCHEDULES_FILE: &str = "break_schedules.json";
+const TICK: Duration = Duration::from_secs(5);
+// Without input for this long the user counts as away.
+const IDLE_THRESHOLD_SECONDS: u64 = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timer {
+    pub id: String,
+    pub label: String,
+    pub agent_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub fires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakSchedule {
+    #[serde(default)]
+    pub id: String,
+    pub label: String,
+    pub message: String,
+    pub every_active_minutes: u32,
+    #[serde(default = "default_reset_after_idle")]
+    pub reset_after_idle_minutes: u32,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_reset_after_idle() -> u32 {
+    5
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakStatus {
+    pub schedule: BreakSchedule,
+    pub active_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerKind {
+    Timer,
+    Break,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerFired {
+    pub id: String,
+    pub kind: TimerKind,
+    pub label: String,
+    pub message: Option<String>,
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IdleStatus {
+    pub idle: bool,
+    pub idle_seconds: u64,
+}
+
+#[derive(Default)]
+pub struct TimerService {
+    timers: Mutex<Vec<Timer>>,
+    schedules: Mutex<Vec<BreakSchedule>>,
+    // schedule id -> accumulated active seconds
+    progress: Mutex<HashMap<String, u64>>,
+    idle: Mutex<Option<IdleStatus>>,
+    last_tick: Mutex<Option<DateTime<Utc>>>,
+}
+
+fn read_idle_seconds() -> Option<u64> {
+    match user_idle::UserIdle::get_time() {
+        Ok(idle) => Some(idle.as_seconds()),
+        Err(e) => {
+            log::debug!("Idle time unavailable: {:?}", e);
+            None
+        }
+    }
+}
+
+fn fire(app: &AppHandle, event: TimerFired) {
+    log::info!("Timer fired: {} ({})", event.label, event.id);
+
+    let alert = Alert {
+        title: event.label.clone(),
+        body: event.message.clone().unwrap_or_else(|| "Timer finished".to_string()),
+        agent_id: event.agent_id.clone(),
+        created_at: Utc::now(),
+    };
+    if let Err(e) = notifications::notify(app, alert) {
+        log::error!("{}", e);
+    }
+    if let Err(e) = app.emit("timer-fired", event) {
+        log::error!("Failed to emit timer-fired event: {}", e);
+    }
+}
+
+fn tick(app: &AppHandle, idle_seconds: Option<u64>) {
+    let service = app.state::<TimerService>();
+    let now = Utc::now();
+    *service.last_tick.lock().unwrap() = Some(now);
+
+    let due: Vec<Timer> = {
+        let mut timers = service.timers.lock().unwrap();
+        let (due, pending): (Vec<Timer>, Vec<Timer>) =
+            timers.drain(..).partition(|t| t.fires_at <= now);
+        *timers = pending;
+        due
+    };
+    for timer in due {
+        jobs::complete(app, &timer.id);
+        fire(
+            app,
+            TimerFired {
+                id: timer.id,
+                kind: TimerKind::Timer,
+                label: timer.label,
+                message: None,
+                agent_id: timer.agent_id,
+            },
+        );
+    }
+
+    // No idle information means we can't tell active time apart, so count everything.
+    let idle_seconds = idle_seconds.unwrap_or(0);
+    let idle = idle_seconds >= IDLE_THRESHOLD_SECONDS;
+
+    let status = IdleStatus { idle, idle_seconds };
+    let idle_changed = {
+        let mut last = service.idle.lock().unwrap();
+        let changed = last.as_ref().map_or(true, |l| l.idle != idle);
+        *last = Some(status.clone());
+        changed
+    };
+    if idle_changed {
+        if let Err(e) = app.emit("user-idle-changed", status) {
+            log::error!("Failed to emit user-idle-changed event: {}", e);
+        }
+    }
+
+    let schedules = service.schedules.lock().unwrap().clone();
+    let mut fired = Vec::new();
+    {
+        let mut progress = service.progress.lock().unwrap();
+        for schedule in schedules.iter().filter(|s| s.enabled) {
+            let active = progress.entry(schedule.id.clone()).or_default();
+            if idle_seconds >= schedule.reset_after_idle_minutes as u64 * 60 {
+                *active = 0;
+            } else if !idle {
+                *active += TICK.as_secs();
+            }
+
+            if *active >= schedule.every_active_minutes as u64 * 60 {
+                *active = 0;
+                fired.push(schedule.clone());
+            }
+        }
+    }
+    for schedule in fired {
+        fire(
+            app,
+            TimerFired {
+                id: schedule.id,
+                kind: TimerKind::Break,
+                label: schedule.label,
+                message: Some(schedule.message),
+                agent_id: None,
+            },
+        );
+    }
+}
+
+// None until the first tick; a stale tick means the scheduler is stuck.
+pub fn last_tick(app: &AppHandle) -> Option<DateTime<Utc>> {
+    *app.state::<TimerService>().last_tick.lock().unwrap()
+}
+
+// How long between ticks is still normal.
+pub fn tick_interval() -> Duration {
+    TICK
+}
+
+pub fn start_service(app: AppHandle) {
+    let schedules: Vec<BreakSchedule> = storage::load_json(&app, SCHEDULES_FILE);
+    *app.state::<TimerService>().schedules.lock().unwrap() = schedules;
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            let idle_seconds = tokio::task::spawn_blocking(read_idle_seconds)
+                .await
+                .ok()
+                .flatten();
+            tick(&app, idle_seconds);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn start_timer(
+    app: AppHandle,
+    label: String,
+    seconds: u64,
+    agent_id: Option<String>,
+    service: State<'_, TimerService>,
+) -> Result<Timer, String> {
+    if seconds == 0 {
+        return Err("Timer duration must be at least one second".to_string());
+    }
+    let now = Utc::now();
+    let timer = Timer {
+        id: uuid::Uuid::new_v4().to_string(),
+        label,
+        agent_id,
+        started_at: now,
+        fires_at: now + ChronoDuration::seconds(seconds as i64),
+    };
+    log::info!("Starting timer '{}' for {}s", timer.label, seconds);
+    jobs::persist(&app, jobs::KIND_TIMER, &timer.id, &timer);
+    service.timers.lock().unwrap().push(timer.clone());
+    Ok(timer)
+}
+
+// Re-arms a timer from before a restart; one that came due meanwhile fires
+// on the next tick.
+pub fn restore(app: &AppHandle, timer: Timer) -> Result<(), String> {
+    let service = app.state::<TimerService>();
+    let mut timers = service.timers.lock().unwrap();
+    if !timers.iter().any(|t| t.id == timer.id) {
+        timers.push(timer);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn cancel_timer(app: AppHandle, id: String, service: State<'_, TimerService>) -> bool {
+    let mut timers = service.timers.lock().unwrap();
+    let before = timers.len();
+    timers.retain(|t| t.id != id);
+    jobs::complete(&app, &id);
+    timers.len() != before
+}
+
+#[tauri::command]
+pub fn list_timers(service: State<'_, TimerService>) -> Vec<Timer> {
+    service.timers.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_break_schedules(
+    app: AppHandle,
+    schedules: Vec<BreakSchedule>,
+    service: State<'_, TimerService>,
+) -> Result<Vec<BreakSchedule>, String> {
+    let mut schedules = schedules;
+    for schedule in schedules.iter_mut() {
+        if schedule.every_active_minutes == 0 {
+            return Err(format!("Break schedule '{}' needs a non-zero interval", schedule.label));
+        }
+        if schedule.reset_after_idle_minutes == 0 {
+            return Err(format!("Break schedule '{}' needs a non-zero idle reset", schedule.label));
+        }
+        if schedule.id.is_empty() {
+            schedule.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+
+    storage::save_json(&app, SCHEDULES_FILE, &schedules)?;
+    service
+        .progress
+        .lock()
+        .unwrap()
+        .retain(|id, _| schedules.iter().any(|s| &s.id == id));
+    *service.schedules.lock().unwrap() = schedules.clone();
+    Ok(schedules)
+}
+
+#[tauri::command]
+pub fn list_break_schedules(service: State<'_, TimerService>) -> Vec<BreakStatus> {
+    let progress = service.progress.lock().unwrap();
+    service
+        .schedules
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|schedule| BreakStatus {
+            schedule: schedule.clone(),
+            active_seconds: progress.get(&schedule.id).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_idle_status(service: State<'_, TimerService>) -> Option<IdleStatus> {
+    service.idle.lock().unwrap().clone()
+}
@@ -0,0 +1,221 @@
+// In src-tauri/src/tokenizer.rs
+//
+// Local token counting so we can warn before a prompt overflows the model's
+// context window (Ollama silently truncates from the front otherwise).
+//
+// Ollama has no tokenize endpoint, so counts come from a local BPE
+// (cl100k). It is within a few percent of the Llama/Gemma/Qwen tokenizers for
+// English text, which is plenty for an early warning. Context windows come
+// from `/api/show` and are cached per model.
+
+use axum::http::HeaderValue;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tiktoken_rs::CoreBPE;
+
+use crate::llm;
+
+// Ollama's default num_ctx when neither the request nor the Modelfile sets one.
+pub const OLLAMA_DEFAULT_NUM_CTX: u32 = 4096;
+// Rough cost of one image for CLIP-style vision encoders.
+const TOKENS_PER_IMAGE: usize = 576;
+// Per-message overhead for chat templates (role markers etc).
+const TOKENS_PER_MESSAGE: usize = 4;
+const WARNING_RATIO: f32 = 0.9;
+const SHOW_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, Serialize, Default)]
+pub struct ContextWindow {
+    // What the model was trained for, from the GGUF metadata.
+    pub model_max: Option<u32>,
+    // What Ollama will actually allocate (num_ctx), which is usually smaller.
+    pub effective: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenCount {
+    pub model: String,
+    pub tokens: usize,
+    pub context_window: ContextWindow,
+    pub exceeds: bool,
+    pub near_limit: bool,
+}
+
+#[derive(Default)]
+pub struct ContextWindowCache(Mutex<HashMap<String, ContextWindow>>);
+
+fn bpe() -> Option<&'static CoreBPE> {
+    static BPE: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    BPE.get_or_init(|| match tiktoken_rs::cl100k_base() {
+        Ok(bpe) => Some(bpe),
+        Err(e) => {
+            log::error!("Failed to load BPE tokenizer, falling back to estimates: {}", e);
+            None
+        }
+    })
+    .as_ref()
+}
+
+pub fn count_text(text: &str) -> usize {
+    match bpe() {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+        // ~4 characters per token for English.
+        None => text.chars().count().div_ceil(4),
+    }
+}
+
+fn count_content(content: &serde_json::Value) -> usize {
+    match content {
+        serde_json::Value::String(text) => count_text(text),
+        // OpenAI multi-part content: [{type: "text", text}, {type: "image_url", ...}]
+        serde_json::Value::Array(parts) => parts
+            .iter()
+            .map(|part| match part["type"].as_str() {
+                Some("text") => count_text(part["text"].as_str().unwrap_or("")),
+                Some("image_url") => TOKENS_PER_IMAGE,
+                _ => 0,
+            })
+            .sum(),
+        _ => 0,
+    }
+}
+
+fn count_images(images: &serde_json::Value) -> usize {
+    images.as_array().map_or(0, |i| i.len()) * TOKENS_PER_IMAGE
+}
+
+// Counts the prompt part of an Ollama (/api/chat, /api/generate) or OpenAI
+// (/v1/chat/completions) request body.
+pub fn count_request(body: &serde_json::Value) -> usize {
+    let messages: usize = body["messages"]
+        .as_array()
+        .map(|messages| {
+            messages
+                .iter()
+                .map(|m| count_content(&m["content"]) + count_images(&m["images"]) + TOKENS_PER_MESSAGE)
+                .sum()
+        })
+        .unwrap_or(0);
+
+    messages + count_content(&body["system"]) + count_content(&body["prompt"]) + count_images(&body["images"])
+}
+
+fn parse_show(show: &serde_json::Value) -> ContextWindow {
+    let model_max = show["model_info"].as_object().and_then(|info| {
+        info.iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, value)| value.as_u64())
+            .map(|v| v as u32)
+    });
+
+    // `parameters` is the Modelfile PARAMETER block as plain text.
+    let num_ctx = show["parameters"].as_str().and_then(|params| {
+        params.lines().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some("num_ctx"), Some(value)) => value.parse::<u32>().ok(),
+                _ => None,
+            }
+        })
+    });
+
+    let effective = num_ctx
+        .unwrap_or(OLLAMA_DEFAULT_NUM_CTX)
+        .min(model_max.unwrap_or(u32::MAX));
+    ContextWindow { model_max, effective }
+}
+
+pub async fn context_window(app: &AppHandle, model: &str) -> ContextWindow {
+    let cached = app.state::<ContextWindowCache>().0.lock().unwrap().get(model).copied();
+    if let Some(cached) = cached {
+        return cached;
+    }
+
+    let url = format!("{}/api/show", llm::ollama_base_url(app));
+    let result = llm::client()
+        .post(&url)
+        .timeout(SHOW_TIMEOUT)
+        .json(&serde_json::json!({ "model": model }))
+        .send()
+        .await;
+
+    let window = match result {
+        Ok(response) if response.status().is_success() => match response.json::<serde_json::Value>().await {
+            Ok(show) => parse_show(&show),
+            Err(e) => {
+                log::warn!("Invalid /api/show response for {}: {}", model, e);
+                return ContextWindow { model_max: None, effective: OLLAMA_DEFAULT_NUM_CTX };
+            }
+        },
+        // Cloud/OpenAI-compatible backends don't have /api/show; don't cache so we retry later.
+        _ => return ContextWindow { model_max: None, effective: OLLAMA_DEFAULT_NUM_CTX },
+    };
+
+    app.state::<ContextWindowCache>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(model.to_string(), window);
+    window
+}
+
+fn build_count(model: &str, tokens: usize, window: ContextWindow) -> TokenCount {
+    let limit = window.effective as usize;
+    TokenCount {
+        model: model.to_string(),
+        tokens,
+        context_window: window,
+        exceeds: tokens > limit,
+        near_limit: tokens as f32 >= limit as f32 * WARNING_RATIO,
+    }
+}
+
+// Used by the proxy: inspects a generation request and returns the count
+// when the body is a JSON generation request. Emits "context-window-warning"
+// when the prompt is close to (or over) the limit.
+pub async fn check_request(app: &AppHandle, body: &[u8]) -> Option<TokenCount> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let model = json["model"].as_str()?.to_string();
+    if json.get("messages").is_none() && json.get("prompt").is_none() {
+        return None;
+    }
+
+    let mut window = context_window(app, &model).await;
+    // An explicit num_ctx in the request wins over the Modelfile.
+    if let Some(num_ctx) = json["options"]["num_ctx"].as_u64() {
+        window.effective = num_ctx as u32;
+    }
+
+    let count = build_count(&model, count_request(&json), window);
+    if count.near_limit {
+        log::warn!(
+            "Prompt for {} uses {} of {} context tokens",
+            model,
+            count.tokens,
+            count.context_window.effective
+        );
+        if let Err(e) = app.emit("context-window-warning", count.clone()) {
+            log::error!("Failed to emit context-window-warning event: {}", e);
+        }
+    }
+    Some(count)
+}
+
+pub fn insert_headers(headers: &mut axum::http::HeaderMap, count: &TokenCount) {
+    headers.insert("x-observer-prompt-tokens", count.tokens.into());
+    headers.insert("x-observer-context-window", count.context_window.effective.into());
+    if count.exceeds {
+        headers.insert("x-observer-context-warning", HeaderValue::from_static("exceeds"));
+    } else if count.near_limit {
+        headers.insert("x-observer-context-warning", HeaderValue::from_static("near-limit"));
+    }
+}
+
+#[tauri::command]
+pub async fn count_tokens(app: AppHandle, model: String, text: String) -> Result<TokenCount, String> {
+    let window = context_window(&app, &model).await;
+    Ok(build_count(&model, count_text(&text), window))
+}
@@ -0,0 +1,131 @@
+// In src-tauri/src/tools.rs
+//
+// Registry of native tools agents can call. `list_tools` returns the
+// definitions in Ollama's `tools` format so agent code can pass them straight
+// to /api/chat; when the model answers with a tool call, the agent runs it
+// through `call_tool` and feeds the result back as a "tool" message. The web
+// app, which can't invoke commands, has the same over `GET /tools` and
+// `POST /tools/call`.
+//
+// Each integration module exposes `tools()` with its definitions and a
+// `call(app, name, args)` dispatcher; most tool names are prefixed with the
+// module ("github_...", "git_...") so dispatch is by prefix. High-impact
+// calls pass moderation.rs first.
+
+use axum::{extract::State as AxumState, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::features::{self, Feature};
+use crate::{files, git, github, input_control, moderation, shell, spreadsheet, AppState};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    // JSON schema of the arguments object.
+    pub parameters: Value,
+}
+
+impl ToolSpec {
+    fn definition(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": self.name,
+                "description": self.description,
+                "parameters": self.parameters,
+            }
+        })
+    }
+}
+
+pub fn specs() -> Vec<ToolSpec> {
+    let mut specs = Vec::new();
+    specs.extend(github::tools());
+    specs.extend(git::tools());
+    specs.extend(shell::tools());
+    specs.extend(files::tools());
+    specs.extend(spreadsheet::tools());
+    specs.extend(input_control::tools());
+    specs
+}
+
+// Models sometimes send arguments as a JSON string instead of an object.
+fn normalize_args(args: Value) -> Value {
+    match args {
+        Value::String(s) => serde_json::from_str(&s).unwrap_or(Value::String(s)),
+        Value::Null => json!({}),
+        other => other,
+    }
+}
+
+pub fn parse_args<T: serde::de::DeserializeOwned>(name: &str, args: Value) -> Result<T, String> {
+    serde_json::from_value(args).map_err(|e| format!("Invalid arguments for {}: {}", name, e))
+}
+
+// `agent_id` is the calling agent, for approvals and the audit log.
+pub async fn call(app: &AppHandle, agent_id: Option<&str>, name: &str, args: Value) -> Result<Value, String> {
+    features::ensure(Feature::Agents)?;
+    let args = normalize_args(args);
+    log::info!("Agent {:?} calls tool {}", agent_id, name);
+    moderation::review(app, agent_id, name, &args).await?;
+    match name {
+        n if n.starts_with("github_") => github::call(app, name, args).await,
+        n if n.starts_with("git_") => git::call(app, name, args).await,
+        "shell_run" => shell::run(app, agent_id, args).await,
+        "read_file" | "write_file" | "list_dir" => files::call(app, agent_id, name, args).await,
+        n if n.starts_with("table_") => spreadsheet::call(app, name, args).await,
+        n if input_control::TOOL_NAMES.contains(&n) => input_control::call(app, agent_id, name, args).await,
+        _ => Err(format!("Unknown tool '{}'", name)),
+    }
+}
+
+fn definitions(app: &AppHandle) -> Vec<Value> {
+    // Mouse and keyboard tools are only offered once the user has turned them on.
+    let input_enabled = input_control::enabled(app);
+    specs()
+        .iter()
+        .filter(|spec| input_enabled || !input_control::TOOL_NAMES.contains(&spec.name))
+        .map(ToolSpec::definition)
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_tools(app: AppHandle) -> Vec<Value> {
+    definitions(&app)
+}
+
+#[tauri::command]
+pub async fn call_tool(
+    app: AppHandle,
+    name: String,
+    arguments: Value,
+    agent_id: Option<String>,
+) -> Result<Value, String> {
+    call(&app, agent_id.as_deref(), &name, arguments).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+}
+
+pub async fn list_handler(AxumState(state): AxumState<AppState>) -> Json<Vec<Value>> {
+    Json(definitions(&state.app_handle))
+}
+
+pub async fn call_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(call_request): Json<ToolCall>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    call(&state.app_handle, call_request.agent_id.as_deref(), &call_request.name, call_request.arguments)
+        .await
+        .map(|result| Json(json!({ "result": result })))
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
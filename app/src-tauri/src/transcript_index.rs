@@ -0,0 +1,265 @@
+// In src-tauri/src/transcript_index.rs
+//
+// Live indexing of transcription sessions into the vector store, so an
+// ongoing meeting can be searched while it's still going. Each chunk
+// processed by `/transcription/process` with a `session_id` is appended to
+// that session's buffer; once the buffer holds `chunk_chars` characters, or
+// its oldest text is `max_delay_secs` old, it's embedded and stored in the
+// "transcript:<session_id>" collection. The last `overlap_chars` of each
+// stored chunk start the next one, so a sentence cut at the boundary is
+// still found whole. Ending the session stores what's left. Buffers are
+// also checked every `FLUSH_INTERVAL`, so the delay holds when no chunk
+// follows, and a session that's had no chunk for `IDLE_SESSION` is ended as
+// if `end` had been called.
+//
+// Nothing is indexed during an incognito session.
+
+use axum::{
+    extract::{Path as AxumPath, Query, State as AxumState},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::vector_store::{self, SearchHit};
+use crate::{incognito, storage, AppState};
+
+const SETTINGS_FILE: &str = "transcript_index.json";
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+const IDLE_SESSION: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptIndexSettings {
+    pub enabled: bool,
+    pub chunk_chars: usize,
+    pub overlap_chars: usize,
+    pub max_delay_secs: u64,
+}
+
+impl Default for TranscriptIndexSettings {
+    fn default() -> Self {
+        Self { enabled: true, chunk_chars: 1000, overlap_chars: 150, max_delay_secs: 60 }
+    }
+}
+
+// A session's text not yet stored.
+struct Pending {
+    text: String,
+    // Characters added since the last stored chunk, overlap excluded.
+    new_chars: usize,
+    started_at: DateTime<Utc>,
+    ended_at: DateTime<Utc>,
+    since: Instant,
+    last_chunk: Instant,
+}
+
+#[derive(Default)]
+pub struct TranscriptIndexState {
+    settings: Mutex<TranscriptIndexSettings>,
+    sessions: Mutex<HashMap<String, Pending>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexedChunk {
+    pub session_id: String,
+    pub id: i64,
+    pub chars: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+pub fn collection(session_id: &str) -> String {
+    format!("transcript:{}", session_id)
+}
+
+fn valid_session_id(session_id: &str) -> bool {
+    !session_id.is_empty()
+        && session_id.len() <= 128
+        && session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn settings(app: &AppHandle) -> TranscriptIndexSettings {
+    app.state::<TranscriptIndexState>().settings.lock().unwrap().clone()
+}
+
+// The last `chars` characters of `text`, starting at a word.
+fn overlap(text: &str, chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= chars {
+        return text.to_string();
+    }
+    let start = text.char_indices().nth(count - chars).map_or(0, |(i, _)| i);
+    let tail = &text[start..];
+    tail.split_once(' ').map_or(tail, |(_, rest)| rest).to_string()
+}
+
+// Takes the buffered text for storing, keeping the overlap for the next chunk.
+fn take(pending: &mut Pending, settings: &TranscriptIndexSettings) -> (String, serde_json::Value) {
+    let text = pending.text.trim().to_string();
+    let metadata = json!({
+        "started_at": pending.started_at,
+        "ended_at": pending.ended_at,
+    });
+    pending.text = overlap(&text, settings.overlap_chars);
+    pending.new_chars = 0;
+    pending.started_at = pending.ended_at;
+    pending.since = Instant::now();
+    (text, metadata)
+}
+
+fn store(app: &AppHandle, session_id: &str, text: String, metadata: serde_json::Value) {
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let chars = text.chars().count();
+        match vector_store::add(&app, &collection(&session_id), &text, Some(metadata)).await {
+            Ok(id) => {
+                let indexed = IndexedChunk { session_id, id, chars };
+                if let Err(e) = app.emit("transcript-indexed", indexed) {
+                    log::error!("Failed to emit transcript-indexed event: {}", e);
+                }
+            }
+            Err(e) => log::error!("Failed to index transcript of session {}: {}", session_id, e),
+        }
+    });
+}
+
+// Called for each processed chunk of a session.
+pub fn append(app: &AppHandle, session_id: &str, text: &str, ended_at: Option<DateTime<Utc>>) {
+    let settings = settings(app);
+    let text = text.trim();
+    if !settings.enabled || incognito::is_active() || text.is_empty() || !valid_session_id(session_id) {
+        return;
+    }
+    let ended_at = ended_at.unwrap_or_else(Utc::now);
+    let ready = {
+        let state = app.state::<TranscriptIndexState>();
+        let mut sessions = state.sessions.lock().unwrap();
+        let pending = sessions.entry(session_id.to_string()).or_insert_with(|| Pending {
+            text: String::new(),
+            new_chars: 0,
+            started_at: ended_at,
+            ended_at,
+            since: Instant::now(),
+            last_chunk: Instant::now(),
+        });
+        if !pending.text.is_empty() {
+            pending.text.push(' ');
+        }
+        pending.text.push_str(text);
+        pending.new_chars += text.chars().count();
+        pending.ended_at = ended_at;
+        pending.last_chunk = Instant::now();
+        let full = pending.text.chars().count() >= settings.chunk_chars.max(1);
+        let due = pending.since.elapsed() >= Duration::from_secs(settings.max_delay_secs);
+        (full || due).then(|| take(pending, &settings))
+    };
+    if let Some((text, metadata)) = ready {
+        store(app, session_id, text, metadata);
+    }
+}
+
+// Stores what's left of a session and forgets it.
+pub fn end(app: &AppHandle, session_id: &str) {
+    let pending = app.state::<TranscriptIndexState>().sessions.lock().unwrap().remove(session_id);
+    let Some(mut pending) = pending.filter(|pending| pending.new_chars > 0) else {
+        return;
+    };
+    let (text, metadata) = take(&mut pending, &settings(app));
+    store(app, session_id, text, metadata);
+}
+
+// Stores buffers that are due and ends idle sessions.
+fn flush(app: &AppHandle) {
+    let settings = settings(app);
+    let mut ready = Vec::new();
+    app.state::<TranscriptIndexState>().sessions.lock().unwrap().retain(|session_id, pending| {
+        let idle = pending.last_chunk.elapsed() >= IDLE_SESSION;
+        let due = pending.since.elapsed() >= Duration::from_secs(settings.max_delay_secs);
+        if pending.new_chars > 0 && (idle || due) {
+            let (text, metadata) = take(pending, &settings);
+            ready.push((session_id.clone(), text, metadata));
+        }
+        !idle
+    });
+    for (session_id, text, metadata) in ready {
+        store(app, &session_id, text, metadata);
+    }
+}
+
+pub async fn search(app: &AppHandle, session_id: &str, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+    if !valid_session_id(session_id) {
+        return Err(format!("Invalid session ID '{}'", session_id));
+    }
+    vector_store::search(app, &collection(session_id), query, limit).await
+}
+
+pub async fn end_handler(AxumState(state): AxumState<AppState>, AxumPath(session_id): AxumPath<String>) -> StatusCode {
+    end(&state.app_handle, &session_id);
+    StatusCode::NO_CONTENT
+}
+
+pub async fn search_handler(
+    AxumState(state): AxumState<AppState>,
+    AxumPath(session_id): AxumPath<String>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<Vec<SearchHit>>, (StatusCode, String)> {
+    search(&state.app_handle, &session_id, &params.q, params.limit.unwrap_or(10))
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<TranscriptIndexState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+            flush(&app);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn search_transcript(
+    app: AppHandle,
+    session_id: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    search(&app, &session_id, &query, limit.unwrap_or(10)).await
+}
+
+#[tauri::command]
+pub fn end_transcript_session(app: AppHandle, session_id: String) {
+    end(&app, &session_id);
+}
+
+#[tauri::command]
+pub fn get_transcript_index_settings(state: State<'_, TranscriptIndexState>) -> TranscriptIndexSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_transcript_index_settings(
+    app: AppHandle,
+    settings: TranscriptIndexSettings,
+    state: State<'_, TranscriptIndexState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
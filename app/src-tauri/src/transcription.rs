@@ -0,0 +1,302 @@
+// In src-tauri/src/transcription.rs
+//
+// Language handling for speech transcription, which itself runs in the
+// frontend (Whisper in a web worker). Each transcribed chunk is posted to
+// `/transcription/process`, which
+//   - detects its language from the text: the script for non-Latin
+//     languages, common words for the Latin ones,
+//   - looks up the route for that language: the Whisper model to transcribe
+//     it with (the frontend transcribes the chunk again when it differs from
+//     the one it used) and instructions for cleaning up or translating it,
+//   - and, with `translate` on, translates it to `preferred_language` with
+//     `translation_model`.
+// Summaries (see summary.rs) are translated to the preferred language too
+// before they're stored. Chunks sent with a `session_id` are indexed for
+// search as they come (see transcript_index.rs).
+
+use axum::{extract::State as AxumState, http::StatusCode, Json};
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::features::{self, Feature};
+use crate::locality::{self, SensitiveContent};
+use crate::{llm, storage, transcript_index, AppState};
+
+const SETTINGS_FILE: &str = "transcription.json";
+// Below this, a detection isn't trusted enough to route or translate on.
+pub const MIN_CONFIDENCE: f64 = 0.5;
+const MIN_WORDS: usize = 3;
+
+const TRANSLATE_PROMPT: &str = "You translate transcribed speech. Translate the text you receive into the \
+requested language, keeping its meaning and tone. Answer with the translation only.";
+
+// Common words of the Latin-script languages, for telling them apart.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "is", "of", "to", "in", "that", "it", "you", "was", "for", "this", "with", "have"]),
+    ("fr", &["le", "la", "les", "et", "est", "de", "des", "un", "une", "que", "pas", "je", "vous", "pour"]),
+    ("de", &["der", "die", "das", "und", "ist", "nicht", "ich", "sie", "ein", "eine", "zu", "mit", "auf", "wir"]),
+    ("es", &["el", "la", "los", "las", "y", "es", "de", "que", "no", "un", "una", "por", "para", "con"]),
+    ("it", &["il", "la", "di", "che", "e", "è", "non", "un", "una", "per", "sono", "del", "della", "con"]),
+    ("pt", &["o", "a", "os", "as", "e", "de", "que", "não", "um", "uma", "para", "com", "do", "da"]),
+    ("nl", &["de", "het", "een", "en", "is", "van", "niet", "ik", "je", "dat", "op", "met", "voor", "zijn"]),
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageRoute {
+    // Transformers.js model id, e.g. "Xenova/whisper-small".
+    pub whisper_model: Option<String>,
+    // Extra instructions for cleaning up or translating this language.
+    pub prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionSettings {
+    pub auto_detect: bool,
+    // ISO 639-1 code, e.g. "en".
+    pub preferred_language: Option<String>,
+    pub translate: bool,
+    pub translation_model: String,
+    // Language code -> route.
+    pub routes: BTreeMap<String, LanguageRoute>,
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            auto_detect: true,
+            preferred_language: None,
+            translate: false,
+            translation_model: "gemma3:4b".to_string(),
+            routes: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TranscriptionState {
+    settings: Mutex<TranscriptionSettings>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Detection {
+    pub language: Option<String>,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProcessRequest {
+    pub text: String,
+    // The Whisper model the frontend used.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    // When the chunk's recording ended, in ms since the epoch.
+    #[serde(default)]
+    pub ended_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessedTranscript {
+    pub language: Option<String>,
+    pub confidence: f64,
+    // Set when the language is routed to another model than the one used.
+    pub retranscribe_with: Option<String>,
+    pub text: String,
+    // The text before translation, when it was translated.
+    pub original: Option<String>,
+}
+
+fn script_language(c: char) -> Option<&'static str> {
+    match c as u32 {
+        0x0400..=0x04FF => Some("ru"),
+        0x0370..=0x03FF => Some("el"),
+        0x0590..=0x05FF => Some("he"),
+        0x0600..=0x06FF => Some("ar"),
+        0x0900..=0x097F => Some("hi"),
+        0x0E00..=0x0E7F => Some("th"),
+        0x3040..=0x30FF => Some("ja"),
+        0xAC00..=0xD7AF | 0x1100..=0x11FF => Some("ko"),
+        0x4E00..=0x9FFF => Some("zh"),
+        _ => None,
+    }
+}
+
+pub fn detect(text: &str) -> Detection {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return Detection { language: None, confidence: 0.0 };
+    }
+
+    let mut scripts: BTreeMap<&str, usize> = BTreeMap::new();
+    for language in letters.iter().filter_map(|&c| script_language(c)) {
+        *scripts.entry(language).or_default() += 1;
+    }
+    // Japanese mixes kana with kanji, which on their own read as Chinese.
+    if scripts.contains_key("ja") {
+        let kanji = scripts.remove("zh").unwrap_or(0);
+        *scripts.entry("ja").or_default() += kanji;
+    }
+    if let Some((&language, &count)) = scripts.iter().max_by_key(|(_, &count)| count) {
+        if count * 2 >= letters.len() {
+            return Detection { language: Some(language.to_string()), confidence: count as f64 / letters.len() as f64 };
+        }
+    }
+
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < MIN_WORDS {
+        return Detection { language: None, confidence: 0.0 };
+    }
+    let mut scores: Vec<(&str, usize)> = STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            (*language, words.iter().filter(|word| stopwords.contains(&word.as_str())).count())
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1));
+    let (best, hits) = scores[0];
+    let runner_up = scores.get(1).map_or(0, |score| score.1);
+    if hits == 0 {
+        return Detection { language: None, confidence: 0.0 };
+    }
+    // How clearly the best language wins, scaled by how many words are common ones.
+    let margin = (hits - runner_up) as f64 / hits as f64;
+    let coverage = (hits as f64 / words.len() as f64 * 4.0).min(1.0);
+    Detection { language: Some(best.to_string()), confidence: (0.5 + margin / 2.0) * coverage }
+}
+
+fn settings(app: &AppHandle) -> TranscriptionSettings {
+    app.state::<TranscriptionState>().settings.lock().unwrap().clone()
+}
+
+// Whether transcribed speech may go to the model backend under the
+// data-locality rules.
+pub fn check_locality(app: &AppHandle) -> Result<(), String> {
+    let base_url = llm::ollama_base_url(app);
+    locality::check(app, None, &base_url, vec![SensitiveContent::Audio]).map_err(|v| v.to_string())
+}
+
+// Translates with `model`, or the configured translation model.
+pub async fn translate(
+    app: &AppHandle,
+    model: Option<&str>,
+    text: &str,
+    from: &str,
+    to: &str,
+) -> Result<String, String> {
+    check_locality(app)?;
+    let settings = settings(app);
+    let model = model.unwrap_or(&settings.translation_model);
+    let mut system = TRANSLATE_PROMPT.to_string();
+    if let Some(prompt) = settings.routes.get(from).and_then(|route| route.prompt.as_deref()) {
+        system.push(' ');
+        system.push_str(prompt);
+    }
+    let prompt = format!("Translate from {} to {}:\n\n{}", from, to, text);
+    llm::generate(app, model, &system, &prompt).await.map(|t| t.trim().to_string())
+}
+
+// `text` in the preferred language, when translation is on and it's in
+// another one; None when it stays as it is.
+pub async fn to_preferred_language(app: &AppHandle, text: &str) -> Option<String> {
+    let settings = settings(app);
+    let preferred = settings.preferred_language.clone().filter(|_| settings.translate)?;
+    let detection = detect(text);
+    let language = detection.language.filter(|_| detection.confidence >= MIN_CONFIDENCE)?;
+    if language == preferred {
+        return None;
+    }
+    match translate(app, None, text, &language, &preferred).await {
+        Ok(translated) => Some(translated),
+        Err(e) => {
+            log::warn!("Couldn't translate from {} to {}: {}", language, preferred, e);
+            None
+        }
+    }
+}
+
+// The Whisper model routed to `language`, if any.
+pub fn whisper_model_for(app: &AppHandle, language: &str) -> Option<String> {
+    settings(app).routes.get(language).and_then(|route| route.whisper_model.clone())
+}
+
+pub async fn process(app: &AppHandle, request: &ProcessRequest) -> ProcessedTranscript {
+    let settings = settings(app);
+    let detection = if settings.auto_detect {
+        detect(&request.text)
+    } else {
+        Detection { language: None, confidence: 0.0 }
+    };
+    let language = detection.language.clone().filter(|_| detection.confidence >= MIN_CONFIDENCE);
+    let retranscribe_with = language
+        .as_ref()
+        .and_then(|language| settings.routes.get(language))
+        .and_then(|route| route.whisper_model.clone())
+        .filter(|model| request.model.as_ref() != Some(model));
+
+    let mut processed = ProcessedTranscript {
+        language: detection.language,
+        confidence: detection.confidence,
+        retranscribe_with,
+        text: request.text.clone(),
+        original: None,
+    };
+    // A chunk about to be transcribed again is translated after that.
+    if processed.retranscribe_with.is_none() {
+        if let Some(translated) = to_preferred_language(app, &request.text).await {
+            processed.original = Some(std::mem::replace(&mut processed.text, translated));
+        }
+        if let Some(session_id) = &request.session_id {
+            let ended_at = request.ended_at.and_then(|ms| Utc.timestamp_millis_opt(ms).single());
+            transcript_index::append(app, session_id, &processed.text, ended_at);
+        }
+    }
+    processed
+}
+
+pub async fn process_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(request): Json<ProcessRequest>,
+) -> Result<Json<ProcessedTranscript>, (StatusCode, String)> {
+    features::ensure(Feature::Audio).map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e))?;
+    Ok(Json(process(&state.app_handle, &request).await))
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<TranscriptionState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_transcription_settings(state: State<'_, TranscriptionState>) -> TranscriptionSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_transcription_settings(
+    app: AppHandle,
+    settings: TranscriptionSettings,
+    state: State<'_, TranscriptionState>,
+) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn detect_language(text: String) -> Detection {
+    detect(&text)
+}
+
+#[tauri::command]
+pub async fn process_transcript(app: AppHandle, text: String, model: Option<String>) -> ProcessedTranscript {
+    process(&app, &ProcessRequest { text, model, session_id: None, ended_at: None }).await
+}
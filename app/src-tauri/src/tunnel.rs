@@ -0,0 +1,391 @@
+// In src-tauri/src/tunnel.rs
+//
+// Reaching Observer from outside the LAN (say, a phone while away) through
+// a cloudflared quick tunnel or Tailscale Funnel, started and supervised by
+// the app.
+//
+// The tunnel never points at the regular server. `start_tunnel` serves the
+// same routes on a fresh loopback port that demands a random access token
+// on every request (a bearer header, `?token=`, or the cookie the first
+// `?token=` visit sets), so the local, unauthenticated server stays local.
+// A `?token=` is taken off the URL before the request goes any further, so
+// it's neither logged nor proxied to the backend. Listeners on other network
+// interfaces (config.toml's [server] bind) use the same check, except for
+// the `/share/*` routes other Observers pull models from (see lib.rs).
+// The tunnel process is restarted if it dies; repeated failures to come up
+// give up with an error. Every change is emitted as "tunnel-status".
+
+use axum::{
+    extract::{Request, State as AxumState},
+    http::{header, HeaderValue, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::watch;
+
+use crate::{llm, server, storage, AppState};
+
+const SETTINGS_FILE: &str = "tunnel.json";
+const COOKIE: &str = "observer_tunnel";
+// Consecutive starts that never produced a public URL before giving up.
+const MAX_FAILURES: u32 = 5;
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TunnelProvider {
+    #[default]
+    Cloudflared,
+    TailscaleFunnel,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TunnelSettings {
+    pub provider: TunnelProvider,
+    // The cloudflared or tailscale executable; found on PATH when unset.
+    pub binary: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TunnelPhase {
+    #[default]
+    Stopped,
+    Starting,
+    Running,
+    Restarting,
+    Failed,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TunnelStatus {
+    pub phase: TunnelPhase,
+    pub provider: TunnelProvider,
+    pub public_url: Option<String>,
+    // The public URL with the access token, for opening on the phone.
+    pub access_url: Option<String>,
+    pub local_port: Option<u16>,
+    pub restarts: u32,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct TunnelState {
+    status: Mutex<TunnelStatus>,
+    token: Mutex<Option<String>>,
+    stop: Mutex<Option<watch::Sender<bool>>>,
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut TunnelStatus)) {
+    let state = app.state::<TunnelState>();
+    let status = {
+        let mut status = state.status.lock().unwrap();
+        change(&mut status);
+        status.access_url = match (&status.public_url, state.token.lock().unwrap().as_ref()) {
+            (Some(url), Some(token)) => Some(format!("{}/?token={}", url.trim_end_matches('/'), token)),
+            _ => None,
+        };
+        status.clone()
+    };
+    if let Err(e) = app.emit("tunnel-status", status) {
+        log::error!("Failed to emit tunnel-status event: {}", e);
+    }
+}
+
+fn find_binary(name: &str) -> Option<PathBuf> {
+    let name = if cfg!(target_os = "windows") { format!("{}.exe", name) } else { name.to_string() };
+    let mut candidates: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).map(|dir| dir.join(&name)).collect())
+        .unwrap_or_default();
+    #[cfg(not(target_os = "windows"))]
+    candidates.extend(["/usr/local/bin", "/usr/bin", "/opt/homebrew/bin"].map(|dir| PathBuf::from(dir).join(&name)));
+    #[cfg(target_os = "macos")]
+    candidates.push(PathBuf::from("/Applications/Tailscale.app/Contents/MacOS/Tailscale"));
+    candidates.into_iter().find(|p| p.is_file())
+}
+
+fn command(settings: &TunnelSettings, port: u16) -> Result<Command, String> {
+    let name = match settings.provider {
+        TunnelProvider::Cloudflared => "cloudflared",
+        TunnelProvider::TailscaleFunnel => "tailscale",
+    };
+    let binary = match &settings.binary {
+        Some(binary) => PathBuf::from(binary),
+        None => find_binary(name).ok_or_else(|| format!("{} isn't installed (or isn't on PATH)", name))?,
+    };
+    let mut command = Command::new(binary);
+    match settings.provider {
+        TunnelProvider::Cloudflared => {
+            command.args(["tunnel", "--no-autoupdate", "--url", &format!("http://127.0.0.1:{}", port)])
+        }
+        TunnelProvider::TailscaleFunnel => command.args(["funnel", &port.to_string()]),
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    Ok(command)
+}
+
+// The public URL, once the tunnel prints it.
+fn public_url(provider: TunnelProvider, line: &str) -> Option<String> {
+    let suffix = match provider {
+        TunnelProvider::Cloudflared => ".trycloudflare.com",
+        TunnelProvider::TailscaleFunnel => ".ts.net",
+    };
+    line.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| c == '|' || c == '"'))
+        .find(|word| word.starts_with("https://") && word.trim_end_matches('/').ends_with(suffix))
+        .map(|url| url.trim_end_matches('/').to_string())
+}
+
+// Runs the tunnel until it exits or is stopped. Returns whether it was stopped.
+async fn run_once(
+    app: &AppHandle,
+    mut child: Child,
+    provider: TunnelProvider,
+    stop: &mut watch::Receiver<bool>,
+) -> bool {
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+    let mut stderr = BufReader::new(child.stderr.take().expect("stderr is piped")).lines();
+    let mut last_line = String::new();
+    let exit = loop {
+        let line = tokio::select! {
+            _ = stop.changed() => {
+                let _ = child.kill().await;
+                return true;
+            }
+            Ok(Some(line)) = stdout.next_line() => line,
+            Ok(Some(line)) = stderr.next_line() => line,
+            status = child.wait() => break status,
+        };
+        log::debug!("tunnel: {}", line);
+        if let Some(url) = public_url(provider, &line) {
+            log::info!("Tunnel is up at {}", url);
+            update(app, |status| {
+                status.phase = TunnelPhase::Running;
+                status.public_url = Some(url);
+                status.error = None;
+            });
+        }
+        last_line = line;
+    };
+    let reason = match exit {
+        Ok(status) => format!("exited with {}", status),
+        Err(e) => e.to_string(),
+    };
+    log::warn!("Tunnel {}: {}", reason, last_line);
+    update(app, |status| status.error = Some(format!("The tunnel {}: {}", reason, last_line)));
+    false
+}
+
+async fn supervise(app: AppHandle, settings: TunnelSettings, port: u16, mut stop: watch::Receiver<bool>) {
+    let mut failures = 0;
+    loop {
+        let spawned = command(&settings, port).and_then(|mut c| c.spawn().map_err(|e| e.to_string()));
+        let child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                log::error!("Failed to start the tunnel: {}", e);
+                update(&app, |status| {
+                    status.phase = TunnelPhase::Failed;
+                    status.error = Some(e);
+                });
+                return;
+            }
+        };
+        if run_once(&app, child, settings.provider, &mut stop).await {
+            return;
+        }
+
+        let was_up = app.state::<TunnelState>().status.lock().unwrap().phase == TunnelPhase::Running;
+        failures = if was_up { 0 } else { failures + 1 };
+        if failures >= MAX_FAILURES {
+            update(&app, |status| status.phase = TunnelPhase::Failed);
+            return;
+        }
+        let backoff = (Duration::from_secs(2) * 2u32.pow(failures)).min(MAX_BACKOFF);
+        update(&app, |status| {
+            status.phase = TunnelPhase::Restarting;
+            status.public_url = None;
+            status.restarts += 1;
+        });
+        tokio::select! {
+            _ = stop.changed() => return,
+            _ = tokio::time::sleep(backoff) => {}
+        }
+    }
+}
+
+fn bearer(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+fn query_token(request: &Request) -> Option<&str> {
+    request
+        .uri()
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+}
+
+fn cookie_token(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|pair| pair.trim().strip_prefix(COOKIE)?.strip_prefix('='))
+}
+
+fn strip_query_token(request: &mut Request) {
+    let Some(query) = request.uri().query() else {
+        return;
+    };
+    let rest: Vec<&str> = query.split('&').filter(|pair| !pair.starts_with("token=")).collect();
+    let path_and_query = match rest.is_empty() {
+        true => request.uri().path().to_string(),
+        false => format!("{}?{}", request.uri().path(), rest.join("&")),
+    };
+    let Ok(path_and_query) = path_and_query.parse() else {
+        return;
+    };
+    let mut parts = request.uri().clone().into_parts();
+    parts.path_and_query = Some(path_and_query);
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *request.uri_mut() = uri;
+    }
+}
+
+// Looks at every byte whatever the first difference, so the time taken
+// doesn't tell a guesser how much of the token they got right.
+pub fn same_token(candidate: &str, token: &str) -> bool {
+    candidate.len() == token.len() && candidate.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+pub async fn require_token(AxumState(token): AxumState<Arc<String>>, mut request: Request, next: Next) -> Response {
+    let matches = |candidate: Option<&str>| candidate.is_some_and(|candidate| same_token(candidate, &token));
+    if matches(bearer(&request)) || matches(cookie_token(&request)) {
+        strip_query_token(&mut request);
+        return next.run(request).await;
+    }
+    if !matches(query_token(&request)) {
+        return (StatusCode::UNAUTHORIZED, "This Observer needs its access token").into_response();
+    }
+    strip_query_token(&mut request);
+    // Remember the token so the page's own requests get through too.
+    let mut response = next.run(request).await;
+    let cookie = format!("{}={}; Path=/; HttpOnly; Secure; SameSite=Strict", COOKIE, token);
+    if let Ok(value) = HeaderValue::from_str(&cookie) {
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+    response
+}
+
+// `require_token` for a LAN listener.
+pub async fn require_token_except_share(token: AxumState<Arc<String>>, request: Request, next: Next) -> Response {
+    if request.uri().path().starts_with("/share/") {
+        return next.run(request).await;
+    }
+    require_token(token, request, next).await
+}
+
+#[tauri::command]
+pub fn get_tunnel_status(state: State<'_, TunnelState>) -> TunnelStatus {
+    state.status.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn get_tunnel_settings(app: AppHandle) -> TunnelSettings {
+    storage::load_json(&app, SETTINGS_FILE)
+}
+
+#[tauri::command]
+pub fn set_tunnel_settings(app: AppHandle, settings: TunnelSettings) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)
+}
+
+#[tauri::command]
+pub async fn start_tunnel(app: AppHandle, provider: Option<TunnelProvider>) -> Result<TunnelStatus, String> {
+    let state = app.state::<TunnelState>();
+    let failed = state.status.lock().unwrap().phase == TunnelPhase::Failed;
+    if let Some(previous) = state.stop.lock().unwrap().as_ref() {
+        if !failed {
+            return Err("A tunnel is already running".to_string());
+        }
+        // A tunnel that gave up still has its server; close it first.
+        let _ = previous.send(true);
+    }
+    let mut settings: TunnelSettings = storage::load_json(&app, SETTINGS_FILE);
+    if let Some(provider) = provider {
+        settings.provider = provider;
+    }
+    // Fail fast on a missing binary instead of in the background.
+    command(&settings, 0)?;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("Failed to open a port for the tunnel: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let router = server::build_router(
+        AppState { app_handle: app.clone(), http_client: llm::client().clone() },
+        server::static_dir(&app),
+    )
+    .layer(axum::middleware::from_fn_with_state(Arc::new(token.clone()), require_token));
+
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let mut shutdown = stop_rx.clone();
+    tauri::async_runtime::spawn(async move {
+        let service = router.into_make_service_with_connect_info::<std::net::SocketAddr>();
+        let served = axum::serve(listener, service).with_graceful_shutdown(async move {
+            let _ = shutdown.changed().await;
+        });
+        if let Err(e) = served.await {
+            log::error!("Tunnel server error: {}", e);
+        }
+    });
+
+    *state.token.lock().unwrap() = Some(token);
+    *state.stop.lock().unwrap() = Some(stop_tx);
+    update(&app, |status| {
+        *status = TunnelStatus {
+            phase: TunnelPhase::Starting,
+            provider: settings.provider,
+            local_port: Some(port),
+            ..Default::default()
+        }
+    });
+    log::info!("Starting a {:?} tunnel to 127.0.0.1:{}", settings.provider, port);
+    tauri::async_runtime::spawn(supervise(app.clone(), settings, port, stop_rx));
+    Ok(state.status.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub fn stop_tunnel(app: AppHandle) -> Result<(), String> {
+    let state = app.state::<TunnelState>();
+    let stop = state.stop.lock().unwrap().take().ok_or("No tunnel is running")?;
+    let _ = stop.send(true);
+    *state.token.lock().unwrap() = None;
+    log::info!("Tunnel stopped");
+    update(&app, |status| {
+        let provider = status.provider;
+        *status = TunnelStatus { provider, ..Default::default() }
+    });
+    Ok(())
+}
@@ -0,0 +1,472 @@
+// In src-tauri/src/ui_elements.rs
+//
+// Detection of UI elements (buttons, inputs, checkboxes, dialogs) in a
+// captured frame, so agents know what's clickable on screen and where:
+// the groundwork for "click the Save button" assistance and for grounding
+// questions about the screen.
+//
+// Two stages, picked by `mode`:
+//   - heuristic: finds the outlines of boxes in the image, i.e. rectangles
+//     whose four sides show up as edges, and classifies them by size and
+//     shape. Labels come from the OCR words the frontend sends along:
+//     the words inside a box, or next to it for inputs and checkboxes.
+//   - vision: asks `vision_model` for the elements as JSON. It's slower but
+//     finds borderless buttons and reads labels itself.
+// With both, vision elements fill in what the heuristic missed.
+//
+// Coordinates are in pixels of the image that was sent.
+
+use axum::{extract::State as AxumState, http::StatusCode, Json};
+use image::{imageops::FilterType, DynamicImage, GrayImage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::llm::{self, ChatMessage};
+use crate::{annotate, capture, storage, AppState};
+
+const SETTINGS_FILE: &str = "ui_elements.json";
+// Frames are analysed at most this wide; coordinates are scaled back.
+const ANALYSIS_WIDTH: u32 = 1600;
+// Minimum brightness step between neighbouring pixels that counts as an edge.
+const EDGE_THRESHOLD: i16 = 18;
+const MIN_CONTROL_WIDTH: u32 = 14;
+const MIN_CONTROL_HEIGHT: u32 = 10;
+const MAX_CONTROL_HEIGHT: u32 = 80;
+// How far the ends of a box's top and bottom edges may differ.
+const SIDE_TOLERANCE: u32 = 3;
+// Share of a side that must be edge pixels.
+const MIN_SIDE_COVERAGE: f32 = 0.8;
+const MAX_ELEMENTS: usize = 200;
+
+const VISION_PROMPT: &str = "List the interactive UI elements visible in this screenshot: buttons, text inputs, \
+checkboxes and dialogs. Answer with JSON only: an array of objects with \"kind\" (button, input, checkbox or \
+dialog), \"label\" (the visible text or placeholder, or null) and \"x\", \"y\", \"width\", \"height\" in pixels of \
+the image.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementKind {
+    Button,
+    Input,
+    Checkbox,
+    Dialog,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionMode {
+    #[default]
+    Heuristic,
+    Vision,
+    Both,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    pub mode: DetectionMode,
+    pub vision_model: Option<String>,
+    pub min_confidence: f32,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self { mode: DetectionMode::Heuristic, vision_model: None, min_confidence: 0.5 }
+    }
+}
+
+#[derive(Default)]
+pub struct UiState {
+    settings: Mutex<UiSettings>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    fn intersection(&self, other: &Rect) -> u64 {
+        let w = self.right().min(other.right()).saturating_sub(self.x.max(other.x));
+        let h = self.bottom().min(other.bottom()).saturating_sub(self.y.max(other.y));
+        w as u64 * h as u64
+    }
+
+    fn iou(&self, other: &Rect) -> f32 {
+        let intersection = self.intersection(other);
+        intersection as f32 / (self.area() + other.area() - intersection).max(1) as f32
+    }
+
+    fn contains_center(&self, other: &Rect) -> bool {
+        let (cx, cy) = (other.x + other.width / 2, other.y + other.height / 2);
+        (self.x..self.right()).contains(&cx) && (self.y..self.bottom()).contains(&cy)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UiElement {
+    pub kind: ElementKind,
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub rect: Rect,
+    pub confidence: f32,
+    // "heuristic" or "vision".
+    pub source: &'static str,
+}
+
+// A word from the frontend's OCR, in image pixels.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OcrWord {
+    pub text: String,
+    pub x0: u32,
+    pub y0: u32,
+    pub x1: u32,
+    pub y1: u32,
+}
+
+impl OcrWord {
+    fn rect(&self) -> Rect {
+        Rect { x: self.x0, y: self.y0, width: self.x1.saturating_sub(self.x0), height: self.y1.saturating_sub(self.y0) }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DetectRequest {
+    pub image: String,
+    #[serde(default)]
+    pub words: Vec<OcrWord>,
+    #[serde(default)]
+    pub mode: Option<DetectionMode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UiElements {
+    pub width: u32,
+    pub height: u32,
+    pub elements: Vec<UiElement>,
+}
+
+// Horizontal (dy) and vertical (dx) edge maps.
+fn edges(gray: &GrayImage) -> (Vec<bool>, Vec<bool>) {
+    let (w, h) = gray.dimensions();
+    let at = |x: u32, y: u32| gray.get_pixel(x, y)[0] as i16;
+    let mut horizontal = vec![false; (w * h) as usize];
+    let mut vertical = vec![false; (w * h) as usize];
+    for y in 0..h.saturating_sub(1) {
+        for x in 0..w.saturating_sub(1) {
+            let i = (y * w + x) as usize;
+            horizontal[i] = (at(x, y + 1) - at(x, y)).abs() >= EDGE_THRESHOLD;
+            vertical[i] = (at(x + 1, y) - at(x, y)).abs() >= EDGE_THRESHOLD;
+        }
+    }
+    (horizontal, vertical)
+}
+
+// Runs of horizontal edge pixels per row, as (start, end) exclusive.
+fn runs(horizontal: &[bool], w: u32, h: u32) -> Vec<Vec<(u32, u32)>> {
+    (0..h)
+        .map(|y| {
+            let row = &horizontal[(y * w) as usize..((y + 1) * w) as usize];
+            let mut runs = Vec::new();
+            let mut start = None;
+            for (x, &edge) in row.iter().chain(std::iter::once(&false)).enumerate() {
+                match (edge, start) {
+                    (true, None) => start = Some(x as u32),
+                    (false, Some(s)) => {
+                        if x as u32 - s >= MIN_CONTROL_WIDTH {
+                            runs.push((s, x as u32));
+                        }
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+            runs
+        })
+        .collect()
+}
+
+// Share of edge pixels along column `x` (or either neighbour) from `y0` to `y1`.
+fn side_coverage(vertical: &[bool], w: u32, x: u32, y0: u32, y1: u32) -> f32 {
+    let hits = (y0..y1)
+        .filter(|&y| {
+            (x.saturating_sub(1)..=(x + 1).min(w - 1)).any(|xx| vertical[(y * w + xx) as usize])
+        })
+        .count();
+    hits as f32 / (y1 - y0).max(1) as f32
+}
+
+// Rectangles whose four sides are edges, with the coverage of their sides.
+fn boxes(gray: &GrayImage) -> Vec<(Rect, f32)> {
+    let (w, h) = gray.dimensions();
+    if w < 3 || h < 3 {
+        return Vec::new();
+    }
+    let (horizontal, vertical) = edges(gray);
+    let rows = runs(&horizontal, w, h);
+    let mut found: Vec<(Rect, f32)> = Vec::new();
+    for (top_y, top_runs) in rows.iter().enumerate() {
+        for &(x0, x1) in top_runs {
+            // Long edges may be the top of a dialog.
+            let max_height = if x1 - x0 >= w / 5 { h } else { MAX_CONTROL_HEIGHT };
+            let top_y = top_y as u32;
+            let bottom = (top_y + MIN_CONTROL_HEIGHT..(top_y + max_height).min(h)).find_map(|y| {
+                rows[y as usize]
+                    .iter()
+                    .find(|&&(bx0, bx1)| bx0.abs_diff(x0) <= SIDE_TOLERANCE && bx1.abs_diff(x1) <= SIDE_TOLERANCE)
+                    .map(|_| y)
+            });
+            let Some(bottom_y) = bottom else {
+                continue;
+            };
+            // The edge between rows y and y + 1 belongs to the box on both.
+            let (y0, y1) = (top_y + 1, bottom_y + 1);
+            let left = side_coverage(&vertical, w, x0.saturating_sub(1), y0, y1);
+            let right = side_coverage(&vertical, w, x1.saturating_sub(1), y0, y1);
+            let coverage = left.min(right);
+            if coverage >= MIN_SIDE_COVERAGE {
+                found.push((Rect { x: x0, y: y0, width: x1 - x0, height: y1 - y0 }, coverage));
+            }
+        }
+    }
+
+    // Borders are a couple of pixels thick, so each box shows up several times.
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut kept: Vec<(Rect, f32)> = Vec::new();
+    for (rect, coverage) in found {
+        if kept.iter().all(|(other, _)| rect.iou(other) < 0.7) {
+            kept.push((rect, coverage));
+        }
+    }
+    kept
+}
+
+fn classify(rect: &Rect, image_width: u32, image_height: u32, has_text_inside: bool) -> Option<ElementKind> {
+    let aspect = rect.width as f32 / rect.height.max(1) as f32;
+    if rect.width >= image_width / 5 && rect.height >= image_height / 6 {
+        // Boxes covering nearly the whole frame are the window, not a dialog.
+        return (rect.width < image_width * 9 / 10 || rect.height < image_height * 9 / 10)
+            .then_some(ElementKind::Dialog);
+    }
+    if rect.height > MAX_CONTROL_HEIGHT {
+        return None;
+    }
+    if (0.8..=1.25).contains(&aspect) && rect.width <= 32 {
+        return Some(ElementKind::Checkbox);
+    }
+    if aspect >= 5.0 && !has_text_inside {
+        return Some(ElementKind::Input);
+    }
+    if (1.2..=10.0).contains(&aspect) && has_text_inside {
+        return Some(if aspect >= 6.0 { ElementKind::Input } else { ElementKind::Button });
+    }
+    None
+}
+
+fn words_text(words: &[&OcrWord]) -> Option<String> {
+    let text = words.iter().map(|word| word.text.trim()).filter(|t| !t.is_empty()).collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+// The words inside `rect`, or for inputs and checkboxes the ones right next to it.
+fn label_for(kind: ElementKind, rect: &Rect, words: &[OcrWord]) -> Option<String> {
+    let mut inside: Vec<&OcrWord> = words.iter().filter(|word| rect.contains_center(&word.rect())).collect();
+    // Controls hold one line of text.
+    inside.sort_by_key(|word| word.x0);
+    if kind == ElementKind::Dialog {
+        // A dialog's title is its top line.
+        let top = inside.iter().map(|word| word.y0).min()?;
+        let title: Vec<&OcrWord> = inside.into_iter().filter(|word| word.y0 <= top + 8).collect();
+        return words_text(&title);
+    }
+    if let Some(text) = words_text(&inside) {
+        return Some(text);
+    }
+    let same_line = |word: &&OcrWord| word.y1 > rect.y && word.y0 < rect.bottom();
+    let mut beside: Vec<&OcrWord> = match kind {
+        ElementKind::Checkbox => words
+            .iter()
+            .filter(same_line)
+            .filter(|word| word.x0 >= rect.right() && word.x0 - rect.right() < rect.width * 8)
+            .collect(),
+        _ => words
+            .iter()
+            .filter(same_line)
+            .filter(|word| word.x1 <= rect.x && rect.x - word.x1 < rect.height * 6)
+            .collect(),
+    };
+    beside.sort_by_key(|word| word.x0);
+    words_text(&beside)
+}
+
+pub fn detect_heuristic(image: &DynamicImage, words: &[OcrWord]) -> Vec<UiElement> {
+    let (width, height) = (image.width(), image.height());
+    let scale = if width > ANALYSIS_WIDTH { width as f32 / ANALYSIS_WIDTH as f32 } else { 1.0 };
+    let gray = if scale > 1.0 {
+        image.resize(ANALYSIS_WIDTH, (height as f32 / scale) as u32, FilterType::Triangle).to_luma8()
+    } else {
+        image.to_luma8()
+    };
+    let up = |v: u32| (v as f32 * scale).round() as u32;
+
+    boxes(&gray)
+        .into_iter()
+        .filter_map(|(rect, coverage)| {
+            let rect = Rect { x: up(rect.x), y: up(rect.y), width: up(rect.width), height: up(rect.height) };
+            let has_text_inside = words.iter().any(|word| rect.contains_center(&word.rect()));
+            let kind = classify(&rect, width, height, has_text_inside)?;
+            let label = label_for(kind, &rect, words);
+            Some(UiElement { kind, label, rect, confidence: coverage, source: "heuristic" })
+        })
+        .collect()
+}
+
+fn parse_vision(answer: &str, width: u32, height: u32) -> Result<Vec<UiElement>, String> {
+    let start = answer.find('[').ok_or("The model didn't answer with a JSON array")?;
+    let end = answer.rfind(']').filter(|&end| end > start).ok_or("The model didn't answer with a JSON array")?;
+    let items: Vec<Value> =
+        serde_json::from_str(&answer[start..=end]).map_err(|e| format!("Invalid element list: {}", e))?;
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            let kind: ElementKind = serde_json::from_value(item["kind"].clone()).ok()?;
+            let number = |key: &str| item[key].as_f64().map(|v| v.max(0.0) as u32);
+            let (x, y) = (number("x")?.min(width), number("y")?.min(height));
+            let rect = Rect {
+                x,
+                y,
+                width: number("width")?.min(width - x),
+                height: number("height")?.min(height - y),
+            };
+            let label = item["label"].as_str().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string);
+            Some(UiElement { kind, label, rect, confidence: 0.7, source: "vision" })
+        })
+        .collect())
+}
+
+async fn detect_vision(
+    app: &AppHandle,
+    model: &str,
+    image: &str,
+    width: u32,
+    height: u32,
+) -> Result<Vec<UiElement>, String> {
+    let encoded = image.split_once(',').map_or(image, |(_, rest)| rest).to_string();
+    let prompt = format!("{} The image is {}x{} pixels.", VISION_PROMPT, width, height);
+    let answer = llm::chat(app, model, vec![ChatMessage::new("user", prompt).with_images(vec![encoded])]).await?;
+    parse_vision(&answer, width, height)
+}
+
+pub async fn detect(
+    app: &AppHandle,
+    image: &str,
+    words: Vec<OcrWord>,
+    mode: Option<DetectionMode>,
+) -> Result<UiElements, String> {
+    let settings = app.state::<UiState>().settings.lock().unwrap().clone();
+    let mode = mode.unwrap_or(settings.mode);
+    let data = image.to_string();
+    let (width, height, mut elements) = tokio::task::spawn_blocking(move || {
+        let decoded = DynamicImage::ImageRgba8(annotate::decode(&data)?);
+        let elements = match mode {
+            DetectionMode::Vision => Vec::new(),
+            DetectionMode::Heuristic | DetectionMode::Both => detect_heuristic(&decoded, &words),
+        };
+        Ok::<_, String>((decoded.width(), decoded.height(), elements))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    elements.retain(|element| element.confidence >= settings.min_confidence);
+
+    if mode != DetectionMode::Heuristic {
+        let model = settings.vision_model.as_deref().ok_or("No vision model is configured for UI detection")?;
+        match detect_vision(app, model, image, width, height).await {
+            Ok(found) => {
+                for element in found {
+                    if elements.iter().all(|known| known.rect.iou(&element.rect) < 0.5) {
+                        elements.push(element);
+                    }
+                }
+            }
+            // With both stages, the heuristic's elements are still worth returning.
+            Err(e) if mode == DetectionMode::Both => log::warn!("Vision UI detection failed: {}", e),
+            Err(e) => return Err(e),
+        }
+    }
+
+    elements.sort_by_key(|element| (element.rect.y, element.rect.x));
+    elements.truncate(MAX_ELEMENTS);
+    Ok(UiElements { width, height, elements })
+}
+
+pub async fn detect_handler(
+    AxumState(state): AxumState<AppState>,
+    Json(request): Json<DetectRequest>,
+) -> Result<Json<UiElements>, (StatusCode, String)> {
+    detect(&state.app_handle, &request.image, request.words, request.mode)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<UiState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_ui_detection_settings(state: State<'_, UiState>) -> UiSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_ui_detection_settings(
+    app: AppHandle,
+    settings: UiSettings,
+    state: State<'_, UiState>,
+) -> Result<(), String> {
+    if settings.mode != DetectionMode::Heuristic && settings.vision_model.is_none() {
+        return Err("Vision detection needs a vision model".to_string());
+    }
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn detect_ui_elements(
+    app: AppHandle,
+    image: String,
+    words: Option<Vec<OcrWord>>,
+    mode: Option<DetectionMode>,
+) -> Result<UiElements, String> {
+    detect(&app, &image, words.unwrap_or_default(), mode).await
+}
+
+// Captures the screen natively and detects its elements; without OCR words,
+// only boxes with text found by the vision stage have labels.
+#[tauri::command]
+pub async fn detect_screen_ui_elements(
+    app: AppHandle,
+    display: Option<usize>,
+    mode: Option<DetectionMode>,
+) -> Result<UiElements, String> {
+    let screen = capture::capture(&app, display.unwrap_or(0)).await?;
+    detect(&app, &screen.image.base64, Vec::new(), mode).await
+}
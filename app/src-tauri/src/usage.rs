@@ -0,0 +1,388 @@
+// In src-tauri/src/usage.rs
+//
+// Token usage of every proxied generation and a rough idea of what it cost.
+// Ollama reports the token counts and generation time in its last response
+// line (the OpenAI-compatible routes send a `usage` object instead); each
+// answer's numbers are stored in the history database with the agent, model
+// and backend, and the generation speed feeds backend routing (see
+// backends.rs).
+//
+// Local inference (see locality.rs) is costed in energy: generation time
+// times the GPU's draw, which is `gpu_watts` or guessed from the hardware
+// (250 W for a discrete GPU, 40 W for Apple Silicon, 65 W without a GPU),
+// and optionally in dollars at `electricity_per_kwh`. Cloud backends are
+// costed from per-model prices per million tokens. `/usage/cost` and
+// `get_usage_cost` report both per agent and day, and the daily summary
+// mentions today's totals.
+
+use axum::{
+    body::Bytes,
+    extract::{Query, State as AxumState},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+use crate::analytics::{self, TimeParams};
+use crate::history::HistoryDb;
+use crate::locality::{self, Locality};
+use crate::{backends, hardware, incognito, request_id, storage, AppState};
+
+const SETTINGS_FILE: &str = "usage_costs.json";
+const DISCRETE_GPU_WATTS: f64 = 250.0;
+const UNIFIED_GPU_WATTS: f64 = 40.0;
+const CPU_WATTS: f64 = 65.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TokenPrice {
+    // Dollars per million tokens.
+    pub input: f64,
+    pub output: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostSettings {
+    // None guesses from the hardware.
+    pub gpu_watts: Option<f64>,
+    pub electricity_per_kwh: Option<f64>,
+    // Model name -> price on cloud backends.
+    pub prices: BTreeMap<String, TokenPrice>,
+    // For cloud models without a price of their own.
+    pub default_price: TokenPrice,
+}
+
+impl Default for CostSettings {
+    fn default() -> Self {
+        Self {
+            gpu_watts: None,
+            electricity_per_kwh: None,
+            prices: BTreeMap::new(),
+            default_price: TokenPrice { input: 0.5, output: 1.5 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counts {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AgentCost {
+    pub agent_id: Option<String>,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub local_seconds: f64,
+    pub energy_wh: f64,
+    // Energy at the electricity price, when there is one.
+    pub energy_cost_usd: Option<f64>,
+    pub cloud_cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DayCost {
+    pub date: NaiveDate,
+    pub agents: Vec<AgentCost>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub gpu_watts: f64,
+    pub days: Vec<DayCost>,
+    pub total: AgentCost,
+}
+
+fn settings(app: &AppHandle) -> CostSettings {
+    storage::load_json(app, SETTINGS_FILE)
+}
+
+static GUESSED_WATTS: OnceLock<f64> = OnceLock::new();
+
+fn guess_watts(hardware: &hardware::HardwareInfo) -> f64 {
+    if hardware.gpus.iter().any(|gpu| !gpu.unified_memory) {
+        DISCRETE_GPU_WATTS
+    } else if hardware.gpus.iter().any(|gpu| gpu.unified_memory) {
+        UNIFIED_GPU_WATTS
+    } else {
+        CPU_WATTS
+    }
+}
+
+fn gpu_watts(settings: &CostSettings) -> f64 {
+    settings.gpu_watts.or_else(|| GUESSED_WATTS.get().copied()).unwrap_or(DISCRETE_GPU_WATTS)
+}
+
+// The usage in one response line, if it's the one that has it.
+fn parse_counts(line: &[u8]) -> Option<Counts> {
+    let text = std::str::from_utf8(line).ok()?.trim();
+    let text = text.strip_prefix("data:").map_or(text, str::trim_start);
+    if !text.contains("eval_count") && !text.contains("\"usage\"") {
+        return None;
+    }
+    let value: Value = serde_json::from_str(text).ok()?;
+    if let Some(completion_tokens) = value["eval_count"].as_u64() {
+        return Some(Counts {
+            prompt_tokens: value["prompt_eval_count"].as_u64().unwrap_or(0),
+            completion_tokens,
+            duration_ms: value["total_duration"].as_u64().map(|ns| ns / 1_000_000),
+        });
+    }
+    let usage = &value["usage"];
+    Some(Counts {
+        prompt_tokens: usage["prompt_tokens"].as_u64()?,
+        completion_tokens: usage["completion_tokens"].as_u64().unwrap_or(0),
+        duration_ms: None,
+    })
+}
+
+// Watches one answer on its way to the client.
+pub struct Meter {
+    app: AppHandle,
+    agent_id: Option<String>,
+    model: String,
+    backend: String,
+    request_id: Option<String>,
+    started: Instant,
+}
+
+impl Meter {
+    pub fn new(app: &AppHandle, agent_id: Option<String>, model: String, backend: &str) -> Self {
+        Self {
+            app: app.clone(),
+            agent_id,
+            model,
+            backend: backend.to_string(),
+            request_id: request_id::current(),
+            started: Instant::now(),
+        }
+    }
+
+    // Passes the answer through, recording its usage once it has been read.
+    pub fn wrap(
+        self,
+        upstream: BoxStream<'static, reqwest::Result<Bytes>>,
+    ) -> BoxStream<'static, reqwest::Result<Bytes>> {
+        async_stream::stream! {
+            let mut upstream = upstream;
+            let mut line = Vec::new();
+            let mut counts = None;
+            while let Some(chunk) = upstream.next().await {
+                if let Ok(bytes) = &chunk {
+                    for piece in bytes.split_inclusive(|b| *b == b'\n') {
+                        line.extend_from_slice(piece);
+                        if piece.ends_with(b"\n") {
+                            counts = parse_counts(&line).or(counts);
+                            line.clear();
+                        }
+                    }
+                }
+                yield chunk;
+            }
+            if let Some(counts) = parse_counts(&line).or(counts) {
+                self.record(counts);
+            }
+        }
+        .boxed()
+    }
+
+    fn record(self, counts: Counts) {
+        let duration_ms = counts.duration_ms.unwrap_or(self.started.elapsed().as_millis() as u64);
+        backends::record_throughput(&self.app, &self.backend, counts.completion_tokens, duration_ms);
+        // Routing still learns the speed; only the accounting is skipped.
+        if incognito::is_active() {
+            return;
+        }
+        let local = locality::classify(&self.app, &self.backend) == Locality::Local;
+        let db = self.app.state::<HistoryDb>();
+        let conn = db.0.lock().unwrap();
+        let result = conn.execute(
+            "INSERT INTO usage (created_at, agent_id, model, backend, local, prompt_tokens, completion_tokens,
+                                duration_ms, request_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                Utc::now().timestamp_millis(),
+                self.agent_id,
+                self.model,
+                self.backend,
+                local,
+                counts.prompt_tokens as i64,
+                counts.completion_tokens as i64,
+                duration_ms as i64,
+                self.request_id,
+            ],
+        );
+        if let Err(e) = result {
+            log::error!("Failed to record token usage: {}", e);
+        }
+    }
+}
+
+impl AgentCost {
+    fn add(&mut self, other: &AgentCost) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.local_seconds += other.local_seconds;
+        self.energy_wh += other.energy_wh;
+        self.energy_cost_usd = match (self.energy_cost_usd, other.energy_cost_usd) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+        };
+        self.cloud_cost_usd += other.cloud_cost_usd;
+    }
+}
+
+pub fn cost_report(app: &AppHandle, from: NaiveDate, to: NaiveDate) -> Result<CostReport, String> {
+    let settings = settings(app);
+    let watts = gpu_watts(&settings);
+    let range_start = analytics::local_midnight(from).timestamp_millis();
+    let range_end = analytics::local_midnight(to + ChronoDuration::days(1)).timestamp_millis();
+
+    type Row = (i64, Option<String>, String, bool, i64, i64, i64);
+    let rows: Vec<Row> = {
+        let db = app.state::<HistoryDb>();
+        let conn = db.0.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT created_at, agent_id, model, local, prompt_tokens, completion_tokens, duration_ms
+                 FROM usage WHERE created_at >= ?1 AND created_at < ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![range_start, range_end], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<_>>().map_err(|e| e.to_string())?
+    };
+
+    let mut per_day: BTreeMap<NaiveDate, BTreeMap<Option<String>, AgentCost>> = BTreeMap::new();
+    for (created_at, agent_id, model, local, prompt_tokens, completion_tokens, duration_ms) in rows {
+        let Some(time) = Utc.timestamp_millis_opt(created_at).single() else {
+            continue;
+        };
+        let date = time.with_timezone(&Local).date_naive();
+        let mut cost = AgentCost {
+            requests: 1,
+            prompt_tokens: prompt_tokens as u64,
+            completion_tokens: completion_tokens as u64,
+            ..Default::default()
+        };
+        if local {
+            cost.local_seconds = duration_ms as f64 / 1000.0;
+            cost.energy_wh = cost.local_seconds * watts / 3600.0;
+            cost.energy_cost_usd = settings.electricity_per_kwh.map(|price| cost.energy_wh / 1000.0 * price);
+        } else {
+            let price = settings.prices.get(&model).unwrap_or(&settings.default_price);
+            cost.cloud_cost_usd =
+                (prompt_tokens as f64 * price.input + completion_tokens as f64 * price.output) / 1_000_000.0;
+        }
+        per_day
+            .entry(date)
+            .or_default()
+            .entry(agent_id.clone())
+            .or_insert_with(|| AgentCost { agent_id, ..Default::default() })
+            .add(&cost);
+    }
+
+    let mut total = AgentCost::default();
+    let days = per_day
+        .into_iter()
+        .map(|(date, agents)| {
+            let agents: Vec<AgentCost> = agents.into_values().collect();
+            for agent in &agents {
+                total.add(agent);
+            }
+            DayCost { date, agents }
+        })
+        .collect();
+    Ok(CostReport { from, to, gpu_watts: watts, days, total })
+}
+
+// Tokens `agent_id` has used since `since`, prompts included.
+pub fn agent_tokens_since(app: &AppHandle, agent_id: &str, since: DateTime<Utc>) -> Result<u64, String> {
+    let db = app.state::<HistoryDb>();
+    let conn = db.0.lock().unwrap();
+    conn.query_row(
+        "SELECT COALESCE(SUM(prompt_tokens + completion_tokens), 0) FROM usage
+         WHERE agent_id = ?1 AND created_at >= ?2",
+        params![agent_id, since.timestamp_millis()],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|tokens| tokens as u64)
+    .map_err(|e| format!("Failed to read token usage: {}", e))
+}
+
+// One-line digest for the daily summary, e.g.
+// "Model usage today: 182k tokens, 0.12 kWh locally, $0.40 in the cloud".
+pub fn today_summary_line(app: &AppHandle) -> Option<String> {
+    let today = Local::now().date_naive();
+    let total = cost_report(app, today, today).ok()?.total;
+    if total.requests == 0 {
+        return None;
+    }
+    let tokens = total.prompt_tokens + total.completion_tokens;
+    let mut line = format!("Model usage today: {}k tokens", tokens / 1000);
+    if total.energy_wh > 0.0 {
+        line.push_str(&format!(", {:.2} kWh locally", total.energy_wh / 1000.0));
+    }
+    if total.cloud_cost_usd > 0.0 {
+        line.push_str(&format!(", ${:.2} in the cloud", total.cloud_cost_usd));
+    }
+    Some(line)
+}
+
+// Guesses the GPU's draw in the background; probing shells out.
+pub fn init(app: &AppHandle) {
+    if settings(app).gpu_watts.is_none() {
+        tauri::async_runtime::spawn_blocking(|| {
+            GUESSED_WATTS.get_or_init(|| guess_watts(&hardware::probe()));
+        });
+    }
+}
+
+pub async fn cost_handler(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<TimeParams>,
+) -> Result<Json<CostReport>, (StatusCode, String)> {
+    let (from, to) = analytics::resolve_range(&params);
+    cost_report(&state.app_handle, from, to).map(Json).map_err(|e| {
+        log::error!("Failed to compute usage costs: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e)
+    })
+}
+
+#[tauri::command]
+pub fn get_usage_cost(
+    app: AppHandle,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    days: Option<u32>,
+) -> Result<CostReport, String> {
+    let (from, to) = analytics::resolve_range(&TimeParams { from, to, days });
+    cost_report(&app, from, to)
+}
+
+#[tauri::command]
+pub fn get_cost_settings(app: AppHandle) -> CostSettings {
+    settings(&app)
+}
+
+#[tauri::command]
+pub fn set_cost_settings(app: AppHandle, settings: CostSettings) -> Result<(), String> {
+    storage::save_json(&app, SETTINGS_FILE, &settings)
+}
@@ -0,0 +1,302 @@
+// In src-tauri/src/vector_store.rs
+//
+// A small vector store on top of the history DB. Texts are embedded with an
+// Ollama embedding model and stored as little-endian f32 blobs in the
+// `vectors` table, grouped by collection ("memory:<agent_id>", ...). Search is
+// brute-force cosine similarity within one collection, which is plenty for
+// the few thousand rows a collection holds. Rows whose embedding failed (no
+// embedding model pulled, server offline) are still stored and found through
+// a keyword fallback. Bulk ingestion (see ingest.rs) embeds many texts per
+// request and stores each batch in one transaction with `insert_many`.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::history::HistoryDb;
+use crate::{incognito, llm, storage};
+
+const SETTINGS_FILE: &str = "vector_store.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VectorSettings {
+    pub embedding_model: String,
+    // Bulk ingestion: texts per embedding request, their combined size, and
+    // how many requests are in flight at once.
+    pub batch_size: usize,
+    pub max_batch_chars: usize,
+    pub concurrency: usize,
+}
+
+impl Default for VectorSettings {
+    fn default() -> Self {
+        Self {
+            embedding_model: "nomic-embed-text".to_string(),
+            batch_size: 32,
+            max_batch_chars: 48_000,
+            concurrency: 2,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct VectorState(pub Mutex<VectorSettings>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VectorRecord {
+    pub id: i64,
+    pub collection: String,
+    pub text: String,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub record: VectorRecord,
+    pub score: f32,
+}
+
+fn to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+// Share of the query's words that appear in the text; used when there are no embeddings.
+fn keyword_score(query: &str, text: &str) -> f32 {
+    let text = text.to_lowercase();
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() > 2)
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    words.iter().filter(|w| text.contains(w.as_str())).count() as f32 / words.len() as f32
+}
+
+pub fn settings(app: &AppHandle) -> VectorSettings {
+    app.state::<VectorState>().0.lock().unwrap().clone()
+}
+
+pub fn embedding_model(app: &AppHandle) -> String {
+    app.state::<VectorState>().0.lock().unwrap().embedding_model.clone()
+}
+
+pub fn insert(
+    db: &HistoryDb,
+    collection: &str,
+    text: &str,
+    metadata: Option<&serde_json::Value>,
+    embedding: Option<(&str, &[f32])>,
+) -> Result<i64, String> {
+    incognito::ensure_persistent()?;
+    let conn = db.0.lock().unwrap();
+    conn.execute(
+        "INSERT INTO vectors (collection, text, metadata, embedding, model, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            collection,
+            text,
+            metadata.map(|m| m.to_string()),
+            embedding.map(|(_, e)| to_blob(e)),
+            embedding.map(|(model, _)| model),
+            Utc::now().timestamp_millis(),
+        ],
+    )
+    .map_err(|e| format!("Failed to store vector: {}", e))?;
+    Ok(conn.last_insert_rowid())
+}
+
+// Stores rows in one transaction; `embeddings` is empty when embedding failed.
+pub fn insert_many(
+    db: &HistoryDb,
+    collection: &str,
+    rows: &[(String, Option<serde_json::Value>)],
+    model: &str,
+    embeddings: &[Vec<f32>],
+) -> Result<(), String> {
+    incognito::ensure_persistent()?;
+    let mut conn = db.0.lock().unwrap();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = Utc::now().timestamp_millis();
+    for (i, (text, metadata)) in rows.iter().enumerate() {
+        let embedding = embeddings.get(i);
+        tx.execute(
+            "INSERT INTO vectors (collection, text, metadata, embedding, model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                collection,
+                text,
+                metadata.as_ref().map(|m| m.to_string()),
+                embedding.map(|e| to_blob(e)),
+                embedding.map(|_| model),
+                now,
+            ],
+        )
+        .map_err(|e| format!("Failed to store vector: {}", e))?;
+    }
+    tx.commit().map_err(|e| format!("Failed to store vectors: {}", e))
+}
+
+// Embeds and stores one text. Embedding failures are logged and the text is
+// stored without a vector so nothing the user asked to keep is lost.
+pub async fn add(
+    app: &AppHandle,
+    collection: &str,
+    text: &str,
+    metadata: Option<serde_json::Value>,
+) -> Result<i64, String> {
+    // Before the text is sent off to be embedded.
+    incognito::ensure_persistent()?;
+    let model = embedding_model(app);
+    let embedding = match llm::embed(app, &model, &[text.to_string()]).await {
+        Ok(mut embeddings) => embeddings.pop(),
+        Err(e) => {
+            log::warn!("Storing '{}' entry without embedding: {}", collection, e);
+            None
+        }
+    };
+
+    insert(
+        &app.state::<HistoryDb>(),
+        collection,
+        text,
+        metadata.as_ref(),
+        embedding.as_deref().map(|e| (model.as_str(), e)),
+    )
+}
+
+fn row_to_record(row: &rusqlite::Row) -> rusqlite::Result<(VectorRecord, Option<Vec<u8>>, Option<String>)> {
+    let metadata: Option<String> = row.get(3)?;
+    Ok((
+        VectorRecord {
+            id: row.get(0)?,
+            collection: row.get(1)?,
+            text: row.get(2)?,
+            metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+            created_at: Utc.timestamp_millis_opt(row.get(6)?).single().unwrap_or_default(),
+        },
+        row.get(4)?,
+        row.get(5)?,
+    ))
+}
+
+pub fn list(db: &HistoryDb, collection: &str, limit: u32) -> Result<Vec<VectorRecord>, String> {
+    let conn = db.0.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, collection, text, metadata, NULL, NULL, created_at FROM vectors
+             WHERE collection = ?1 ORDER BY created_at DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![collection, limit], |row| row_to_record(row).map(|(r, _, _)| r))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+pub async fn search(
+    app: &AppHandle,
+    collection: &str,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SearchHit>, String> {
+    let model = embedding_model(app);
+    let query_embedding = match llm::embed(app, &model, &[query.to_string()]).await {
+        Ok(mut embeddings) => embeddings.pop(),
+        Err(e) => {
+            log::warn!("Falling back to keyword search in '{}': {}", collection, e);
+            None
+        }
+    };
+
+    let rows = {
+        let db = app.state::<HistoryDb>();
+        let conn = db.0.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, collection, text, metadata, embedding, model, created_at FROM vectors
+                 WHERE collection = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![collection], row_to_record)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())?
+    };
+
+    let mut hits: Vec<SearchHit> = rows
+        .into_iter()
+        .map(|(record, embedding, row_model)| {
+            let score = match (&query_embedding, embedding) {
+                // Vectors from a different model live in a different space.
+                (Some(query), Some(blob)) if row_model.as_deref() == Some(model.as_str()) => {
+                    cosine(query, &from_blob(&blob))
+                }
+                _ => keyword_score(query, &record.text),
+            };
+            SearchHit { record, score }
+        })
+        .collect();
+
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+pub fn delete(db: &HistoryDb, collection: &str, id: i64) -> Result<bool, String> {
+    let conn = db.0.lock().unwrap();
+    let deleted = conn
+        .execute("DELETE FROM vectors WHERE collection = ?1 AND id = ?2", params![collection, id])
+        .map_err(|e| e.to_string())?;
+    Ok(deleted > 0)
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<VectorState>().0.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_vector_settings(state: State<'_, VectorState>) -> VectorSettings {
+    state.0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_vector_settings(
+    app: AppHandle,
+    mut settings: VectorSettings,
+    state: State<'_, VectorState>,
+) -> Result<(), String> {
+    settings.batch_size = settings.batch_size.clamp(1, 512);
+    settings.concurrency = settings.concurrency.clamp(1, 16);
+    storage::save_json(&app, SETTINGS_FILE, &settings)?;
+    *state.0.lock().unwrap() = settings;
+    Ok(())
+}
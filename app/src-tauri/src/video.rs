@@ -0,0 +1,214 @@
+// In src-tauri/src/video.rs
+//
+// Summaries of video files such as screen recordings. ffmpeg samples frames
+// at `fps_sample` frames per second; a vision model describes each frame
+// with its timestamp and the previous notes as rolling context, so it
+// reports what changed rather than re-describing the screen. The notes are
+// then condensed into a chaptered summary that's stored in history as a
+// "video" entry.
+//
+// Needs `ffmpeg` (and `ffprobe` for durations) on the PATH.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::process::Command;
+
+use crate::history::{self, HistoryDb};
+use crate::llm::{self, ChatMessage};
+use crate::storage;
+
+const DEFAULT_MODEL: &str = "gemma3:4b";
+const MAX_FRAMES: f64 = 120.0;
+const FRAME_WIDTH: u32 = 1024;
+const CONTEXT_NOTES: usize = 3;
+
+const FRAME_PROMPT: &str = "You are reviewing a video frame by frame. Describe what is happening in \
+this frame in one to three sentences. Focus on what changed compared to the previous notes: new \
+windows, errors, dialogs, text being written, scene changes. If nothing meaningful changed, answer \
+\"No change.\"";
+
+const SUMMARY_PROMPT: &str = "Below are timestamped notes taken while watching a video. Write a \
+chaptered summary. Start each chapter on its own line as \"MM:SS - Chapter title\" followed by two \
+or three sentences. Merge notes that belong to the same activity and skip \"No change.\" notes. End \
+with a short overall summary.";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FrameNote {
+    pub seconds: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoSummary {
+    pub history_id: i64,
+    pub path: String,
+    pub duration_seconds: Option<f64>,
+    pub frames: usize,
+    pub notes: Vec<FrameNote>,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct VideoProgress {
+    path: String,
+    stage: &'static str,
+    frame: usize,
+    total: usize,
+}
+
+fn emit_progress(app: &AppHandle, path: &str, stage: &'static str, frame: usize, total: usize) {
+    let progress = VideoProgress { path: path.to_string(), stage, frame, total };
+    if let Err(e) = app.emit("video-progress", progress) {
+        log::error!("Failed to emit video-progress event: {}", e);
+    }
+}
+
+pub fn timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    if total >= 3600 {
+        format!("{}:{:02}:{:02}", total / 3600, total / 60 % 60, total % 60)
+    } else {
+        format!("{:02}:{:02}", total / 60, total % 60)
+    }
+}
+
+pub async fn duration(path: &Path) -> Option<f64> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .await
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+// Writes JPEG frames into `dir`, returned in order.
+async fn extract_frames(path: &Path, fps: f64, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-i"])
+        .arg(path)
+        .arg("-vf")
+        .arg(format!("fps={},scale='min({},iw)':-2", fps, FRAME_WIDTH))
+        .args(["-q:v", "4"])
+        .arg(dir.join("frame_%05d.jpg"))
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffmpeg (is it installed?): {}", e))?;
+    if !output.status.success() {
+        return Err(format!("ffmpeg failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let mut frames: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "jpg"))
+        .collect();
+    frames.sort();
+    Ok(frames)
+}
+
+async fn describe_frames(
+    app: &AppHandle,
+    model: &str,
+    path: &str,
+    frames: &[PathBuf],
+    fps: f64,
+) -> Result<Vec<FrameNote>, String> {
+    let mut notes: Vec<FrameNote> = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        emit_progress(app, path, "describing", i + 1, frames.len());
+        let seconds = i as f64 / fps;
+        let image = STANDARD.encode(std::fs::read(frame).map_err(|e| e.to_string())?);
+
+        let context: Vec<String> = notes
+            .iter()
+            .rev()
+            .take(CONTEXT_NOTES)
+            .rev()
+            .map(|n| format!("[{}] {}", timestamp(n.seconds), n.text))
+            .collect();
+        let prompt = if context.is_empty() {
+            format!("Frame at {} (the start of the video).", timestamp(seconds))
+        } else {
+            format!("Previous notes:\n{}\n\nFrame at {}.", context.join("\n"), timestamp(seconds))
+        };
+
+        let messages = vec![
+            ChatMessage::new("system", FRAME_PROMPT),
+            ChatMessage::new("user", prompt).with_images(vec![image]),
+        ];
+        let text = llm::chat(app, model, messages).await?;
+        notes.push(FrameNote { seconds, text: text.trim().to_string() });
+    }
+    Ok(notes)
+}
+
+pub async fn summarize(app: &AppHandle, path: &str, fps_sample: f64, model: &str) -> Result<VideoSummary, String> {
+    let video = Path::new(path);
+    if !video.is_file() {
+        return Err(format!("'{}' is not a file", path));
+    }
+
+    // Keep long videos to a manageable number of model calls.
+    let duration = duration(video).await;
+    let mut fps = fps_sample.clamp(0.01, 5.0);
+    if let Some(duration) = duration.filter(|d| *d > 0.0) {
+        fps = fps.min(MAX_FRAMES / duration);
+    }
+
+    let work_dir = storage::data_path(app, "video_frames")?.join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&work_dir).map_err(|e| format!("Failed to create {:?}: {}", work_dir, e))?;
+    emit_progress(app, path, "extracting", 0, 0);
+
+    let result = async {
+        let frames = extract_frames(video, fps, &work_dir).await?;
+        if frames.is_empty() {
+            return Err("No frames could be extracted".to_string());
+        }
+        log::info!("Summarizing {} frames of {}", frames.len(), path);
+        let notes = describe_frames(app, model, path, &frames, fps).await?;
+
+        emit_progress(app, path, "summarizing", frames.len(), frames.len());
+        let transcript = notes
+            .iter()
+            .map(|n| format!("[{}] {}", timestamp(n.seconds), n.text))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let summary = llm::generate(app, model, SUMMARY_PROMPT, &transcript).await?;
+        Ok((frames.len(), notes, summary))
+    }
+    .await;
+    if let Err(e) = std::fs::remove_dir_all(&work_dir) {
+        log::warn!("Failed to remove {:?}: {}", work_dir, e);
+    }
+    let (frames, notes, summary) = result?;
+
+    let file_name = video.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let history_id = app
+        .state::<HistoryDb>()
+        .insert(history::KIND_VIDEO, None, &format!("Video: {}\n\n{}", file_name, summary))?;
+    emit_progress(app, path, "done", frames, frames);
+
+    Ok(VideoSummary {
+        history_id,
+        path: path.to_string(),
+        duration_seconds: duration,
+        frames,
+        notes,
+        summary,
+    })
+}
+
+#[tauri::command]
+pub async fn summarize_video(
+    app: AppHandle,
+    path: String,
+    fps_sample: Option<f64>,
+    model: Option<String>,
+) -> Result<VideoSummary, String> {
+    let model = model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    summarize(&app, &path, fps_sample.unwrap_or(0.2), &model).await
+}
@@ -0,0 +1,243 @@
+// In src-tauri/src/vram.rs
+//
+// VRAM manager. Ollama loads a requested model even when it doesn't fit next
+// to the ones already loaded, and then either evicts on its own terms or
+// thrashes between GPU and CPU. With the manager on, the proxy checks
+// `/api/ps` before sending a request for a model that isn't loaded yet and,
+// if the model's size (from `/api/tags`, plus `headroom_mb`) won't fit in
+// the free GPU memory, unloads idle models first: least recently used
+// first, never a pinned model and never one with a request in flight (see
+// active.rs). If unloading all of those still isn't enough, the request
+// goes ahead and Ollama decides.
+//
+// GPU memory is `capacity_mb` or what hardware.rs finds. Off by default.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::OnceCell;
+
+use crate::active::ActiveRequests;
+use crate::{hardware, llm, storage};
+
+const SETTINGS_FILE: &str = "vram_manager.json";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VramSettings {
+    pub enabled: bool,
+    // None uses the probed GPU memory.
+    pub capacity_mb: Option<u64>,
+    // Room left for the context and compute buffers of the new model.
+    pub headroom_mb: u64,
+    pub pinned: Vec<String>,
+}
+
+impl Default for VramSettings {
+    fn default() -> Self {
+        Self { enabled: false, capacity_mb: None, headroom_mb: 1024, pinned: Vec::new() }
+    }
+}
+
+#[derive(Default)]
+pub struct VramState {
+    settings: Mutex<VramSettings>,
+    // Serializes decisions so two requests don't unload for each other.
+    deciding: tokio::sync::Mutex<()>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedModel {
+    pub name: String,
+    pub vram_mb: u64,
+    pub expires_at: Option<String>,
+    pub pinned: bool,
+    pub busy: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VramStatus {
+    pub enabled: bool,
+    pub capacity_mb: Option<u64>,
+    pub used_mb: u64,
+    pub loaded: Vec<LoadedModel>,
+}
+
+// "llama3" and "llama3:latest" are the same model.
+fn normalize(model: &str) -> String {
+    if model.contains(':') {
+        model.to_string()
+    } else {
+        format!("{}:latest", model)
+    }
+}
+
+async fn probed_capacity_mb() -> Option<u64> {
+    static CAPACITY: OnceCell<Option<u64>> = OnceCell::const_new();
+    *CAPACITY
+        .get_or_init(|| async {
+            tokio::task::spawn_blocking(|| hardware::probe().model_memory_mb()).await.ok().flatten()
+        })
+        .await
+}
+
+async fn get_json(url: &str) -> Result<Value, String> {
+    let response = llm::client()
+        .get(url)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    response.json().await.map_err(|e| format!("Invalid response from {}: {}", url, e))
+}
+
+async fn loaded_models(app: &AppHandle, settings: &VramSettings) -> Result<Vec<LoadedModel>, String> {
+    let ps = get_json(&format!("{}/api/ps", llm::ollama_base_url(app))).await?;
+    let pinned: HashSet<String> = settings.pinned.iter().map(|m| normalize(m)).collect();
+    let busy: HashSet<String> =
+        app.state::<ActiveRequests>().list().into_iter().filter_map(|r| r.model).map(|m| normalize(&m)).collect();
+    let mut loaded: Vec<LoadedModel> = ps["models"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| {
+            let name = normalize(model["name"].as_str().or(model["model"].as_str())?);
+            Some(LoadedModel {
+                vram_mb: model["size_vram"].as_u64().unwrap_or(0) / 1024 / 1024,
+                expires_at: model["expires_at"].as_str().map(str::to_string),
+                pinned: pinned.contains(&name),
+                busy: busy.contains(&name),
+                name,
+            })
+        })
+        .collect();
+    // Ollama pushes `expires_at` back on every use, so the earliest is the least recently used.
+    loaded.sort_by(|a, b| a.expires_at.cmp(&b.expires_at));
+    Ok(loaded)
+}
+
+async fn model_size_mb(app: &AppHandle, model: &str) -> Result<Option<u64>, String> {
+    let tags = get_json(&format!("{}/api/tags", llm::ollama_base_url(app))).await?;
+    Ok(tags["models"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|m| m["name"].as_str().map(normalize).as_deref() == Some(model))
+        .and_then(|m| m["size"].as_u64())
+        .map(|bytes| bytes / 1024 / 1024))
+}
+
+async fn unload(app: &AppHandle, model: &str) -> Result<(), String> {
+    let url = format!("{}/api/generate", llm::ollama_base_url(app));
+    let response = llm::client()
+        .post(&url)
+        .timeout(REQUEST_TIMEOUT)
+        .json(&json!({ "model": model, "keep_alive": 0 }))
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Unloading {} failed: {}", model, response.status()));
+    }
+    Ok(())
+}
+
+async fn make_room(app: &AppHandle, model: &str, settings: &VramSettings) -> Result<(), String> {
+    let model = normalize(model);
+    let loaded = loaded_models(app, settings).await?;
+    if loaded.iter().any(|m| m.name == model) {
+        return Ok(());
+    }
+    let capacity = match settings.capacity_mb {
+        Some(capacity) => capacity,
+        None => probed_capacity_mb().await.ok_or("the GPU memory is unknown; set capacity_mb")?,
+    };
+    let Some(size) = model_size_mb(app, &model).await? else {
+        return Ok(());
+    };
+    let needed = size + settings.headroom_mb;
+    let mut used: u64 = loaded.iter().map(|m| m.vram_mb).sum();
+    for candidate in loaded.iter().filter(|m| !m.pinned && !m.busy) {
+        if used + needed <= capacity {
+            break;
+        }
+        log::info!("Unloading {} ({} MB) to make room for {} ({} MB)", candidate.name, candidate.vram_mb, model, size);
+        unload(app, &candidate.name).await?;
+        used = used.saturating_sub(candidate.vram_mb);
+    }
+    if used + needed > capacity {
+        log::warn!("{} ({} MB) may not fit next to the pinned and busy models ({} MB used)", model, size, used);
+    }
+    Ok(())
+}
+
+// Called before a request for `model` goes to Ollama. Failures are logged;
+// the request is sent either way.
+pub async fn prepare(app: &AppHandle, model: &str) {
+    let state = app.state::<VramState>();
+    let settings = state.settings.lock().unwrap().clone();
+    if !settings.enabled {
+        return;
+    }
+    let _deciding = state.deciding.lock().await;
+    if let Err(e) = make_room(app, model, &settings).await {
+        log::warn!("VRAM manager: {}", e);
+    }
+}
+
+fn save(app: &AppHandle, state: &VramState, settings: VramSettings) -> Result<(), String> {
+    storage::save_json(app, SETTINGS_FILE, &settings)?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+pub fn init(app: &AppHandle) {
+    *app.state::<VramState>().settings.lock().unwrap() = storage::load_json(app, SETTINGS_FILE);
+}
+
+#[tauri::command]
+pub fn get_vram_settings(state: State<'_, VramState>) -> VramSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_vram_settings(app: AppHandle, settings: VramSettings, state: State<'_, VramState>) -> Result<(), String> {
+    log::info!("VRAM manager {}", if settings.enabled { "enabled" } else { "disabled" });
+    save(&app, &state, settings)
+}
+
+#[tauri::command]
+pub fn pin_model(app: AppHandle, model: String, state: State<'_, VramState>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap().clone();
+    if !settings.pinned.iter().any(|m| normalize(m) == normalize(&model)) {
+        settings.pinned.push(model);
+    }
+    save(&app, &state, settings)
+}
+
+#[tauri::command]
+pub fn unpin_model(app: AppHandle, model: String, state: State<'_, VramState>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap().clone();
+    settings.pinned.retain(|m| normalize(m) != normalize(&model));
+    save(&app, &state, settings)
+}
+
+#[tauri::command]
+pub async fn get_vram_status(app: AppHandle) -> Result<VramStatus, String> {
+    let settings = app.state::<VramState>().settings.lock().unwrap().clone();
+    let loaded = loaded_models(&app, &settings).await?;
+    let capacity_mb = match settings.capacity_mb {
+        Some(capacity) => Some(capacity),
+        None => probed_capacity_mb().await,
+    };
+    Ok(VramStatus {
+        enabled: settings.enabled,
+        capacity_mb,
+        used_mb: loaded.iter().map(|m| m.vram_mb).sum(),
+        loaded,
+    })
+}
@@ -0,0 +1,157 @@
+// In src-tauri/src/wake.rs
+//
+// Notices when the computer wakes from sleep and when it moves to another
+// network. Sleep shows up as a wall-clock gap between two checks that is far
+// longer than the check interval; a network change as a different local
+// address on the default route. After either, and once the network had a
+// moment to come up, the backend's health is checked again and the change
+// is emitted: "system-resumed" (with how long the machine slept) or
+// "network-changed", each with the fresh health report, so the frontend can
+// reconnect its SSE streams and run each active agent once rather than once
+// per missed interval. The browser app, which doesn't get Tauri events,
+// follows the same events on `GET /system/events` (SSE). Backend schedulers
+// compare wall-clock times and already catch up with a single run. Other
+// modules can `subscribe`; offline.rs `publish`es the backend going away and
+// coming back on the same channel.
+
+use axum::{
+    extract::State as AxumState,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use chrono::{DateTime, Utc};
+use futures::stream::Stream;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::net::{IpAddr, UdpSocket};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast;
+
+use crate::health::{self, Health};
+use crate::AppState;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+// A gap this much longer than the interval means the machine was asleep.
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(30);
+// Time for Wi-Fi and DHCP to come back before the backend is checked.
+const SETTLE_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SystemChange {
+    Resumed { slept_seconds: i64 },
+    NetworkChanged { previous: Option<IpAddr>, address: Option<IpAddr> },
+    // The Ollama backend stopped or started answering (see offline.rs).
+    BackendOffline { keep_capturing: bool },
+    BackendOnline { queued_agents: Vec<String> },
+    // A sound event fired (see sound_events.rs).
+    SoundDetected { class: String, score: f32, name: Option<String>, agents: Vec<String> },
+}
+
+impl SystemChange {
+    fn event_name(&self) -> &'static str {
+        match self {
+            SystemChange::Resumed { .. } => "system-resumed",
+            SystemChange::NetworkChanged { .. } => "network-changed",
+            SystemChange::BackendOffline { .. } => "backend-offline",
+            SystemChange::BackendOnline { .. } => "backend-online",
+            SystemChange::SoundDetected { .. } => "sound-detected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeEvent {
+    #[serde(flatten)]
+    pub change: SystemChange,
+    pub at: DateTime<Utc>,
+    pub health: Health,
+}
+
+pub struct WakeState {
+    changes: broadcast::Sender<ChangeEvent>,
+    address: Mutex<Option<IpAddr>>,
+}
+
+impl Default for WakeState {
+    fn default() -> Self {
+        Self { changes: broadcast::channel(16).0, address: Mutex::new(None) }
+    }
+}
+
+// The local address the OS would use to reach the internet. Connecting a UDP
+// socket only picks the route; nothing is sent.
+fn primary_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+pub fn subscribe(app: &AppHandle) -> broadcast::Receiver<ChangeEvent> {
+    app.state::<WakeState>().changes.subscribe()
+}
+
+async fn announce(app: AppHandle, change: SystemChange) {
+    tokio::time::sleep(SETTLE_DELAY).await;
+    publish(&app, change).await;
+}
+
+// Emits `change` with a fresh health report, to Tauri and `/system/events`.
+pub async fn publish(app: &AppHandle, change: SystemChange) {
+    let health = health::health(app).await;
+    log::info!("Backend health after {:?}: {:?}", change, health.status);
+    let name = change.event_name();
+    let event = ChangeEvent { change, at: Utc::now(), health };
+    // Nobody listening is fine.
+    let _ = app.state::<WakeState>().changes.send(event.clone());
+    if let Err(e) = app.emit(name, event) {
+        log::error!("Failed to emit {} event: {}", name, e);
+    }
+}
+
+pub async fn events_handler(
+    AxumState(state): AxumState<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut changes = subscribe(&state.app_handle);
+    let stream = async_stream::stream! {
+        loop {
+            match changes.recv().await {
+                Ok(event) => {
+                    let data = serde_json::to_string(&event).unwrap_or_default();
+                    yield Ok(Event::default().event(event.change.event_name()).data(data));
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+pub fn start_monitor(app: AppHandle) {
+    *app.state::<WakeState>().address.lock().unwrap() = primary_address();
+
+    tauri::async_runtime::spawn(async move {
+        let mut last_check = Utc::now();
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let now = Utc::now();
+            let elapsed = (now - last_check).to_std().unwrap_or_default();
+            last_check = now;
+            if elapsed > CHECK_INTERVAL + SLEEP_THRESHOLD {
+                log::info!("System resumed after about {}s asleep", elapsed.as_secs());
+                let change = SystemChange::Resumed { slept_seconds: elapsed.as_secs() as i64 };
+                tauri::async_runtime::spawn(announce(app.clone(), change));
+            }
+
+            let address = tokio::task::spawn_blocking(primary_address).await.ok().flatten();
+            let previous = std::mem::replace(&mut *app.state::<WakeState>().address.lock().unwrap(), address);
+            if previous != address {
+                log::info!("Network changed: {:?} -> {:?}", previous, address);
+                let change = SystemChange::NetworkChanged { previous, address };
+                tauri::async_runtime::spawn(announce(app.clone(), change));
+            }
+        }
+    });
+}
@@ -8,6 +8,7 @@ import {
   ChevronDown, ChevronUp,
   Eye,
   ScanText,
+  MousePointerClick,
   Play,
   X,
   Zap,
@@ -239,6 +240,7 @@ const PromptContent: React.FC<PromptContentProps> = ({
         <label className="block text-xs text-gray-500 mb-2 font-medium">INSERT SENSOR:</label>
         <div className="flex flex-wrap gap-2">
             <SensorButton icon={ScanText} label="Screen Text" onClick={() => insertSystemPromptText('$SCREEN_OCR')} />
+            <SensorButton icon={MousePointerClick} label="UI Elements" onClick={() => insertSystemPromptText('$SCREEN_UI')} />
             <SensorButton icon={Monitor} label="Screen Image" onClick={() => insertSystemPromptText('$SCREEN_64')} colorClass="text-purple-600" />
             <SensorButton icon={Camera} label="Camera" onClick={() => insertSystemPromptText('$CAMERA')} colorClass="text-purple-600" />
             <SensorButton icon={Clipboard} label="Clipboard" onClick={() => insertSystemPromptText('$CLIPBOARD_TEXT')} />
@@ -39,6 +39,7 @@ const SettingsTab = () => {
   // --- OCR State Management (Existing) ---
   const [ocrLang, setOcrLang] = useState(SensorSettings.getOcrLanguage());
   const [ocrConfidence, setOcrConfidence] = useState(SensorSettings.getOcrConfidenceThreshold());
+  const [ocrLayout, setOcrLayout] = useState(SensorSettings.getOcrLayout());
 
   // --- OCR Handler Functions (Existing) ---
   const handleOcrLangChange = (e: React.ChangeEvent<HTMLSelectElement>) => {
@@ -52,6 +53,11 @@ const SettingsTab = () => {
     setOcrConfidence(newConfidence);
     SensorSettings.setOcrConfidenceThreshold(newConfidence);
   };
+
+  const handleOcrLayoutChange = (e: React.ChangeEvent<HTMLInputElement>) => {
+    setOcrLayout(e.target.checked);
+    SensorSettings.setOcrLayout(e.target.checked);
+  };
   
 
   // --- NEW STATE FOR TRANSCRIPTION DIAGNOSTICS ---
@@ -219,6 +225,12 @@ const SettingsTab = () => {
             <label htmlFor="ocr-confidence" className="block text-sm font-medium text-gray-700">Minimum Confidence ({ocrConfidence}%)</label>
             <input type="range" id="ocr-confidence" min="0" max="100" value={ocrConfidence} onChange={handleOcrConfidenceChange} className="w-full h-2 bg-gray-200 rounded-lg appearance-none cursor-pointer" />
           </div>
+          <div className="flex items-start">
+            <input type="checkbox" id="ocr-layout" checked={ocrLayout} onChange={handleOcrLayoutChange} className="mt-1 h-4 w-4 text-blue-600 border-gray-300 rounded" />
+            <label htmlFor="ocr-layout" className="ml-2 text-sm text-gray-700">
+              Preserve layout (columns, tables and positions) so models can read on-screen tables correctly
+            </label>
+          </div>
         </div>
       </SettingsCard>
 
@@ -0,0 +1,169 @@
+import { useState, useEffect, useRef, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { Crop, EyeOff, ArrowUpRight, Highlighter, Undo2, Check, X } from 'lucide-react';
+
+// Overlay window for marking up a screenshot before it goes to a model.
+// Marks are collected here in image pixel coordinates; the backend
+// (annotate.rs) applies them when the user confirms.
+
+type Tool = 'crop' | 'blur' | 'arrow' | 'highlight';
+
+interface Rect { x: number; y: number; width: number; height: number }
+interface Point { x: number; y: number }
+
+type Annotation =
+  | { type: 'crop'; rect: Rect }
+  | { type: 'blur'; rect: Rect }
+  | { type: 'highlight'; rect: Rect }
+  | { type: 'arrow'; from: Point; to: Point };
+
+const TOOLS: { id: Tool; label: string; icon: React.ElementType }[] = [
+  { id: 'crop', label: 'Crop', icon: Crop },
+  { id: 'blur', label: 'Blur', icon: EyeOff },
+  { id: 'arrow', label: 'Arrow', icon: ArrowUpRight },
+  { id: 'highlight', label: 'Highlight', icon: Highlighter },
+];
+
+const toRect = (a: Point, b: Point): Rect => ({
+  x: Math.round(Math.min(a.x, b.x)),
+  y: Math.round(Math.min(a.y, b.y)),
+  width: Math.round(Math.abs(a.x - b.x)),
+  height: Math.round(Math.abs(a.y - b.y)),
+});
+
+function AnnotationOverlay() {
+  const [source, setSource] = useState<string | null>(null);
+  const [tool, setTool] = useState<Tool>('highlight');
+  const [annotations, setAnnotations] = useState<Annotation[]>([]);
+  const [dragStart, setDragStart] = useState<Point | null>(null);
+  const [dragEnd, setDragEnd] = useState<Point | null>(null);
+  const imageRef = useRef<HTMLImageElement>(null);
+
+  const loadSource = useCallback(() => {
+    invoke<string | null>('get_annotation_source')
+      .then(image => {
+        setSource(image && !image.startsWith('data:') ? `data:image/png;base64,${image}` : image);
+        setAnnotations([]);
+      })
+      .catch(console.error);
+  }, []);
+
+  useEffect(() => {
+    loadSource();
+    const unlisten = listen('annotation-source-changed', loadSource);
+    return () => { unlisten.then(fn => fn()); };
+  }, [loadSource]);
+
+  // Converts a mouse position to pixels of the original image.
+  const toImagePoint = (e: React.MouseEvent): Point | null => {
+    const img = imageRef.current;
+    if (!img) return null;
+    const bounds = img.getBoundingClientRect();
+    return {
+      x: ((e.clientX - bounds.left) / bounds.width) * img.naturalWidth,
+      y: ((e.clientY - bounds.top) / bounds.height) * img.naturalHeight,
+    };
+  };
+
+  const handleMouseUp = () => {
+    if (dragStart && dragEnd) {
+      const annotation: Annotation = tool === 'arrow'
+        ? { type: 'arrow', from: dragStart, to: dragEnd }
+        : { type: tool, rect: toRect(dragStart, dragEnd) };
+      const isEmpty = annotation.type !== 'arrow' && (annotation.rect.width < 4 || annotation.rect.height < 4);
+      if (!isEmpty) {
+        // Only one crop makes sense; a new one replaces the old.
+        setAnnotations(prev => [...prev.filter(a => annotation.type !== 'crop' || a.type !== 'crop'), annotation]);
+      }
+    }
+    setDragStart(null);
+    setDragEnd(null);
+  };
+
+  const finish = (confirmed: boolean) => {
+    invoke('finish_annotation', { annotations: confirmed ? annotations : null }).catch(console.error);
+  };
+
+  // Draws marks as percentages so they follow the scaled preview.
+  const renderMark = (a: Annotation, key: string | number) => {
+    const img = imageRef.current;
+    if (!img || !img.naturalWidth) return null;
+    const px = (v: number) => `${(v / img.naturalWidth) * 100}%`;
+    const py = (v: number) => `${(v / img.naturalHeight) * 100}%`;
+    if (a.type === 'arrow') {
+      return (
+        <line key={key} x1={px(a.from.x)} y1={py(a.from.y)} x2={px(a.to.x)} y2={py(a.to.y)}
+          stroke="#ff3b30" strokeWidth={4} markerEnd="url(#arrowhead)" />
+      );
+    }
+    const styles = {
+      crop: { fill: 'none', stroke: '#ffffff', strokeDasharray: '6 4' },
+      blur: { fill: 'rgba(100,116,139,0.6)', stroke: '#64748b' },
+      highlight: { fill: 'rgba(255,214,10,0.3)', stroke: '#ffd60a' },
+    }[a.type];
+    return (
+      <rect key={key} x={px(a.rect.x)} y={py(a.rect.y)} width={px(a.rect.width)} height={py(a.rect.height)}
+        strokeWidth={3} {...styles} />
+    );
+  };
+
+  const preview: Annotation | null = dragStart && dragEnd
+    ? (tool === 'arrow' ? { type: 'arrow', from: dragStart, to: dragEnd } : { type: tool, rect: toRect(dragStart, dragEnd) })
+    : null;
+
+  return (
+    <div className="fixed inset-0 bg-slate-900 flex flex-col font-sans select-none">
+      <div className="flex items-center justify-between px-4 py-2 bg-slate-800">
+        <div className="flex space-x-1">
+          {TOOLS.map(({ id, label, icon: Icon }) => (
+            <button key={id} onClick={() => setTool(id)} title={label}
+              className={`flex items-center px-3 py-1.5 rounded-md text-sm ${tool === id ? 'bg-blue-600 text-white' : 'text-slate-300 hover:bg-slate-700'}`}>
+              <Icon className="h-4 w-4 mr-1.5" />{label}
+            </button>
+          ))}
+          <button onClick={() => setAnnotations(prev => prev.slice(0, -1))} disabled={annotations.length === 0}
+            className="flex items-center px-3 py-1.5 rounded-md text-sm text-slate-300 hover:bg-slate-700 disabled:opacity-40">
+            <Undo2 className="h-4 w-4 mr-1.5" />Undo
+          </button>
+        </div>
+        <div className="flex space-x-2">
+          <button onClick={() => finish(false)}
+            className="flex items-center px-3 py-1.5 rounded-md text-sm text-slate-300 hover:bg-slate-700">
+            <X className="h-4 w-4 mr-1.5" />Cancel
+          </button>
+          <button onClick={() => finish(true)} disabled={!source}
+            className="flex items-center px-4 py-1.5 rounded-md text-sm bg-green-600 text-white hover:bg-green-700 disabled:opacity-40">
+            <Check className="h-4 w-4 mr-1.5" />Send to model
+          </button>
+        </div>
+      </div>
+
+      <div className="flex-1 flex items-center justify-center p-4 overflow-hidden">
+        {source ? (
+          <div className="relative max-h-full max-w-full cursor-crosshair"
+            onMouseDown={e => { const p = toImagePoint(e); setDragStart(p); setDragEnd(p); }}
+            onMouseMove={e => { if (dragStart) setDragEnd(toImagePoint(e)); }}
+            onMouseUp={handleMouseUp}
+            onMouseLeave={handleMouseUp}>
+            <img ref={imageRef} src={source} alt="Screenshot to annotate" draggable={false}
+              className="max-h-[calc(100vh-6rem)] max-w-full block" />
+            <svg className="absolute inset-0 w-full h-full pointer-events-none">
+              <defs>
+                <marker id="arrowhead" markerWidth="8" markerHeight="8" refX="6" refY="4" orient="auto">
+                  <path d="M0,0 L8,4 L0,8 z" fill="#ff3b30" />
+                </marker>
+              </defs>
+              {annotations.map((a, i) => renderMark(a, i))}
+              {preview && renderMark(preview, 'preview')}
+            </svg>
+          </div>
+        ) : (
+          <p className="text-slate-400">No screenshot to annotate.</p>
+        )}
+      </div>
+    </div>
+  );
+}
+
+export default AnnotationOverlay;
@@ -0,0 +1,127 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { ShieldAlert } from 'lucide-react';
+
+// Asks the user about actions agents want to take that need their approval
+// first. The backend waits on the matching respond_* command and treats no
+// answer as a denial, so each request stays up until it's answered.
+
+interface ApprovalSource {
+  event: string;
+  respond: string;
+  title: string;
+  details: (payload: any) => string[];
+  // Get out of the way first, so the action lands where the agent meant it to.
+  hideBeforeAnswer?: boolean;
+}
+
+const SOURCES: ApprovalSource[] = [
+  {
+    // shell.rs
+    event: 'shell-approval-requested',
+    respond: 'respond_shell_approval',
+    title: 'Run a shell command',
+    details: p => [p.command, p.cwd ? `in ${p.cwd}` : '', p.justification],
+  },
+  {
+    // input_control.rs
+    event: 'input-approval-requested',
+    respond: 'respond_input_approval',
+    title: 'Use the mouse or keyboard',
+    details: p => [describeInput(p.action), p.justification],
+    hideBeforeAnswer: true,
+  },
+  {
+    // moderation.rs
+    event: 'action-approval-requested',
+    respond: 'respond_action_approval',
+    title: 'Call a tool',
+    details: p => [
+      `${p.tool} ${JSON.stringify(p.arguments)}`,
+      p.concern ? `Reviewer's concern: ${p.concern}` : '',
+    ],
+  },
+];
+
+function describeInput(action: { tool: string; x?: number | null; y?: number | null; text?: string; button?: string; double?: boolean }): string {
+  const at = action.x != null && action.y != null ? ` at (${action.x}, ${action.y})` : ' where the pointer is';
+  switch (action.tool) {
+    case 'move_mouse': return `Move the mouse to (${action.x}, ${action.y})`;
+    case 'click': return `${action.double ? 'Double-click' : 'Click'} ${action.button ?? 'left'}${at}`;
+    case 'type_text': return `Type: ${action.text ?? ''}`;
+    default: return action.tool;
+  }
+}
+
+interface PendingApproval {
+  id: string;
+  agentId: string | null;
+  source: ApprovalSource;
+  details: string[];
+}
+
+function ApprovalPrompts() {
+  const [pending, setPending] = useState<PendingApproval[]>([]);
+
+  useEffect(() => {
+    const unlisteners = SOURCES.map(source =>
+      listen<any>(source.event, e => {
+        const request: PendingApproval = {
+          id: e.payload.id,
+          agentId: e.payload.agent_id ?? null,
+          source,
+          details: source.details(e.payload).filter(Boolean),
+        };
+        setPending(current => [...current, request]);
+      })
+    );
+    return () => { unlisteners.forEach(unlisten => unlisten.then(fn => fn())); };
+  }, []);
+
+  const answer = async (request: PendingApproval, approved: boolean) => {
+    setPending(current => current.filter(p => p.id !== request.id));
+    if (approved && request.source.hideBeforeAnswer) {
+      await getCurrentWindow().hide().catch(console.error);
+    }
+    // Fails once the request has timed out, which already counted as a denial.
+    invoke(request.source.respond, { id: request.id, approved }).catch(console.error);
+  };
+
+  const request = pending[0];
+  if (!request) return null;
+
+  return (
+    <div className="fixed inset-0 z-50 bg-black/40 flex items-center justify-center p-4">
+      <div className="bg-white rounded-xl shadow-2xl p-6 max-w-lg w-full text-left">
+        <div className="flex items-center mb-3">
+          <ShieldAlert className="h-6 w-6 mr-2 text-amber-500" />
+          <h2 className="text-lg font-semibold text-slate-800">
+            {request.agentId ?? 'An agent'} wants to: {request.source.title.toLowerCase()}
+          </h2>
+        </div>
+        <div className="space-y-2 mb-5">
+          {request.details.map((line, i) => (
+            <p key={i} className={i === 0 ? 'font-mono text-sm bg-slate-100 rounded p-2 break-all' : 'text-sm text-slate-600'}>
+              {line}
+            </p>
+          ))}
+        </div>
+        <div className="flex justify-end space-x-2">
+          <button onClick={() => answer(request, false)} className="px-4 py-2 rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300">
+            Deny
+          </button>
+          <button onClick={() => answer(request, true)} className="px-4 py-2 rounded-md bg-blue-600 text-white hover:bg-blue-700">
+            Allow
+          </button>
+        </div>
+        {pending.length > 1 && (
+          <p className="text-xs text-slate-400 mt-3">{pending.length - 1} more waiting</p>
+        )}
+      </div>
+    </div>
+  );
+}
+
+export default ApprovalPrompts;
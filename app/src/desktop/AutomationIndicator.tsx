@@ -0,0 +1,55 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { MousePointerClick } from 'lucide-react';
+
+// Always-on-top strip shown while agents may use the mouse and keyboard
+// without asking (input_control.rs). Shows the last action and revokes the
+// grant in one click.
+
+interface InputSettings {
+  enabled: boolean;
+  autonomous_agents: string[];
+}
+
+interface InputActionEvent {
+  agent_id: string | null;
+  action: { tool: string; x?: number | null; y?: number | null; text?: string };
+  autonomous: boolean;
+}
+
+function describe(event: InputActionEvent): string {
+  const { action } = event;
+  if (action.tool === 'type_text') return `typed ${action.text?.length ?? 0} characters`;
+  const at = action.x != null && action.y != null ? ` at (${action.x},${action.y})` : '';
+  return `${action.tool === 'click' ? 'clicked' : 'moved the mouse'}${at}`;
+}
+
+function AutomationIndicator() {
+  const [agents, setAgents] = useState<string[]>([]);
+  const [last, setLast] = useState<InputActionEvent | null>(null);
+
+  useEffect(() => {
+    invoke<InputSettings>('get_input_settings').then(s => setAgents(s.autonomous_agents)).catch(console.error);
+    const unlisten = listen<InputActionEvent>('input-action', e => setLast(e.payload));
+    return () => { unlisten.then(fn => fn()); };
+  }, []);
+
+  const revoke = () => { invoke('revoke_input_autonomy').catch(console.error); };
+
+  return (
+    <div data-tauri-drag-region className="fixed inset-0 bg-red-600 text-white flex items-center font-sans select-none px-4 text-sm">
+      <MousePointerClick className="h-4 w-4 mr-2 flex-shrink-0 animate-pulse" />
+      <span data-tauri-drag-region className="flex-1 truncate">
+        {last
+          ? `${last.agent_id ?? 'An agent'} ${describe(last)}`
+          : `${agents.join(', ') || 'Agents'} may control mouse and keyboard`}
+      </span>
+      <button onClick={revoke} className="ml-3 px-2 py-1 rounded bg-white text-red-700 font-medium hover:bg-red-50">
+        Stop
+      </button>
+    </div>
+  );
+}
+
+export default AutomationIndicator;
@@ -0,0 +1,132 @@
+import { useState, useEffect, useRef } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { X, Languages, Loader2 } from 'lucide-react';
+import { TranscriptionService, TranscriptionChunk } from '../utils/transcriptionService';
+import { StreamManager } from '../utils/streamManager';
+
+// Overlay window for live translated captions. It captures the audio here,
+// transcribes it in short chunks and has the backend (captions.rs) translate
+// each one; the translations come back as "caption" events.
+
+const CAPTIONS_AGENT_ID = 'live-captions';
+
+interface CaptionSettings {
+  audio: 'screenAudio' | 'microphone';
+  source_language: string | null;
+  target_language: string;
+  chunk_ms: number;
+  max_lines: number;
+  font_size: number;
+  show_original: boolean;
+}
+
+interface Caption {
+  created_at: string;
+  language: string | null;
+  original: string;
+  text: string;
+  latency_ms: number | null;
+}
+
+function CaptionOverlay() {
+  const [settings, setSettings] = useState<CaptionSettings | null>(null);
+  const [captions, setCaptions] = useState<Caption[]>([]);
+  const [error, setError] = useState<string | null>(null);
+  const serviceRef = useRef<TranscriptionService | null>(null);
+
+  useEffect(() => {
+    invoke<CaptionSettings>('get_caption_settings').then(setSettings).catch(e => setError(String(e)));
+    const unlistenSettings = listen<CaptionSettings>('caption-settings-changed', e => setSettings(e.payload));
+    const unlistenCaption = listen<Caption>('caption', e => {
+      setCaptions(prev => [...prev.slice(-19), e.payload]);
+    });
+    return () => {
+      unlistenSettings.then(fn => fn());
+      unlistenCaption.then(fn => fn());
+    };
+  }, []);
+
+  // (Re)starts the pipeline whenever the audio source or chunking changes.
+  useEffect(() => {
+    if (!settings) return;
+    let cancelled = false;
+
+    const start = async () => {
+      try {
+        await StreamManager.requestStreamsForAgent(CAPTIONS_AGENT_ID, [settings.audio]);
+        const state = StreamManager.getCurrentState();
+        const stream = settings.audio === 'microphone' ? state.microphoneStream : state.screenAudioStream;
+        if (!stream) throw new Error(`No ${settings.audio === 'microphone' ? 'microphone' : 'screen audio'} available`);
+        if (cancelled) return;
+
+        const model = await invoke<string | null>('caption_whisper_model');
+        const service = new TranscriptionService();
+        serviceRef.current = service;
+        const onChunk = (chunk: TranscriptionChunk) => {
+          invoke('translate_caption', { text: chunk.text, capturedAt: chunk.endedAt ?? null })
+            .catch(e => setError(String(e)));
+        };
+        await service.start(stream, onChunk, {
+          chunkMs: settings.chunk_ms,
+          model: model ?? undefined,
+          language: settings.source_language ?? undefined,
+          // Captions are translated to their own target language.
+          process: false,
+        });
+        setError(null);
+      } catch (e) {
+        setError(String(e));
+      }
+    };
+    start();
+
+    return () => {
+      cancelled = true;
+      serviceRef.current?.stop();
+      serviceRef.current = null;
+      StreamManager.releaseStreamsForAgent(CAPTIONS_AGENT_ID);
+    };
+  }, [settings?.audio, settings?.chunk_ms, settings?.source_language]);
+
+  const close = () => { invoke('close_caption_overlay').catch(console.error); };
+
+  const visible = settings ? captions.slice(-settings.max_lines) : [];
+  const latest = captions[captions.length - 1];
+
+  return (
+    <div data-tauri-drag-region className="fixed inset-0 bg-black/85 text-white flex flex-col font-sans select-none px-5 py-3">
+      <div data-tauri-drag-region className="flex items-center justify-between text-xs text-slate-400 mb-1">
+        <span data-tauri-drag-region className="flex items-center">
+          <Languages className="h-3.5 w-3.5 mr-1.5" />
+          {settings ? `${settings.source_language ?? 'auto'} → ${settings.target_language}` : 'Live captions'}
+          {latest?.latency_ms != null && <span className="ml-3">{(latest.latency_ms / 1000).toFixed(1)}s behind</span>}
+        </span>
+        <button onClick={close} title="Close captions" className="p-1 rounded hover:bg-slate-700">
+          <X className="h-3.5 w-3.5" />
+        </button>
+      </div>
+
+      <div data-tauri-drag-region className="flex-1 flex flex-col justify-end overflow-hidden">
+        {error ? (
+          <p className="text-red-400 text-sm">{error}</p>
+        ) : visible.length === 0 ? (
+          <p className="flex items-center text-slate-400 text-sm">
+            <Loader2 className="h-4 w-4 mr-2 animate-spin" />Listening…
+          </p>
+        ) : (
+          visible.map((caption, i) => (
+            <div key={`${caption.created_at}-${i}`} className="leading-snug">
+              <p style={{ fontSize: settings?.font_size }}>{caption.text}</p>
+              {settings?.show_original && caption.original !== caption.text && (
+                <p className="text-slate-400" style={{ fontSize: (settings.font_size * 0.6) }}>{caption.original}</p>
+              )}
+            </div>
+          ))
+        )}
+      </div>
+    </div>
+  );
+}
+
+export default CaptionOverlay;
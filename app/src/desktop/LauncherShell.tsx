@@ -5,6 +5,7 @@ import {
   ExternalLink, Loader, CheckCircle2, XCircle, Power,
   Download, Settings, RotateCw, Check, AlertTriangle
 } from 'lucide-react';
+import ApprovalPrompts from './ApprovalPrompts';
 
 // --- Helper Component for the Status Display (MODIFIED) ---
 // Now accepts strings to display full URLs or ports.
@@ -182,6 +183,7 @@ function LauncherShell() {
 
   return (
     <div className="fixed inset-0 bg-gray-100 flex items-center justify-center p-4 font-sans">
+      <ApprovalPrompts />
       <div className="bg-white rounded-2xl shadow-2xl p-8 sm:p-10 max-w-2xl w-full text-center transition-all">
 
         {/* Header (unchanged) */}
@@ -7,6 +7,9 @@ import '@/index.css';
 // Import the two possible application entry points
 import App from './web/App'; // Your existing App.tsx, now the "WebApp"
 import LauncherShell from './desktop/LauncherShell'; // The new "DesktopApp"
+import AnnotationOverlay from './desktop/AnnotationOverlay';
+import CaptionOverlay from './desktop/CaptionOverlay';
+import AutomationIndicator from './desktop/AutomationIndicator';
 
 // Helper function to safely check for the Tauri environment
 function isTauri() {
@@ -16,8 +19,17 @@ function isTauri() {
   );
 }
 
-// Decide which component to render at the root level
-const RootComponent = isTauri() ? LauncherShell : App;
+// Decide which component to render at the root level.
+// The screenshot annotation overlay and the live caption overlay are their
+// own Tauri windows, opened at #annotate and #captions.
+const OVERLAYS: Record<string, React.ComponentType> = {
+  '#annotate': AnnotationOverlay,
+  '#captions': CaptionOverlay,
+  '#automation': AutomationIndicator,
+};
+const RootComponent = !isTauri()
+  ? App
+  : OVERLAYS[window.location.hash] ?? LauncherShell;
 
 // Render the chosen component
 ReactDOM.createRoot(document.getElementById('root')!).render(
@@ -2,6 +2,7 @@
 // Database utilities for agent management with unified CompleteAgent type
 //
 import { dispatchMemoryUpdate } from '@components/MemoryManager';
+import { Logger } from './logging';
 import yaml from 'js-yaml';
 
 export interface CompleteAgent {
@@ -62,6 +63,68 @@ export async function openDB(): Promise<IDBDatabase> {
   });
 }
 
+// --- Desktop app mirror ---
+// The desktop app keeps a copy of every agent (agents.rs) for the features
+// that run without this page. Without the app there's nobody to tell.
+export function appServerUrl(): string {
+  return (localStorage.getItem('observer_local_server_address') || 'http://localhost:3838').replace(/\/$/, '');
+}
+
+async function tellApp(path: string, method: 'PUT' | 'DELETE', body?: unknown): Promise<void> {
+  try {
+    const response = await fetch(`${appServerUrl()}${path}`, {
+      method,
+      headers: body === undefined ? undefined : { 'Content-Type': 'application/json' },
+      body: body === undefined ? undefined : JSON.stringify(body),
+    });
+    if (!response.ok && response.status !== 404) {
+      Logger.warn('AGENTS', `Desktop app rejected ${method} ${path}: ${response.status}`);
+    }
+  } catch (error) {
+    Logger.debug('AGENTS', `Desktop app unavailable for agent sync: ${error}`);
+  }
+}
+
+async function agentExport(agent: CompleteAgent): Promise<AgentExport> {
+  return {
+    id: agent.id,
+    name: agent.name,
+    description: agent.description,
+    model_name: agent.model_name,
+    system_prompt: agent.system_prompt,
+    loop_interval_seconds: agent.loop_interval_seconds,
+    code: (await getAgentCode(agent.id)) ?? '',
+    memory: await getAgentMemory(agent.id),
+  };
+}
+
+// Stores the agents the desktop app added itself (imports, onboarding) and
+// returns how many there were; none without the app.
+export async function takeImportedAgents(): Promise<number> {
+  let imported: AgentExport[];
+  try {
+    const response = await fetch(`${appServerUrl()}/agents/imported`, { method: 'POST' });
+    if (!response.ok) return 0;
+    imported = await response.json();
+  } catch (error) {
+    Logger.debug('AGENTS', `Desktop app unavailable for imported agents: ${error}`);
+    return 0;
+  }
+  for (const { code, memory, ...agent } of imported) {
+    Logger.info('AGENTS', `Adding agent ${agent.id} from the desktop app`);
+    await saveAgent(agent, code);
+    await updateAgentMemory(agent.id, memory);
+  }
+  return imported.length;
+}
+
+// Sends every stored agent to the desktop app, replacing what it had.
+export async function syncAgentsToApp(): Promise<void> {
+  await takeImportedAgents();
+  const agents = await Promise.all((await listAgents()).map(agentExport));
+  await tellApp('/agents', 'PUT', agents);
+}
+
 // Create or update an agent
 export async function saveAgent(
   agent: CompleteAgent,
@@ -128,6 +191,8 @@ export async function saveAgent(
   if (memory === null) {
     await updateAgentMemory(agent.id, '');
   }
+
+  await tellApp(`/agents/${agent.id}`, 'PUT', await agentExport(agent));
   
   return agent;
 }
@@ -311,6 +376,8 @@ export async function deleteAgent(agentId: string): Promise<void> {
     tx.oncomplete = () => resolve();
     tx.onerror = () => reject(tx.error);
   });
+
+  await tellApp(`/agents/${agentId}`, 'DELETE');
 }
 
 export interface AgentExport {
@@ -0,0 +1,23 @@
+// src/utils/deep_links.ts
+
+import { appServerUrl } from './agent_database';
+import { Logger } from './logging';
+
+// An observer:// link, or an observerctl run/stop, that the desktop app
+// queued for this page (deep_link.rs).
+export type DeepLinkAction =
+  | { action: 'show' }
+  | { action: 'run_agent'; id: string }
+  | { action: 'stop_agent'; id: string }
+  | { action: 'chat'; prompt: string };
+
+// Takes what's queued; nothing when the desktop app isn't running.
+export async function takeDeepLinks(): Promise<DeepLinkAction[]> {
+  try {
+    const response = await fetch(`${appServerUrl()}/observer/deep-links`, { method: 'POST' });
+    return response.ok ? await response.json() : [];
+  } catch (error) {
+    Logger.debug('APP', `Desktop app unavailable for deep links: ${error}`);
+    return [];
+  }
+}
@@ -0,0 +1,30 @@
+// src/utils/file_drops.ts
+
+import { appServerUrl } from './agent_database';
+import { Logger } from './logging';
+
+// A file dropped onto the desktop app's window, processed for a model
+// (file_drop.rs). `target` is an agent ID, or null for the active chat.
+export type DroppedContent =
+  | { kind: 'image'; mime: string; base64: string }
+  | { kind: 'pdf'; text: string }
+  | { kind: 'text'; text: string }
+  | { kind: 'audio'; mime: string; base64: string };
+
+export interface DroppedFile {
+  path: string;
+  file_name: string;
+  target: string | null;
+  content: DroppedContent;
+}
+
+// Takes what's queued; nothing when the desktop app isn't running.
+export async function takeDroppedFiles(): Promise<DroppedFile[]> {
+  try {
+    const response = await fetch(`${appServerUrl()}/files/dropped`, { method: 'POST' });
+    return response.ok ? await response.json() : [];
+  } catch (error) {
+    Logger.debug('APP', `Desktop app unavailable for dropped files: ${error}`);
+    return [];
+  }
+}
@@ -31,8 +31,10 @@ export async function executeJavaScript(
         }
         return await utils.appendMemory(targetId, content, separator);
       },
-      notify: utils.notify,
+      notify: async (title: string, message: string) => await utils.notify(title, message, agentId),
       time: utils.time,
+      listTools: utils.listTools,
+      callTool: async (name: string, args?: any) => await utils.callTool(agentId, name, args),
       console: console,
       startAgent: async (targetAgentId?: string) => {
         const idToStart = targetAgentId === undefined ? agentId : targetAgentId;
@@ -1,7 +1,7 @@
 // src/utils/handlers/utils.ts
 
 import { Logger } from '../logging';
-import { getAgentMemory as fetchAgentMemory, updateAgentMemory as saveAgentMemory } from '../agent_database';
+import { getAgentMemory as fetchAgentMemory, updateAgentMemory as saveAgentMemory, appServerUrl } from '../agent_database';
 import { recordingManager } from '../recordingManager'; 
 
 /**
@@ -62,9 +62,49 @@ export async function appendMemory(agentId: string, content: string, separator:
 }
 
 /**
- * Send a notification
+ * List the desktop app's native tools, in Ollama's `tools` format
  */
-export function notify(title: string, message: string): void {
+export async function listTools(): Promise<any[]> {
+  const response = await fetch(`${appServerUrl()}/tools`);
+  if (!response.ok) throw new Error(`Couldn't list tools: ${response.status}`);
+  return await response.json();
+}
+
+/**
+ * Run one of the desktop app's native tools for an agent
+ */
+export async function callTool(agentId: string, name: string, args: any = {}): Promise<any> {
+  const response = await fetch(`${appServerUrl()}/tools/call`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ name, arguments: args, agent_id: agentId }),
+  });
+  if (!response.ok) throw new Error(`Tool ${name} failed: ${await response.text()}`);
+  const { result } = await response.json();
+  Logger.info(agentId, `Tool ${name} called`, { args, result });
+  return result;
+}
+
+/**
+ * Send a notification through the desktop app, which holds it back in focus
+ * or offline mode; the browser shows it when the app isn't there
+ */
+export async function notify(title: string, message: string, agentId?: string): Promise<void> {
+  try {
+    const response = await fetch(`${appServerUrl()}/notify`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ title, body: message, agent_id: agentId ?? null }),
+    });
+    if (response.ok) return;
+    Logger.warn('NOTIFICATION', `Desktop app couldn't notify: ${await response.text()}`);
+  } catch {
+    // Not connected to the desktop app.
+  }
+  browserNotify(title, message);
+}
+
+function browserNotify(title: string, message: string): void {
   try {
     if (!("Notification" in window)) {
       Logger.error('NOTIFICATION', 'Browser does not support notifications');
@@ -1,12 +1,13 @@
 // src/utils/main_loop.ts
 
-import { getAgent, getAgentCode } from './agent_database';
+import { getAgent, getAgentCode, appServerUrl } from './agent_database';
 import { sendPrompt, UnauthorizedError } from './sendApi';
 import { Logger } from './logging';
 import { preProcess } from './pre-processor';
 import { postProcess } from './post-processor';
 import { StreamManager, PseudoStreamType } from './streamManager'; // Import the new manager
 import { recordingManager } from './recordingManager'
+import { SoundEventService, SoundEventSettings } from './soundEventService';
 
 export type TokenProvider = () => Promise<string | undefined>;
 
@@ -33,6 +34,238 @@ export function getOllamaServerAddress(): { host: string; port: string } {
   return { host: serverHost, port: serverPort };
 }
 
+// --- Privacy kill switch ---
+// While agents run, the streams this page holds are reported to the desktop
+// app (privacy.rs), and every agent is stopped once its kill switch is on.
+const PRIVACY_POLL_MS = 3000;
+let privacyPollId: number | null = null;
+
+async function reportSensors(): Promise<void> {
+  const { screenVideoStream, screenAudioStream, cameraStream, microphoneStream } = StreamManager.getCurrentState();
+  try {
+    const response = await fetch(`${serverHost}:${serverPort}/privacy/sensors`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({
+        screen_video: !!screenVideoStream,
+        screen_audio: !!screenAudioStream,
+        camera: !!cameraStream,
+        microphone: !!microphoneStream,
+      }),
+    });
+    if (!response.ok) return;
+    const status: { kill_switch: boolean } = await response.json();
+    if (status.kill_switch && getRunningAgentIds().length > 0) {
+      Logger.warn('PRIVACY', 'Kill switch engaged, stopping all agents');
+      await Promise.all(getRunningAgentIds().map(id => stopAgentLoop(id)));
+    }
+  } catch {
+    // Not connected to the desktop app.
+  }
+}
+
+function startPrivacyMonitor(): void {
+  if (privacyPollId !== null) return;
+  privacyPollId = window.setInterval(reportSensors, PRIVACY_POLL_MS);
+}
+
+function stopPrivacyMonitor(): void {
+  if (privacyPollId === null) return;
+  window.clearInterval(privacyPollId);
+  privacyPollId = null;
+  // One last report so the dashboard sees the streams go away.
+  reportSensors();
+}
+
+// --- Feature flags ---
+// Subsystems the desktop app was started without (features.rs), e.g. in
+// safe mode. Without the app everything is allowed.
+interface FeatureFlags { capture: boolean; audio: boolean; agents: boolean }
+
+async function fetchFeatures(): Promise<FeatureFlags | null> {
+  try {
+    const response = await fetch(`${serverHost}:${serverPort}/features`);
+    if (!response.ok) return null;
+    const status: { safe_mode: boolean; active: FeatureFlags } = await response.json();
+    return status.active;
+  } catch {
+    return null;
+  }
+}
+
+const STREAM_FEATURES: Record<PseudoStreamType, keyof FeatureFlags> = {
+  screenVideo: 'capture', camera: 'capture',
+  screenAudio: 'audio', microphone: 'audio', allAudio: 'audio',
+};
+
+// --- Sleep and network changes ---
+// The desktop app reports waking from sleep and network changes (wake.rs).
+// Each running agent then runs once right away instead of waiting out its
+// interval; the missed iterations aren't made up.
+let systemEvents: EventSource | null = null;
+
+function catchUpRunningAgents(event: MessageEvent): void {
+  Logger.info('SYSTEM', `${event.type}, running each active agent once`);
+  for (const id of getRunningAgentIds()) {
+    executeAgentIteration(id).catch(e => Logger.error(id, `Error in catch-up iteration: ${e}`, e));
+  }
+}
+
+// --- Offline mode ---
+// While the backend is unreachable (offline.rs) iterations skip the model
+// call, optionally still capturing, and the agent is queued. When the
+// backend is back each queued agent that is still running runs once.
+let backendOffline = false;
+let keepCapturingOffline = false;
+const offlineQueue = new Set<string>();
+
+function handleBackendOffline(event: MessageEvent): void {
+  const data = JSON.parse(event.data);
+  backendOffline = true;
+  keepCapturingOffline = Boolean(data.keep_capturing);
+  Logger.warn('SYSTEM', `Backend offline, holding model calls${keepCapturingOffline ? ' (still capturing)' : ''}`);
+}
+
+function handleBackendOnline(event: MessageEvent): void {
+  const data = JSON.parse(event.data);
+  backendOffline = false;
+  const queued = new Set<string>([...offlineQueue, ...(data.queued_agents ?? [])]);
+  offlineQueue.clear();
+  const running = getRunningAgentIds().filter(id => queued.has(id));
+  Logger.info('SYSTEM', `Backend online, running ${running.length} queued agents`);
+  for (const id of running) {
+    executeAgentIteration(id).catch(e => Logger.error(id, `Error in queued iteration: ${e}`, e));
+  }
+}
+
+// --- Sound events ---
+// While agents run, the microphone is classified for sound events when the
+// desktop app has them enabled (sound_events.rs). A firing class runs each
+// of its agents that is running once, like a wake-up does.
+const SOUND_EVENTS_ID = 'system-sound-events';
+let soundEvents: SoundEventService | null = null;
+
+function handleSoundDetected(event: MessageEvent): void {
+  const data = JSON.parse(event.data);
+  const agents = new Set<string>(data.agents ?? []);
+  const running = getRunningAgentIds().filter(id => agents.has(id));
+  Logger.info('SYSTEM', `Heard ${data.class}${data.name ? ` (${data.name})` : ''}, running ${running.length} agents`);
+  for (const id of running) {
+    executeAgentIteration(id).catch(e => Logger.error(id, `Error in sound-triggered iteration: ${e}`, e));
+  }
+}
+
+async function startSoundEvents(): Promise<void> {
+  if (soundEvents !== null) return;
+  const serverUrl = `${serverHost}:${serverPort}`;
+  try {
+    const response = await fetch(`${serverUrl}/sound/settings`);
+    if (!response.ok) return;
+    const settings = await response.json() as SoundEventSettings;
+    if (!settings.enabled || soundEvents !== null) return;
+    await StreamManager.requestStreamsForAgent(SOUND_EVENTS_ID, ['microphone']);
+    const stream = StreamManager.getCurrentState().microphoneStream;
+    if (!stream) return;
+    soundEvents = new SoundEventService(serverUrl, settings);
+    soundEvents.start(stream);
+  } catch {
+    // Not connected to the desktop app, or no microphone.
+  }
+}
+
+function stopSoundEvents(): void {
+  if (soundEvents === null) return;
+  soundEvents.stop();
+  soundEvents = null;
+  StreamManager.releaseStreamsForAgent(SOUND_EVENTS_ID);
+}
+
+function startSystemEvents(): void {
+  if (systemEvents !== null || typeof EventSource === 'undefined') return;
+  systemEvents = new EventSource(`${serverHost}:${serverPort}/system/events`);
+  systemEvents.addEventListener('system-resumed', catchUpRunningAgents);
+  systemEvents.addEventListener('network-changed', catchUpRunningAgents);
+  systemEvents.addEventListener('backend-offline', handleBackendOffline);
+  systemEvents.addEventListener('backend-online', handleBackendOnline);
+  systemEvents.addEventListener('sound-detected', handleSoundDetected);
+  startSoundEvents();
+}
+
+function stopSystemEvents(): void {
+  systemEvents?.close();
+  systemEvents = null;
+  stopSoundEvents();
+}
+
+// --- Power ---
+// On battery or under thermal pressure the desktop app (power.rs) asks for
+// longer intervals and may pause heavy agents, those that capture images or
+// audio. Without the app nothing is throttled.
+const POWER_POLL_MS = 30000;
+const HEAVY_PLACEHOLDERS = ['$SCREEN_64', '$CAMERA', '$SCREEN_AUDIO', '$MICROPHONE', '$ALL_AUDIO'];
+let powerThrottle = { interval_multiplier: 1, pause_heavy_agents: false };
+let powerPollId: number | null = null;
+
+async function refreshPowerState(): Promise<void> {
+  try {
+    const response = await fetch(`${serverHost}:${serverPort}/power/state`);
+    if (!response.ok) return;
+    const state: { profile: string; throttle: typeof powerThrottle } = await response.json();
+    if (state.throttle.interval_multiplier !== powerThrottle.interval_multiplier
+        || state.throttle.pause_heavy_agents !== powerThrottle.pause_heavy_agents) {
+      Logger.info('SYSTEM', `Power profile ${state.profile}: intervals x${state.throttle.interval_multiplier}`
+        + `${state.throttle.pause_heavy_agents ? ', heavy agents paused' : ''}`);
+    }
+    powerThrottle = state.throttle;
+  } catch {
+    // Not connected to the desktop app.
+  }
+}
+
+function startPowerMonitor(): void {
+  if (powerPollId !== null) return;
+  refreshPowerState();
+  powerPollId = window.setInterval(refreshPowerState, POWER_POLL_MS);
+}
+
+function stopPowerMonitor(): void {
+  if (powerPollId === null) return;
+  window.clearInterval(powerPollId);
+  powerPollId = null;
+}
+
+// Runs the agent again after its interval, stretched by the power profile.
+function scheduleNextIteration(agentId: string, intervalMs: number): void {
+  const loop = activeLoops[agentId];
+  if (!loop?.isRunning) return;
+  loop.intervalId = window.setTimeout(async () => {
+    // Stopping (and maybe restarting) the agent replaces its loop entry.
+    if (activeLoops[agentId] !== loop) return;
+    try {
+      await executeAgentIteration(agentId);
+    } catch (e) {
+      Logger.error(agentId, `Error in interval: ${e}`, e);
+    }
+    if (activeLoops[agentId] === loop) scheduleNextIteration(agentId, intervalMs);
+  }, intervalMs * powerThrottle.interval_multiplier);
+}
+
+// --- History ---
+// What each iteration saw, heard and answered goes into the desktop app's
+// history (history.rs), which summaries and datasets are built from.
+async function recordHistory(kind: 'observation' | 'transcription' | 'agent_output', agentId: string, content: string) {
+  try {
+    const response = await fetch(`${appServerUrl()}/history`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ kind, agent_id: agentId, content }),
+    });
+    if (!response.ok) Logger.debug(agentId, `History not recorded: ${await response.text()}`);
+  } catch {
+    // Not connected to the desktop app.
+  }
+}
+
 export async function startAgentLoop(agentId: string, getToken?: TokenProvider): Promise<void> {
   if (activeLoops[agentId]?.isRunning) {
     Logger.warn(agentId, `Agent is already running`);
@@ -47,7 +280,7 @@ export async function startAgentLoop(agentId: string, getToken?: TokenProvider):
     if (!agent) throw new Error(`Agent ${agentId} not found`);
 
     const streamRequirementsMap = {
-      '$SCREEN_64': 'screenVideo', '$SCREEN_OCR': 'screenVideo', '$CAMERA': 'camera', '$SCREEN_AUDIO': 'screenAudio',
+      '$SCREEN_64': 'screenVideo', '$SCREEN_OCR': 'screenVideo', '$SCREEN_UI': 'screenVideo', '$CAMERA': 'camera', '$SCREEN_AUDIO': 'screenAudio',
       '$MICROPHONE': 'microphone', '$ALL_AUDIO': 'allAudio'
     };
     
@@ -55,6 +288,11 @@ export async function startAgentLoop(agentId: string, getToken?: TokenProvider):
       .filter(([placeholder, _]) => agent.system_prompt.includes(placeholder))
       .map(([_, streamType]) => streamType as PseudoStreamType);
     
+    const features = await fetchFeatures();
+    if (features && !features.agents) throw new Error('Agents are disabled in the desktop app (safe mode?)');
+    const blocked = requiredStreams.filter(stream => features && !features[STREAM_FEATURES[stream]]);
+    if (blocked.length > 0) throw new Error(`Disabled in the desktop app: ${blocked.join(', ')}`);
+
     if (requiredStreams.length > 0) {
       // A single, transactional call to the StreamManager.
       await StreamManager.requestStreamsForAgent(agentId, requiredStreams);
@@ -62,6 +300,10 @@ export async function startAgentLoop(agentId: string, getToken?: TokenProvider):
     
     if (isFirstAgent) {
       recordingManager.initialize();
+      startPrivacyMonitor();
+      startSystemEvents();
+      await refreshPowerState();
+      startPowerMonitor();
     }
 
     activeLoops[agentId] = { 
@@ -81,16 +323,7 @@ export async function startAgentLoop(agentId: string, getToken?: TokenProvider):
     await executeAgentIteration(agentId);
 
     // then schedule
-    const intervalMs = agent.loop_interval_seconds * 1000;
-    activeLoops[agentId].intervalId = window.setInterval(async () => {
-      if (activeLoops[agentId]?.isRunning) {
-        try {
-          await executeAgentIteration(agentId);
-        } catch (e) {
-          Logger.error(agentId, `Error in interval: ${e}`, e);
-        }
-      }
-    }, intervalMs);
+    scheduleNextIteration(agentId, agent.loop_interval_seconds * 1000);
   } catch (error) {
 
     let displayError = error;
@@ -117,7 +350,7 @@ export async function startAgentLoop(agentId: string, getToken?: TokenProvider):
 export async function stopAgentLoop(agentId: string): Promise<void> {
   const loop = activeLoops[agentId];
   if (loop?.isRunning) {
-    if (loop.intervalId !== null) window.clearInterval(loop.intervalId);
+    if (loop.intervalId !== null) window.clearTimeout(loop.intervalId);
 
     // --- STREAM MANAGEMENT ---
     // Tell the StreamManager this agent no longer needs these streams.
@@ -134,6 +367,9 @@ export async function stopAgentLoop(agentId: string): Promise<void> {
     if (getRunningAgentIds().length === 0) {
       // This was the last running agent, so shut down the recorder.
       recordingManager.forceStop();
+      stopPrivacyMonitor();
+      stopSystemEvents();
+      stopPowerMonitor();
     }
 
     window.dispatchEvent(
@@ -157,14 +393,33 @@ export async function executeAgentIteration(agentId: string): Promise<void> {
   }
 
   try {
+    if (backendOffline && !keepCapturingOffline) {
+      offlineQueue.add(agentId);
+      Logger.debug(agentId, `Backend offline, iteration queued`);
+      return;
+    }
+
     Logger.debug(agentId, `Starting agent iteration`);
 
     const agent = await getAgent(agentId);
     const agentCode = await getAgentCode(agentId) || '';
     if (!agent) throw new Error(`Agent ${agentId} not found`);
+    if (powerThrottle.pause_heavy_agents && HEAVY_PLACEHOLDERS.some(p => agent.system_prompt.includes(p))) {
+      Logger.debug(agentId, `Paused by the power profile, iteration skipped`);
+      return;
+    }
 
     const systemPrompt = await preProcess(agentId, agent.system_prompt);
+    if (backendOffline) {
+      offlineQueue.add(agentId);
+      Logger.debug(agentId, `Backend offline, captured without calling the model`);
+      return;
+    }
     Logger.info(agentId, `Prompt`, { logType: 'model-prompt', content: systemPrompt });
+    for (const transcript of systemPrompt.transcripts ?? []) {
+      await recordHistory('transcription', agentId, transcript);
+    }
+    await recordHistory('observation', agentId, systemPrompt.modifiedPrompt);
 
     let token: string | undefined;
     if (loopData.getToken) {
@@ -180,6 +435,7 @@ export async function executeAgentIteration(agentId: string): Promise<void> {
     const response = await sendPrompt(serverHost, serverPort, agent.model_name, systemPrompt, token);
     Logger.info(agentId, `Response`, { logType: 'model-response', content: response });
     Logger.debug(agentId, `Response Received: ${response}`);
+    await recordHistory('agent_output', agentId, response);
 
     try {
       // Pass the getToken FUNCTION down to the post-processor
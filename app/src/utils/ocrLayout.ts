@@ -0,0 +1,147 @@
+// src/utils/ocrLayout.ts
+
+// Turns Tesseract's blocks into layout-aware text for prompts. A raw OCR
+// dump reads columns side by side and flattens tables into runs of words,
+// so models mix up which value belongs to which row. Here:
+//   - each line is split into segments where words are far apart,
+//   - segments are grouped into rows by vertical overlap,
+//   - runs of rows whose segments line up in the same columns become
+//     markdown tables,
+//   - the remaining segments are grouped into regions (paragraphs and
+//     columns) and read top to bottom, left column first.
+// Every region and table is prefixed with its top-left corner as a
+// percentage of the screen, e.g. `@(55,10)`, so the model knows roughly
+// where it is without the cost of full bounding boxes.
+
+interface Bbox { x0: number; y0: number; x1: number; y1: number }
+interface OcrWord { text: string; bbox: Bbox }
+interface OcrLine { words: OcrWord[]; bbox: Bbox }
+interface OcrBlock { paragraphs: { lines: OcrLine[] }[] }
+
+interface Segment { text: string; bbox: Bbox }
+interface Region { kind: 'text' | 'table'; bbox: Bbox; lines: string[] }
+
+// Words further apart than this many line heights are separate segments.
+const SEGMENT_GAP = 1.5;
+// Columns closer than this many line heights count as aligned.
+const ALIGN_TOLERANCE = 1.5;
+const MIN_TABLE_ROWS = 3;
+
+const heightOf = (b: Bbox) => Math.max(1, b.y1 - b.y0);
+const centerY = (b: Bbox) => (b.y0 + b.y1) / 2;
+const union = (a: Bbox, b: Bbox): Bbox => ({
+  x0: Math.min(a.x0, b.x0), y0: Math.min(a.y0, b.y0), x1: Math.max(a.x1, b.x1), y1: Math.max(a.y1, b.y1),
+});
+
+function segmentsOf(line: OcrLine): Segment[] {
+  const words = line.words.filter(w => w.text.trim());
+  const segments: Segment[] = [];
+  const gap = heightOf(line.bbox) * SEGMENT_GAP;
+  for (const word of words) {
+    const last = segments[segments.length - 1];
+    if (last && word.bbox.x0 - last.bbox.x1 <= gap) {
+      last.text += ` ${word.text.trim()}`;
+      last.bbox = union(last.bbox, word.bbox);
+    } else {
+      segments.push({ text: word.text.trim(), bbox: { ...word.bbox } });
+    }
+  }
+  return segments;
+}
+
+// Rows of segments, top to bottom, each sorted left to right.
+function rowsOf(segments: Segment[]): Segment[][] {
+  const rows: { bbox: Bbox; segments: Segment[] }[] = [];
+  for (const segment of [...segments].sort((a, b) => centerY(a.bbox) - centerY(b.bbox))) {
+    const row = rows.find(r => Math.abs(centerY(r.bbox) - centerY(segment.bbox)) < heightOf(r.bbox) / 2);
+    if (row) {
+      row.segments.push(segment);
+      row.bbox = union(row.bbox, segment.bbox);
+    } else {
+      rows.push({ bbox: { ...segment.bbox }, segments: [segment] });
+    }
+  }
+  return rows.map(r => r.segments.sort((a, b) => a.bbox.x0 - b.bbox.x0));
+}
+
+// Same number of cells, each left-, right- or centre-aligned with the one above.
+function aligned(a: Segment[], b: Segment[], tolerance: number): boolean {
+  const near = (x: number, y: number) => Math.abs(x - y) <= tolerance;
+  return a.length >= 2 && a.length === b.length && a.every((s, i) => {
+    const t = b[i].bbox;
+    return near(s.bbox.x0, t.x0) || near(s.bbox.x1, t.x1) || near(s.bbox.x0 + s.bbox.x1, t.x0 + t.x1);
+  });
+}
+
+const cell = (text: string) => text.replace(/\|/g, '\\|');
+
+function tableRegion(rows: Segment[][]): Region {
+  const lines = rows.map(row => `| ${row.map(s => cell(s.text)).join(' | ')} |`);
+  lines.splice(1, 0, `|${' --- |'.repeat(rows[0].length)}`);
+  const bbox = rows.flat().map(s => s.bbox).reduce(union);
+  return { kind: 'table', bbox, lines };
+}
+
+// Groups loose segments into regions: a segment joins the region right above
+// it when they overlap horizontally and the gap is under two line heights.
+function textRegions(segments: Segment[]): Region[] {
+  const regions: Region[] = [];
+  for (const segment of [...segments].sort((a, b) => a.bbox.y0 - b.bbox.y0 || a.bbox.x0 - b.bbox.x0)) {
+    const region = regions.find(r =>
+      segment.bbox.x0 < r.bbox.x1 && segment.bbox.x1 > r.bbox.x0
+      && segment.bbox.y0 - r.bbox.y1 < heightOf(segment.bbox) * 2
+      && segment.bbox.y0 >= r.bbox.y0);
+    if (region) {
+      region.lines.push(segment.text);
+      region.bbox = union(region.bbox, segment.bbox);
+    } else {
+      regions.push({ kind: 'text', bbox: { ...segment.bbox }, lines: [segment.text] });
+    }
+  }
+  return regions;
+}
+
+// Top to bottom; of regions side by side, the left one (with what's under it) first.
+function readingOrder(regions: Region[]): Region[] {
+  const remaining = [...regions];
+  const ordered: Region[] = [];
+  while (remaining.length > 0) {
+    const top = remaining.reduce((a, b) => (b.bbox.y0 < a.bbox.y0 ? b : a));
+    const next = remaining
+      .filter(r => r.bbox.y0 < top.bbox.y1)
+      .reduce((a, b) => (b.bbox.x0 < a.bbox.x0 ? b : a));
+    ordered.push(next);
+    remaining.splice(remaining.indexOf(next), 1);
+  }
+  return ordered;
+}
+
+export function formatLayout(blocks: OcrBlock[], width: number, height: number): string {
+  const lines = blocks.flatMap(block => block.paragraphs.flatMap(p => p.lines));
+  if (lines.length === 0) return '';
+  const lineHeight = lines.map(l => heightOf(l.bbox)).sort((a, b) => a - b)[Math.floor(lines.length / 2)];
+  const rows = rowsOf(lines.flatMap(segmentsOf));
+
+  const regions: Region[] = [];
+  const loose: Segment[] = [];
+  for (let i = 0; i < rows.length;) {
+    let end = i + 1;
+    while (end < rows.length && aligned(rows[i], rows[end], lineHeight * ALIGN_TOLERANCE)) end++;
+    if (end - i >= MIN_TABLE_ROWS) {
+      regions.push(tableRegion(rows.slice(i, end)));
+    } else {
+      loose.push(...rows.slice(i, end).flat());
+    }
+    i = end;
+  }
+  regions.push(...textRegions(loose));
+
+  const pct = (value: number, total: number) => Math.round((value / Math.max(1, total)) * 100);
+  const parts = readingOrder(regions).map(region => {
+    const at = `@(${pct(region.bbox.x0, width)},${pct(region.bbox.y0, height)})`;
+    return region.kind === 'table'
+      ? `${at} table:\n${region.lines.join('\n')}`
+      : `${at} ${region.lines.join('\n')}`;
+  });
+  return `[screen text by position, @(x%,y%) from the top left]\n${parts.join('\n\n')}`;
+}
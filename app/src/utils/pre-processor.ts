@@ -2,7 +2,7 @@
 
 import { Logger } from './logging'; 
 import { getAgentMemory } from './agent_database'; 
-import { captureFrameAndOCR, captureScreenImage } from './screenCapture'; 
+import { captureFrameAndOCR, captureScreenImage, captureNativeScreen, recognizeWords } from './screenCapture'; 
 import { captureCameraImage } from './cameraCapture'; 
 import { StreamManager } from './streamManager';
 
@@ -11,12 +11,36 @@ import { StreamManager } from './streamManager';
 export interface PreProcessorResult {
   modifiedPrompt: string;  // The text prompt with placeholders removed
   images?: string[];       // Base64 encoded images for the API
+  sensitive?: string[];    // Kinds of private data in the prompt (clipboard, audio), for the data-locality check
+  transcripts?: string[];  // Audio transcripts put into the prompt, for the desktop app's history
+}
+
+// Untrusted text (OCR, clipboard) is screened for prompt injection by the desktop app
+// before it enters the prompt. Without the app the text is used as is.
+async function screenUntrusted(agentId: string, source: string, text: string): Promise<string> {
+  const server = localStorage.getItem('observer_local_server_address') || 'http://localhost:3838';
+  try {
+    const response = await fetch(`${server.replace(/\/$/, '')}/injection/scan`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ agent_id: agentId, source, text }),
+    });
+    if (!response.ok) return text;
+    const result: { flagged: boolean; reasons: string[]; text: string } = await response.json();
+    if (result.flagged) {
+      Logger.warn(agentId, `Possible prompt injection in ${source}: ${result.reasons.join(', ')}`);
+    }
+    return result.text;
+  } catch {
+    return text;
+  }
 }
 
 // Map of processor functions
 type ProcessorFunction = (agentId: string, prompt: string, match: RegExpExecArray) => Promise<{
   replacementText?: string;
   images?: string[];
+  sensitive?: string[];
 }>;
 
 // Simple map of placeholder patterns to handler functions
@@ -30,11 +54,11 @@ const processors: Record<string, { regex: RegExp, handler: ProcessorFunction }>
         const { screenVideoStream } = StreamManager.getCurrentState();
         if (!screenVideoStream) throw new Error('Screen stream not available for OCR.');
 
-        const ocrResult = await captureFrameAndOCR(screenVideoStream); 
+        const ocrResult = await captureFrameAndOCR(screenVideoStream, agentId); 
 
         if (ocrResult.success && ocrResult.text) {
           Logger.debug(agentId, `OCR successful, text injected into prompt`);
-          return { replacementText: ocrResult.text };
+          return { replacementText: await screenUntrusted(agentId, 'screen_ocr', ocrResult.text) };
         }
         Logger.error(agentId, `OCR failed: ${ocrResult.error || 'Unknown error'}`);
         return { replacementText: '[Error performing OCR]' };
@@ -45,6 +69,46 @@ const processors: Record<string, { regex: RegExp, handler: ProcessorFunction }>
     }
   },
   
+  // Buttons, inputs and dialogs on screen with their labels and positions (ui_elements.rs).
+  'SCREEN_UI': {
+    regex: /\$SCREEN_UI/g,
+    handler: async (agentId: string) => {
+      const server = (localStorage.getItem('observer_local_server_address') || 'http://localhost:3838').replace(/\/$/, '');
+      try {
+        let image = await captureNativeScreen();
+        if (!image) {
+          const { screenVideoStream } = StreamManager.getCurrentState();
+          if (!screenVideoStream) throw new Error('Screen stream not available for UI detection.');
+          image = await captureScreenImage(screenVideoStream);
+        }
+        if (!image) return { replacementText: '[Error capturing screen]' };
+
+        const words = await recognizeWords(image, agentId);
+        const response = await fetch(`${server}/ui/elements`, {
+          method: 'POST',
+          headers: { 'Content-Type': 'application/json' },
+          body: JSON.stringify({ image, words }),
+        });
+        if (!response.ok) throw new Error(await response.text());
+        const result: {
+          width: number;
+          height: number;
+          elements: { kind: string; label: string | null; x: number; y: number; width: number; height: number }[];
+        } = await response.json();
+
+        const lines = result.elements.map(e =>
+          `${e.kind}${e.label ? ` "${e.label}"` : ''} at (${e.x},${e.y}) ${e.width}x${e.height}`);
+        Logger.debug(agentId, `Detected ${lines.length} UI elements`);
+        const text = `[UI elements on the ${result.width}x${result.height} screen, top-left corner and size]\n`
+          + (lines.length > 0 ? lines.join('\n') : 'none found');
+        return { replacementText: await screenUntrusted(agentId, 'screen_ui', text) };
+      } catch (error) {
+        Logger.error(agentId, `Error detecting UI elements: ${error instanceof Error ? error.message : String(error)}`);
+        return { replacementText: '[Error detecting UI elements]' };
+      }
+    }
+  },
+
   // Memory processor
   'MEMORY': {
     regex: /\$MEMORY@([a-zA-Z0-9_]+)/g,
@@ -65,11 +129,15 @@ const processors: Record<string, { regex: RegExp, handler: ProcessorFunction }>
     handler: async (agentId: string) => {
       try {
 
-        const { screenVideoStream } = StreamManager.getCurrentState();
-        if (!screenVideoStream) throw new Error('Screen stream not available for image capture.');
-        
-        // Pass the existing stream to the utility
-        const base64Image = await captureScreenImage(screenVideoStream);
+        // Native capture first; the browser stream is the fallback.
+        let base64Image = await captureNativeScreen();
+        if (!base64Image) {
+          const { screenVideoStream } = StreamManager.getCurrentState();
+          if (!screenVideoStream) throw new Error('Screen stream not available for image capture.');
+
+          // Pass the existing stream to the utility
+          base64Image = await captureScreenImage(screenVideoStream);
+        }
 
         if (base64Image) {
           // Basic check for data URI prefix, then the base64 part
@@ -99,7 +167,7 @@ const processors: Record<string, { regex: RegExp, handler: ProcessorFunction }>
         if (typeof navigator !== 'undefined' && navigator.clipboard && typeof navigator.clipboard.readText === 'function') {
           const clipboardText = await navigator.clipboard.readText();
           Logger.debug(agentId, `Retrieved clipboard text: "${clipboardText}"`);
-          return { replacementText: clipboardText };
+          return { replacementText: await screenUntrusted(agentId, 'clipboard', clipboardText), sensitive: ['clipboard'] };
         }
         Logger.warn(agentId, `navigator.clipboard.readText is not available for CLIPBOARD_TEXT.`);
         return { replacementText: '[Error: Clipboard API not available or permission denied]' };
@@ -120,7 +188,7 @@ const processors: Record<string, { regex: RegExp, handler: ProcessorFunction }>
       try {
         const transcript = StreamManager.getTranscript('microphone');
         Logger.debug(agentId, `Retrieved microphone transcript via StreamManager: "${transcript}"`);
-        return { replacementText: transcript };
+        return { replacementText: transcript, sensitive: ['audio'] };
       } catch (error: any) {
         Logger.error(agentId, `Error retrieving microphone transcript: ${error.message}`);
         return { replacementText: `[Error processing microphone input: ${error.message}]` };
@@ -162,7 +230,7 @@ const processors: Record<string, { regex: RegExp, handler: ProcessorFunction }>
       try {
         const transcript = StreamManager.getTranscript('screenAudio');
         Logger.debug(agentId, `Retrieved system audio transcript via StreamManager: "${transcript}"`);
-        return { replacementText: transcript };
+        return { replacementText: transcript, sensitive: ['audio'] };
       } catch (error: any) {
         Logger.error(agentId, `Error retrieving system audio transcript: ${error.message}`);
         return { replacementText: `[Error processing system audio: ${error.message}]` };
@@ -177,7 +245,7 @@ const processors: Record<string, { regex: RegExp, handler: ProcessorFunction }>
       try {
         const transcript = StreamManager.getTranscript('allAudio');
         Logger.debug(agentId, `Retrieved combined audio transcript via StreamManager: "${transcript}"`);
-        return { replacementText: transcript };
+        return { replacementText: transcript, sensitive: ['audio'] };
       } catch (error: any) {
         Logger.error(agentId, `Error retrieving combined audio transcript: ${error.message}`);
         return { replacementText: `[Error processing combined audio: ${error.message}]` };
@@ -241,6 +309,12 @@ export async function preProcess(agentId: string, systemPrompt: string): Promise
         if (processorResult.images && processorResult.images.length > 0) {
           result.images = [...(result.images || []), ...processorResult.images];
         }
+        if (processorResult.sensitive) {
+          result.sensitive = [...new Set([...(result.sensitive || []), ...processorResult.sensitive])];
+        }
+        if (processorResult.sensitive?.includes('audio') && processorResult.replacementText?.trim()) {
+          result.transcripts = [...(result.transcripts || []), processorResult.replacementText];
+        }
         
         // Safety break for empty placeholder matches to prevent infinite loops
         // if somehow regex.lastIndex isn't advanced by the above.
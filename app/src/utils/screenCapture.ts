@@ -1,4 +1,5 @@
 import { createWorker, Worker } from 'tesseract.js';
+import { formatLayout } from './ocrLayout';
 import { SensorSettings } from './settings';
 
 interface OCRResult {
@@ -9,7 +10,7 @@ interface OCRResult {
 }
 
 
-export async function captureFrameAndOCR(stream: MediaStream): Promise<OCRResult> {
+export async function captureFrameAndOCR(stream: MediaStream, agentId?: string): Promise<OCRResult> {
   try {
     const video = document.createElement('video');
     video.srcObject = stream;
@@ -29,7 +30,7 @@ export async function captureFrameAndOCR(stream: MediaStream): Promise<OCRResult
           const imageData = canvas.toDataURL('image/png').split(',')[1];
           
           // This will be passed to performOCR, which adds the prefix back on for Tesseract.
-          const ocrResult = await performOCR(imageData);
+          const ocrResult = await performOCR(imageData, agentId, canvas.width, canvas.height);
           resolve(ocrResult);
         } else {
           resolve({ error: 'Failed to get canvas context' });
@@ -43,6 +44,33 @@ export async function captureFrameAndOCR(stream: MediaStream): Promise<OCRResult
   }
 }
 
+// The desktop app's capture token, which it only gives to pages it serves.
+let captureToken: string | null = null;
+
+// Screenshot from the desktop app's native capture backends (capture.rs),
+// which needs no getDisplayMedia stream. Resolves to null when the local
+// server isn't reachable or can't capture, so callers can fall back.
+export async function captureNativeScreen(display = 0): Promise<string | null> {
+  const server = (localStorage.getItem('observer_local_server_address') || 'http://localhost:3838').replace(/\/$/, '');
+  try {
+    if (!captureToken) {
+      const tokenResponse = await fetch(`${server}/capture/token`);
+      if (!tokenResponse.ok) return null;
+      captureToken = (await tokenResponse.json()).token;
+    }
+    const response = await fetch(`${server}/capture/screen?display=${display}`, {
+      headers: { 'X-Observer-Capture-Token': captureToken ?? '' },
+    });
+    // A new token after the app restarted.
+    if (response.status === 401) captureToken = null;
+    if (!response.ok) return null;
+    const result: { base64: string } = await response.json();
+    return result.base64;
+  } catch {
+    return null;
+  }
+}
+
 // REFACTORED: This function also accepts a stream and returns the raw Base64 data.
 export async function captureScreenImage(stream: MediaStream): Promise<string | null> {
   try {
@@ -77,42 +105,96 @@ export async function captureScreenImage(stream: MediaStream): Promise<string |
   }
 }
 
+// The languages for an agent's OCR, from the desktop app's language packs
+// (ocr_languages.rs). Null when the app isn't reachable.
+interface OcrPlan {
+  languages: string;
+  fallback: string | null;
+  local: boolean;
+  source_url: string;
+}
+
+async function fetchOcrPlan(agentId?: string): Promise<OcrPlan | null> {
+  const server = (localStorage.getItem('observer_local_server_address') || 'http://localhost:3838').replace(/\/$/, '');
+  try {
+    const query = agentId ? `?agent_id=${encodeURIComponent(agentId)}` : '';
+    const response = await fetch(`${server}/ocr/languages${query}`);
+    if (!response.ok) return null;
+    const plan = await response.json() as OcrPlan;
+    return { ...plan, source_url: plan.local ? `${server}/ocr/tessdata` : plan.source_url };
+  } catch {
+    return null;
+  }
+}
+
+async function recognize(imageData: string, languages: string, langPath: string) {
+  const worker: Worker = await createWorker(languages, 1, {
+    workerPath: SensorSettings.getOcrWorkerPath(),
+    langPath,
+    corePath: SensorSettings.getOcrCorePath(),
+    logger: m => console.log('[Tesseract]', m)
+  });
+  try {
+    // Blocks carry the word positions the layout is built from.
+    return (await worker.recognize(`data:image/png;base64,${imageData}`, {}, { text: true, blocks: true })).data;
+  } finally {
+    await worker.terminate();
+  }
+}
+
+// Every recognized word with its box, in pixels of the image, for UI
+// element labels (ui_elements.rs). Empty when OCR fails.
+export interface OcrWordBox { text: string; x0: number; y0: number; x1: number; y1: number }
+
+export async function recognizeWords(imageData: string, agentId?: string): Promise<OcrWordBox[]> {
+  try {
+    const plan = await fetchOcrPlan(agentId);
+    const result = await recognize(
+      imageData,
+      plan?.languages ?? SensorSettings.getOcrLanguage(),
+      plan?.source_url ?? SensorSettings.getOcrLangPath(),
+    );
+    return (result.blocks ?? [])
+      .flatMap(block => block.paragraphs.flatMap(p => p.lines.flatMap(l => l.words)))
+      .filter(word => word.text.trim())
+      .map(word => ({ text: word.text.trim(), ...word.bbox }));
+  } catch (error) {
+    console.error('OCR for UI elements failed:', error);
+    return [];
+  }
+}
+
 // Function to perform OCR on image data
-async function performOCR(imageData: string): Promise<OCRResult> {
+async function performOCR(imageData: string, agentId?: string, width = 0, height = 0): Promise<OCRResult> {
   console.log('Starting OCR processing...');
   
   try {
-    // Initialize worker
-    const worker: Worker = await createWorker(SensorSettings.getOcrLanguage(), 1, {
-      workerPath: SensorSettings.getOcrWorkerPath(),
-      langPath: SensorSettings.getOcrLangPath(),
-      corePath: SensorSettings.getOcrCorePath(),
-      logger: m => console.log('[Tesseract]', m)
-    });
-
-    // DEFAULTS:
-    //const worker: Worker = await createWorker('eng', 1, {
-    //  workerPath: 'https://unpkg.com/tesseract.js@6.0.0/dist/worker.min.js',
-    //  langPath: 'https://tessdata.projectnaptha.com/4.0.0',
-    //  corePath: 'https://unpkg.com/tesseract.js-core@4.0.2/tesseract-core.wasm.js',
-    //  logger: m => console.log('[Tesseract]', m)
-    //});
-
-      
-    // Recognize text
-    const result = await worker.recognize(`data:image/png;base64,${imageData}`);
-    
-    // Terminate worker
-    await worker.terminate();
-    
+    // The app's languages for this agent, or the browser's settings without the app.
+    const plan = await fetchOcrPlan(agentId);
+    const languages = plan?.languages ?? SensorSettings.getOcrLanguage();
+    const langPath = plan?.source_url ?? SensorSettings.getOcrLangPath();
     const confidenceThreshold = SensorSettings.getOcrConfidenceThreshold();
-    const isConfident = result.data.confidence >= confidenceThreshold;
 
-    console.log(`OCR processing complete. Confidence: ${result.data.confidence}`);
+    let result = await recognize(imageData, languages, langPath);
+
+    // Not confident: maybe the screen is in another language; try every installed one.
+    if (result.confidence < confidenceThreshold && plan?.fallback) {
+      console.log(`OCR confidence ${result.confidence} with ${languages}, retrying with ${plan.fallback}`);
+      const retry = await recognize(imageData, plan.fallback, langPath);
+      if (retry.confidence > result.confidence) result = retry;
+    }
+
+    const isConfident = result.confidence >= confidenceThreshold;
+    // Columns and tables kept apart, unless plain text is asked for.
+    const layoutText = SensorSettings.getOcrLayout() && result.blocks && width > 0
+      ? formatLayout(result.blocks, width, height)
+      : '';
+
+    console.log(`OCR processing complete. Confidence: ${result.confidence}`);
     return { 
       success: isConfident,
-      text: isConfident ? result.data.text : '', // Return empty text if below threshold
-      confidence: result.data.confidence
+      text: isConfident ? (layoutText || result.text) : '', // Return empty text if below threshold
+      confidence: result.confidence
     };
 
 
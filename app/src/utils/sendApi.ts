@@ -53,6 +53,11 @@ export async function sendPrompt(
       'Content-Type': 'application/json',
     };
 
+    // Lets the desktop app keep clipboard and audio content off cloud backends.
+    if (preprocessResult.sensitive && preprocessResult.sensitive.length > 0) {
+      headers['X-Observer-Content'] = preprocessResult.sensitive.join(',');
+    }
+
     if (host === 'https://api.observer-ai.com') {
       if (token) {
         headers['Authorization'] = `Bearer ${token}`;
@@ -102,6 +107,15 @@ export async function sendPrompt(
     if (!response.ok) {
         const errorBody = await response.text(); // Attempt to read error body
         console.error(`API Error Response Body: ${errorBody}`);
+        // Data-locality refusals explain how to fix them; show that instead.
+        if (response.status === 403) {
+          try {
+            const parsed = JSON.parse(errorBody);
+            if (parsed.code === 'data_locality_violation') throw new Error(parsed.error);
+          } catch (parseError) {
+            if (!(parseError instanceof SyntaxError)) throw parseError;
+          }
+        }
         throw new Error(`API error: ${response.status} ${response.statusText}`);
     }
 
@@ -9,6 +9,7 @@ class SettingsManager {
     private readonly OCR_LANG_PATH_KEY = 'observer-ai:settings:ocrLangPath';
     private readonly OCR_CORE_PATH_KEY = 'observer-ai:settings:ocrCorePath';
     private readonly OCR_LANGUAGE_KEY = 'observer-ai:settings:ocrLanguage';
+    private readonly OCR_LAYOUT_KEY = 'observer-ai:settings:ocrLayout';
 
     // --- SENSIBLE DEFAULTS ---
     private readonly DEFAULTS = {
@@ -17,6 +18,7 @@ class SettingsManager {
         ocrLangPath: 'https://tessdata.projectnaptha.com/4.0.0',
         ocrCorePath: 'https://unpkg.com/tesseract.js-core@4.0.2/tesseract-core.wasm.js',
         ocrLanguage: 'eng',
+        ocrLayout: true,
     };
 
     // --- GETTER AND SETTER FUNCTIONS ---
@@ -53,6 +55,14 @@ class SettingsManager {
     public setOcrLanguage(value: string): void {
         localStorage.setItem(this.OCR_LANGUAGE_KEY, value);
     }
+    // Layout-aware OCR text (columns, tables, positions) instead of a plain dump.
+    public getOcrLayout(): boolean {
+        const stored = localStorage.getItem(this.OCR_LAYOUT_KEY);
+        return stored === null ? this.DEFAULTS.ocrLayout : stored === 'true';
+    }
+    public setOcrLayout(value: boolean): void {
+        localStorage.setItem(this.OCR_LAYOUT_KEY, String(value));
+    }
 }
 
 // Export a single instance
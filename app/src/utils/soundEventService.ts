@@ -0,0 +1,100 @@
+// src/utils/soundEventService.ts
+import { Logger } from './logging';
+
+// Listens to the microphone in short chunks, classifies each one in
+// soundEvents.worker.ts and reports the class scores (never the audio) to
+// the desktop app, which decides what fires (sound_events.rs).
+
+export interface SoundEventSettings {
+  enabled: boolean;
+  names: string[];
+  chunk_ms: number;
+}
+
+export class SoundEventService {
+  private isRunning = false;
+  private worker: Worker | null = null;
+  private audioContext: AudioContext | null = null;
+  private stream: MediaStream | null = null;
+  private chunkCounter = 0;
+
+  constructor(private readonly serverUrl: string, private readonly settings: SoundEventSettings) {}
+
+  public start(stream: MediaStream): void {
+    if (this.isRunning) return;
+    this.isRunning = true;
+    this.stream = stream;
+    this.audioContext = new AudioContext({ sampleRate: 16000 });
+    this.worker = new Worker(new URL('./soundEvents.worker.ts', import.meta.url), { type: 'module' });
+
+    this.worker.onmessage = (event) => {
+      const { status, scores, name, chunkId } = event.data;
+      if (status === 'classified') {
+        this.report(scores, name);
+      } else if (status === 'error') {
+        Logger.error('SoundEvents', `Worker error (chunkId: ${chunkId}): ${event.data.message}`);
+      }
+    };
+    this.worker.onerror = (error) => {
+      Logger.error('SoundEvents', `Worker error: ${error.message}`);
+    };
+
+    Logger.info('SoundEvents', `Listening for sound events in ${this.settings.chunk_ms} ms chunks`);
+    this.listenLoop();
+  }
+
+  public stop(): void {
+    if (!this.isRunning) return;
+    this.isRunning = false;
+    this.worker?.terminate();
+    this.worker = null;
+    this.audioContext?.close();
+    this.audioContext = null;
+    this.stream = null;
+  }
+
+  private async report(scores: Record<string, number>, name: string | null): Promise<void> {
+    try {
+      await fetch(`${this.serverUrl}/sound/detections`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ scores, name }),
+      });
+    } catch (error) {
+      Logger.debug('SoundEvents', `Couldn't report sound scores: ${error}`);
+    }
+  }
+
+  private async listenLoop(): Promise<void> {
+    while (this.isRunning) {
+      try {
+        const chunkId = ++this.chunkCounter;
+        const blob = await this.recordChunk(this.stream!, this.settings.chunk_ms);
+        if (!this.isRunning || !this.audioContext || blob.size === 0) continue;
+        const audioBuffer = await this.audioContext.decodeAudioData(await blob.arrayBuffer());
+        const audio = audioBuffer.getChannelData(0);
+        this.worker?.postMessage({ audio, names: this.settings.names, chunkId }, [audio.buffer]);
+      } catch (error) {
+        if (this.isRunning) {
+          Logger.error('SoundEvents', `Error in listening loop: ${error}`);
+          await new Promise(resolve => setTimeout(resolve, this.settings.chunk_ms));
+        }
+      }
+    }
+  }
+
+  private recordChunk(stream: MediaStream, durationMs: number): Promise<Blob> {
+    return new Promise((resolve, reject) => {
+      if (!stream || stream.getAudioTracks().length === 0) {
+        return reject(new Error('No active audio stream to listen to.'));
+      }
+      const recorder = new MediaRecorder(stream);
+      const chunks: BlobPart[] = [];
+      recorder.ondataavailable = (e) => { if (e.data.size > 0) chunks.push(e.data); };
+      recorder.onstop = () => resolve(new Blob(chunks, { type: recorder.mimeType }));
+      recorder.onerror = () => reject(new Error('MediaRecorder error while listening.'));
+      recorder.start();
+      setTimeout(() => { if (recorder.state !== 'inactive') recorder.stop(); }, durationMs);
+    });
+  }
+}
@@ -0,0 +1,67 @@
+// src/utils/soundEvents.worker.ts
+
+import { pipeline, env } from '@huggingface/transformers';
+
+// Classifies audio chunks into the sound event classes of sound_events.rs.
+// Everything runs here; only the model weights are downloaded.
+env.allowLocalModels = false;
+
+const CLASSIFIER_MODEL = 'Xenova/ast-finetuned-audioset-10-10-0.4593';
+const TRANSCRIBER_MODEL = 'Xenova/whisper-tiny';
+// Below this speech score, a chunk isn't transcribed to look for names.
+const SPEECH_THRESHOLD = 0.3;
+
+// AudioSet labels that make up each class.
+const CLASS_LABELS: Record<string, string[]> = {
+    doorbell: ['Doorbell', 'Ding-dong', 'Knock'],
+    alarm: ['Alarm', 'Alarm clock', 'Smoke detector, smoke alarm', 'Fire alarm', 'Siren', 'Buzzer', 'Civil defense siren'],
+    baby_crying: ['Baby cry, infant cry', 'Crying, sobbing'],
+};
+
+let classifier: Promise<any> | null = null;
+let transcriber: Promise<any> | null = null;
+
+const progress = (data: any) => self.postMessage({ status: 'progress', ...data });
+
+function getClassifier() {
+    classifier ??= pipeline('audio-classification', CLASSIFIER_MODEL, {
+        progress_callback: progress, dtype: 'q8', device: 'wasm'
+    });
+    return classifier;
+}
+
+function getTranscriber() {
+    transcriber ??= pipeline('automatic-speech-recognition', TRANSCRIBER_MODEL, {
+        progress_callback: progress, dtype: 'q8', device: 'wasm'
+    });
+    return transcriber;
+}
+
+const escapeRegExp = (text: string) => text.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+
+self.onmessage = async (event) => {
+    const { audio, names, chunkId } = event.data as { audio: Float32Array; names: string[]; chunkId: number };
+
+    try {
+        const labels: { label: string; score: number }[] = await (await getClassifier())(audio, { top_k: 30 });
+        const scoreOf = (wanted: string[]) =>
+            Math.max(0, ...labels.filter(l => wanted.includes(l.label)).map(l => l.score));
+
+        const scores: Record<string, number> = {};
+        for (const [cls, wanted] of Object.entries(CLASS_LABELS)) {
+            scores[cls] = scoreOf(wanted);
+        }
+
+        let name: string | null = null;
+        if (names.length > 0 && scoreOf(['Speech']) >= SPEECH_THRESHOLD) {
+            const output = await (await getTranscriber())(audio);
+            const text = (output.text as string).toLowerCase();
+            name = names.find(n => new RegExp(`\\b${escapeRegExp(n.toLowerCase())}\\b`).test(text)) ?? null;
+            scores.name_mentioned = name ? 1 : 0;
+        }
+
+        self.postMessage({ status: 'classified', scores, name, chunkId });
+    } catch (error) {
+        self.postMessage({ status: 'error', message: `Error classifying sound: ${error}`, chunkId });
+    }
+};
@@ -5,32 +5,39 @@ import { pipeline, env, PipelineType } from '@huggingface/transformers';
 env.allowLocalModels = false;
 
 const TASK: PipelineType = 'automatic-speech-recognition';
-const MODEL = 'Xenova/whisper-tiny';
+const DEFAULT_MODEL = 'Xenova/whisper-tiny';
 
-class PipelineSingleton {
-    private static instance: any = null;
+// One pipeline per model, since chunks in some languages are routed to a larger one.
+class PipelineCache {
+    private static instances = new Map<string, Promise<any>>();
 
-    static async getInstance() { // Keep progress_callback parameter
-        if (this.instance === null) {
-            this.instance = await pipeline(TASK, MODEL, { 
+    static getInstance(model: string) { // Keep progress_callback parameter
+        let instance = this.instances.get(model);
+        if (!instance) {
+            instance = pipeline(TASK, model, {
                 progress_callback: (data: any) => {
                     self.postMessage(data);
                 }, dtype: 'q8', device: 'wasm'
             });
+            this.instances.set(model, instance);
         }
-        return this.instance;
+        return instance;
     }
 }
 
 // Listen for messages from the main thread
 self.onmessage = async (event) => {
     // MODIFIED: event.data is now an object containing audio and chunkId
-    const { audio, chunkId } = event.data as { audio: Float32Array; chunkId: number };
+    // model and language are set when a chunk is transcribed again for its language.
+    const { audio, chunkId, model, language } = event.data as {
+        audio: Float32Array; chunkId: number; model?: string; language?: string
+    };
+    const modelId = model || DEFAULT_MODEL;
 
     try {
-        const transcriber = await PipelineSingleton.getInstance();
+        const transcriber = await PipelineCache.getInstance(modelId);
 
-        const output = await transcriber(audio)
+        const output = await transcriber(audio, language ? { language } : {})
         const newText = (output.text as string).trim();
 
         if (newText) {
@@ -38,6 +45,7 @@ self.onmessage = async (event) => {
                 status: 'transcription-complete',
                 text: newText,
                 chunkId: chunkId, // MODIFIED: Echo back the chunkId
+                model: modelId,
             });
         }
     } catch (error) {
@@ -5,6 +5,27 @@ export interface TranscriptionChunk {
   id: number;
   blob: Blob;
   text: string;
+  // When the chunk's recording ended, in ms since the epoch.
+  endedAt?: number;
+}
+
+// What the app makes of a transcribed chunk, see src-tauri/src/transcription.rs.
+interface ProcessedTranscript {
+  language: string | null;
+  confidence: number;
+  retranscribe_with: string | null;
+  text: string;
+  original: string | null;
+}
+
+export interface TranscriptionOptions {
+  // Length of each recorded chunk; shorter means lower latency, less context.
+  chunkMs?: number;
+  // Whisper model and spoken language; the worker's default model otherwise.
+  model?: string;
+  language?: string;
+  // Send chunks to the app for language routing and translation.
+  process?: boolean;
 }
 
 declare interface MediaRecorderErrorEvent extends Event {
@@ -28,7 +49,20 @@ export class TranscriptionService {
   // NEW: Map to store audio blobs by their chunk ID until transcription is complete
   private pendingChunks = new Map<number, Blob>(); 
 
-  public async start(stream: MediaStream, onChunkProcessed?: (chunk: TranscriptionChunk) => void): Promise<void> {
+  // A copy of each chunk's audio, in case its language is routed to another model.
+  private pendingAudio = new Map<number, Float32Array>();
+  private chunkEndTimes = new Map<number, number>();
+
+  private options: TranscriptionOptions = {};
+
+  // Chunks of one run are indexed together for search, see src-tauri/src/transcript_index.rs.
+  private sessionId = '';
+
+  public async start(
+    stream: MediaStream,
+    onChunkProcessed?: (chunk: TranscriptionChunk) => void,
+    options: TranscriptionOptions = {},
+  ): Promise<void> {
     if (this.isRunning) {
       Logger.warn('TranscriptionService', 'Service instance is already running.');
       return;
@@ -37,6 +71,8 @@ export class TranscriptionService {
     Logger.info('TranscriptionService', `Starting new transcription instance...`);
     
     this.onChunkProcessed = onChunkProcessed || null;
+    this.options = options;
+    this.sessionId = `transcript-${Date.now()}`;
     this.chunkCounter = 0; // Reset counter on start
     this.currentStream = stream; // Store the stream here for repeated use
     this.pendingChunks.clear(); // Ensure map is clean on start
@@ -48,34 +84,17 @@ export class TranscriptionService {
 
     this.worker.onmessage = (event) => {
       // MODIFIED: Expecting 'chunkId' from the worker now
-      const { status, text, chunkId } = event.data; 
+      const { status, text, chunkId, model } = event.data; 
       console.log('[Whisper]', event.data); // Keep for debugging
 
       if (status === 'transcription-complete' && text) {
-
-        this.recentChunkTexts.push(text);
-        this.recentChunkTexts = this.recentChunkTexts.slice(-this.MAX_CHUNKS_TO_KEEP);
-
-        Logger.debug('TranscriptionService', `[Chunk] ${text} for ID: ${chunkId}`);
-
-        // MODIFIED: Retrieve the blob using the chunkId sent back by the worker
-        const blobForThisChunk = this.pendingChunks.get(chunkId);
-
-        if (this.onChunkProcessed && blobForThisChunk) {
-            this.onChunkProcessed({
-                id: chunkId, // Use the ID from the worker's message
-                blob: blobForThisChunk,
-                text: text,
-            });
-            this.pendingChunks.delete(chunkId); // Clean up the map
-        } else if (this.onChunkProcessed) {
-            Logger.warn('TranscriptionService', `Blob for chunk ID ${chunkId} not found in pendingChunks.`);
-        }
+        this.handleTranscribed(chunkId, text, model);
       } else if (status === 'error') {
           // Log worker errors, also clearing the pending chunk if an ID is present
           Logger.error('TranscriptionService', `Worker error (chunkId: ${chunkId}): ${event.data.message}`);
           if (chunkId !== undefined) { // Check if chunkId was sent with error
               this.pendingChunks.delete(chunkId); // Clean up
+              this.pendingAudio.delete(chunkId);
           }
       }
     };
@@ -87,11 +106,77 @@ export class TranscriptionService {
     this.transcribeLoop();
   }
 
+  // Detects the chunk's language in the app: transcribes it again when the
+  // language is routed to another model, and takes the translation if any.
+  private async handleTranscribed(chunkId: number, rawText: string, model: string): Promise<void> {
+    let text = rawText;
+    const processed = this.options.process === false
+      ? null
+      : await this.processTranscript(rawText, model, this.chunkEndTimes.get(chunkId));
+    if (!this.isRunning) return;
+    const audio = this.pendingAudio.get(chunkId);
+    if (processed?.retranscribe_with && audio) {
+      Logger.info('TranscriptionService',
+        `Chunk ${chunkId} is ${processed.language}, transcribing it again with ${processed.retranscribe_with}.`);
+      this.pendingAudio.delete(chunkId);
+      this.worker?.postMessage({
+        audio, chunkId, model: processed.retranscribe_with, language: processed.language ?? undefined
+      }, [audio.buffer]);
+      return;
+    }
+    this.pendingAudio.delete(chunkId);
+    if (processed) {
+      text = processed.text;
+    }
+
+    this.recentChunkTexts.push(text);
+    this.recentChunkTexts = this.recentChunkTexts.slice(-this.MAX_CHUNKS_TO_KEEP);
+
+    Logger.debug('TranscriptionService', `[Chunk] ${text} for ID: ${chunkId}`);
+
+    // MODIFIED: Retrieve the blob using the chunkId sent back by the worker
+    const blobForThisChunk = this.pendingChunks.get(chunkId);
+
+    if (this.onChunkProcessed && blobForThisChunk) {
+        this.onChunkProcessed({
+            id: chunkId, // Use the ID from the worker's message
+            blob: blobForThisChunk,
+            text: text,
+            endedAt: this.chunkEndTimes.get(chunkId),
+        });
+        this.pendingChunks.delete(chunkId); // Clean up the map
+        this.chunkEndTimes.delete(chunkId);
+    } else if (this.onChunkProcessed) {
+        Logger.warn('TranscriptionService', `Blob for chunk ID ${chunkId} not found in pendingChunks.`);
+    }
+  }
+
+  private async processTranscript(text: string, model: string, endedAt?: number): Promise<ProcessedTranscript | null> {
+    const serverUrl = localStorage.getItem('observer_local_server_address') || 'http://localhost:3838';
+    try {
+      const response = await fetch(`${serverUrl}/transcription/process`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ text, model, session_id: this.sessionId, ended_at: endedAt }),
+      });
+      if (!response.ok) {
+        Logger.warn('TranscriptionService', `Transcript processing failed: ${response.status}`);
+        return null;
+      }
+      return await response.json() as ProcessedTranscript;
+    } catch (error) {
+      // Without the app, the chunk is kept as transcribed.
+      Logger.debug('TranscriptionService', `Transcript processing unavailable: ${error}`);
+      return null;
+    }
+  }
+
   public stop(): void {
     if (!this.isRunning) return;
     
     Logger.info('TranscriptionService', 'Stopping transcription instance...');
     this.isRunning = false;
+    this.endSession();
     // No explicit mediaRecorder.stop() needed here as they are per-chunk and self-stopping
     this.worker?.terminate();
     this.worker = null;
@@ -100,6 +185,17 @@ export class TranscriptionService {
     this.onChunkProcessed = null;
     this.currentStream = null; // Clear the stream reference
     this.pendingChunks.clear(); // Clear any remaining pending chunks
+    this.pendingAudio.clear();
+    this.chunkEndTimes.clear();
+  }
+
+  // Has the app index what's left of this run's transcript.
+  private endSession(): void {
+    if (this.options.process === false || !this.sessionId) return;
+    const serverUrl = localStorage.getItem('observer_local_server_address') || 'http://localhost:3838';
+    fetch(`${serverUrl}/transcription/sessions/${this.sessionId}/end`, { method: 'POST' }).catch((error) => {
+      Logger.debug('TranscriptionService', `Transcript session end unavailable: ${error}`);
+    });
   }
 
   public getTranscript(): string {
@@ -114,11 +210,12 @@ export class TranscriptionService {
         this.chunkCounter = currentChunkId; 
 
         // MODIFIED: Pass the stored stream to recordChunk
-        const audioBlob = await this.recordChunk(this.currentStream!, 15000); 
+        const audioBlob = await this.recordChunk(this.currentStream!, this.options.chunkMs ?? 15000); 
         if (!this.isRunning || !this.audioContext) break; 
 
         // Store the captured blob in the map, associated with its ID
         this.pendingChunks.set(currentChunkId, audioBlob);
+        this.chunkEndTimes.set(currentChunkId, Date.now());
 
         const arrayBuffer = await audioBlob.arrayBuffer();
         // Use non-null assertion as audioContext is guaranteed to be initialized here
@@ -128,7 +225,9 @@ export class TranscriptionService {
         Logger.info('TranscriptionService', `Loop called. Sending chunk ${currentChunkId} to worker.`);
 
         // MODIFIED: Send the chunkId along with the raw audio to the worker
-        this.worker?.postMessage({ audio: rawAudio, chunkId: currentChunkId }, [rawAudio.buffer]);
+        this.pendingAudio.set(currentChunkId, rawAudio.slice());
+        const { model, language } = this.options;
+        this.worker?.postMessage({ audio: rawAudio, chunkId: currentChunkId, model, language }, [rawAudio.buffer]);
       } catch (error) {
         if (this.isRunning) {
           Logger.error('TranscriptionService', `Error in recording loop for chunk ${this.chunkCounter}: ${error}`);
@@ -7,9 +7,14 @@ import {
   getAgentCode,
   deleteAgent,
   saveAgent,
+  syncAgentsToApp,
+  takeImportedAgents,
   CompleteAgent,
 } from '@utils/agent_database';
-import { startAgentLoop, stopAgentLoop, AGENT_STATUS_CHANGED_EVENT } from '@utils/main_loop';
+import { startAgentLoop, stopAgentLoop, isAgentLoopRunning, AGENT_STATUS_CHANGED_EVENT } from '@utils/main_loop';
+import { takeDeepLinks } from '@utils/deep_links';
+import { takeDroppedFiles } from '@utils/file_drops';
+import { appendMemory } from '@utils/handlers/utils';
 import { Logger } from '@utils/logging';
 import { MEMORY_UPDATE_EVENT } from '@components/MemoryManager';
 
@@ -37,6 +42,9 @@ import { UpgradeSuccessPage } from '../pages/UpgradeSuccessPage'; // Assuming th
 import { ObServerTab } from '@components/ObServerTab';
 import { UpgradeModal } from '@components/UpgradeModal';
 
+// How often the desktop app is asked for queued deep links.
+const DEEP_LINK_POLL_MS = 2000;
+
 
 function AppContent() {
   // Check our environment variable to see if Auth0 should be disabled
@@ -397,6 +405,43 @@ function AppContent() {
     };
   }, [memoryAgentId, isMemoryManagerOpen]);
 
+  // The desktop app's copy of the agents may be stale from an earlier run.
+  useEffect(() => {
+    syncAgentsToApp()
+      .then(() => fetchAgents())
+      .catch(err => Logger.error('APP', 'Error syncing agents to the desktop app:', err));
+  }, [fetchAgents]);
+
+  // Agent runs and stops asked for through observer:// links or observerctl.
+  useEffect(() => {
+    const interval = setInterval(async () => {
+      for (const link of await takeDeepLinks()) {
+        if (link.action === 'run_agent' && !isAgentLoopRunning(link.id)) {
+          Logger.info(link.id, 'Starting agent from a deep link');
+          startAgentLoop(link.id, getToken).catch(err => Logger.error(link.id, 'Failed to start agent:', err));
+        } else if (link.action === 'stop_agent') {
+          Logger.info(link.id, 'Stopping agent from a deep link');
+          stopAgentLoop(link.id);
+        } else if (link.action === 'chat') {
+          Logger.warn('APP', 'Chat links are not supported by the web app yet');
+        }
+      }
+      // Agents imported or created in the desktop app meanwhile.
+      if (await takeImportedAgents() > 0) fetchAgents();
+      // Text from files dropped onto the desktop app goes into the target agent's memory.
+      for (const file of await takeDroppedFiles()) {
+        const { content } = file;
+        if (file.target && (content.kind === 'text' || content.kind === 'pdf')) {
+          Logger.info(file.target, `Adding dropped file ${file.file_name} to memory`);
+          await appendMemory(file.target, `${file.file_name}:\n${content.text}`);
+        } else {
+          Logger.warn('APP', `Dropped ${content.kind} file ${file.file_name} is not supported by the web app yet`);
+        }
+      }
+    }, DEEP_LINK_POLL_MS);
+    return () => clearInterval(interval);
+  }, [getToken, fetchAgents]);
+
   useEffect(() => {
     Logger.info('APP', 'Application starting');
     fetchAgents();